@@ -23,6 +23,7 @@ import { Minimap } from './ui/minimap';
 import { HotbarTooltip } from './ui/hotbar-tooltip';
 import { BuildingToolbar } from './ui/building-toolbar';
 import { DeathScreen } from './ui/death-screen';
+import { showProtocolMismatch } from './ui/protocol-mismatch';
 import { TerminalOverlay } from './ui/terminal-overlay';
 import { AgentWorldTooltip, type AgentWorldData } from './ui/agent-world-tooltip';
 import { ChestWorldTooltip, type ChestTooltipCallbacks } from './ui/chest-world-tooltip';
@@ -472,6 +473,10 @@ async function startGame() {
     terminalOverlay.writeOutput(agentId, data);
   });
 
+  connection.onProtocolMismatch((serverVersion, clientVersion) => {
+    showProtocolMismatch(serverVersion, clientVersion);
+  });
+
   connection.onVibeSession((event) => {
     if (event.type === 'started') {
       console.log(`[vibe] Session started for agent ${event.agentId}`);
@@ -1,5 +1,15 @@
 import { encode, decode } from '@msgpack/msgpack';
-import type { GameStateUpdate, PlayerInput, ServerMessage } from './protocol';
+import { PROTOCOL_VERSION, isCompatibleProtocol } from './protocol';
+import type {
+  EntityDelta,
+  EntityId,
+  GameStateUpdate,
+  GameStateUpdateDelta,
+  PlayerInput,
+  ServerMessage,
+  Tick,
+  VersionedMessage,
+} from './protocol';
 
 export class Connection {
   private ws: WebSocket;
@@ -7,8 +17,14 @@ export class Connection {
   private vibeOutputCallback: ((agentId: number, data: Uint8Array) => void) | null = null;
   private vibeSessionCallback: ((event: { type: 'started' | 'ended'; agentId: number; reason?: string }) => void) | null = null;
   private gradeResultCallback: ((buildingId: string, stars: number, reasoning: string) => void) | null = null;
+  private protocolMismatchCallback: ((serverVersion: number, clientVersion: number) => void) | null = null;
+  // Reported once per connection, whichever side notices first.
+  private mismatchReported = false;
   private _connected = false;
   private pendingQueue: Uint8Array[] = [];
+  // Entities as of `baseTick`, which deltas are applied on top of.
+  private entities = new Map<EntityId, EntityDelta>();
+  private baseTick: Tick | null = null;
 
   constructor(url: string) {
     this.ws = new WebSocket(url);
@@ -26,6 +42,8 @@ export class Connection {
     this.ws.addEventListener('close', () => {
       console.log('[network] Disconnected from server');
       this._connected = false;
+      this.baseTick = null;
+      this.mismatchReported = false;
     });
 
     this.ws.addEventListener('error', (e) => {
@@ -38,9 +56,19 @@ export class Connection {
           const msg = decode(new Uint8Array(event.data)) as ServerMessage;
 
           if ('GameState' in msg) {
+            if (!isCompatibleProtocol(msg.GameState.protocol_version, PROTOCOL_VERSION)) {
+              this.reportMismatch(msg.GameState.protocol_version, PROTOCOL_VERSION);
+            }
+            this.entities = new Map(msg.GameState.entities_changed.map((e) => [e.id, e]));
+            this.baseTick = msg.GameState.tick;
             if (this.stateCallback) {
               this.stateCallback(msg.GameState);
             }
+          } else if ('GameStateDelta' in msg) {
+            const state = this.applyDelta(msg.GameStateDelta);
+            if (state && this.stateCallback) {
+              this.stateCallback(state);
+            }
           } else if ('VibeOutput' in msg) {
             if (this.vibeOutputCallback) {
               this.vibeOutputCallback(
@@ -63,6 +91,8 @@ export class Connection {
                 reason: msg.VibeSessionEnded.reason,
               });
             }
+          } else if ('ProtocolMismatch' in msg) {
+            this.reportMismatch(msg.ProtocolMismatch.server_version, msg.ProtocolMismatch.client_version);
           } else if ('GradeResult' in msg) {
             if (this.gradeResultCallback) {
               this.gradeResultCallback(
@@ -79,6 +109,40 @@ export class Connection {
     });
   }
 
+  /**
+   * Rebuild the full update a delta stands for. Returns null if the delta
+   * isn't based on the last update applied (one was dropped); state then
+   * holds until the next full GameState keyframe.
+   */
+  private applyDelta(delta: GameStateUpdateDelta): GameStateUpdate | null {
+    if (this.baseTick === null || delta.base_tick !== this.baseTick) {
+      return null;
+    }
+    for (const id of delta.removed) {
+      this.entities.delete(id);
+    }
+    for (const entity of delta.changed) {
+      this.entities.set(entity.id, entity);
+    }
+    this.baseTick = delta.state.tick;
+    return {
+      ...delta.state,
+      entities_changed: Array.from(this.entities.values()),
+      entities_removed: delta.removed,
+    };
+  }
+
+  private reportMismatch(serverVersion: number, clientVersion: number): void {
+    if (this.mismatchReported) {
+      return;
+    }
+    this.mismatchReported = true;
+    console.error(`[network] Protocol mismatch: server ${serverVersion}, client ${clientVersion}`);
+    if (this.protocolMismatchCallback) {
+      this.protocolMismatchCallback(serverVersion, clientVersion);
+    }
+  }
+
   onState(callback: (state: GameStateUpdate) => void): void {
     this.stateCallback = callback;
   }
@@ -95,8 +159,13 @@ export class Connection {
     this.gradeResultCallback = callback;
   }
 
+  onProtocolMismatch(callback: (serverVersion: number, clientVersion: number) => void): void {
+    this.protocolMismatchCallback = callback;
+  }
+
   sendInput(input: PlayerInput): void {
-    const bytes = encode(input);
+    const message: VersionedMessage<PlayerInput> = { protocol_version: PROTOCOL_VERSION, payload: input };
+    const bytes = encode(message);
     if (this._connected && this.ws.readyState === WebSocket.OPEN) {
       this.ws.send(bytes);
     } else {
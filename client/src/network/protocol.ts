@@ -3,6 +3,31 @@
 export type EntityId = number;
 export type Tick = number;
 
+// ── Protocol version ───────────────────────────────────────────────
+
+// Major version in the high 16 bits, minor in the low 16. Must match the
+// server's PROTOCOL_VERSION; peers can talk if their major versions match.
+export const PROTOCOL_VERSION = 2 << 16;
+
+export function protocolMajor(version: number): number {
+  return version >>> 16;
+}
+
+export function isCompatibleProtocol(a: number, b: number): boolean {
+  return protocolMajor(a) === protocolMajor(b);
+}
+
+// "major.minor" for display.
+export function formatProtocolVersion(version: number): string {
+  return `${protocolMajor(version)}.${version & 0xffff}`;
+}
+
+// Envelope the client wraps every PlayerInput in.
+export interface VersionedMessage<T> {
+  protocol_version: number;
+  payload: T;
+}
+
 // ── Geometry ───────────────────────────────────────────────────────
 
 export interface Vec2 {
@@ -199,11 +224,19 @@ export interface BuildingGradeState {
   grading: boolean;
 }
 
+// Unit variants serialize as plain strings, struct variants as
+// { "VariantName": { ...fields } }
+export type ProjectStatus =
+  | "NotInitialized"
+  | "Ready"
+  | { Running: { port: number } }
+  | { Error: { message: string } };
+
 export interface ProjectManagerState {
   base_dir: string | null;
   initialized: boolean;
   unlocked_buildings: string[];
-  building_statuses: Record<string, string>;
+  building_statuses: Record<string, ProjectStatus>;
   agent_assignments: Record<string, number[]>;
   building_grades: Record<string, BuildingGradeState>;
 }
@@ -248,6 +281,8 @@ export interface WheelSnapshot {
 // ── Main game state update (Server -> Client) ─────────────────────
 
 export interface GameStateUpdate {
+  // PROTOCOL_VERSION of the server that produced this update.
+  protocol_version: number;
   tick: Tick;
   player: PlayerSnapshot;
   entities_changed: EntityDelta[];
@@ -268,13 +303,27 @@ export interface GameStateUpdate {
   chest_rewards: ChestReward[];
 }
 
+// A GameStateUpdate reduced to the entities that changed since base_tick.
+// `state.entities_changed` / `state.entities_removed` are left empty in
+// favour of `changed` / `removed`.
+export interface GameStateUpdateDelta {
+  base_tick: Tick;
+  changed: EntityDelta[];
+  removed: EntityId[];
+  state: GameStateUpdate;
+}
+
 // ── Server → Client message wrapper ────────────────────────────────
 export type ServerMessage =
   | { GameState: GameStateUpdate }
+  | { GameStateDelta: GameStateUpdateDelta }
   | { VibeOutput: { agent_id: number; data: number[] } }
   | { VibeSessionStarted: { agent_id: number } }
   | { VibeSessionEnded: { agent_id: number; reason: string } }
-  | { GradeResult: { building_id: string; stars: number; reasoning: string } };
+  | { GradeResult: { building_id: string; stars: number; reasoning: string } }
+  // The server couldn't decode our input or doesn't support our version.
+  // client_version is 0 if it couldn't tell.
+  | { ProtocolMismatch: { server_version: number; client_version: number } };
 
 // ── AI Backend ────────────────────────────────────────────────────
 
@@ -292,7 +341,7 @@ export type PlayerAction =
   | { PlaceBuilding: { building_type: BuildingTypeKind; x: number; y: number } }
   | "CrankStart"
   | "CrankStop"
-  | "RollbackAgent"
+  | { RollbackAgent: { agent_id: number } }
   // Home base actions
   | { RecruitAgent: { entity_id: number } }
   | { ReviveAgent: { entity_id: number } }
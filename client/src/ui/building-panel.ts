@@ -1,4 +1,4 @@
-import type { PlayerAction } from '../network/protocol';
+import type { PlayerAction, ProjectStatus } from '../network/protocol';
 
 // ── Style constants ──────────────────────────────────────────────────
 
@@ -213,9 +213,8 @@ export class BuildingPanel {
 
   /**
    * Show the panel for a building.
-   * @param status One of: "NotInitialized", "Ready", "Running:PORT", "Error:MSG"
    */
-  open(buildingId: string, name: string, description: string, status: string): void {
+  open(buildingId: string, name: string, description: string, status: ProjectStatus): void {
     this._currentBuildingId = buildingId;
     this.currentPort = null;
     // Reset iframe so the previous building's app doesn't bleed through
@@ -240,13 +239,13 @@ export class BuildingPanel {
   }
 
   /**
-   * Update the displayed status. Parse the status string:
-   *  - "NotInitialized" -- grey badge, Start button disabled
-   *  - "Ready"          -- green badge, Start button enabled
-   *  - "Running:PORT"   -- bright green badge showing ":PORT", Stop button, iframe loads localhost
-   *  - "Error:MSG"      -- red badge, Start button enabled
+   * Update the displayed status:
+   *  - NotInitialized -- grey badge, Start button disabled
+   *  - Ready          -- green badge, Start button enabled
+   *  - Running        -- bright green badge showing ":PORT", Stop button, iframe loads localhost
+   *  - Error          -- red badge, Start button enabled
    */
-  updateStatus(status: string): void {
+  updateStatus(status: ProjectStatus): void {
     this.currentPort = null;
 
     if (status === 'NotInitialized') {
@@ -255,10 +254,9 @@ export class BuildingPanel {
     } else if (status === 'Ready') {
       this.setStatusBadge('Ready', COLORS.statusGreen, '#1a2e1a');
       this.setToggleButton('Start', false);
-    } else if (status.startsWith('Running:')) {
-      const port = status.slice('Running:'.length);
-      const portNum = parseInt(port, 10);
-      this.currentPort = isNaN(portNum) ? null : portNum;
+    } else if ('Running' in status) {
+      const port = status.Running.port;
+      this.currentPort = port;
 
       this.setStatusBadge(`:${port}`, COLORS.statusBrightGreen, '#1a3a1a');
       this.setToggleButton('Stop', false);
@@ -272,14 +270,9 @@ export class BuildingPanel {
           this.loadedIframeSrc = url;
         }
       }
-    } else if (status.startsWith('Error:')) {
-      const msg = status.slice('Error:'.length);
-      this.setStatusBadge(`Error: ${msg}`, COLORS.statusRed, '#2e1a1a');
-      this.setToggleButton('Start', false);
     } else {
-      // Unknown status -- show as grey
-      this.setStatusBadge(status, COLORS.statusGrey, '#222222');
-      this.setToggleButton('Start', true);
+      this.setStatusBadge(`Error: ${status.Error.message}`, COLORS.statusRed, '#2e1a1a');
+      this.setToggleButton('Start', false);
     }
   }
 
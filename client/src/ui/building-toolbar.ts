@@ -1,3 +1,5 @@
+import type { ProjectStatus } from '../network/protocol';
+
 export interface BuildingToolbarCallbacks {
   onAssignAgent: (buildingId: string, agentId: number) => void;
   onUnassignAgent: (buildingId: string, agentId: number) => void;
@@ -184,7 +186,7 @@ export class BuildingToolbar {
     this.idleAgents = agents;
   }
 
-  show(buildingId: string, name: string, status: string, assignedAgents: AssignedAgent[], opts?: { description?: string; noPylon?: boolean }) {
+  show(buildingId: string, name: string, status: ProjectStatus, assignedAgents: AssignedAgent[], opts?: { description?: string; noPylon?: boolean }) {
     if (this.hideTimer) {
       clearTimeout(this.hideTimer);
       this.hideTimer = null;
@@ -315,7 +317,7 @@ export class BuildingToolbar {
     this.updateSlots(this.lastAssignedAgents);
   }
 
-  private updateStatusBadge(status: string) {
+  private updateStatusBadge(status: ProjectStatus) {
     if (status === 'NotInitialized') {
       this.statusEl.textContent = 'Not Init';
       this.statusEl.style.background = '#333';
@@ -324,18 +326,14 @@ export class BuildingToolbar {
       this.statusEl.textContent = 'Ready';
       this.statusEl.style.background = '#1a2e1a';
       this.statusEl.style.color = '#4a8';
-    } else if (status.startsWith('Running:')) {
+    } else if ('Running' in status) {
       this.statusEl.textContent = 'Running';
       this.statusEl.style.background = '#1a3e1a';
       this.statusEl.style.color = '#4c4';
-    } else if (status.startsWith('Error:')) {
+    } else {
       this.statusEl.textContent = 'Error';
       this.statusEl.style.background = '#2e1a1a';
       this.statusEl.style.color = '#c44';
-    } else {
-      this.statusEl.textContent = status;
-      this.statusEl.style.background = '#333';
-      this.statusEl.style.color = '#888';
     }
   }
 
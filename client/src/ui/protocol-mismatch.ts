@@ -0,0 +1,40 @@
+import { PROTOCOL_VERSION, formatProtocolVersion } from '../network/protocol';
+
+const FONT = '"IBM Plex Mono", monospace';
+const BANNER_ID = 'protocol-mismatch';
+
+/**
+ * Show a banner saying the client and server speak incompatible protocol
+ * versions. It is a DOM element rather than part of the PixiJS UI so it
+ * also covers the loading screen, which a mismatched server never clears.
+ * `clientVersion` is the version the server saw; 0 means it couldn't tell.
+ */
+export function showProtocolMismatch(serverVersion: number, clientVersion: number): void {
+  if (document.getElementById(BANNER_ID)) {
+    return;
+  }
+
+  const client = formatProtocolVersion(clientVersion || PROTOCOL_VERSION);
+  const server = formatProtocolVersion(serverVersion);
+
+  const banner = document.createElement('div');
+  banner.id = BANNER_ID;
+  banner.textContent =
+    `Version mismatch: this client speaks protocol ${client} but the server speaks ${server}. ` +
+    'Update both to the same release and reload.';
+  Object.assign(banner.style, {
+    position: 'fixed',
+    top: '0',
+    left: '0',
+    right: '0',
+    zIndex: '10000',
+    padding: '12px 16px',
+    background: '#2a0a0a',
+    borderBottom: '2px solid #ff2222',
+    color: '#ff6666',
+    fontFamily: FONT,
+    fontSize: '14px',
+    textAlign: 'center',
+  });
+  document.body.appendChild(banner);
+}
@@ -2,10 +2,108 @@ use hecs::World;
 use rand::Rng;
 
 use crate::ecs::components::{
-    Agent, AgentXP, GuardianRogue, Player, Position, Rogue, RogueAI, RogueBehaviorState,
-    RogueType, Velocity,
+    Agent, AgentName, AgentState, AgentXP, Building, ConstructionProgress, Feared, GuardianRogue, Health, Knockback, MimicDisguise, MimicPhase,
+    PackBonus, Player, Position, Projectile, RangedCooldown, Rogue, RogueAI, RogueBehaviorState,
+    RogueBossPhase, RogueType, RogueVisibility, StatusEffects, Velocity, ZoneOfControl,
 };
-use crate::protocol::RogueTypeKind;
+use crate::ecs::systems::spawn::spawn_pending;
+use crate::ecs::systems::status_effect::apply_status;
+use crate::game::spatial::SpatialGrid;
+use crate::game::building::get_building_definition;
+use crate::protocol::{AgentStateKind, AudioEvent, CombatEvent, RogueTypeKind, StatusEffect};
+
+/// Agents further than this from a rogue are never preferred over the player.
+const MAX_AGENT_SEARCH_RADIUS: f32 = 600.0;
+
+/// Ticks a boss retreats after summoning in phase 1.
+pub const BOSS_RETREAT_TICKS: u32 = 60;
+/// Movement speed of a boss in its final phase.
+pub const BOSS_ENRAGED_SPEED: f32 = 3.0;
+/// Corruption a final-phase boss inflicts on player contact.
+pub const BOSS_CORRUPTION: StatusEffect = StatusEffect::Corrupted { ticks_remaining: 100 };
+/// A disguised Mimic reveals itself when the player or an agent comes this
+/// close. Wider than the torch's reach: having stalked its way toward the
+/// player, it springs from the dark rather than waiting to be walked into.
+pub const MIMIC_REVEAL_RANGE: f32 = 200.0;
+/// Ticks a fresh Mimic spends stalking the player unseen before it settles
+/// into its disguise.
+pub const MIMIC_STALK_TICKS: u32 = 100;
+/// How fast a stalking Mimic creeps toward the player.
+pub const MIMIC_STALK_SPEED: f32 = 0.2;
+/// Swarms of the same pack this close to each other hunt together.
+pub const PACK_RADIUS: f32 = 80.0;
+/// Pack members (counting the Swarm itself) needed for the speed boost.
+pub const PACK_MIN_MEMBERS: u32 = 3;
+/// Speed multiplier for a Swarm hunting with its pack.
+pub const PACK_SPEED_MULTIPLIER: f32 = 1.3;
+/// Architects fire at targets closer than this.
+pub const ARCHITECT_FIRE_RANGE: f32 = 150.0;
+/// Ticks between Architect shots.
+pub const ARCHITECT_FIRE_COOLDOWN: u32 = 60;
+/// Architect bolts are slow enough to dodge.
+pub const ARCHITECT_BOLT_SPEED: f32 = 2.5;
+pub const ARCHITECT_BOLT_DAMAGE: i32 = 6;
+
+/// Result returned by [`rogue_ai_system`] each tick.
+#[derive(Default)]
+pub struct RogueAiResult {
+    /// Log messages, e.g. boss phase transitions.
+    pub log_entries: Vec<String>,
+    /// Audio cues, e.g. a Mimic revealing itself.
+    pub audio_events: Vec<AudioEvent>,
+    /// Combat feed events, e.g. an assassin locking onto its mark.
+    pub combat_events: Vec<CombatEvent>,
+}
+
+/// Assassin candidate: (entity, x, y, level, xp, name).
+type Mark = (hecs::Entity, f32, f32, u32, u64, String);
+
+/// The agent an assassin at `(rx, ry)` should hunt: highest level, then
+/// highest XP, then nearest.
+fn pick_mark(marks: &[Mark], rx: f32, ry: f32) -> Option<&Mark> {
+    let dist_sq = |&(_, x, y, ..): &Mark| (x - rx).powi(2) + (y - ry).powi(2);
+    marks.iter().max_by(|a, b| {
+        (a.3, a.4)
+            .cmp(&(b.3, b.4))
+            .then_with(|| dist_sq(b).total_cmp(&dist_sq(a)))
+    })
+}
+
+/// Guardian snapshot: (entity, x, y, kind, home_x, home_y, leash_radius, patrol_pause).
+type GuardianData = (hecs::Entity, f32, f32, RogueTypeKind, f32, f32, f32, u32);
+
+/// Movement multiplier from the rogue's status effects (1.0 if none).
+fn slow_factor(world: &World, entity: hecs::Entity) -> f32 {
+    world
+        .get::<&StatusEffects>(entity)
+        .map(|effects| effects.speed_factor())
+        .unwrap_or(1.0)
+}
+
+/// Completed zone-of-control building: (x, y, radius, slow_factor).
+type Zone = (f32, f32, f32, f32);
+
+/// Movement multiplier from the strongest zone of control covering
+/// `(x, y)` (1.0 if none).
+fn zone_factor(zones: &[Zone], x: f32, y: f32) -> f32 {
+    zones
+        .iter()
+        .filter(|&&(zx, zy, radius, _slow)| (zx - x).powi(2) + (zy - y).powi(2) <= radius * radius)
+        .map(|&(_zx, _zy, _radius, slow)| slow)
+        .fold(1.0, f32::min)
+}
+
+/// Velocity carrying a rogue at `(rx, ry)` straight away from `(px, py)`
+/// at `speed`; zero if they're on top of each other.
+fn flee_velocity(rx: f32, ry: f32, px: f32, py: f32, speed: f32) -> (f32, f32) {
+    let (dx, dy) = (rx - px, ry - py);
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist > 0.001 {
+        (dx / dist * speed, dy / dist * speed)
+    } else {
+        (0.0, 0.0)
+    }
+}
 
 /// Returns the movement speed for a given rogue type.
 fn speed_for_type(kind: RogueTypeKind) -> f32 {
@@ -15,8 +113,10 @@ fn speed_for_type(kind: RogueTypeKind) -> f32 {
         RogueTypeKind::Corruptor => 0.52,
         RogueTypeKind::Looper => 0.65,
         RogueTypeKind::TokenDrain => 0.33,
-        RogueTypeKind::Mimic => 0.0, // stationary
+        RogueTypeKind::Mimic => 1.3, // stationary while disguised, then pounces
         RogueTypeKind::Architect => 0.39,
+        RogueTypeKind::Multiplier => 0.7,
+        RogueTypeKind::Plague => 0.45,
     }
 }
 
@@ -26,8 +126,35 @@ fn speed_for_type(kind: RogueTypeKind) -> f32 {
 /// 2. Collects all agent positions and the player position as potential targets.
 /// 3. For each rogue, finds the nearest target and moves toward it at type-specific speed.
 /// 4. Updates behavior state based on distance to nearest target.
-/// 5. Special: Assassin targets the highest-XP agent specifically.
-pub fn rogue_ai_system(world: &mut World) {
+/// 5. Special: Assassins lock onto the highest-level agent (ties go to the
+///    higher XP, then the nearer) and keep hunting it until it dies or
+///    goes Unresponsive. They only go for the player when no agent is left.
+/// 6. Special: Architect bosses with a `RogueBossPhase` summon Swarms and
+///    retreat in phase 1, then chase at burst speed and corrupt the player
+///    on contact in phase 2.
+/// 7. Special: Mimics spend their first `MIMIC_STALK_TICKS` (tracked by
+///    `MimicPhase`) invisible, creeping toward the player at
+///    `MIMIC_STALK_SPEED`. After that they sit still in their
+///    `MimicDisguise` until the player or an agent comes within
+///    `MIMIC_REVEAL_RANGE` or they take damage, then drop the disguise,
+///    attack, and behave like any other rogue.
+/// 8. Special: Swarms with a `PackBonus` move `PACK_SPEED_MULTIPLIER` faster
+///    while at least `PACK_MIN_MEMBERS` of their pack are within
+///    `PACK_RADIUS` of them.
+/// 9. Special: Architects fire a slow bolt at their target whenever it is
+///    within `ARCHITECT_FIRE_RANGE`, every `ARCHITECT_FIRE_COOLDOWN` ticks.
+/// 10. Rogues inside the `ZoneOfControl` of a completed building move at
+///     its `slow_factor` (the strongest applies where zones overlap).
+/// 11. Rogues with a `Knockback` drift by it instead of doing any of the
+///     above, until its ticks run out.
+/// 12. Rogues with a `Feared` (from a Flare) flee directly away from the
+///     player at their normal speed until it wears off.
+///
+/// `agent_grid` holds agent positions; nearest-target search only looks at
+/// agents in cells closer than the player (capped at `MAX_AGENT_SEARCH_RADIUS`).
+pub fn rogue_ai_system(world: &mut World, agent_grid: &SpatialGrid) -> RogueAiResult {
+    let mut result = RogueAiResult::default();
+
     // ── Collect rogue data ────────────────────────────────────────────
     let rogues: Vec<(hecs::Entity, f32, f32, RogueTypeKind)> = world
         .query::<(&Rogue, &Position, &RogueType)>()
@@ -35,6 +162,15 @@ pub fn rogue_ai_system(world: &mut World) {
         .map(|(entity, (_rogue, pos, rogue_type))| (entity, pos.x, pos.y, rogue_type.kind))
         .collect();
 
+    // ── Collect zones of control from completed buildings ────────────
+    let zones: Vec<Zone> = world
+        .query::<(&Position, &ZoneOfControl, &ConstructionProgress)>()
+        .with::<&Building>()
+        .iter()
+        .filter(|(_e, (_pos, _zone, progress))| progress.current >= progress.total)
+        .map(|(_e, (pos, zone, _progress))| (pos.x, pos.y, zone.radius, zone.slow_factor))
+        .collect();
+
     // ── Collect potential targets ─────────────────────────────────────
     // Player position
     let player_target: Option<(hecs::Entity, f32, f32)> = world
@@ -43,24 +179,149 @@ pub fn rogue_ai_system(world: &mut World) {
         .map(|(entity, (_player, pos))| (entity, pos.x, pos.y))
         .next();
 
-    // Agent positions (with XP for assassin targeting)
-    let agent_targets: Vec<(hecs::Entity, f32, f32, u64)> = world
+    // Agent positions
+    let agent_lookup: std::collections::HashMap<hecs::Entity, (f32, f32)> = world
         .query::<(&Agent, &Position, &AgentXP)>()
         .iter()
-        .map(|(entity, (_agent, pos, xp))| (entity, pos.x, pos.y, xp.xp))
+        .map(|(entity, (_agent, pos, _xp))| (entity, (pos.x, pos.y)))
         .collect();
 
-    // ── Find the highest-XP agent for assassin targeting ──────────────
-    let highest_xp_agent: Option<(hecs::Entity, f32, f32)> = agent_targets
+    // ── Agents an assassin may hunt ───────────────────────────────────
+    let marks: Vec<Mark> = world
+        .query::<(&Agent, &Position, &AgentXP, Option<&AgentState>, Option<&AgentName>)>()
+        .iter()
+        .filter(|(_e, (_agent, _pos, _xp, state, _name))| {
+            !state.is_some_and(|s| s.state == AgentStateKind::Unresponsive)
+        })
+        .map(|(entity, (_agent, pos, xp, _state, name))| {
+            let name = name.map(|n| n.name.clone()).unwrap_or_else(|| "an agent".to_string());
+            (entity, pos.x, pos.y, xp.level, xp.xp, name)
+        })
+        .collect();
+
+    // ── Disguised mimics ─────────────────────────────────────────────
+    let mut disguised: std::collections::HashSet<hecs::Entity> = std::collections::HashSet::new();
+    let mimics: Vec<(hecs::Entity, f32, f32, bool, &'static str, u32)> = world
+        .query::<(&Position, &Health, &MimicDisguise, Option<&MimicPhase>)>()
+        .with::<&Rogue>()
+        .iter()
+        .map(|(entity, (pos, hp, mimic, phase))| {
+            // Mimics from before phases existed are past stalking.
+            let elapsed = phase.map_or(MIMIC_STALK_TICKS, |p| p.elapsed_ticks);
+            (entity, pos.x, pos.y, hp.current < hp.max, get_building_definition(&mimic.disguise).name, elapsed)
+        })
+        .collect();
+
+    for (entity, mx, my, damaged, disguise_name, elapsed) in mimics {
+        if let Ok(mut phase) = world.get::<&mut MimicPhase>(entity) {
+            phase.elapsed_ticks = phase.elapsed_ticks.saturating_add(1);
+        }
+
+        if elapsed < MIMIC_STALK_TICKS {
+            disguised.insert(entity);
+            let (mut vx, mut vy) = (0.0, 0.0);
+            if let Some((_pe, px, py)) = player_target {
+                let (dx, dy) = (px - mx, py - my);
+                let dist = (dx * dx + dy * dy).sqrt();
+                if dist > 0.001 {
+                    vx = dx / dist * MIMIC_STALK_SPEED;
+                    vy = dy / dist * MIMIC_STALK_SPEED;
+                }
+            }
+            if let Ok(mut vel) = world.get::<&mut Velocity>(entity) { vel.x = vx; vel.y = vy; }
+            if let Ok(mut pos) = world.get::<&mut Position>(entity) { pos.x += vx; pos.y += vy; }
+            if let Ok(mut ai) = world.get::<&mut RogueAI>(entity) {
+                ai.behavior_state = RogueBehaviorState::Wandering;
+                ai.target = None;
+            }
+            if let Ok(mut visibility) = world.get::<&mut RogueVisibility>(entity) {
+                visibility.visible = false;
+            }
+            continue;
+        }
+
+        if let Ok(mut visibility) = world.get::<&mut RogueVisibility>(entity) {
+            visibility.visible = true;
+        }
+        let within_reveal = |x: f32, y: f32| {
+            let (dx, dy) = (x - mx, y - my);
+            dx * dx + dy * dy <= MIMIC_REVEAL_RANGE * MIMIC_REVEAL_RANGE
+        };
+        let player_close = player_target.is_some_and(|(_pe, px, py)| within_reveal(px, py));
+        let agent_close = agent_lookup.values().any(|&(ax, ay)| within_reveal(ax, ay));
+        if player_close || agent_close || damaged {
+            let _ = world.remove_one::<MimicDisguise>(entity);
+            if let Ok(mut phase) = world.get::<&mut MimicPhase>(entity) {
+                phase.revealed = true;
+            }
+            if let Ok(mut ai) = world.get::<&mut RogueAI>(entity) {
+                ai.behavior_state = RogueBehaviorState::Attacking;
+            }
+            // It spends the reveal tick springing the trap.
+            disguised.insert(entity);
+            result
+                .log_entries
+                .push(format!("[combat] that {} was a Mimic!", disguise_name));
+            result.audio_events.push(AudioEvent::MimicReveal);
+        } else {
+            disguised.insert(entity);
+            if let Ok(mut vel) = world.get::<&mut Velocity>(entity) {
+                vel.x = 0.0;
+                vel.y = 0.0;
+            }
+        }
+    }
+
+    // ── Knockback and fear override everything else ─────────────────
+    let mut overridden: std::collections::HashSet<hecs::Entity> = std::collections::HashSet::new();
+    let mut knockback_over: Vec<hecs::Entity> = Vec::new();
+    for (entity, (knockback, pos, vel)) in world.query_mut::<(&mut Knockback, &mut Position, &mut Velocity)>() {
+        overridden.insert(entity);
+        vel.x = knockback.dx;
+        vel.y = knockback.dy;
+        pos.x += knockback.dx;
+        pos.y += knockback.dy;
+        knockback.ticks = knockback.ticks.saturating_sub(1);
+        if knockback.ticks == 0 {
+            knockback_over.push(entity);
+        }
+    }
+    for entity in knockback_over {
+        let _ = world.remove_one::<Knockback>(entity);
+    }
+
+    // Feared rogues run straight away from the player.
+    let feared: Vec<(hecs::Entity, f32, f32, RogueTypeKind)> = rogues
         .iter()
-        .max_by_key(|(_e, _x, _y, xp)| *xp)
-        .map(|(e, x, y, _xp)| (*e, *x, *y));
+        .filter(|(entity, ..)| !overridden.contains(entity) && world.get::<&Feared>(*entity).is_ok())
+        .copied()
+        .collect();
+    for (entity, rx, ry, rogue_kind) in feared {
+        overridden.insert(entity);
+        let speed = speed_for_type(rogue_kind) * slow_factor(world, entity) * zone_factor(&zones, rx, ry);
+        if let Some((_pe, px, py)) = player_target {
+            let (vx, vy) = flee_velocity(rx, ry, px, py, speed);
+            if let Ok(mut vel) = world.get::<&mut Velocity>(entity) { vel.x = vx; vel.y = vy; }
+            if let Ok(mut pos) = world.get::<&mut Position>(entity) { pos.x += vx; pos.y += vy; }
+        }
+        if let Ok(mut ai) = world.get::<&mut RogueAI>(entity) {
+            ai.behavior_state = RogueBehaviorState::Fleeing;
+            ai.target = None;
+        }
+        let calmed = world.get::<&mut Feared>(entity).is_ok_and(|mut fear| {
+            fear.ticks_remaining = fear.ticks_remaining.saturating_sub(1);
+            fear.ticks_remaining == 0
+        });
+        if calmed {
+            let _ = world.remove_one::<Feared>(entity);
+        }
+    }
 
     // ── Process guardian rogues (leashed behavior) ──────────────────
     let mut guardian_entities: std::collections::HashSet<hecs::Entity> = std::collections::HashSet::new();
 
     // Collect guardian data
-    let guardians: Vec<(hecs::Entity, f32, f32, RogueTypeKind, f32, f32, f32, u32)> = world
+    let guardians: Vec<GuardianData> = world
         .query::<(&Rogue, &Position, &RogueType, &GuardianRogue)>()
         .iter()
         .map(|(entity, (_rogue, pos, rtype, guard))| {
@@ -70,7 +331,10 @@ pub fn rogue_ai_system(world: &mut World) {
 
     for (entity, rx, ry, rogue_kind, home_x, home_y, leash_radius, patrol_pause) in &guardians {
         guardian_entities.insert(*entity);
-        let speed = speed_for_type(*rogue_kind);
+        if overridden.contains(entity) {
+            continue;
+        }
+        let speed = speed_for_type(*rogue_kind) * slow_factor(world, *entity) * zone_factor(&zones, *rx, *ry);
 
         let dx_home = home_x - rx;
         let dy_home = home_y - ry;
@@ -170,20 +434,138 @@ pub fn rogue_ai_system(world: &mut World) {
         }
     }
 
+    // ── Boss phases ──────────────────────────────────────────────────
+    let mut retreating_bosses: std::collections::HashSet<hecs::Entity> = std::collections::HashSet::new();
+    let mut enraged_bosses: std::collections::HashSet<hecs::Entity> = std::collections::HashSet::new();
+    let mut summons: Vec<(f32, f32, RogueTypeKind)> = Vec::new();
+
+    let bosses: Vec<(hecs::Entity, f32, f32, f32)> = world
+        .query::<(&Rogue, &Position, &RogueType, &Health, &RogueBossPhase)>()
+        .iter()
+        .filter(|(_e, (_rogue, _pos, rtype, _hp, _boss))| rtype.kind == RogueTypeKind::Architect)
+        .map(|(entity, (_rogue, pos, _rtype, hp, _boss))| {
+            (entity, pos.x, pos.y, hp.current as f32 / hp.max.max(1) as f32)
+        })
+        .collect();
+
+    for (entity, bx, by, hp_fraction) in &bosses {
+        let retreating = {
+            let Ok(mut boss) = world.get::<&mut RogueBossPhase>(*entity) else { continue };
+            let target_phase = boss.phase_for(*hp_fraction);
+            while boss.phase < target_phase {
+                boss.phase += 1;
+                if boss.phase == 1 {
+                    summons.push((bx - 20.0, *by, RogueTypeKind::Swarm));
+                    summons.push((bx + 20.0, *by, RogueTypeKind::Swarm));
+                    boss.retreat_ticks = BOSS_RETREAT_TICKS;
+                    result.log_entries.push("[combat] the Architect summons a swarm and falls back.".to_string());
+                } else {
+                    result.log_entries.push("[combat] the Architect is enraged.".to_string());
+                }
+            }
+
+            if boss.retreat_ticks > 0 {
+                boss.retreat_ticks -= 1;
+                true
+            } else {
+                if boss.phase >= 2 {
+                    enraged_bosses.insert(*entity);
+                }
+                false
+            }
+        };
+
+        if !retreating || overridden.contains(entity) {
+            continue;
+        }
+        retreating_bosses.insert(*entity);
+
+        // Retreat directly away from the player.
+        let speed = speed_for_type(RogueTypeKind::Architect) * slow_factor(world, *entity) * zone_factor(&zones, *bx, *by);
+        if let Some((_pe, px, py)) = player_target {
+            let dx = bx - px;
+            let dy = by - py;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist > 0.001 {
+                let vx = dx / dist * speed;
+                let vy = dy / dist * speed;
+                if let Ok(mut vel) = world.get::<&mut Velocity>(*entity) { vel.x = vx; vel.y = vy; }
+                if let Ok(mut pos) = world.get::<&mut Position>(*entity) { pos.x += vx; pos.y += vy; }
+            }
+        }
+        if let Ok(mut ai) = world.get::<&mut RogueAI>(*entity) {
+            ai.behavior_state = RogueBehaviorState::Fleeing;
+            ai.target = None;
+        }
+    }
+
+    // ── Swarm packs ──────────────────────────────────────────────────
+    let pack_members: Vec<(hecs::Entity, u32, f32, f32)> = world
+        .query::<(&PackBonus, &Position)>()
+        .with::<&Rogue>()
+        .iter()
+        .map(|(entity, (pack, pos))| (entity, pack.pack_id, pos.x, pos.y))
+        .collect();
+    let mut packed: std::collections::HashSet<hecs::Entity> = std::collections::HashSet::new();
+    for &(entity, pack_id, x, y) in &pack_members {
+        let nearby = pack_members
+            .iter()
+            .filter(|&&(_e, id, ox, oy)| {
+                id == pack_id && (ox - x) * (ox - x) + (oy - y) * (oy - y) <= PACK_RADIUS * PACK_RADIUS
+            })
+            .count() as u32;
+        if let Ok(mut pack) = world.get::<&mut PackBonus>(entity) {
+            pack.members = nearby;
+        }
+        if nearby >= PACK_MIN_MEMBERS {
+            packed.insert(entity);
+        }
+    }
+
     // ── Process each rogue ────────────────────────────────────────────
+    let mut bolts: Vec<(Position, Projectile)> = Vec::new();
     for (rogue_entity, rx, ry, rogue_kind) in &rogues {
-        // Skip guardians — they were already processed above
-        if guardian_entities.contains(rogue_entity) {
+        // Skip guardians, retreating bosses and knocked-back or feared
+        // rogues — they
+        // were already processed above — and mimics still stalking, in
+        // disguise or just springing their trap.
+        if guardian_entities.contains(rogue_entity)
+            || retreating_bosses.contains(rogue_entity)
+            || overridden.contains(rogue_entity)
+            || disguised.contains(rogue_entity)
+        {
             continue;
         }
 
-        let speed = speed_for_type(*rogue_kind);
+        let pack_factor = if packed.contains(rogue_entity) { PACK_SPEED_MULTIPLIER } else { 1.0 };
+        let speed = if enraged_bosses.contains(rogue_entity) {
+            BOSS_ENRAGED_SPEED
+        } else {
+            speed_for_type(*rogue_kind)
+        } * slow_factor(world, *rogue_entity)
+            * zone_factor(&zones, *rx, *ry)
+            * pack_factor;
 
         // Determine the target based on rogue type.
-        // Assassins specifically target the highest-XP agent.
+        // Assassins keep their mark while it is still huntable.
         let target: Option<(hecs::Entity, f32, f32)> = if *rogue_kind == RogueTypeKind::Assassin {
-            // Prefer highest-XP agent, fall back to player
-            highest_xp_agent.or(player_target)
+            let locked = world.get::<&RogueAI>(*rogue_entity).ok().and_then(|ai| ai.target);
+            if let Some(&(e, x, y, ..)) = marks.iter().find(|m| Some(m.0) == locked) {
+                Some((e, x, y))
+            } else if let Some((e, x, y, _level, _xp, name)) = pick_mark(&marks, *rx, *ry) {
+                result.log_entries.push(format!("[combat] an assassin is hunting {}", name));
+                result.combat_events.push(CombatEvent {
+                    x: *x,
+                    y: *y,
+                    damage: 0,
+                    is_kill: false,
+                    rogue_type: Some(RogueTypeKind::Assassin),
+                    is_crit: false,
+                });
+                Some((*e, *x, *y))
+            } else {
+                player_target
+            }
         } else {
             // Find nearest target among all agents and the player.
             let mut nearest: Option<(hecs::Entity, f32, f32, f32)> = None; // (entity, x, y, dist_sq)
@@ -195,14 +577,21 @@ pub fn rogue_ai_system(world: &mut World) {
                 nearest = Some((pe, px, py, dist_sq));
             }
 
-            for (ae, ax, ay, _xp) in &agent_targets {
+            // Only agents closer than the player can win, so bound the search.
+            let search_radius = nearest
+                .map(|(_e, _x, _y, d)| d.sqrt())
+                .unwrap_or(f32::MAX)
+                .min(MAX_AGENT_SEARCH_RADIUS);
+
+            for ae in agent_grid.query_radius(*rx, *ry, search_radius) {
+                let Some(&(ax, ay)) = agent_lookup.get(&ae) else { continue };
                 let dx = ax - rx;
                 let dy = ay - ry;
                 let dist_sq = dx * dx + dy * dy;
                 match nearest {
                     Some((_ne, _nx, _ny, nd)) if nd <= dist_sq => {}
                     _ => {
-                        nearest = Some((*ae, *ax, *ay, dist_sq));
+                        nearest = Some((ae, ax, ay, dist_sq));
                     }
                 }
             }
@@ -253,9 +642,463 @@ pub fn rogue_ai_system(world: &mut World) {
             RogueBehaviorState::Wandering
         };
 
+        // Enraged bosses corrupt the player on contact.
+        let player_entity = player_target.map(|(e, _, _)| e);
+        if enraged_bosses.contains(rogue_entity) && dist < 20.0 && target_entity == player_entity {
+            if let Some(player) = player_entity {
+                apply_status(world, player, BOSS_CORRUPTION);
+            }
+        }
+
         if let Ok(mut ai) = world.get::<&mut RogueAI>(*rogue_entity) {
             ai.behavior_state = new_state;
             ai.target = target_entity;
         }
+
+        if *rogue_kind == RogueTypeKind::Architect {
+            if let Some((_te, tx, ty)) = target {
+                bolts.extend(architect_volley(world, *rogue_entity, tx, ty, dist));
+            }
+        }
+    }
+
+    spawn_pending(world, &summons);
+    for bolt in bolts {
+        world.spawn(bolt);
+    }
+
+    result
+}
+
+/// Ticks an Architect's ranged cooldown and, if it is ready and the target
+/// at `(tx, ty)` is `dist` away within range, returns a bolt aimed at it.
+fn architect_volley(
+    world: &mut World,
+    entity: hecs::Entity,
+    tx: f32,
+    ty: f32,
+    dist: f32,
+) -> Option<(Position, Projectile)> {
+    if world.get::<&RangedCooldown>(entity).is_err() {
+        let _ = world.insert_one(entity, RangedCooldown::default());
+    }
+    let mut cooldown = world.get::<&mut RangedCooldown>(entity).ok()?;
+    if cooldown.remaining > 0 {
+        cooldown.remaining -= 1;
+        return None;
+    }
+    if !(0.001..ARCHITECT_FIRE_RANGE).contains(&dist) {
+        return None;
+    }
+    cooldown.remaining = ARCHITECT_FIRE_COOLDOWN;
+    drop(cooldown);
+
+    let pos = Position { ..*world.get::<&Position>(entity).ok()? };
+    let (dx, dy) = ((tx - pos.x) / dist, (ty - pos.y) / dist);
+    Some((
+        pos,
+        Projectile {
+            dx,
+            dy,
+            speed: ARCHITECT_BOLT_SPEED,
+            damage: ARCHITECT_BOLT_DAMAGE,
+            range_remaining: ARCHITECT_FIRE_RANGE * 1.5,
+            owner_is_player: false,
+            is_crit: false,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::systems::spawn::spawn_rogue;
+
+    fn spawn_boss(world: &mut World) -> hecs::Entity {
+        let boss = spawn_rogue(world, 100.0, 0.0, RogueTypeKind::Architect);
+        world.insert_one(boss, RogueBossPhase::architect()).unwrap();
+        boss
+    }
+
+    fn set_hp_percent(world: &mut World, entity: hecs::Entity, percent: i32) {
+        let mut hp = world.get::<&mut Health>(entity).unwrap();
+        hp.current = hp.max * percent / 100;
+    }
+
+    fn swarm_count(world: &World) -> usize {
+        world
+            .query::<&RogueType>()
+            .iter()
+            .filter(|(_, t)| t.kind == RogueTypeKind::Swarm)
+            .count()
+    }
+
+    #[test]
+    fn phase_for_counts_crossed_thresholds() {
+        let boss = RogueBossPhase::architect();
+        assert_eq!(boss.phase_for(1.0), 0);
+        assert_eq!(boss.phase_for(0.6), 0);
+        assert_eq!(boss.phase_for(0.59), 1);
+        assert_eq!(boss.phase_for(0.3), 1);
+        assert_eq!(boss.phase_for(0.29), 2);
+        assert_eq!(boss.phase_for(0.0), 2);
+    }
+
+    #[test]
+    fn boss_summons_retreats_then_enrages() {
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let boss = spawn_boss(&mut world);
+        let grid = SpatialGrid::new(64.0);
+
+        assert!(rogue_ai_system(&mut world, &grid).log_entries.is_empty());
+        assert_eq!(world.get::<&RogueBossPhase>(boss).unwrap().phase, 0);
+
+        set_hp_percent(&mut world, boss, 55);
+        let x_before = world.get::<&Position>(boss).unwrap().x;
+        let result = rogue_ai_system(&mut world, &grid);
+        assert_eq!(result.log_entries.len(), 1);
+        assert_eq!(world.get::<&RogueBossPhase>(boss).unwrap().phase, 1);
+        assert_eq!(swarm_count(&world), 2);
+        assert!(matches!(world.get::<&RogueAI>(boss).unwrap().behavior_state, RogueBehaviorState::Fleeing));
+        assert!(world.get::<&Position>(boss).unwrap().x > x_before);
+
+        // Staying in phase 1 never summons again.
+        rogue_ai_system(&mut world, &grid);
+        assert_eq!(swarm_count(&world), 2);
+
+        set_hp_percent(&mut world, boss, 20);
+        let result = rogue_ai_system(&mut world, &grid);
+        assert_eq!(result.log_entries.len(), 1);
+        assert_eq!(world.get::<&RogueBossPhase>(boss).unwrap().phase, 2);
+        assert_eq!(swarm_count(&world), 2);
+    }
+
+    #[test]
+    fn boss_skipping_to_last_phase_passes_through_each() {
+        let mut world = World::new();
+        let player = world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let boss = spawn_boss(&mut world);
+        world.get::<&mut Position>(boss).unwrap().x = 10.0;
+        let grid = SpatialGrid::new(64.0);
+
+        set_hp_percent(&mut world, boss, 10);
+        let result = rogue_ai_system(&mut world, &grid);
+        assert_eq!(result.log_entries.len(), 2);
+        assert_eq!(swarm_count(&world), 2);
+
+        // Finish the retreat, then the enraged boss corrupts the player on contact.
+        world.get::<&mut RogueBossPhase>(boss).unwrap().retreat_ticks = 0;
+        world.get::<&mut Position>(boss).unwrap().x = 10.0;
+        rogue_ai_system(&mut world, &grid);
+        let effects = world.get::<&StatusEffects>(player).unwrap();
+        assert!(effects.effects.contains(&BOSS_CORRUPTION));
+    }
+
+    /// A Mimic at `(x, 0)` that has finished stalking.
+    fn settled_mimic(world: &mut World, x: f32) -> hecs::Entity {
+        let mimic = spawn_rogue(world, x, 0.0, RogueTypeKind::Mimic);
+        world.get::<&mut MimicPhase>(mimic).unwrap().elapsed_ticks = MIMIC_STALK_TICKS;
+        mimic
+    }
+
+    #[test]
+    fn mimic_poses_as_a_building_until_the_player_comes_close() {
+        use crate::network::snapshot::rogue_deltas;
+        use crate::protocol::{EntityData, EntityKind, MimicPhaseSnapshot};
+
+        let mut world = World::new();
+        let player = world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let start = MIMIC_REVEAL_RANGE + 100.0;
+        let mimic = settled_mimic(&mut world, start);
+        let grid = SpatialGrid::new(64.0);
+
+        let result = rogue_ai_system(&mut world, &grid);
+        assert!(result.audio_events.is_empty());
+        assert_eq!(world.get::<&Position>(mimic).unwrap().x, start);
+        let delta = &rogue_deltas(&world)[0];
+        assert_eq!(delta.kind, EntityKind::Building);
+        assert!(matches!(delta.data, EntityData::Building { construction_pct, .. } if construction_pct == 1.0));
+
+        world.get::<&mut Position>(player).unwrap().x = start - MIMIC_REVEAL_RANGE;
+        let result = rogue_ai_system(&mut world, &grid);
+        assert!(result.log_entries[0].contains("was a Mimic"));
+        assert!(matches!(result.audio_events[..], [AudioEvent::MimicReveal]));
+        assert!(world.get::<&MimicDisguise>(mimic).is_err());
+        let delta = &rogue_deltas(&world)[0];
+        assert_eq!(delta.kind, EntityKind::Rogue);
+        assert!(matches!(
+            delta.data,
+            EntityData::Rogue { rogue_type: RogueTypeKind::Mimic, mimic_phase: Some(MimicPhaseSnapshot { revealed: true, .. }), .. }
+        ));
+
+        // Revealed, it comes for the player.
+        rogue_ai_system(&mut world, &grid);
+        assert!(world.get::<&Position>(mimic).unwrap().x < start);
+    }
+
+    #[test]
+    fn mimic_stalks_unseen_then_reveals_near_an_agent() {
+        use crate::ecs::components::RogueVisibility;
+        use crate::ecs::systems::combat::is_hidden_mimic;
+
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let mimic = spawn_rogue(&mut world, 1000.0, 0.0, RogueTypeKind::Mimic);
+        let grid = SpatialGrid::new(64.0);
+
+        // Stalking: invisible, creeping toward the player.
+        world.get::<&mut MimicPhase>(mimic).unwrap().elapsed_ticks = MIMIC_STALK_TICKS - 1;
+        assert!(is_hidden_mimic(&world, mimic));
+        rogue_ai_system(&mut world, &grid);
+        assert!(!world.get::<&RogueVisibility>(mimic).unwrap().visible);
+        assert_eq!(world.get::<&Position>(mimic).unwrap().x, 1000.0 - MIMIC_STALK_SPEED);
+        assert!(matches!(world.get::<&RogueAI>(mimic).unwrap().behavior_state, RogueBehaviorState::Wandering));
+        // Its stalk is over, so it can be hit while it waits in disguise.
+        assert!(!is_hidden_mimic(&world, mimic));
+
+        // Stalking over, an agent in range springs it.
+        world.spawn((Agent, Position { x: 1000.0 - MIMIC_REVEAL_RANGE, y: 0.0 }, AgentXP { xp: 0, level: 1 }));
+        let result = rogue_ai_system(&mut world, &grid);
+        assert!(matches!(result.audio_events[..], [AudioEvent::MimicReveal]));
+        assert!(world.get::<&RogueVisibility>(mimic).unwrap().visible);
+        assert!(world.get::<&MimicPhase>(mimic).unwrap().revealed);
+        assert!(matches!(world.get::<&RogueAI>(mimic).unwrap().behavior_state, RogueBehaviorState::Attacking));
+        assert!(!is_hidden_mimic(&world, mimic));
+    }
+
+    #[test]
+    fn attacking_a_mimic_reveals_it() {
+        use crate::ecs::systems::combat::combat_system;
+        use crate::ecs::world::create_world;
+
+        // The player stands at (400, 300), facing the Mimic just below.
+        let (mut world, mut game_state) = create_world();
+        game_state.god_mode = true;
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        let mimic = spawn_rogue(&mut world, 400.0, 310.0, RogueTypeKind::Mimic);
+        let mut grid = SpatialGrid::default();
+        grid.insert(mimic, 400.0, 310.0);
+
+        // Stalking, it can't be hit.
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        let max = world.get::<&Health>(mimic).unwrap().max;
+        assert_eq!(world.get::<&Health>(mimic).unwrap().current, max);
+
+        // Settled into its disguise, a hit lands.
+        world.get::<&mut MimicPhase>(mimic).unwrap().elapsed_ticks = MIMIC_STALK_TICKS;
+        world.get::<&mut crate::ecs::components::CombatPower>(player).unwrap().cooldown_remaining = 0;
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        assert!(world.get::<&Health>(mimic).unwrap().current < max);
+        assert!(world.get::<&MimicDisguise>(mimic).is_ok());
+
+        // Even with nobody in range, the wound gives it away.
+        world.get::<&mut Position>(player).unwrap().x = 400.0 + 2.0 * MIMIC_REVEAL_RANGE;
+        let result = rogue_ai_system(&mut world, &grid);
+        assert!(matches!(result.audio_events[..], [AudioEvent::MimicReveal]));
+        assert!(world.get::<&MimicDisguise>(mimic).is_err());
+    }
+
+    #[test]
+    fn knockback_overrides_the_ai_until_it_runs_out() {
+        use crate::network::snapshot::rogue_deltas;
+        use crate::protocol::EntityData;
+
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let rogue = spawn_rogue(&mut world, 500.0, 0.0, RogueTypeKind::Swarm);
+        world.insert_one(rogue, Knockback { dx: 10.0, dy: 0.0, ticks: 5 }).unwrap();
+        let grid = SpatialGrid::new(64.0);
+        assert!(matches!(rogue_deltas(&world)[0].data, EntityData::Rogue { knockback_active: true, .. }));
+
+        for tick in 1..=5 {
+            rogue_ai_system(&mut world, &grid);
+            assert_eq!(world.get::<&Position>(rogue).unwrap().x, 500.0 + 10.0 * tick as f32);
+        }
+        assert!(world.get::<&Knockback>(rogue).is_err());
+        assert!(matches!(rogue_deltas(&world)[0].data, EntityData::Rogue { knockback_active: false, .. }));
+
+        // Back to hunting the player.
+        rogue_ai_system(&mut world, &grid);
+        assert!(world.get::<&Position>(rogue).unwrap().x < 550.0);
+    }
+
+    #[test]
+    fn swarms_hunting_in_packs_of_three_move_faster() {
+        use crate::network::snapshot::rogue_deltas;
+        use crate::protocol::EntityData;
+
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let grid = SpatialGrid::new(64.0);
+
+        let solo = spawn_rogue(&mut world, 500.0, 0.0, RogueTypeKind::Swarm);
+        world.insert_one(solo, PackBonus { pack_id: 0, members: 1 }).unwrap();
+        let pack: Vec<hecs::Entity> = (0..3)
+            .map(|i| {
+                let swarm = spawn_rogue(&mut world, -500.0, i as f32 * 30.0, RogueTypeKind::Swarm);
+                world.insert_one(swarm, PackBonus { pack_id: 1, members: 3 }).unwrap();
+                swarm
+            })
+            .collect();
+
+        rogue_ai_system(&mut world, &grid);
+        let solo_speed = 500.0 - world.get::<&Position>(solo).unwrap().x;
+        assert!((solo_speed - speed_for_type(RogueTypeKind::Swarm)).abs() < 1e-3);
+        let vel = world.get::<&Velocity>(pack[0]).unwrap();
+        let pack_speed = (vel.x * vel.x + vel.y * vel.y).sqrt();
+        assert!((pack_speed - solo_speed * PACK_SPEED_MULTIPLIER).abs() < 1e-3);
+        drop(vel);
+
+        let deltas = rogue_deltas(&world);
+        let in_pack = |e: hecs::Entity| {
+            let id: u64 = e.to_bits().into();
+            deltas.iter().any(|d| d.id == id && matches!(d.data, EntityData::Rogue { in_pack: true, .. }))
+        };
+        assert!(!in_pack(solo));
+        assert!(pack.iter().all(|&e| in_pack(e)));
+
+        // A straggler beyond PACK_RADIUS breaks the pack up.
+        world.get::<&mut Position>(pack[2]).unwrap().y += PACK_RADIUS * 2.0;
+        rogue_ai_system(&mut world, &grid);
+        assert_eq!(world.get::<&PackBonus>(pack[0]).unwrap().members, 2);
+        let vel = world.get::<&Velocity>(pack[0]).unwrap();
+        assert!(((vel.x * vel.x + vel.y * vel.y).sqrt() - solo_speed).abs() < 1e-3);
+    }
+
+    #[test]
+    fn architect_fires_at_a_nearby_target_then_cools_down() {
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        spawn_rogue(&mut world, 100.0, 0.0, RogueTypeKind::Architect);
+        let grid = SpatialGrid::new(64.0);
+
+        rogue_ai_system(&mut world, &grid);
+        let bolts: Vec<Projectile> = world.query::<&Projectile>().iter().map(|(_e, p)| p.clone()).collect();
+        assert_eq!(bolts.len(), 1);
+        assert!(!bolts[0].owner_is_player);
+        assert!(bolts[0].dx < 0.0);
+        assert_eq!(bolts[0].damage, ARCHITECT_BOLT_DAMAGE);
+
+        for _ in 0..ARCHITECT_FIRE_COOLDOWN {
+            rogue_ai_system(&mut world, &grid);
+        }
+        assert_eq!(world.query::<&Projectile>().iter().count(), 1);
+        rogue_ai_system(&mut world, &grid);
+        assert_eq!(world.query::<&Projectile>().iter().count(), 2);
+    }
+
+    #[test]
+    fn compute_farm_zone_halves_rogue_speed() {
+        use crate::ecs::systems::placement::zone_of_control;
+        use crate::protocol::BuildingTypeKind;
+
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 1000.0 }));
+        world.spawn((
+            Building,
+            Position { x: 0.0, y: 0.0 },
+            ConstructionProgress { current: 1.0, total: 1.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+            zone_of_control(BuildingTypeKind::ComputeFarm).unwrap(),
+        ));
+        let inside = spawn_rogue(&mut world, 0.0, 50.0, RogueTypeKind::Swarm);
+        let outside = spawn_rogue(&mut world, 0.0, -200.0, RogueTypeKind::Swarm);
+        let grid = SpatialGrid::new(64.0);
+
+        rogue_ai_system(&mut world, &grid);
+        let speed = |e| {
+            let vel = world.get::<&Velocity>(e).unwrap();
+            (vel.x * vel.x + vel.y * vel.y).sqrt()
+        };
+        let normal = speed_for_type(RogueTypeKind::Swarm);
+        assert!((speed(inside) - normal * 0.5).abs() < 1e-4);
+        assert!((speed(outside) - normal).abs() < 1e-4);
+    }
+
+    fn spawn_marked_agent(world: &mut World, name: &str, x: f32, level: u32, xp: u64) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            Position { x, y: 0.0 },
+            AgentXP { xp, level },
+            AgentState { state: AgentStateKind::Idle },
+            AgentName { name: name.to_string() },
+        ))
+    }
+
+    fn assassin_target(world: &World, assassin: hecs::Entity) -> Option<hecs::Entity> {
+        world.get::<&RogueAI>(assassin).unwrap().target
+    }
+
+    #[test]
+    fn assassin_locks_onto_its_mark() {
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 500.0 }));
+        let veteran = spawn_marked_agent(&mut world, "Ada", 400.0, 3, 500);
+        spawn_marked_agent(&mut world, "Bob", 150.0, 1, 50);
+        let assassin = spawn_rogue(&mut world, 100.0, 0.0, RogueTypeKind::Assassin);
+        let grid = SpatialGrid::new(64.0);
+
+        let result = rogue_ai_system(&mut world, &grid);
+        assert_eq!(assassin_target(&world, assassin), Some(veteran));
+        assert!(result.log_entries.contains(&"[combat] an assassin is hunting Ada".to_string()));
+        assert_eq!(result.combat_events.len(), 1);
+
+        // A newcomer outranking the mark does not break the lock, and the
+        // warning is not repeated.
+        spawn_marked_agent(&mut world, "Cy", 120.0, 5, 900);
+        for _ in 0..3 {
+            let result = rogue_ai_system(&mut world, &grid);
+            assert!(result.combat_events.is_empty());
+            assert!(!result.log_entries.iter().any(|l| l.contains("hunting")));
+        }
+        assert_eq!(assassin_target(&world, assassin), Some(veteran));
+        assert!(world.get::<&Position>(assassin).unwrap().x > 100.0);
+    }
+
+    #[test]
+    fn assassin_retargets_when_its_mark_falls() {
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 500.0 }));
+        let near = spawn_marked_agent(&mut world, "Ada", 150.0, 2, 200);
+        let far = spawn_marked_agent(&mut world, "Bob", 400.0, 2, 200);
+        let assassin = spawn_rogue(&mut world, 100.0, 0.0, RogueTypeKind::Assassin);
+        let grid = SpatialGrid::new(64.0);
+
+        // Equal level and XP: the nearer agent wins.
+        rogue_ai_system(&mut world, &grid);
+        assert_eq!(assassin_target(&world, assassin), Some(near));
+
+        world.get::<&mut AgentState>(near).unwrap().state = AgentStateKind::Unresponsive;
+        let result = rogue_ai_system(&mut world, &grid);
+        assert_eq!(assassin_target(&world, assassin), Some(far));
+        assert!(result.log_entries.contains(&"[combat] an assassin is hunting Bob".to_string()));
+
+        world.despawn(far).unwrap();
+        rogue_ai_system(&mut world, &grid);
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e);
+        assert_eq!(assassin_target(&world, assassin), player);
+    }
+
+    #[test]
+    fn feared_rogues_run_straight_away_from_the_player() {
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 100.0, y: 100.0 }));
+        let rogue = spawn_rogue(&mut world, 130.0, 140.0, RogueTypeKind::Swarm);
+        world.insert_one(rogue, Feared { ticks_remaining: 2 }).unwrap();
+        let grid = SpatialGrid::new(64.0);
+
+        rogue_ai_system(&mut world, &grid);
+        let (vx, vy) = world.get::<&Velocity>(rogue).map(|v| (v.x, v.y)).unwrap();
+        let speed = speed_for_type(RogueTypeKind::Swarm);
+        // (30, 40) from the player: a 3-4-5 triangle.
+        assert!((vx - 0.6 * speed).abs() < 1e-5 && (vy - 0.8 * speed).abs() < 1e-5);
+        assert!(matches!(world.get::<&RogueAI>(rogue).unwrap().behavior_state, RogueBehaviorState::Fleeing));
+
+        rogue_ai_system(&mut world, &grid);
+        assert!(world.get::<&Feared>(rogue).is_err());
+        // The fear is gone: it turns back on the player.
+        rogue_ai_system(&mut world, &grid);
+        let vel = world.get::<&Velocity>(rogue).unwrap();
+        assert!(vel.x < 0.0 && vel.y < 0.0);
     }
 }
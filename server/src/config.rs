@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_WS_ADDR: &str = "127.0.0.1:9001";
+pub const DEFAULT_HTTP_ADDR: &str = "127.0.0.1:9002";
+pub const DEFAULT_SPECTATOR_ADDR: &str = "127.0.0.1:9003";
+pub const DEFAULT_TICK_RATE_HZ: u64 = 20;
+pub const DEFAULT_DEV_PORT_RANGE: u16 = 20;
+pub const DEFAULT_BUILD_RADIUS: f32 = crate::ecs::systems::placement::DEFAULT_BASE_BUILD_RADIUS;
+
+/// Server settings resolved from command-line flags, then environment
+/// variables, then defaults.
+///
+/// | Flag              | Environment           | Default                     |
+/// |-------------------|-----------------------|-----------------------------|
+/// | `--ws-addr`       | `ITTB_WS_ADDR`        | `127.0.0.1:9001`            |
+/// | `--http-addr`     | `ITTB_HTTP_ADDR`      | `127.0.0.1:9002`            |
+/// | `--spectator-addr`| `ITTB_SPECTATOR_ADDR` | `127.0.0.1:9003`            |
+/// | `--tick-rate`     | `ITTB_TICK_RATE`      | `20`                        |
+/// | `--manifest-path` | `ITTB_MANIFEST_PATH`  | `buildings_manifest.json`, falling back to `../buildings_manifest.json` |
+/// | `--dev-port-range`| `ITTB_DEV_PORT_RANGE` | `20`                        |
+/// | `--debug-allowed` | `ITTB_DEBUG_ALLOWED`  | `true`                      |
+/// | `--build-radius`  | `ITTB_BUILD_RADIUS`   | `600`                       |
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub ws_addr: String,
+    pub http_addr: String,
+    /// Where a read-only spectator client may connect.
+    pub spectator_addr: String,
+    pub tick_rate: u64,
+    pub manifest_path: PathBuf,
+    /// Ports above a building's manifest port to try when it is taken.
+    pub dev_port_range: u16,
+    /// Whether clients may use the `Debug*` actions.
+    pub debug_allowed: bool,
+    /// How far (pixels) from the Token Wheel app buildings may be placed.
+    pub build_radius: f32,
+}
+
+impl ServerConfig {
+    /// Resolves the config from the process arguments and environment.
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(
+            std::env::args().skip(1),
+            |key| std::env::var(key).ok(),
+            |path| path.exists(),
+        )
+    }
+
+    /// Resolves the config from `args` (without the program name) and the
+    /// `env` lookup. `exists` is used for the manifest path fallback.
+    pub fn parse(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+        exists: impl Fn(&Path) -> bool,
+    ) -> Result<Self, String> {
+        let mut ws_addr = env("ITTB_WS_ADDR");
+        let mut http_addr = env("ITTB_HTTP_ADDR");
+        let mut spectator_addr = env("ITTB_SPECTATOR_ADDR");
+        let mut tick_rate = env("ITTB_TICK_RATE");
+        let mut manifest_path = env("ITTB_MANIFEST_PATH");
+        let mut dev_port_range = env("ITTB_DEV_PORT_RANGE");
+        let mut debug_allowed = env("ITTB_DEBUG_ALLOWED");
+        let mut build_radius = env("ITTB_BUILD_RADIUS");
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`.
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let slot = match flag.as_str() {
+                "--ws-addr" => &mut ws_addr,
+                "--http-addr" => &mut http_addr,
+                "--spectator-addr" => &mut spectator_addr,
+                "--tick-rate" => &mut tick_rate,
+                "--manifest-path" => &mut manifest_path,
+                "--dev-port-range" => &mut dev_port_range,
+                "--debug-allowed" => &mut debug_allowed,
+                "--build-radius" => &mut build_radius,
+                _ => return Err(format!("unknown argument: {}", flag)),
+            };
+            let value = match inline {
+                Some(value) => value,
+                None => args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {}", flag))?,
+            };
+            *slot = Some(value);
+        }
+
+        let tick_rate = match tick_rate {
+            Some(raw) => match raw.parse::<u64>() {
+                Ok(rate) if (1..=1000).contains(&rate) => rate,
+                _ => return Err(format!("invalid tick rate: {} (expected 1-1000)", raw)),
+            },
+            None => DEFAULT_TICK_RATE_HZ,
+        };
+
+        let dev_port_range = match dev_port_range {
+            Some(raw) => raw
+                .parse::<u16>()
+                .map_err(|_| format!("invalid dev port range: {} (expected 0-65535)", raw))?,
+            None => DEFAULT_DEV_PORT_RANGE,
+        };
+
+        let debug_allowed = match debug_allowed.as_deref() {
+            None => true,
+            Some("true" | "1" | "on") => true,
+            Some("false" | "0" | "off") => false,
+            Some(raw) => return Err(format!("invalid debug-allowed value: {} (expected true or false)", raw)),
+        };
+
+        let build_radius = match build_radius {
+            Some(raw) => match raw.parse::<f32>() {
+                Ok(radius) if radius.is_finite() && radius > 0.0 => radius,
+                _ => return Err(format!("invalid build radius: {} (expected a positive number)", raw)),
+            },
+            None => DEFAULT_BUILD_RADIUS,
+        };
+
+        // The manifest lives at the repo root, so fall back to the parent
+        // directory when running from server/.
+        let manifest_path = match manifest_path {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let local = PathBuf::from("buildings_manifest.json");
+                if exists(&local) {
+                    local
+                } else {
+                    PathBuf::from("../buildings_manifest.json")
+                }
+            }
+        };
+
+        Ok(ServerConfig {
+            ws_addr: ws_addr.unwrap_or_else(|| DEFAULT_WS_ADDR.to_string()),
+            http_addr: http_addr.unwrap_or_else(|| DEFAULT_HTTP_ADDR.to_string()),
+            spectator_addr: spectator_addr.unwrap_or_else(|| DEFAULT_SPECTATOR_ADDR.to_string()),
+            tick_rate,
+            manifest_path,
+            dev_port_range,
+            debug_allowed,
+            build_radius,
+        })
+    }
+
+    /// Wall-clock time between game ticks.
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_micros(1_000_000 / self.tick_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn parse(args: &[&str], env: &[(&str, &str)], manifest_here: bool) -> Result<ServerConfig, String> {
+        let env: HashMap<String, String> =
+            env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        ServerConfig::parse(
+            args.iter().map(|a| a.to_string()),
+            |key| env.get(key).cloned(),
+            |_| manifest_here,
+        )
+    }
+
+    #[test]
+    fn defaults_match_the_previous_hard_coded_values() {
+        let config = parse(&[], &[], true).unwrap();
+        assert_eq!(config.ws_addr, "127.0.0.1:9001");
+        assert_eq!(config.http_addr, "127.0.0.1:9002");
+        assert_eq!(config.spectator_addr, "127.0.0.1:9003");
+        assert_eq!(config.tick_rate, 20);
+        assert_eq!(config.tick_duration(), Duration::from_millis(50));
+        assert_eq!(config.manifest_path, PathBuf::from("buildings_manifest.json"));
+        assert_eq!(config.dev_port_range, 20);
+        assert!(config.debug_allowed);
+        assert_eq!(config.build_radius, 600.0);
+
+        let config = parse(&[], &[], false).unwrap();
+        assert_eq!(config.manifest_path, PathBuf::from("../buildings_manifest.json"));
+    }
+
+    #[test]
+    fn flags_override_environment() {
+        let env = [("ITTB_WS_ADDR", "0.0.0.0:7000"), ("ITTB_TICK_RATE", "10")];
+        let config = parse(&[], &env, true).unwrap();
+        assert_eq!(config.ws_addr, "0.0.0.0:7000");
+        assert_eq!(config.tick_rate, 10);
+
+        let config = parse(
+            &["--ws-addr", "0.0.0.0:8000", "--tick-rate=40", "--http-addr", "0.0.0.0:8001", "--manifest-path", "/tmp/m.json"],
+            &env,
+            true,
+        )
+        .unwrap();
+        assert_eq!(config.ws_addr, "0.0.0.0:8000");
+        assert_eq!(config.http_addr, "0.0.0.0:8001");
+        assert_eq!(config.tick_rate, 40);
+        assert_eq!(config.tick_duration(), Duration::from_millis(25));
+        assert_eq!(config.manifest_path, PathBuf::from("/tmp/m.json"));
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        assert!(parse(&["--port", "1"], &[], true).is_err());
+        assert!(parse(&["--ws-addr"], &[], true).is_err());
+        assert!(parse(&["--tick-rate", "0"], &[], true).is_err());
+        assert!(parse(&[], &[("ITTB_TICK_RATE", "fast")], true).is_err());
+        assert!(parse(&["--dev-port-range", "-1"], &[], true).is_err());
+        assert!(parse(&["--debug-allowed", "maybe"], &[], true).is_err());
+        assert!(parse(&["--build-radius", "0"], &[], true).is_err());
+        assert!(parse(&[], &[("ITTB_BUILD_RADIUS", "far")], true).is_err());
+    }
+
+    #[test]
+    fn debug_actions_can_be_disabled() {
+        assert!(!parse(&["--debug-allowed=false"], &[], true).unwrap().debug_allowed);
+        assert!(!parse(&[], &[("ITTB_DEBUG_ALLOWED", "off")], true).unwrap().debug_allowed);
+        assert!(parse(&["--debug-allowed", "1"], &[("ITTB_DEBUG_ALLOWED", "0")], true).unwrap().debug_allowed);
+    }
+}
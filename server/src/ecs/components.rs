@@ -1,25 +1,53 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use serde::{Deserialize, Serialize};
 use crate::game::upgrades::UpgradeState;
-use crate::protocol::{AgentStateKind, AgentTierKind, BuildingTypeKind, RogueTypeKind, TaskAssignment};
+use crate::protocol::{
+    AgentSpecialization, AgentStateKind, AgentTierKind, BuildingTypeKind, RogueTypeKind, StatusEffect, TaskAssignment,
+    TransactionEntry,
+};
+pub use crate::protocol::{CrankTier, GamePhase};
 
 // ── Marker Components ────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Agent;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Building;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rogue;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DroppedItem;
 
-#[derive(Debug, Clone)]
+/// Items a rogue may drop when killed; each entry is rolled independently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootTable {
+    pub entries: Vec<LootEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootEntry {
+    /// Inventory item type; `blueprint:any` resolves to a random blueprint.
+    pub item_type: String,
+    /// Chance (0.0-1.0) that this entry drops.
+    pub weight: f32,
+    /// Inclusive range of how many drop.
+    pub count_range: (u32, u32),
+}
+
+/// Loot lying on the ground, picked up when the player walks over it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Loot {
+    pub item_type: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Projectile {
     pub dx: f32,
     pub dy: f32,
@@ -27,17 +55,20 @@ pub struct Projectile {
     pub damage: i32,
     pub range_remaining: f32,
     pub owner_is_player: bool,
+    /// Whether the shot rolled a critical when fired; `damage` already includes it.
+    #[serde(default)]
+    pub is_crit: bool,
 }
 
 // ── Spatial ──────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
@@ -49,20 +80,20 @@ impl Default for Velocity {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Collider {
     pub radius: f32,
 }
 
 // ── Player Components ────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Health {
     pub current: i32,
     pub max: i32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Facing {
     pub dx: f32,
     pub dy: f32,
@@ -74,18 +105,29 @@ impl Default for Facing {
     }
 }
 
+/// An in-progress (or cooling-down) player dodge roll. While `iframes` is
+/// non-zero rogues cannot hurt the player.
 #[derive(Debug, Clone)]
+pub struct DodgeState {
+    pub ticks_remaining: u32,
+    pub dx: f32,
+    pub dy: f32,
+    pub iframes: u32,
+    pub cooldown_remaining: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TorchRange {
     pub radius: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CarryCapacity {
     pub current: u32,
     pub max: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WeaponType {
     ProcessTerminator,
     HardReset,
@@ -94,7 +136,7 @@ pub enum WeaponType {
     Flare,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CombatPower {
     pub base_damage: i32,
     pub attack_speed: f32,
@@ -104,9 +146,28 @@ pub struct CombatPower {
     pub range: f32,
     pub arc_degrees: f32,
     pub is_projectile: bool,
+    /// Chance (0..1) that a hit is a critical.
+    #[serde(default)]
+    pub crit_chance: f32,
+    /// Damage multiplier applied to critical hits.
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: f32,
 }
 
-#[derive(Debug, Clone)]
+fn default_crit_multiplier() -> f32 {
+    1.0
+}
+
+/// Wear on the player's equipped weapon. Only breakable weapons carry one;
+/// at zero the weapon breaks and the player falls back to the basic
+/// ProcessTerminator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Durability {
+    pub current: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ArmorType {
     BasePrompt,
     FewShotPadding,
@@ -114,7 +175,7 @@ pub enum ArmorType {
     ConstitutionalPlate,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Armor {
     pub armor_type: ArmorType,
     pub damage_reduction: f32,
@@ -123,7 +184,7 @@ pub struct Armor {
 
 // ── Agent Components ─────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentStats {
     pub reliability: f32,
     pub speed: f32,
@@ -131,43 +192,56 @@ pub struct AgentStats {
     pub resilience: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentState {
     pub state: AgentStateKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentMorale {
     pub value: f32,
 }
 
-#[derive(Debug, Clone)]
+/// An agent that dropped its work when its morale hit zero. It keeps its
+/// `Assignment` and goes back to it once morale recovers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefusingWork;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentXP {
     pub xp: u64,
     pub level: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentTier {
     pub tier: AgentTierKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentName {
     pub name: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentPersonality {
     pub traits: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceProfile {
     pub voice_id: String,
 }
 
-#[derive(Debug, Clone)]
+/// When an agent last spoke and the state it was last seen in, so speech
+/// can be rate-limited and state changes noticed. Added on first speech.
+#[derive(Debug, Clone, Default)]
+pub struct Chatter {
+    pub last_spoke_tick: Option<u64>,
+    pub last_state: Option<AgentStateKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentVibeConfig {
     pub model_id: String,
     pub model_lore_name: String,
@@ -182,7 +256,7 @@ pub struct AgentVibeConfig {
     pub stars: u8,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WanderState {
     pub home_x: f32,
     pub home_y: f32,
@@ -192,21 +266,85 @@ pub struct WanderState {
     pub wander_radius: f32,
     /// When set, agent walks to this target and transitions to Building on arrival.
     pub walk_target: Option<(f32, f32)>,
+    /// Consecutive ticks spent walking without getting closer to `walk_target`.
+    #[serde(default)]
+    pub walk_ticks: u32,
 }
 
-#[derive(Debug, Clone)]
+/// Build fatigue. Long Building sessions accumulate ticks; past a tier
+/// threshold the agent must rest off `rest_debt` ticks before working again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentFatigue {
+    pub accumulated_ticks: u32,
+    pub rest_debt: u32,
+}
+
+/// Countdown on an Unresponsive agent. The player can revive it until this
+/// runs out; after that it is gone for good.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviveTimer {
+    pub ticks_remaining: u32,
+}
+
+/// The role an agent was specialized into. Permanent once added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Specialization {
+    pub role: AgentSpecialization,
+}
+
+/// Countdown on an Erroring agent until it recovers to Idle by itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorRecovery {
+    pub ticks_remaining: u32,
+}
+
+/// An agent as it was when its last vibe session started, for the player
+/// to roll back to if the session goes wrong.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentCheckpoint {
+    pub state_snapshot: AgentState,
+    pub health_snapshot: Health,
+    pub position_snapshot: Position,
+    pub saved_at_tick: u64,
+}
+
+/// An agent posted at the Token Wheel, remembering where it wandered before
+/// so unassigning can send it back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WheelPost {
+    pub prev_home_x: f32,
+    pub prev_home_y: f32,
+    pub prev_wander_radius: f32,
+}
+
+/// Ticks until a Defending agent may strike again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DefenseCooldown {
+    pub remaining: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Assignment {
     pub task: TaskAssignment,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Recruitable {
     pub cost: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoundAgent;
 
+/// A [`BoundAgent`] whose guardians are all dead: it can now be recruited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Unguarded;
+
+/// A rescued NPC survivor working as a permanent scout. Scouts are agents
+/// without a vibe config: they never code, only explore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scout;
+
 #[derive(Debug, Clone)]
 pub struct GuardianRogue {
     pub home_x: f32,
@@ -218,9 +356,32 @@ pub struct GuardianRogue {
     pub patrol_pause: u32,
 }
 
+/// Multi-phase boss behavior for an Architect rogue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RogueBossPhase {
+    /// Current phase; 0 until the first threshold is crossed.
+    pub phase: u8,
+    /// HP fractions (descending) below which the boss enters the next phase.
+    pub phase_threshold: Vec<f32>,
+    /// Ticks left in the post-summon retreat.
+    pub retreat_ticks: u32,
+}
+
+impl RogueBossPhase {
+    /// The Architect boss: phase 1 below 60% HP, phase 2 below 30%.
+    pub fn architect() -> Self {
+        Self { phase: 0, phase_threshold: vec![0.6, 0.3], retreat_ticks: 0 }
+    }
+
+    /// The phase a boss at `hp_fraction` of its max HP should be in.
+    pub fn phase_for(&self, hp_fraction: f32) -> u8 {
+        self.phase_threshold.iter().filter(|&&t| hp_fraction < t).count() as u8
+    }
+}
+
 // ── Building Components ──────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildingType {
     pub kind: BuildingTypeKind,
 }
@@ -230,15 +391,25 @@ pub struct ConstructionProgress {
     pub current: f32,
     pub total: f32,
     pub assigned_agents: Vec<hecs::Entity>,
+    /// Relative share of pooled construction work (1.0 = normal).
+    pub priority_weight: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LightSource {
     pub radius: f32,
     pub color: (f32, f32, f32),
 }
 
-#[derive(Debug, Clone)]
+/// Rogues within `radius` of a completed building move at `slow_factor`
+/// of their usual speed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoneOfControl {
+    pub radius: f32,
+    pub slow_factor: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BuildingEffect {
     PassiveIncome(f64),
     AgentMoraleBoost(f32),
@@ -248,19 +419,60 @@ pub enum BuildingEffect {
     CrankHeatReduction(f32),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildingEffects {
     pub effects: Vec<BuildingEffect>,
 }
 
+/// Names of the synergies a building led last tick, so new ones can be
+/// announced.
+#[derive(Debug, Clone, Default)]
+pub struct ActiveSynergies {
+    pub names: Vec<&'static str>,
+}
+
+// ── Status Effects ──────────────────────────────────────────────────
+
+/// Timed effects on a rogue or the player, ticked by the status effect system.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Adds `effect`, replacing any active effect of the same kind so that
+    /// re-applying refreshes the timer instead of stacking.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        self.effects
+            .retain(|e| std::mem::discriminant(e) != std::mem::discriminant(&effect));
+        self.effects.push(effect);
+    }
+
+    /// Movement speed multiplier from any active Slowed effect.
+    pub fn speed_factor(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| match e {
+                StatusEffect::Slowed { factor, .. } => *factor,
+                _ => 1.0,
+            })
+            .fold(1.0, f32::min)
+    }
+
+    /// Whether a Corrupted effect is active.
+    pub fn is_corrupted(&self) -> bool {
+        self.effects.iter().any(|e| matches!(e, StatusEffect::Corrupted { .. }))
+    }
+}
+
 // ── Rogue Components ─────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RogueType {
     pub kind: RogueTypeKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RogueBehaviorState {
     Wandering,
     Approaching,
@@ -275,21 +487,90 @@ pub struct RogueAI {
     pub target: Option<hecs::Entity>,
 }
 
-#[derive(Debug, Clone)]
+/// A rogue thrown back by a heavy hit: it drifts `(dx, dy)` per tick for
+/// `ticks` ticks instead of following its AI, and can't attack meanwhile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Knockback {
+    pub dx: f32,
+    pub dy: f32,
+    pub ticks: u32,
+}
+
+/// The player's torch blazing after a Flare hit: `TorchRange.radius` stays
+/// at `base_radius` plus the flare bonus until `ticks_remaining` runs out,
+/// then drops back to `base_radius`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlareEffect {
+    pub ticks_remaining: u32,
+    pub base_radius: f32,
+}
+
+/// A rogue scared off by a Flare: it runs straight away from the player
+/// instead of following its AI for `ticks_remaining` ticks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feared {
+    pub ticks_remaining: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RogueVisibility {
     pub visible: bool,
 }
 
-// ── World State (plain structs, not ECS entities) ────────────────────
+/// A Swarm spawned alongside others in the same wave. `members` is how many
+/// of its pack (itself included) are close enough to hunt together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackBonus {
+    pub pack_id: u32,
+    pub members: u32,
+}
 
-#[derive(Debug, Clone)]
-pub enum CrankTier {
-    HandCrank,
-    GearAssembly,
-    WaterWheel,
-    RunicEngine,
+/// A rogue nest out in the world. Each tick it spawns a Swarm with
+/// probability `spawn_rate` until its `Health` runs out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RogueNest {
+    pub spawn_rate: f32,
+}
+
+/// A rogue spawned as part of rogue wave `wave`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveRogue {
+    pub wave: u32,
+}
+
+/// Ticks until a rogue with a ranged attack may fire again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RangedCooldown {
+    pub remaining: u32,
+}
+
+/// A Mimic still posing as a building. Removed when it reveals itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MimicDisguise {
+    pub disguise: BuildingTypeKind,
+}
+
+/// Where a Mimic is in its ambush: stalking the player unseen for its
+/// first ticks, then posing as its disguise until something comes close.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MimicPhase {
+    pub elapsed_ticks: u32,
+    pub revealed: bool,
+}
+
+/// A TokenDrain's leeching progress, attached the first time it drains.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenDrainState {
+    /// Consecutive ticks spent within reach of the player or a building.
+    pub ticks_in_range: u32,
+    /// Tokens stolen so far; half is handed back when the drain is killed.
+    pub stolen: i64,
+    /// Sub-token drain carried over between ticks.
+    pub fractional: f64,
 }
 
+// ── World State (plain structs, not ECS entities) ────────────────────
+
 #[derive(Debug, Clone)]
 pub struct CrankState {
     pub heat: f32,
@@ -298,28 +579,39 @@ pub struct CrankState {
     pub cool_rate: f32,
     pub tier: CrankTier,
     pub is_cranking: bool,
-    pub assigned_agent: Option<hecs::Entity>,
+    /// Agents working the crank, at most `MAX_CRANK_AGENTS`.
+    pub assigned_agents: Vec<hecs::Entity>,
     pub tokens_per_rotation: f64,
+    /// Ticks left in an overheat lockout; 0 when the crank can be worked.
+    pub overheated_remaining: u32,
+    /// Heat is at 90% or more and nearby buildings are taking damage.
+    pub overheating: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenEconomy {
     pub balance: i64,
-    /// Sub-token accumulator so fractional generation isn't lost.
+    /// Sub-token accumulator so fractional crank generation isn't lost.
     pub fractional: f64,
+    /// Sub-token building income not yet paid into `balance`.
+    #[serde(default)]
+    pub income_fractional: f64,
+    /// Sub-token agent wages not yet taken from `balance`.
+    #[serde(default)]
+    pub wage_fractional: f64,
     pub income_per_tick: f64,
     pub expenditure_per_tick: f64,
     pub income_sources: Vec<(String, f64)>,
     pub expenditure_sinks: Vec<(String, f64)>,
-}
-
-#[derive(Debug, Clone)]
-pub enum GamePhase {
-    Hut,
-    Outpost,
-    Village,
-    Network,
-    City,
+    /// Most recent balance changes, oldest first.
+    #[serde(default)]
+    pub transaction_log: VecDeque<TransactionEntry>,
+    /// Total tokens ever earned, used to gate phase progression.
+    #[serde(default)]
+    pub lifetime_earned: i64,
+    /// Lowest the balance may fall outside god mode (0 until debt exists).
+    #[serde(default)]
+    pub min_balance: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -338,6 +630,74 @@ pub struct GameState {
     pub inventory: Vec<crate::protocol::InventoryItem>,
     pub opened_chests: HashSet<(i32, i32)>,
     pub spawned_camps: HashSet<(i32, i32)>,
+    /// Chunks that have already had discoveries scattered into them.
+    pub populated_chunks: HashSet<(i32, i32)>,
+    /// Chunks whose rogue nest has been destroyed; no nest is scattered
+    /// into them again.
+    pub destroyed_nests: HashSet<(i32, i32)>,
+    /// Set once a mum's card has been found; no more are scattered.
+    pub mums_card_found: bool,
+    /// Power cores collected so far (each permanently boosts the crank).
+    pub power_cores_collected: u32,
+    /// Id handed to the next pack of Swarms spawned together.
+    pub next_pack_id: u32,
+    /// Tokens locked away until they mature (see `economy::invest`).
+    pub investments: Vec<Investment>,
+    /// Scheduled rogue waves (see `spawn::wave_system`).
+    pub wave: WaveSchedule,
+}
+
+/// Progress of the periodic rogue waves.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaveSchedule {
+    /// Tick the next wave hits; 0 until the first one is scheduled.
+    pub next_wave_tick: u64,
+    /// Waves spawned so far (the current wave's number).
+    pub wave_number: u32,
+    /// Rogues from the current wave are still alive.
+    pub active: bool,
+    /// Compass direction of the announced or arriving wave, set once its
+    /// warning has gone out.
+    pub incoming: Option<u8>,
+    /// The announced wave, or what is left of it while it spawns.
+    pub current_wave: Option<WaveSpec>,
+    /// Ticks the arriving wave still has to finish spawning in.
+    pub spawn_ticks_left: u64,
+}
+
+/// What a rogue wave is made of.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WaveSpec {
+    /// Each kind of rogue and how many of it.
+    pub rogues: Vec<(crate::protocol::RogueTypeKind, u32)>,
+}
+
+impl WaveSpec {
+    /// Rogues left in the wave.
+    pub fn total(&self) -> u32 {
+        self.rogues.iter().map(|(_kind, count)| count).sum()
+    }
+
+    /// Removes up to `n` rogues, in listed order, returning their kinds.
+    pub fn take(&mut self, n: u32) -> Vec<crate::protocol::RogueTypeKind> {
+        let mut taken = Vec::new();
+        for (kind, count) in &mut self.rogues {
+            while *count > 0 && (taken.len() as u32) < n {
+                *count -= 1;
+                taken.push(*kind);
+            }
+        }
+        self.rogues.retain(|(_kind, count)| *count > 0);
+        taken
+    }
+}
+
+/// Tokens locked until `matures_at`, when `return_amount` is paid back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Investment {
+    pub principal: i64,
+    pub return_amount: i64,
+    pub matures_at: u64,
 }
 
 impl GameState {
@@ -379,7 +739,7 @@ impl GameState {
 
 use crate::game::exploration::DiscoveryKind;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Discovery {
     pub kind: DiscoveryKind,
     pub interacted: bool,
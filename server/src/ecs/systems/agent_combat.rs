@@ -0,0 +1,334 @@
+use hecs::World;
+
+use crate::ecs::components::{
+    Agent, AgentName, AgentState, AgentStats, AgentTier, DefenseCooldown, Health, MimicDisguise,
+    Position, RogueType, TokenEconomy, Velocity, WanderState,
+};
+use crate::ecs::systems::combat::bounty_for;
+use crate::ecs::systems::economy::record_transaction;
+use crate::ecs::systems::spawn::death_spawns;
+use crate::ecs::systems::token_drain::drain_refund;
+use crate::ecs::systems::xp::award_xp;
+use crate::game::spatial::SpatialGrid;
+use crate::game::upgrades::UpgradeState;
+use crate::protocol::{AgentStateKind, AgentTierKind, AudioEvent, CombatEvent, RogueTypeKind};
+
+/// Base chase speed multiplier. Effective speed = BASE_DEFEND_SPEED * agent.speed.
+const BASE_DEFEND_SPEED: f32 = 0.6;
+
+/// Distance (pixels) at which a defender can strike its target.
+pub const DEFENDER_ATTACK_RANGE: f32 = 24.0;
+
+/// Ticks between defender strikes (1 second at 20Hz).
+pub const DEFENDER_COOLDOWN_TICKS: u32 = 20;
+
+/// XP awarded to a defender for each kill.
+pub const DEFENDER_KILL_XP: u64 = 10;
+
+/// Distance threshold to consider the guard post "reached".
+const HOME_THRESHOLD: f32 = 2.0;
+
+/// The result of running the agent defense system for one tick.
+#[derive(Default)]
+pub struct AgentCombatResult {
+    pub killed_rogues: Vec<(hecs::Entity, RogueTypeKind)>,
+    pub combat_events: Vec<CombatEvent>,
+    pub audio_events: Vec<AudioEvent>,
+    pub log_entries: Vec<String>,
+    pub bounty_tokens: i64,
+    /// Rogues to spawn once combat resolves, e.g. Swarms from a Multiplier.
+    pub pending_spawns: Vec<(f32, f32, RogueTypeKind)>,
+}
+
+/// Damage a defender deals per strike, by tier.
+pub fn defender_damage(tier: AgentTierKind) -> i32 {
+    match tier {
+        AgentTierKind::Apprentice => 5,
+        AgentTierKind::Journeyman => 8,
+        AgentTierKind::Artisan => 12,
+        AgentTierKind::Architect => 18,
+    }
+}
+
+/// Runs the agent defense system for a single tick.
+///
+/// Defending agents acquire the nearest rogue within their `awareness`
+/// radius, close in at their `speed`, and strike on a cooldown. Kills pay
+/// bounty into `economy` and XP to the agent. With nothing in range they
+/// walk back to their guard post (`WanderState` home).
+///
+/// `rogue_grid` must contain every live rogue; killed rogues are removed
+/// from it and despawned.
+pub fn agent_combat_system(
+    world: &mut World,
+    economy: &mut TokenEconomy,
+    tick: u64,
+    rogue_grid: &mut SpatialGrid,
+    upgrades: &UpgradeState,
+) -> AgentCombatResult {
+    let mut result = AgentCombatResult::default();
+
+    let defenders: Vec<(hecs::Entity, f32, f32, f32, f32, AgentTierKind)> = world
+        .query::<(&Agent, &AgentState, &AgentStats, &AgentTier, &Position)>()
+        .iter()
+        .filter(|(_e, (_a, state, _stats, _tier, _pos))| state.state == AgentStateKind::Defending)
+        .map(|(e, (_a, _state, stats, tier, pos))| {
+            (e, pos.x, pos.y, stats.speed, stats.awareness, tier.tier)
+        })
+        .collect();
+
+    for (entity, ax, ay, speed, awareness, tier) in defenders {
+        // Tick down (or attach) this defender's strike cooldown.
+        let remaining = world.get::<&DefenseCooldown>(entity).map(|cd| cd.remaining).ok();
+        let ready = match remaining {
+            Some(remaining) => {
+                if let Ok(mut cd) = world.get::<&mut DefenseCooldown>(entity) {
+                    cd.remaining = remaining.saturating_sub(1);
+                }
+                remaining <= 1
+            }
+            None => {
+                let _ = world.insert_one(entity, DefenseCooldown::default());
+                true
+            }
+        };
+
+        // ── Acquire nearest rogue within awareness ──────────────────
+        // Defenders are fooled by a Mimic's disguise like everyone else.
+        let target = rogue_grid
+            .query_radius(ax, ay, awareness)
+            .filter(|&r| world.get::<&MimicDisguise>(r).is_err())
+            .filter_map(|r| {
+                let pos = world.get::<&Position>(r).ok()?;
+                let dx = pos.x - ax;
+                let dy = pos.y - ay;
+                Some((r, pos.x, pos.y, dx * dx + dy * dy))
+            })
+            .min_by(|a, b| a.3.total_cmp(&b.3));
+
+        let Some((rogue, rx, ry, dist_sq)) = target else {
+            // ── Disengage: return to guard post ─────────────────────
+            let home = world.get::<&WanderState>(entity).map(|w| (w.home_x, w.home_y));
+            if let Ok((hx, hy)) = home {
+                step_toward(world, entity, ax, ay, hx, hy, BASE_DEFEND_SPEED * speed, HOME_THRESHOLD);
+            }
+            continue;
+        };
+
+        if dist_sq > DEFENDER_ATTACK_RANGE * DEFENDER_ATTACK_RANGE {
+            step_toward(world, entity, ax, ay, rx, ry, BASE_DEFEND_SPEED * speed, DEFENDER_ATTACK_RANGE);
+            continue;
+        }
+
+        if let Ok(mut vel) = world.get::<&mut Velocity>(entity) {
+            vel.x = 0.0;
+            vel.y = 0.0;
+        }
+        if !ready {
+            continue;
+        }
+
+        // ── Strike ──────────────────────────────────────────────────
+        let damage = defender_damage(tier);
+        let Ok(kind) = world.get::<&RogueType>(rogue).map(|rt| rt.kind) else {
+            continue;
+        };
+        let is_kill = match world.get::<&mut Health>(rogue) {
+            Ok(mut health) => {
+                health.current -= damage;
+                health.current <= 0
+            }
+            Err(_) => continue,
+        };
+        if let Ok(mut cd) = world.get::<&mut DefenseCooldown>(entity) {
+            cd.remaining = DEFENDER_COOLDOWN_TICKS;
+        }
+
+        result.audio_events.push(AudioEvent::CombatHit);
+        result.combat_events.push(CombatEvent {
+            x: rx,
+            y: ry,
+            damage,
+            is_kill,
+            rogue_type: Some(kind),
+            is_crit: false,
+        });
+
+        if is_kill {
+            let bounty = bounty_for(kind);
+            let refund = drain_refund(world, rogue);
+            result.bounty_tokens += bounty + refund;
+            result.killed_rogues.push((rogue, kind));
+            result.pending_spawns.extend(death_spawns(kind, rx, ry));
+            rogue_grid.remove(rogue);
+            let _ = world.despawn(rogue);
+            record_transaction(economy, bounty, &format!("{:?} bounty", kind), tick);
+            if refund > 0 {
+                record_transaction(economy, refund, "TokenDrain refund", tick);
+            }
+
+            award_xp(world, entity, DEFENDER_KILL_XP, upgrades);
+            let name = world
+                .get::<&AgentName>(entity)
+                .map(|n| n.name.clone())
+                .unwrap_or_else(|_| "agent".to_string());
+            result.log_entries.push(format!("[{}] defended against {:?} (+{} tokens)", name, kind, bounty));
+        }
+    }
+
+    result
+}
+
+/// Moves `entity` from (x, y) toward (tx, ty) at `speed`, stopping once
+/// within `stop_dist`.
+#[allow(clippy::too_many_arguments)]
+fn step_toward(world: &mut World, entity: hecs::Entity, x: f32, y: f32, tx: f32, ty: f32, speed: f32, stop_dist: f32) {
+    let dx = tx - x;
+    let dy = ty - y;
+    let dist = (dx * dx + dy * dy).sqrt();
+    let (vx, vy) = if dist <= stop_dist || dist == 0.0 {
+        (0.0, 0.0)
+    } else {
+        let step = speed.min(dist - stop_dist);
+        (dx / dist * step, dy / dist * step)
+    };
+
+    if let Ok(mut vel) = world.get::<&mut Velocity>(entity) {
+        vel.x = vx;
+        vel.y = vy;
+    }
+    if let Ok(mut pos) = world.get::<&mut Position>(entity) {
+        pos.x += vx;
+        pos.y += vy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::{AgentXP, Rogue, TokenEconomy};
+
+    fn economy() -> TokenEconomy {
+        TokenEconomy {
+            balance: 0,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        }
+    }
+
+    fn spawn_defender(world: &mut World, x: f32, y: f32, home: (f32, f32)) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentName { name: "Ada".to_string() },
+            AgentState { state: AgentStateKind::Defending },
+            AgentStats { reliability: 1.0, speed: 1.0, awareness: 100.0, resilience: 0.0 },
+            AgentTier { tier: AgentTierKind::Apprentice },
+            AgentXP { xp: 0, level: 1 },
+            Position { x, y },
+            Velocity::default(),
+            WanderState {
+                home_x: home.0,
+                home_y: home.1,
+                waypoint_x: home.0,
+                waypoint_y: home.1,
+                pause_remaining: 0,
+                wander_radius: 0.0,
+                walk_target: None,
+                walk_ticks: 0,
+            },
+        ))
+    }
+
+    fn spawn_rogue(world: &mut World, x: f32, y: f32, hp: i32) -> hecs::Entity {
+        world.spawn((
+            Rogue,
+            RogueType { kind: RogueTypeKind::Swarm },
+            Position { x, y },
+            Health { current: hp, max: hp },
+        ))
+    }
+
+    fn grid(world: &World) -> SpatialGrid {
+        let mut grid = SpatialGrid::default();
+        grid.insert_all::<Rogue>(world);
+        grid
+    }
+
+    fn pos(world: &World, e: hecs::Entity) -> (f32, f32) {
+        let p = world.get::<&Position>(e).unwrap();
+        (p.x, p.y)
+    }
+
+    #[test]
+    fn defender_moves_toward_nearest_rogue_in_awareness() {
+        let mut world = World::new();
+        let agent = spawn_defender(&mut world, 0.0, 0.0, (0.0, 0.0));
+        spawn_rogue(&mut world, -90.0, 0.0, 10);
+        spawn_rogue(&mut world, 60.0, 0.0, 10);
+        spawn_rogue(&mut world, 0.0, 300.0, 10); // outside awareness
+
+        let mut rogues = grid(&world);
+        agent_combat_system(&mut world, &mut economy(), 0, &mut rogues, &UpgradeState::new());
+
+        let (x, y) = pos(&world, agent);
+        assert!(x > 0.0, "should chase the nearer rogue on the right");
+        assert_eq!(y, 0.0);
+    }
+
+    #[test]
+    fn defender_kill_awards_bounty_and_xp() {
+        let mut world = World::new();
+        let agent = spawn_defender(&mut world, 0.0, 0.0, (0.0, 0.0));
+        let rogue = spawn_rogue(&mut world, 10.0, 0.0, 5);
+        let mut econ = economy();
+
+        let mut rogues = grid(&world);
+        let result = agent_combat_system(&mut world, &mut econ, 0, &mut rogues, &UpgradeState::new());
+
+        assert_eq!(result.killed_rogues, vec![(rogue, RogueTypeKind::Swarm)]);
+        assert!(!world.contains(rogue));
+        assert!(!rogues.contains(rogue));
+        assert_eq!(econ.balance, bounty_for(RogueTypeKind::Swarm));
+        assert_eq!(world.get::<&AgentXP>(agent).unwrap().xp, DEFENDER_KILL_XP);
+    }
+
+    #[test]
+    fn defender_strikes_respect_cooldown() {
+        let mut world = World::new();
+        spawn_defender(&mut world, 0.0, 0.0, (0.0, 0.0));
+        let rogue = spawn_rogue(&mut world, 10.0, 0.0, 100);
+        let mut econ = economy();
+        let mut rogues = grid(&world);
+
+        for _ in 0..DEFENDER_COOLDOWN_TICKS {
+            agent_combat_system(&mut world, &mut econ, 0, &mut rogues, &UpgradeState::new());
+        }
+        let dmg = defender_damage(AgentTierKind::Apprentice);
+        assert_eq!(world.get::<&Health>(rogue).unwrap().current, 100 - dmg);
+
+        agent_combat_system(&mut world, &mut econ, 0, &mut rogues, &UpgradeState::new());
+        assert_eq!(world.get::<&Health>(rogue).unwrap().current, 100 - 2 * dmg);
+    }
+
+    #[test]
+    fn defender_returns_home_when_no_rogues_in_range() {
+        let mut world = World::new();
+        let agent = spawn_defender(&mut world, 10.0, 0.0, (0.0, 0.0));
+        spawn_rogue(&mut world, 500.0, 0.0, 10);
+        let mut rogues = grid(&world);
+
+        for _ in 0..40 {
+            agent_combat_system(&mut world, &mut economy(), 0, &mut rogues, &UpgradeState::new());
+        }
+        let (x, y) = pos(&world, agent);
+        assert!(x.abs() <= HOME_THRESHOLD && y.abs() <= HOME_THRESHOLD);
+    }
+}
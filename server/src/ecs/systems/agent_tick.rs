@@ -1,68 +1,393 @@
+use std::collections::HashSet;
+
 use hecs::World;
 
 use crate::ecs::components::{
-    Agent, AgentName, AgentState, AgentStats, AgentVibeConfig, TokenEconomy,
+    Agent, AgentCheckpoint, AgentMorale, AgentName, AgentState, AgentStats, AgentVibeConfig, Building,
+    BuildingEffect, BuildingEffects, ConstructionProgress, ErrorRecovery, Health, Position, TokenEconomy,
 };
+use crate::ecs::systems::economy::record_transaction;
+use crate::ecs::systems::morale::{is_low_morale, LOW_MORALE_ERROR_MULTIPLIER};
 use crate::protocol::AgentStateKind;
 
+/// Ticks between turns for a working agent (5 seconds at 20Hz).
+pub const TURN_INTERVAL_TICKS: u64 = 100;
+/// Ticks an Erroring agent takes to recover on its own (10 seconds at 20Hz).
+pub const ERROR_RECOVERY_TICKS: u32 = 200;
+
 /// Result of the agent tick system -- log entries for the client.
 pub struct AgentTickResult {
     pub log_entries: Vec<String>,
 }
 
-/// Tick all working agents: increment turns_used, check for errors, handle erroring state.
-pub fn agent_tick_system(world: &mut World, economy: &mut TokenEconomy) -> AgentTickResult {
+enum TurnOutcome {
+    Paused,
+    ContextExhausted,
+    Errored,
+}
+
+/// Combined error-rate reduction from completed buildings, capped at 1.0.
+fn error_rate_reduction(world: &World) -> f32 {
+    world
+        .query::<(&BuildingEffects, Option<&ConstructionProgress>)>()
+        .with::<&Building>()
+        .iter()
+        .filter(|(_e, (_effects, progress))| progress.is_none_or(|p| p.current >= p.total))
+        .flat_map(|(_e, (effects, _progress))| effects.effects.clone())
+        .map(|effect| match effect {
+            BuildingEffect::ErrorRateReduction(r) => r,
+            _ => 0.0,
+        })
+        .sum::<f32>()
+        .min(1.0)
+}
+
+/// Tick all working agents.
+///
+/// Every [`TURN_INTERVAL_TICKS`], each Building, Exploring or Defending agent
+/// takes a turn: `turns_used` goes up and `token_burn_rate` tokens are
+/// burned. An agent that can't afford its turn pauses (goes Idle). Reaching
+/// `max_turns` exhausts the context and the agent starts Erroring; otherwise
+/// it rolls `error_chance_base` (scaled by reliability, turn ratio, low morale
+/// and the `ErrorRateReduction` of completed buildings) to start Erroring.
+///
+/// Agents in `in_session` are driven by a real Vibe CLI session, which
+/// enforces its own turn limit, and are left alone. Erroring agents burn
+/// their rate every tick and recover to Idle with a fresh context after
+/// [`ERROR_RECOVERY_TICKS`] (or sooner via [`rollback_agent`], if they
+/// have a checkpoint).
+pub fn agent_tick_system(
+    world: &mut World,
+    economy: &mut TokenEconomy,
+    tick: u64,
+    in_session: &HashSet<hecs::Entity>,
+) -> AgentTickResult {
     let mut log_entries = Vec::new();
-    let mut to_error: Vec<hecs::Entity> = Vec::new();
+    let mut outcomes: Vec<(hecs::Entity, TurnOutcome)> = Vec::new();
     let mut token_drain: i64 = 0;
+    let turn_due = tick.is_multiple_of(TURN_INTERVAL_TICKS);
+    let reduction = error_rate_reduction(world);
+
+    // ── Lazily attach recovery timers to Erroring agents ──────────
+    let missing: Vec<hecs::Entity> = world
+        .query::<hecs::Without<hecs::With<&AgentState, &Agent>, &ErrorRecovery>>()
+        .iter()
+        .filter(|(_e, state)| state.state == AgentStateKind::Erroring)
+        .map(|(e, _)| e)
+        .collect();
+    for e in missing {
+        let _ = world.insert_one(e, ErrorRecovery { ticks_remaining: ERROR_RECOVERY_TICKS });
+    }
 
-    // Phase 1: Check working agents for turn limits and random errors
-    for (id, (state, vibe, stats)) in world
-        .query_mut::<hecs::With<(&AgentState, &mut AgentVibeConfig, &AgentStats), &Agent>>()
-    {
+    // Phase 1: Working agents take their turns
+    let mut recovered: Vec<hecs::Entity> = Vec::new();
+    for (id, (state, vibe, stats, morale, recovery)) in world.query_mut::<hecs::With<
+        (&AgentState, &mut AgentVibeConfig, &AgentStats, Option<&AgentMorale>, Option<&mut ErrorRecovery>),
+        &Agent,
+    >>() {
         match state.state {
-            AgentStateKind::Building => {
-                // Building agents run a real Vibe CLI session.
-                // The CLI enforces its own --max-turns limit and exits naturally.
-                // We don't increment turns_used or error-check here.
-            }
-            AgentStateKind::Exploring | AgentStateKind::Defending => {
+            AgentStateKind::Building | AgentStateKind::Exploring | AgentStateKind::Defending => {
+                if !turn_due || in_session.contains(&id) {
+                    continue;
+                }
+                if economy.balance - token_drain < vibe.token_burn_rate {
+                    outcomes.push((id, TurnOutcome::Paused));
+                    continue;
+                }
                 vibe.turns_used += 1;
+                token_drain += vibe.token_burn_rate;
 
                 // Check turn limit
                 if vibe.turns_used >= vibe.max_turns {
-                    to_error.push(id);
+                    outcomes.push((id, TurnOutcome::ContextExhausted));
                     continue;
                 }
 
                 // Random error check
                 let turn_ratio = vibe.turns_used as f32 / vibe.max_turns as f32;
-                let error_chance = vibe.error_chance_base * (1.0 - stats.reliability) * turn_ratio;
+                let mut error_chance = vibe.error_chance_base * (1.0 - stats.reliability) * turn_ratio;
+                if morale.is_some_and(|m| is_low_morale(m.value)) {
+                    error_chance *= LOW_MORALE_ERROR_MULTIPLIER;
+                }
+                error_chance *= 1.0 - reduction;
                 let roll: f32 = rand::random();
                 if roll < error_chance {
-                    to_error.push(id);
+                    outcomes.push((id, TurnOutcome::Errored));
                 }
             }
             AgentStateKind::Erroring => {
                 // Burn tokens while erroring
                 token_drain += vibe.token_burn_rate;
+                if let Some(recovery) = recovery {
+                    recovery.ticks_remaining = recovery.ticks_remaining.saturating_sub(1);
+                    if recovery.ticks_remaining == 0 {
+                        recovered.push(id);
+                    }
+                }
             }
             _ => {}
         }
     }
 
-    // Phase 2: Transition agents to Erroring
-    for entity in to_error {
+    // Phase 2: Apply transitions
+    for (entity, outcome) in outcomes {
+        let name = agent_name(world, entity);
+        let (next, text) = match outcome {
+            TurnOutcome::Paused => (AgentStateKind::Idle, format!("[{}] out of tokens -- pausing work", name)),
+            TurnOutcome::ContextExhausted => {
+                (AgentStateKind::Erroring, format!("[{}] context limit reached -- ERRORING", name))
+            }
+            TurnOutcome::Errored => (AgentStateKind::Erroring, format!("[{}] hit an error -- ERRORING", name)),
+        };
         if let Ok(mut state) = world.get::<&mut AgentState>(entity) {
-            state.state = AgentStateKind::Erroring;
+            state.state = next;
         }
-        if let Ok(name) = world.get::<&AgentName>(entity) {
-            log_entries.push(format!("[{}] context limit reached -- ERRORING", name.name));
+        if next == AgentStateKind::Erroring {
+            let _ = world.insert_one(entity, ErrorRecovery { ticks_remaining: ERROR_RECOVERY_TICKS });
         }
+        log_entries.push(text);
+    }
+
+    for entity in recovered {
+        reset_context(world, entity);
+        log_entries.push(format!("[{}] recovered from error", agent_name(world, entity)));
     }
 
     // Phase 3: Drain tokens from economy
-    economy.balance -= token_drain;
+    record_transaction(economy, -token_drain, "agent turns", tick);
 
     AgentTickResult { log_entries }
 }
+
+fn agent_name(world: &World, entity: hecs::Entity) -> String {
+    world
+        .get::<&AgentName>(entity)
+        .map(|n| n.name.clone())
+        .unwrap_or_else(|_| "agent".to_string())
+}
+
+/// Returns an agent to Idle with its turns reset and any error cleared.
+fn reset_context(world: &mut World, entity: hecs::Entity) {
+    if let Ok(mut state) = world.get::<&mut AgentState>(entity) {
+        state.state = AgentStateKind::Idle;
+    }
+    if let Ok(mut vibe) = world.get::<&mut AgentVibeConfig>(entity) {
+        vibe.turns_used = 0;
+    }
+    let _ = world.remove_one::<ErrorRecovery>(entity);
+}
+
+/// Records `agent`'s state, health and position as of `tick`, replacing any
+/// earlier checkpoint.
+pub fn save_checkpoint(world: &mut World, agent: hecs::Entity, tick: u64) -> Result<(), String> {
+    let checkpoint = {
+        let state = world.get::<&AgentState>(agent).map_err(|_| "Agent not found".to_string())?;
+        let health = world.get::<&Health>(agent).map_err(|_| "Agent has no health".to_string())?;
+        let pos = world.get::<&Position>(agent).map_err(|_| "Agent has no position".to_string())?;
+        AgentCheckpoint {
+            state_snapshot: (*state).clone(),
+            health_snapshot: (*health).clone(),
+            position_snapshot: (*pos).clone(),
+            saved_at_tick: tick,
+        }
+    };
+    world.insert_one(agent, checkpoint).map_err(|_| "Agent not found".to_string())
+}
+
+/// Rolls an agent back to its checkpoint: state, health and position as
+/// they were, then Idle with a fresh context. The checkpoint is kept, so
+/// the agent can be rolled back to it again.
+pub fn rollback_agent(world: &mut World, agent: hecs::Entity) -> Result<String, String> {
+    if !world.contains(agent) {
+        return Err("Agent not found".to_string());
+    }
+    let checkpoint = world
+        .get::<&AgentCheckpoint>(agent)
+        .map(|c| (*c).clone())
+        .map_err(|_| format!("{} has no checkpoint", agent_name(world, agent)))?;
+    if let Ok(mut state) = world.get::<&mut AgentState>(agent) {
+        *state = checkpoint.state_snapshot;
+    }
+    if let Ok(mut health) = world.get::<&mut Health>(agent) {
+        *health = checkpoint.health_snapshot;
+    }
+    if let Ok(mut pos) = world.get::<&mut Position>(agent) {
+        *pos = checkpoint.position_snapshot;
+    }
+    reset_context(world, agent);
+    Ok(format!("[{}] rolled back to tick {}", agent_name(world, agent), checkpoint.saved_at_tick))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn economy(balance: i64) -> TokenEconomy {
+        TokenEconomy {
+            balance,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        }
+    }
+
+    fn spawn_agent(world: &mut World, state: AgentStateKind, error_chance_base: f32) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentName { name: "ada".to_string() },
+            AgentState { state },
+            Position { x: 0.0, y: 0.0 },
+            AgentStats { reliability: 0.0, speed: 1.0, awareness: 1.0, resilience: 1.0 },
+            AgentVibeConfig {
+                model_id: String::new(),
+                model_lore_name: String::new(),
+                vibe_agent_name: String::new(),
+                max_turns: 5,
+                turns_used: 0,
+                context_window: 32000,
+                token_burn_rate: 3,
+                error_chance_base,
+                stars: 1,
+            },
+        ))
+    }
+
+    fn state(world: &World, agent: hecs::Entity) -> AgentStateKind {
+        world.get::<&AgentState>(agent).unwrap().state
+    }
+
+    fn turns_used(world: &World, agent: hecs::Entity) -> u32 {
+        world.get::<&AgentVibeConfig>(agent).unwrap().turns_used
+    }
+
+    #[test]
+    fn building_agent_takes_a_turn_each_interval() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.0);
+        let mut economy = economy(100);
+        let none = HashSet::new();
+
+        agent_tick_system(&mut world, &mut economy, 1, &none);
+        assert_eq!(turns_used(&world, agent), 0);
+        assert_eq!(economy.balance, 100);
+
+        agent_tick_system(&mut world, &mut economy, TURN_INTERVAL_TICKS, &none);
+        assert_eq!(turns_used(&world, agent), 1);
+        assert_eq!(economy.balance, 97);
+        assert_eq!(state(&world, agent), AgentStateKind::Building);
+    }
+
+    #[test]
+    fn agents_in_a_vibe_session_are_left_alone() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.0);
+        let mut economy = economy(100);
+
+        agent_tick_system(&mut world, &mut economy, 0, &HashSet::from([agent]));
+        assert_eq!(turns_used(&world, agent), 0);
+        assert_eq!(economy.balance, 100);
+    }
+
+    #[test]
+    fn agent_pauses_when_the_turn_is_unaffordable() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.0);
+        let mut economy = economy(2);
+
+        let result = agent_tick_system(&mut world, &mut economy, 0, &HashSet::new());
+        assert_eq!(state(&world, agent), AgentStateKind::Idle);
+        assert_eq!(turns_used(&world, agent), 0);
+        assert_eq!(economy.balance, 2);
+        assert_eq!(result.log_entries, vec!["[ada] out of tokens -- pausing work".to_string()]);
+    }
+
+    #[test]
+    fn exhausting_the_context_starts_erroring() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Exploring, 0.0);
+        world.get::<&mut AgentVibeConfig>(agent).unwrap().turns_used = 4;
+        let mut economy = economy(100);
+
+        let result = agent_tick_system(&mut world, &mut economy, 0, &HashSet::new());
+        assert_eq!(state(&world, agent), AgentStateKind::Erroring);
+        assert_eq!(world.get::<&ErrorRecovery>(agent).unwrap().ticks_remaining, ERROR_RECOVERY_TICKS);
+        assert!(result.log_entries[0].contains("context limit reached"));
+    }
+
+    #[test]
+    fn failed_error_roll_starts_erroring() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Defending, 100.0);
+        let mut economy = economy(100);
+
+        let result = agent_tick_system(&mut world, &mut economy, 0, &HashSet::new());
+        assert_eq!(state(&world, agent), AgentStateKind::Erroring);
+        assert!(result.log_entries[0].contains("hit an error"));
+    }
+
+    #[test]
+    fn error_rate_reduction_buildings_prevent_errors() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 100.0);
+        world.spawn((Building, BuildingEffects { effects: vec![BuildingEffect::ErrorRateReduction(1.0)] }));
+        let mut economy = economy(100);
+
+        agent_tick_system(&mut world, &mut economy, 0, &HashSet::new());
+        assert_eq!(state(&world, agent), AgentStateKind::Building);
+    }
+
+    #[test]
+    fn erroring_agent_burns_tokens_then_recovers() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Erroring, 0.0);
+        world.get::<&mut AgentVibeConfig>(agent).unwrap().turns_used = 5;
+        let mut economy = economy(10_000);
+        let none = HashSet::new();
+
+        for tick in 1..ERROR_RECOVERY_TICKS as u64 {
+            agent_tick_system(&mut world, &mut economy, tick, &none);
+        }
+        assert_eq!(state(&world, agent), AgentStateKind::Erroring);
+        assert_eq!(economy.balance, 10_000 - 3 * (ERROR_RECOVERY_TICKS as i64 - 1));
+
+        let result = agent_tick_system(&mut world, &mut economy, 0, &none);
+        assert_eq!(state(&world, agent), AgentStateKind::Idle);
+        assert_eq!(turns_used(&world, agent), 0);
+        assert!(world.get::<&ErrorRecovery>(agent).is_err());
+        assert_eq!(result.log_entries, vec!["[ada] recovered from error".to_string()]);
+    }
+
+    #[test]
+    fn rollback_restores_the_checkpoint() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.0);
+        world.insert_one(agent, Health { current: 80, max: 100 }).unwrap();
+        assert_eq!(rollback_agent(&mut world, agent), Err("ada has no checkpoint".to_string()));
+
+        world.get::<&mut Position>(agent).unwrap().x = 50.0;
+        save_checkpoint(&mut world, agent, 50).unwrap();
+
+        // Fifty ticks of a session gone wrong.
+        *world.get::<&mut Position>(agent).unwrap() = Position { x: 100.0, y: 40.0 };
+        world.get::<&mut Health>(agent).unwrap().current = 20;
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Erroring;
+        world.insert_one(agent, ErrorRecovery { ticks_remaining: 10 }).unwrap();
+        world.get::<&mut AgentVibeConfig>(agent).unwrap().turns_used = 5;
+
+        assert_eq!(rollback_agent(&mut world, agent), Ok("[ada] rolled back to tick 50".to_string()));
+        let pos = world.get::<&Position>(agent).unwrap().clone();
+        assert_eq!((pos.x, pos.y), (50.0, 0.0));
+        assert_eq!(world.get::<&Health>(agent).unwrap().current, 80);
+        assert_eq!(state(&world, agent), AgentStateKind::Idle);
+        assert_eq!(turns_used(&world, agent), 0);
+        assert!(world.get::<&ErrorRecovery>(agent).is_err());
+        assert_eq!(world.get::<&AgentCheckpoint>(agent).unwrap().saved_at_tick, 50);
+    }
+}
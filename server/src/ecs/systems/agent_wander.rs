@@ -1,6 +1,8 @@
 use hecs::World;
 
-use crate::ecs::components::{Agent, AgentState, AgentStats, Position, Velocity, WanderState};
+use crate::ecs::components::{Agent, AgentName, AgentState, AgentStats, Position, Velocity, WanderState, WheelPost};
+use crate::ecs::systems::crank::WHEEL_WANDER_RADIUS;
+use crate::game::collision;
 use crate::protocol::AgentStateKind;
 
 /// Base wander speed multiplier. Effective speed = BASE_WANDER_SPEED * agent.speed.
@@ -10,7 +12,11 @@ const BASE_WANDER_SPEED: f32 = 0.4;
 const WAYPOINT_THRESHOLD: f32 = 2.0;
 
 /// Distance threshold for Walking agents to be considered "arrived" at building.
-const BUILDING_ARRIVAL_THRESHOLD: f32 = 48.0;
+const BUILDING_ARRIVAL_THRESHOLD: f32 = 16.0;
+
+/// Ticks a Walking agent may go without getting closer to its target before
+/// giving up (10 seconds at 20Hz).
+pub const WALK_TIMEOUT_TICKS: u32 = 200;
 
 /// Minimum pause ticks at waypoint (1 second at 20Hz).
 const MIN_PAUSE_TICKS: u32 = 20;
@@ -18,14 +24,42 @@ const MIN_PAUSE_TICKS: u32 = 20;
 /// Maximum pause ticks at waypoint (3 seconds at 20Hz).
 const MAX_PAUSE_TICKS: u32 = 60;
 
+/// Result returned by [`agent_wander_system`] each tick.
+pub struct AgentWanderResult {
+    pub log_entries: Vec<String>,
+}
+
+/// Moves `entity` by (vx, vy), checking each axis against terrain
+/// walkability independently so the agent slides along water and cliffs
+/// the same way the player does.
+fn step_walkable(world: &World, entity: hecs::Entity, vx: f32, vy: f32) {
+    let Ok(mut pos) = world.get::<&mut Position>(entity) else { return };
+
+    let future_tx = collision::pixel_to_tile(pos.x + vx);
+    let cur_ty = collision::pixel_to_tile(pos.y);
+    if collision::is_walkable(future_tx, cur_ty) {
+        pos.x += vx;
+    }
+
+    let cur_tx = collision::pixel_to_tile(pos.x);
+    let future_ty = collision::pixel_to_tile(pos.y + vy);
+    if collision::is_walkable(cur_tx, future_ty) {
+        pos.y += vy;
+    }
+}
+
 /// Runs the agent wander system for a single tick.
 ///
 /// Processes agents in Idle, Building, or Walking states.
-/// - Walking agents move directly toward their walk_target with no pausing.
-///   When they arrive (within BUILDING_ARRIVAL_THRESHOLD), they transition to
-///   Building state with reduced wander radius.
+/// - Walking agents move toward their walk_target with no pausing, blocked
+///   by unwalkable terrain. When they arrive (within
+///   BUILDING_ARRIVAL_THRESHOLD), they transition to Building state with
+///   reduced wander radius (agents posted at the wheel are pinned to it
+///   instead). After WALK_TIMEOUT_TICKS without getting closer
+///   they give up and go Idle.
 /// - Idle/Building agents wander randomly around their home position with pauses.
-pub fn agent_wander_system(world: &mut World) {
+pub fn agent_wander_system(world: &mut World) -> AgentWanderResult {
+    let mut log_entries = Vec::new();
     // Collect agents that should move
     let moveable_agents: Vec<(hecs::Entity, f32, AgentStateKind)> = world
         .query::<(&Agent, &AgentState, &AgentStats)>()
@@ -37,12 +71,14 @@ pub fn agent_wander_system(world: &mut World) {
         .collect();
 
     let mut arrivals: Vec<hecs::Entity> = Vec::new();
+    let mut stranded: Vec<hecs::Entity> = Vec::new();
 
     for (entity, speed, agent_state) in moveable_agents {
         // Walking agents: move directly toward walk_target, no pausing
         if agent_state == AgentStateKind::Walking {
             let Ok(wander) = world.get::<&WanderState>(entity) else { continue; };
             let Some((tx, ty)) = wander.walk_target else { continue; };
+            let timed_out = wander.walk_ticks >= WALK_TIMEOUT_TICKS;
             drop(wander);
 
             let Ok(pos) = world.get::<&Position>(entity) else { continue; };
@@ -53,6 +89,8 @@ pub fn agent_wander_system(world: &mut World) {
 
             if dist < BUILDING_ARRIVAL_THRESHOLD {
                 arrivals.push(entity);
+            } else if timed_out {
+                stranded.push(entity);
             } else {
                 let walk_speed = BASE_WANDER_SPEED * speed;
                 let nx = dx / dist;
@@ -64,9 +102,19 @@ pub fn agent_wander_system(world: &mut World) {
                     vel.x = vx;
                     vel.y = vy;
                 }
-                if let Ok(mut pos) = world.get::<&mut Position>(entity) {
-                    pos.x += vx;
-                    pos.y += vy;
+                step_walkable(world, entity, vx, vy);
+
+                // Track ticks without progress so unreachable targets time out.
+                let new_dist = world
+                    .get::<&Position>(entity)
+                    .map(|p| ((tx - p.x).powi(2) + (ty - p.y).powi(2)).sqrt())
+                    .unwrap_or(dist);
+                if let Ok(mut wander) = world.get::<&mut WanderState>(entity) {
+                    if new_dist < dist - 0.01 {
+                        wander.walk_ticks = 0;
+                    } else {
+                        wander.walk_ticks += 1;
+                    }
                 }
             }
             continue;
@@ -153,20 +201,59 @@ pub fn agent_wander_system(world: &mut World) {
         // Set home to the agent's current position (a couple blocks from building)
         // so they wander near where they stopped, not on top of the building.
         let stopped_pos = world.get::<&Position>(entity).ok().map(|p| (p.x, p.y));
+        let at_wheel = world.satisfies::<&WheelPost>(entity).unwrap_or(false);
+        if let Ok(mut wander) = world.get::<&mut WanderState>(entity) {
+            if at_wheel {
+                if let Some((tx, ty)) = wander.walk_target {
+                    wander.home_x = tx;
+                    wander.home_y = ty;
+                }
+                wander.wander_radius = WHEEL_WANDER_RADIUS;
+            } else {
+                if let Some((sx, sy)) = stopped_pos {
+                    wander.home_x = sx;
+                    wander.home_y = sy;
+                }
+                wander.wander_radius = 20.0;
+            }
+            wander.walk_target = None;
+            wander.walk_ticks = 0;
+            wander.pause_remaining = 0;
+        }
+        if let Ok(mut vel) = world.get::<&mut Velocity>(entity) {
+            vel.x = 0.0;
+            vel.y = 0.0;
+        }
+    }
+
+    // Walkers that never arrived give up and idle where they stand
+    for entity in stranded {
+        if let Ok(mut state) = world.get::<&mut AgentState>(entity) {
+            state.state = AgentStateKind::Idle;
+        }
+        let stopped_pos = world.get::<&Position>(entity).ok().map(|p| (p.x, p.y));
         if let Ok(mut wander) = world.get::<&mut WanderState>(entity) {
             if let Some((sx, sy)) = stopped_pos {
                 wander.home_x = sx;
                 wander.home_y = sy;
+                wander.waypoint_x = sx;
+                wander.waypoint_y = sy;
             }
-            wander.wander_radius = 20.0;
             wander.walk_target = None;
-            wander.pause_remaining = 0;
+            wander.walk_ticks = 0;
         }
         if let Ok(mut vel) = world.get::<&mut Velocity>(entity) {
             vel.x = 0.0;
             vel.y = 0.0;
         }
+        let name = world
+            .get::<&AgentName>(entity)
+            .map(|n| n.name.clone())
+            .unwrap_or_else(|_| "an agent".to_string());
+        log_entries.push(format!("{} couldn't find a way to the building and gave up", name));
     }
+
+    AgentWanderResult { log_entries }
 }
 
 #[cfg(test)]
@@ -200,6 +287,7 @@ mod tests {
                 pause_remaining: 0,
                 wander_radius: 120.0,
                 walk_target: None,
+                walk_ticks: 0,
             },
         ))
     }
@@ -261,6 +349,7 @@ mod tests {
                 pause_remaining: 0,
                 wander_radius: 120.0,
                 walk_target: None,
+                walk_ticks: 0,
             },
         ));
 
@@ -341,6 +430,7 @@ mod tests {
                 pause_remaining: 0,
                 wander_radius: 120.0,
                 walk_target: Some((500.0, 100.0)),
+                walk_ticks: 0,
             },
         ));
 
@@ -374,6 +464,7 @@ mod tests {
                 pause_remaining: 0,
                 wander_radius: 120.0,
                 walk_target: Some((500.0, 100.0)),
+                walk_ticks: 0,
             },
         ));
 
@@ -387,4 +478,65 @@ mod tests {
         assert_eq!(wander.home_x, 490.0, "home should be agent's stopped position");
         assert_eq!(wander.wander_radius, 20.0, "wander_radius should be reduced");
     }
+
+    /// Helper: spawn a Walking agent at (x, y) heading for `target`.
+    fn spawn_walking_agent(world: &mut World, x: f32, y: f32, target: (f32, f32)) -> hecs::Entity {
+        let entity = spawn_idle_agent(world, x, y, 1.0);
+        world.get::<&mut AgentState>(entity).unwrap().state = AgentStateKind::Walking;
+        world.get::<&mut WanderState>(entity).unwrap().walk_target = Some(target);
+        entity
+    }
+
+    #[test]
+    fn walking_agent_keeps_walking_until_close() {
+        let mut world = World::new();
+        let (x, y) = find_open_row();
+        let entity = spawn_walking_agent(&mut world, x, y, (x + 40.0, y));
+
+        agent_wander_system(&mut world);
+        assert_eq!(world.get::<&AgentState>(entity).unwrap().state, AgentStateKind::Walking);
+
+        for _ in 0..200 {
+            agent_wander_system(&mut world);
+        }
+        assert_eq!(world.get::<&AgentState>(entity).unwrap().state, AgentStateKind::Building);
+        let pos = world.get::<&Position>(entity).unwrap();
+        assert!(x + 40.0 - pos.x < BUILDING_ARRIVAL_THRESHOLD);
+    }
+
+    /// Centre of a walkable tile with a walkable run of tiles to its right.
+    fn find_open_row() -> (f32, f32) {
+        (0..400)
+            .flat_map(|ty| (0..400).map(move |tx| (tx, ty)))
+            .find(|&(tx, ty)| (0..5).all(|i| collision::is_walkable(tx + i, ty)))
+            .map(|(tx, ty)| (collision::tile_center(tx), collision::tile_center(ty)))
+            .expect("some open row")
+    }
+
+    #[test]
+    fn water_blocks_walking_agent_until_it_gives_up() {
+        // A walkable tile directly left of an unwalkable one.
+        let (tx, ty) = (0..400)
+            .flat_map(|ty| (1..400).map(move |tx| (tx, ty)))
+            .find(|&(tx, ty)| collision::is_walkable(tx - 1, ty) && !collision::is_walkable(tx, ty))
+            .expect("some shoreline");
+        let (x, y) = (collision::tile_center(tx - 1), collision::tile_center(ty));
+        let target = (collision::tile_center(tx + 10), y);
+
+        let mut world = World::new();
+        let entity = spawn_walking_agent(&mut world, x, y, target);
+        world.insert_one(entity, AgentName { name: "ada".to_string() }).unwrap();
+
+        let blocked_edge = collision::tile_center(tx) - 8.0;
+        let mut logs = Vec::new();
+        for _ in 0..WALK_TIMEOUT_TICKS + 20 {
+            logs.extend(agent_wander_system(&mut world).log_entries);
+            assert!(world.get::<&Position>(entity).unwrap().x < blocked_edge, "walked into water");
+        }
+
+        assert_eq!(world.get::<&AgentState>(entity).unwrap().state, AgentStateKind::Idle);
+        assert!(world.get::<&WanderState>(entity).unwrap().walk_target.is_none());
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with("ada"));
+    }
 }
@@ -1,9 +1,16 @@
+use std::collections::HashMap;
+
 use hecs::World;
 
 use crate::ecs::components::{
-    Agent, AgentState, AgentStats, Assignment, Building, BuildingType, ConstructionProgress,
+    Agent, AgentMorale, AgentPersonality, AgentState, AgentStats, Assignment, Building, BuildingType, ConstructionProgress,
+    Health, Position, Specialization,
 };
-use crate::protocol::{AgentStateKind, BuildingTypeKind, TaskAssignment};
+use crate::ecs::systems::morale::{is_low_morale, LOW_MORALE_BUILD_SPEED};
+use crate::game::agents::{trait_bonuses, BUILDER_BUILD_SPEED_MULT};
+use crate::game::upgrades::UpgradeState;
+use crate::project::ProjectManager;
+use crate::protocol::{AgentSpecialization, AgentStateKind, AudioEvent, BuildingTypeKind, TaskAssignment};
 
 /// The result of running the building construction system for one tick.
 pub struct BuildingSystemResult {
@@ -11,99 +18,472 @@ pub struct BuildingSystemResult {
     pub completed_buildings: Vec<(hecs::Entity, BuildingTypeKind)>,
     /// Log messages generated (e.g. construction-complete announcements).
     pub log_entries: Vec<String>,
+    /// A `BuildComplete` for each completed building.
+    pub audio_events: Vec<AudioEvent>,
+}
+
+/// Distance (pixels) within which an assigned agent contributes to construction.
+pub const BUILD_RANGE: f32 = 40.0;
+
+/// Health an on-site crew restores to a damaged, completed building per tick.
+pub const MAINTENANCE_HP_PER_TICK: i32 = 1;
+
+/// Weight of each successive agent on one building, strongest first; agents
+/// beyond the last entry use the last weight.
+pub const STACKING_WEIGHTS: [f32; 3] = [1.0, 0.75, 0.5];
+
+/// Bounds for a building's construction priority weight.
+pub const MIN_BUILD_PRIORITY: f32 = 0.1;
+pub const MAX_BUILD_PRIORITY: f32 = 5.0;
+
+/// Set the construction priority of `building`, clamped to
+/// [`MIN_BUILD_PRIORITY`, `MAX_BUILD_PRIORITY`]. Returns the value applied.
+///
+/// # Errors
+///
+/// Returns an error if the entity is not a building under construction.
+pub fn set_build_priority(world: &mut World, building: hecs::Entity, priority: f32) -> Result<f32, String> {
+    if !priority.is_finite() {
+        return Err(format!("invalid priority {}", priority));
+    }
+    let mut progress = world
+        .get::<&mut ConstructionProgress>(building)
+        .map_err(|_| "Entity is not a building".to_string())?;
+    progress.priority_weight = priority.clamp(MIN_BUILD_PRIORITY, MAX_BUILD_PRIORITY);
+    Ok(progress.priority_weight)
+}
+
+/// The agents listed in `assigned` plus those assigned to the project for
+/// `kind` in `agent_assignments`, without duplicates.
+fn crew_of(
+    assigned: &[hecs::Entity],
+    kind: BuildingTypeKind,
+    agent_assignments: &HashMap<String, Vec<u64>>,
+) -> Vec<hecs::Entity> {
+    let mut crew = assigned.to_vec();
+    let project_agents = ProjectManager::building_type_to_id(&format!("{:?}", kind))
+        .and_then(|id| agent_assignments.get(&id))
+        .into_iter()
+        .flatten()
+        .filter_map(|&id| hecs::Entity::from_bits(id));
+    for agent in project_agents {
+        if !crew.contains(&agent) {
+            crew.push(agent);
+        }
+    }
+    crew
 }
 
 /// Runs the building construction system for a single tick.
 ///
-/// Finds all agents in the `Building` state with a `Build` task assignment,
-/// sums their construction speed, and distributes that speed equally among all
-/// incomplete buildings.  When a building reaches its target construction
-/// points it is marked complete.
-pub fn building_system(world: &mut World) -> BuildingSystemResult {
+/// Each incomplete building is built only by its own crew: agents listed in
+/// its `ConstructionProgress::assigned_agents` or assigned to its project in
+/// `agent_assignments`, that are in the `Building` state with a `Build` task
+/// and within [`BUILD_RANGE`] of it.  Crew speeds (halved for low-morale
+/// agents, raised by [`BUILDER_BUILD_SPEED_MULT`] for Builders and by
+/// `DILIGENT_BUILD_SPEED_MULT` for diligent ones) stack with diminishing returns per [`STACKING_WEIGHTS`].  The
+/// combined output of every crew is then shared out in proportion to each
+/// site's crew output times its `priority_weight`, so with equal priorities
+/// every site keeps its own crew's output.  When a building reaches its
+/// target construction points it is marked complete.  Purchased upgrades may
+/// scale build speed.
+pub fn building_system(
+    world: &mut World,
+    upgrades: &UpgradeState,
+    agent_assignments: &HashMap<String, Vec<u64>>,
+) -> BuildingSystemResult {
     let mut completed_buildings: Vec<(hecs::Entity, BuildingTypeKind)> = Vec::new();
     let mut log_entries: Vec<String> = Vec::new();
 
-    // ── Gather total build power from qualifying agents ───────────
-    let mut total_build_speed: f32 = 0.0;
-    let mut builder_count: u32 = 0;
-
-    for (_entity, (_agent, agent_state, agent_stats, assignment)) in world
-        .query::<(&Agent, &AgentState, &AgentStats, &Assignment)>()
+    // ── Gather incomplete buildings and their crews ───────────────
+    let sites: Vec<(hecs::Entity, f32, f32, f32, Vec<hecs::Entity>)> = world
+        .query::<hecs::With<(&Position, &BuildingType, &ConstructionProgress), &Building>>()
         .iter()
-    {
-        if agent_state.state == AgentStateKind::Building
-            && assignment.task == TaskAssignment::Build
-        {
-            total_build_speed += agent_stats.speed;
-            builder_count += 1;
+        .filter(|(_e, (_pos, _bt, progress))| progress.current < progress.total)
+        .map(|(e, (pos, bt, progress))| {
+            let crew = crew_of(&progress.assigned_agents, bt.kind, agent_assignments);
+            (e, pos.x, pos.y, progress.priority_weight, crew)
+        })
+        .collect();
+
+    let multiplier = upgrades.build_speed_multiplier();
+
+    // ── Each site's crew output (speeds stacked) ──────────────────
+    let mut demands: Vec<(hecs::Entity, f32, f32)> = Vec::new();
+    for (entity, bx, by, priority, crew) in sites {
+        // ── Sum the speed of crew members on site ─────────────────
+        let mut speeds: Vec<f32> = crew
+            .iter()
+            .filter_map(|&agent| {
+                let mut query = world
+                    .query_one::<hecs::With<
+                        (
+                            &AgentState,
+                            &AgentStats,
+                            &Assignment,
+                            &Position,
+                            Option<&AgentMorale>,
+                            Option<&Specialization>,
+                            Option<&AgentPersonality>,
+                        ),
+                        &Agent,
+                    >>(agent)
+                    .ok()?;
+                let (state, stats, assignment, pos, morale, specialization, personality) = query.get()?;
+                if state.state != AgentStateKind::Building || assignment.task != TaskAssignment::Build {
+                    return None;
+                }
+                let (dx, dy) = (pos.x - bx, pos.y - by);
+                if dx * dx + dy * dy > BUILD_RANGE * BUILD_RANGE {
+                    return None;
+                }
+                let morale_factor = if morale.is_some_and(|m| is_low_morale(m.value)) {
+                    LOW_MORALE_BUILD_SPEED
+                } else {
+                    1.0
+                };
+                let role_factor = if specialization.is_some_and(|s| s.role == AgentSpecialization::Builder) {
+                    BUILDER_BUILD_SPEED_MULT
+                } else {
+                    1.0
+                };
+                let trait_factor = personality.map_or(1.0, |p| trait_bonuses(&p.traits).build_speed_mult);
+                Some(stats.speed * morale_factor * role_factor * trait_factor)
+            })
+            .collect();
+        if speeds.is_empty() {
+            continue;
         }
+        speeds.sort_by(|a, b| b.total_cmp(a));
+        let build_speed: f32 = speeds
+            .iter()
+            .enumerate()
+            .map(|(i, speed)| speed * STACKING_WEIGHTS[i.min(STACKING_WEIGHTS.len() - 1)])
+            .sum();
+        demands.push((entity, build_speed, priority));
     }
 
-    // Nothing to do if nobody is building.
-    if builder_count == 0 || total_build_speed <= 0.0 {
-        return BuildingSystemResult {
-            completed_buildings,
-            log_entries,
-        };
+    // ── Share the pooled output by priority-weighted demand ───────
+    let pool: f32 = demands.iter().map(|(_e, speed, _p)| speed).sum();
+    let weighted_demand: f32 = demands.iter().map(|(_e, speed, priority)| speed * priority).sum();
+    if weighted_demand <= 0.0 {
+        return BuildingSystemResult { completed_buildings, log_entries, audio_events: Vec::new() };
     }
+    let share_per_demand = pool / weighted_demand;
 
-    // ── Count incomplete buildings ────────────────────────────────
-    let mut incomplete_count: u32 = 0;
-    for (_entity, (_building, progress)) in
-        world.query::<(&Building, &ConstructionProgress)>().iter()
-    {
+    for (entity, build_speed, priority) in demands {
+        let Ok(mut progress) = world.get::<&mut ConstructionProgress>(entity) else {
+            continue;
+        };
+        progress.current += build_speed * priority * share_per_demand * multiplier;
         if progress.current < progress.total {
-            incomplete_count += 1;
+            continue;
         }
-    }
+        progress.current = progress.total;
+        drop(progress);
 
-    if incomplete_count == 0 {
-        return BuildingSystemResult {
-            completed_buildings,
-            log_entries,
+        let Ok(kind) = world.get::<&BuildingType>(entity).map(|bt| bt.kind) else {
+            continue;
         };
+        completed_buildings.push((entity, kind));
+        log_entries.push(format!("{:?} construction complete!", kind));
     }
 
-    // ── Distribute build power equally among incomplete buildings ─
-    let speed_per_building = total_build_speed / incomplete_count as f32;
+    let audio_events = completed_buildings.iter().map(|_| AudioEvent::BuildComplete).collect();
+    BuildingSystemResult {
+        completed_buildings,
+        log_entries,
+        audio_events,
+    }
+}
 
-    // Collect entities to update (we cannot mutate while iterating with
-    // a query that borrows the world, so gather first, mutate second).
-    let targets: Vec<hecs::Entity> = world
-        .query::<(&Building, &ConstructionProgress)>()
+/// The result of running the building regeneration system for one tick.
+pub struct BuildingRegenResult {
+    /// Log messages generated (e.g. fully-repaired announcements).
+    pub log_entries: Vec<String>,
+}
+
+/// Runs the building regeneration system for a single tick.
+///
+/// Every completed, damaged building with a crew member on site (an agent
+/// from its crew, as in [`building_system`], in the `Building` state and
+/// within [`BUILD_RANGE`]) regains [`MAINTENANCE_HP_PER_TICK`], capped at
+/// its maximum. Buildings at zero health are gone and are not maintained.
+pub fn building_regen_system(
+    world: &mut World,
+    agent_assignments: &HashMap<String, Vec<u64>>,
+) -> BuildingRegenResult {
+    let mut log_entries: Vec<String> = Vec::new();
+
+    let on_duty: HashMap<hecs::Entity, (f32, f32)> = world
+        .query::<hecs::With<(&AgentState, &Position), &Agent>>()
         .iter()
-        .filter(|(_entity, (_building, progress))| progress.current < progress.total)
-        .map(|(entity, _)| entity)
+        .filter(|(_e, (state, _pos))| state.state == AgentStateKind::Building)
+        .map(|(e, (_state, pos))| (e, (pos.x, pos.y)))
         .collect();
 
-    for entity in targets {
-        // Fetch mutable components for this entity.
-        let (progress, building_type) = match world
-            .query_one::<(&mut ConstructionProgress, &BuildingType)>(entity)
-        {
-            Ok(mut q) => match q.get() {
-                Some((p, bt)) => {
-                    let was_incomplete = p.current < p.total;
-                    p.current += speed_per_building;
-                    let now_complete = p.current >= p.total;
-                    if now_complete {
-                        p.current = p.total;
-                    }
-                    (was_incomplete && now_complete, bt.kind)
-                }
-                None => continue,
-            },
-            Err(_) => continue,
-        };
+    for (_entity, (_building, building_type, pos, health, progress)) in world.query_mut::<(
+        &Building,
+        &BuildingType,
+        &Position,
+        &mut Health,
+        Option<&ConstructionProgress>,
+    )>() {
+        if health.current <= 0 || health.current >= health.max {
+            continue;
+        }
+        if progress.is_some_and(|p| p.current < p.total) {
+            continue;
+        }
 
-        if progress {
-            completed_buildings.push((entity, building_type));
-            log_entries.push(format!("{:?} construction complete!", building_type));
+        let assigned = progress.map(|p| p.assigned_agents.as_slice()).unwrap_or(&[]);
+        let staffed = crew_of(assigned, building_type.kind, agent_assignments).iter().any(|agent| {
+            on_duty.get(agent).is_some_and(|&(ax, ay)| {
+                (ax - pos.x).powi(2) + (ay - pos.y).powi(2) <= BUILD_RANGE * BUILD_RANGE
+            })
+        });
+        if !staffed {
+            continue;
+        }
+
+        health.current = (health.current + MAINTENANCE_HP_PER_TICK).min(health.max);
+        if health.current == health.max {
+            log_entries.push(format!("{:?} fully repaired", building_type.kind));
         }
     }
 
-    BuildingSystemResult {
-        completed_buildings,
-        log_entries,
+    BuildingRegenResult { log_entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::upgrades::{UpgradeId, FILE_SYSTEM_BUILD_SPEED_MULT};
+
+    fn spawn_builder(world: &mut World, x: f32, speed: f32) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Building },
+            AgentStats { reliability: 1.0, speed, awareness: 0.0, resilience: 0.0 },
+            Assignment { task: TaskAssignment::Build },
+            Position { x, y: 0.0 },
+        ))
+    }
+
+    fn spawn_site(world: &mut World, kind: BuildingTypeKind, x: f32, crew: Vec<hecs::Entity>) -> hecs::Entity {
+        world.spawn((
+            Building,
+            BuildingType { kind },
+            Position { x, y: 0.0 },
+            ConstructionProgress { current: 0.0, total: 100.0, assigned_agents: crew, priority_weight: 1.0 },
+        ))
+    }
+
+    /// One agent standing next to a TodoApp it is assigned to.
+    fn setup() -> (World, hecs::Entity) {
+        let mut world = World::new();
+        let agent = spawn_builder(&mut world, 10.0, 1.0);
+        let building = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, vec![agent]);
+        (world, building)
+    }
+
+    fn progress(world: &World, e: hecs::Entity) -> f32 {
+        world.get::<&ConstructionProgress>(e).unwrap().current
+    }
+
+    #[test]
+    fn file_system_access_speeds_up_construction() {
+        let (mut world, building) = setup();
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        let before = progress(&world, building);
+
+        let (mut world, building) = setup();
+        let mut upgrades = UpgradeState::new();
+        upgrades.purchased.insert(UpgradeId::FileSystemAccess);
+        building_system(&mut world, &upgrades, &HashMap::new());
+        let after = progress(&world, building);
+
+        assert_eq!(before, 1.0);
+        assert!((after - before * FILE_SYSTEM_BUILD_SPEED_MULT).abs() < 1e-6);
+    }
+
+    #[test]
+    fn completing_a_building_plays_build_complete() {
+        let (mut world, building) = setup();
+        world.get::<&mut ConstructionProgress>(building).unwrap().current = 99.5;
+
+        let result = building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(result.completed_buildings, vec![(building, BuildingTypeKind::TodoApp)]);
+        assert!(matches!(result.audio_events[..], [AudioEvent::BuildComplete]));
+
+        let result = building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert!(result.audio_events.is_empty());
+    }
+
+    #[test]
+    fn builders_build_faster() {
+        let mut world = World::new();
+        let agent = spawn_builder(&mut world, 10.0, 1.0);
+        world.insert_one(agent, Specialization { role: AgentSpecialization::Builder }).unwrap();
+        let building = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, vec![agent]);
+
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert!((progress(&world, building) - BUILDER_BUILD_SPEED_MULT).abs() < 1e-6);
+    }
+
+    #[test]
+    fn low_morale_slows_construction() {
+        let (mut world, building) = setup();
+        let agent = world.query::<&Agent>().iter().next().unwrap().0;
+        world.insert_one(agent, AgentMorale { value: 0.3 }).unwrap();
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, building), 1.0);
+
+        world.get::<&mut AgentMorale>(agent).unwrap().value = 0.29;
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, building), 1.0 + LOW_MORALE_BUILD_SPEED);
+    }
+
+    #[test]
+    fn agents_only_build_their_own_building() {
+        let mut world = World::new();
+        let agent = spawn_builder(&mut world, 10.0, 1.0);
+        let mine = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, vec![agent]);
+        // Right next to the agent, but it isn't assigned there.
+        let other = spawn_site(&mut world, BuildingTypeKind::Blockchain, 20.0, Vec::new());
+
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, mine), 1.0);
+        assert_eq!(progress(&world, other), 0.0);
+    }
+
+    #[test]
+    fn assigned_agents_must_be_on_site() {
+        let mut world = World::new();
+        let agent = spawn_builder(&mut world, BUILD_RANGE + 1.0, 1.0);
+        let building = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, vec![agent]);
+
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, building), 0.0);
+
+        world.get::<&mut Position>(agent).unwrap().x = BUILD_RANGE;
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, building), 1.0);
+    }
+
+    #[test]
+    fn project_assignments_count_as_crew() {
+        let mut world = World::new();
+        let agent = spawn_builder(&mut world, 0.0, 1.0);
+        let building = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, Vec::new());
+        let assignments = HashMap::from([("todo_app".to_string(), vec![agent.to_bits().get()])]);
+
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, building), 0.0);
+        building_system(&mut world, &UpgradeState::new(), &assignments);
+        assert_eq!(progress(&world, building), 1.0);
+    }
+
+    #[test]
+    fn crews_stack_with_diminishing_returns() {
+        let mut world = World::new();
+        let crew: Vec<hecs::Entity> = (0..4).map(|i| spawn_builder(&mut world, i as f32, 1.0)).collect();
+        let building = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, crew);
+
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        assert_eq!(progress(&world, building), 1.0 + 0.75 + 0.5 + 0.5);
+    }
+
+    #[test]
+    fn priority_weights_the_share_of_build_ticks() {
+        let mut world = World::new();
+        let a = spawn_builder(&mut world, 0.0, 1.0);
+        let b = spawn_builder(&mut world, 500.0, 1.0);
+        let normal = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, vec![a]);
+        let urgent = spawn_site(&mut world, BuildingTypeKind::Calculator, 500.0, vec![b]);
+        assert_eq!(set_build_priority(&mut world, urgent, 2.0), Ok(2.0));
+
+        building_system(&mut world, &UpgradeState::new(), &HashMap::new());
+        let (normal, urgent) = (progress(&world, normal), progress(&world, urgent));
+        assert!((urgent - 2.0 * normal).abs() < 1e-6);
+        // The pool itself is unchanged: one tick from each agent.
+        assert!((urgent + normal - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn priority_is_clamped() {
+        let mut world = World::new();
+        let site = spawn_site(&mut world, BuildingTypeKind::TodoApp, 0.0, Vec::new());
+        assert_eq!(set_build_priority(&mut world, site, 50.0), Ok(MAX_BUILD_PRIORITY));
+        assert_eq!(set_build_priority(&mut world, site, 0.0), Ok(MIN_BUILD_PRIORITY));
+        assert!(set_build_priority(&mut world, site, f32::NAN).is_err());
+        let agent = spawn_builder(&mut world, 0.0, 1.0);
+        assert!(set_build_priority(&mut world, agent, 1.0).is_err());
+    }
+
+    fn damaged_todo_app(world: &mut World, current: i32) -> hecs::Entity {
+        world.spawn((
+            Building,
+            BuildingType { kind: BuildingTypeKind::TodoApp },
+            Position { x: 0.0, y: 0.0 },
+            Health { current, max: 100 },
+            ConstructionProgress { current: 100.0, total: 100.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ))
+    }
+
+    #[test]
+    fn staffed_building_regenerates_to_max() {
+        let mut world = World::new();
+        let building = damaged_todo_app(&mut world, 50);
+        let agent = spawn_builder(&mut world, 10.0, 1.0);
+        let assignments = HashMap::from([("todo_app".to_string(), vec![agent.to_bits().into()])]);
+
+        let mut repaired_logs = 0;
+        for _ in 0..50 {
+            repaired_logs += building_regen_system(&mut world, &assignments).log_entries.len();
+        }
+        assert_eq!(world.get::<&Health>(building).unwrap().current, 100);
+        assert_eq!(repaired_logs, 1);
+
+        for _ in 0..10 {
+            building_regen_system(&mut world, &assignments);
+        }
+        assert_eq!(world.get::<&Health>(building).unwrap().current, 100);
+    }
+
+    #[test]
+    fn unstaffed_building_does_not_regenerate() {
+        let mut world = World::new();
+        let building = damaged_todo_app(&mut world, 50);
+        let assignments = HashMap::from([("todo_app".to_string(), Vec::new())]);
+
+        building_regen_system(&mut world, &assignments);
+        assert_eq!(world.get::<&Health>(building).unwrap().current, 50);
+    }
+
+    #[test]
+    fn maintenance_needs_a_crew_member_on_site() {
+        let mut world = World::new();
+        let building = damaged_todo_app(&mut world, 50);
+        let agent = spawn_builder(&mut world, BUILD_RANGE + 10.0, 1.0);
+        world.get::<&mut ConstructionProgress>(building).unwrap().assigned_agents.push(agent);
+        let hp = |world: &World| world.get::<&Health>(building).unwrap().current;
+
+        building_regen_system(&mut world, &HashMap::new());
+        assert_eq!(hp(&world), 50);
+
+        world.get::<&mut Position>(agent).unwrap().x = 10.0;
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Idle;
+        building_regen_system(&mut world, &HashMap::new());
+        assert_eq!(hp(&world), 50);
+
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Building;
+        building_regen_system(&mut world, &HashMap::new());
+        assert_eq!(hp(&world), 50 + MAINTENANCE_HP_PER_TICK);
+
+        // A destroyed building stays destroyed.
+        world.get::<&mut Health>(building).unwrap().current = 0;
+        building_regen_system(&mut world, &HashMap::new());
+        assert_eq!(hp(&world), 0);
     }
 }
@@ -4,9 +4,9 @@ use rand::Rng;
 use crate::ecs::components::{
     Agent, AgentMorale, AgentName, AgentState, AgentStats, AgentTier, AgentXP,
     BoundAgent, Collider, GameState, GuardianRogue, Health, Position, Recruitable, Rogue, RogueAI,
-    RogueBehaviorState, RogueType, RogueVisibility, Velocity, VoiceProfile, WanderState,
+    RogueBehaviorState, RogueType, RogueVisibility, Unguarded, Velocity, VoiceProfile, WanderState,
 };
-use crate::game::agents::generate_config_for_backend;
+use crate::game::agents::{generate_config_for_backend, living_guardians};
 use crate::protocol::{AgentStateKind, AgentTierKind, AiBackend, RogueTypeKind};
 
 /// Grid spacing for bound-agent camp positions (world units).
@@ -210,6 +210,7 @@ pub fn camp_spawner_system(
                     pause_remaining: 0,
                     wander_radius: 20.0,
                     walk_target: None,
+                    walk_ticks: 0,
                 },
             ));
 
@@ -256,3 +257,68 @@ pub fn camp_spawner_system(
         }
     }
 }
+
+/// Marks bound agents whose last guardian has died as [`Unguarded`], so the
+/// client sees they can be recruited. Returns a log entry for each.
+pub fn camp_release_system(world: &mut World) -> Vec<String> {
+    let freed: Vec<(hecs::Entity, String)> = world
+        .query::<&AgentName>()
+        .with::<&BoundAgent>()
+        .without::<&Unguarded>()
+        .iter()
+        .map(|(e, name)| (e, name.name.clone()))
+        .collect();
+
+    let mut log_entries = Vec::new();
+    for (agent, name) in freed {
+        if living_guardians(world, agent) > 0 {
+            continue;
+        }
+        let _ = world.insert_one(agent, Unguarded);
+        log_entries.push(format!("{}'s guardians have fallen. {} can be recruited.", name, name));
+    }
+    log_entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_guardian(world: &mut World, agent: hecs::Entity) -> hecs::Entity {
+        world.spawn((
+            Rogue,
+            Health { current: 15, max: 15 },
+            GuardianRogue {
+                home_x: 0.0,
+                home_y: 0.0,
+                leash_radius: 200.0,
+                bound_agent_entity: agent,
+                patrol_waypoint_x: 0.0,
+                patrol_waypoint_y: 0.0,
+                patrol_pause: 0,
+            },
+        ))
+    }
+
+    #[test]
+    fn agent_is_released_when_its_last_guardian_dies() {
+        let mut world = World::new();
+        let agent = world.spawn((Agent, BoundAgent, AgentName { name: "Ember".to_string() }));
+        let first = spawn_guardian(&mut world, agent);
+        let second = spawn_guardian(&mut world, agent);
+
+        assert!(camp_release_system(&mut world).is_empty());
+        world.despawn(first).unwrap();
+        assert!(camp_release_system(&mut world).is_empty());
+        assert!(world.get::<&Unguarded>(agent).is_err());
+
+        world.get::<&mut Health>(second).unwrap().current = 0;
+        assert_eq!(
+            camp_release_system(&mut world),
+            vec!["Ember's guardians have fallen. Ember can be recruited.".to_string()]
+        );
+        assert!(world.get::<&Unguarded>(agent).is_ok());
+        // Announced once.
+        assert!(camp_release_system(&mut world).is_empty());
+    }
+}
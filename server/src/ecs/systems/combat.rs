@@ -1,12 +1,28 @@
+use std::collections::HashMap;
+
 use hecs::World;
 
+use crate::ai::rogue_ai::MIMIC_STALK_TICKS;
 use crate::ecs::components::{
-    Agent, AgentName, AgentState, Armor, CombatPower, Facing, GameState, Health, Player, Position,
-    Rogue, RogueType,
+    Agent, AgentName, AgentState, Armor, CombatPower, Durability, Facing, Feared, FlareEffect, GameState, Health,
+    Knockback, MimicDisguise, MimicPhase, Player, Position, ReviveTimer, Rogue, RogueNest, RogueType, Specialization,
+    TorchRange, WeaponType,
 };
-use crate::protocol::{AgentStateKind, AudioEvent, CombatEvent, RogueTypeKind};
+use crate::ecs::systems::dodge::has_iframes;
+use crate::ecs::systems::economy::record_transaction;
+use crate::ecs::systems::loot::drop_loot;
+use crate::ecs::systems::morale::{adjust_morale, DAMAGE_MORALE_LOSS};
+use crate::ecs::systems::revival::REVIVE_WINDOW_TICKS;
+use crate::ecs::systems::spawn::death_spawns;
+use crate::ecs::systems::status_effect::{apply_status, FLARE_BURN, JAMMER_SLOW};
+use crate::ecs::systems::token_drain::drain_refund;
+use crate::ecs::weapon_stats::{fresh_durability, weapon_stats};
+use crate::game::agents::DEFENDER_DAMAGE_REDUCTION;
+use crate::game::spatial::SpatialGrid;
+use crate::protocol::{AgentSpecialization, AgentStateKind, AudioEvent, CombatEvent, DropEvent, RogueTypeKind, StatusEffect};
 
 /// The result of running the combat system for one tick.
+#[derive(Default)]
 pub struct CombatResult {
     pub killed_rogues: Vec<(hecs::Entity, RogueTypeKind)>,
     pub killed_agents: Vec<(hecs::Entity, String)>,
@@ -17,15 +33,98 @@ pub struct CombatResult {
     pub bounty_tokens: i64,
     pub combat_events: Vec<CombatEvent>,
     pub player_attacked: bool,
+    /// Rogues to spawn once combat resolves, e.g. Swarms from a Multiplier.
+    pub pending_spawns: Vec<(f32, f32, RogueTypeKind)>,
+    /// Loot dropped by killed rogues.
+    pub drops: Vec<DropEvent>,
+}
+
+/// An area attack centred on a point. Rogues inside `radius` take
+/// `damage * (1 - falloff * dist / radius)`, truncated, minimum 1.
+#[derive(Debug, Clone)]
+pub struct SplashAttack {
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32,
+    pub damage: i32,
+    pub falloff: f32,
+    /// Status effect left on every rogue that survives the hit.
+    pub on_hit: Option<StatusEffect>,
+    /// Per-rogue critical chance and multiplier, as on `CombatPower`.
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+}
+
+impl SplashAttack {
+    /// Damage dealt at `dist` pixels from the centre, or `None` if outside.
+    pub fn damage_at(&self, dist: f32) -> Option<i32> {
+        if dist > self.radius || self.radius <= 0.0 {
+            return None;
+        }
+        // Multiply before dividing so exact fractions don't truncate down.
+        let raw = self.damage as f32 * (self.radius - self.falloff * dist) / self.radius;
+        Some((raw as i32).max(1))
+    }
+}
+
+/// Pixels per tick a rogue struck by the HardReset is thrown back.
+pub const HARD_RESET_KNOCKBACK_SPEED: f32 = 4.0;
+/// Ticks a HardReset knockback lasts.
+pub const HARD_RESET_KNOCKBACK_TICKS: u32 = 10;
+
+/// Extra torch radius while a Flare hit is burning.
+pub const FLARE_LIGHT_BONUS: f32 = 80.0;
+/// Ticks the torch stays flared after a Flare hit (3s at 20Hz).
+pub const FLARE_LIGHT_TICKS: u32 = 60;
+/// Ticks rogues caught in a Flare burst flee from the player (2s at 20Hz).
+pub const FLARE_FEAR_TICKS: u32 = 40;
+
+/// Flares the player's torch for [`FLARE_LIGHT_TICKS`]. Another hit while
+/// it's burning restarts the timer rather than adding more light.
+fn ignite_flare(world: &mut World, player: hecs::Entity) {
+    if let Ok(mut flare) = world.get::<&mut FlareEffect>(player) {
+        flare.ticks_remaining = FLARE_LIGHT_TICKS;
+        return;
+    }
+    let Ok(base_radius) = world.get::<&mut TorchRange>(player).map(|mut torch| {
+        let base = torch.radius;
+        torch.radius = base + FLARE_LIGHT_BONUS;
+        base
+    }) else {
+        return;
+    };
+    let _ = world.insert_one(player, FlareEffect { ticks_remaining: FLARE_LIGHT_TICKS, base_radius });
+}
+
+/// Counts down [`FlareEffect`]s and puts the torch back to its base radius
+/// when one burns out.
+pub fn flare_system(world: &mut World) {
+    let mut burnt_out: Vec<hecs::Entity> = Vec::new();
+    for (entity, (flare, torch)) in world.query_mut::<(&mut FlareEffect, &mut TorchRange)>() {
+        flare.ticks_remaining = flare.ticks_remaining.saturating_sub(1);
+        if flare.ticks_remaining == 0 {
+            torch.radius = flare.base_radius;
+            burnt_out.push(entity);
+        }
+    }
+    for entity in burnt_out {
+        let _ = world.remove_one::<FlareEffect>(entity);
+    }
 }
 
-fn distance_sq(a: &Position, b: &Position) -> f32 {
-    let dx = a.x - b.x;
-    let dy = a.y - b.y;
-    dx * dx + dy * dy
+/// Rolls a critical hit: returns `damage` multiplied by `crit_multiplier`
+/// (truncated) and `true` with probability `crit_chance`, otherwise
+/// `damage` unchanged and `false`.
+pub fn roll_crit(damage: i32, crit_chance: f32, crit_multiplier: f32) -> (i32, bool) {
+    if rand::random::<f32>() < crit_chance {
+        ((damage as f32 * crit_multiplier) as i32, true)
+    } else {
+        (damage, false)
+    }
 }
 
-fn bounty_for(kind: RogueTypeKind) -> i64 {
+/// Tokens awarded for killing a rogue of `kind`.
+pub fn bounty_for(kind: RogueTypeKind) -> i64 {
     match kind {
         RogueTypeKind::Swarm => 5,
         RogueTypeKind::Corruptor => 15,
@@ -34,6 +133,8 @@ fn bounty_for(kind: RogueTypeKind) -> i64 {
         RogueTypeKind::Assassin => 30,
         RogueTypeKind::Mimic => 15,
         RogueTypeKind::Architect => 50,
+        RogueTypeKind::Multiplier => 20,
+        RogueTypeKind::Plague => 18,
     }
 }
 
@@ -46,6 +147,8 @@ fn rogue_damage_to_player(kind: RogueTypeKind) -> i32 {
         RogueTypeKind::Mimic => 1,
         RogueTypeKind::TokenDrain => 0,
         RogueTypeKind::Architect => 1,
+        RogueTypeKind::Multiplier => 3,
+        RogueTypeKind::Plague => 1,
     }
 }
 
@@ -75,10 +178,114 @@ fn is_in_arc(facing: &Facing, attacker_pos: &Position, target_pos: &Position, ar
     dot >= half_arc_rad.cos()
 }
 
+/// Takes one point of durability off the player's weapon. A weapon that runs
+/// out breaks and the player reverts to the basic ProcessTerminator, keeping
+/// any cooldown in progress. Breakable weapons missing a `Durability` (e.g.
+/// from an older save) get a fresh one first.
+fn wear_weapon(world: &mut World, player: hecs::Entity, weapon: &WeaponType, result: &mut CombatResult) {
+    if world.get::<&Durability>(player).is_err() {
+        match fresh_durability(weapon.clone()) {
+            Some(durability) => {
+                let _ = world.insert_one(player, durability);
+            }
+            None => return,
+        }
+    }
+
+    let broke = match world.get::<&mut Durability>(player) {
+        Ok(mut durability) => {
+            durability.current = durability.current.saturating_sub(1);
+            durability.current == 0
+        }
+        Err(_) => false,
+    };
+    if !broke {
+        return;
+    }
+
+    if let Ok(mut combat) = world.get::<&mut CombatPower>(player) {
+        let cooldown = combat.cooldown_remaining;
+        *combat = weapon_stats(WeaponType::ProcessTerminator);
+        combat.cooldown_remaining = cooldown;
+    }
+    let _ = world.remove_one::<Durability>(player);
+    result.log_entries.push(format!("[combat] your {:?} broke", weapon));
+}
+
+/// A Mimic still stalking unseen, which can't be hit. Once it settles into
+/// its disguise it takes damage again, and being hurt gives it away.
+pub fn is_hidden_mimic(world: &World, entity: hecs::Entity) -> bool {
+    world
+        .get::<&MimicPhase>(entity)
+        .is_ok_and(|phase| !phase.revealed && phase.elapsed_ticks < MIMIC_STALK_TICKS)
+}
+
+/// Applies splash attacks to every rogue in range. Kills, bounty, audio and
+/// combat events are appended to `result`; despawning is left to the caller.
+/// A rogue already killed earlier this tick is not hit again.
+pub fn splash_attack_system(
+    world: &mut World,
+    rogue_grid: &SpatialGrid,
+    attacks: &[SplashAttack],
+    result: &mut CombatResult,
+) {
+    for attack in attacks {
+        for rogue_entity in rogue_grid.query_radius(attack.center_x, attack.center_y, attack.radius) {
+            if result.killed_rogues.iter().any(|(e, _)| *e == rogue_entity) || is_hidden_mimic(world, rogue_entity) {
+                continue;
+            }
+            let (rogue_pos, rogue_kind) = match world.query_one_mut::<(&Position, &RogueType)>(rogue_entity) {
+                Ok((pos, rt)) => (pos.clone(), rt.kind),
+                Err(_) => continue,
+            };
+            let dx = rogue_pos.x - attack.center_x;
+            let dy = rogue_pos.y - attack.center_y;
+            let Some(damage) = attack.damage_at((dx * dx + dy * dy).sqrt()) else {
+                continue;
+            };
+            let (damage, is_crit) = roll_crit(damage, attack.crit_chance, attack.crit_multiplier);
+
+            let is_kill = match world.get::<&mut Health>(rogue_entity) {
+                Ok(mut health) => {
+                    health.current -= damage;
+                    health.current <= 0
+                }
+                Err(_) => continue,
+            };
+            result.audio_events.push(AudioEvent::CombatHit);
+            if is_crit {
+                result.audio_events.push(AudioEvent::CritHit);
+            }
+            result.combat_events.push(CombatEvent {
+                x: rogue_pos.x,
+                y: rogue_pos.y,
+                damage,
+                is_kill,
+                rogue_type: Some(rogue_kind),
+                is_crit,
+            });
+
+            if is_kill {
+                result.bounty_tokens += bounty_for(rogue_kind);
+                result.killed_rogues.push((rogue_entity, rogue_kind));
+                result.log_entries.push(format!("[combat] {:?} terminated", rogue_kind));
+            } else if let Some(effect) = attack.on_hit {
+                apply_status(world, rogue_entity, effect);
+            }
+        }
+    }
+}
+
+/// Resolves melee combat for one tick.
+///
+/// `rogue_grid` must contain every live rogue; proximity checks only look at
+/// nearby cells, and rogues killed here are removed from the grid so later
+/// systems in the same tick don't see them.
 pub fn combat_system(
     world: &mut World,
     game_state: &mut GameState,
     player_attacking: bool,
+    rogue_grid: &mut SpatialGrid,
 ) -> CombatResult {
     let mut result = CombatResult {
         killed_rogues: Vec::new(),
@@ -90,6 +297,8 @@ pub fn combat_system(
         bounty_tokens: 0,
         combat_events: Vec::new(),
         player_attacked: false,
+        pending_spawns: Vec::new(),
+        drops: Vec::new(),
     };
 
     // ── Gather player info ──────────────────────────────────────────
@@ -100,9 +309,12 @@ pub fn combat_system(
     let mut player_cooldown_remaining: u32 = 0;
     let mut player_cooldown_ticks: u32 = 6;
     let mut player_is_projectile: bool = false;
+    let mut player_weapon = WeaponType::ProcessTerminator;
     let mut player_entity: Option<hecs::Entity> = None;
     let mut player_facing = Facing::default();
     let mut player_armor_def: f32 = 0.0;
+    let mut player_crit_chance: f32 = 0.0;
+    let mut player_crit_multiplier: f32 = 1.0;
 
     for (entity, (_player, pos, combat, facing)) in
         world.query::<(&Player, &Position, &CombatPower, &Facing)>().iter()
@@ -114,6 +326,9 @@ pub fn combat_system(
         player_cooldown_remaining = combat.cooldown_remaining;
         player_cooldown_ticks = combat.cooldown_ticks;
         player_is_projectile = combat.is_projectile;
+        player_crit_chance = combat.crit_chance;
+        player_crit_multiplier = combat.crit_multiplier;
+        player_weapon = combat.weapon.clone();
         player_entity = Some(entity);
         player_facing = Facing { dx: facing.dx, dy: facing.dy };
     }
@@ -131,14 +346,29 @@ pub fn combat_system(
     };
 
     // ── Gather rogue info ───────────────────────────────────────────
-    let rogues: Vec<(hecs::Entity, Position, RogueTypeKind)> = world
+    // Stalking Mimics can't be hit, so they aren't targets.
+    let rogues: HashMap<hecs::Entity, (Position, RogueTypeKind)> = world
         .query::<(&Rogue, &Position, &RogueType)>()
         .iter()
-        .map(|(entity, (_rogue, pos, rogue_type))| (entity, pos.clone(), rogue_type.kind))
+        .filter(|(entity, _)| !is_hidden_mimic(world, *entity))
+        .map(|(entity, (_rogue, pos, rogue_type))| (entity, (pos.clone(), rogue_type.kind)))
         .collect();
+    // Disguised mimics don't fight back, and neither do rogues being
+    // knocked back.
+    let disguised: std::collections::HashSet<hecs::Entity> =
+        world.query::<&MimicDisguise>().iter().map(|(e, _)| e).collect();
+    let knocked_back: std::collections::HashSet<hecs::Entity> =
+        world.query::<&Knockback>().iter().map(|(e, _)| e).collect();
+    let rogues_near = |x: f32, y: f32, r: f32| -> Vec<(hecs::Entity, Position, RogueTypeKind)> {
+        rogue_grid
+            .query_radius(x, y, r)
+            .filter_map(|e| rogues.get(&e).map(|(pos, kind)| (e, pos.clone(), *kind)))
+            .collect()
+    };
 
     // ── Player attacks rogues (directional, with cooldown) ──────────
-    let attack_range_sq = player_range * player_range;
+    let mut splash_attacks: Vec<SplashAttack> = Vec::new();
+    let mut weapon_landed = false;
 
     if player_attacking && player_cooldown_remaining == 0 && !player_is_projectile {
         result.player_attacked = true;
@@ -150,34 +380,109 @@ pub fn combat_system(
             }
         }
 
-        for &(rogue_entity, ref rogue_pos, rogue_kind) in &rogues {
-            if distance_sq(&player_pos, rogue_pos) > attack_range_sq {
-                continue;
-            }
-
+        // The Flare bursts around the player instead of striking in an arc.
+        let melee_targets = if matches!(player_weapon, WeaponType::Flare) {
+            splash_attacks.push(SplashAttack {
+                center_x: player_pos.x,
+                center_y: player_pos.y,
+                radius: player_range,
+                damage: player_damage,
+                falloff: 1.0,
+                on_hit: Some(FLARE_BURN),
+                crit_chance: player_crit_chance,
+                crit_multiplier: player_crit_multiplier,
+            });
+            Vec::new()
+        } else {
+            rogues_near(player_pos.x, player_pos.y, player_range)
+        };
+
+        for (rogue_entity, ref rogue_pos, rogue_kind) in melee_targets {
             // Check directional arc
             if !is_in_arc(&player_facing, &player_pos, rogue_pos, player_arc) {
                 continue;
             }
 
-            if let Ok(mut health) = world.get::<&mut Health>(rogue_entity) {
-                health.current -= player_damage;
-                result.audio_events.push(AudioEvent::CombatHit);
+            let (damage, is_crit) = roll_crit(player_damage, player_crit_chance, player_crit_multiplier);
+            let is_kill = match world.get::<&mut Health>(rogue_entity) {
+                Ok(mut health) => {
+                    health.current -= damage;
+                    health.current <= 0
+                }
+                Err(_) => continue,
+            };
+            weapon_landed = true;
+            result.audio_events.push(AudioEvent::CombatHit);
+            if is_crit {
+                result.audio_events.push(AudioEvent::CritHit);
+            }
 
-                result.combat_events.push(CombatEvent {
-                    x: rogue_pos.x,
-                    y: rogue_pos.y,
-                    damage: player_damage,
-                    is_kill: health.current <= 0,
-                    rogue_type: Some(rogue_kind),
+            result.combat_events.push(CombatEvent {
+                x: rogue_pos.x,
+                y: rogue_pos.y,
+                damage,
+                is_kill,
+                rogue_type: Some(rogue_kind),
+                is_crit,
+            });
+
+            if is_kill {
+                let bounty = bounty_for(rogue_kind);
+                result.bounty_tokens += bounty;
+                result.killed_rogues.push((rogue_entity, rogue_kind));
+                result.log_entries.push(format!("[combat] {:?} terminated", rogue_kind));
+            } else if matches!(player_weapon, WeaponType::SignalJammer) {
+                apply_status(world, rogue_entity, JAMMER_SLOW);
+            } else if matches!(player_weapon, WeaponType::HardReset) {
+                let (dx, dy) = (rogue_pos.x - player_pos.x, rogue_pos.y - player_pos.y);
+                let dist = (dx * dx + dy * dy).sqrt();
+                let (nx, ny) = if dist > 0.001 { (dx / dist, dy / dist) } else { (player_facing.dx, player_facing.dy) };
+                let _ = world.insert_one(rogue_entity, Knockback {
+                    dx: nx * HARD_RESET_KNOCKBACK_SPEED,
+                    dy: ny * HARD_RESET_KNOCKBACK_SPEED,
+                    ticks: HARD_RESET_KNOCKBACK_TICKS,
                 });
+            }
+        }
 
-                if health.current <= 0 {
-                    let bounty = bounty_for(rogue_kind);
-                    result.bounty_tokens += bounty;
-                    result.killed_rogues.push((rogue_entity, rogue_kind));
-                    result.log_entries.push(format!("[combat] {:?} terminated", rogue_kind));
-                }
+        // Rogue nests in reach take the hit too; `nest_spawn_system` clears
+        // out the ones that drop to zero.
+        for (_nest, (pos, health)) in world.query_mut::<(&Position, &mut Health)>().with::<&RogueNest>() {
+            let (dx, dy) = (pos.x - player_pos.x, pos.y - player_pos.y);
+            if dx * dx + dy * dy > player_range * player_range
+                || !is_in_arc(&player_facing, &player_pos, pos, player_arc)
+            {
+                continue;
+            }
+            let (damage, is_crit) = roll_crit(player_damage, player_crit_chance, player_crit_multiplier);
+            health.current -= damage;
+            weapon_landed = true;
+            result.audio_events.push(AudioEvent::CombatHit);
+            result.combat_events.push(CombatEvent {
+                x: pos.x,
+                y: pos.y,
+                damage,
+                is_kill: health.current <= 0,
+                rogue_type: None,
+                is_crit,
+            });
+        }
+    }
+
+    let events_before_splash = result.combat_events.len();
+    splash_attack_system(world, rogue_grid, &splash_attacks, &mut result);
+    let splash_landed = result.combat_events.len() > events_before_splash;
+    weapon_landed |= splash_landed;
+
+    // A Flare that lands lights up the area and scatters the rogues in its
+    // burst. Architects hold their ground.
+    if splash_landed && matches!(player_weapon, WeaponType::Flare) {
+        if let Some(pe) = player_entity {
+            ignite_flare(world, pe);
+        }
+        for (rogue_entity, _pos, rogue_kind) in rogues_near(player_pos.x, player_pos.y, player_range) {
+            if rogue_kind != RogueTypeKind::Architect {
+                let _ = world.insert_one(rogue_entity, Feared { ticks_remaining: FLARE_FEAR_TICKS });
             }
         }
     }
@@ -190,23 +495,28 @@ pub fn combat_system(
                 combat.cooldown_remaining = player_cooldown_ticks;
             }
         }
-        // Projectile spawning is handled in main.rs after combat_system returns
+        // Projectile spawning is handled in main.rs after combat_system returns.
+        // Bolts wear the weapon as they are fired.
+        weapon_landed = true;
     }
 
-    // ── Rogues attack player (with armor reduction) ──────────────────
-    if !game_state.god_mode {
-        let player_threat_range_sq: f32 = 20.0 * 20.0;
+    if weapon_landed {
+        if let Some(pe) = player_entity {
+            wear_weapon(world, pe, &player_weapon, &mut result);
+        }
+    }
 
-        for &(_rogue_entity, ref rogue_pos, rogue_kind) in &rogues {
-            if distance_sq(&player_pos, rogue_pos) > player_threat_range_sq {
-                continue;
-            }
+    // ── Rogues attack player (with armor reduction) ──────────────────
+    // A player mid-dodge is untouchable.
+    let dodging = player_entity.is_some_and(|pe| has_iframes(world, pe));
+    if !game_state.god_mode && !dodging {
+        let player_threat_range: f32 = 20.0;
 
-            if rogue_kind == RogueTypeKind::TokenDrain {
-                game_state.economy.balance = (game_state.economy.balance - 1).max(0);
+        for (rogue_entity, _rogue_pos, rogue_kind) in rogues_near(player_pos.x, player_pos.y, player_threat_range) {
+            if disguised.contains(&rogue_entity) || knocked_back.contains(&rogue_entity) {
                 continue;
             }
-
+            // TokenDrains leech tokens instead (see `token_drain_system`).
             let raw_dmg = rogue_damage_to_player(rogue_kind);
             if raw_dmg > 0 {
                 let final_dmg = (raw_dmg - player_armor_def as i32).max(1);
@@ -222,25 +532,33 @@ pub fn combat_system(
     }
 
     // ── Rogues attack nearby agents ─────────────────────────────────
-    let agent_threat_range_sq: f32 = 25.0 * 25.0;
+    let agent_threat_range: f32 = 25.0;
 
-    let agents: Vec<(hecs::Entity, Position, String)> = world
-        .query::<(&Agent, &Position, &AgentState, &AgentName)>()
+    // Defenders shrug off part of every hit.
+    let agents: Vec<(hecs::Entity, Position, String, i32)> = world
+        .query::<(&Agent, &Position, &AgentState, &AgentName, Option<&Specialization>)>()
         .iter()
-        .filter(|(_entity, (_agent, _pos, state, _name))| {
+        .filter(|(_entity, (_agent, _pos, state, _name, _spec))| {
             state.state != AgentStateKind::Unresponsive
                 && state.state != AgentStateKind::Dormant
         })
-        .map(|(entity, (_agent, pos, _state, name))| (entity, pos.clone(), name.name.clone()))
+        .map(|(entity, (_agent, pos, _state, name, spec))| {
+            let reduction = if spec.is_some_and(|s| s.role == AgentSpecialization::Defender) {
+                DEFENDER_DAMAGE_REDUCTION
+            } else {
+                0
+            };
+            (entity, pos.clone(), name.name.clone(), reduction)
+        })
         .collect();
 
-    for (agent_entity, ref agent_pos, ref agent_name) in &agents {
-        for &(_rogue_entity, ref rogue_pos, rogue_kind) in &rogues {
-            if distance_sq(agent_pos, rogue_pos) > agent_threat_range_sq {
+    for (agent_entity, ref agent_pos, ref agent_name, reduction) in &agents {
+        for (rogue_entity, _rogue_pos, rogue_kind) in rogues_near(agent_pos.x, agent_pos.y, agent_threat_range) {
+            if disguised.contains(&rogue_entity) || knocked_back.contains(&rogue_entity) {
                 continue;
             }
-
-            let dmg = rogue_damage_to_agent(rogue_kind);
+            let dmg = (rogue_damage_to_agent(rogue_kind) - reduction).max(1);
+            adjust_morale(world, *agent_entity, -DAMAGE_MORALE_LOSS);
             if let Ok(mut health) = world.get::<&mut Health>(*agent_entity) {
                 health.current -= dmg;
 
@@ -256,13 +574,370 @@ pub fn combat_system(
             }
         }
     }
+    for (agent_entity, _name) in &result.killed_agents {
+        let _ = world.insert_one(*agent_entity, ReviveTimer { ticks_remaining: REVIVE_WINDOW_TICKS });
+    }
 
     // ── Despawn killed rogues ────────────────────────────────────────
-    for &(rogue_entity, _kind) in &result.killed_rogues {
+    for &(rogue_entity, kind) in &result.killed_rogues {
+        if let Ok(pos) = world.get::<&Position>(rogue_entity) {
+            result.pending_spawns.extend(death_spawns(kind, pos.x, pos.y));
+        }
+        result.drops.extend(drop_loot(world, rogue_entity, &mut rand::thread_rng()));
+        let refund = drain_refund(world, rogue_entity);
         let _ = world.despawn(rogue_entity);
+        rogue_grid.remove(rogue_entity);
+        record_transaction(&mut game_state.economy, bounty_for(kind), &format!("{:?} bounty", kind), game_state.tick);
+        if refund > 0 {
+            result.bounty_tokens += refund;
+            record_transaction(&mut game_state.economy, refund, "TokenDrain refund", game_state.tick);
+        }
     }
 
-    game_state.economy.balance += result.bounty_tokens;
-
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_test_rogue(world: &mut World, grid: &mut SpatialGrid, x: f32, y: f32) -> hecs::Entity {
+        let e = world.spawn((
+            Rogue,
+            Position { x, y },
+            RogueType { kind: RogueTypeKind::Architect },
+            Health { current: 100, max: 100 },
+        ));
+        grid.insert(e, x, y);
+        e
+    }
+
+    #[test]
+    fn splash_damage_falls_off_with_distance() {
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let near = spawn_test_rogue(&mut world, &mut grid, 15.0, 0.0);
+        let edge = spawn_test_rogue(&mut world, &mut grid, 0.0, 25.0);
+        let outside = spawn_test_rogue(&mut world, &mut grid, 30.0, 0.0);
+
+        let attack = SplashAttack { center_x: 0.0, center_y: 0.0, radius: 25.0, damage: 10, falloff: 1.0, on_hit: None, crit_chance: 0.0, crit_multiplier: 1.0 };
+        let mut result = CombatResult::default();
+        splash_attack_system(&mut world, &grid, &[attack], &mut result);
+
+        let hp = |e| world.get::<&Health>(e).unwrap().current;
+        let near_dmg = 100 - hp(near);
+        let edge_dmg = 100 - hp(edge);
+        assert_eq!(near_dmg, 4); // 10 * (1 - 15/25)
+        assert_eq!(edge_dmg, 1); // truncated to 0, clamped to minimum 1
+        assert_ne!(near_dmg, edge_dmg);
+        assert_eq!(hp(outside), 100);
+        assert_eq!(result.combat_events.len(), 2);
+    }
+
+    #[test]
+    fn splash_kills_are_reported() {
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let rogue = spawn_test_rogue(&mut world, &mut grid, 0.0, 0.0);
+        world.get::<&mut Health>(rogue).unwrap().current = 5;
+
+        let attack = SplashAttack { center_x: 0.0, center_y: 0.0, radius: 25.0, damage: 10, falloff: 1.0, on_hit: None, crit_chance: 0.0, crit_multiplier: 1.0 };
+        let mut result = CombatResult::default();
+        splash_attack_system(&mut world, &grid, &[attack], &mut result);
+
+        assert_eq!(result.killed_rogues, vec![(rogue, RogueTypeKind::Architect)]);
+        assert_eq!(result.bounty_tokens, bounty_for(RogueTypeKind::Architect));
+    }
+
+    #[test]
+    fn splash_on_hit_effect_lands_on_survivors() {
+        use crate::ecs::components::StatusEffects;
+
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let rogue = spawn_test_rogue(&mut world, &mut grid, 5.0, 0.0);
+
+        let attack = SplashAttack { center_x: 0.0, center_y: 0.0, radius: 25.0, damage: 10, falloff: 1.0, on_hit: Some(FLARE_BURN), crit_chance: 0.0, crit_multiplier: 1.0 };
+        let mut result = CombatResult::default();
+        splash_attack_system(&mut world, &grid, &[attack], &mut result);
+
+        assert_eq!(world.get::<&StatusEffects>(rogue).unwrap().effects, vec![FLARE_BURN]);
+    }
+
+    #[test]
+    fn each_kill_records_a_bounty_transaction() {
+        use crate::ecs::world::create_world;
+
+        let (mut world, mut game_state) = create_world();
+        let mut grid = SpatialGrid::default();
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        let kinds = [
+            RogueTypeKind::Swarm,
+            RogueTypeKind::Looper,
+            RogueTypeKind::Corruptor,
+            RogueTypeKind::Swarm,
+            RogueTypeKind::Mimic,
+        ];
+
+        for (i, kind) in kinds.iter().enumerate() {
+            game_state.tick = i as u64;
+            let rogue = world.spawn((
+                Rogue,
+                Position { x: 400.0, y: 310.0 },
+                RogueType { kind: *kind },
+                Health { current: 1, max: 1 },
+            ));
+            grid.insert(rogue, 400.0, 310.0);
+            world.get::<&mut CombatPower>(player).unwrap().cooldown_remaining = 0;
+
+            let result = combat_system(&mut world, &mut game_state, true, &mut grid);
+            assert_eq!(result.killed_rogues.len(), 1);
+        }
+
+        let log: Vec<_> = game_state.economy.transaction_log.iter().collect();
+        assert_eq!(log.len(), 5);
+        let mut previous = 0;
+        for (entry, kind) in log.iter().zip(&kinds) {
+            assert_eq!(entry.delta, bounty_for(*kind));
+            assert_eq!(entry.source, format!("{:?} bounty", kind));
+            assert!(entry.balance_after > previous);
+            previous = entry.balance_after;
+        }
+        assert_eq!(previous, game_state.economy.balance);
+    }
+
+    /// A god-mode player holding a HardReset with `durability` left, facing a
+    /// sturdy rogue.
+    fn armed_player(durability: u32) -> (World, GameState, SpatialGrid, hecs::Entity) {
+        use crate::ecs::world::create_world;
+
+        let (mut world, mut game_state) = create_world();
+        game_state.god_mode = true;
+        let mut grid = SpatialGrid::default();
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        *world.get::<&mut CombatPower>(player).unwrap() = weapon_stats(WeaponType::HardReset);
+        world.insert_one(player, Durability { current: durability, max: 60 }).unwrap();
+        spawn_test_rogue(&mut world, &mut grid, 400.0, 310.0);
+        (world, game_state, grid, player)
+    }
+
+    #[test]
+    fn landed_attacks_wear_the_weapon() {
+        let (mut world, mut game_state, mut grid, player) = armed_player(60);
+
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        assert_eq!(world.get::<&Durability>(player).unwrap().current, 59);
+
+        // Still on cooldown: no swing, no wear.
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        assert_eq!(world.get::<&Durability>(player).unwrap().current, 59);
+
+        // A swing at thin air doesn't wear the weapon either.
+        world.get::<&mut CombatPower>(player).unwrap().cooldown_remaining = 0;
+        world.get::<&mut Facing>(player).unwrap().dy = -1.0;
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        assert_eq!(world.get::<&Durability>(player).unwrap().current, 59);
+    }
+
+    #[test]
+    fn broken_weapon_reverts_to_the_process_terminator() {
+        let (mut world, mut game_state, mut grid, player) = armed_player(1);
+
+        let result = combat_system(&mut world, &mut game_state, true, &mut grid);
+
+        assert!(result.log_entries.contains(&"[combat] your HardReset broke".to_string()));
+        let combat = world.get::<&CombatPower>(player).unwrap();
+        assert!(matches!(combat.weapon, WeaponType::ProcessTerminator));
+        assert_eq!(combat.base_damage, weapon_stats(WeaponType::ProcessTerminator).base_damage);
+        assert_eq!(combat.cooldown_remaining, weapon_stats(WeaponType::HardReset).cooldown_ticks);
+        assert!(world.get::<&Durability>(player).is_err());
+    }
+
+    #[test]
+    fn hard_reset_knocks_rogues_away_from_the_player() {
+        let (mut world, mut game_state, mut grid, _player) = armed_player(60);
+        let rogue = world.query::<&Rogue>().iter().next().map(|(e, _)| e).unwrap();
+        world.get::<&mut Health>(rogue).unwrap().current = 1000;
+
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        let knockback = world.get::<&Knockback>(rogue).unwrap();
+        // The player stands at (400, 300), straight above the rogue.
+        assert_eq!((knockback.dx, knockback.dy), (0.0, HARD_RESET_KNOCKBACK_SPEED));
+        assert_eq!(knockback.ticks, HARD_RESET_KNOCKBACK_TICKS);
+    }
+
+    #[test]
+    fn grid_matches_brute_force_with_a_thousand_rogues() {
+        use crate::ecs::systems::spawn::spawn_rogue;
+        use crate::ecs::world::create_world;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        // A single enormous cell turns every query into a full scan.
+        let run = |mut grid: SpatialGrid| {
+            let (mut world, mut game_state) = create_world();
+            let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+            let (px, py) = {
+                let pos = world.get::<&Position>(player).unwrap();
+                (pos.x, pos.y)
+            };
+            {
+                let mut combat = world.get::<&mut CombatPower>(player).unwrap();
+                combat.range = 200.0;
+                combat.arc_degrees = 120.0;
+                combat.cooldown_remaining = 0;
+                combat.crit_chance = 0.0;
+            }
+
+            let kinds = [RogueTypeKind::Swarm, RogueTypeKind::Corruptor, RogueTypeKind::TokenDrain, RogueTypeKind::Looper];
+            let mut rng = StdRng::seed_from_u64(42);
+            for _ in 0..1000 {
+                let x = px + rng.gen_range(-600.0..600.0);
+                let y = py + rng.gen_range(-600.0..600.0);
+                spawn_rogue(&mut world, x, y, kinds[rng.gen_range(0..kinds.len())]);
+            }
+            grid.insert_all::<Rogue>(&world);
+
+            let result = combat_system(&mut world, &mut game_state, true, &mut grid);
+            let mut killed: Vec<u64> = result.killed_rogues.iter().map(|(e, _)| e.to_bits().into()).collect();
+            killed.sort();
+            let mut hp: Vec<(u64, i32)> = world
+                .query::<(&Rogue, &Health)>()
+                .iter()
+                .map(|(e, (_, h))| (e.to_bits().into(), h.current))
+                .collect();
+            hp.sort();
+            let player_hp = world.get::<&Health>(player).unwrap().current;
+            (killed, hp, player_hp, game_state.economy.balance, result.combat_events.len())
+        };
+
+        let grid = run(SpatialGrid::default());
+        let brute = run(SpatialGrid::new(1.0e9));
+        assert!(grid.4 > 0, "the attack should hit something");
+        assert_eq!(grid, brute);
+    }
+
+    /// Swings the player's weapon 1000 times at a sturdy rogue right in front
+    /// of them, returning every combat event.
+    fn thousand_swings(crit_chance: f32, crit_multiplier: f32) -> (i32, Vec<CombatEvent>, usize) {
+        use crate::ecs::world::create_world;
+
+        let (mut world, mut game_state) = create_world();
+        game_state.god_mode = true;
+        let mut grid = SpatialGrid::default();
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        let (px, py) = {
+            let pos = world.get::<&Position>(player).unwrap();
+            (pos.x, pos.y)
+        };
+        let base = {
+            let mut combat = world.get::<&mut CombatPower>(player).unwrap();
+            combat.crit_chance = crit_chance;
+            combat.crit_multiplier = crit_multiplier;
+            combat.arc_degrees = 360.0;
+            combat.base_damage
+        };
+        let rogue = spawn_test_rogue(&mut world, &mut grid, px + 5.0, py);
+        world.get::<&mut Health>(rogue).unwrap().current = i32::MAX;
+
+        let mut events = Vec::new();
+        let mut crit_audio = 0;
+        for _ in 0..1000 {
+            world.get::<&mut CombatPower>(player).unwrap().cooldown_remaining = 0;
+            let result = combat_system(&mut world, &mut game_state, true, &mut grid);
+            crit_audio += result.audio_events.iter().filter(|a| matches!(a, AudioEvent::CritHit)).count();
+            events.extend(result.combat_events);
+        }
+        (base, events, crit_audio)
+    }
+
+    #[test]
+    fn guaranteed_crits_double_every_hit() {
+        let (base, events, crit_audio) = thousand_swings(1.0, 2.0);
+        assert_eq!(events.len(), 1000);
+        assert!(events.iter().all(|e| e.is_crit && e.damage == base * 2));
+        assert_eq!(crit_audio, 1000);
+    }
+
+    #[test]
+    fn zero_crit_chance_never_crits() {
+        let (base, events, crit_audio) = thousand_swings(0.0, 2.0);
+        assert_eq!(events.len(), 1000);
+        assert!(events.iter().all(|e| !e.is_crit && e.damage == base));
+        assert_eq!(crit_audio, 0);
+    }
+
+    /// Health an agent at (2000, 2000) has left after one tick next to an
+    /// Assassin.
+    fn agent_health_after_assassin_hit(role: Option<AgentSpecialization>) -> i32 {
+        use crate::ecs::world::create_world;
+
+        let (mut world, mut game_state) = create_world();
+        let mut grid = SpatialGrid::default();
+        let agent = world.spawn((
+            Agent,
+            Position { x: 2000.0, y: 2000.0 },
+            AgentState { state: AgentStateKind::Idle },
+            AgentName { name: "sol".to_string() },
+            Health { current: 50, max: 50 },
+        ));
+        if let Some(role) = role {
+            world.insert_one(agent, Specialization { role }).unwrap();
+        }
+        let rogue = world.spawn((
+            Rogue,
+            Position { x: 2010.0, y: 2000.0 },
+            RogueType { kind: RogueTypeKind::Assassin },
+            Health { current: 100, max: 100 },
+        ));
+        grid.insert(rogue, 2010.0, 2000.0);
+
+        combat_system(&mut world, &mut game_state, false, &mut grid);
+        let health = world.get::<&Health>(agent).unwrap().current;
+        health
+    }
+
+    #[test]
+    fn defenders_take_less_damage_from_rogues() {
+        let plain = agent_health_after_assassin_hit(None);
+        let defender = agent_health_after_assassin_hit(Some(AgentSpecialization::Defender));
+        assert_eq!(plain, 50 - rogue_damage_to_agent(RogueTypeKind::Assassin));
+        assert_eq!(defender, plain + DEFENDER_DAMAGE_REDUCTION);
+        assert_eq!(agent_health_after_assassin_hit(Some(AgentSpecialization::Builder)), plain);
+    }
+
+    #[test]
+    fn flare_hits_light_the_torch_and_scatter_rogues() {
+        use crate::ecs::world::create_world;
+
+        let (mut world, mut game_state) = create_world();
+        game_state.god_mode = true;
+        let mut grid = SpatialGrid::default();
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        *world.get::<&mut CombatPower>(player).unwrap() = weapon_stats(WeaponType::Flare);
+        let base = world.get::<&TorchRange>(player).unwrap().radius;
+        let architect = spawn_test_rogue(&mut world, &mut grid, 400.0, 310.0);
+        let swarm = spawn_test_rogue(&mut world, &mut grid, 410.0, 300.0);
+        world.get::<&mut RogueType>(swarm).unwrap().kind = RogueTypeKind::Swarm;
+
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        assert_eq!(world.get::<&TorchRange>(player).unwrap().radius, base + FLARE_LIGHT_BONUS);
+        assert_eq!(world.get::<&Feared>(swarm).unwrap().ticks_remaining, FLARE_FEAR_TICKS);
+        assert!(world.get::<&Feared>(architect).is_err());
+
+        // A second hit mid-flare refreshes the timer without adding light.
+        for _ in 0..10 {
+            flare_system(&mut world);
+        }
+        world.get::<&mut CombatPower>(player).unwrap().cooldown_remaining = 0;
+        combat_system(&mut world, &mut game_state, true, &mut grid);
+        assert_eq!(world.get::<&TorchRange>(player).unwrap().radius, base + FLARE_LIGHT_BONUS);
+        assert_eq!(world.get::<&FlareEffect>(player).unwrap().ticks_remaining, FLARE_LIGHT_TICKS);
+
+        for _ in 0..FLARE_LIGHT_TICKS {
+            flare_system(&mut world);
+        }
+        assert_eq!(world.get::<&TorchRange>(player).unwrap().radius, base);
+        assert!(world.get::<&FlareEffect>(player).is_err());
+    }
+}
@@ -1,4 +1,209 @@
-use crate::ecs::components::{CrankTier, GameState};
+use hecs::World;
+
+use crate::ecs::components::{
+    AgentName, AgentState, Building, BuildingEffect, BuildingEffects, BuildingType, ConstructionProgress, CrankTier,
+    GameState, Health, Player, Position, WanderState, WheelPost,
+};
+use crate::protocol::{AgentStateKind, AudioEvent, BuildingTypeKind};
+use crate::ecs::systems::economy::{clamp_balance, record_transaction, take_whole_tokens};
+use crate::game::upgrades::UpgradeId;
+
+/// Most agents that can work the crank at once.
+pub const MAX_CRANK_AGENTS: usize = 3;
+
+/// Assigned agents only add their bonus within this distance (pixels) of
+/// the Token Wheel.
+pub const WHEEL_WORK_RADIUS: f32 = 40.0;
+
+/// How far an agent posted at the wheel strays from it.
+pub const WHEEL_WANDER_RADIUS: f32 = 8.0;
+
+/// Seconds the crank stays locked after hitting max heat.
+pub const OVERHEAT_LOCKOUT_SECS: u32 = 5;
+
+/// [`OVERHEAT_LOCKOUT_SECS`] in ticks at `tick_rate`.
+pub const fn overheat_lockout_ticks(tick_rate: u64) -> u32 {
+    OVERHEAT_LOCKOUT_SECS * tick_rate as u32
+}
+
+/// Damage dealt to a player who keeps hauling on a locked crank.
+pub const OVERHEAT_BURN_DAMAGE: i32 = 2;
+
+/// Ticks between burns while the player holds a locked crank.
+pub const OVERHEAT_BURN_INTERVAL_TICKS: u32 = 20;
+
+/// Fraction of `max_heat` at which the crank starts scorching nearby
+/// buildings.
+pub const OVERHEATING_THRESHOLD: f32 = 0.9;
+
+/// Buildings within this distance (pixels) of the Token Wheel take heat
+/// damage while the crank is overheating.
+pub const OVERHEAT_DAMAGE_RADIUS: f32 = 100.0;
+
+/// Damage per tick to each nearby building while overheating.
+pub const OVERHEAT_BUILDING_DAMAGE: i32 = 1;
+
+/// Tokens an emergency vent costs.
+pub const EMERGENCY_VENT_COST: i64 = 50;
+
+/// Most of `heat_rate` that building effects can shave off.
+pub const MAX_CRANK_HEAT_REDUCTION: f32 = 0.9;
+
+/// Combined `CrankHeatReduction` from completed buildings, capped at
+/// [`MAX_CRANK_HEAT_REDUCTION`].
+pub fn crank_heat_reduction(world: &World) -> f32 {
+    world
+        .query::<(&BuildingEffects, Option<&ConstructionProgress>)>()
+        .with::<&Building>()
+        .iter()
+        .filter(|(_e, (_effects, progress))| progress.is_none_or(|p| p.current >= p.total))
+        .flat_map(|(_e, (effects, _progress))| effects.effects.clone())
+        .map(|effect| match effect {
+            BuildingEffect::CrankHeatReduction(r) => r,
+            _ => 0.0,
+        })
+        .sum::<f32>()
+        .min(MAX_CRANK_HEAT_REDUCTION)
+}
+
+/// Passive tokens per tick each assigned agent adds at `tier`.
+pub fn agent_bonus_per_tick(tier: &CrankTier) -> f64 {
+    match tier {
+        CrankTier::HandCrank => 0.001,
+        CrankTier::GearAssembly => 0.0016,
+        CrankTier::WaterWheel => 0.002,
+        CrankTier::RunicEngine => 0.003,
+    }
+}
+
+/// Position of the Token Wheel building, if there is one.
+fn wheel_position(world: &World) -> Option<(f32, f32)> {
+    world
+        .query::<(&BuildingType, &Position)>()
+        .iter()
+        .find(|(_e, (bt, _pos))| bt.kind == BuildingTypeKind::TokenWheel)
+        .map(|(_e, (_bt, pos))| (pos.x, pos.y))
+}
+
+/// Adds `agent` to the crank's workers and sends it walking to the wheel.
+/// The agent must be Idle, not already assigned, and there must be a free
+/// slot.
+pub fn assign_agent(world: &mut World, game_state: &mut GameState, agent: hecs::Entity) -> Result<(), String> {
+    if !game_state.upgrades.has(UpgradeId::CrankAssignment) {
+        return Err("requires the Crank Assignment upgrade".to_string());
+    }
+    let crank = &mut game_state.crank;
+    if crank.assigned_agents.contains(&agent) {
+        return Err("agent is already assigned to the wheel".to_string());
+    }
+    // Agents that have since died no longer hold a slot.
+    crank.assigned_agents.retain(|&e| world.contains(e));
+    if crank.assigned_agents.len() >= MAX_CRANK_AGENTS {
+        return Err(format!("the wheel already has {} agents", MAX_CRANK_AGENTS));
+    }
+    match world.get::<&AgentState>(agent) {
+        Ok(state) if state.state == AgentStateKind::Idle => {}
+        Ok(_) => return Err("agent must be idle".to_string()),
+        Err(_) => return Err("no such agent".to_string()),
+    }
+    crank.assigned_agents.push(agent);
+
+    // Walk over like a project assignment; arriving pins the agent's home
+    // to the wheel (see `agent_wander_system`).
+    let Some((wx, wy)) = wheel_position(world) else { return Ok(()) };
+    let post = world.get::<&WanderState>(agent).ok().map(|w| WheelPost {
+        prev_home_x: w.home_x,
+        prev_home_y: w.home_y,
+        prev_wander_radius: w.wander_radius,
+    });
+    if let Some(post) = post {
+        let _ = world.insert_one(agent, post);
+        if let Ok(mut wander) = world.get::<&mut WanderState>(agent) {
+            wander.walk_target = Some((wx, wy));
+            wander.walk_ticks = 0;
+            wander.waypoint_x = wx;
+            wander.waypoint_y = wy;
+            wander.pause_remaining = 0;
+        }
+        if let Ok(mut state) = world.get::<&mut AgentState>(agent) {
+            state.state = AgentStateKind::Walking;
+        }
+    }
+    Ok(())
+}
+
+/// Takes `agent` off the wheel, back to Idle at its old home and wander
+/// radius.
+pub fn unassign_agent(world: &mut World, game_state: &mut GameState, agent: hecs::Entity) -> Result<(), String> {
+    if !game_state.crank.assigned_agents.contains(&agent) {
+        return Err("agent is not assigned to the wheel".to_string());
+    }
+    game_state.crank.assigned_agents.retain(|&e| e != agent);
+    release_post(world, agent);
+    if let Ok(mut state) = world.get::<&mut AgentState>(agent) {
+        if matches!(state.state, AgentStateKind::Walking | AgentStateKind::Building) {
+            state.state = AgentStateKind::Idle;
+        }
+    }
+    Ok(())
+}
+
+/// Restores the wander home an agent had before its wheel post.
+fn release_post(world: &mut World, agent: hecs::Entity) {
+    let Ok(post) = world.remove_one::<WheelPost>(agent) else { return };
+    if let Ok(mut wander) = world.get::<&mut WanderState>(agent) {
+        wander.home_x = post.prev_home_x;
+        wander.home_y = post.prev_home_y;
+        wander.wander_radius = post.prev_wander_radius;
+        wander.walk_target = None;
+        wander.walk_ticks = 0;
+    }
+}
+
+/// How many assigned agents are within [`WHEEL_WORK_RADIUS`] of the wheel
+/// -- the count [`crank_system`] pays the agent bonus for.
+pub fn agents_at_wheel(world: &World, game_state: &GameState) -> usize {
+    let Some((wx, wy)) = wheel_position(world) else { return 0 };
+    game_state
+        .crank
+        .assigned_agents
+        .iter()
+        .filter(|&&agent| {
+            world.get::<&Position>(agent).is_ok_and(|pos| {
+                let (dx, dy) = (pos.x - wx, pos.y - wy);
+                dx * dx + dy * dy <= WHEEL_WORK_RADIUS * WHEEL_WORK_RADIUS
+            })
+        })
+        .count()
+}
+
+/// Drops assigned agents that died or went Unresponsive from the wheel.
+/// Returns a log entry for each.
+pub fn wheel_crew_system(world: &mut World, game_state: &mut GameState) -> Vec<String> {
+    let lost: Vec<hecs::Entity> = game_state
+        .crank
+        .assigned_agents
+        .iter()
+        .copied()
+        .filter(|&agent| {
+            world
+                .get::<&AgentState>(agent)
+                .map_or(true, |s| matches!(s.state, AgentStateKind::Unresponsive | AgentStateKind::Dormant))
+        })
+        .collect();
+
+    let mut log_entries = Vec::new();
+    for agent in lost {
+        game_state.crank.assigned_agents.retain(|&e| e != agent);
+        release_post(world, agent);
+        let name = world
+            .get::<&AgentName>(agent)
+            .map(|n| n.name.clone())
+            .unwrap_or_else(|_| "an agent".to_string());
+        log_entries.push(format!("[wheel] {} is down and has left the wheel", name));
+    }
+    log_entries
+}
 
 /// The result of running the crank system for one tick.
 pub struct CrankResult {
@@ -6,19 +211,40 @@ pub struct CrankResult {
     pub tokens_generated: f64,
     /// An optional log message (e.g. overheat warning).
     pub log_message: Option<String>,
+    /// A `CrankTurn` when a whole token rolled over while the player cranks.
+    pub audio_events: Vec<AudioEvent>,
+    /// Damage dealt to the player for cranking during an overheat lockout.
+    pub player_hit_damage: i32,
 }
 
 /// Runs the crank system for a single tick.
 ///
+/// Reaching max heat locks the crank for [`OVERHEAT_LOCKOUT_SECS`]: no
+/// manual or agent tokens, and a player who keeps cranking gets burned.
+/// `CrankHeatReduction` effects from completed buildings lower the heat
+/// gained per tick.
+///
+/// * `world` -- for building effects and the player's health.
 /// * `game_state` -- mutable reference to the global game state.
 /// * `player_cranking` -- whether the player is actively cranking this tick.
+/// * `assigned_agents` -- how many assigned agents are at the wheel (see
+///   [`agents_at_wheel`]).
+/// * `tick_rate` -- ticks per second, to time the lockout.
 ///
 /// Returns a [`CrankResult`] describing how many tokens were generated and any
 /// log messages that should be emitted.
-pub fn crank_system(game_state: &mut GameState, player_cranking: bool, agent_assigned: bool) -> CrankResult {
+pub fn crank_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    player_cranking: bool,
+    assigned_agents: usize,
+    tick_rate: u64,
+) -> CrankResult {
+    let heat_rate = game_state.crank.heat_rate * (1.0 - crank_heat_reduction(world));
     let crank = &mut game_state.crank;
     let mut tokens_generated: f64 = 0.0;
     let mut log_message: Option<String> = None;
+    let mut burn = 0;
 
     // ── Tier-based efficiency multiplier ─────────────────────────────
     let efficiency = match crank.tier {
@@ -29,23 +255,30 @@ pub fn crank_system(game_state: &mut GameState, player_cranking: bool, agent_ass
     };
 
     // ── Manual cranking ──────────────────────────────────────────────
-    if player_cranking {
-        if crank.heat < crank.max_heat {
-            crank.is_cranking = true;
-            crank.heat += crank.heat_rate;
-
-            // Clamp heat to max so we don't exceed the ceiling.
-            if crank.heat > crank.max_heat {
-                crank.heat = crank.max_heat;
+    let overheated = crank.overheated_remaining > 0;
+    if overheated {
+        // Locked out -- the crank cools whether or not it is held.
+        crank.overheated_remaining -= 1;
+        crank.is_cranking = false;
+        crank.heat = (crank.heat - crank.cool_rate).max(0.0);
+        if player_cranking {
+            log_message = Some("overheated \u{2014} cooling required".to_string());
+            if crank.overheated_remaining.is_multiple_of(OVERHEAT_BURN_INTERVAL_TICKS) {
+                burn = OVERHEAT_BURN_DAMAGE;
             }
+        }
+    } else if player_cranking {
+        crank.is_cranking = true;
+        crank.heat += heat_rate;
 
-            // Base rate: 0.02 tokens/tick → ~0.4 tokens/sec at HandCrank
-            let manual_tokens = crank.tokens_per_rotation * efficiency;
-            tokens_generated += manual_tokens;
-        } else {
-            // Overheated -- cannot crank.
-            crank.is_cranking = false;
-            log_message = Some("overheated \u{2014} cooling required".to_string());
+        // Base rate: 0.02 tokens/tick → ~0.4 tokens/sec at HandCrank
+        let manual_tokens = crank.tokens_per_rotation * efficiency;
+        tokens_generated += manual_tokens;
+
+        if crank.heat >= crank.max_heat {
+            crank.heat = crank.max_heat;
+            crank.overheated_remaining = overheat_lockout_ticks(tick_rate);
+            log_message = Some("the crank overheated and locked up".to_string());
         }
     } else {
         // Not cranking -- cool down.
@@ -53,6 +286,8 @@ pub fn crank_system(game_state: &mut GameState, player_cranking: bool, agent_ass
         crank.heat = (crank.heat - crank.cool_rate).max(0.0);
     }
 
+    crank.overheating = crank.heat >= crank.max_heat * OVERHEATING_THRESHOLD;
+
     // ── Passive generation (always runs) ─────────────────────────────
     let passive_tokens = match crank.tier {
         CrankTier::WaterWheel => 0.006,
@@ -62,26 +297,366 @@ pub fn crank_system(game_state: &mut GameState, player_cranking: bool, agent_ass
     tokens_generated += passive_tokens;
 
     // ── Agent-assigned passive generation ──────────────────────
-    if agent_assigned {
-        let agent_bonus = match crank.tier {
-            CrankTier::HandCrank => 0.001,
-            CrankTier::GearAssembly => 0.0016,
-            CrankTier::WaterWheel => 0.002,
-            CrankTier::RunicEngine => 0.003,
-        };
-        tokens_generated += agent_bonus;
+    if !overheated {
+        tokens_generated += agent_bonus_per_tick(&crank.tier) * assigned_agents as f64;
+    }
+
+    // ── Burn a player hauling on a locked crank ──────────────────
+    let mut player_hit_damage = 0;
+    if burn > 0 && !game_state.god_mode {
+        for (_e, health) in world.query_mut::<&mut Health>().with::<&Player>() {
+            health.current -= burn;
+            player_hit_damage += burn;
+        }
     }
 
     // ── Apply to economy balance via fractional accumulator ──────────
-    game_state.economy.fractional += tokens_generated;
-    let whole = game_state.economy.fractional as i64;
+    let whole = take_whole_tokens(&mut game_state.economy.fractional, tokens_generated);
+    let mut audio_events = Vec::new();
     if whole > 0 {
-        game_state.economy.balance += whole;
-        game_state.economy.fractional -= whole as f64;
+        record_transaction(&mut game_state.economy, whole, "crank", game_state.tick);
+        if game_state.crank.is_cranking {
+            audio_events.push(AudioEvent::CrankTurn);
+        }
     }
+    clamp_balance(&mut game_state.economy, game_state.god_mode);
 
     CrankResult {
         tokens_generated,
         log_message,
+        audio_events,
+        player_hit_damage,
+    }
+}
+
+/// Scorches every building within [`OVERHEAT_DAMAGE_RADIUS`] of the Token
+/// Wheel for [`OVERHEAT_BUILDING_DAMAGE`] while the crank is overheating.
+/// Run after [`crank_system`], which sets the flag.
+///
+/// Returns a warning log entry for each tick the crank sits at max heat.
+pub fn crank_damage_system(world: &mut World, game_state: &GameState) -> Vec<String> {
+    let crank = &game_state.crank;
+    if !crank.overheating {
+        return Vec::new();
+    }
+
+    let wheel = world
+        .query::<(&BuildingType, &Position)>()
+        .iter()
+        .find(|(_e, (bt, _pos))| bt.kind == BuildingTypeKind::TokenWheel)
+        .map(|(e, (_bt, pos))| (e, pos.x, pos.y));
+    if let Some((wheel, wx, wy)) = wheel {
+        for (e, (pos, health)) in world.query_mut::<(&Position, &mut Health)>().with::<&Building>() {
+            if e == wheel || health.current <= 0 {
+                continue;
+            }
+            let (dx, dy) = (pos.x - wx, pos.y - wy);
+            if dx * dx + dy * dy <= OVERHEAT_DAMAGE_RADIUS * OVERHEAT_DAMAGE_RADIUS {
+                health.current = (health.current - OVERHEAT_BUILDING_DAMAGE).max(0);
+            }
+        }
+    }
+
+    if crank.heat >= crank.max_heat {
+        vec!["the wheel is at max heat and scorching nearby buildings".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Dumps all the crank's heat for [`EMERGENCY_VENT_COST`] tokens. Returns
+/// the log entry.
+pub fn emergency_vent(game_state: &mut GameState) -> Result<String, String> {
+    if game_state.crank.heat <= 0.0 {
+        return Err("the crank is already cool".to_string());
+    }
+    if game_state.economy.balance < EMERGENCY_VENT_COST {
+        return Err(format!(
+            "not enough tokens: need {}, have {}",
+            EMERGENCY_VENT_COST, game_state.economy.balance
+        ));
+    }
+    record_transaction(&mut game_state.economy, -EMERGENCY_VENT_COST, "emergency vent", game_state.tick);
+    game_state.crank.heat = 0.0;
+    game_state.crank.overheating = false;
+    Ok("[wheel] emergency vent: the crank is cool".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_TICK_RATE_HZ;
+    use crate::ecs::world::create_world;
+
+    const OVERHEAT_LOCKOUT_TICKS: u32 = overheat_lockout_ticks(DEFAULT_TICK_RATE_HZ);
+
+    fn overheat(world: &mut World, game_state: &mut GameState) {
+        game_state.crank.heat = game_state.crank.max_heat - game_state.crank.heat_rate;
+        crank_system(world, game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+        assert_eq!(game_state.crank.overheated_remaining, OVERHEAT_LOCKOUT_TICKS);
+    }
+
+    fn player_health(world: &World) -> i32 {
+        world.query::<&Health>().with::<&Player>().iter().next().unwrap().1.current
+    }
+
+    fn idle_agents(world: &World) -> Vec<hecs::Entity> {
+        world
+            .query::<&AgentState>()
+            .with::<&AgentName>()
+            .iter()
+            .filter(|(_e, s)| s.state == AgentStateKind::Idle)
+            .map(|(e, _s)| e)
+            .collect()
+    }
+
+    #[test]
+    fn sub_token_cranking_pays_exactly_over_time() {
+        let (mut world, mut game_state) = create_world();
+        game_state.crank.tokens_per_rotation = 0.003;
+        game_state.crank.heat_rate = 0.0;
+        let start = game_state.economy.balance;
+
+        for _ in 0..1000 {
+            crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+        }
+        assert_eq!(game_state.economy.balance, start + 3);
+    }
+
+    #[test]
+    fn overheating_locks_the_crank_for_the_full_lockout() {
+        let (mut world, mut game_state) = create_world();
+        overheat(&mut world, &mut game_state);
+
+        for _ in 0..OVERHEAT_LOCKOUT_TICKS {
+            let result = crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+            assert!(!game_state.crank.is_cranking);
+            assert_eq!(result.tokens_generated, 0.0);
+        }
+        // Heat fell during the lockout, but only now can the crank turn again.
+        assert!(game_state.crank.heat < game_state.crank.max_heat);
+        assert_eq!(game_state.crank.overheated_remaining, 0);
+        assert!(crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ).tokens_generated > 0.0);
+        assert!(game_state.crank.is_cranking);
+    }
+
+    #[test]
+    fn holding_a_locked_crank_burns_the_player() {
+        let (mut world, mut game_state) = create_world();
+        let start = player_health(&world);
+        overheat(&mut world, &mut game_state);
+
+        let burned: i32 = (0..OVERHEAT_LOCKOUT_TICKS)
+            .map(|_| crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ).player_hit_damage)
+            .sum();
+        let bursts = (OVERHEAT_LOCKOUT_TICKS / OVERHEAT_BURN_INTERVAL_TICKS) as i32;
+        assert_eq!(burned, bursts * OVERHEAT_BURN_DAMAGE);
+        assert_eq!(player_health(&world), start - burned);
+
+        // Letting go during the lockout is safe.
+        overheat(&mut world, &mut game_state);
+        for _ in 0..OVERHEAT_LOCKOUT_TICKS {
+            assert_eq!(crank_system(&mut world, &mut game_state, false, 0, DEFAULT_TICK_RATE_HZ).player_hit_damage, 0);
+        }
+    }
+
+    #[test]
+    fn heat_reduction_effects_stack_up_to_the_cap() {
+        let (mut world, mut game_state) = create_world();
+        let reducer = |r| (Building, BuildingEffects { effects: vec![BuildingEffect::CrankHeatReduction(r)] });
+        world.spawn(reducer(0.25));
+        world.spawn(reducer(0.25));
+        // Still under construction: no effect yet.
+        world.spawn((
+            Building,
+            BuildingEffects { effects: vec![BuildingEffect::CrankHeatReduction(0.25)] },
+            ConstructionProgress { current: 0.0, total: 10.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ));
+        assert_eq!(crank_heat_reduction(&world), 0.5);
+
+        crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+        assert_eq!(game_state.crank.heat, game_state.crank.heat_rate * 0.5);
+
+        world.spawn(reducer(1.0));
+        assert_eq!(crank_heat_reduction(&world), MAX_CRANK_HEAT_REDUCTION);
+    }
+
+    #[test]
+    fn agents_earn_nothing_while_overheated() {
+        let (mut world, mut game_state) = create_world();
+        overheat(&mut world, &mut game_state);
+        assert_eq!(crank_system(&mut world, &mut game_state, false, MAX_CRANK_AGENTS, DEFAULT_TICK_RATE_HZ).tokens_generated, 0.0);
+
+        game_state.crank.overheated_remaining = 0;
+        assert!(crank_system(&mut world, &mut game_state, false, MAX_CRANK_AGENTS, DEFAULT_TICK_RATE_HZ).tokens_generated > 0.0);
+    }
+
+    #[test]
+    fn three_agents_triple_the_bonus() {
+        let (mut world, mut game_state) = create_world();
+        let one = crank_system(&mut world, &mut game_state, false, 1, DEFAULT_TICK_RATE_HZ).tokens_generated;
+        let three = crank_system(&mut world, &mut game_state, false, 3, DEFAULT_TICK_RATE_HZ).tokens_generated;
+        assert!(one > 0.0);
+        assert!((three - 3.0 * one).abs() < 1e-12);
+    }
+
+    #[test]
+    fn assignment_is_validated_and_capped() {
+        let (mut world, mut game_state) = create_world();
+        let mut agents = idle_agents(&world);
+        while agents.len() < MAX_CRANK_AGENTS + 1 {
+            let e = world.spawn((AgentName { name: format!("extra{}", agents.len()) }, AgentState { state: AgentStateKind::Idle }));
+            agents.push(e);
+        }
+
+        assert!(assign_agent(&mut world, &mut game_state, agents[0]).is_err());
+        game_state.upgrades.purchased.insert(UpgradeId::CrankAssignment);
+
+        for &agent in &agents[..MAX_CRANK_AGENTS] {
+            assign_agent(&mut world, &mut game_state, agent).unwrap();
+        }
+        assert!(assign_agent(&mut world, &mut game_state, agents[0]).is_err());
+        assert!(assign_agent(&mut world, &mut game_state, agents[MAX_CRANK_AGENTS]).is_err());
+        assert_eq!(game_state.crank.assigned_agents, agents[..MAX_CRANK_AGENTS].to_vec());
+
+        // A dead agent frees its slot.
+        world.despawn(agents[1]).unwrap();
+        assign_agent(&mut world, &mut game_state, agents[MAX_CRANK_AGENTS]).unwrap();
+        assert_eq!(game_state.crank.assigned_agents.len(), MAX_CRANK_AGENTS);
+    }
+
+    #[test]
+    fn crank_turn_plays_when_a_cranked_token_rolls_over() {
+        let (mut world, mut game_state) = create_world();
+        let mut turns = 0;
+        for _ in 0..60 {
+            let result = crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+            turns += result.audio_events.iter().filter(|e| matches!(e, AudioEvent::CrankTurn)).count();
+        }
+        // 0.02 tokens a tick at the HandCrank: a token every 50 ticks.
+        assert_eq!(turns, 1);
+        assert_eq!(game_state.economy.balance, 1);
+
+        // Passive income rolling over while idle is silent.
+        game_state.crank.tier = CrankTier::RunicEngine;
+        for _ in 0..100 {
+            assert!(crank_system(&mut world, &mut game_state, false, 0, DEFAULT_TICK_RATE_HZ).audio_events.is_empty());
+        }
+        assert!(game_state.economy.balance > 1);
+    }
+
+    #[test]
+    fn overheating_scorches_buildings_near_the_wheel() {
+        let (mut world, mut game_state) = create_world();
+        let (wx, wy) = {
+            let mut query = world.query::<(&BuildingType, &Position)>();
+            let (_e, (_bt, pos)) = query.iter().find(|(_e, (bt, _))| bt.kind == BuildingTypeKind::TokenWheel).unwrap();
+            (pos.x, pos.y)
+        };
+        let near = world.spawn((Building, Position { x: wx + 60.0, y: wy }, Health { current: 50, max: 50 }));
+        let far = world.spawn((Building, Position { x: wx + 300.0, y: wy }, Health { current: 50, max: 50 }));
+
+        // Below the threshold nothing burns.
+        crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+        assert!(!game_state.crank.overheating);
+        assert!(crank_damage_system(&mut world, &game_state).is_empty());
+
+        // Hitting max heat warns; the lockout then cools it, still hot
+        // enough to burn.
+        game_state.crank.heat = game_state.crank.max_heat;
+        for tick in 0..5 {
+            crank_system(&mut world, &mut game_state, true, 0, DEFAULT_TICK_RATE_HZ);
+            assert!(game_state.crank.overheating);
+            assert_eq!(crank_damage_system(&mut world, &game_state).len(), usize::from(tick == 0));
+        }
+        assert_eq!(world.get::<&Health>(near).unwrap().current, 45);
+        assert_eq!(world.get::<&Health>(far).unwrap().current, 50);
+    }
+
+    #[test]
+    fn emergency_vent_costs_tokens_and_dumps_heat() {
+        let (_world, mut game_state) = create_world();
+        game_state.crank.heat = game_state.crank.max_heat;
+        game_state.crank.overheating = true;
+        game_state.economy.balance = EMERGENCY_VENT_COST - 1;
+        assert!(emergency_vent(&mut game_state).is_err());
+        assert_eq!(game_state.crank.heat, game_state.crank.max_heat);
+
+        game_state.economy.balance = EMERGENCY_VENT_COST;
+        emergency_vent(&mut game_state).unwrap();
+        assert_eq!(game_state.economy.balance, 0);
+        assert_eq!(game_state.crank.heat, 0.0);
+        assert!(!game_state.crank.overheating);
+    }
+
+    fn wheel_pos(world: &World) -> (f32, f32) {
+        wheel_position(world).unwrap()
+    }
+
+    #[test]
+    fn agents_only_crank_once_they_reach_the_wheel() {
+        use crate::ecs::systems::agent_wander::agent_wander_system;
+
+        let (mut world, mut game_state) = create_world();
+        game_state.upgrades.purchased.insert(UpgradeId::CrankAssignment);
+        let (wx, wy) = wheel_pos(&world);
+        let agent = crate::game::agents::spawn_scout(&mut world, "sol", wx + 120.0, wy);
+        let home = {
+            let wander = world.get::<&WanderState>(agent).unwrap();
+            (wander.home_x, wander.home_y, wander.wander_radius)
+        };
+
+        assign_agent(&mut world, &mut game_state, agent).unwrap();
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Walking);
+        assert_eq!(agents_at_wheel(&world, &game_state), 0);
+
+        for _ in 0..2000 {
+            agent_wander_system(&mut world);
+            if world.get::<&AgentState>(agent).unwrap().state != AgentStateKind::Walking {
+                break;
+            }
+        }
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Building);
+        {
+            let wander = world.get::<&WanderState>(agent).unwrap();
+            assert_eq!((wander.home_x, wander.home_y, wander.wander_radius), (wx, wy, WHEEL_WANDER_RADIUS));
+        }
+        // Pinned: wandering keeps it in range.
+        for _ in 0..500 {
+            agent_wander_system(&mut world);
+            assert_eq!(agents_at_wheel(&world, &game_state), 1);
+        }
+
+        // Dragged away, it stops counting.
+        world.get::<&mut Position>(agent).unwrap().x = wx + WHEEL_WORK_RADIUS + 1.0;
+        assert_eq!(agents_at_wheel(&world, &game_state), 0);
+
+        unassign_agent(&mut world, &mut game_state, agent).unwrap();
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Idle);
+        let wander = world.get::<&WanderState>(agent).unwrap();
+        assert_eq!((wander.home_x, wander.home_y, wander.wander_radius), home);
+        assert!(!world.satisfies::<&WheelPost>(agent).unwrap());
+    }
+
+    #[test]
+    fn downed_and_dead_agents_leave_the_wheel() {
+        let (mut world, mut game_state) = create_world();
+        game_state.upgrades.purchased.insert(UpgradeId::CrankAssignment);
+        let mut agents = idle_agents(&world);
+        while agents.len() < 3 {
+            let e = world.spawn((AgentName { name: format!("extra{}", agents.len()) }, AgentState { state: AgentStateKind::Idle }));
+            agents.push(e);
+        }
+        for &agent in &agents[..3] {
+            assign_agent(&mut world, &mut game_state, agent).unwrap();
+        }
+        assert!(wheel_crew_system(&mut world, &mut game_state).is_empty());
+
+        world.get::<&mut AgentState>(agents[0]).unwrap().state = AgentStateKind::Unresponsive;
+        world.despawn(agents[1]).unwrap();
+        let log = wheel_crew_system(&mut world, &mut game_state);
+        assert_eq!(log.len(), 2);
+        assert_eq!(game_state.crank.assigned_agents, vec![agents[2]]);
+        assert!(!world.satisfies::<&WheelPost>(agents[0]).unwrap());
     }
 }
@@ -0,0 +1,475 @@
+use std::collections::HashSet;
+
+use hecs::World;
+use rand::Rng;
+
+use crate::ecs::components::{
+    Agent, AgentName, AgentState, Assignment, Discovery, GameState, Position, Scout, Specialization,
+};
+use crate::ecs::systems::nest::{spawn_nest, NEST_SPAWN_OFFSET};
+use crate::ecs::systems::spawn::spawn_rogue;
+use crate::game::agents::{spawn_scout, EXPLORER_PICKUP_RANGE_MULT};
+use crate::game::exploration::{interact_with_discovery, scatter_discoveries, spawn_discovery, DiscoveryKind};
+use crate::game::tilemap::{TileMap, CHUNK_SIZE, TILE_SIZE};
+use crate::protocol::{AgentSpecialization, AgentStateKind, RogueTypeKind, TaskAssignment};
+
+/// World seed for discovery scattering.
+const DISCOVERY_SEED: u32 = 31337;
+
+/// Chunks within this many chunks of the player get populated.
+const DISCOVERY_CHUNK_RADIUS: i32 = 1;
+
+/// Maximum distance (pixels) at which the player can interact with a discovery.
+pub const DISCOVERY_INTERACT_RANGE: f32 = 24.0;
+
+/// Maximum distance (pixels) for an `InteractDiscovery` aimed at a specific
+/// discovery; clicking is less precise than standing on top of it.
+pub const DISCOVERY_TARGET_RANGE: f32 = 40.0;
+
+/// Each exploring scout turns up a token cache this often.
+pub const SCOUT_FIND_INTERVAL_TICKS: u64 = 300;
+
+/// Result returned by [`interact_system`].
+pub struct InteractResult {
+    pub log_entries: Vec<String>,
+}
+
+/// Scatters discoveries into every chunk around the player that hasn't been
+/// populated yet.  Populated chunks are remembered in
+/// `game_state.populated_chunks` so each chunk is only scattered once.
+///
+/// A scattered rogue nest becomes a live [`RogueNest`](crate::ecs::components::RogueNest)
+/// entity rather than a discovery, unless the chunk's nest was already
+/// destroyed.
+pub fn discovery_spawner_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    player_x: f32,
+    player_y: f32,
+) {
+    let (pcx, pcy) = TileMap::world_to_chunk(player_x, player_y);
+
+    for cx in pcx - DISCOVERY_CHUNK_RADIUS..=pcx + DISCOVERY_CHUNK_RADIUS {
+        for cy in pcy - DISCOVERY_CHUNK_RADIUS..=pcy + DISCOVERY_CHUNK_RADIUS {
+            if !game_state.populated_chunks.insert((cx, cy)) {
+                continue;
+            }
+
+            let discoveries = scatter_discoveries(
+                cx,
+                cy,
+                DISCOVERY_SEED,
+                &game_state.phase,
+                game_state.mums_card_found,
+            );
+            for (x, y, kind) in discoveries {
+                match kind {
+                    DiscoveryKind::RogueNest if game_state.destroyed_nests.contains(&(cx, cy)) => {}
+                    DiscoveryKind::RogueNest => {
+                        spawn_nest(world, x, y);
+                    }
+                    kind => {
+                        spawn_discovery(world, x, y, kind);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The closest discovery the player hasn't interacted with yet that lies
+/// within [`DISCOVERY_INTERACT_RANGE`] of `(x, y)`.
+pub fn nearest_discovery(world: &World, x: f32, y: f32) -> Option<hecs::Entity> {
+    nearest_discovery_within(world, x, y, DISCOVERY_INTERACT_RANGE)
+}
+
+/// The closest discovery the player hasn't interacted with yet that lies
+/// within `range` of `(x, y)`.
+pub fn nearest_discovery_within(world: &World, x: f32, y: f32, range: f32) -> Option<hecs::Entity> {
+    let range_sq = range * range;
+
+    world
+        .query::<(&Discovery, &Position)>()
+        .iter()
+        .filter(|(_, (disc, _))| !disc.interacted)
+        .map(|(e, (_, pos))| {
+            let dx = pos.x - x;
+            let dy = pos.y - y;
+            (e, dx * dx + dy * dy)
+        })
+        .filter(|(_, dist_sq)| *dist_sq <= range_sq)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e)
+}
+
+/// Handles a player `Interact` action at `(x, y)`.
+///
+/// Triggers the nearest discovery in range, applies its effect, and despawns
+/// it.  Finding a mum's card sets `game_state.mums_card_found` so no more are
+/// scattered, a rogue nest releases 2–3 swarm rogues around itself, and a
+/// survivor joins as a scout.
+pub fn interact_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    x: f32,
+    y: f32,
+    rng: &mut impl Rng,
+) -> InteractResult {
+    let Some(entity) = nearest_discovery(world, x, y) else {
+        return InteractResult { log_entries: Vec::new() };
+    };
+    trigger_discovery(world, game_state, entity, rng)
+}
+
+/// Handles a player `InteractDiscovery` action at `(x, y)`.
+///
+/// Triggers the discovery `entity_id` if it is within
+/// [`DISCOVERY_TARGET_RANGE`], otherwise the nearest discovery in that range.
+pub fn interact_discovery_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    entity_id: u64,
+    x: f32,
+    y: f32,
+    rng: &mut impl Rng,
+) -> InteractResult {
+    let in_range = |pos: &Position| {
+        let (dx, dy) = (pos.x - x, pos.y - y);
+        dx * dx + dy * dy <= DISCOVERY_TARGET_RANGE * DISCOVERY_TARGET_RANGE
+    };
+    let targeted = hecs::Entity::from_bits(entity_id).filter(|&e| {
+        world
+            .query_one::<(&Discovery, &Position)>(e)
+            .ok()
+            .and_then(|mut q| q.get().map(|(disc, pos)| !disc.interacted && in_range(pos)))
+            .unwrap_or(false)
+    });
+
+    let Some(entity) = targeted.or_else(|| nearest_discovery_within(world, x, y, DISCOVERY_TARGET_RANGE))
+    else {
+        return InteractResult { log_entries: Vec::new() };
+    };
+    trigger_discovery(world, game_state, entity, rng)
+}
+
+/// Handles a player `InteractSurvivor` action at `(x, y)`: recruits the
+/// survivor `entity_id` as a scout if it is within [`DISCOVERY_TARGET_RANGE`].
+///
+/// # Errors
+///
+/// Returns an error if the entity is not an NPC survivor or is out of range.
+pub fn interact_survivor_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    entity_id: u64,
+    x: f32,
+    y: f32,
+    rng: &mut impl Rng,
+) -> Result<InteractResult, String> {
+    let entity = hecs::Entity::from_bits(entity_id).ok_or_else(|| "Unknown entity".to_string())?;
+    let (is_survivor, dist_sq) = {
+        let mut query = world
+            .query_one::<(&Discovery, &Position)>(entity)
+            .map_err(|_| "Entity is not a discovery".to_string())?;
+        let (disc, pos) = query.get().ok_or_else(|| "Entity is not a discovery".to_string())?;
+        let is_survivor = !disc.interacted && matches!(disc.kind, DiscoveryKind::NpcSurvivor { .. });
+        (is_survivor, (pos.x - x).powi(2) + (pos.y - y).powi(2))
+    };
+    if !is_survivor {
+        return Err("Not a survivor".to_string());
+    }
+    if dist_sq > DISCOVERY_TARGET_RANGE * DISCOVERY_TARGET_RANGE {
+        return Err("Survivor is too far away".to_string());
+    }
+    Ok(trigger_discovery(world, game_state, entity, rng))
+}
+
+/// Every [`SCOUT_FIND_INTERVAL_TICKS`], each scout assigned to `Explore`
+/// turns up a small token cache somewhere in a random `revealed` chunk.
+pub fn scout_exploration_system(
+    world: &mut World,
+    tick: u64,
+    revealed: &HashSet<(i32, i32)>,
+    rng: &mut impl Rng,
+) -> InteractResult {
+    let mut log_entries = Vec::new();
+    if !tick.is_multiple_of(SCOUT_FIND_INTERVAL_TICKS) || revealed.is_empty() {
+        return InteractResult { log_entries };
+    }
+
+    let scouts: Vec<String> = world
+        .query::<hecs::With<(&Assignment, &AgentName), (&Agent, &Scout)>>()
+        .iter()
+        .filter(|(_e, (assignment, _name))| assignment.task == TaskAssignment::Explore)
+        .map(|(_e, (_assignment, name))| name.name.clone())
+        .collect();
+
+    // Sorted so the pick only depends on the rng, not on hash order.
+    let mut chunks: Vec<(i32, i32)> = revealed.iter().copied().collect();
+    chunks.sort_unstable();
+    let chunk_world_size = CHUNK_SIZE as f32 * TILE_SIZE;
+    for name in scouts {
+        let (cx, cy) = chunks[rng.gen_range(0..chunks.len())];
+        let x = (cx as f32 + rng.gen::<f32>()) * chunk_world_size;
+        let y = (cy as f32 + rng.gen::<f32>()) * chunk_world_size;
+        let amount = rng.gen_range(5..15);
+        spawn_discovery(world, x, y, DiscoveryKind::TokenCache { amount });
+        log_entries.push(format!("[exp] scout {} marked a token cache ({} tokens)", name, amount));
+    }
+    InteractResult { log_entries }
+}
+
+/// Exploring agents pick up the nearest discovery within
+/// [`DISCOVERY_INTERACT_RANGE`] of them, as if the player had interacted with
+/// it. Explorers reach [`EXPLORER_PICKUP_RANGE_MULT`] times further.
+pub fn agent_discovery_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    rng: &mut impl Rng,
+) -> InteractResult {
+    let explorers: Vec<(f32, f32, f32, String)> = world
+        .query::<hecs::With<(&AgentState, &Position, &AgentName, Option<&Specialization>), &Agent>>()
+        .iter()
+        .filter(|(_e, (state, _pos, _name, _spec))| state.state == AgentStateKind::Exploring)
+        .map(|(_e, (_state, pos, name, spec))| {
+            let range = if spec.is_some_and(|s| s.role == AgentSpecialization::Explorer) {
+                DISCOVERY_INTERACT_RANGE * EXPLORER_PICKUP_RANGE_MULT
+            } else {
+                DISCOVERY_INTERACT_RANGE
+            };
+            (pos.x, pos.y, range, name.name.clone())
+        })
+        .collect();
+
+    let mut log_entries = Vec::new();
+    for (x, y, range, name) in explorers {
+        let Some(entity) = nearest_discovery_within(world, x, y, range) else {
+            continue;
+        };
+        log_entries.push(format!("[{}] found something while exploring", name));
+        log_entries.extend(trigger_discovery(world, game_state, entity, rng).log_entries);
+    }
+    InteractResult { log_entries }
+}
+
+/// Marks `entity` as interacted, applies its effect, and despawns it.
+fn trigger_discovery(
+    world: &mut World,
+    game_state: &mut GameState,
+    entity: hecs::Entity,
+    rng: &mut impl Rng,
+) -> InteractResult {
+    let (kind, x, y) = {
+        let mut disc = world.get::<&mut Discovery>(entity).unwrap();
+        disc.interacted = true;
+        let pos = world.get::<&Position>(entity).unwrap();
+        (disc.kind.clone(), pos.x, pos.y)
+    };
+    let _ = world.despawn(entity);
+
+    let mut log_entries = interact_with_discovery(&kind, game_state);
+
+    match kind {
+        DiscoveryKind::MumsCard { .. } => {
+            game_state.mums_card_found = true;
+        }
+        DiscoveryKind::RogueNest => {
+            let count = rng.gen_range(2..=3);
+            for i in 0..count {
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                spawn_rogue(
+                    world,
+                    x + angle.cos() * NEST_SPAWN_OFFSET,
+                    y + angle.sin() * NEST_SPAWN_OFFSET,
+                    RogueTypeKind::Swarm,
+                );
+            }
+        }
+        DiscoveryKind::NpcSurvivor { name } => {
+            spawn_scout(world, &name, x, y);
+            log_entries.push(format!("[exp] {} joins you as a scout.", name));
+        }
+        _ => {}
+    }
+
+    InteractResult { log_entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::Rogue;
+    use crate::ecs::world::create_world;
+    use crate::game::exploration::CardVariant;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn is_mums_card(kind: &DiscoveryKind) -> bool {
+        matches!(kind, DiscoveryKind::MumsCard { .. })
+    }
+
+    #[test]
+    fn interact_picks_nearest_in_range() {
+        let (mut world, mut game_state) = create_world();
+        let far = spawn_discovery(&mut world, 20.0, 0.0, DiscoveryKind::TokenCache { amount: 10 });
+        let near = spawn_discovery(&mut world, 5.0, 5.0, DiscoveryKind::TokenCache { amount: 20 });
+        spawn_discovery(&mut world, 40.0, 0.0, DiscoveryKind::TokenCache { amount: 30 });
+
+        assert_eq!(nearest_discovery(&world, 0.0, 0.0), Some(near));
+
+        let balance = game_state.economy.balance;
+        let mut rng = StdRng::seed_from_u64(1);
+        let result = interact_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert_eq!(game_state.economy.balance, balance + 20);
+        assert!(result.log_entries[0].contains("+20"));
+        assert!(!world.contains(near));
+
+        assert_eq!(nearest_discovery(&world, 0.0, 0.0), Some(far));
+        interact_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert_eq!(nearest_discovery(&world, 0.0, 0.0), None);
+        assert!(interact_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng).log_entries.is_empty());
+    }
+
+    #[test]
+    fn interacted_discoveries_are_skipped() {
+        let mut world = World::new();
+        let e = spawn_discovery(&mut world, 0.0, 0.0, DiscoveryKind::McpRuin);
+        world.get::<&mut Discovery>(e).unwrap().interacted = true;
+
+        assert_eq!(nearest_discovery(&world, 0.0, 0.0), None);
+    }
+
+    #[test]
+    fn mums_card_sets_cooldown_flag() {
+        let (mut world, mut game_state) = create_world();
+        spawn_discovery(&mut world, 0.0, 0.0, DiscoveryKind::MumsCard { variant: CardVariant::Standard });
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert!(!game_state.mums_card_found);
+        interact_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert!(game_state.mums_card_found);
+
+        // With the flag set, a chunk that would hold a card scatters none.
+        let chunk = (1..100)
+            .flat_map(|cx| (1..100).map(move |cy| (cx, cy)))
+            .find(|&(cx, cy)| {
+                scatter_discoveries(cx, cy, DISCOVERY_SEED, &game_state.phase, false)
+                    .iter()
+                    .any(|(_, _, k)| is_mums_card(k))
+            })
+            .expect("some chunk should hold a card");
+        let chunk_extent = crate::game::tilemap::CHUNK_SIZE as f32 * crate::game::tilemap::TILE_SIZE;
+        let (px, py) = ((chunk.0 as f32 + 0.5) * chunk_extent, (chunk.1 as f32 + 0.5) * chunk_extent);
+
+        discovery_spawner_system(&mut world, &mut game_state, px, py);
+        assert!(game_state.populated_chunks.contains(&chunk));
+        assert!(!world.query::<&Discovery>().iter().any(|(_, d)| is_mums_card(&d.kind)));
+    }
+
+    #[test]
+    fn rogue_nest_releases_rogues() {
+        let (mut world, mut game_state) = create_world();
+        let rogues_before = world.query::<&Rogue>().iter().count();
+        spawn_discovery(&mut world, 10.0, 0.0, DiscoveryKind::RogueNest);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        interact_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        let spawned = world.query::<&Rogue>().iter().count() - rogues_before;
+        assert!((2..=3).contains(&spawned));
+    }
+
+    #[test]
+    fn interact_discovery_prefers_the_target_within_forty_pixels() {
+        let (mut world, mut game_state) = create_world();
+        let near = spawn_discovery(&mut world, 30.0, 0.0, DiscoveryKind::TokenCache { amount: 10 });
+        let target = spawn_discovery(&mut world, 0.0, 35.0, DiscoveryKind::PowerCore { boost: 0.01 });
+        let too_far = spawn_discovery(&mut world, 45.0, 0.0, DiscoveryKind::TokenCache { amount: 30 });
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let before = game_state.crank.tokens_per_rotation;
+        let result = interact_discovery_system(&mut world, &mut game_state, target.to_bits().into(), 0.0, 0.0, &mut rng);
+        assert!(result.log_entries[0].contains("power core"));
+        assert_eq!(game_state.crank.tokens_per_rotation, before + 0.01);
+        assert!(!world.contains(target));
+
+        // An out-of-range target falls back to the nearest discovery in range.
+        interact_discovery_system(&mut world, &mut game_state, too_far.to_bits().into(), 0.0, 0.0, &mut rng);
+        assert!(!world.contains(near));
+        assert!(world.contains(too_far));
+        assert!(interact_discovery_system(&mut world, &mut game_state, too_far.to_bits().into(), 0.0, 0.0, &mut rng)
+            .log_entries
+            .is_empty());
+    }
+
+    #[test]
+    fn explorers_pick_up_discoveries_from_further_away() {
+        let (_w, mut game_state) = create_world();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut world = World::new();
+        let agent = world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Exploring },
+            Position { x: 0.0, y: 0.0 },
+            AgentName { name: "sol".to_string() },
+        ));
+        let cache = spawn_discovery(&mut world, 30.0, 0.0, DiscoveryKind::TokenCache { amount: 10 });
+
+        assert!(agent_discovery_system(&mut world, &mut game_state, &mut rng).log_entries.is_empty());
+        assert!(world.contains(cache));
+
+        world.insert_one(agent, Specialization { role: AgentSpecialization::Explorer }).unwrap();
+        let balance = game_state.economy.balance;
+        let result = agent_discovery_system(&mut world, &mut game_state, &mut rng);
+        assert!(result.log_entries[0].contains("sol"));
+        assert!(!world.contains(cache));
+        assert_eq!(game_state.economy.balance, balance + 10);
+    }
+
+    #[test]
+    fn interacting_with_a_survivor_recruits_one_scout() {
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        let survivor = spawn_discovery(&mut world, 10.0, 0.0, DiscoveryKind::NpcSurvivor { name: "marguerite".to_string() });
+        let cache = spawn_discovery(&mut world, 5.0, 0.0, DiscoveryKind::TokenCache { amount: 10 });
+
+        assert!(interact_survivor_system(&mut world, &mut game_state, cache.to_bits().into(), 0.0, 0.0, &mut rng).is_err());
+        assert!(interact_survivor_system(&mut world, &mut game_state, survivor.to_bits().into(), 100.0, 0.0, &mut rng).is_err());
+        assert_eq!(world.query::<&Scout>().iter().count(), 0);
+
+        interact_survivor_system(&mut world, &mut game_state, survivor.to_bits().into(), 0.0, 0.0, &mut rng).unwrap();
+        let scouts: Vec<String> = world
+            .query::<hecs::With<&AgentName, (&Agent, &Scout)>>()
+            .iter()
+            .map(|(_e, name)| name.name.clone())
+            .collect();
+        assert_eq!(scouts, vec!["marguerite".to_string()]);
+        assert!(!world.contains(survivor));
+    }
+
+    #[test]
+    fn exploring_scouts_turn_up_token_caches() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        let scout = spawn_scout(&mut world, "marguerite", 0.0, 0.0);
+        let revealed: HashSet<(i32, i32)> = [(2, 3)].into_iter().collect();
+        let caches = |world: &World| world.query::<&Discovery>().iter().count();
+
+        // Idle scouts don't find anything.
+        scout_exploration_system(&mut world, SCOUT_FIND_INTERVAL_TICKS, &revealed, &mut rng);
+        assert_eq!(caches(&world), 0);
+
+        world.insert_one(scout, Assignment { task: TaskAssignment::Explore }).unwrap();
+        scout_exploration_system(&mut world, SCOUT_FIND_INTERVAL_TICKS + 1, &revealed, &mut rng);
+        assert_eq!(caches(&world), 0);
+        scout_exploration_system(&mut world, SCOUT_FIND_INTERVAL_TICKS * 2, &revealed, &mut rng);
+        assert_eq!(caches(&world), 1);
+
+        let chunk_world_size = CHUNK_SIZE as f32 * TILE_SIZE;
+        for (_e, (disc, pos)) in world.query::<(&Discovery, &Position)>().iter() {
+            assert!(matches!(disc.kind, DiscoveryKind::TokenCache { amount } if (5..15).contains(&amount)));
+            assert_eq!(TileMap::world_to_chunk(pos.x, pos.y), (2, 3));
+            assert!(pos.x >= 2.0 * chunk_world_size);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use hecs::World;
+
+use crate::ecs::components::{DodgeState, Facing, Player, Position};
+use crate::game::collision;
+
+/// Ticks the roll carries the player.
+pub const DODGE_TICKS: u32 = 8;
+
+/// Ticks of invulnerability at the start of a roll.
+pub const DODGE_IFRAMES: u32 = 6;
+
+/// Pixels moved per tick while rolling.
+pub const DODGE_SPEED: f32 = 6.0;
+
+/// Ticks between the start of one dodge and the next.
+pub const DODGE_COOLDOWN_TICKS: u32 = 40;
+
+/// Start a dodge roll in `(dx, dy)`, or along the player's facing if that is
+/// zero. Returns `false` if the previous dodge is still cooling down.
+pub fn start_dodge(world: &mut World, dx: f32, dy: f32) -> bool {
+    let Some((player, facing)) = world
+        .query::<&Facing>()
+        .with::<&Player>()
+        .iter()
+        .next()
+        .map(|(e, f)| (e, (f.dx, f.dy)))
+    else {
+        return false;
+    };
+    if dodge_cooldown(world, player) > 0 {
+        return false;
+    }
+
+    let (dx, dy) = if dx * dx + dy * dy > 0.0 { (dx, dy) } else { facing };
+    let len = (dx * dx + dy * dy).sqrt();
+    if len == 0.0 {
+        return false;
+    }
+
+    let _ = world.insert_one(player, DodgeState {
+        ticks_remaining: DODGE_TICKS,
+        dx: dx / len,
+        dy: dy / len,
+        iframes: DODGE_IFRAMES,
+        cooldown_remaining: DODGE_COOLDOWN_TICKS,
+    });
+    true
+}
+
+/// Ticks until `entity` may dodge again (0 if it is not dodging).
+pub fn dodge_cooldown(world: &World, entity: hecs::Entity) -> u32 {
+    world.get::<&DodgeState>(entity).map_or(0, |d| d.cooldown_remaining)
+}
+
+/// Whether `entity` is inside its dodge invulnerability window.
+pub fn has_iframes(world: &World, entity: hecs::Entity) -> bool {
+    world.get::<&DodgeState>(entity).is_ok_and(|d| d.iframes > 0)
+}
+
+/// Runs the dodge system for a single tick.
+///
+/// Rolling players move `DODGE_SPEED` pixels along the roll direction
+/// (sliding along walls like normal movement), then every counter ticks
+/// down. The `DodgeState` is dropped once the cooldown has run out.
+pub fn dodge_system(world: &mut World) {
+    let mut finished = Vec::new();
+
+    for (entity, (pos, dodge)) in world.query_mut::<hecs::With<(&mut Position, &mut DodgeState), &Player>>() {
+        if dodge.ticks_remaining > 0 {
+            let step_x = dodge.dx * DODGE_SPEED;
+            let step_y = dodge.dy * DODGE_SPEED;
+            if collision::is_walkable(collision::pixel_to_tile(pos.x + step_x), collision::pixel_to_tile(pos.y)) {
+                pos.x += step_x;
+            }
+            if collision::is_walkable(collision::pixel_to_tile(pos.x), collision::pixel_to_tile(pos.y + step_y)) {
+                pos.y += step_y;
+            }
+        }
+
+        dodge.ticks_remaining = dodge.ticks_remaining.saturating_sub(1);
+        dodge.iframes = dodge.iframes.saturating_sub(1);
+        dodge.cooldown_remaining = dodge.cooldown_remaining.saturating_sub(1);
+        if dodge.cooldown_remaining == 0 {
+            finished.push(entity);
+        }
+    }
+
+    for entity in finished {
+        let _ = world.remove_one::<DodgeState>(entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::{Health, Rogue, RogueType};
+    use crate::ecs::systems::combat::combat_system;
+    use crate::ecs::world::create_world;
+    use crate::game::spatial::SpatialGrid;
+    use crate::protocol::RogueTypeKind;
+
+    #[test]
+    fn iframes_block_melee_damage_until_they_run_out() {
+        let (mut world, mut game_state) = create_world();
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        let rogue = world.spawn((
+            Rogue,
+            Position { x: 0.0, y: 0.0 },
+            RogueType { kind: RogueTypeKind::Assassin },
+            Health { current: 100, max: 100 },
+        ));
+        let mut grid = SpatialGrid::default();
+        // Keep the rogue glued to the rolling player.
+        let mut tick = |world: &mut World| {
+            let (px, py) = {
+                let pos = world.get::<&Position>(player).unwrap();
+                (pos.x, pos.y)
+            };
+            *world.get::<&mut Position>(rogue).unwrap() = Position { x: px + 5.0, y: py };
+            grid.clear();
+            grid.insert_all::<Rogue>(world);
+            let hit = combat_system(world, &mut game_state, false, &mut grid).player_hit_damage;
+            dodge_system(world);
+            hit
+        };
+
+        assert!(start_dodge(&mut world, 1.0, 0.0));
+        assert!(!start_dodge(&mut world, 1.0, 0.0), "dodge is on cooldown");
+        for _ in 0..DODGE_IFRAMES {
+            assert_eq!(tick(&mut world), 0);
+        }
+        assert_eq!(world.get::<&Health>(player).unwrap().current, 100);
+        assert!(tick(&mut world) > 0);
+    }
+
+    #[test]
+    fn roll_moves_the_player_then_cools_down() {
+        let (mut world, _game_state) = create_world();
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        let start_x = world.get::<&Position>(player).unwrap().x;
+
+        assert!(start_dodge(&mut world, 3.0, 0.0));
+        for _ in 0..DODGE_COOLDOWN_TICKS - 1 {
+            dodge_system(&mut world);
+        }
+        let moved = world.get::<&Position>(player).unwrap().x - start_x;
+        assert!((moved - DODGE_SPEED * DODGE_TICKS as f32).abs() < 1e-4);
+        assert_eq!(dodge_cooldown(&world, player), 1);
+        assert!(!start_dodge(&mut world, 1.0, 0.0));
+
+        dodge_system(&mut world);
+        assert_eq!(dodge_cooldown(&world, player), 0);
+        assert!(start_dodge(&mut world, 0.0, 0.0), "falls back to facing");
+    }
+}
@@ -1,22 +1,130 @@
+use std::collections::HashMap;
+
 use hecs::World;
 
 use crate::ecs::components::{
     Agent, AgentState, AgentTier, Building, BuildingType, ConstructionProgress, GameState,
+    Investment, Specialization, TokenEconomy,
 };
+use crate::ecs::systems::power::{PowerGrid, BROWNOUT_INCOME_MULT, BROWNOUT_WARNING_INTERVAL_TICKS};
+use crate::ecs::systems::synergy::{grade_multiplier, income_bonus, SynergyBonus};
+use crate::game::agents::ANALYST_BONUS_STARS;
 use crate::grading::GradingService;
 use crate::project::ProjectManager;
-use crate::protocol::{AgentStateKind, AgentTierKind, BuildingTypeKind};
+use crate::protocol::{
+    AgentSpecialization, AgentStateKind, AgentTierKind, BuildingTypeKind, TransactionEntry,
+};
+
+/// Number of entries kept in `TokenEconomy::transaction_log`.
+pub const TRANSACTION_LOG_LEN: usize = 100;
+
+/// Applies `delta` to the balance and appends it to the transaction log,
+/// dropping the oldest entries beyond [`TRANSACTION_LOG_LEN`].
+pub fn record_transaction(economy: &mut TokenEconomy, delta: i64, source: &str, tick: u64) {
+    if delta == 0 {
+        return;
+    }
+    economy.balance += delta;
+    if delta > 0 {
+        economy.lifetime_earned += delta;
+    }
+    economy.transaction_log.push_back(TransactionEntry {
+        tick,
+        delta,
+        source: source.to_string(),
+        balance_after: economy.balance,
+    });
+    while economy.transaction_log.len() > TRANSACTION_LOG_LEN {
+        economy.transaction_log.pop_front();
+    }
+}
+
+/// Slack for floating-point drift when whole tokens are taken out of a
+/// sub-token accumulator, so 1000 ticks of 0.003 pay exactly 3 tokens.
+const FRACTIONAL_EPSILON: f64 = 1e-9;
+
+/// Adds `amount` to the sub-token `accumulator` and takes out the whole
+/// tokens it now holds. The accumulator never goes negative.
+pub fn take_whole_tokens(accumulator: &mut f64, amount: f64) -> i64 {
+    *accumulator += amount;
+    let whole = (*accumulator + FRACTIONAL_EPSILON).floor().max(0.0);
+    *accumulator = (*accumulator - whole).max(0.0);
+    whole as i64
+}
+
+/// Raises the balance back to `min_balance` if it fell below it, unless
+/// god mode is on.
+pub fn clamp_balance(economy: &mut TokenEconomy, god_mode: bool) {
+    if !god_mode {
+        economy.balance = economy.balance.max(economy.min_balance);
+    }
+}
+
+/// Interest earned per tick an investment stays locked.
+pub const INVESTMENT_RATE_PER_TICK: f64 = 0.0001;
+
+/// Result returned by [`economy_system`] each tick.
+pub struct EconomyResult {
+    pub log_entries: Vec<String>,
+}
+
+/// Tokens paid back for locking `amount` for `duration_ticks`.
+pub fn investment_return(amount: i64, duration_ticks: u64) -> i64 {
+    (amount as f64 * (1.0 + INVESTMENT_RATE_PER_TICK * duration_ticks as f64)) as i64
+}
+
+/// Locks `amount` tokens until `duration_ticks` from now, deducting them
+/// immediately. Returns the amount that will be paid back.
+pub fn invest(game_state: &mut GameState, amount: i64, duration_ticks: u64) -> Result<i64, String> {
+    if amount <= 0 {
+        return Err("Investment must be positive".to_string());
+    }
+    if duration_ticks == 0 {
+        return Err("Investment needs a duration".to_string());
+    }
+    if amount > game_state.economy.balance {
+        return Err(format!("Need {} tokens, have {}", amount, game_state.economy.balance));
+    }
+
+    let return_amount = investment_return(amount, duration_ticks);
+    record_transaction(&mut game_state.economy, -amount, "investment", game_state.tick);
+    game_state.investments.push(Investment {
+        principal: amount,
+        return_amount,
+        matures_at: game_state.tick + duration_ticks,
+    });
+    Ok(return_amount)
+}
 
 /// Runs the economy system for a single tick.
 ///
 /// Calculates total agent wages (expenditure) and building passive income,
-/// then updates `game_state.economy` with the computed values and applies
-/// the net change to the balance.
-pub fn economy_system(world: &World, game_state: &mut GameState, grading_service: &GradingService) {
+/// then updates `game_state.economy` with the computed values. Income and
+/// wages each build up in their own sub-token accumulator and only whole
+/// tokens move into the balance, so a 0.02/tick TodoApp still pays out every
+/// 50 ticks. The crank (which runs after this system each tick) keeps its own
+/// `fractional` accumulator, so the order of the two doesn't change either's
+/// payout. A graded building with an Analyst among its project's
+/// `agent_assignments` earns as if graded [`ANALYST_BONUS_STARS`] higher.
+/// Income is then scaled by the building's pylon coverage in `power_grid`,
+/// with a browned-out warning every [`BROWNOUT_WARNING_INTERVAL_TICKS`].
+/// Active `synergies` add flat income and boost grade multipliers.
+/// Matured investments are paid out. Outside god mode the balance never
+/// ends the tick below `min_balance`.
+pub fn economy_system(
+    world: &World,
+    game_state: &mut GameState,
+    grading_service: &GradingService,
+    agent_assignments: &HashMap<String, Vec<u64>>,
+    power_grid: &PowerGrid,
+    synergies: &[SynergyBonus],
+) -> EconomyResult {
+    let mut log_entries = Vec::new();
     let mut total_wages: f64 = 0.0;
     let mut wage_sinks: Vec<(String, f64)> = Vec::new();
 
     // ── Agent wages (expenditure) ────────────────────────────────────
+    let wage_multiplier = game_state.upgrades.wage_multiplier();
     for (_entity, (_agent, agent_state, agent_tier)) in
         world.query::<(&Agent, &AgentState, &AgentTier)>().iter()
     {
@@ -34,12 +142,12 @@ pub fn economy_system(world: &World, game_state: &mut GameState, grading_service
             AgentTierKind::Architect => 0.4,
         };
 
-        // Idle agents cost half.
-        let wage = if agent_state.state == AgentStateKind::Idle {
-            base_wage * 0.5
-        } else {
-            base_wage
-        };
+        // Idle agents cost half, resting agents a quarter.
+        let wage = match agent_state.state {
+            AgentStateKind::Idle => base_wage * 0.5,
+            AgentStateKind::Resting => base_wage * 0.25,
+            _ => base_wage,
+        } * wage_multiplier;
 
         total_wages += wage;
         wage_sinks.push((format!("{:?}", agent_tier.tier), wage));
@@ -48,8 +156,9 @@ pub fn economy_system(world: &World, game_state: &mut GameState, grading_service
     // ── Building passive income ──────────────────────────────────────
     let mut total_income: f64 = 0.0;
     let mut income_sources: Vec<(String, f64)> = Vec::new();
+    let mut browned_out = 0;
 
-    for (_entity, (_building, building_type, progress)) in world
+    for (entity, (_building, building_type, progress)) in world
         .query::<(&Building, &BuildingType, &ConstructionProgress)>()
         .iter()
     {
@@ -60,13 +169,14 @@ pub fn economy_system(world: &World, game_state: &mut GameState, grading_service
 
         let base_income = match building_type.kind {
             BuildingTypeKind::ComputeFarm => 0.5,
+            BuildingTypeKind::Nexus => 0.3,
             BuildingTypeKind::TodoApp => 0.02,
             BuildingTypeKind::WeatherDashboard => 0.1,
             BuildingTypeKind::EcommerceStore => 0.3,
             BuildingTypeKind::AiImageGenerator => 0.25,
             BuildingTypeKind::Blockchain => 1.0,
             _ => 0.0,
-        };
+        } + income_bonus(synergies, entity);
 
         if base_income > 0.0 {
             // Look up grade multiplier for app buildings
@@ -74,14 +184,27 @@ pub fn economy_system(world: &World, game_state: &mut GameState, grading_service
             let building_id = ProjectManager::building_type_to_id(&type_name);
             let multiplier = building_id
                 .as_deref()
-                .map(|id| grading_service.get_multiplier(id))
-                .unwrap_or(1.0);
+                .map(|id| {
+                    let has_analyst = agent_assignments.get(id).into_iter().flatten().any(|&bits| {
+                        hecs::Entity::from_bits(bits)
+                            .and_then(|agent| world.get::<&Specialization>(agent).ok())
+                            .is_some_and(|s| s.role == AgentSpecialization::Analyst)
+                    });
+                    let bonus = if has_analyst { ANALYST_BONUS_STARS } else { 0 };
+                    grading_service.get_multiplier_with_bonus(id, bonus)
+                })
+                .unwrap_or(1.0)
+                * grade_multiplier(synergies, entity);
 
-            let income = base_income * multiplier;
+            let power = power_grid.income_multiplier(entity, &game_state.phase);
+            if power == BROWNOUT_INCOME_MULT {
+                browned_out += 1;
+            }
+            let income = base_income * multiplier * power;
             total_income += income;
 
-            let label = if multiplier != 1.0 {
-                format!("{:?} ({}x)", building_type.kind, multiplier)
+            let label = if multiplier * power != 1.0 {
+                format!("{:?} ({}x)", building_type.kind, multiplier * power)
             } else {
                 format!("{:?}", building_type.kind)
             };
@@ -89,19 +212,306 @@ pub fn economy_system(world: &World, game_state: &mut GameState, grading_service
         }
     }
 
+    if browned_out > 0 && game_state.tick.is_multiple_of(BROWNOUT_WARNING_INTERVAL_TICKS) {
+        log_entries.push(format!(
+            "[power] {} building(s) outside pylon coverage are browned out",
+            browned_out
+        ));
+    }
+
     // ── Update economy state ─────────────────────────────────────────
     game_state.economy.income_per_tick = total_income;
     game_state.economy.expenditure_per_tick = total_wages;
     game_state.economy.income_sources = income_sources;
     game_state.economy.expenditure_sinks = wage_sinks;
 
-    // Apply net change to balance using fractional accumulator so sub-token
-    // amounts aren't silently truncated to zero each tick.
-    let net = total_income - total_wages;
-    game_state.economy.fractional += net;
-    let whole = game_state.economy.fractional as i64;
-    if whole != 0 {
-        game_state.economy.balance += whole;
-        game_state.economy.fractional -= whole as f64;
+    // Income and wages accumulate separately so sub-token amounts aren't
+    // truncated away, and each shows up under its own transaction source.
+    // Outside god mode, wages the balance can't cover are written off.
+    let economy = &mut game_state.economy;
+    let earned = take_whole_tokens(&mut economy.income_fractional, total_income);
+    record_transaction(economy, earned, "building income", game_state.tick);
+
+    let mut owed = take_whole_tokens(&mut economy.wage_fractional, total_wages);
+    if !game_state.god_mode {
+        owed = owed.min((economy.balance - economy.min_balance).max(0));
+    }
+    record_transaction(economy, -owed, "agent wages", game_state.tick);
+
+    // ── Matured investments ──────────────────────────────────────────
+    let tick = game_state.tick;
+    let (matured, locked): (Vec<Investment>, Vec<Investment>) =
+        game_state.investments.drain(..).partition(|inv| inv.matures_at <= tick);
+    game_state.investments = locked;
+    for inv in matured {
+        record_transaction(&mut game_state.economy, inv.return_amount, "investment return", tick);
+        log_entries.push(format!(
+            "[economy] investment of {} matured: +{} tokens",
+            inv.principal, inv.return_amount
+        ));
+    }
+
+    clamp_balance(&mut game_state.economy, game_state.god_mode);
+
+    EconomyResult { log_entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+
+    fn spawn_completed(world: &mut World, kind: BuildingTypeKind) {
+        world.spawn((
+            Building,
+            BuildingType { kind },
+            ConstructionProgress { current: 1.0, total: 1.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ));
+    }
+
+    fn todo_app_income(grading_service: &GradingService) -> f64 {
+        let (_world, mut game_state) = create_world();
+        let mut world = World::new();
+        spawn_completed(&mut world, BuildingTypeKind::TodoApp);
+        economy_system(&world, &mut game_state, grading_service, &HashMap::new(), &PowerGrid::new(), &[]);
+        game_state.economy.income_per_tick
+    }
+
+    fn ungraded() -> GradingService {
+        GradingService { api_key: None, grades: Default::default(), last_graded_at: Default::default() }
+    }
+
+    #[test]
+    fn token_compression_reduces_wages() {
+        use crate::game::upgrades::{UpgradeId, TOKEN_COMPRESSION_WAGE_MULT};
+        use crate::ecs::components::AgentState;
+
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Building },
+            AgentTier { tier: AgentTierKind::Journeyman },
+        ));
+
+        economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        let before = game_state.economy.expenditure_per_tick;
+
+        game_state.upgrades.purchased.insert(UpgradeId::TokenCompression);
+        economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        let after = game_state.economy.expenditure_per_tick;
+
+        assert!((before - 0.1).abs() < 1e-9);
+        assert!((after - before * TOKEN_COMPRESSION_WAGE_MULT).abs() < 1e-9);
+    }
+
+    #[test]
+    fn investment_pays_the_formula_amount_at_maturity() {
+        let (_w, mut game_state) = create_world();
+        let world = World::new();
+        game_state.economy.balance = 150;
+
+        assert!(invest(&mut game_state, 200, 200).is_err());
+        assert_eq!(invest(&mut game_state, 100, 200), Ok(investment_return(100, 200)));
+        assert_eq!(game_state.economy.balance, 50);
+
+        game_state.tick = 199;
+        assert!(economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]).log_entries.is_empty());
+        assert_eq!(game_state.economy.balance, 50);
+
+        game_state.tick = 200;
+        let result = economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        assert_eq!(result.log_entries.len(), 1);
+        assert_eq!(investment_return(100, 200), (100.0 * (1.0 + 0.0001 * 200.0)) as i64);
+        assert_eq!(game_state.economy.balance, 50 + investment_return(100, 200));
+        assert!(game_state.investments.is_empty());
+    }
+
+    #[test]
+    fn lone_todo_app_eventually_pays_out() {
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        spawn_completed(&mut world, BuildingTypeKind::TodoApp);
+        let start = game_state.economy.balance;
+
+        for _ in 0..49 {
+            economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        }
+        assert_eq!(game_state.economy.balance, start);
+        for _ in 0..2 {
+            economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        }
+        assert_eq!(game_state.economy.balance, start + 1);
+        assert_eq!(game_state.economy.transaction_log.back().unwrap().source, "building income");
+    }
+
+    #[test]
+    fn idle_wages_eventually_drain_the_balance() {
+        use crate::ecs::components::AgentState;
+
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Idle },
+            AgentTier { tier: AgentTierKind::Apprentice },
+        ));
+        game_state.economy.balance = 10;
+        let start = game_state.economy.balance;
+
+        // 0.025 tokens/tick: a whole token is owed after 40 ticks.
+        for _ in 0..41 {
+            economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        }
+        assert_eq!(game_state.economy.balance, start - 1);
+        assert_eq!(game_state.economy.transaction_log.back().unwrap().source, "agent wages");
+    }
+
+    #[test]
+    fn wages_never_push_the_balance_below_zero_outside_god_mode() {
+        use crate::ecs::components::AgentState;
+
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Building },
+            AgentTier { tier: AgentTierKind::Architect },
+        ));
+        game_state.economy.balance = 1;
+
+        for _ in 0..20 {
+            economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        }
+        assert_eq!(game_state.economy.balance, 0);
+        assert!(game_state.economy.wage_fractional >= 0.0);
+
+        game_state.god_mode = true;
+        for _ in 0..5 {
+            economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        }
+        assert_eq!(game_state.economy.balance, -2);
+    }
+
+    #[test]
+    fn fractional_income_adds_up_to_whole_tokens() {
+        let mut accumulator = 0.0;
+        let total: i64 = (0..1000).map(|_| take_whole_tokens(&mut accumulator, 0.003)).sum();
+        assert_eq!(total, 3);
+        assert!(accumulator.abs() < 1e-6);
+    }
+
+    #[test]
+    fn resting_agents_cost_a_quarter() {
+        use crate::ecs::components::AgentState;
+
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Resting },
+            AgentTier { tier: AgentTierKind::Journeyman },
+        ));
+
+        economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &PowerGrid::new(), &[]);
+        assert!((game_state.economy.expenditure_per_tick - 0.025).abs() < 1e-9);
+    }
+
+    #[test]
+    fn transaction_log_keeps_last_hundred() {
+        let (_w, mut game_state) = create_world();
+        for tick in 0..150 {
+            record_transaction(&mut game_state.economy, 1, "test", tick);
+        }
+        record_transaction(&mut game_state.economy, 0, "nothing", 150);
+
+        let log = &game_state.economy.transaction_log;
+        assert_eq!(log.len(), TRANSACTION_LOG_LEN);
+        assert_eq!(log.front().unwrap().tick, 50);
+        assert_eq!(log.back().unwrap().balance_after, 150);
+        assert_eq!(game_state.economy.balance, 150);
+    }
+
+    #[test]
+    fn five_star_todo_app_earns_five_times_ungraded() {
+        let mut graded = ungraded();
+        graded.set_grade("todo_app", 5, "great".to_string(), 1);
+
+        let base = todo_app_income(&ungraded());
+        assert!((todo_app_income(&graded) - base * 5.0).abs() < 1e-9);
+        assert_eq!(graded.snapshots()[0].building_id, "todo_app");
+        assert_eq!(graded.snapshots()[0].stars, 5);
+    }
+
+    #[test]
+    fn six_star_todo_app_earns_ten_times_ungraded() {
+        let ungraded = ungraded();
+        let mut graded = GradingService { api_key: None, grades: Default::default(), last_graded_at: Default::default() };
+        graded.set_grade("todo_app", 6, "excellent".to_string(), 1);
+
+        let base = todo_app_income(&ungraded);
+        assert!(base > 0.0);
+        assert!((todo_app_income(&graded) - base * 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn analyst_adds_a_star_to_its_building() {
+        let mut graded = ungraded();
+        graded.set_grade("todo_app", 3, "fine".to_string(), 1);
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        spawn_completed(&mut world, BuildingTypeKind::TodoApp);
+        let analyst = world.spawn((Agent, Specialization { role: AgentSpecialization::Analyst }));
+        let builder = world.spawn((Agent, Specialization { role: AgentSpecialization::Builder }));
+
+        let staffed_by = |agent: hecs::Entity| HashMap::from([("todo_app".to_string(), vec![agent.to_bits().get()])]);
+        economy_system(&world, &mut game_state, &graded, &staffed_by(builder), &PowerGrid::new(), &[]);
+        let three_star = game_state.economy.income_per_tick;
+        economy_system(&world, &mut game_state, &graded, &staffed_by(analyst), &PowerGrid::new(), &[]);
+        let boosted = game_state.economy.income_per_tick;
+
+        // 3 stars pays 2x, 4 stars 3x.
+        assert!((boosted - three_star * 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pylon_coverage_scales_income_and_brownouts_warn() {
+        use crate::ecs::components::{GamePhase, Position};
+        use crate::ecs::systems::power::POWERED_INCOME_MULT;
+
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        let farm = |x: f32| {
+            (
+                Building,
+                Position { x, y: 0.0 },
+                BuildingType { kind: BuildingTypeKind::ComputeFarm },
+                ConstructionProgress { current: 1.0, total: 1.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+            )
+        };
+        world.spawn(farm(1000.0));
+        let mut grid = PowerGrid::new();
+        grid.update(&world);
+
+        economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &grid, &[]);
+        let base = game_state.economy.income_per_tick;
+
+        game_state.phase = GamePhase::Network;
+        game_state.tick = BROWNOUT_WARNING_INTERVAL_TICKS;
+        let result = economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &grid, &[]);
+        assert!((game_state.economy.income_per_tick - base * BROWNOUT_INCOME_MULT).abs() < 1e-9);
+        assert!(result.log_entries.iter().any(|l| l.starts_with("[power]")));
+
+        world.spawn(farm(0.0));
+        world.spawn((
+            Building,
+            Position { x: 0.0, y: 0.0 },
+            BuildingType { kind: BuildingTypeKind::Pylon },
+            ConstructionProgress { current: 1.0, total: 1.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ));
+        grid.update(&world);
+        economy_system(&world, &mut game_state, &ungraded(), &HashMap::new(), &grid, &[]);
+        let expected = base * BROWNOUT_INCOME_MULT + base * POWERED_INCOME_MULT;
+        assert!((game_state.economy.income_per_tick - expected).abs() < 1e-9);
     }
 }
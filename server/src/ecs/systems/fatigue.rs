@@ -0,0 +1,137 @@
+use hecs::World;
+
+use crate::ecs::components::{Agent, AgentFatigue, AgentName, AgentState, AgentTier};
+use crate::protocol::{AgentStateKind, AgentTierKind};
+
+/// Fatigue shed per tick while an agent is idle.
+pub const IDLE_FATIGUE_RECOVERY: u32 = 2;
+/// Ticks a fatigued agent must rest before it can work again.
+pub const REST_DEBT_TICKS: u32 = 500;
+
+/// Result returned by [`fatigue_system`] each tick.
+pub struct FatigueResult {
+    pub log_entries: Vec<String>,
+}
+
+/// Building ticks an agent of `tier` can sustain before it must rest.
+pub fn fatigue_threshold(tier: AgentTierKind) -> u32 {
+    match tier {
+        AgentTierKind::Apprentice => 1000,
+        AgentTierKind::Journeyman => 2000,
+        AgentTierKind::Artisan => 3500,
+        AgentTierKind::Architect => 6000,
+    }
+}
+
+/// Runs the fatigue system for a single tick.
+///
+/// Building agents accumulate one tick of fatigue per tick and idle agents
+/// shed it.  Once fatigue exceeds the tier threshold the agent drops into
+/// `Resting` and must pay off `REST_DEBT_TICKS` before returning to `Idle`.
+pub fn fatigue_system(world: &mut World) -> FatigueResult {
+    let mut log_entries = Vec::new();
+
+    // ── Lazily attach fatigue to agents that lack it ──────────────
+    let missing: Vec<hecs::Entity> = world
+        .query::<hecs::Without<&Agent, &AgentFatigue>>()
+        .iter()
+        .map(|(e, _)| e)
+        .collect();
+    for e in missing {
+        let _ = world.insert_one(e, AgentFatigue::default());
+    }
+
+    for (_id, (state, tier, fatigue, name)) in world.query_mut::<hecs::With<
+        (&mut AgentState, &AgentTier, &mut AgentFatigue, &AgentName),
+        &Agent,
+    >>() {
+        match state.state {
+            AgentStateKind::Building => {
+                fatigue.accumulated_ticks += 1;
+                if fatigue.accumulated_ticks > fatigue_threshold(tier.tier) {
+                    state.state = AgentStateKind::Resting;
+                    fatigue.rest_debt = REST_DEBT_TICKS;
+                    log_entries.push(format!("{} is exhausted and needs rest", name.name));
+                }
+            }
+            AgentStateKind::Idle => {
+                fatigue.accumulated_ticks =
+                    fatigue.accumulated_ticks.saturating_sub(IDLE_FATIGUE_RECOVERY);
+            }
+            AgentStateKind::Resting => {
+                fatigue.rest_debt = fatigue.rest_debt.saturating_sub(1);
+                if fatigue.rest_debt == 0 {
+                    state.state = AgentStateKind::Idle;
+                    fatigue.accumulated_ticks = 0;
+                    log_entries.push(format!("{} is rested and ready to work", name.name));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    FatigueResult { log_entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::agents::assign_task;
+    use crate::protocol::TaskAssignment;
+
+    fn spawn_agent(world: &mut World, tier: AgentTierKind) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentName { name: "ada".to_string() },
+            AgentState { state: AgentStateKind::Building },
+            AgentTier { tier },
+        ))
+    }
+
+    fn state(world: &World, e: hecs::Entity) -> AgentStateKind {
+        world.get::<&AgentState>(e).unwrap().state
+    }
+
+    #[test]
+    fn agent_tires_rests_and_returns_to_idle() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentTierKind::Apprentice);
+
+        for _ in 0..1000 {
+            fatigue_system(&mut world);
+        }
+        assert_eq!(state(&world, agent), AgentStateKind::Building);
+        assert_eq!(world.get::<&AgentFatigue>(agent).unwrap().accumulated_ticks, 1000);
+
+        let result = fatigue_system(&mut world);
+        assert_eq!(state(&world, agent), AgentStateKind::Resting);
+        assert_eq!(world.get::<&AgentFatigue>(agent).unwrap().rest_debt, REST_DEBT_TICKS);
+        assert_eq!(result.log_entries.len(), 1);
+        assert!(assign_task(&mut world, agent, TaskAssignment::Build).is_err());
+
+        for _ in 0..REST_DEBT_TICKS - 1 {
+            fatigue_system(&mut world);
+        }
+        assert_eq!(state(&world, agent), AgentStateKind::Resting);
+
+        fatigue_system(&mut world);
+        assert_eq!(state(&world, agent), AgentStateKind::Idle);
+        let fatigue = world.get::<&AgentFatigue>(agent).unwrap();
+        assert_eq!(fatigue.accumulated_ticks, 0);
+        assert_eq!(fatigue.rest_debt, 0);
+    }
+
+    #[test]
+    fn idle_agents_recover_fatigue() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentTierKind::Journeyman);
+        world.insert_one(agent, AgentFatigue { accumulated_ticks: 5, rest_debt: 0 }).unwrap();
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Idle;
+
+        fatigue_system(&mut world);
+        assert_eq!(world.get::<&AgentFatigue>(agent).unwrap().accumulated_ticks, 3);
+        fatigue_system(&mut world);
+        fatigue_system(&mut world);
+        assert_eq!(world.get::<&AgentFatigue>(agent).unwrap().accumulated_ticks, 0);
+    }
+}
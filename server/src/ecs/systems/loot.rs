@@ -0,0 +1,158 @@
+use hecs::World;
+use rand::Rng;
+
+use crate::ecs::components::{DroppedItem, GameState, Loot, LootEntry, LootTable, Player, Position};
+use crate::game::chests::BLUEPRINTS;
+use crate::protocol::{DropEvent, RogueTypeKind};
+
+/// The player picks up loot within this distance.
+pub const LOOT_PICKUP_RANGE: f32 = 24.0;
+
+/// Result returned by [`loot_pickup_system`] each tick.
+pub struct LootPickupResult {
+    /// Loot entities picked up (and despawned) this tick.
+    pub picked_up: Vec<hecs::Entity>,
+    /// Item types added to the inventory, e.g. so the caller can unlock
+    /// picked-up blueprints.
+    pub items: Vec<String>,
+    pub log_entries: Vec<String>,
+}
+
+fn entry(item_type: &str, weight: f32) -> LootEntry {
+    LootEntry { item_type: item_type.to_string(), weight, count_range: (1, 1) }
+}
+
+/// The loot table a rogue of `kind` spawns with, if it drops anything.
+pub fn loot_table_for(kind: RogueTypeKind) -> Option<LootTable> {
+    let entries = match kind {
+        RogueTypeKind::Swarm => vec![entry("material:circuit_shard", 0.2)],
+        RogueTypeKind::Corruptor => vec![entry("material:corruption_essence", 0.3)],
+        RogueTypeKind::Architect => vec![entry("blueprint:any", 0.15)],
+        _ => return None,
+    };
+    Some(LootTable { entries })
+}
+
+/// Rolls every entry of `table`, returning the `(item_type, count)` pairs
+/// that dropped.
+pub fn roll_loot(table: &LootTable, rng: &mut impl Rng) -> Vec<(String, u32)> {
+    let mut drops = Vec::new();
+    for e in &table.entries {
+        if rng.gen::<f32>() >= e.weight {
+            continue;
+        }
+        let item_type = if e.item_type == "blueprint:any" {
+            format!("blueprint:{}", BLUEPRINTS[rng.gen_range(0..BLUEPRINTS.len())])
+        } else {
+            e.item_type.clone()
+        };
+        let (lo, hi) = e.count_range;
+        drops.push((item_type, rng.gen_range(lo..=hi.max(lo))));
+    }
+    drops
+}
+
+/// Rolls a dying rogue's loot table and spawns whatever drops as loot on
+/// the ground where it fell. Call before despawning the rogue.
+pub fn drop_loot(world: &mut World, rogue: hecs::Entity, rng: &mut impl Rng) -> Vec<DropEvent> {
+    let drops = {
+        let Ok(table) = world.get::<&LootTable>(rogue) else { return Vec::new() };
+        roll_loot(&table, rng)
+    };
+    let Some((x, y)) = world.get::<&Position>(rogue).ok().map(|p| (p.x, p.y)) else {
+        return Vec::new();
+    };
+
+    drops
+        .into_iter()
+        .map(|(item_type, count)| {
+            let entity = world.spawn((DroppedItem, Position { x, y }, Loot { item_type: item_type.clone(), count }));
+            DropEvent { entity_id: entity.to_bits().into(), item_type, count }
+        })
+        .collect()
+}
+
+/// Moves loot within [`LOOT_PICKUP_RANGE`] of the player into the inventory.
+pub fn loot_pickup_system(world: &mut World, game_state: &mut GameState) -> LootPickupResult {
+    let mut result = LootPickupResult { picked_up: Vec::new(), items: Vec::new(), log_entries: Vec::new() };
+    let Some((px, py)) = world
+        .query::<&Position>()
+        .with::<&Player>()
+        .iter()
+        .next()
+        .map(|(_e, pos)| (pos.x, pos.y))
+    else {
+        return result;
+    };
+
+    let in_reach: Vec<(hecs::Entity, String, u32)> = world
+        .query::<(&Position, &Loot)>()
+        .iter()
+        .filter(|(_e, (pos, _loot))| {
+            (pos.x - px).powi(2) + (pos.y - py).powi(2) <= LOOT_PICKUP_RANGE * LOOT_PICKUP_RANGE
+        })
+        .map(|(e, (_pos, loot))| (e, loot.item_type.clone(), loot.count))
+        .collect();
+
+    for (entity, item_type, count) in in_reach {
+        game_state.add_inventory_item(&item_type, count);
+        let _ = world.despawn(entity);
+        result.log_entries.push(format!("[loot] picked up {} x{}", item_type, count));
+        result.picked_up.push(entity);
+        result.items.push(item_type);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::systems::spawn::spawn_rogue;
+    use crate::ecs::world::create_world;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn hundred_swarms_drop_about_twenty_shards() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut shards = 0;
+        for i in 0..100 {
+            let swarm = spawn_rogue(&mut world, i as f32, 0.0, RogueTypeKind::Swarm);
+            for drop in drop_loot(&mut world, swarm, &mut rng) {
+                assert_eq!(drop.item_type, "material:circuit_shard");
+                shards += drop.count;
+            }
+            world.despawn(swarm).unwrap();
+        }
+        assert!((10..=30).contains(&shards), "{} shards", shards);
+        assert_eq!(world.query::<&Loot>().iter().count() as u32, shards);
+    }
+
+    #[test]
+    fn architect_blueprint_resolves_to_a_real_blueprint() {
+        let table = loot_table_for(RogueTypeKind::Architect).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let drops: Vec<(String, u32)> = (0..100).flat_map(|_| roll_loot(&table, &mut rng)).collect();
+        assert!(!drops.is_empty());
+        for (item_type, _count) in drops {
+            let bp = item_type.strip_prefix("blueprint:").unwrap();
+            assert!(BLUEPRINTS.contains(&bp));
+        }
+    }
+
+    #[test]
+    fn player_picks_up_nearby_loot() {
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }));
+        let near = world.spawn((DroppedItem, Position { x: 10.0, y: 0.0 }, Loot { item_type: "material:circuit_shard".to_string(), count: 2 }));
+        let far = world.spawn((DroppedItem, Position { x: 100.0, y: 0.0 }, Loot { item_type: "material:circuit_shard".to_string(), count: 1 }));
+
+        let result = loot_pickup_system(&mut world, &mut game_state);
+        assert_eq!(result.picked_up, vec![near]);
+        assert!(game_state.has_inventory_item("material:circuit_shard", 2));
+        assert!(!world.contains(near));
+        assert!(world.contains(far));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod agent_combat;
 pub mod agent_tick;
 pub mod agent_wander;
 pub mod crank;
@@ -7,4 +8,19 @@ pub mod spawn;
 pub mod combat;
 pub mod projectile;
 pub mod placement;
+pub mod status_effect;
+pub mod token_drain;
 pub mod camp_spawner;
+pub mod morale;
+pub mod fatigue;
+pub mod discovery;
+pub mod xp;
+pub mod revival;
+pub mod dodge;
+pub mod plague;
+pub mod loot;
+pub mod power;
+pub mod nest;
+pub mod personality;
+pub mod speech;
+pub mod synergy;
@@ -0,0 +1,350 @@
+use hecs::World;
+
+use crate::ecs::components::{
+    Agent, AgentFatigue, AgentMorale, AgentName, AgentState, AgentTier, AgentVibeConfig, Assignment, Building,
+    BuildingEffect, BuildingEffects, ConstructionProgress, Position, RefusingWork, StatusEffects, WanderState,
+};
+use crate::ecs::systems::plague::CORRUPTED_ERROR_MULTIPLIER;
+use crate::game::agents::{assign_task, base_error_chance};
+use crate::protocol::AgentStateKind;
+
+/// Morale lost per tick while an agent is erroring.
+pub const ERRORING_MORALE_DECAY: f32 = 0.01;
+/// Morale lost per tick while an agent is idle away from home.
+pub const IDLE_MORALE_DECAY: f32 = 0.002;
+/// Morale gained per tick while an agent is idle near its home base.
+pub const IDLE_HOME_RECOVERY: f32 = 0.001;
+/// Radius (pixels) around an agent's wander home that counts as home base.
+pub const HOME_BASE_RADIUS: f32 = 150.0;
+/// Building ticks without a break after which an agent starts to sour.
+pub const OVERWORK_TICKS: u32 = 600;
+/// Morale lost per tick while an agent is overworked.
+pub const OVERWORK_MORALE_DECAY: f32 = 0.001;
+/// Morale lost each time an agent takes a hit.
+pub const DAMAGE_MORALE_LOSS: f32 = 0.05;
+/// Morale lost when an agent's vibe session exits with an error.
+pub const SESSION_ERROR_MORALE_LOSS: f32 = 0.1;
+/// Morale gained by each agent on a building graded `HIGH_GRADE_STARS` or more.
+pub const HIGH_GRADE_MORALE_GAIN: f32 = 0.15;
+/// Grade at which the agents on a building get a morale lift.
+pub const HIGH_GRADE_STARS: u8 = 4;
+/// Morale below which an agent builds slower and errors more.
+pub const LOW_MORALE: f32 = 0.3;
+/// Build speed multiplier for a low-morale agent.
+pub const LOW_MORALE_BUILD_SPEED: f32 = 0.5;
+/// Error chance multiplier for a low-morale agent.
+pub const LOW_MORALE_ERROR_MULTIPLIER: f32 = 1.5;
+/// Morale gained per tick by a building agent near a morale building.
+pub const MORALE_BUILDING_RECOVERY: f32 = 0.005;
+/// Radius (pixels) within which a morale building lifts nearby agents.
+pub const MORALE_BUILDING_RADIUS: f32 = 200.0;
+
+/// Result returned by [`morale_system`] each tick.
+pub struct MoraleResult {
+    pub log_entries: Vec<String>,
+}
+
+/// Whether `morale` is low enough to hurt an agent's work.
+pub fn is_low_morale(morale: f32) -> bool {
+    morale < LOW_MORALE
+}
+
+/// Shifts `agent`'s morale by `delta`, clamped to [0, 1]. Does nothing for
+/// entities without morale.
+pub fn adjust_morale(world: &mut World, agent: hecs::Entity, delta: f32) {
+    if let Ok(mut morale) = world.get::<&mut AgentMorale>(agent) {
+        morale.value = (morale.value + delta).clamp(0.0, 1.0);
+    }
+}
+
+/// Runs the morale system for a single tick.
+///
+/// Erroring agents, agents idling away from home and agents building for
+/// more than `OVERWORK_TICKS` without a break slowly lose morale; agents
+/// idling near their home base recover it, as do idle or building agents near
+/// a completed morale building (one with an `AgentMoraleBoost` effect).
+/// Each agent's `error_chance_base` is then re-derived from its tier so that
+/// low-morale agents error more often, up to double at zero morale, and
+/// Corrupted agents error `CORRUPTED_ERROR_MULTIPLIER` times as often.  A
+/// working agent whose morale hits zero refuses to work and drops to `Idle`
+/// until its morale is back up to `LOW_MORALE`, when it returns to its
+/// `Assignment`.
+pub fn morale_system(world: &mut World) -> MoraleResult {
+    let mut log_entries = Vec::new();
+    let mut refused: Vec<hecs::Entity> = Vec::new();
+    let mut recovered: Vec<(hecs::Entity, bool)> = Vec::new();
+
+    // ── Collect completed morale buildings ────────────────────────
+    let morale_buildings: Vec<(f32, f32)> = world
+        .query::<(&Building, &Position, &BuildingEffects, Option<&ConstructionProgress>)>()
+        .iter()
+        .filter(|(_, (_, _, effects, progress))| {
+            progress.is_none_or(|p| p.current >= p.total)
+                && effects
+                    .effects
+                    .iter()
+                    .any(|e| matches!(e, BuildingEffect::AgentMoraleBoost(_)))
+        })
+        .map(|(_, (_, pos, _, _))| (pos.x, pos.y))
+        .collect();
+
+    let radius_sq = MORALE_BUILDING_RADIUS * MORALE_BUILDING_RADIUS;
+    let home_radius_sq = HOME_BASE_RADIUS * HOME_BASE_RADIUS;
+
+    // ── Update morale and derived error chance ────────────────────
+    for (id, (state, pos, tier, morale, vibe, wander, fatigue, name, effects, refusing)) in world.query_mut::<hecs::With<
+        (
+            &mut AgentState,
+            &Position,
+            &AgentTier,
+            &mut AgentMorale,
+            &mut AgentVibeConfig,
+            Option<&WanderState>,
+            Option<&AgentFatigue>,
+            Option<&AgentName>,
+            Option<&StatusEffects>,
+            Option<&RefusingWork>,
+        ),
+        &Agent,
+    >>() {
+        let near_morale_building = morale_buildings.iter().any(|(bx, by)| {
+            let dx = bx - pos.x;
+            let dy = by - pos.y;
+            dx * dx + dy * dy <= radius_sq
+        });
+        let boost = if near_morale_building { MORALE_BUILDING_RECOVERY } else { 0.0 };
+
+        let delta = match state.state {
+            AgentStateKind::Erroring => -ERRORING_MORALE_DECAY,
+            AgentStateKind::Idle => {
+                let at_home = wander.is_some_and(|w| {
+                    let dx = w.home_x - pos.x;
+                    let dy = w.home_y - pos.y;
+                    dx * dx + dy * dy <= home_radius_sq
+                });
+                let base = if at_home { IDLE_HOME_RECOVERY } else { -IDLE_MORALE_DECAY };
+                base + boost
+            }
+            AgentStateKind::Building => {
+                let overworked = fatigue.is_some_and(|f| f.accumulated_ticks > OVERWORK_TICKS);
+                let base = if overworked { -OVERWORK_MORALE_DECAY } else { 0.0 };
+                base + boost
+            }
+            _ => 0.0,
+        };
+
+        morale.value = (morale.value + delta).clamp(0.0, 1.0);
+        vibe.error_chance_base = base_error_chance(tier.tier) * (2.0 - morale.value);
+        if effects.is_some_and(|e| e.is_corrupted()) {
+            vibe.error_chance_base *= CORRUPTED_ERROR_MULTIPLIER;
+        }
+
+        let working = matches!(
+            state.state,
+            AgentStateKind::Building | AgentStateKind::Exploring | AgentStateKind::Defending
+        );
+        let name = name.map_or("an agent", |n| n.name.as_str());
+        if working && morale.value <= 0.0 {
+            state.state = AgentStateKind::Idle;
+            refused.push(id);
+            log_entries.push(format!("{} refuses to work", name));
+        } else if refusing.is_some() && state.state != AgentStateKind::Idle {
+            // Reassigned (or otherwise busy) since refusing; nothing to resume.
+            recovered.push((id, false));
+        } else if refusing.is_some() && !is_low_morale(morale.value) {
+            recovered.push((id, true));
+            log_entries.push(format!("{} is back to work", name));
+        }
+    }
+
+    for agent in refused {
+        let _ = world.insert_one(agent, RefusingWork);
+    }
+    for (agent, resume) in recovered {
+        let _ = world.remove_one::<RefusingWork>(agent);
+        let task = world.get::<&Assignment>(agent).map(|a| a.task).ok();
+        if let (true, Some(task)) = (resume, task) {
+            let _ = assign_task(world, agent, task);
+        }
+    }
+
+    MoraleResult { log_entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::agents::generate_vibe_config;
+    use crate::protocol::{AgentTierKind, BuildingTypeKind};
+    use crate::ecs::components::BuildingType;
+
+    fn spawn_agent(world: &mut World, state: AgentStateKind, morale: f32, x: f32) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentState { state },
+            Position { x, y: 0.0 },
+            AgentTier { tier: AgentTierKind::Apprentice },
+            AgentMorale { value: morale },
+            generate_vibe_config(AgentTierKind::Apprentice),
+        ))
+    }
+
+    fn morale(world: &World, e: hecs::Entity) -> f32 {
+        world.get::<&AgentMorale>(e).unwrap().value
+    }
+
+    #[test]
+    fn erroring_agent_loses_morale_each_tick() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Erroring, 0.5, 0.0);
+
+        morale_system(&mut world);
+        assert!((morale(&world, agent) - 0.49).abs() < 1e-6);
+        morale_system(&mut world);
+        assert!((morale(&world, agent) - 0.48).abs() < 1e-6);
+    }
+
+    #[test]
+    fn idle_agents_recover_at_home_and_sour_away_from_it() {
+        let mut world = World::new();
+        let home = spawn_agent(&mut world, AgentStateKind::Idle, 0.9995, 0.0);
+        let away = spawn_agent(&mut world, AgentStateKind::Idle, 0.001, 500.0);
+        for agent in [home, away] {
+            world.insert_one(agent, WanderState {
+                home_x: 0.0,
+                home_y: 0.0,
+                waypoint_x: 0.0,
+                waypoint_y: 0.0,
+                pause_remaining: 0,
+                wander_radius: 120.0,
+                walk_target: None,
+                walk_ticks: 0,
+            }).unwrap();
+        }
+
+        morale_system(&mut world);
+        assert_eq!(morale(&world, home), 1.0);
+        assert_eq!(morale(&world, away), 0.0);
+        // Idle agents have nothing to refuse.
+        assert_eq!(world.get::<&AgentState>(away).unwrap().state, AgentStateKind::Idle);
+    }
+
+    #[test]
+    fn overworked_builders_lose_morale() {
+        let mut world = World::new();
+        let fresh = spawn_agent(&mut world, AgentStateKind::Building, 0.5, 500.0);
+        let tired = spawn_agent(&mut world, AgentStateKind::Building, 0.5, 500.0);
+        world.insert_one(fresh, AgentFatigue { accumulated_ticks: OVERWORK_TICKS, rest_debt: 0 }).unwrap();
+        world.insert_one(tired, AgentFatigue { accumulated_ticks: OVERWORK_TICKS + 1, rest_debt: 0 }).unwrap();
+
+        morale_system(&mut world);
+        assert_eq!(morale(&world, fresh), 0.5);
+        assert!((morale(&world, tired) - (0.5 - OVERWORK_MORALE_DECAY)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_morale_agent_refuses_to_work() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.0005, 500.0);
+        world.insert_one(agent, AgentFatigue { accumulated_ticks: OVERWORK_TICKS + 1, rest_debt: 0 }).unwrap();
+        world.insert_one(agent, AgentName { name: "ada".to_string() }).unwrap();
+
+        let result = morale_system(&mut world);
+        assert_eq!(morale(&world, agent), 0.0);
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Idle);
+        assert_eq!(result.log_entries, vec!["ada refuses to work".to_string()]);
+    }
+
+    #[test]
+    fn adjustments_are_clamped() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.95, 0.0);
+
+        adjust_morale(&mut world, agent, HIGH_GRADE_MORALE_GAIN);
+        assert_eq!(morale(&world, agent), 1.0);
+        adjust_morale(&mut world, agent, -2.0);
+        assert_eq!(morale(&world, agent), 0.0);
+        assert!(is_low_morale(0.29) && !is_low_morale(LOW_MORALE));
+    }
+
+    #[test]
+    fn zero_morale_doubles_error_chance() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Erroring, 0.005, 0.0);
+
+        morale_system(&mut world);
+        assert_eq!(morale(&world, agent), 0.0);
+        let vibe = world.get::<&AgentVibeConfig>(agent).unwrap();
+        let base = base_error_chance(AgentTierKind::Apprentice);
+        assert!((vibe.error_chance_base - base * 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn corruption_triples_error_chance() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 1.0, 500.0);
+        crate::ecs::systems::status_effect::apply_status(
+            &mut world,
+            agent,
+            crate::ecs::systems::plague::PLAGUE_CORRUPTION,
+        );
+
+        morale_system(&mut world);
+        let vibe = world.get::<&AgentVibeConfig>(agent).unwrap();
+        let base = base_error_chance(AgentTierKind::Apprentice);
+        assert!((vibe.error_chance_base - base * CORRUPTED_ERROR_MULTIPLIER).abs() < 1e-6);
+    }
+
+    #[test]
+    fn building_near_morale_building_recovers() {
+        let mut world = World::new();
+        world.spawn((
+            Building,
+            BuildingType { kind: BuildingTypeKind::LandingPage },
+            Position { x: 0.0, y: 0.0 },
+            BuildingEffects { effects: vec![BuildingEffect::AgentMoraleBoost(0.05)] },
+            ConstructionProgress { current: 70.0, total: 70.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ));
+        let near = spawn_agent(&mut world, AgentStateKind::Building, 0.5, 150.0);
+        let far = spawn_agent(&mut world, AgentStateKind::Building, 0.5, 500.0);
+
+        morale_system(&mut world);
+        assert!((morale(&world, near) - 0.505).abs() < 1e-6);
+        assert_eq!(morale(&world, far), 0.5);
+    }
+
+    #[test]
+    fn refusing_agent_goes_back_to_work_once_morale_recovers() {
+        use crate::protocol::TaskAssignment;
+
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building, 0.0, 100.0);
+        world.insert_one(agent, Assignment { task: TaskAssignment::Build }).unwrap();
+
+        morale_system(&mut world);
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Idle);
+        assert!(world.get::<&RefusingWork>(agent).is_ok());
+
+        // Idling by a morale building, it climbs back to LOW_MORALE.
+        world.spawn((
+            Building,
+            Position { x: 0.0, y: 0.0 },
+            BuildingEffects { effects: vec![BuildingEffect::AgentMoraleBoost(0.05)] },
+        ));
+        let mut ticks = 0;
+        let result = loop {
+            ticks += 1;
+            let result = morale_system(&mut world);
+            if world.get::<&RefusingWork>(agent).is_err() {
+                break result;
+            }
+            assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Idle);
+        };
+        assert!(ticks > 1);
+        assert!(!is_low_morale(morale(&world, agent)));
+        assert_eq!(result.log_entries, vec!["an agent is back to work".to_string()]);
+        // Back on its build, walking to the site.
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Walking);
+        assert_eq!(world.get::<&Assignment>(agent).unwrap().task, TaskAssignment::Build);
+    }
+}
@@ -0,0 +1,111 @@
+use hecs::World;
+use rand::Rng;
+
+use crate::ecs::components::{GameState, Health, Position, RogueNest};
+use crate::ecs::systems::spawn::spawn_rogue;
+use crate::game::tilemap::TileMap;
+use crate::protocol::RogueTypeKind;
+
+/// Hit points of a freshly scattered nest.
+pub const NEST_HEALTH: i32 = 50;
+
+/// Chance per tick that a nest spawns a Swarm.
+pub const DEFAULT_NEST_SPAWN_RATE: f32 = 0.01;
+
+/// Distance (pixels) from a rogue nest at which its rogues appear.
+pub const NEST_SPAWN_OFFSET: f32 = 30.0;
+
+/// Result returned by [`nest_spawn_system`].
+#[derive(Default)]
+pub struct NestResult {
+    pub log_entries: Vec<String>,
+    /// Nests destroyed this tick (already despawned).
+    pub destroyed: Vec<hecs::Entity>,
+}
+
+/// Spawns a rogue nest at `(x, y)` with full health and the default spawn
+/// rate.
+pub fn spawn_nest(world: &mut World, x: f32, y: f32) -> hecs::Entity {
+    world.spawn((
+        Position { x, y },
+        Health { current: NEST_HEALTH, max: NEST_HEALTH },
+        RogueNest { spawn_rate: DEFAULT_NEST_SPAWN_RATE },
+    ))
+}
+
+/// Despawns nests the player has destroyed, remembering their chunk in
+/// `game_state.destroyed_nests`, then gives every other nest a
+/// `spawn_rate` chance to release one Swarm nearby.
+///
+/// Nests stay quiet while spawning is disabled.
+pub fn nest_spawn_system(world: &mut World, game_state: &mut GameState, rng: &mut impl Rng) -> NestResult {
+    let mut result = NestResult::default();
+
+    let nests: Vec<(hecs::Entity, f32, f32, i32, f32)> = world
+        .query::<(&Position, &Health, &RogueNest)>()
+        .iter()
+        .map(|(entity, (pos, health, nest))| (entity, pos.x, pos.y, health.current, nest.spawn_rate))
+        .collect();
+
+    for (entity, x, y, health, spawn_rate) in nests {
+        if health <= 0 {
+            let _ = world.despawn(entity);
+            game_state.destroyed_nests.insert(TileMap::world_to_chunk(x, y));
+            result.destroyed.push(entity);
+            result.log_entries.push("[combat] rogue nest destroyed".to_string());
+            continue;
+        }
+        if game_state.spawning_enabled && rng.gen::<f32>() < spawn_rate {
+            let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+            spawn_rogue(
+                world,
+                x + angle.cos() * NEST_SPAWN_OFFSET,
+                y + angle.sin() * NEST_SPAWN_OFFSET,
+                RogueTypeKind::Swarm,
+            );
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::{Rogue, RogueType};
+    use crate::ecs::world::create_world;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn swarm_count(world: &World) -> usize {
+        world
+            .query::<&RogueType>()
+            .with::<&Rogue>()
+            .iter()
+            .filter(|(_e, t)| t.kind == RogueTypeKind::Swarm)
+            .count()
+    }
+
+    #[test]
+    fn nest_spawns_one_swarm_per_tick_until_destroyed() {
+        let (mut world, mut game_state) = create_world();
+        let mut rng = StdRng::seed_from_u64(1);
+        let nest = spawn_nest(&mut world, 1000.0, 1000.0);
+        world.get::<&mut RogueNest>(nest).unwrap().spawn_rate = 1.0;
+        let before = swarm_count(&world);
+
+        for tick in 1..=3 {
+            nest_spawn_system(&mut world, &mut game_state, &mut rng);
+            assert_eq!(swarm_count(&world), before + tick);
+        }
+
+        world.get::<&mut Health>(nest).unwrap().current = 0;
+        let result = nest_spawn_system(&mut world, &mut game_state, &mut rng);
+        assert_eq!(result.destroyed, vec![nest]);
+        assert!(!world.contains(nest));
+        assert!(game_state.destroyed_nests.contains(&TileMap::world_to_chunk(1000.0, 1000.0)));
+
+        nest_spawn_system(&mut world, &mut game_state, &mut rng);
+        assert_eq!(swarm_count(&world), before + 3);
+    }
+}
@@ -0,0 +1,53 @@
+use hecs::World;
+
+use crate::ecs::components::{Agent, AgentPersonality, AgentState, Health};
+use crate::game::agents::{trait_bonuses, RESILIENT_REGEN_INTERVAL_TICKS};
+use crate::protocol::AgentStateKind;
+
+/// Every [`RESILIENT_REGEN_INTERVAL_TICKS`], each resilient agent that is
+/// Idle heals 1 HP, up to its max.
+pub fn resilient_regen_system(world: &mut World, tick: u64) {
+    if !tick.is_multiple_of(RESILIENT_REGEN_INTERVAL_TICKS) {
+        return;
+    }
+    for (_e, (state, personality, health)) in
+        world.query_mut::<hecs::With<(&AgentState, &AgentPersonality, &mut Health), &Agent>>()
+    {
+        if state.state == AgentStateKind::Idle && trait_bonuses(&personality.traits).idle_regen {
+            health.current = (health.current + 1).min(health.max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_agent(world: &mut World, state: AgentStateKind, traits: &[&str]) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentState { state },
+            AgentPersonality { traits: traits.iter().map(|t| t.to_string()).collect() },
+            Health { current: 10, max: 11 },
+        ))
+    }
+
+    #[test]
+    fn resilient_idle_agents_regenerate() {
+        let mut world = World::new();
+        let resilient = spawn_agent(&mut world, AgentStateKind::Idle, &["resilient"]);
+        let busy = spawn_agent(&mut world, AgentStateKind::Building, &["resilient"]);
+        let plain = spawn_agent(&mut world, AgentStateKind::Idle, &["chatty"]);
+        let hp = |world: &World, e| world.get::<&Health>(e).unwrap().current;
+
+        resilient_regen_system(&mut world, RESILIENT_REGEN_INTERVAL_TICKS + 1);
+        assert_eq!(hp(&world, resilient), 10);
+
+        for n in 1..=2 {
+            resilient_regen_system(&mut world, RESILIENT_REGEN_INTERVAL_TICKS * n);
+        }
+        assert_eq!(hp(&world, resilient), 11);
+        assert_eq!(hp(&world, busy), 10);
+        assert_eq!(hp(&world, plain), 10);
+    }
+}
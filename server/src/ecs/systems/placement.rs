@@ -2,14 +2,42 @@ use hecs::World;
 
 use crate::ecs::components::{
     Building, BuildingEffects, BuildingType, ConstructionProgress, Health, LightSource, Position,
-    TokenEconomy,
+    TokenEconomy, ZoneOfControl,
 };
-use crate::game::building::get_building_definition;
+use crate::ecs::systems::economy::record_transaction;
+use crate::game::building::{get_building_definition, is_app};
+use crate::game::collision::{is_walkable, pixel_to_tile};
 use crate::protocol::BuildingTypeKind;
 
-/// Returns true if this building kind can have multiple instances.
-fn is_stackable(kind: &BuildingTypeKind) -> bool {
-    matches!(kind, BuildingTypeKind::Pylon | BuildingTypeKind::ComputeFarm)
+/// Default for [`PlacementRules::base_radius`] (pixels).
+pub const DEFAULT_BASE_BUILD_RADIUS: f32 = 600.0;
+
+/// Pixel size of one tile of a building's footprint.
+const FOOTPRINT_TILE_PX: f32 = 16.0;
+
+/// Where buildings may go, beyond cost and instance limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacementRules {
+    /// App buildings must stand within this distance of the Token Wheel.
+    pub base_radius: f32,
+}
+
+impl Default for PlacementRules {
+    fn default() -> Self {
+        Self { base_radius: DEFAULT_BASE_BUILD_RADIUS }
+    }
+}
+
+/// Maximum number of instances of this building kind.
+fn instance_limit(kind: &BuildingTypeKind) -> u32 {
+    match kind {
+        BuildingTypeKind::Pylon
+        | BuildingTypeKind::Relay
+        | BuildingTypeKind::Nexus
+        | BuildingTypeKind::ComputeFarm => u32::MAX,
+        BuildingTypeKind::Watchtower => 3,
+        _ => 1,
+    }
 }
 
 /// Returns true if this building kind has escalating costs per instance.
@@ -17,6 +45,105 @@ fn has_escalating_cost(kind: &BuildingTypeKind) -> bool {
     matches!(kind, BuildingTypeKind::Pylon | BuildingTypeKind::ComputeFarm)
 }
 
+/// The building this kind upgrades into, if any.
+pub fn upgrade_target(kind: &BuildingTypeKind) -> Option<BuildingTypeKind> {
+    match kind {
+        BuildingTypeKind::Pylon => Some(BuildingTypeKind::Relay),
+        BuildingTypeKind::Relay => Some(BuildingTypeKind::Nexus),
+        _ => None,
+    }
+}
+
+/// Half the width and height (pixels) of `kind`'s footprint.
+fn half_extents(kind: &BuildingTypeKind) -> (f32, f32) {
+    let def = get_building_definition(kind);
+    (def.width as f32 * FOOTPRINT_TILE_PX / 2.0, def.height as f32 * FOOTPRINT_TILE_PX / 2.0)
+}
+
+/// Checks that a `building_type` centred on `(x, y)` would stand entirely
+/// on walkable ground, clear of every other building, and (for apps) within
+/// `rules.base_radius` of the Token Wheel. A world without a Token Wheel
+/// has no base to measure from, so the distance check is skipped there.
+///
+/// Returns the reason placement would fail, worded for the player.
+pub fn validate_location(
+    world: &World,
+    building_type: BuildingTypeKind,
+    x: f32,
+    y: f32,
+    rules: &PlacementRules,
+) -> Result<(), String> {
+    let def = get_building_definition(&building_type);
+    let (half_w, half_h) = half_extents(&building_type);
+
+    // ── Terrain under the whole footprint ───────────────────────────
+    let (min_tx, max_tx) = (pixel_to_tile(x - half_w), pixel_to_tile(x + half_w - 1.0));
+    let (min_ty, max_ty) = (pixel_to_tile(y - half_h), pixel_to_tile(y + half_h - 1.0));
+    let blocked = (min_tx..=max_tx).any(|tx| (min_ty..=max_ty).any(|ty| !is_walkable(tx, ty)));
+    if blocked {
+        return Err(format!("Can't build a {} on water or cliffs.", def.name));
+    }
+
+    // ── Overlap with existing buildings ─────────────────────────────
+    for (_e, (bt, pos)) in world.query::<(&BuildingType, &Position)>().with::<&Building>().iter() {
+        let (other_w, other_h) = half_extents(&bt.kind);
+        if (pos.x - x).abs() < half_w + other_w && (pos.y - y).abs() < half_h + other_h {
+            return Err(format!(
+                "A {} there would overlap the {}.",
+                def.name,
+                get_building_definition(&bt.kind).name
+            ));
+        }
+    }
+
+    // ── Apps stay near the base ─────────────────────────────────────
+    // Infrastructure and Watchtowers are meant to reach out across the
+    // map, so they are exempt.
+    if is_app(&building_type) {
+        let wheel = world
+            .query::<(&BuildingType, &Position)>()
+            .iter()
+            .find(|(_e, (bt, _pos))| bt.kind == BuildingTypeKind::TokenWheel)
+            .map(|(_e, (_bt, pos))| (pos.x, pos.y));
+        if let Some((wx, wy)) = wheel {
+            let dist_sq = (x - wx).powi(2) + (y - wy).powi(2);
+            if dist_sq > rules.base_radius * rules.base_radius {
+                return Err(format!(
+                    "{} is too far from base: apps must be within {:.0}px of the Token Wheel.",
+                    def.name, rules.base_radius
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks everything [`place_building`] would, except whether the player can
+/// afford it, so the client can preview a placement.
+pub fn validate_placement(
+    world: &World,
+    building_type: BuildingTypeKind,
+    x: f32,
+    y: f32,
+    rules: &PlacementRules,
+) -> Result<(), String> {
+    if building_type == BuildingTypeKind::Nest {
+        return Err("Rogue nests can't be built.".to_string());
+    }
+    let def = get_building_definition(&building_type);
+    let existing_count = count_existing(world, &building_type);
+    let limit = instance_limit(&building_type);
+    if existing_count >= limit {
+        return Err(if limit == 1 {
+            format!("Already built a {}. Only one instance allowed.", def.name)
+        } else {
+            format!("Already built {} {}s. Only {} instances allowed.", existing_count, def.name, limit)
+        });
+    }
+    validate_location(world, building_type, x, y, rules)
+}
+
 /// Count how many buildings of the given kind already exist in the world.
 fn count_existing(world: &World, kind: &BuildingTypeKind) -> u32 {
     let mut count = 0u32;
@@ -38,13 +165,14 @@ fn escalating_cost(base_cost: i64, existing_count: u32) -> i64 {
 
 /// Attempts to place a building in the world.
 ///
-/// Checks that the player can afford the building, deducts the token cost from
-/// the economy, and spawns a new building entity with the appropriate
-/// components (including a light source if the building definition specifies
-/// one).
+/// Checks the placement is allowed (see [`validate_placement`]) and that the
+/// player can afford the building, deducts the token cost from the economy,
+/// and spawns a new building entity with the appropriate components
+/// (including a light source if the building definition specifies one).
 ///
-/// App buildings (non-infrastructure) are limited to 1 instance each.
-/// Pylons and Compute Farms can have multiple instances but cost more each time.
+/// App buildings (non-infrastructure) are limited to 1 instance each, and
+/// Watchtowers to 3. Pylons and Compute Farms can have multiple instances but
+/// cost more each time.
 ///
 /// Returns the newly spawned entity on success, or a descriptive error string.
 pub fn place_building(
@@ -52,19 +180,14 @@ pub fn place_building(
     building_type: BuildingTypeKind,
     x: f32,
     y: f32,
+    rules: &PlacementRules,
     economy: &mut TokenEconomy,
+    tick: u64,
 ) -> Result<hecs::Entity, String> {
+    validate_placement(world, building_type, x, y, rules)?;
     let def = get_building_definition(&building_type);
     let existing_count = count_existing(world, &building_type);
 
-    // ── Uniqueness check for non-stackable buildings ────────────────
-    if !is_stackable(&building_type) && existing_count > 0 {
-        return Err(format!(
-            "Already built a {}. Only one instance allowed.",
-            def.name
-        ));
-    }
-
     // ── Calculate actual cost (escalating for ComputeFarm only) ─────
     let actual_cost = if has_escalating_cost(&building_type) {
         escalating_cost(def.token_cost, existing_count)
@@ -81,10 +204,151 @@ pub fn place_building(
     }
 
     // ── Deduct cost ─────────────────────────────────────────────────
-    economy.balance -= actual_cost;
+    record_transaction(economy, -actual_cost, &format!("build {}", def.name), tick);
+
+    Ok(spawn_building(world, building_type, x, y))
+}
+
+/// Upgrades a completed building into the next kind on its upgrade path
+/// (Pylon → Relay → Nexus).
+///
+/// Checks that an upgrade exists and the player can afford it, deducts the
+/// upgraded building's cost, then despawns the old building and spawns the
+/// new one in its place under construction.
+///
+/// Returns the newly spawned entity on success, or a descriptive error string.
+pub fn upgrade_building(
+    world: &mut World,
+    entity: hecs::Entity,
+    economy: &mut TokenEconomy,
+    tick: u64,
+) -> Result<hecs::Entity, String> {
+    let (kind, x, y, complete) = {
+        let bt = world
+            .get::<&BuildingType>(entity)
+            .map_err(|_| "Not a building".to_string())?;
+        let pos = world
+            .get::<&Position>(entity)
+            .map_err(|_| "Building has no position".to_string())?;
+        let complete = world
+            .get::<&ConstructionProgress>(entity)
+            .map(|p| p.current >= p.total)
+            .unwrap_or(true);
+        (bt.kind, pos.x, pos.y, complete)
+    };
+
+    let from = get_building_definition(&kind);
+    let Some(target) = upgrade_target(&kind) else {
+        return Err(format!("{} cannot be upgraded", from.name));
+    };
+    if !complete {
+        return Err(format!("{} is still under construction", from.name));
+    }
+
+    let def = get_building_definition(&target);
+    if economy.balance < def.token_cost {
+        return Err(format!(
+            "Not enough tokens: need {}, have {}",
+            def.token_cost, economy.balance
+        ));
+    }
+
+    record_transaction(economy, -def.token_cost, &format!("upgrade to {}", def.name), tick);
+    let _ = world.despawn(entity);
+
+    Ok(spawn_building(world, target, x, y))
+}
+
+/// Tokens charged per missing HP when the player repairs a building.
+pub const REPAIR_TOKENS_PER_HP: f64 = 0.5;
+
+/// The player must be this close to a building to repair it.
+pub const REPAIR_RANGE: f32 = 80.0;
 
-    // ── Spawn the building entity ───────────────────────────────────
-    let entity = if let Some((radius, color)) = def.light_source {
+/// Tokens to restore `missing_hp`, rounded up.
+pub fn repair_cost(missing_hp: i32) -> i64 {
+    (missing_hp.max(0) as f64 * REPAIR_TOKENS_PER_HP).ceil() as i64
+}
+
+/// Restores a damaged, completed building to full health for
+/// [`repair_cost`] of its missing health. The player at `player_pos` must be
+/// within [`REPAIR_RANGE`]. A building at zero health is gone and can't be
+/// repaired.
+///
+/// Returns a log line on success, or a descriptive error string.
+pub fn repair_building(
+    world: &mut World,
+    entity: hecs::Entity,
+    player_pos: (f32, f32),
+    economy: &mut TokenEconomy,
+    tick: u64,
+) -> Result<String, String> {
+    let (kind, x, y, complete) = {
+        let bt = world
+            .get::<&BuildingType>(entity)
+            .map_err(|_| "Not a building".to_string())?;
+        let pos = world
+            .get::<&Position>(entity)
+            .map_err(|_| "Building has no position".to_string())?;
+        let complete = world
+            .get::<&ConstructionProgress>(entity)
+            .map(|p| p.current >= p.total)
+            .unwrap_or(true);
+        (bt.kind, pos.x, pos.y, complete)
+    };
+    let def = get_building_definition(&kind);
+
+    let mut health = world
+        .get::<&mut Health>(entity)
+        .map_err(|_| format!("{} has no health", def.name))?;
+    if health.current <= 0 {
+        return Err(format!("{} is destroyed", def.name));
+    }
+    if !complete {
+        return Err(format!("{} is still under construction", def.name));
+    }
+    if health.current >= health.max {
+        return Err(format!("{} is not damaged", def.name));
+    }
+    let (dx, dy) = (x - player_pos.0, y - player_pos.1);
+    if dx * dx + dy * dy > REPAIR_RANGE * REPAIR_RANGE {
+        return Err(format!("Too far from {}", def.name));
+    }
+
+    let cost = repair_cost(health.max - health.current);
+    if economy.balance < cost {
+        return Err(format!("Not enough tokens: need {}, have {}", cost, economy.balance));
+    }
+
+    record_transaction(economy, -cost, &format!("repair {}", def.name), tick);
+    health.current = health.max;
+    Ok(format!("{:?} fully repaired", kind))
+}
+
+/// The zone of control a building of this kind projects, if any.
+pub fn zone_of_control(kind: BuildingTypeKind) -> Option<ZoneOfControl> {
+    match kind {
+        BuildingTypeKind::ComputeFarm => Some(ZoneOfControl { radius: 80.0, slow_factor: 0.5 }),
+        BuildingTypeKind::Pylon => Some(ZoneOfControl { radius: 120.0, slow_factor: 0.3 }),
+        _ => None,
+    }
+}
+
+/// Spawns a new, unbuilt building entity with the components its definition
+/// calls for (including a light source if it specifies one, and any zone of
+/// control).
+fn spawn_building(world: &mut World, building_type: BuildingTypeKind, x: f32, y: f32) -> hecs::Entity {
+    let entity = spawn_with_definition(world, building_type, x, y);
+    if let Some(zone) = zone_of_control(building_type) {
+        let _ = world.insert_one(entity, zone);
+    }
+    entity
+}
+
+/// Spawns the components the building's definition calls for.
+fn spawn_with_definition(world: &mut World, building_type: BuildingTypeKind, x: f32, y: f32) -> hecs::Entity {
+    let def = get_building_definition(&building_type);
+    if let Some((radius, color)) = def.light_source {
         world.spawn((
             Building,
             Position { x, y },
@@ -93,6 +357,7 @@ pub fn place_building(
                 current: 0.0,
                 total: def.build_time,
                 assigned_agents: Vec::new(),
+                priority_weight: 1.0,
             },
             Health {
                 current: 100,
@@ -112,6 +377,7 @@ pub fn place_building(
                 current: 0.0,
                 total: def.build_time,
                 assigned_agents: Vec::new(),
+                priority_weight: 1.0,
             },
             Health {
                 current: 100,
@@ -121,7 +387,186 @@ pub fn place_building(
                 effects: def.effects,
             },
         ))
-    };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn economy(balance: i64) -> TokenEconomy {
+        TokenEconomy {
+            balance,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        }
+    }
+
+    fn complete(world: &mut World, entity: hecs::Entity) {
+        let mut progress = world.get::<&mut ConstructionProgress>(entity).unwrap();
+        progress.current = progress.total;
+    }
+
+    #[test]
+    fn up_to_three_watchtowers_allowed() {
+        let mut world = World::new();
+        let mut economy = economy(1000);
 
-    Ok(entity)
+        for i in 0..3 {
+            place_building(&mut world, BuildingTypeKind::Watchtower, i as f32 * 100.0, 0.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        }
+        assert!(place_building(&mut world, BuildingTypeKind::Watchtower, 400.0, 0.0, &PlacementRules::default(), &mut economy, 0).is_err());
+        assert_eq!(economy.balance, 1000 - 3 * 80);
+
+        place_building(&mut world, BuildingTypeKind::TodoApp, 0.0, 100.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        assert!(place_building(&mut world, BuildingTypeKind::TodoApp, 0.0, 200.0, &PlacementRules::default(), &mut economy, 0).is_err());
+    }
+
+    #[test]
+    fn pylon_upgrades_to_relay_then_nexus() {
+        let mut world = World::new();
+        let mut economy = economy(30 + 150 + 350);
+
+        let pylon = place_building(&mut world, BuildingTypeKind::Pylon, 50.0, 60.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        assert!(upgrade_building(&mut world, pylon, &mut economy, 0).is_err(), "unbuilt pylon upgraded");
+        complete(&mut world, pylon);
+
+        let relay = upgrade_building(&mut world, pylon, &mut economy, 0).unwrap();
+        assert!(!world.contains(pylon));
+        assert_eq!(world.get::<&BuildingType>(relay).unwrap().kind, BuildingTypeKind::Relay);
+        assert_eq!(economy.balance, 350);
+        complete(&mut world, relay);
+
+        let nexus = upgrade_building(&mut world, relay, &mut economy, 0).unwrap();
+        assert_eq!(world.get::<&BuildingType>(nexus).unwrap().kind, BuildingTypeKind::Nexus);
+        let pos = world.get::<&Position>(nexus).unwrap();
+        assert_eq!((pos.x, pos.y), (50.0, 60.0));
+        assert_eq!(economy.balance, 0);
+        assert_eq!(economy.transaction_log.back().unwrap().delta, -350);
+        drop(pos);
+
+        complete(&mut world, nexus);
+        let err = upgrade_building(&mut world, nexus, &mut economy, 0).unwrap_err();
+        assert!(err.contains("cannot be upgraded"));
+    }
+
+    #[test]
+    fn upgrades_require_a_path_and_tokens() {
+        let mut world = World::new();
+        let mut economy = economy(1000);
+
+        let app = place_building(&mut world, BuildingTypeKind::TodoApp, 0.0, 0.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        complete(&mut world, app);
+        let balance = economy.balance;
+        assert!(upgrade_building(&mut world, app, &mut economy, 0).is_err());
+        assert!(world.contains(app));
+        assert_eq!(economy.balance, balance);
+
+        let pylon = place_building(&mut world, BuildingTypeKind::Pylon, 100.0, 0.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        complete(&mut world, pylon);
+        economy.balance = 149;
+        assert!(upgrade_building(&mut world, pylon, &mut economy, 0).is_err());
+        assert!(world.contains(pylon));
+    }
+
+    #[test]
+    fn repair_cost_scales_with_missing_health() {
+        assert_eq!(repair_cost(0), 0);
+        assert_eq!(repair_cost(1), 1);
+        assert_eq!(repair_cost(40), 20);
+        assert_eq!(repair_cost(41), 21);
+        assert_eq!(repair_cost(-5), 0);
+    }
+
+    #[test]
+    fn repair_needs_the_player_nearby_and_a_living_building() {
+        let mut world = World::new();
+        let mut economy = economy(1000);
+        let app = place_building(&mut world, BuildingTypeKind::TodoApp, 0.0, 0.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        assert!(repair_building(&mut world, app, (0.0, 0.0), &mut economy, 0).is_err());
+        complete(&mut world, app);
+        assert!(repair_building(&mut world, app, (0.0, 0.0), &mut economy, 0).is_err());
+
+        world.get::<&mut Health>(app).unwrap().current = 60;
+        let before = economy.balance;
+        assert!(repair_building(&mut world, app, (REPAIR_RANGE + 1.0, 0.0), &mut economy, 0).is_err());
+        assert_eq!(economy.balance, before);
+
+        repair_building(&mut world, app, (REPAIR_RANGE - 1.0, 0.0), &mut economy, 0).unwrap();
+        assert_eq!(economy.balance, before - repair_cost(40));
+        let hp = world.get::<&Health>(app).unwrap();
+        assert_eq!(hp.current, hp.max);
+        drop(hp);
+
+        world.get::<&mut Health>(app).unwrap().current = 0;
+        assert!(repair_building(&mut world, app, (0.0, 0.0), &mut economy, 0).is_err());
+        assert_eq!(world.get::<&Health>(app).unwrap().current, 0);
+    }
+
+    /// Pixel position of the first unwalkable tile scanning out from the origin.
+    fn unwalkable_spot() -> (f32, f32) {
+        (0..400)
+            .flat_map(|ty| (0..400).map(move |tx| (tx, ty)))
+            .find(|&(tx, ty)| !is_walkable(tx, ty))
+            .map(|(tx, ty)| (crate::game::collision::tile_center(tx), crate::game::collision::tile_center(ty)))
+            .expect("the map has water or cliffs somewhere")
+    }
+
+    #[test]
+    fn placement_rejects_unwalkable_ground() {
+        let mut world = World::new();
+        let (x, y) = unwalkable_spot();
+        let err = place_building(&mut world, BuildingTypeKind::Pylon, x, y, &PlacementRules::default(), &mut economy(1000), 0)
+            .unwrap_err();
+        assert_eq!(err, "Can't build a Pylon on water or cliffs.");
+    }
+
+    #[test]
+    fn placement_rejects_overlapping_footprints() {
+        let mut world = World::new();
+        let mut economy = economy(1000);
+        let rules = PlacementRules::default();
+        place_building(&mut world, BuildingTypeKind::Pylon, 0.0, 0.0, &rules, &mut economy, 0).unwrap();
+
+        let err = validate_placement(&world, BuildingTypeKind::TodoApp, 16.0, 0.0, &rules).unwrap_err();
+        assert_eq!(err, "A Todo App there would overlap the Pylon.");
+        // Still blocked while only under construction, and the failed
+        // attempt costs nothing.
+        let balance = economy.balance;
+        assert!(place_building(&mut world, BuildingTypeKind::Pylon, 8.0, 8.0, &rules, &mut economy, 0).is_err());
+        assert_eq!(economy.balance, balance);
+    }
+
+    #[test]
+    fn apps_must_stay_near_the_token_wheel() {
+        let mut world = World::new();
+        world.spawn((BuildingType { kind: BuildingTypeKind::TokenWheel }, Position { x: 0.0, y: 0.0 }));
+        let rules = PlacementRules { base_radius: 200.0 };
+
+        let err = validate_placement(&world, BuildingTypeKind::TodoApp, 0.0, 256.0, &rules).unwrap_err();
+        assert_eq!(err, "Todo App is too far from base: apps must be within 200px of the Token Wheel.");
+        // Infrastructure may go anywhere walkable.
+        assert_eq!(validate_placement(&world, BuildingTypeKind::Pylon, 0.0, 256.0, &rules), Ok(()));
+        assert_eq!(validate_placement(&world, BuildingTypeKind::TodoApp, 0.0, 128.0, &rules), Ok(()));
+        assert!(validate_placement(&world, BuildingTypeKind::TodoApp, 0.0, 256.0, &PlacementRules::default()).is_ok());
+    }
+
+    #[test]
+    fn valid_placement_spawns_the_building() {
+        let (mut world, mut game_state) = crate::ecs::world::create_world();
+        game_state.economy.balance = 1000;
+        let rules = PlacementRules::default();
+        let (x, y) = (400.0, 400.0);
+        assert_eq!(validate_placement(&world, BuildingTypeKind::TodoApp, x, y, &rules), Ok(()));
+        let app = place_building(&mut world, BuildingTypeKind::TodoApp, x, y, &rules, &mut game_state.economy, 0).unwrap();
+        assert_eq!(world.get::<&BuildingType>(app).unwrap().kind, BuildingTypeKind::TodoApp);
+    }
 }
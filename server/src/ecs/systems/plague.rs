@@ -0,0 +1,125 @@
+use hecs::World;
+
+use crate::ecs::components::{Agent, AgentName, Position, Rogue, RogueType, StatusEffects};
+use crate::ecs::systems::status_effect::apply_status;
+use crate::protocol::{RogueTypeKind, StatusEffect};
+
+/// Radius (pixels) around a Plague rogue within which agents are corrupted.
+pub const PLAGUE_AURA_RADIUS: f32 = 40.0;
+
+/// Corruption applied (and refreshed) each tick an agent is in the aura.
+pub const PLAGUE_CORRUPTION: StatusEffect = StatusEffect::Corrupted { ticks_remaining: 120 };
+
+/// Factor applied to a Corrupted agent's `error_chance_base`.
+pub const CORRUPTED_ERROR_MULTIPLIER: f32 = 3.0;
+
+/// How close the player must be to cure a Corrupted agent.
+pub const CURE_RANGE: f32 = 30.0;
+
+/// Runs the plague aura for a single tick: every agent within
+/// [`PLAGUE_AURA_RADIUS`] of a Plague rogue is Corrupted, refreshing the
+/// timer of agents already infected.
+pub fn plague_aura_system(world: &mut World) {
+    let plagues: Vec<(f32, f32)> = world
+        .query::<(&Position, &RogueType)>()
+        .with::<&Rogue>()
+        .iter()
+        .filter(|(_e, (_pos, rt))| rt.kind == RogueTypeKind::Plague)
+        .map(|(_e, (pos, _rt))| (pos.x, pos.y))
+        .collect();
+    if plagues.is_empty() {
+        return;
+    }
+
+    let radius_sq = PLAGUE_AURA_RADIUS * PLAGUE_AURA_RADIUS;
+    let infected: Vec<hecs::Entity> = world
+        .query::<&Position>()
+        .with::<&Agent>()
+        .iter()
+        .filter(|(_e, pos)| {
+            plagues
+                .iter()
+                .any(|(px, py)| (pos.x - px).powi(2) + (pos.y - py).powi(2) <= radius_sq)
+        })
+        .map(|(e, _)| e)
+        .collect();
+
+    for agent in infected {
+        apply_status(world, agent, PLAGUE_CORRUPTION);
+    }
+}
+
+/// Whether `entity` currently carries the Corrupted effect.
+pub fn is_corrupted(world: &World, entity: hecs::Entity) -> bool {
+    world.get::<&StatusEffects>(entity).is_ok_and(|e| e.is_corrupted())
+}
+
+/// The nearest Corrupted agent within [`CURE_RANGE`] of `(x, y)`.
+pub fn nearest_corrupted_agent(world: &World, x: f32, y: f32) -> Option<hecs::Entity> {
+    world
+        .query::<(&Position, &StatusEffects)>()
+        .with::<&Agent>()
+        .iter()
+        .filter(|(_e, (_pos, effects))| effects.is_corrupted())
+        .map(|(e, (pos, _))| (e, (pos.x - x).powi(2) + (pos.y - y).powi(2)))
+        .filter(|&(_e, dist_sq)| dist_sq <= CURE_RANGE * CURE_RANGE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e)
+}
+
+/// Removes the Corrupted effect from `agent`, returning a log line if it was
+/// corrupted.
+pub fn cure_agent(world: &mut World, agent: hecs::Entity) -> Option<String> {
+    let mut effects = world.get::<&mut StatusEffects>(agent).ok()?;
+    if !effects.is_corrupted() {
+        return None;
+    }
+    effects.effects.retain(|e| !matches!(e, StatusEffect::Corrupted { .. }));
+    drop(effects);
+
+    let name = world
+        .get::<&AgentName>(agent)
+        .map(|n| n.name.clone())
+        .unwrap_or_else(|_| "agent".to_string());
+    Some(format!("{} cured of corruption", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::systems::spawn::spawn_rogue;
+
+    fn spawn_agent(world: &mut World, x: f32) -> hecs::Entity {
+        world.spawn((Agent, AgentName { name: "ada".to_string() }, Position { x, y: 0.0 }))
+    }
+
+    #[test]
+    fn plague_corrupts_agents_in_its_aura() {
+        let mut world = World::new();
+        spawn_rogue(&mut world, 0.0, 0.0, RogueTypeKind::Plague);
+        let near = spawn_agent(&mut world, PLAGUE_AURA_RADIUS);
+        let far = spawn_agent(&mut world, PLAGUE_AURA_RADIUS + 1.0);
+
+        plague_aura_system(&mut world);
+
+        let effects = world.get::<&StatusEffects>(near).unwrap();
+        assert_eq!(effects.effects, vec![StatusEffect::Corrupted { ticks_remaining: 120 }]);
+        assert!(!is_corrupted(&world, far));
+    }
+
+    #[test]
+    fn player_can_cure_a_nearby_corrupted_agent() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, 0.0);
+        apply_status(&mut world, agent, PLAGUE_CORRUPTION);
+        apply_status(&mut world, agent, StatusEffect::Slowed { factor: 0.5, ticks_remaining: 10 });
+
+        assert_eq!(nearest_corrupted_agent(&world, CURE_RANGE + 1.0, 0.0), None);
+        assert_eq!(nearest_corrupted_agent(&world, CURE_RANGE, 0.0), Some(agent));
+
+        assert_eq!(cure_agent(&mut world, agent), Some("ada cured of corruption".to_string()));
+        assert!(!is_corrupted(&world, agent));
+        assert_eq!(world.get::<&StatusEffects>(agent).unwrap().effects.len(), 1);
+        assert_eq!(cure_agent(&mut world, agent), None);
+    }
+}
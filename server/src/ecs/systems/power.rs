@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+
+use hecs::World;
+
+use crate::ecs::components::{
+    Building, BuildingEffect, BuildingEffects, BuildingType, ConstructionProgress, GamePhase, Position,
+};
+use crate::protocol::BuildingTypeKind;
+
+/// Radius a completed pylon powers before any `PylonRangeBoost`.
+pub const PYLON_BASE_RADIUS: f32 = 200.0;
+
+/// Income multiplier for a building inside at least one pylon's radius.
+pub const POWERED_INCOME_MULT: f64 = 1.25;
+
+/// Income multiplier for an unpowered building during the Network and City
+/// phases.
+pub const BROWNOUT_INCOME_MULT: f64 = 0.5;
+
+/// How often the economy warns about browned-out buildings (30s at 20Hz).
+pub const BROWNOUT_WARNING_INTERVAL_TICKS: u64 = 600;
+
+/// Whether this building kind is part of the pylon network (Pylon and its
+/// upgrades).
+pub fn is_pylon(kind: BuildingTypeKind) -> bool {
+    matches!(kind, BuildingTypeKind::Pylon | BuildingTypeKind::Relay | BuildingTypeKind::Nexus)
+}
+
+/// The radius a pylon with these effects powers.
+pub fn pylon_radius(effects: &[BuildingEffect]) -> f32 {
+    PYLON_BASE_RADIUS
+        + effects
+            .iter()
+            .map(|e| match e {
+                BuildingEffect::PylonRangeBoost(boost) => *boost,
+                _ => 0.0,
+            })
+            .sum::<f32>()
+}
+
+/// Which buildings sit inside the pylon network's coverage.
+///
+/// Coverage is cached: [`PowerGrid::update`] only recomputes it when a
+/// building has been added, removed, upgraded or finished since the last
+/// call.
+#[derive(Debug, Default)]
+pub struct PowerGrid {
+    powered: HashSet<hecs::Entity>,
+    /// Every building, its kind and whether it is complete, as of the last
+    /// recompute.
+    signature: Vec<(hecs::Entity, BuildingTypeKind, bool)>,
+}
+
+impl PowerGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recomputes coverage if the set of buildings changed. Returns true if
+    /// it did.
+    pub fn update(&mut self, world: &World) -> bool {
+        let mut signature: Vec<(hecs::Entity, BuildingTypeKind, bool)> = world
+            .query::<(&BuildingType, Option<&ConstructionProgress>)>()
+            .with::<&Building>()
+            .iter()
+            .map(|(e, (bt, progress))| (e, bt.kind, progress.is_none_or(|p| p.current >= p.total)))
+            .collect();
+        signature.sort_by_key(|(e, _, _)| e.to_bits());
+        if signature == self.signature {
+            return false;
+        }
+        self.signature = signature;
+        self.recompute(world);
+        true
+    }
+
+    fn recompute(&mut self, world: &World) {
+        let pylons: Vec<(f32, f32, f32)> = world
+            .query::<(&Position, &BuildingType, Option<&ConstructionProgress>, Option<&BuildingEffects>)>()
+            .with::<&Building>()
+            .iter()
+            .filter(|(_e, (_pos, bt, progress, _effects))| {
+                is_pylon(bt.kind) && progress.is_none_or(|p| p.current >= p.total)
+            })
+            .map(|(_e, (pos, _bt, _progress, effects))| {
+                let radius = pylon_radius(effects.map(|e| e.effects.as_slice()).unwrap_or(&[]));
+                (pos.x, pos.y, radius)
+            })
+            .collect();
+
+        self.powered = world
+            .query::<&Position>()
+            .with::<&Building>()
+            .iter()
+            .filter(|(_e, pos)| {
+                pylons
+                    .iter()
+                    .any(|&(x, y, r)| (pos.x - x).powi(2) + (pos.y - y).powi(2) <= r * r)
+            })
+            .map(|(e, _pos)| e)
+            .collect();
+    }
+
+    /// Whether `building` was inside a pylon's radius at the last update.
+    pub fn is_powered(&self, building: hecs::Entity) -> bool {
+        self.powered.contains(&building)
+    }
+
+    /// The income multiplier for `building` in `phase`: a bonus when powered,
+    /// a brownout penalty when unpowered from the Network phase on.
+    pub fn income_multiplier(&self, building: hecs::Entity, phase: &GamePhase) -> f64 {
+        if self.is_powered(building) {
+            POWERED_INCOME_MULT
+        } else if matches!(phase, GamePhase::Network | GamePhase::City) {
+            BROWNOUT_INCOME_MULT
+        } else {
+            1.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_completed(world: &mut World, kind: BuildingTypeKind, x: f32, y: f32, effects: Vec<BuildingEffect>) -> hecs::Entity {
+        world.spawn((
+            Building,
+            Position { x, y },
+            BuildingType { kind },
+            ConstructionProgress { current: 1.0, total: 1.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+            BuildingEffects { effects },
+        ))
+    }
+
+    #[test]
+    fn buildings_inside_a_pylon_radius_are_powered() {
+        let mut world = World::new();
+        spawn_completed(&mut world, BuildingTypeKind::Pylon, 0.0, 0.0, vec![]);
+        let near = spawn_completed(&mut world, BuildingTypeKind::TodoApp, 150.0, 0.0, vec![]);
+        let far = spawn_completed(&mut world, BuildingTypeKind::ComputeFarm, 300.0, 0.0, vec![]);
+
+        let mut grid = PowerGrid::new();
+        assert!(grid.update(&world));
+        assert!(grid.is_powered(near));
+        assert!(!grid.is_powered(far));
+        assert_eq!(grid.income_multiplier(near, &GamePhase::Hut), POWERED_INCOME_MULT);
+    }
+
+    #[test]
+    fn range_boost_extends_coverage() {
+        let mut world = World::new();
+        spawn_completed(&mut world, BuildingTypeKind::Relay, 0.0, 0.0, vec![BuildingEffect::PylonRangeBoost(160.0)]);
+        let far = spawn_completed(&mut world, BuildingTypeKind::ComputeFarm, 300.0, 0.0, vec![]);
+
+        let mut grid = PowerGrid::new();
+        grid.update(&world);
+        assert!(grid.is_powered(far));
+    }
+
+    #[test]
+    fn unfinished_pylons_power_nothing() {
+        let mut world = World::new();
+        let pylon = spawn_completed(&mut world, BuildingTypeKind::Pylon, 0.0, 0.0, vec![]);
+        world.get::<&mut ConstructionProgress>(pylon).unwrap().current = 0.5;
+        let farm = spawn_completed(&mut world, BuildingTypeKind::ComputeFarm, 50.0, 0.0, vec![]);
+
+        let mut grid = PowerGrid::new();
+        grid.update(&world);
+        assert!(!grid.is_powered(farm));
+    }
+
+    #[test]
+    fn brownout_only_applies_from_the_network_phase() {
+        let mut world = World::new();
+        let farm = spawn_completed(&mut world, BuildingTypeKind::ComputeFarm, 0.0, 0.0, vec![]);
+        let mut grid = PowerGrid::new();
+        grid.update(&world);
+
+        assert_eq!(grid.income_multiplier(farm, &GamePhase::Village), 1.0);
+        assert_eq!(grid.income_multiplier(farm, &GamePhase::Network), BROWNOUT_INCOME_MULT);
+        assert_eq!(grid.income_multiplier(farm, &GamePhase::City), BROWNOUT_INCOME_MULT);
+    }
+
+    #[test]
+    fn coverage_is_only_recomputed_when_buildings_change() {
+        let mut world = World::new();
+        let farm = spawn_completed(&mut world, BuildingTypeKind::ComputeFarm, 0.0, 0.0, vec![]);
+        let mut grid = PowerGrid::new();
+        assert!(grid.update(&world));
+        assert!(!grid.update(&world));
+
+        spawn_completed(&mut world, BuildingTypeKind::Pylon, 10.0, 0.0, vec![]);
+        assert!(grid.update(&world));
+        assert!(grid.is_powered(farm));
+        assert!(!grid.update(&world));
+    }
+}
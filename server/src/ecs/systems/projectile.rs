@@ -1,6 +1,26 @@
+use std::collections::HashMap;
+
 use hecs::World;
-use crate::ecs::components::{Health, Position, Projectile, Rogue, RogueType};
-use crate::protocol::{AudioEvent, CombatEvent, RogueTypeKind};
+use crate::ecs::components::{
+    Agent, AgentName, AgentState, Armor, Building, ConstructionProgress, Health, Player, Position,
+    Projectile, ReviveTimer, Rogue, RogueType,
+};
+use crate::ecs::systems::combat::is_hidden_mimic;
+use crate::ecs::systems::dodge::has_iframes;
+use crate::ecs::systems::loot::drop_loot;
+use crate::ecs::systems::morale::{adjust_morale, DAMAGE_MORALE_LOSS};
+use crate::ecs::systems::revival::REVIVE_WINDOW_TICKS;
+use crate::ecs::systems::spawn::death_spawns;
+use crate::ecs::systems::token_drain::drain_refund;
+use crate::game::collision;
+use crate::game::spatial::SpatialGrid;
+use crate::protocol::{AgentStateKind, AudioEvent, CombatEvent, DropEvent, RogueTypeKind};
+
+/// Distance within which a projectile hits a rogue, agent or the player.
+pub const PROJECTILE_HIT_RANGE: f32 = 8.0;
+
+/// Distance within which a projectile strikes a completed building.
+pub const BUILDING_HIT_RANGE: f32 = 16.0;
 
 pub struct ProjectileResult {
     pub despawned: Vec<hecs::Entity>,
@@ -8,6 +28,16 @@ pub struct ProjectileResult {
     pub combat_events: Vec<CombatEvent>,
     pub audio_events: Vec<AudioEvent>,
     pub bounty_tokens: i64,
+    /// Stolen tokens handed back by killed TokenDrains (included in
+    /// `bounty_tokens`); the caller records the transaction.
+    pub refund_tokens: i64,
+    /// Rogues to spawn once projectiles resolve, e.g. Swarms from a Multiplier.
+    pub pending_spawns: Vec<(f32, f32, RogueTypeKind)>,
+    /// Damage dealt to the player by rogue projectiles.
+    pub player_hit_damage: i32,
+    pub log_entries: Vec<String>,
+    /// Loot dropped by killed rogues.
+    pub drops: Vec<DropEvent>,
 }
 
 fn bounty_for(kind: RogueTypeKind) -> i64 {
@@ -19,20 +49,34 @@ fn bounty_for(kind: RogueTypeKind) -> i64 {
         RogueTypeKind::Assassin => 30,
         RogueTypeKind::Mimic => 15,
         RogueTypeKind::Architect => 50,
+        RogueTypeKind::Multiplier => 20,
+        RogueTypeKind::Plague => 18,
     }
 }
 
-pub fn projectile_system(world: &mut World) -> ProjectileResult {
+/// Moves projectiles and resolves their hits.
+///
+/// A projectile that flies onto an impassable tile or into a completed
+/// building is destroyed; rogue-owned projectiles damage the building they
+/// strike. Player projectiles hit rogues in `rogue_grid` (killed rogues are
+/// removed from the grid); rogue projectiles hit the player (unless
+/// `god_mode` or mid-dodge, with armor reduction) and active agents.
+pub fn projectile_system(world: &mut World, rogue_grid: &mut SpatialGrid, god_mode: bool) -> ProjectileResult {
     let mut result = ProjectileResult {
         despawned: Vec::new(),
         killed_rogues: Vec::new(),
         combat_events: Vec::new(),
         audio_events: Vec::new(),
         bounty_tokens: 0,
+        refund_tokens: 0,
+        pending_spawns: Vec::new(),
+        player_hit_damage: 0,
+        log_entries: Vec::new(),
+        drops: Vec::new(),
     };
 
     // Move projectiles and track which are still alive
-    let mut live_projectiles: Vec<(hecs::Entity, Position, i32, bool)> = Vec::new();
+    let mut live_projectiles: Vec<(hecs::Entity, Position, i32, bool, bool)> = Vec::new();
     let mut to_despawn: Vec<hecs::Entity> = Vec::new();
 
     for (entity, (pos, proj)) in world.query_mut::<(&mut Position, &mut Projectile)>() {
@@ -40,35 +84,64 @@ pub fn projectile_system(world: &mut World) -> ProjectileResult {
         pos.y += proj.dy * proj.speed;
         proj.range_remaining -= proj.speed;
 
-        if proj.range_remaining <= 0.0 {
+        let blocked = !collision::is_walkable(collision::pixel_to_tile(pos.x), collision::pixel_to_tile(pos.y));
+        if proj.range_remaining <= 0.0 || blocked {
             to_despawn.push(entity);
         } else {
-            live_projectiles.push((entity, pos.clone(), proj.damage, proj.owner_is_player));
+            live_projectiles.push((entity, pos.clone(), proj.damage, proj.owner_is_player, proj.is_crit));
         }
     }
 
     // Gather rogues for collision
-    let rogues: Vec<(hecs::Entity, Position, RogueTypeKind)> = world
+    let rogues: HashMap<hecs::Entity, (Position, RogueTypeKind)> = world
         .query::<(&Rogue, &Position, &RogueType)>()
         .iter()
-        .map(|(e, (_, p, rt))| (e, p.clone(), rt.kind))
+        .filter(|(e, _)| !is_hidden_mimic(world, *e))
+        .map(|(e, (_, p, rt))| (e, (p.clone(), rt.kind)))
+        .collect();
+
+    // Completed buildings block every projectile
+    let buildings: Vec<(hecs::Entity, f32, f32)> = world
+        .query::<(&Position, Option<&ConstructionProgress>)>()
+        .with::<&Building>()
+        .iter()
+        .filter(|(_e, (_pos, progress))| progress.is_none_or(|p| p.current >= p.total))
+        .map(|(e, (pos, _))| (e, pos.x, pos.y))
         .collect();
 
     // Check collisions
-    let hit_range_sq: f32 = 8.0 * 8.0;
+    for (proj_entity, proj_pos, proj_damage, is_player, is_crit) in &live_projectiles {
+        let struck = buildings.iter().find(|(_e, bx, by)| {
+            (bx - proj_pos.x).powi(2) + (by - proj_pos.y).powi(2) <= BUILDING_HIT_RANGE * BUILDING_HIT_RANGE
+        });
+        if let Some(&(building, _, _)) = struck {
+            if !is_player {
+                if let Ok(mut health) = world.get::<&mut Health>(building) {
+                    health.current = (health.current - proj_damage).max(0);
+                }
+            }
+            to_despawn.push(*proj_entity);
+            continue;
+        }
 
-    for (proj_entity, proj_pos, proj_damage, is_player) in &live_projectiles {
-        if !is_player { continue; }
+        if !is_player {
+            if rogue_projectile_hit(world, proj_pos, *proj_damage, god_mode, &mut result) {
+                to_despawn.push(*proj_entity);
+            }
+            continue;
+        }
 
-        for &(rogue_entity, ref rogue_pos, rogue_kind) in &rogues {
-            let dx = proj_pos.x - rogue_pos.x;
-            let dy = proj_pos.y - rogue_pos.y;
-            if dx * dx + dy * dy > hit_range_sq { continue; }
+        for rogue_entity in rogue_grid.query_radius(proj_pos.x, proj_pos.y, PROJECTILE_HIT_RANGE) {
+            let Some((rogue_pos, rogue_kind)) = rogues.get(&rogue_entity) else { continue };
+            let rogue_kind = *rogue_kind;
 
             // Hit!
             if let Ok(mut health) = world.get::<&mut Health>(rogue_entity) {
                 health.current -= proj_damage;
                 result.audio_events.push(AudioEvent::CombatHit);
+                if *is_crit {
+                    result.audio_events.push(AudioEvent::CritHit);
+                }
                 let is_kill = health.current <= 0;
                 result.combat_events.push(CombatEvent {
                     x: rogue_pos.x,
@@ -76,6 +149,7 @@ pub fn projectile_system(world: &mut World) -> ProjectileResult {
                     damage: *proj_damage,
                     is_kill,
                     rogue_type: Some(rogue_kind),
+                    is_crit: *is_crit,
                 });
 
                 if is_kill {
@@ -97,9 +171,181 @@ pub fn projectile_system(world: &mut World) -> ProjectileResult {
     }
 
     // Despawn killed rogues
-    for &(rogue_entity, _) in &result.killed_rogues {
+    for &(rogue_entity, kind) in &result.killed_rogues {
+        if let Ok(pos) = world.get::<&Position>(rogue_entity) {
+            result.pending_spawns.extend(death_spawns(kind, pos.x, pos.y));
+        }
+        result.drops.extend(drop_loot(world, rogue_entity, &mut rand::thread_rng()));
+        let refund = drain_refund(world, rogue_entity);
+        result.refund_tokens += refund;
+        result.bounty_tokens += refund;
         let _ = world.despawn(rogue_entity);
+        rogue_grid.remove(rogue_entity);
     }
 
     result
 }
+
+/// Resolves a rogue-owned projectile at `pos` against the player and active
+/// agents, damaging the first one in range. Returns whether it hit.
+fn rogue_projectile_hit(world: &mut World, pos: &Position, damage: i32, god_mode: bool, result: &mut ProjectileResult) -> bool {
+    let in_range = |p: &Position| (p.x - pos.x).powi(2) + (p.y - pos.y).powi(2) <= PROJECTILE_HIT_RANGE * PROJECTILE_HIT_RANGE;
+
+    let player = world
+        .query::<(&Position, Option<&Armor>)>()
+        .with::<&Player>()
+        .iter()
+        .find(|(_e, (p, _armor))| in_range(p))
+        .map(|(e, (_p, armor))| (e, armor.map_or(0.0, |a| a.damage_reduction)));
+    if let Some((player, armor_def)) = player {
+        if !god_mode && !has_iframes(world, player) {
+            let final_dmg = (damage - armor_def as i32).max(1);
+            if let Ok(mut health) = world.get::<&mut Health>(player) {
+                health.current -= final_dmg;
+                result.player_hit_damage += final_dmg;
+                result.audio_events.push(AudioEvent::CombatHit);
+            }
+        }
+        return true;
+    }
+
+    let agent = world
+        .query::<(&Position, &AgentState, &AgentName)>()
+        .with::<&Agent>()
+        .iter()
+        .find(|(_e, (p, state, _name))| {
+            in_range(p) && !matches!(state.state, AgentStateKind::Unresponsive | AgentStateKind::Dormant)
+        })
+        .map(|(e, (_p, _state, name))| (e, name.name.clone()));
+    let Some((agent, name)) = agent else { return false };
+
+    adjust_morale(world, agent, -DAMAGE_MORALE_LOSS);
+    let downed = match world.get::<&mut Health>(agent) {
+        Ok(mut health) => {
+            health.current -= damage;
+            health.current <= 0
+        }
+        Err(_) => false,
+    };
+    if downed {
+        if let Ok(mut state) = world.get::<&mut AgentState>(agent) {
+            state.state = AgentStateKind::Unresponsive;
+        }
+        let _ = world.insert_one(agent, ReviveTimer { ticks_remaining: REVIVE_WINDOW_TICKS });
+        result.log_entries.push(format!("[agent_{}] has stopped responding.", name));
+        result.audio_events.push(AudioEvent::AgentDeath);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::systems::spawn::{spawn_pending, spawn_rogue};
+
+    #[test]
+    fn killing_a_multiplier_spawns_two_swarms() {
+        let mut world = World::new();
+        spawn_rogue(&mut world, 100.0, 100.0, RogueTypeKind::Multiplier);
+        let mut grid = SpatialGrid::default();
+        grid.insert_all::<Rogue>(&world);
+        for (_e, health) in world.query_mut::<&mut Health>() {
+            health.current = 1;
+        }
+        world.spawn((
+            Position { x: 94.0, y: 100.0 },
+            Projectile { dx: 1.0, dy: 0.0, speed: 6.0, damage: 10, range_remaining: 100.0, owner_is_player: true, is_crit: false },
+        ));
+
+        let result = projectile_system(&mut world, &mut grid, false);
+        assert_eq!(result.killed_rogues.len(), 1);
+        assert_eq!(result.bounty_tokens, 20);
+        spawn_pending(&mut world, &result.pending_spawns);
+
+        let kinds: Vec<RogueTypeKind> = world.query::<&RogueType>().iter().map(|(_e, rt)| rt.kind).collect();
+        assert_eq!(kinds, vec![RogueTypeKind::Swarm, RogueTypeKind::Swarm]);
+        let mut xs: Vec<f32> = world.query::<(&Rogue, &Position)>().iter().map(|(_e, (_r, p))| p.x).collect();
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(xs, vec![80.0, 120.0]);
+    }
+
+    fn bolt(x: f32, y: f32, owner_is_player: bool) -> (Position, Projectile) {
+        (
+            Position { x, y },
+            Projectile { dx: 1.0, dy: 0.0, speed: 4.0, damage: 6, range_remaining: 100.0, owner_is_player, is_crit: false },
+        )
+    }
+
+    /// Centre of a tile whose row has a few walkable tiles to its right.
+    fn open_ground() -> (f32, f32) {
+        for ty in 0..200 {
+            for tx in 0..200 {
+                if (0..4).all(|dx| collision::is_walkable(tx + dx, ty)) {
+                    return (collision::tile_center(tx), collision::tile_center(ty));
+                }
+            }
+        }
+        panic!("no open ground near the origin");
+    }
+
+    #[test]
+    fn projectile_is_destroyed_by_impassable_terrain() {
+        let (tx, ty) = (0..400)
+            .flat_map(|ty| (0..400).map(move |tx| (tx, ty)))
+            .find(|&(tx, ty)| !collision::is_walkable(tx, ty))
+            .expect("no impassable tile near the origin");
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let proj = world.spawn(bolt(collision::tile_center(tx) - 4.0, collision::tile_center(ty), true));
+
+        let result = projectile_system(&mut world, &mut grid, false);
+        assert_eq!(result.despawned, vec![proj]);
+        assert!(!world.contains(proj));
+    }
+
+    #[test]
+    fn rogue_projectile_damages_the_player() {
+        let (x, y) = open_ground();
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let player = world.spawn((Player, Position { x: x + 4.0, y }, Health { current: 100, max: 100 }));
+        let proj = world.spawn(bolt(x, y, false));
+
+        let result = projectile_system(&mut world, &mut grid, false);
+        assert_eq!(result.player_hit_damage, 6);
+        assert_eq!(world.get::<&Health>(player).unwrap().current, 94);
+        assert!(!world.contains(proj));
+
+        world.get::<&mut Health>(player).unwrap().current = 100;
+        world.spawn(bolt(x, y, false));
+        assert_eq!(projectile_system(&mut world, &mut grid, true).player_hit_damage, 0);
+        assert_eq!(world.get::<&Health>(player).unwrap().current, 100);
+    }
+
+    #[test]
+    fn player_projectile_passes_over_the_player() {
+        let (x, y) = open_ground();
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let player = world.spawn((Player, Position { x: x + 4.0, y }, Health { current: 100, max: 100 }));
+        let proj = world.spawn(bolt(x, y, true));
+
+        let result = projectile_system(&mut world, &mut grid, false);
+        assert_eq!(result.player_hit_damage, 0);
+        assert_eq!(world.get::<&Health>(player).unwrap().current, 100);
+        assert!(world.contains(proj));
+    }
+
+    #[test]
+    fn rogue_projectile_damages_a_completed_building() {
+        let (x, y) = open_ground();
+        let mut world = World::new();
+        let mut grid = SpatialGrid::default();
+        let building = world.spawn((Building, Position { x: x + 4.0, y }, Health { current: 50, max: 50 }));
+        let proj = world.spawn(bolt(x, y, false));
+
+        projectile_system(&mut world, &mut grid, false);
+        assert_eq!(world.get::<&Health>(building).unwrap().current, 44);
+        assert!(!world.contains(proj));
+    }
+}
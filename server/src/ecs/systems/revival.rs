@@ -0,0 +1,119 @@
+use hecs::World;
+
+use crate::ecs::components::{Agent, AgentName, AgentState, ReviveTimer};
+use crate::protocol::AgentStateKind;
+
+/// Ticks a downed agent can wait for revival before it is lost (15 seconds at 20Hz).
+pub const REVIVE_WINDOW_TICKS: u32 = 300;
+
+/// Result returned by [`revive_timer_system`] each tick.
+pub struct ReviveTimerResult {
+    pub log_entries: Vec<String>,
+    /// Agents whose revival window ran out and were despawned.
+    pub despawned: Vec<hecs::Entity>,
+}
+
+/// Runs the revival countdown for a single tick.
+///
+/// Every Unresponsive agent carries a [`ReviveTimer`] (attached here if it
+/// somehow lacks one). The timer ticks down and, once it runs out, the agent
+/// is despawned for good.
+pub fn revive_timer_system(world: &mut World) -> ReviveTimerResult {
+    let mut log_entries = Vec::new();
+
+    // ── Lazily attach timers to downed agents that lack one ───────
+    let missing: Vec<hecs::Entity> = world
+        .query::<hecs::Without<hecs::With<&AgentState, &Agent>, &ReviveTimer>>()
+        .iter()
+        .filter(|(_e, state)| state.state == AgentStateKind::Unresponsive)
+        .map(|(e, _)| e)
+        .collect();
+    for e in missing {
+        let _ = world.insert_one(e, ReviveTimer { ticks_remaining: REVIVE_WINDOW_TICKS });
+    }
+
+    let mut expired = Vec::new();
+    for (id, (state, timer)) in world.query_mut::<hecs::With<(&AgentState, &mut ReviveTimer), &Agent>>() {
+        if state.state != AgentStateKind::Unresponsive {
+            continue;
+        }
+        timer.ticks_remaining = timer.ticks_remaining.saturating_sub(1);
+        if timer.ticks_remaining == 0 {
+            expired.push(id);
+        }
+    }
+
+    for &entity in &expired {
+        let name = world
+            .get::<&AgentName>(entity)
+            .map(|n| n.name.clone())
+            .unwrap_or_else(|_| "an agent".to_string());
+        log_entries.push(format!("[agent_{}] is gone for good.", name));
+        let _ = world.despawn(entity);
+    }
+
+    ReviveTimerResult { log_entries, despawned: expired }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::{Health, TokenEconomy};
+    use crate::game::agents::{revival_cost, revive_agent};
+    use crate::protocol::AgentTierKind;
+
+    fn downed_agent(world: &mut World) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentName { name: "sol".to_string() },
+            AgentState { state: AgentStateKind::Unresponsive },
+            Health { current: 0, max: 100 },
+        ))
+    }
+
+    #[test]
+    fn unrevived_agent_is_lost_when_the_timer_expires() {
+        let mut world = World::new();
+        let agent = downed_agent(&mut world);
+
+        for _ in 0..REVIVE_WINDOW_TICKS - 1 {
+            assert!(revive_timer_system(&mut world).despawned.is_empty());
+        }
+        assert_eq!(world.get::<&ReviveTimer>(agent).unwrap().ticks_remaining, 1);
+
+        let result = revive_timer_system(&mut world);
+        assert_eq!(result.despawned, vec![agent]);
+        assert_eq!(result.log_entries, vec!["[agent_sol] is gone for good.".to_string()]);
+        assert!(!world.contains(agent));
+    }
+
+    #[test]
+    fn revived_agent_keeps_its_place() {
+        let mut world = World::new();
+        let agent = downed_agent(&mut world);
+        world.insert_one(agent, crate::ecs::components::AgentTier { tier: AgentTierKind::Apprentice }).unwrap();
+        revive_timer_system(&mut world);
+
+        let mut economy = TokenEconomy {
+            balance: 100,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        };
+        revive_agent(&mut world, agent, &mut economy, 0).unwrap();
+        assert_eq!(economy.balance, 100 - revival_cost(AgentTierKind::Apprentice));
+        assert!(world.get::<&ReviveTimer>(agent).is_err());
+
+        for _ in 0..REVIVE_WINDOW_TICKS {
+            revive_timer_system(&mut world);
+        }
+        assert!(world.contains(agent));
+    }
+}
@@ -2,10 +2,13 @@ use hecs::World;
 use rand::Rng;
 
 use crate::ecs::components::{
-    Building, Collider, GamePhase, GameState, Health, Position, Rogue, RogueAI,
-    RogueBehaviorState, RogueType, RogueVisibility, Velocity,
+    Building, Collider, GamePhase, GameState, Health, MimicDisguise, MimicPhase, PackBonus, Position, Rogue,
+    RogueAI, RogueBehaviorState, RogueType, RogueVisibility, Velocity, WaveRogue, WaveSpec,
 };
-use crate::protocol::RogueTypeKind;
+use crate::ecs::systems::economy::record_transaction;
+use crate::ecs::systems::loot::loot_table_for;
+use crate::game::upgrades::UpgradeState;
+use crate::protocol::{AudioEvent, BuildingTypeKind, RogueTypeKind};
 
 /// Ticks between cascade waves (30 seconds at 20 Hz).
 const CASCADE_WAVE_INTERVAL: u64 = 600;
@@ -16,43 +19,66 @@ const CASCADE_TOTAL_WAVES: u64 = 10;
 /// Ticks after city_reached_tick before cascade begins (matches progression.rs).
 const CASCADE_DELAY: u64 = 6000;
 
+/// Normal spawning stops while this many rogues are alive, so long idle
+/// sessions don't pile up rogues.  Cascade waves ignore the cap.
+pub const MAX_ROGUES: usize = 150;
+
+/// Ticks of warning before a rogue wave hits (15 seconds at 20 Hz).
+pub const WAVE_WARNING_TICKS: u64 = 300;
+
+/// Ticks an arriving wave takes to finish spawning.
+pub const WAVE_SPAWN_TICKS: u64 = 5;
+
+/// Each wave survived brings the next one this many ticks sooner.
+const WAVE_INTERVAL_DECAY_TICKS: u64 = 20;
+
+/// Shortest gap between waves, however many have come.
+pub const MIN_WAVE_INTERVAL_TICKS: u64 = 200;
+
+/// Rogues in the first wave, before scaling with buildings.
+const WAVE_BASE_SIZE: u32 = 4;
+
+/// Extra rogues per wave survived.
+const WAVE_SIZE_PER_WAVE: u32 = 2;
+
+/// One extra wave rogue per this many buildings.
+const WAVE_BUILDINGS_PER_ROGUE: u32 = 3;
+
+/// Tokens for clearing a wave, per wave number.
+pub const WAVE_CLEAR_BONUS_PER_WAVE: i64 = 25;
+
+/// Directions a wave can come from, with the spawn angle for each
+/// (screen space, so north is -y).
+const COMPASS: [(&str, f32); 4] = [
+    ("north", -std::f32::consts::FRAC_PI_2),
+    ("east", 0.0),
+    ("south", std::f32::consts::FRAC_PI_2),
+    ("west", std::f32::consts::PI),
+];
+
+/// Buildings a Mimic may pose as: cheap ones nobody looks at twice.
+const MIMIC_DISGUISES: [BuildingTypeKind; 4] = [
+    BuildingTypeKind::Pylon,
+    BuildingTypeKind::TodoApp,
+    BuildingTypeKind::Calculator,
+    BuildingTypeKind::LandingPage,
+];
+
 /// Result returned by [`spawn_system`] each tick.
+#[derive(Default)]
 pub struct SpawnResult {
+    /// Rogues spawned this tick.
+    pub spawned: Vec<(hecs::Entity, RogueTypeKind)>,
     /// Log messages generated by the spawn system (e.g. cascade events).
     pub log_entries: Vec<String>,
+    pub audio_events: Vec<AudioEvent>,
 }
 
-/// Runs the spawn system for a single tick.
-///
-/// Determines whether to spawn a new rogue enemy based on the current game
-/// phase and building count, then places it at a random position around the
-/// player.  When the cascade is active, delegates to [`cascade_spawn`] instead
-/// of normal probabilistic spawning.
-pub fn spawn_system(
-    world: &mut World,
-    game_state: &mut GameState,
-    player_x: f32,
-    player_y: f32,
-) -> SpawnResult {
-    // ── If spawning is disabled via debug, skip all spawning ──────────
-    if !game_state.spawning_enabled {
-        return SpawnResult {
-            log_entries: Vec::new(),
-        };
-    }
-
-    // ── If cascade is active, use cascade spawning ────────────────────
-    if game_state.cascade_active {
-        return cascade_spawn(world, game_state, player_x, player_y);
-    }
-
-    let mut rng = rand::thread_rng();
-
-    // ── Count buildings for scaling spawn rate ─────────────────────────
-    let building_count = world.query::<&Building>().iter().count() as f32;
-
+/// Per-tick probability of a normal rogue spawn: a phase base rate plus a
+/// bonus per building, scaled by purchased upgrades.
+pub fn spawn_chance(phase: &GamePhase, building_count: f32, upgrades: &UpgradeState) -> f32 {
     // ── Base spawn rate by phase ──────────────────────────────────────
-    let base_rate = match game_state.phase {
+    let base_rate = match phase {
         GamePhase::Hut => 0.0002,
         GamePhase::Outpost => 0.0005,
         GamePhase::Village => 0.001,
@@ -60,24 +86,54 @@ pub fn spawn_system(
         GamePhase::City => 0.003,
     };
 
-    let spawn_chance = base_rate + building_count * 0.0002;
+    (base_rate + building_count * 0.0002) * upgrades.spawn_chance_multiplier()
+}
 
-    // ── Roll for spawn ────────────────────────────────────────────────
-    if rng.gen::<f32>() > spawn_chance {
-        return SpawnResult {
-            log_entries: Vec::new(),
-        };
+/// Ticks between rogue waves: ten minutes in the Hut phase, shrinking to
+/// four in the City.
+pub fn wave_interval_ticks(phase: &GamePhase) -> u64 {
+    match phase {
+        GamePhase::Hut => 12000,
+        GamePhase::Outpost => 9600,
+        GamePhase::Village => 7200,
+        GamePhase::Network => 6000,
+        GamePhase::City => 4800,
     }
+}
 
-    // ── Spawn position: random angle, 300-500 units from player ───────
-    let angle = rng.gen::<f32>() * std::f32::consts::TAU;
-    let distance = rng.gen_range(300.0..500.0_f32);
-    let spawn_x = player_x + angle.cos() * distance;
-    let spawn_y = player_y + angle.sin() * distance;
+/// When the wave after wave `wave_number` hits, counting from `tick`: the
+/// phase's [`wave_interval_ticks`] less [`WAVE_INTERVAL_DECAY_TICKS`] per
+/// wave so far, but never under [`MIN_WAVE_INTERVAL_TICKS`].
+pub fn next_wave_tick(tick: u64, phase: &GamePhase, wave_number: u32) -> u64 {
+    let interval = wave_interval_ticks(phase).saturating_sub(wave_number as u64 * WAVE_INTERVAL_DECAY_TICKS);
+    tick + interval.max(MIN_WAVE_INTERVAL_TICKS)
+}
 
-    // ── Choose rogue type based on game phase ─────────────────────────
-    let roll: f32 = rng.gen();
-    let rogue_kind = match game_state.phase {
+/// How many rogues wave `wave_number` (counting from 1) brings against
+/// `building_count` buildings.
+pub fn wave_size(wave_number: u32, building_count: u32) -> u32 {
+    WAVE_BASE_SIZE
+        + wave_number.saturating_sub(1) * WAVE_SIZE_PER_WAVE
+        + building_count / WAVE_BUILDINGS_PER_ROGUE
+}
+
+/// A `size`-strong wave, each rogue rolled from the phase's normal spawn
+/// table.
+pub fn wave_spec(phase: &GamePhase, size: u32, rng: &mut impl Rng) -> WaveSpec {
+    let mut spec = WaveSpec::default();
+    for _ in 0..size {
+        let kind = roll_rogue_kind(phase, rng.gen());
+        match spec.rogues.iter_mut().find(|(k, _count)| *k == kind) {
+            Some((_kind, count)) => *count += 1,
+            None => spec.rogues.push((kind, 1)),
+        }
+    }
+    spec
+}
+
+/// The rogue a normal spawn in `phase` produces for `roll` in [0, 1).
+fn roll_rogue_kind(phase: &GamePhase, roll: f32) -> RogueTypeKind {
+    match phase {
         GamePhase::Hut => {
             if roll < 0.70 {
                 RogueTypeKind::Swarm
@@ -86,7 +142,9 @@ pub fn spawn_system(
             }
         }
         GamePhase::Outpost => {
-            if roll < 0.40 {
+            if roll < 0.02 {
+                RogueTypeKind::Plague
+            } else if roll < 0.40 {
                 RogueTypeKind::Swarm
             } else if roll < 0.70 {
                 RogueTypeKind::Corruptor
@@ -97,7 +155,11 @@ pub fn spawn_system(
             }
         }
         GamePhase::Village | GamePhase::Network | GamePhase::City => {
-            if roll < 0.25 {
+            if roll < 0.03 {
+                RogueTypeKind::Multiplier
+            } else if roll < 0.05 {
+                RogueTypeKind::Plague
+            } else if roll < 0.25 {
                 RogueTypeKind::Swarm
             } else if roll < 0.45 {
                 RogueTypeKind::Corruptor
@@ -113,12 +175,210 @@ pub fn spawn_system(
                 RogueTypeKind::Architect
             }
         }
-    };
+    }
+}
+
+/// Runs the spawn system for a single tick.
+///
+/// Determines whether to spawn a new rogue enemy based on the current game
+/// phase and building count, then places it at a random position around the
+/// player.  Nothing spawns while spawning is disabled or [`MAX_ROGUES`] are
+/// already alive.  Rogue waves from [`wave_system`] come on top of this
+/// trickle.  When the cascade is active, delegates to [`cascade_spawn`]
+/// instead of normal probabilistic spawning and waves.
+pub fn spawn_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    player_x: f32,
+    player_y: f32,
+    rng: &mut impl Rng,
+) -> SpawnResult {
+    // ── If spawning is disabled via debug, skip all spawning ──────────
+    if !game_state.spawning_enabled {
+        return SpawnResult::default();
+    }
+
+    // ── If cascade is active, use cascade spawning ────────────────────
+    if game_state.cascade_active {
+        let result = cascade_spawn(world, game_state, player_x, player_y, rng);
+        assign_pack(world, game_state, &result.spawned);
+        return result;
+    }
+
+    let mut result = wave_system(world, game_state, player_x, player_y, rng);
+
+    // ── Respect the rogue cap ─────────────────────────────────────────
+    if world.query::<&Rogue>().iter().count() >= MAX_ROGUES {
+        return result;
+    }
+
+    // ── Count buildings for scaling spawn rate ─────────────────────────
+    let building_count = world.query::<&Building>().iter().count() as f32;
 
-    spawn_rogue(world, spawn_x, spawn_y, rogue_kind);
+    let spawn_chance = spawn_chance(&game_state.phase, building_count, &game_state.upgrades);
 
-    SpawnResult {
-        log_entries: Vec::new(),
+    // ── Roll for spawn ────────────────────────────────────────────────
+    if rng.gen::<f32>() > spawn_chance {
+        return result;
+    }
+
+    // ── Spawn position: random angle, 300-500 units from player ───────
+    let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+    let distance = rng.gen_range(300.0..500.0_f32);
+    let spawn_x = player_x + angle.cos() * distance;
+    let spawn_y = player_y + angle.sin() * distance;
+
+    // ── Choose rogue type based on game phase ─────────────────────────
+    let rogue_kind = roll_rogue_kind(&game_state.phase, rng.gen());
+
+    let entity = spawn_rogue(world, spawn_x, spawn_y, rogue_kind);
+    let spawned = vec![(entity, rogue_kind)];
+    assign_pack(world, game_state, &spawned);
+
+    result.spawned.extend(spawned);
+    result.log_entries.push(format!("[sys] a {:?} emerges from the dark.", rogue_kind));
+    result.audio_events.push(AudioEvent::RogueSpawn);
+    result
+}
+
+/// Schedules, announces and spawns rogue waves.
+///
+/// The first wave is scheduled with [`next_wave_tick`] on the first call.
+/// [`WAVE_WARNING_TICKS`] before it hits, its [`WaveSpec`] ([`wave_size`]
+/// rogues drawn from the phase's spawn table) is fixed and a warning names
+/// the compass direction it will come from. When it hits, the rogues appear
+/// over [`WAVE_SPAWN_TICKS`] ticks in a cluster on that side of the player,
+/// and the next wave is scheduled. Killing every rogue of a wave before the
+/// next one spawns pays [`WAVE_CLEAR_BONUS_PER_WAVE`] per wave number.
+pub fn wave_system(
+    world: &mut World,
+    game_state: &mut GameState,
+    player_x: f32,
+    player_y: f32,
+    rng: &mut impl Rng,
+) -> SpawnResult {
+    let mut result = SpawnResult::default();
+    let tick = game_state.tick;
+
+    // ── Cleared waves pay out ─────────────────────────────────────────
+    let wave_number = game_state.wave.wave_number;
+    if game_state.wave.active
+        && game_state.wave.spawn_ticks_left == 0
+        && !world.query::<&WaveRogue>().iter().any(|(_e, w)| w.wave == wave_number)
+    {
+        game_state.wave.active = false;
+        let bonus = WAVE_CLEAR_BONUS_PER_WAVE * wave_number as i64;
+        record_transaction(&mut game_state.economy, bonus, "wave cleared", tick);
+        result.log_entries.push(format!("[sys] wave {} cleared. +{} tokens.", wave_number, bonus));
+    }
+
+    if game_state.wave.next_wave_tick == 0 {
+        game_state.wave.next_wave_tick = next_wave_tick(tick, &game_state.phase, wave_number);
+        return result;
+    }
+
+    // ── A wave still arriving ─────────────────────────────────────────
+    if game_state.wave.spawn_ticks_left > 0 {
+        spawn_wave_batch(world, game_state, player_x, player_y, rng, &mut result);
+        return result;
+    }
+
+    // ── Warning ───────────────────────────────────────────────────────
+    if game_state.wave.incoming.is_none() && tick + WAVE_WARNING_TICKS >= game_state.wave.next_wave_tick {
+        let direction = rng.gen_range(0..COMPASS.len() as u8);
+        let building_count = world.query::<&Building>().iter().count() as u32;
+        let spec = game_state.wave.current_wave.take().unwrap_or_else(|| {
+            wave_spec(&game_state.phase, wave_size(wave_number + 1, building_count), rng)
+        });
+        result.log_entries.push(format!(
+            "[sys] wave {} approaches from the {}: {} rogues in 15 seconds.",
+            wave_number + 1,
+            COMPASS[direction as usize].0,
+            spec.total()
+        ));
+        result.audio_events.push(AudioEvent::WaveWarning);
+        game_state.wave.incoming = Some(direction);
+        game_state.wave.current_wave = Some(spec);
+    }
+
+    if tick < game_state.wave.next_wave_tick {
+        return result;
+    }
+
+    // ── The wave hits ─────────────────────────────────────────────────
+    if game_state.wave.current_wave.is_none() {
+        let building_count = world.query::<&Building>().iter().count() as u32;
+        game_state.wave.current_wave = Some(wave_spec(&game_state.phase, wave_size(wave_number + 1, building_count), rng));
+    }
+    let direction = *game_state.wave.incoming.get_or_insert(0);
+    let wave_number = wave_number + 1;
+    game_state.wave.wave_number = wave_number;
+    game_state.wave.active = true;
+    game_state.wave.spawn_ticks_left = WAVE_SPAWN_TICKS;
+    game_state.wave.next_wave_tick = next_wave_tick(tick, &game_state.phase, wave_number);
+    result.log_entries.push(format!(
+        "[sys] wave {} breaks from the {}: {} rogues.",
+        wave_number,
+        COMPASS[direction as usize].0,
+        game_state.wave.current_wave.as_ref().map_or(0, |w| w.total())
+    ));
+    result.audio_events.push(AudioEvent::RogueSpawn);
+    spawn_wave_batch(world, game_state, player_x, player_y, rng, &mut result);
+    result
+}
+
+/// Spawns this tick's share of the arriving wave: what is left of it
+/// spread evenly over the remaining spawn ticks.
+fn spawn_wave_batch(
+    world: &mut World,
+    game_state: &mut GameState,
+    player_x: f32,
+    player_y: f32,
+    rng: &mut impl Rng,
+    result: &mut SpawnResult,
+) {
+    let wave = &mut game_state.wave;
+    let angle = COMPASS[wave.incoming.unwrap_or(0) as usize].1;
+    let kinds = match &mut wave.current_wave {
+        Some(spec) => {
+            let n = (spec.total() as u64).div_ceil(wave.spawn_ticks_left.max(1)) as u32;
+            spec.take(n)
+        }
+        None => Vec::new(),
+    };
+    wave.spawn_ticks_left = wave.spawn_ticks_left.saturating_sub(1);
+    if wave.spawn_ticks_left == 0 {
+        wave.current_wave = None;
+        wave.incoming = None;
+    }
+    let wave_number = wave.wave_number;
+
+    let mut spawned = Vec::with_capacity(kinds.len());
+    for kind in kinds {
+        let a = angle + rng.gen_range(-0.4..0.4_f32);
+        let distance = rng.gen_range(350.0..450.0_f32);
+        let entity = spawn_rogue(world, player_x + a.cos() * distance, player_y + a.sin() * distance, kind);
+        let _ = world.insert_one(entity, WaveRogue { wave: wave_number });
+        spawned.push((entity, kind));
+    }
+    assign_pack(world, game_state, &spawned);
+    result.spawned.extend(spawned);
+}
+
+/// Puts every Swarm in `spawned` into one new pack.
+fn assign_pack(world: &mut World, game_state: &mut GameState, spawned: &[(hecs::Entity, RogueTypeKind)]) {
+    let swarms: Vec<hecs::Entity> = spawned
+        .iter()
+        .filter(|(_e, kind)| *kind == RogueTypeKind::Swarm)
+        .map(|(e, _kind)| *e)
+        .collect();
+    if swarms.is_empty() {
+        return;
+    }
+    let pack_id = game_state.next_pack_id;
+    game_state.next_pack_id = game_state.next_pack_id.wrapping_add(1);
+    for swarm in &swarms {
+        let _ = world.insert_one(*swarm, PackBonus { pack_id, members: swarms.len() as u32 });
     }
 }
 
@@ -131,22 +391,23 @@ fn cascade_spawn(
     game_state: &mut GameState,
     player_x: f32,
     player_y: f32,
+    rng: &mut impl Rng,
 ) -> SpawnResult {
-    let mut log_entries: Vec<String> = Vec::new();
+    let mut result = SpawnResult::default();
 
     let city_tick = match game_state.city_reached_tick {
         Some(t) => t,
         None => {
             // Shouldn't happen, but safety fallback
             game_state.cascade_active = false;
-            return SpawnResult { log_entries };
+            return result;
         }
     };
 
     // Cascade starts at city_reached_tick + CASCADE_DELAY
     let cascade_start = city_tick + CASCADE_DELAY;
     if game_state.tick < cascade_start {
-        return SpawnResult { log_entries };
+        return result;
     }
 
     let ticks_into_cascade = game_state.tick - cascade_start;
@@ -155,18 +416,18 @@ fn cascade_spawn(
     // ── Check if cascade is over ──────────────────────────────────────
     if wave_number >= CASCADE_TOTAL_WAVES {
         game_state.cascade_active = false;
-        log_entries.push("[sys] the cascade breaks. you endured.".to_string());
-        log_entries.push("[sys] build complete. what's next?".to_string());
-        return SpawnResult { log_entries };
+        result.log_entries.push("[sys] the cascade breaks. you endured.".to_string());
+        result.log_entries.push("[sys] build complete. what's next?".to_string());
+        return result;
     }
 
     // ── Only spawn at the exact start of each wave ────────────────────
     let ticks_into_wave = ticks_into_cascade % CASCADE_WAVE_INTERVAL;
     if ticks_into_wave != 0 {
-        return SpawnResult { log_entries };
+        return result;
     }
 
-    log_entries.push(format!(
+    result.log_entries.push(format!(
         "[sys] cascade wave {} incoming.",
         wave_number + 1
     ));
@@ -193,23 +454,42 @@ fn cascade_spawn(
         (RogueTypeKind::Architect, architect_count),
     ];
 
-    let mut rng = rand::thread_rng();
-
     for (kind, count) in &spawn_list {
         for _ in 0..*count {
             let angle = rng.gen::<f32>() * std::f32::consts::TAU;
             let distance = rng.gen_range(250.0..400.0_f32);
             let spawn_x = player_x + angle.cos() * distance;
             let spawn_y = player_y + angle.sin() * distance;
-            spawn_rogue(world, spawn_x, spawn_y, *kind);
+            let entity = spawn_rogue(world, spawn_x, spawn_y, *kind);
+            result.spawned.push((entity, *kind));
         }
     }
+    result.audio_events.push(AudioEvent::RogueSpawn);
 
-    SpawnResult { log_entries }
+    result
+}
+
+/// Rogues that should appear when a rogue of `kind` dies at (x, y).
+/// A Multiplier splits into two Swarms either side of where it fell.
+pub fn death_spawns(kind: RogueTypeKind, x: f32, y: f32) -> Vec<(f32, f32, RogueTypeKind)> {
+    match kind {
+        RogueTypeKind::Multiplier => vec![
+            (x - 20.0, y, RogueTypeKind::Swarm),
+            (x + 20.0, y, RogueTypeKind::Swarm),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// Spawns every queued `(x, y, kind)` rogue.
+pub fn spawn_pending(world: &mut World, pending: &[(f32, f32, RogueTypeKind)]) {
+    for &(x, y, kind) in pending {
+        spawn_rogue(world, x, y, kind);
+    }
 }
 
 /// Spawns a single rogue entity of the given type at the given position.
-pub fn spawn_rogue(world: &mut World, x: f32, y: f32, rogue_kind: RogueTypeKind) {
+pub fn spawn_rogue(world: &mut World, x: f32, y: f32, rogue_kind: RogueTypeKind) -> hecs::Entity {
     // ── HP and damage by type ─────────────────────────────────────────
     let (hp, _damage) = match rogue_kind {
         RogueTypeKind::Swarm => (20, 4),
@@ -219,13 +499,15 @@ pub fn spawn_rogue(world: &mut World, x: f32, y: f32, rogue_kind: RogueTypeKind)
         RogueTypeKind::Assassin => (46, 20),
         RogueTypeKind::Mimic => (39, 10),
         RogueTypeKind::Architect => (104, 13),
+        RogueTypeKind::Multiplier => (50, 3),
+        RogueTypeKind::Plague => (30, 1),
     };
 
-    // ── Visibility: TokenDrain starts invisible ───────────────────────
-    let visible = rogue_kind != RogueTypeKind::TokenDrain;
+    // ── Visibility: TokenDrains and stalking Mimics start invisible ───
+    let visible = !matches!(rogue_kind, RogueTypeKind::TokenDrain | RogueTypeKind::Mimic);
 
     // ── Spawn the rogue entity ────────────────────────────────────────
-    world.spawn((
+    let entity = world.spawn((
         Rogue,
         Position { x, y },
         Velocity::default(),
@@ -241,4 +523,222 @@ pub fn spawn_rogue(world: &mut World, x: f32, y: f32, rogue_kind: RogueTypeKind)
         },
         RogueVisibility { visible },
     ));
+
+    // ── Mimics stalk unseen, then pose as a building ──────────────────
+    if rogue_kind == RogueTypeKind::Mimic {
+        let disguise = MIMIC_DISGUISES[rand::random::<usize>() % MIMIC_DISGUISES.len()];
+        let _ = world.insert(entity, (MimicDisguise { disguise }, MimicPhase::default()));
+    }
+
+    if let Some(table) = loot_table_for(rogue_kind) {
+        let _ = world.insert_one(entity, table);
+    }
+
+    entity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+    use crate::game::upgrades::{UpgradeId, ALIGNMENT_SPAWN_CHANCE_MULT};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// A City-phase world with enough buildings that every roll spawns.
+    fn certain_spawn_world() -> (World, GameState) {
+        let (_w, mut game_state) = create_world();
+        game_state.phase = GamePhase::City;
+        let mut world = World::new();
+        for _ in 0..5000 {
+            world.spawn((Building,));
+        }
+        (world, game_state)
+    }
+
+    fn rogue_count(world: &World) -> usize {
+        world.query::<&Rogue>().iter().count()
+    }
+
+    #[test]
+    fn spawns_with_log_and_audio() {
+        let (mut world, mut game_state) = certain_spawn_world();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let result = spawn_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert_eq!(result.spawned.len(), 1);
+        assert_eq!(result.log_entries.len(), 1);
+        assert!(matches!(result.audio_events[..], [AudioEvent::RogueSpawn]));
+        assert_eq!(rogue_count(&world), 1);
+    }
+
+    #[test]
+    fn disabled_spawning_spawns_nothing() {
+        let (mut world, mut game_state) = certain_spawn_world();
+        game_state.spawning_enabled = false;
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..10 {
+            let result = spawn_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+            assert!(result.spawned.is_empty());
+        }
+        assert_eq!(rogue_count(&world), 0);
+    }
+
+    #[test]
+    fn spawning_stops_at_rogue_cap() {
+        let (mut world, mut game_state) = certain_spawn_world();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..MAX_ROGUES - 1 {
+            spawn_rogue(&mut world, 0.0, 0.0, RogueTypeKind::Swarm);
+        }
+
+        spawn_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert_eq!(rogue_count(&world), MAX_ROGUES);
+
+        let result = spawn_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert!(result.spawned.is_empty());
+        assert_eq!(rogue_count(&world), MAX_ROGUES);
+    }
+
+    #[test]
+    fn cascade_swarms_share_a_pack() {
+        let (mut world, mut game_state) = certain_spawn_world();
+        game_state.cascade_active = true;
+        game_state.city_reached_tick = Some(0);
+        game_state.tick = CASCADE_DELAY;
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let result = spawn_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        let packs: Vec<(u32, u32)> = result
+            .spawned
+            .iter()
+            .filter(|(_e, kind)| *kind == RogueTypeKind::Swarm)
+            .map(|(e, _kind)| {
+                let pack = world.get::<&PackBonus>(*e).unwrap();
+                (pack.pack_id, pack.members)
+            })
+            .collect();
+        assert_eq!(packs.len(), 5);
+        assert!(packs.iter().all(|&p| p == (0, 5)));
+        assert!(result
+            .spawned
+            .iter()
+            .filter(|(_e, kind)| *kind != RogueTypeKind::Swarm)
+            .all(|(e, _kind)| world.get::<&PackBonus>(*e).is_err()));
+        assert_eq!(game_state.next_pack_id, 1);
+    }
+
+    #[test]
+    fn alignment_protocols_lowers_spawn_chance() {
+        let mut upgrades = UpgradeState::new();
+        let before = spawn_chance(&GamePhase::Village, 3.0, &upgrades);
+        upgrades.purchased.insert(UpgradeId::AlignmentProtocols);
+        let after = spawn_chance(&GamePhase::Village, 3.0, &upgrades);
+
+        assert!((before - 0.0016).abs() < 1e-6);
+        assert!((after - before * ALIGNMENT_SPAWN_CHANCE_MULT).abs() < 1e-6);
+    }
+
+    #[test]
+    fn waves_come_faster_in_later_phases_and_grow() {
+        let phases = [GamePhase::Hut, GamePhase::Outpost, GamePhase::Village, GamePhase::Network, GamePhase::City];
+        let intervals: Vec<u64> = phases.iter().map(wave_interval_ticks).collect();
+        assert!(intervals.windows(2).all(|w| w[0] > w[1]), "{:?}", intervals);
+        assert_eq!(wave_interval_ticks(&GamePhase::Hut), 10 * 60 * 20);
+
+        assert_eq!(wave_size(1, 0), WAVE_BASE_SIZE);
+        assert_eq!(wave_size(3, 0), WAVE_BASE_SIZE + 2 * WAVE_SIZE_PER_WAVE);
+        assert_eq!(wave_size(1, 7), WAVE_BASE_SIZE + 2);
+    }
+
+    #[test]
+    fn wave_spec_uses_the_phase_table() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let hut = wave_spec(&GamePhase::Hut, 200, &mut rng);
+        assert_eq!(hut.total(), 200);
+        let kinds: Vec<RogueTypeKind> = hut.rogues.iter().map(|(kind, _count)| *kind).collect();
+        assert_eq!(kinds.len(), 2);
+        assert!(kinds.contains(&RogueTypeKind::Swarm) && kinds.contains(&RogueTypeKind::Corruptor));
+
+        let city = wave_spec(&GamePhase::City, 200, &mut rng);
+        assert!(city.rogues.iter().any(|(kind, _count)| *kind == RogueTypeKind::Assassin));
+    }
+
+    #[test]
+    fn waves_come_sooner_down_to_a_floor() {
+        let base = wave_interval_ticks(&GamePhase::City);
+        assert_eq!(next_wave_tick(100, &GamePhase::City, 0), 100 + base);
+        assert_eq!(next_wave_tick(100, &GamePhase::City, 3), 100 + base - 60);
+        assert_eq!(next_wave_tick(100, &GamePhase::City, 10_000), 100 + MIN_WAVE_INTERVAL_TICKS);
+    }
+
+    #[test]
+    fn first_wave_spawns_its_spec_over_five_ticks() {
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(5);
+        game_state.tick = 50;
+        game_state.wave.next_wave_tick = 50;
+        game_state.wave.current_wave = Some(WaveSpec {
+            rogues: vec![(RogueTypeKind::Swarm, 4), (RogueTypeKind::Corruptor, 3)],
+        });
+
+        let mut per_tick = Vec::new();
+        for _ in 0..WAVE_SPAWN_TICKS + 2 {
+            per_tick.push(wave_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng).spawned.len());
+            game_state.tick += 1;
+        }
+        assert_eq!(per_tick, vec![2, 2, 1, 1, 1, 0, 0]);
+        assert_eq!(world.query::<&WaveRogue>().iter().count(), 7);
+        assert_eq!(world.query::<&RogueType>().iter().filter(|(_e, t)| t.kind == RogueTypeKind::Corruptor).count(), 3);
+        assert_eq!(game_state.wave.wave_number, 1);
+        assert!(game_state.wave.current_wave.is_none());
+        assert_eq!(game_state.wave.next_wave_tick, next_wave_tick(50, &GamePhase::Hut, 1));
+        for (_e, pos) in world.query::<&Position>().iter() {
+            let d = (pos.x * pos.x + pos.y * pos.y).sqrt();
+            assert!((350.0..450.0).contains(&d), "{}", d);
+        }
+    }
+
+    #[test]
+    fn wave_is_announced_then_spawns_and_pays_when_cleared() {
+        let (_w, mut game_state) = create_world();
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(11);
+        game_state.tick = 1;
+        let quiet = wave_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert!(quiet.log_entries.is_empty());
+        let hits_at = game_state.wave.next_wave_tick;
+        assert_eq!(hits_at, next_wave_tick(1, &GamePhase::Hut, 0));
+
+        game_state.tick = hits_at - WAVE_WARNING_TICKS;
+        let warning = wave_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert!(warning.log_entries[0].contains("approaches"));
+        assert!(matches!(warning.audio_events[..], [AudioEvent::WaveWarning]));
+        assert!(warning.spawned.is_empty());
+
+        let mut spawned = Vec::new();
+        for t in 0..WAVE_SPAWN_TICKS {
+            game_state.tick = hits_at + t;
+            spawned.extend(wave_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng).spawned);
+        }
+        assert_eq!(spawned.len() as u32, wave_size(1, 0));
+        assert_eq!(game_state.wave.wave_number, 1);
+        assert!(game_state.wave.active);
+        assert_eq!(game_state.wave.next_wave_tick, next_wave_tick(hits_at, &GamePhase::Hut, 1));
+
+        game_state.tick += 1;
+        wave_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert!(game_state.wave.active);
+
+        for (entity, _kind) in &spawned {
+            world.despawn(*entity).unwrap();
+        }
+        let balance = game_state.economy.balance;
+        let cleared = wave_system(&mut world, &mut game_state, 0.0, 0.0, &mut rng);
+        assert!(!game_state.wave.active);
+        assert!(cleared.log_entries[0].contains("cleared"));
+        assert_eq!(game_state.economy.balance, balance + WAVE_CLEAR_BONUS_PER_WAVE);
+    }
 }
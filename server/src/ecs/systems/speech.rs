@@ -0,0 +1,186 @@
+use hecs::World;
+use rand::Rng;
+
+use crate::ecs::components::{Agent, AgentMorale, AgentPersonality, AgentState, Chatter, Position, Recruitable, Rogue};
+use crate::ecs::systems::morale::is_low_morale;
+use crate::game::speech::{pick_line, SpeechEvent};
+use crate::protocol::AgentStateKind;
+
+/// Fewest ticks between two lines from the same agent (5s at 20Hz).
+pub const SPEECH_COOLDOWN_TICKS: u64 = 100;
+
+/// Fewest ticks between unprompted remarks (rogues, low morale) from the
+/// same agent (30s at 20Hz).
+pub const AMBIENT_SPEECH_INTERVAL_TICKS: u64 = 600;
+
+/// An agent within this distance (pixels) of a rogue may call it out.
+pub const ROGUE_NEARBY_RADIUS: f32 = 150.0;
+
+/// A line of speech for the client to show as a bubble, with an
+/// `AudioEvent::AgentSpeak`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentLine {
+    pub agent_id: u64,
+    pub text: String,
+}
+
+/// Result returned by [`speech_system`].
+#[derive(Default)]
+pub struct SpeechResult {
+    pub lines: Vec<AgentLine>,
+}
+
+/// Ticks since `agent` last spoke, or `None` if it never has.
+fn ticks_since_spoke(world: &World, agent: hecs::Entity, tick: u64) -> Option<u64> {
+    world
+        .get::<&Chatter>(agent)
+        .ok()
+        .and_then(|c| c.last_spoke_tick)
+        .map(|at| tick.saturating_sub(at))
+}
+
+/// Applies `update` to `agent`'s [`Chatter`], adding one first if needed.
+fn update_chatter(world: &mut World, agent: hecs::Entity, update: impl FnOnce(&mut Chatter)) {
+    if let Ok(mut chatter) = world.get::<&mut Chatter>(agent) {
+        update(&mut chatter);
+        return;
+    }
+    let mut chatter = Chatter::default();
+    update(&mut chatter);
+    let _ = world.insert_one(agent, chatter);
+}
+
+/// Has `agent` say something about `event`, unless it spoke within
+/// [`SPEECH_COOLDOWN_TICKS`]. The line is drawn from its personality's
+/// phrase bank.
+pub fn speak(
+    world: &mut World,
+    agent: hecs::Entity,
+    event: SpeechEvent,
+    tick: u64,
+    rng: &mut impl Rng,
+) -> Option<AgentLine> {
+    if !world.satisfies::<&Agent>(agent).unwrap_or(false) {
+        return None;
+    }
+    if ticks_since_spoke(world, agent, tick).is_some_and(|t| t < SPEECH_COOLDOWN_TICKS) {
+        return None;
+    }
+    let traits = world
+        .get::<&AgentPersonality>(agent)
+        .map(|p| p.traits.clone())
+        .unwrap_or_default();
+    let text = pick_line(&traits, event, rng).to_string();
+
+    update_chatter(world, agent, |c| c.last_spoke_tick = Some(tick));
+    Some(AgentLine { agent_id: agent.to_bits().into(), text })
+}
+
+/// Gives hired agents a voice for things no action reports: starting to
+/// error, a rogue close by, or flagging morale. Erroring is remarked on as
+/// it happens; the others at most every [`AMBIENT_SPEECH_INTERVAL_TICKS`].
+pub fn speech_system(world: &mut World, tick: u64, rng: &mut impl Rng) -> SpeechResult {
+    let mut result = SpeechResult::default();
+
+    let rogues: Vec<(f32, f32)> = world
+        .query::<&Position>()
+        .with::<&Rogue>()
+        .iter()
+        .map(|(_e, pos)| (pos.x, pos.y))
+        .collect();
+
+    let agents: Vec<(hecs::Entity, AgentStateKind, Option<AgentStateKind>, f32, f32, f32)> = world
+        .query::<(&AgentState, &Position, Option<&AgentMorale>, Option<&Chatter>)>()
+        .with::<&Agent>()
+        .without::<&Recruitable>()
+        .iter()
+        .map(|(e, (state, pos, morale, chatter))| {
+            (
+                e,
+                state.state,
+                chatter.and_then(|c| c.last_state),
+                pos.x,
+                pos.y,
+                morale.map_or(1.0, |m| m.value),
+            )
+        })
+        .collect();
+
+    for (agent, state, last_state, x, y, morale) in agents {
+        let ambient_ok = ticks_since_spoke(world, agent, tick).is_none_or(|t| t >= AMBIENT_SPEECH_INTERVAL_TICKS);
+        let event = if state == AgentStateKind::Erroring && last_state.is_some_and(|s| s != state) {
+            Some(SpeechEvent::Errored)
+        } else if state == AgentStateKind::Unresponsive || !ambient_ok {
+            None
+        } else if rogues
+            .iter()
+            .any(|&(rx, ry)| (rx - x).powi(2) + (ry - y).powi(2) <= ROGUE_NEARBY_RADIUS * ROGUE_NEARBY_RADIUS)
+        {
+            Some(SpeechEvent::RogueNearby)
+        } else if is_low_morale(morale) {
+            Some(SpeechEvent::LowMorale)
+        } else {
+            None
+        };
+
+        if let Some(line) = event.and_then(|event| speak(world, agent, event, tick, rng)) {
+            result.lines.push(line);
+        }
+
+        update_chatter(world, agent, |c| c.last_state = Some(state));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::systems::spawn::spawn_rogue;
+    use crate::protocol::RogueTypeKind;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn spawn_agent(world: &mut World, state: AgentStateKind) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentState { state },
+            Position { x: 0.0, y: 0.0 },
+            AgentMorale { value: 0.8 },
+            AgentPersonality { traits: vec!["loyal".to_string()] },
+        ))
+    }
+
+    #[test]
+    fn speech_is_rate_limited_per_agent() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        let a = spawn_agent(&mut world, AgentStateKind::Idle);
+        let b = spawn_agent(&mut world, AgentStateKind::Idle);
+
+        assert!(speak(&mut world, a, SpeechEvent::BuildStarted, 10, &mut rng).is_some());
+        assert!(speak(&mut world, a, SpeechEvent::BuildStarted, 10 + SPEECH_COOLDOWN_TICKS - 1, &mut rng).is_none());
+        assert!(speak(&mut world, b, SpeechEvent::BuildStarted, 11, &mut rng).is_some());
+        assert!(speak(&mut world, a, SpeechEvent::BuildStarted, 10 + SPEECH_COOLDOWN_TICKS, &mut rng).is_some());
+    }
+
+    #[test]
+    fn agents_remark_on_errors_and_rogues() {
+        let mut world = World::new();
+        let mut rng = StdRng::seed_from_u64(7);
+        let agent = spawn_agent(&mut world, AgentStateKind::Building);
+        assert!(speech_system(&mut world, 1, &mut rng).lines.is_empty());
+
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Erroring;
+        let result = speech_system(&mut world, 2, &mut rng);
+        assert_eq!(result.lines.len(), 1);
+        // Still erroring: nothing new to say.
+        assert!(speech_system(&mut world, 2 + SPEECH_COOLDOWN_TICKS, &mut rng).lines.is_empty());
+
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Idle;
+        spawn_rogue(&mut world, 50.0, 0.0, RogueTypeKind::Swarm);
+        assert!(speech_system(&mut world, 100, &mut rng).lines.is_empty());
+        let result = speech_system(&mut world, 2 + AMBIENT_SPEECH_INTERVAL_TICKS, &mut rng);
+        assert_eq!(result.lines.len(), 1);
+    }
+}
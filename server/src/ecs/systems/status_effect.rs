@@ -0,0 +1,184 @@
+use hecs::World;
+
+use crate::ecs::components::{Health, Position, Rogue, RogueType, StatusEffects};
+use crate::ecs::systems::combat::bounty_for;
+use crate::ecs::systems::spawn::death_spawns;
+use crate::ecs::systems::token_drain::drain_refund;
+use crate::protocol::{CombatEvent, RogueTypeKind, StatusEffect};
+
+/// Burning applied by each Flare hit.
+pub const FLARE_BURN: StatusEffect = StatusEffect::Burning { damage_per_tick: 2, ticks_remaining: 60 };
+
+/// Slow applied by each Signal Jammer hit.
+pub const JAMMER_SLOW: StatusEffect = StatusEffect::Slowed { factor: 0.4, ticks_remaining: 40 };
+
+/// The result of running the status effect system for one tick.
+#[derive(Default)]
+pub struct StatusEffectResult {
+    /// Rogues killed by damage-over-time this tick (already despawned).
+    pub killed_rogues: Vec<(hecs::Entity, RogueTypeKind)>,
+    pub combat_events: Vec<CombatEvent>,
+    pub log_entries: Vec<String>,
+    pub bounty_tokens: i64,
+    /// Stolen tokens handed back by killed TokenDrains (included in
+    /// `bounty_tokens`); the caller records the transaction.
+    pub refund_tokens: i64,
+    /// Rogues to spawn once effects resolve, e.g. Swarms from a Multiplier.
+    pub pending_spawns: Vec<(f32, f32, RogueTypeKind)>,
+}
+
+/// Applies `effect` to `entity`, attaching a `StatusEffects` component if
+/// it doesn't have one yet.
+pub fn apply_status(world: &mut World, entity: hecs::Entity, effect: StatusEffect) {
+    if let Ok(mut effects) = world.get::<&mut StatusEffects>(entity) {
+        effects.apply(effect);
+        return;
+    }
+    let mut effects = StatusEffects::default();
+    effects.apply(effect);
+    let _ = world.insert_one(entity, effects);
+}
+
+/// Runs the status effect system for a single tick.
+///
+/// Burning deals its damage to the entity's `Health`, then every effect's
+/// timer ticks down and expired effects are dropped. Slowed is read by the
+/// movement code via `StatusEffects::speed_factor`. Rogues burned to death
+/// are despawned here and pay bounty like any other kill; the player's
+/// death is left to the main loop.
+pub fn status_effect_system(world: &mut World) -> StatusEffectResult {
+    let mut result = StatusEffectResult::default();
+    let mut burned_out: Vec<hecs::Entity> = Vec::new();
+
+    for (entity, (effects, health)) in world.query_mut::<(&mut StatusEffects, Option<&mut Health>)>() {
+        let burn: i32 = effects
+            .effects
+            .iter()
+            .map(|e| match e {
+                StatusEffect::Burning { damage_per_tick, .. } => *damage_per_tick,
+                _ => 0,
+            })
+            .sum();
+        if let Some(health) = health {
+            if burn > 0 && health.current > 0 {
+                health.current -= burn;
+                if health.current <= 0 {
+                    burned_out.push(entity);
+                }
+            }
+        }
+
+        for effect in effects.effects.iter_mut() {
+            match effect {
+                StatusEffect::Burning { ticks_remaining, .. }
+                | StatusEffect::Slowed { ticks_remaining, .. }
+                | StatusEffect::Corrupted { ticks_remaining } => {
+                    *ticks_remaining = ticks_remaining.saturating_sub(1);
+                }
+            }
+        }
+        effects.effects.retain(|e| match e {
+            StatusEffect::Burning { ticks_remaining, .. }
+            | StatusEffect::Slowed { ticks_remaining, .. }
+            | StatusEffect::Corrupted { ticks_remaining } => *ticks_remaining > 0,
+        });
+    }
+
+    for entity in burned_out {
+        let rogue = world
+            .query_one_mut::<hecs::With<(&Position, &RogueType), &Rogue>>(entity)
+            .map(|(pos, rt)| (pos.x, pos.y, rt.kind));
+        let Ok((x, y, kind)) = rogue else { continue };
+
+        let refund = drain_refund(world, entity);
+        result.refund_tokens += refund;
+        result.bounty_tokens += bounty_for(kind) + refund;
+        result.killed_rogues.push((entity, kind));
+        result.pending_spawns.extend(death_spawns(kind, x, y));
+        result.combat_events.push(CombatEvent {
+            x,
+            y,
+            damage: 0,
+            is_kill: true,
+            rogue_type: Some(kind),
+            is_crit: false,
+        });
+        result.log_entries.push(format!("[combat] {:?} burned out", kind));
+        let _ = world.despawn(entity);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_rogue(world: &mut World, hp: i32) -> hecs::Entity {
+        world.spawn((
+            Rogue,
+            RogueType { kind: RogueTypeKind::Looper },
+            Position { x: 0.0, y: 0.0 },
+            Health { current: hp, max: hp },
+        ))
+    }
+
+    #[test]
+    fn burning_deals_two_per_tick_for_sixty_ticks() {
+        let mut world = World::new();
+        let burning = spawn_rogue(&mut world, 500);
+        let untouched = spawn_rogue(&mut world, 500);
+        apply_status(&mut world, burning, FLARE_BURN);
+
+        for tick in 1..=60 {
+            status_effect_system(&mut world);
+            assert_eq!(world.get::<&Health>(burning).unwrap().current, 500 - 2 * tick);
+        }
+        assert!(world.get::<&StatusEffects>(burning).unwrap().effects.is_empty());
+
+        status_effect_system(&mut world);
+        assert_eq!(world.get::<&Health>(burning).unwrap().current, 380);
+        assert_eq!(world.get::<&Health>(untouched).unwrap().current, 500);
+    }
+
+    #[test]
+    fn reapplying_refreshes_instead_of_stacking() {
+        let mut world = World::new();
+        let rogue = spawn_rogue(&mut world, 500);
+        apply_status(&mut world, rogue, FLARE_BURN);
+        for _ in 0..30 {
+            status_effect_system(&mut world);
+        }
+        apply_status(&mut world, rogue, FLARE_BURN);
+
+        let effects = world.get::<&StatusEffects>(rogue).unwrap();
+        assert_eq!(effects.effects, vec![FLARE_BURN]);
+    }
+
+    #[test]
+    fn slowed_sets_speed_factor_until_expiry() {
+        let mut world = World::new();
+        let rogue = spawn_rogue(&mut world, 10);
+        apply_status(&mut world, rogue, JAMMER_SLOW);
+        assert_eq!(world.get::<&StatusEffects>(rogue).unwrap().speed_factor(), 0.4);
+
+        for _ in 0..40 {
+            status_effect_system(&mut world);
+        }
+        assert_eq!(world.get::<&StatusEffects>(rogue).unwrap().speed_factor(), 1.0);
+    }
+
+    #[test]
+    fn burning_kill_despawns_rogue_and_pays_bounty() {
+        let mut world = World::new();
+        let rogue = spawn_rogue(&mut world, 3);
+        apply_status(&mut world, rogue, FLARE_BURN);
+
+        status_effect_system(&mut world);
+        let result = status_effect_system(&mut world);
+
+        assert_eq!(result.killed_rogues, vec![(rogue, RogueTypeKind::Looper)]);
+        assert_eq!(result.bounty_tokens, bounty_for(RogueTypeKind::Looper));
+        assert!(!world.contains(rogue));
+    }
+}
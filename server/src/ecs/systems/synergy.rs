@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use hecs::World;
+
+use crate::ecs::components::{
+    ActiveSynergies, AgentVibeConfig, Building, BuildingType, ConstructionProgress, Position,
+};
+use crate::protocol::{BuildingTypeKind, SynergyDescriptor};
+
+/// What a synergy grants while its two buildings stand close together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SynergyEffect {
+    /// Flat tokens per tick added to the first building's income.
+    Income(f64),
+    /// Fraction taken off `error_chance_base` for agents on either building.
+    ErrorReduction(f32),
+    /// Factor applied to both buildings' grade multiplier.
+    GradeMultiplier(f64),
+}
+
+/// A pair of building kinds that reinforce each other within `range`.
+#[derive(Debug)]
+pub struct SynergyRule {
+    pub name: &'static str,
+    pub first: BuildingTypeKind,
+    pub second: BuildingTypeKind,
+    pub range: f32,
+    pub effect: SynergyEffect,
+}
+
+/// Every synergy in the game.
+pub const SYNERGIES: &[SynergyRule] = &[
+    SynergyRule {
+        name: "Powered Compute",
+        first: BuildingTypeKind::ComputeFarm,
+        second: BuildingTypeKind::Pylon,
+        range: 200.0,
+        effect: SynergyEffect::Income(0.1),
+    },
+    SynergyRule {
+        name: "Productivity Suite",
+        first: BuildingTypeKind::TodoApp,
+        second: BuildingTypeKind::ChatApp,
+        range: 200.0,
+        effect: SynergyEffect::ErrorReduction(0.1),
+    },
+    SynergyRule {
+        name: "Generated Storefront",
+        first: BuildingTypeKind::EcommerceStore,
+        second: BuildingTypeKind::AiImageGenerator,
+        range: 200.0,
+        effect: SynergyEffect::GradeMultiplier(1.3),
+    },
+];
+
+/// A synergy active this tick between `building` (of the rule's first kind)
+/// and its nearest `partner`.
+#[derive(Debug, Clone)]
+pub struct SynergyBonus {
+    pub rule: &'static SynergyRule,
+    pub building: hecs::Entity,
+    pub partner: hecs::Entity,
+    /// The synergy was not active for `building` last tick.
+    pub newly_active: bool,
+}
+
+impl SynergyBonus {
+    /// How the client sees this synergy.
+    pub fn descriptor(&self) -> SynergyDescriptor {
+        SynergyDescriptor {
+            name: self.rule.name.to_string(),
+            building_ids: vec![self.building.to_bits().into(), self.partner.to_bits().into()],
+        }
+    }
+}
+
+/// Extra income per tick `building` earns from synergies.
+pub fn income_bonus(synergies: &[SynergyBonus], building: hecs::Entity) -> f64 {
+    synergies
+        .iter()
+        .filter(|s| s.building == building)
+        .map(|s| match s.rule.effect {
+            SynergyEffect::Income(amount) => amount,
+            _ => 0.0,
+        })
+        .sum()
+}
+
+/// Factor applied to `building`'s grade multiplier by synergies.
+pub fn grade_multiplier(synergies: &[SynergyBonus], building: hecs::Entity) -> f64 {
+    synergies
+        .iter()
+        .filter(|s| s.building == building || s.partner == building)
+        .map(|s| match s.rule.effect {
+            SynergyEffect::GradeMultiplier(factor) => factor,
+            _ => 1.0,
+        })
+        .fold(1.0, f64::max)
+}
+
+/// Finds every active synergy: each completed building of a rule's first
+/// kind pairs with its nearest completed building of the second kind within
+/// range. Agents on either building of an `ErrorReduction` synergy have
+/// their `error_chance_base` lowered, so this must run after the morale
+/// system re-derives it. Each building remembers its synergies in
+/// [`ActiveSynergies`] so new ones can be announced.
+pub fn synergy_system(world: &mut World) -> Vec<SynergyBonus> {
+    let buildings: Vec<(hecs::Entity, BuildingTypeKind, f32, f32, Vec<hecs::Entity>)> = world
+        .query::<(&BuildingType, &Position, Option<&ConstructionProgress>)>()
+        .with::<&Building>()
+        .iter()
+        .filter(|(_e, (_bt, _pos, progress))| progress.is_none_or(|p| p.current >= p.total))
+        .map(|(e, (bt, pos, progress))| {
+            let crew = progress.map(|p| p.assigned_agents.clone()).unwrap_or_default();
+            (e, bt.kind, pos.x, pos.y, crew)
+        })
+        .collect();
+
+    let mut bonuses = Vec::new();
+    // An agent counts once, however many synergies its building joins.
+    let mut crews_helped: HashMap<hecs::Entity, f32> = HashMap::new();
+    for rule in SYNERGIES {
+        for (building, _kind, x, y, crew) in buildings.iter().filter(|b| b.1 == rule.first) {
+            let nearest = buildings
+                .iter()
+                .filter(|b| b.1 == rule.second)
+                .map(|b| (b, (b.2 - x).powi(2) + (b.3 - y).powi(2)))
+                .filter(|(_b, dist_sq)| *dist_sq <= rule.range * rule.range)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((partner, _)) = nearest else { continue };
+
+            if let SynergyEffect::ErrorReduction(reduction) = rule.effect {
+                for &agent in crew.iter().chain(&partner.4) {
+                    let best = crews_helped.entry(agent).or_insert(0.0);
+                    *best = best.max(reduction);
+                }
+            }
+            bonuses.push(SynergyBonus { rule, building: *building, partner: partner.0, newly_active: false });
+        }
+    }
+
+    for (agent, reduction) in crews_helped {
+        if let Ok(mut vibe) = world.get::<&mut AgentVibeConfig>(agent) {
+            vibe.error_chance_base *= 1.0 - reduction;
+        }
+    }
+
+    for (building, ..) in &buildings {
+        let names: Vec<&'static str> =
+            bonuses.iter().filter(|b| b.building == *building).map(|b| b.rule.name).collect();
+        let previous = world
+            .get::<&ActiveSynergies>(*building)
+            .map(|a| a.names.clone())
+            .unwrap_or_default();
+        for bonus in bonuses.iter_mut().filter(|b| b.building == *building) {
+            bonus.newly_active = !previous.contains(&bonus.rule.name);
+        }
+        if names.is_empty() && previous.is_empty() {
+            continue;
+        }
+        let _ = world.insert_one(*building, ActiveSynergies { names });
+    }
+
+    bonuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::agents::generate_vibe_config;
+    use crate::protocol::AgentTierKind;
+
+    fn spawn_building(world: &mut World, kind: BuildingTypeKind, x: f32, y: f32) -> hecs::Entity {
+        world.spawn((
+            Building,
+            BuildingType { kind },
+            Position { x, y },
+            ConstructionProgress { current: 10.0, total: 10.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ))
+    }
+
+    #[test]
+    fn nearby_pylon_powers_up_a_compute_farm() {
+        let mut world = World::new();
+        let farm = spawn_building(&mut world, BuildingTypeKind::ComputeFarm, 0.0, 0.0);
+        let pylon = spawn_building(&mut world, BuildingTypeKind::Pylon, 150.0, 0.0);
+
+        let bonuses = synergy_system(&mut world);
+        assert_eq!(bonuses.len(), 1);
+        assert_eq!((bonuses[0].building, bonuses[0].partner), (farm, pylon));
+        assert!(bonuses[0].newly_active);
+        assert_eq!(income_bonus(&bonuses, farm), 0.1);
+
+        // Still active, but no longer news.
+        assert!(!synergy_system(&mut world)[0].newly_active);
+
+        world.get::<&mut Position>(pylon).unwrap().x = 400.0;
+        let bonuses = synergy_system(&mut world);
+        assert!(bonuses.is_empty());
+        assert_eq!(income_bonus(&bonuses, farm), 0.0);
+
+        world.get::<&mut Position>(pylon).unwrap().x = 150.0;
+        assert!(synergy_system(&mut world)[0].newly_active);
+    }
+
+    #[test]
+    fn productivity_suite_steadies_both_crews() {
+        let mut world = World::new();
+        let todo = spawn_building(&mut world, BuildingTypeKind::TodoApp, 0.0, 0.0);
+        let chat = spawn_building(&mut world, BuildingTypeKind::ChatApp, 100.0, 0.0);
+        let vibe = generate_vibe_config(AgentTierKind::Apprentice);
+        let base = vibe.error_chance_base;
+        let on_todo = world.spawn((vibe.clone(),));
+        let on_chat = world.spawn((vibe.clone(),));
+        let elsewhere = world.spawn((vibe,));
+        world.get::<&mut ConstructionProgress>(todo).unwrap().assigned_agents.push(on_todo);
+        world.get::<&mut ConstructionProgress>(chat).unwrap().assigned_agents.push(on_chat);
+
+        synergy_system(&mut world);
+        for agent in [on_todo, on_chat] {
+            assert!((world.get::<&AgentVibeConfig>(agent).unwrap().error_chance_base - base * 0.9).abs() < 1e-6);
+        }
+        assert_eq!(world.get::<&AgentVibeConfig>(elsewhere).unwrap().error_chance_base, base);
+    }
+
+    #[test]
+    fn unfinished_buildings_have_no_synergy() {
+        let mut world = World::new();
+        let store = spawn_building(&mut world, BuildingTypeKind::EcommerceStore, 0.0, 0.0);
+        let generator = spawn_building(&mut world, BuildingTypeKind::AiImageGenerator, 50.0, 0.0);
+        world.get::<&mut ConstructionProgress>(generator).unwrap().current = 0.0;
+        assert!(synergy_system(&mut world).is_empty());
+
+        world.get::<&mut ConstructionProgress>(generator).unwrap().current = 10.0;
+        let bonuses = synergy_system(&mut world);
+        assert_eq!(grade_multiplier(&bonuses, store), 1.3);
+        assert_eq!(grade_multiplier(&bonuses, generator), 1.3);
+    }
+}
@@ -0,0 +1,249 @@
+use hecs::World;
+
+use crate::ecs::components::{
+    Building, BuildingType, GameState, Player, Position, Rogue, RogueAI, RogueBehaviorState,
+    RogueType, RogueVisibility, TokenDrainState,
+};
+use crate::ecs::systems::economy::record_transaction;
+use crate::game::building::get_building_definition;
+use crate::protocol::{CombatEvent, RogueTypeKind};
+
+/// Distance (pixels) at which a TokenDrain can leech from the player.
+const PLAYER_DRAIN_RANGE: f32 = 20.0;
+
+/// Distance (pixels) at which a TokenDrain can leech from a building.
+const BUILDING_DRAIN_RANGE: f32 = 30.0;
+
+/// Consecutive ticks in range before a TokenDrain attaches (1 second at 20Hz).
+pub const ATTACH_TICKS: u32 = 20;
+
+/// Tokens per tick drained on attaching (1 token/s at 20Hz).
+const BASE_DRAIN_PER_TICK: f64 = 0.05;
+
+/// Extra tokens per tick for every tick spent attached.
+const DRAIN_ESCALATION_PER_TICK: f64 = 0.0025;
+
+/// Ceiling on a single drain's rate (10 tokens/s).
+const MAX_DRAIN_PER_TICK: f64 = 0.5;
+
+/// Expenditure sink name shown in the economy panel.
+pub const TOKEN_DRAIN_SINK: &str = "token_drain";
+
+/// Result returned by [`token_drain_system`].
+#[derive(Default)]
+pub struct TokenDrainResult {
+    pub log_entries: Vec<String>,
+    /// One event per drain that took tokens this tick, for the leech effect.
+    pub combat_events: Vec<CombatEvent>,
+    /// Whole tokens taken this tick across all drains.
+    pub drained: i64,
+}
+
+/// Drain rate for a TokenDrain that has been attached for `attached_ticks`.
+pub fn drain_rate(attached_ticks: u32) -> f64 {
+    (BASE_DRAIN_PER_TICK + DRAIN_ESCALATION_PER_TICK * attached_ticks as f64).min(MAX_DRAIN_PER_TICK)
+}
+
+/// Half of what the TokenDrain `entity` has stolen, handed back as extra
+/// bounty when it is killed. Zero for anything else.
+pub fn drain_refund(world: &World, entity: hecs::Entity) -> i64 {
+    world.get::<&TokenDrainState>(entity).map_or(0, |d| d.stolen / 2)
+}
+
+/// Runs the TokenDrain leech for a single tick.
+///
+/// A TokenDrain that stays within reach of the player or a building for
+/// [`ATTACH_TICKS`] attaches: it becomes visible, switches to
+/// `RogueBehaviorState::Attached`, and drains tokens at a rate that grows
+/// the longer it stays. Drifting out of range detaches it and hides it
+/// again. Must run after the economy system, which resets the expenditure
+/// sinks this adds to.
+pub fn token_drain_system(world: &mut World, game_state: &mut GameState) -> TokenDrainResult {
+    let mut result = TokenDrainResult::default();
+
+    let player: Option<(f32, f32)> = if game_state.god_mode || game_state.player_dead {
+        None
+    } else {
+        world
+            .query::<&Position>()
+            .with::<&Player>()
+            .iter()
+            .next()
+            .map(|(_e, pos)| (pos.x, pos.y))
+    };
+    let buildings: Vec<(f32, f32, &'static str)> = world
+        .query::<(&Position, &BuildingType)>()
+        .with::<&Building>()
+        .iter()
+        .map(|(_e, (pos, bt))| (pos.x, pos.y, get_building_definition(&bt.kind).name))
+        .collect();
+    let drains: Vec<(hecs::Entity, f32, f32)> = world
+        .query::<(&Position, &RogueType)>()
+        .with::<&Rogue>()
+        .iter()
+        .filter(|(_e, (_pos, rt))| rt.kind == RogueTypeKind::TokenDrain)
+        .map(|(e, (pos, _rt))| (e, pos.x, pos.y))
+        .collect();
+
+    let within = |x: f32, y: f32, tx: f32, ty: f32, range: f32| {
+        let (dx, dy) = (tx - x, ty - y);
+        dx * dx + dy * dy <= range * range
+    };
+
+    let mut total_rate = 0.0;
+
+    for (entity, x, y) in drains {
+        let victim = player
+            .filter(|&(px, py)| within(x, y, px, py, PLAYER_DRAIN_RANGE))
+            .map(|_| "you")
+            .or_else(|| {
+                buildings
+                    .iter()
+                    .find(|&&(bx, by, _)| within(x, y, bx, by, BUILDING_DRAIN_RANGE))
+                    .map(|&(_, _, name)| name)
+            });
+
+        if world.get::<&TokenDrainState>(entity).is_err() {
+            if victim.is_none() {
+                continue;
+            }
+            let _ = world.insert_one(entity, TokenDrainState::default());
+        }
+
+        let (attached, attached_ticks) = {
+            let mut drain = world.get::<&mut TokenDrainState>(entity).unwrap();
+            let was_attached = drain.ticks_in_range >= ATTACH_TICKS;
+            drain.ticks_in_range = if victim.is_some() { drain.ticks_in_range + 1 } else { 0 };
+            let attached = drain.ticks_in_range >= ATTACH_TICKS;
+
+            if attached && !was_attached {
+                result.log_entries.push(format!(
+                    "[combat] a TokenDrain latched onto {}.",
+                    victim.unwrap_or("something")
+                ));
+            }
+            (attached, drain.ticks_in_range.saturating_sub(ATTACH_TICKS))
+        };
+
+        if let Ok(mut visibility) = world.get::<&mut RogueVisibility>(entity) {
+            visibility.visible = attached;
+        }
+        if !attached {
+            continue;
+        }
+        if let Ok(mut ai) = world.get::<&mut RogueAI>(entity) {
+            ai.behavior_state = RogueBehaviorState::Attached;
+        }
+
+        let rate = drain_rate(attached_ticks);
+        total_rate += rate;
+
+        let taken = {
+            let mut drain = world.get::<&mut TokenDrainState>(entity).unwrap();
+            drain.fractional += rate;
+            let whole = drain.fractional.floor() as i64;
+            drain.fractional -= whole as f64;
+            let taken = whole.min(game_state.economy.balance.max(0));
+            drain.stolen += taken;
+            taken
+        };
+        if taken > 0 {
+            record_transaction(&mut game_state.economy, -taken, TOKEN_DRAIN_SINK, game_state.tick);
+            result.drained += taken;
+            result.combat_events.push(CombatEvent {
+                x,
+                y,
+                damage: taken as i32,
+                is_kill: false,
+                rogue_type: Some(RogueTypeKind::TokenDrain),
+                is_crit: false,
+            });
+        }
+    }
+
+    if total_rate > 0.0 {
+        game_state.economy.expenditure_per_tick += total_rate;
+        game_state.economy.expenditure_sinks.push((TOKEN_DRAIN_SINK.to_string(), total_rate));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::Health;
+    use crate::ecs::systems::combat::{bounty_for, combat_system};
+    use crate::ecs::systems::spawn::spawn_rogue;
+    use crate::ecs::world::create_world;
+    use crate::game::spatial::SpatialGrid;
+
+    fn player_pos(world: &World) -> (f32, f32) {
+        world
+            .query::<&Position>()
+            .with::<&Player>()
+            .iter()
+            .next()
+            .map(|(_e, p)| (p.x, p.y))
+            .unwrap()
+    }
+
+    #[test]
+    fn drain_attaches_and_escalates() {
+        let (mut world, mut game_state) = create_world();
+        game_state.economy.balance = 1000;
+        let (px, py) = player_pos(&world);
+        let drain = spawn_rogue(&mut world, px + 5.0, py, RogueTypeKind::TokenDrain);
+        assert!(!world.get::<&RogueVisibility>(drain).unwrap().visible);
+
+        for _ in 0..ATTACH_TICKS - 1 {
+            token_drain_system(&mut world, &mut game_state);
+        }
+        assert!(!world.get::<&RogueVisibility>(drain).unwrap().visible);
+        assert_eq!(game_state.economy.balance, 1000);
+
+        let result = token_drain_system(&mut world, &mut game_state);
+        assert!(world.get::<&RogueVisibility>(drain).unwrap().visible);
+        assert!(matches!(world.get::<&RogueAI>(drain).unwrap().behavior_state, RogueBehaviorState::Attached));
+        assert!(result.log_entries[0].contains("latched onto you"));
+        assert!(game_state.economy.expenditure_sinks.iter().any(|(name, _)| name == TOKEN_DRAIN_SINK));
+
+        // Stolen tokens match the integrated, escalating rate.
+        let ticks = 200;
+        let mut drained = 0;
+        for _ in 0..ticks {
+            drained += token_drain_system(&mut world, &mut game_state).drained;
+        }
+        let expected: f64 = (0..=ticks).map(drain_rate).sum();
+        let stolen = world.get::<&TokenDrainState>(drain).unwrap().stolen;
+        assert!((stolen - expected.floor() as i64).abs() <= 1);
+        assert_eq!(game_state.economy.balance, 1000 - stolen);
+        assert!(drained > 0 && drained <= stolen);
+        assert!(drain_rate(ticks) > drain_rate(0));
+
+        // Moving away detaches and hides it again.
+        world.get::<&mut Position>(drain).unwrap().x += 500.0;
+        token_drain_system(&mut world, &mut game_state);
+        assert!(!world.get::<&RogueVisibility>(drain).unwrap().visible);
+        assert_eq!(world.get::<&TokenDrainState>(drain).unwrap().ticks_in_range, 0);
+    }
+
+    #[test]
+    fn killing_a_drain_refunds_half_of_what_it_stole() {
+        let (mut world, mut game_state) = create_world();
+        let (px, py) = player_pos(&world);
+        // The player faces down by default, so put the drain in the swing.
+        let drain = spawn_rogue(&mut world, px, py + 10.0, RogueTypeKind::TokenDrain);
+        world.insert_one(drain, TokenDrainState { ticks_in_range: 0, stolen: 41, fractional: 0.0 }).unwrap();
+        world.get::<&mut Health>(drain).unwrap().current = 1;
+
+        let balance = game_state.economy.balance;
+        let mut grid = SpatialGrid::default();
+        grid.insert_all::<Rogue>(&world);
+        let result = combat_system(&mut world, &mut game_state, true, &mut grid);
+
+        assert_eq!(result.killed_rogues, vec![(drain, RogueTypeKind::TokenDrain)]);
+        assert_eq!(result.bounty_tokens, bounty_for(RogueTypeKind::TokenDrain) + 20);
+        assert_eq!(game_state.economy.balance, balance + bounty_for(RogueTypeKind::TokenDrain) + 20);
+    }
+}
@@ -0,0 +1,233 @@
+use hecs::World;
+
+use crate::ecs::components::{
+    Agent, AgentState, AgentStats, AgentVibeConfig, AgentXP, ConstructionProgress, Health,
+};
+use crate::game::upgrades::UpgradeState;
+use crate::protocol::AgentStateKind;
+
+/// XP for a completed vibe session, per grading star (ungraded counts as one).
+pub const SESSION_XP_PER_STAR: u64 = 25;
+/// XP for each agent that helped construct a building.
+pub const CONSTRUCTION_XP: u64 = 40;
+
+/// Highest level an agent can reach.
+pub const MAX_AGENT_LEVEL: u32 = 5;
+
+/// Speed gained per level, and the ceiling it grows towards.
+pub const LEVEL_SPEED_GAIN: f32 = 0.05;
+pub const MAX_AGENT_SPEED: f32 = 2.0;
+/// Reliability gained per level, and the ceiling it grows towards.
+pub const LEVEL_RELIABILITY_GAIN: f32 = 0.05;
+pub const MAX_AGENT_RELIABILITY: f32 = 0.99;
+/// Max health gained per level, and the HP healed on level-up.
+pub const LEVEL_MAX_HEALTH_GAIN: i32 = 1;
+pub const LEVEL_UP_HEAL: i32 = 10;
+/// Extra vibe turns per session gained per level.
+pub const LEVEL_MAX_TURNS_GAIN: u32 = 1;
+
+/// Total XP an agent at `level` needs to reach the next level, or `None`
+/// at [`MAX_AGENT_LEVEL`].
+pub fn xp_for_level(level: u32) -> Option<u64> {
+    match level {
+        0 | 1 => Some(100),
+        2 => Some(250),
+        3 => Some(500),
+        4 => Some(1000),
+        _ => None,
+    }
+}
+
+/// XP for a finished vibe session on a building graded `stars` (if at all).
+pub fn session_xp(stars: Option<u8>) -> u64 {
+    SESSION_XP_PER_STAR * stars.unwrap_or(1).max(1) as u64
+}
+
+/// Grant `amount` XP to an agent, scaled by purchased upgrades.
+///
+/// Unresponsive and dormant agents learn nothing.  Returns the XP actually
+/// granted.
+pub fn award_xp(world: &mut World, agent: hecs::Entity, amount: u64, upgrades: &UpgradeState) -> u64 {
+    let learning = world.get::<&AgentState>(agent).is_ok_and(|s| {
+        !matches!(s.state, AgentStateKind::Unresponsive | AgentStateKind::Dormant)
+    });
+    if !learning {
+        return 0;
+    }
+
+    let granted = (amount as f64 * upgrades.xp_multiplier()).round() as u64;
+    match world.get::<&mut AgentXP>(agent) {
+        Ok(mut xp) => {
+            xp.xp += granted;
+            granted
+        }
+        Err(_) => 0,
+    }
+}
+
+/// Grant construction XP to every agent assigned to a just-completed building.
+pub fn award_construction_xp(world: &mut World, building: hecs::Entity, upgrades: &UpgradeState) {
+    let helpers: Vec<hecs::Entity> = world
+        .get::<&ConstructionProgress>(building)
+        .map(|p| p.assigned_agents.clone())
+        .unwrap_or_default();
+    for agent in helpers {
+        award_xp(world, agent, CONSTRUCTION_XP, upgrades);
+    }
+}
+
+/// Runs the level-up system for a single tick.
+///
+/// Levels up every agent whose XP has reached the next threshold. Each level
+/// nudges speed and reliability up towards their ceilings, raises max health
+/// (healing the agent a little) and grants an extra vibe turn. Returns each
+/// agent that levelled up with its new level.
+pub fn level_up_system(world: &mut World) -> Vec<(hecs::Entity, u32)> {
+    let mut level_ups = Vec::new();
+
+    for (id, (xp, stats, health, vibe)) in world.query_mut::<hecs::With<
+        (&mut AgentXP, &mut AgentStats, Option<&mut Health>, Option<&mut AgentVibeConfig>),
+        &Agent,
+    >>() {
+        let mut health = health;
+        let mut vibe = vibe;
+        while xp_for_level(xp.level).is_some_and(|needed| xp.xp >= needed) {
+            xp.level += 1;
+            stats.speed = (stats.speed + LEVEL_SPEED_GAIN).min(MAX_AGENT_SPEED);
+            stats.reliability = (stats.reliability + LEVEL_RELIABILITY_GAIN).min(MAX_AGENT_RELIABILITY);
+            if let Some(health) = health.as_deref_mut() {
+                health.max += LEVEL_MAX_HEALTH_GAIN;
+                health.current = (health.current + LEVEL_UP_HEAL).min(health.max);
+            }
+            if let Some(vibe) = vibe.as_deref_mut() {
+                vibe.max_turns += LEVEL_MAX_TURNS_GAIN;
+            }
+            level_ups.push((id, xp.level));
+        }
+    }
+
+    level_ups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::AgentName;
+    use crate::game::upgrades::{UpgradeId, PERSISTENT_MEMORY_XP_MULT};
+
+    fn spawn_agent(world: &mut World, state: AgentStateKind) -> hecs::Entity {
+        world.spawn((
+            Agent,
+            AgentName { name: "ada".to_string() },
+            AgentState { state },
+            AgentStats { reliability: 0.9, speed: 1.0, awareness: 50.0, resilience: 50.0 },
+            AgentXP { xp: 0, level: 1 },
+        ))
+    }
+
+    #[test]
+    fn level_thresholds_stop_at_max_level() {
+        assert_eq!(xp_for_level(1), Some(100));
+        assert_eq!(xp_for_level(2), Some(250));
+        assert_eq!(xp_for_level(3), Some(500));
+        assert_eq!(xp_for_level(4), Some(1000));
+        assert_eq!(xp_for_level(MAX_AGENT_LEVEL), None);
+    }
+
+    #[test]
+    fn apprentice_levels_from_one_to_five() {
+        use crate::ecs::world::create_world;
+        use crate::game::agents::recruit_agent;
+        use crate::protocol::{AgentTierKind, AiBackend};
+
+        let (mut world, mut game_state) = create_world();
+        game_state.economy.balance = 1000;
+        let agent = recruit_agent(
+            &mut world,
+            AgentTierKind::Apprentice,
+            0.0,
+            0.0,
+            &mut game_state.economy,
+            0,
+            AiBackend::MistralVibe,
+        )
+        .unwrap();
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Building;
+        world.get::<&mut Health>(agent).unwrap().current = 1;
+        let upgrades = UpgradeState::new();
+
+        let stats_before = (*world.get::<&AgentStats>(agent).unwrap()).clone();
+        let max_health_before = world.get::<&Health>(agent).unwrap().max;
+        let turns_before = world.get::<&AgentVibeConfig>(agent).unwrap().max_turns;
+
+        for (threshold, level) in [(100, 2), (250, 3), (500, 4), (1000, 5)] {
+            let have = world.get::<&AgentXP>(agent).unwrap().xp;
+            award_xp(&mut world, agent, threshold - 1 - have, &upgrades);
+            assert!(level_up_system(&mut world).is_empty(), "levelled up below {threshold} XP");
+
+            award_xp(&mut world, agent, 1, &upgrades);
+            assert_eq!(level_up_system(&mut world), vec![(agent, level)]);
+        }
+
+        // Level 5 is the cap.
+        award_xp(&mut world, agent, 1_000_000, &upgrades);
+        assert!(level_up_system(&mut world).is_empty());
+        assert_eq!(world.get::<&AgentXP>(agent).unwrap().level, MAX_AGENT_LEVEL);
+
+        let stats = world.get::<&AgentStats>(agent).unwrap();
+        assert!((stats.speed - (stats_before.speed + 4.0 * LEVEL_SPEED_GAIN)).abs() < 1e-5);
+        let expected_reliability = (stats_before.reliability + 4.0 * LEVEL_RELIABILITY_GAIN).min(MAX_AGENT_RELIABILITY);
+        assert!((stats.reliability - expected_reliability).abs() < 1e-5);
+        let health = world.get::<&Health>(agent).unwrap();
+        assert_eq!(health.max, max_health_before + 4);
+        assert_eq!(health.current, 1 + 4 * LEVEL_UP_HEAL);
+        assert_eq!(world.get::<&AgentVibeConfig>(agent).unwrap().max_turns, turns_before + 4);
+    }
+
+    #[test]
+    fn stat_growth_respects_ceilings() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building);
+        {
+            let mut stats = world.get::<&mut AgentStats>(agent).unwrap();
+            stats.speed = MAX_AGENT_SPEED - 0.01;
+            stats.reliability = MAX_AGENT_RELIABILITY - 0.01;
+        }
+
+        award_xp(&mut world, agent, 1000, &UpgradeState::new());
+        assert_eq!(level_up_system(&mut world).len(), 4);
+        let stats = world.get::<&AgentStats>(agent).unwrap();
+        assert_eq!(stats.speed, MAX_AGENT_SPEED);
+        assert_eq!(stats.reliability, MAX_AGENT_RELIABILITY);
+    }
+
+    #[test]
+    fn unresponsive_agents_gain_no_xp() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Unresponsive);
+
+        assert_eq!(award_xp(&mut world, agent, 500, &UpgradeState::new()), 0);
+        assert_eq!(world.get::<&AgentXP>(agent).unwrap().xp, 0);
+        assert!(level_up_system(&mut world).is_empty());
+    }
+
+    #[test]
+    fn persistent_memory_boosts_xp_and_construction_pays_helpers() {
+        let mut world = World::new();
+        let agent = spawn_agent(&mut world, AgentStateKind::Building);
+        let building = world.spawn((ConstructionProgress {
+            current: 100.0,
+            total: 100.0,
+            assigned_agents: vec![agent],
+            priority_weight: 1.0,
+        },));
+        let mut upgrades = UpgradeState::new();
+        upgrades.purchased.insert(UpgradeId::PersistentMemory);
+
+        award_construction_xp(&mut world, building, &upgrades);
+        let expected = (CONSTRUCTION_XP as f64 * PERSISTENT_MEMORY_XP_MULT).round() as u64;
+        assert_eq!(world.get::<&AgentXP>(agent).unwrap().xp, expected);
+        assert_eq!(session_xp(None), SESSION_XP_PER_STAR);
+        assert_eq!(session_xp(Some(4)), SESSION_XP_PER_STAR * 4);
+    }
+}
@@ -1,4 +1,4 @@
-use super::components::{ArmorType, CombatPower, WeaponType, Armor};
+use super::components::{ArmorType, CombatPower, Durability, WeaponType, Armor};
 
 /// Returns the full CombatPower for a given weapon type.
 pub fn weapon_stats(weapon: WeaponType) -> CombatPower {
@@ -12,6 +12,8 @@ pub fn weapon_stats(weapon: WeaponType) -> CombatPower {
             range: 45.0,
             arc_degrees: 90.0,
             is_projectile: false,
+            crit_chance: 0.10,
+            crit_multiplier: 1.5,
         },
         WeaponType::HardReset => CombatPower {
             base_damage: 24,
@@ -22,6 +24,8 @@ pub fn weapon_stats(weapon: WeaponType) -> CombatPower {
             range: 52.5,
             arc_degrees: 180.0,
             is_projectile: false,
+            crit_chance: 0.05,
+            crit_multiplier: 2.0,
         },
         WeaponType::SignalJammer => CombatPower {
             base_damage: 14,
@@ -32,6 +36,8 @@ pub fn weapon_stats(weapon: WeaponType) -> CombatPower {
             range: 60.0,
             arc_degrees: 120.0,
             is_projectile: false,
+            crit_chance: 0.08,
+            crit_multiplier: 1.8,
         },
         WeaponType::NullPointer => CombatPower {
             base_damage: 16,
@@ -42,6 +48,8 @@ pub fn weapon_stats(weapon: WeaponType) -> CombatPower {
             range: 180.0,
             arc_degrees: 0.0,
             is_projectile: true,
+            crit_chance: 0.15,
+            crit_multiplier: 2.5,
         },
         WeaponType::Flare => CombatPower {
             base_damage: 10,
@@ -52,10 +60,29 @@ pub fn weapon_stats(weapon: WeaponType) -> CombatPower {
             range: 37.5,
             arc_degrees: 360.0,
             is_projectile: false,
+            crit_chance: 0.03,
+            crit_multiplier: 1.3,
         },
     }
 }
 
+/// Number of landed attacks a weapon survives before breaking. The basic
+/// ProcessTerminator is what broken weapons fall back to, so it never wears.
+pub fn max_durability(weapon: WeaponType) -> Option<u32> {
+    match weapon {
+        WeaponType::ProcessTerminator => None,
+        WeaponType::HardReset => Some(60),
+        WeaponType::SignalJammer => Some(90),
+        WeaponType::NullPointer => Some(80),
+        WeaponType::Flare => Some(50),
+    }
+}
+
+/// A fresh `Durability` for `weapon`, or `None` if it cannot break.
+pub fn fresh_durability(weapon: WeaponType) -> Option<Durability> {
+    max_durability(weapon).map(|max| Durability { current: max, max })
+}
+
 /// Returns the full Armor stats for a given armor type.
 pub fn armor_stats(armor: ArmorType) -> Armor {
     match armor {
@@ -1,7 +1,9 @@
 use hecs::World;
 
-use crate::protocol::{AgentStateKind, AgentTierKind, BuildingTypeKind, TaskAssignment};
+use crate::protocol::{AgentStateKind, AgentTierKind, BuildingTypeKind, InventoryItem, TaskAssignment};
 
+use crate::game::agents::{CURIOUS_WANDER_RADIUS_MULT, DEFAULT_WANDER_RADIUS};
+use crate::game::equipment::STARTER_EQUIPMENT;
 use crate::game::upgrades::UpgradeState;
 
 use super::components::{
@@ -9,7 +11,7 @@ use super::components::{
     AgentVibeConfig, Assignment, Building, BuildingEffects, BuildingType, CarryCapacity,
     ConstructionProgress, CrankState, CrankTier, GamePhase, GameState, Health, LightSource,
     Player, Position, Recruitable, TokenEconomy, TorchRange, Velocity, VoiceProfile, WanderState,
-    WeaponType, ArmorType, Facing,
+    WeaponType, ArmorType, Facing, WaveSchedule,
 };
 use super::weapon_stats;
 
@@ -90,8 +92,10 @@ pub fn create_world() -> (World, GameState) {
             waypoint_x: 400.0,
             waypoint_y: 390.0,
             pause_remaining: 0,
-            wander_radius: 120.0,
+            // Sol is curious.
+            wander_radius: DEFAULT_WANDER_RADIUS * CURIOUS_WANDER_RADIUS_MULT,
             walk_target: None,
+            walk_ticks: 0,
         },
     )).unwrap();
 
@@ -104,6 +108,7 @@ pub fn create_world() -> (World, GameState) {
             current: 1.0,
             total: 1.0,
             assigned_agents: Vec::new(),
+            priority_weight: 1.0,
         },
         Health { current: 100, max: 100 },
         BuildingEffects { effects: vec![] },
@@ -119,6 +124,7 @@ pub fn create_world() -> (World, GameState) {
             current: 1.0,
             total: 1.0,
             assigned_agents: Vec::new(),
+            priority_weight: 1.0,
         },
         Health { current: 100, max: 100 },
         BuildingEffects { effects: vec![] },
@@ -136,16 +142,23 @@ pub fn create_world() -> (World, GameState) {
             cool_rate: 0.5,
             tier: CrankTier::HandCrank,
             is_cranking: false,
-            assigned_agent: None,
+            assigned_agents: Vec::new(),
             tokens_per_rotation: 0.02,
+            overheated_remaining: 0,
+            overheating: false,
         },
         economy: TokenEconomy {
             balance: 0,
             fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
             income_per_tick: 0.0,
             expenditure_per_tick: 0.0,
             income_sources: vec![],
             expenditure_sinks: vec![],
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
         },
         cascade_active: false,
         city_reached_tick: None,
@@ -154,9 +167,19 @@ pub fn create_world() -> (World, GameState) {
         god_mode: false,
         player_dead: false,
         death_tick: None,
-        inventory: Vec::new(),
+        inventory: STARTER_EQUIPMENT
+            .iter()
+            .map(|item_type| InventoryItem { item_type: item_type.to_string(), count: 1 })
+            .collect(),
         opened_chests: std::collections::HashSet::new(),
         spawned_camps: std::collections::HashSet::new(),
+        populated_chunks: std::collections::HashSet::new(),
+        destroyed_nests: std::collections::HashSet::new(),
+        mums_card_found: false,
+        power_cores_collected: 0,
+        next_pack_id: 0,
+        investments: Vec::new(),
+        wave: WaveSchedule::default(),
     };
 
     (world, game_state)
@@ -2,10 +2,14 @@ use hecs::World;
 use rand::Rng;
 
 use crate::ecs::components::{
-    Agent, AgentMorale, AgentName, AgentState, AgentStats, AgentTier, AgentVibeConfig, AgentXP,
-    Assignment, Collider, Health, Position, TokenEconomy, Velocity, VoiceProfile, WanderState,
+    Agent, AgentMorale, AgentName, AgentPersonality, AgentState, AgentStats, AgentTier,
+    AgentVibeConfig, AgentXP, Assignment, BoundAgent, Collider, GuardianRogue, Health, Position,
+    Recruitable, ReviveTimer, Rogue, Scout, Specialization, TokenEconomy, Unguarded, Velocity,
+    VoiceProfile, WanderState,
 };
-use crate::protocol::{AgentStateKind, AgentTierKind, TaskAssignment};
+use crate::ecs::systems::economy::record_transaction;
+use crate::game::upgrades::{UpgradeId, UpgradeState};
+use crate::protocol::{AgentSpecialization, AgentStateKind, AgentTierKind, AudioEvent, TaskAssignment};
 
 /// Bank of 24 procedural agent names.
 const NAME_BANK: [&str; 24] = [
@@ -14,6 +18,16 @@ const NAME_BANK: [&str; 24] = [
     "ember",
 ];
 
+/// Bank of 12 personality traits; recruits draw one or two. Only the first
+/// four carry gameplay bonuses (see [`trait_bonuses`]).
+const TRAIT_BANK: [&str; 12] = [
+    "curious", "diligent", "cautious", "resilient", "chatty", "stubborn", "meticulous",
+    "optimistic", "sarcastic", "nocturnal", "impatient", "loyal",
+];
+
+/// Radius an agent wanders around its home by default.
+pub const DEFAULT_WANDER_RADIUS: f32 = 120.0;
+
 /// Returns the recruitment cost in tokens for a given agent tier.
 fn recruitment_cost(tier: AgentTierKind) -> i64 {
     match tier {
@@ -24,17 +38,30 @@ fn recruitment_cost(tier: AgentTierKind) -> i64 {
     }
 }
 
-/// Returns the revival cost in tokens for a given agent tier.
+/// Distance (pixels) within which the player can revive a downed agent.
+pub const REVIVE_RANGE: f32 = 50.0;
+
+/// Returns the revival cost in tokens for a given agent tier: half its
+/// recruitment cost.
 pub fn revival_cost(tier: AgentTierKind) -> i64 {
-    match tier {
-        AgentTierKind::Apprentice => 15,
-        AgentTierKind::Journeyman => 45,
-        AgentTierKind::Artisan => 120,
-        AgentTierKind::Architect => 300,
-    }
+    recruitment_cost(tier) / 2
 }
 
-/// Revive a dead (Unresponsive) agent, restoring them to Idle state with full health.
+/// The nearest Unresponsive agent within [`REVIVE_RANGE`] of `(x, y)`.
+pub fn nearest_downed_agent(world: &World, x: f32, y: f32) -> Option<hecs::Entity> {
+    world
+        .query::<(&AgentState, &Position)>()
+        .with::<&Agent>()
+        .iter()
+        .filter(|(_e, (state, _pos))| state.state == AgentStateKind::Unresponsive)
+        .map(|(e, (_state, pos))| (e, (pos.x - x).powi(2) + (pos.y - y).powi(2)))
+        .filter(|&(_e, dist_sq)| dist_sq <= REVIVE_RANGE * REVIVE_RANGE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e)
+}
+
+/// Revive a dead (Unresponsive) agent, restoring them to Idle state at a
+/// quarter of their health.
 ///
 /// Checks that the agent is in the `Unresponsive` state and that the economy has
 /// sufficient balance for the tier's revival cost.
@@ -46,6 +73,7 @@ pub fn revive_agent(
     world: &mut World,
     agent_entity: hecs::Entity,
     economy: &mut TokenEconomy,
+    tick: u64,
 ) -> Result<(), String> {
     let current_state = world
         .get::<&AgentState>(agent_entity)
@@ -70,23 +98,25 @@ pub fn revive_agent(
         ));
     }
 
-    economy.balance -= cost;
+    record_transaction(economy, -cost, "revive agent", tick);
 
     // Restore state to Idle
     if let Ok(mut state) = world.get::<&mut AgentState>(agent_entity) {
         state.state = AgentStateKind::Idle;
     }
 
-    // Restore health to max
+    // Back on its feet, but only just
     if let Ok(mut health) = world.get::<&mut Health>(agent_entity) {
-        health.current = health.max;
+        health.current = (health.max / 4).max(1);
     }
 
-    // Restore morale to 0.5 (revived agents are a bit shaken)
+    // Revived agents are shaken
     if let Ok(mut morale) = world.get::<&mut AgentMorale>(agent_entity) {
-        morale.value = 0.5;
+        morale.value = 0.4;
     }
 
+    let _ = world.remove_one::<ReviveTimer>(agent_entity);
+
     Ok(())
 }
 
@@ -111,6 +141,17 @@ fn generate_stats(tier: AgentTierKind) -> AgentStats {
     }
 }
 
+/// Base per-turn error chance for a given agent tier, before morale. Every
+/// backend's config starts from this.
+pub fn base_error_chance(tier: AgentTierKind) -> f32 {
+    match tier {
+        AgentTierKind::Apprentice => 0.15,
+        AgentTierKind::Journeyman => 0.08,
+        AgentTierKind::Artisan => 0.04,
+        AgentTierKind::Architect => 0.02,
+    }
+}
+
 /// Generate the Vibe configuration for a given agent tier.
 pub fn generate_vibe_config(tier: AgentTierKind) -> AgentVibeConfig {
     match tier {
@@ -122,7 +163,7 @@ pub fn generate_vibe_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 128_000,
             token_burn_rate: 3,
-            error_chance_base: 0.15,
+            error_chance_base: base_error_chance(tier),
             stars: 1,
         },
         AgentTierKind::Journeyman => AgentVibeConfig {
@@ -133,7 +174,7 @@ pub fn generate_vibe_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 128_000,
             token_burn_rate: 2,
-            error_chance_base: 0.08,
+            error_chance_base: base_error_chance(tier),
             stars: 2,
         },
         AgentTierKind::Artisan => AgentVibeConfig {
@@ -144,7 +185,7 @@ pub fn generate_vibe_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 256_000,
             token_burn_rate: 1,
-            error_chance_base: 0.04,
+            error_chance_base: base_error_chance(tier),
             stars: 3,
         },
         AgentTierKind::Architect => AgentVibeConfig {
@@ -155,7 +196,7 @@ pub fn generate_vibe_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 256_000,
             token_burn_rate: 1,
-            error_chance_base: 0.02,
+            error_chance_base: base_error_chance(tier),
             stars: 3,
         },
     }
@@ -172,7 +213,7 @@ pub fn generate_claude_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 200_000,
             token_burn_rate: 3,
-            error_chance_base: 0.15,
+            error_chance_base: base_error_chance(tier),
             stars: 1,
         },
         AgentTierKind::Journeyman => AgentVibeConfig {
@@ -183,7 +224,7 @@ pub fn generate_claude_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 200_000,
             token_burn_rate: 2,
-            error_chance_base: 0.08,
+            error_chance_base: base_error_chance(tier),
             stars: 2,
         },
         AgentTierKind::Artisan => AgentVibeConfig {
@@ -194,7 +235,7 @@ pub fn generate_claude_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 200_000,
             token_burn_rate: 1,
-            error_chance_base: 0.04,
+            error_chance_base: base_error_chance(tier),
             stars: 3,
         },
         AgentTierKind::Architect => AgentVibeConfig {
@@ -205,7 +246,7 @@ pub fn generate_claude_config(tier: AgentTierKind) -> AgentVibeConfig {
             turns_used: 0,
             context_window: 200_000,
             token_burn_rate: 1,
-            error_chance_base: 0.02,
+            error_chance_base: base_error_chance(tier),
             stars: 3,
         },
     }
@@ -229,8 +270,8 @@ fn pick_name() -> String {
 /// Recruit a new agent into the world.
 ///
 /// Checks that the economy has sufficient balance for the tier's cost, deducts the cost,
-/// generates random stats and a procedural name, then spawns the agent entity with all
-/// required components.
+/// generates random stats, a procedural name and one or two personality traits, then
+/// spawns the agent entity with all required components.
 ///
 /// # Errors
 ///
@@ -241,6 +282,7 @@ pub fn recruit_agent(
     spawn_x: f32,
     spawn_y: f32,
     economy: &mut TokenEconomy,
+    tick: u64,
     backend: crate::protocol::AiBackend,
 ) -> Result<hecs::Entity, String> {
     let cost = recruitment_cost(tier);
@@ -252,13 +294,113 @@ pub fn recruit_agent(
         ));
     }
 
-    economy.balance -= cost;
+    record_transaction(economy, -cost, &format!("recruit {:?}", tier), tick);
+
+    let entity = spawn_agent_body(world, tier, spawn_x, spawn_y, pick_name());
+    world
+        .insert_one(entity, generate_config_for_backend(backend, tier))
+        .map_err(|e| format!("Failed to insert vibe config: {}", e))?;
+
+    let personality = AgentPersonality { traits: pick_traits(&mut rand::thread_rng()) };
+    if let Ok(mut wander) = world.get::<&mut WanderState>(entity) {
+        wander.wander_radius = wander_radius_for(Some(&personality));
+    }
+    world
+        .insert_one(entity, personality)
+        .map_err(|e| format!("Failed to insert personality: {}", e))?;
+
+    Ok(entity)
+}
+
+/// Where freed bound agents walk back to.
+const HOME_BASE: (f32, f32) = (400.0, 300.0);
+
+/// Result of hiring a [`Recruitable`] agent.
+pub struct RecruitResult {
+    pub log_entries: Vec<String>,
+    /// An `AgentSpeak` as the agent joins.
+    pub audio_events: Vec<AudioEvent>,
+}
+
+/// How many guardians still alive hold `agent` at its camp.
+pub fn living_guardians(world: &World, agent: hecs::Entity) -> usize {
+    world
+        .query::<(&GuardianRogue, Option<&Health>)>()
+        .iter()
+        .filter(|(_e, (g, health))| g.bound_agent_entity == agent && health.is_none_or(|h| h.current > 0))
+        .count()
+}
+
+/// Pays a [`Recruitable`] agent's cost and brings it into the team.
+///
+/// An agent held at a camp ([`BoundAgent`]) can only be hired once its
+/// guardians are dead; it then walks back to base and makes its home there.
+/// Any other recruitable goes Idle where it stands.
+pub fn hire_recruitable(
+    world: &mut World,
+    economy: &mut TokenEconomy,
+    target: hecs::Entity,
+    tick: u64,
+) -> Result<RecruitResult, String> {
+    let cost = world
+        .get::<&Recruitable>(target)
+        .map(|r| r.cost)
+        .map_err(|_| "not recruitable".to_string())?;
+    if economy.balance < cost {
+        return Err(format!("need {} tokens, have {}", cost, economy.balance));
+    }
+    if living_guardians(world, target) > 0 {
+        return Err("guardians still protect this one".to_string());
+    }
+    record_transaction(economy, -cost, "recruit agent", tick);
+    let _ = world.remove_one::<Recruitable>(target);
+
+    let name = world.get::<&AgentName>(target).map(|n| n.name.clone()).unwrap_or_default();
+    let log_entry = if world.remove_one::<BoundAgent>(target).is_ok() {
+        let _ = world.remove_one::<Unguarded>(target);
+        if let Ok(mut wander) = world.get::<&mut WanderState>(target) {
+            (wander.home_x, wander.home_y) = HOME_BASE;
+            (wander.waypoint_x, wander.waypoint_y) = HOME_BASE;
+            wander.walk_target = Some(HOME_BASE);
+            wander.walk_ticks = 0;
+        }
+        if let Ok(mut state) = world.get::<&mut AgentState>(target) {
+            state.state = AgentStateKind::Walking;
+        }
+        let guardians: Vec<hecs::Entity> = world
+            .query::<&GuardianRogue>()
+            .iter()
+            .filter(|(_e, g)| g.bound_agent_entity == target)
+            .map(|(e, _g)| e)
+            .collect();
+        for guardian in guardians {
+            let _ = world.remove_one::<GuardianRogue>(guardian);
+        }
+        format!("{} freed! returning to base.", name)
+    } else {
+        if let Ok(mut state) = world.get::<&mut AgentState>(target) {
+            state.state = AgentStateKind::Idle;
+        }
+        format!("{} recruited!", name)
+    };
+
+    Ok(RecruitResult { log_entries: vec![log_entry], audio_events: vec![AudioEvent::AgentSpeak] })
+}
 
+/// Spawn a rescued survivor as a permanent Apprentice scout named `name`.
+/// Scouts are free and get no vibe config.
+pub fn spawn_scout(world: &mut World, name: &str, spawn_x: f32, spawn_y: f32) -> hecs::Entity {
+    let entity = spawn_agent_body(world, AgentTierKind::Apprentice, spawn_x, spawn_y, name.to_string());
+    world.insert_one(entity, Scout).expect("scout was just spawned");
+    entity
+}
+
+/// Spawn an Idle agent of `tier` with random stats, minus its vibe config.
+fn spawn_agent_body(world: &mut World, tier: AgentTierKind, spawn_x: f32, spawn_y: f32, name: String) -> hecs::Entity {
     let stats = generate_stats(tier);
     let resilience = stats.resilience as i32;
-    let name = pick_name();
 
-    let entity = world.spawn((
+    world.spawn((
         Agent,
         Position {
             x: spawn_x,
@@ -271,8 +413,9 @@ pub fn recruit_agent(
             waypoint_x: spawn_x + (rand::random::<f32>() - 0.5) * 240.0,
             waypoint_y: spawn_y + (rand::random::<f32>() - 0.5) * 240.0,
             pause_remaining: (rand::random::<f32>() * 40.0) as u32 + 20,
-            wander_radius: 120.0,
+            wander_radius: DEFAULT_WANDER_RADIUS,
             walk_target: None,
+            walk_ticks: 0,
         },
         Collider { radius: 5.0 },
         Health {
@@ -290,10 +433,7 @@ pub fn recruit_agent(
         VoiceProfile {
             voice_id: "placeholder".to_string(),
         },
-        generate_config_for_backend(backend, tier),
-    ));
-
-    Ok(entity)
+    ))
 }
 
 /// Assign a task to an existing agent entity.
@@ -302,10 +442,13 @@ pub fn recruit_agent(
 /// appropriate `AgentStateKind`, updates the agent's state, and inserts (or replaces)
 /// the `Assignment` component on the entity.
 ///
+/// A cautious agent refuses to `Explore` while any rogue is within
+/// [`CAUTIOUS_ROGUE_RADIUS`] of it.
+///
 /// # Errors
 ///
 /// Returns an error if the entity does not exist, lacks an `AgentState` component,
-/// or is currently `Unresponsive`.
+/// is currently `Unresponsive` or `Resting`, or is too cautious to explore.
 pub fn assign_task(
     world: &mut World,
     agent_entity: hecs::Entity,
@@ -320,6 +463,19 @@ pub fn assign_task(
     if current_state == AgentStateKind::Unresponsive {
         return Err("Agent is unresponsive and cannot accept tasks".to_string());
     }
+    if current_state == AgentStateKind::Resting {
+        return Err("Agent is resting and cannot accept tasks".to_string());
+    }
+    if task == TaskAssignment::Explore && personality_of(world, agent_entity).avoids_rogues {
+        if let Ok(pos) = world.get::<&Position>(agent_entity) {
+            let rogue_near = world.query::<&Position>().with::<&Rogue>().iter().any(|(_e, r)| {
+                (r.x - pos.x).powi(2) + (r.y - pos.y).powi(2) <= CAUTIOUS_ROGUE_RADIUS * CAUTIOUS_ROGUE_RADIUS
+            });
+            if rogue_near {
+                return Err("Agent is too cautious to explore with rogues nearby".to_string());
+            }
+        }
+    }
 
     // Map task to the corresponding agent state
     let new_state = match task {
@@ -343,6 +499,127 @@ pub fn assign_task(
     Ok(())
 }
 
+// ── Personality ─────────────────────────────────────────────────────
+
+/// Wander radius multiplier for a curious agent.
+pub const CURIOUS_WANDER_RADIUS_MULT: f32 = 1.5;
+/// Build speed multiplier for a diligent agent.
+pub const DILIGENT_BUILD_SPEED_MULT: f32 = 1.1;
+/// A cautious agent won't go exploring with a rogue this close.
+pub const CAUTIOUS_ROGUE_RADIUS: f32 = 200.0;
+/// A resilient idle agent heals 1 HP this often.
+pub const RESILIENT_REGEN_INTERVAL_TICKS: u64 = 100;
+
+/// What an agent's personality traits do in play.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraitBonuses {
+    pub wander_radius_mult: f32,
+    pub build_speed_mult: f32,
+    /// Refuses to explore near rogues ("cautious").
+    pub avoids_rogues: bool,
+    /// Heals while idle ("resilient").
+    pub idle_regen: bool,
+}
+
+impl Default for TraitBonuses {
+    fn default() -> Self {
+        Self { wander_radius_mult: 1.0, build_speed_mult: 1.0, avoids_rogues: false, idle_regen: false }
+    }
+}
+
+/// Maps well-known trait strings to their bonuses. Unknown traits are
+/// flavour only.
+pub fn trait_bonuses(traits: &[String]) -> TraitBonuses {
+    let mut bonuses = TraitBonuses::default();
+    for t in traits {
+        match t.as_str() {
+            "curious" => bonuses.wander_radius_mult *= CURIOUS_WANDER_RADIUS_MULT,
+            "diligent" => bonuses.build_speed_mult *= DILIGENT_BUILD_SPEED_MULT,
+            "cautious" => bonuses.avoids_rogues = true,
+            "resilient" => bonuses.idle_regen = true,
+            _ => {}
+        }
+    }
+    bonuses
+}
+
+/// The trait bonuses of `agent` (the defaults if it has no personality).
+pub fn personality_of(world: &World, agent: hecs::Entity) -> TraitBonuses {
+    world
+        .get::<&AgentPersonality>(agent)
+        .map(|p| trait_bonuses(&p.traits))
+        .unwrap_or_default()
+}
+
+/// The wander radius an agent with `personality` settles back to.
+pub fn wander_radius_for(personality: Option<&AgentPersonality>) -> f32 {
+    let mult = personality.map_or(1.0, |p| trait_bonuses(&p.traits).wander_radius_mult);
+    DEFAULT_WANDER_RADIUS * mult
+}
+
+/// Draws one or two distinct traits from the trait bank.
+pub fn pick_traits(rng: &mut impl Rng) -> Vec<String> {
+    let count = rng.gen_range(1..=2);
+    rand::seq::index::sample(rng, TRAIT_BANK.len(), count)
+        .into_iter()
+        .map(|i| TRAIT_BANK[i].to_string())
+        .collect()
+}
+
+// ── Specialization ──────────────────────────────────────────────────
+
+/// Build speed multiplier for a Builder.
+pub const BUILDER_BUILD_SPEED_MULT: f32 = 1.2;
+/// Discovery pickup radius multiplier for an Explorer.
+pub const EXPLORER_PICKUP_RANGE_MULT: f32 = 1.5;
+/// Max health multiplier applied once when an agent becomes a Defender.
+pub const DEFENDER_HEALTH_MULT: f32 = 1.3;
+/// Damage a Defender shrugs off from every rogue hit (hits still deal 1).
+pub const DEFENDER_DAMAGE_REDUCTION: i32 = 1;
+/// Stars an Analyst adds to the grade of the buildings it is assigned to.
+pub const ANALYST_BONUS_STARS: u8 = 1;
+
+/// The role `agent` has been specialized into, if any.
+pub fn specialization_of(world: &World, agent: hecs::Entity) -> Option<AgentSpecialization> {
+    world.get::<&Specialization>(agent).ok().map(|s| s.role)
+}
+
+/// Permanently specializes `agent` into `role`. Needs the Specialization
+/// upgrade, and each agent can only be specialized once. A Defender's
+/// health (current and max) grows by [`DEFENDER_HEALTH_MULT`] right away;
+/// the other bonuses are applied by the systems they affect.
+///
+/// Returns a log line on success.
+pub fn specialize_agent(
+    world: &mut World,
+    agent: hecs::Entity,
+    role: AgentSpecialization,
+    upgrades: &UpgradeState,
+) -> Result<String, String> {
+    if !upgrades.has(UpgradeId::Specialization) {
+        return Err("Specialization upgrade not purchased".to_string());
+    }
+    let name = world
+        .get::<&AgentName>(agent)
+        .map(|n| n.name.clone())
+        .map_err(|_| "Entity is not an agent".to_string())?;
+    if let Some(existing) = specialization_of(world, agent) {
+        return Err(format!("{} is already a {:?}", name, existing));
+    }
+
+    if role == AgentSpecialization::Defender {
+        if let Ok(mut health) = world.get::<&mut Health>(agent) {
+            health.max = (health.max as f32 * DEFENDER_HEALTH_MULT) as i32;
+            health.current = (health.current as f32 * DEFENDER_HEALTH_MULT) as i32;
+        }
+    }
+    world
+        .insert_one(agent, Specialization { role })
+        .map_err(|e| format!("Failed to insert Specialization component: {}", e))?;
+
+    Ok(format!("[{}] specialized as {:?}", name, role))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,27 +628,102 @@ mod tests {
         TokenEconomy {
             balance,
             fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
             income_per_tick: 0.0,
             expenditure_per_tick: 0.0,
             income_sources: Vec::new(),
             expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
         }
     }
 
+    #[test]
+    fn revival_costs_half_of_recruitment() {
+        assert_eq!(revival_cost(AgentTierKind::Apprentice), 10);
+        assert_eq!(revival_cost(AgentTierKind::Journeyman), 30);
+        assert_eq!(revival_cost(AgentTierKind::Artisan), 75);
+        assert_eq!(revival_cost(AgentTierKind::Architect), 200);
+    }
+
+    #[test]
+    fn revive_restores_a_quarter_of_health() {
+        let mut world = World::new();
+        let mut economy = make_economy(500);
+        let agent = recruit_agent(&mut world, AgentTierKind::Artisan, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
+        assert!(revive_agent(&mut world, agent, &mut economy, 0).is_err());
+        assert!(nearest_downed_agent(&world, 0.0, 0.0).is_none());
+
+        world.get::<&mut AgentState>(agent).unwrap().state = AgentStateKind::Unresponsive;
+        world.get::<&mut Health>(agent).unwrap().current = 0;
+        world.insert_one(agent, ReviveTimer { ticks_remaining: 10 }).unwrap();
+        assert_eq!(nearest_downed_agent(&world, REVIVE_RANGE, 0.0), Some(agent));
+        assert!(nearest_downed_agent(&world, REVIVE_RANGE + 1.0, 0.0).is_none());
+
+        let balance = economy.balance;
+        revive_agent(&mut world, agent, &mut economy, 0).unwrap();
+        assert_eq!(economy.balance, balance - 75);
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Idle);
+        let health = world.get::<&Health>(agent).unwrap();
+        assert_eq!(health.current, health.max / 4);
+        assert_eq!(world.get::<&AgentMorale>(agent).unwrap().value, 0.4);
+        assert!(world.get::<&ReviveTimer>(agent).is_err());
+    }
+
     #[test]
     fn recruit_apprentice_deducts_cost() {
         let mut world = World::new();
         let mut economy = make_economy(100);
-        let result = recruit_agent(&mut world, AgentTierKind::Apprentice, 10.0, 20.0, &mut economy, crate::protocol::AiBackend::MistralVibe);
+        let result = recruit_agent(&mut world, AgentTierKind::Apprentice, 10.0, 20.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe);
         assert!(result.is_ok());
         assert_eq!(economy.balance, 80); // 100 - 20
     }
 
+    #[test]
+    fn curious_recruits_wander_further() {
+        let mut world = World::new();
+        let mut economy = make_economy(i64::MAX);
+        let mut curious_seen = false;
+        for _ in 0..500 {
+            let entity = recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
+            let traits = world.get::<&AgentPersonality>(entity).unwrap().traits.clone();
+            assert!((1..=2).contains(&traits.len()));
+            let radius = world.get::<&WanderState>(entity).unwrap().wander_radius;
+            if traits.iter().any(|t| t == "curious") {
+                curious_seen = true;
+                assert_eq!(radius, DEFAULT_WANDER_RADIUS * 1.5);
+            } else {
+                assert_eq!(radius, DEFAULT_WANDER_RADIUS);
+            }
+        }
+        assert!(curious_seen);
+    }
+
+    #[test]
+    fn cautious_agents_wont_explore_near_rogues() {
+        let mut world = World::new();
+        let agent = world.spawn((
+            Agent,
+            AgentState { state: AgentStateKind::Idle },
+            Position { x: 0.0, y: 0.0 },
+            AgentPersonality { traits: vec!["cautious".to_string()] },
+        ));
+        let rogue = world.spawn((Rogue, Position { x: 150.0, y: 0.0 }));
+
+        assert!(assign_task(&mut world, agent, TaskAssignment::Explore).is_err());
+        assert!(assign_task(&mut world, agent, TaskAssignment::Guard).is_ok());
+
+        world.get::<&mut Position>(rogue).unwrap().x = CAUTIOUS_ROGUE_RADIUS + 10.0;
+        assert!(assign_task(&mut world, agent, TaskAssignment::Explore).is_ok());
+    }
+
     #[test]
     fn recruit_fails_with_insufficient_balance() {
         let mut world = World::new();
         let mut economy = make_economy(10);
-        let result = recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe);
+        let result = recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe);
         assert!(result.is_err());
         assert_eq!(economy.balance, 10); // unchanged
     }
@@ -380,7 +732,7 @@ mod tests {
     fn recruit_architect_costs_400() {
         let mut world = World::new();
         let mut economy = make_economy(500);
-        let result = recruit_agent(&mut world, AgentTierKind::Architect, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe);
+        let result = recruit_agent(&mut world, AgentTierKind::Architect, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe);
         assert!(result.is_ok());
         assert_eq!(economy.balance, 100); // 500 - 400
     }
@@ -390,7 +742,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(200);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Journeyman, 5.0, 15.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Journeyman, 5.0, 15.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         // Verify position
         let pos = world.get::<&Position>(entity).unwrap();
@@ -419,7 +771,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(100);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         let result = assign_task(&mut world, entity, TaskAssignment::Explore);
         assert!(result.is_ok());
@@ -436,7 +788,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(100);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         // Force unresponsive state
         if let Ok(mut state) = world.get::<&mut AgentState>(entity) {
@@ -452,7 +804,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(100);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         assign_task(&mut world, entity, TaskAssignment::Guard).unwrap();
 
@@ -465,7 +817,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(100);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         assign_task(&mut world, entity, TaskAssignment::Crank).unwrap();
 
@@ -478,7 +830,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(100);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         let vibe = world.get::<&AgentVibeConfig>(entity).unwrap();
         assert_eq!(vibe.max_turns, 5);
@@ -492,7 +844,7 @@ mod tests {
         let mut world = World::new();
         let mut economy = make_economy(500);
         let entity =
-            recruit_agent(&mut world, AgentTierKind::Architect, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Architect, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         let vibe = world.get::<&AgentVibeConfig>(entity).unwrap();
         assert_eq!(vibe.max_turns, 50);
@@ -506,9 +858,9 @@ mod tests {
         let mut economy = make_economy(1000);
 
         let apprentice =
-            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
         let architect =
-            recruit_agent(&mut world, AgentTierKind::Architect, 10.0, 0.0, &mut economy, crate::protocol::AiBackend::MistralVibe).unwrap();
+            recruit_agent(&mut world, AgentTierKind::Architect, 10.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
 
         let a_vibe = world.get::<&AgentVibeConfig>(apprentice).unwrap();
         let arch_vibe = world.get::<&AgentVibeConfig>(architect).unwrap();
@@ -521,4 +873,88 @@ mod tests {
         // Apprentice burns more tokens when erroring
         assert!(a_vibe.token_burn_rate > arch_vibe.token_burn_rate);
     }
+
+    #[test]
+    fn specialization_needs_the_upgrade_and_happens_once() {
+        let mut world = World::new();
+        let mut economy = make_economy(500);
+        let agent = recruit_agent(&mut world, AgentTierKind::Artisan, 0.0, 0.0, &mut economy, 0, crate::protocol::AiBackend::MistralVibe).unwrap();
+        let max_before = world.get::<&Health>(agent).unwrap().max;
+        let mut upgrades = UpgradeState::new();
+        assert!(specialize_agent(&mut world, agent, AgentSpecialization::Defender, &upgrades).is_err());
+
+        upgrades.purchased.insert(UpgradeId::Specialization);
+        specialize_agent(&mut world, agent, AgentSpecialization::Defender, &upgrades).unwrap();
+        assert_eq!(specialization_of(&world, agent), Some(AgentSpecialization::Defender));
+        let health = world.get::<&Health>(agent).unwrap().max;
+        assert_eq!(health, (max_before as f32 * DEFENDER_HEALTH_MULT) as i32);
+
+        assert!(specialize_agent(&mut world, agent, AgentSpecialization::Builder, &upgrades).is_err());
+        assert_eq!(specialization_of(&world, agent), Some(AgentSpecialization::Defender));
+    }
+
+    #[test]
+    fn hiring_a_recruitable_agent_speaks() {
+        let mut world = World::new();
+        let mut economy = make_economy(100);
+        let agent = spawn_agent_body(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, "wren".to_string());
+        world.insert_one(agent, Recruitable { cost: 40 }).unwrap();
+
+        let result = hire_recruitable(&mut world, &mut economy, agent, 0).unwrap();
+        assert_eq!(result.log_entries, vec!["wren recruited!".to_string()]);
+        assert!(matches!(result.audio_events[..], [AudioEvent::AgentSpeak]));
+        assert_eq!(economy.balance, 60);
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Idle);
+        assert!(hire_recruitable(&mut world, &mut economy, agent, 0).is_err());
+    }
+
+    #[test]
+    fn freeing_a_bound_agent_releases_its_guardians() {
+        let mut world = World::new();
+        let mut economy = make_economy(100);
+        let agent = spawn_agent_body(&mut world, AgentTierKind::Apprentice, 0.0, 0.0, "ash".to_string());
+        world.insert(agent, (Recruitable { cost: 150 }, BoundAgent)).unwrap();
+        let guardian = world.spawn((
+            GuardianRogue {
+                home_x: 0.0,
+                home_y: 0.0,
+                leash_radius: 100.0,
+                bound_agent_entity: agent,
+                patrol_waypoint_x: 0.0,
+                patrol_waypoint_y: 0.0,
+                patrol_pause: 0,
+            },
+            Health { current: 10, max: 10 },
+        ));
+
+        assert!(hire_recruitable(&mut world, &mut economy, agent, 0).is_err());
+        economy.balance = 150;
+        assert_eq!(
+            hire_recruitable(&mut world, &mut economy, agent, 0).err().as_deref(),
+            Some("guardians still protect this one")
+        );
+        assert_eq!(economy.balance, 150);
+
+        world.get::<&mut Health>(guardian).unwrap().current = 0;
+        let result = hire_recruitable(&mut world, &mut economy, agent, 0).unwrap();
+        assert!(matches!(result.audio_events[..], [AudioEvent::AgentSpeak]));
+        assert!(world.get::<&BoundAgent>(agent).is_err());
+        assert!(world.get::<&GuardianRogue>(guardian).is_err());
+        assert_eq!(world.get::<&AgentState>(agent).unwrap().state, AgentStateKind::Walking);
+        let wander = world.get::<&WanderState>(agent).unwrap();
+        assert_eq!((wander.home_x, wander.home_y), HOME_BASE);
+    }
+
+    #[test]
+    fn both_backends_start_from_the_tier_error_chance() {
+        for tier in [
+            AgentTierKind::Apprentice,
+            AgentTierKind::Journeyman,
+            AgentTierKind::Artisan,
+            AgentTierKind::Architect,
+        ] {
+            assert_eq!(generate_vibe_config(tier).error_chance_base, base_error_chance(tier));
+            assert_eq!(generate_claude_config(tier).error_chance_base, base_error_chance(tier));
+        }
+    }
 }
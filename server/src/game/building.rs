@@ -16,6 +16,22 @@ pub struct BuildingDefinition {
     pub description: &'static str,
 }
 
+/// Whether `kind` is an app, one of the buildings that hosts a project,
+/// rather than infrastructure or a utility like the Watchtower.
+pub fn is_app(kind: &BuildingTypeKind) -> bool {
+    !matches!(
+        kind,
+        BuildingTypeKind::Pylon
+            | BuildingTypeKind::Relay
+            | BuildingTypeKind::Nexus
+            | BuildingTypeKind::ComputeFarm
+            | BuildingTypeKind::Watchtower
+            | BuildingTypeKind::TokenWheel
+            | BuildingTypeKind::CraftingTable
+            | BuildingTypeKind::Nest
+    )
+}
+
 /// Returns the canonical [`BuildingDefinition`] for the given building kind.
 pub fn get_building_definition(kind: &BuildingTypeKind) -> BuildingDefinition {
     match kind {
@@ -32,6 +48,33 @@ pub fn get_building_definition(kind: &BuildingTypeKind) -> BuildingDefinition {
             effects: vec![],
             description: "Illuminates surrounding area. Safety.",
         },
+        BuildingTypeKind::Relay => BuildingDefinition {
+            kind: *kind,
+            name: "Relay",
+            tier: 0,
+            token_cost: 150,
+            build_time: 150.0,
+            width: 2,
+            height: 2,
+            light_source: Some((360.0, (1.0, 0.85, 0.5))),
+            effects: vec![BuildingEffect::PylonRangeBoost(160.0)],
+            description: "An upgraded Pylon. The light reaches further.",
+        },
+        BuildingTypeKind::Nexus => BuildingDefinition {
+            kind: *kind,
+            name: "Nexus",
+            tier: 0,
+            token_cost: 350,
+            build_time: 250.0,
+            width: 2,
+            height: 2,
+            light_source: Some((500.0, (1.0, 0.9, 0.65))),
+            effects: vec![
+                BuildingEffect::PylonRangeBoost(300.0),
+                BuildingEffect::PassiveIncome(0.3),
+            ],
+            description: "The light hums. Tokens hum with it.",
+        },
         BuildingTypeKind::ComputeFarm => BuildingDefinition {
             kind: *kind,
             name: "Compute Farm",
@@ -121,6 +164,19 @@ pub fn get_building_definition(kind: &BuildingTypeKind) -> BuildingDefinition {
             description: "Drag tasks from To-Do to Done. Mostly the other direction.",
         },
 
+        BuildingTypeKind::Watchtower => BuildingDefinition {
+            kind: *kind,
+            name: "Watchtower",
+            tier: 2,
+            token_cost: 80,
+            build_time: 200.0,
+            width: 2,
+            height: 2,
+            light_source: None,
+            effects: vec![BuildingEffect::PylonRangeBoost(80.0)],
+            description: "Keeps watch over the dark. Sees further every night.",
+        },
+
         // ── Tier 3 ───────────────────────────────────────────────────
         BuildingTypeKind::EcommerceStore => BuildingDefinition {
             kind: *kind,
@@ -198,5 +254,19 @@ pub fn get_building_definition(kind: &BuildingTypeKind) -> BuildingDefinition {
             effects: vec![],
             description: "Craft items and research upgrades.",
         },
+
+        // ── Hostile ────────────────────────────────────────────────
+        BuildingTypeKind::Nest => BuildingDefinition {
+            kind: *kind,
+            name: "Rogue Nest",
+            tier: 0,
+            token_cost: 0,
+            build_time: 1.0,
+            width: 2,
+            height: 2,
+            light_source: None,
+            effects: vec![],
+            description: "Keeps spawning Swarms until destroyed.",
+        },
     }
 }
@@ -0,0 +1,178 @@
+use rand::Rng;
+
+use crate::ecs::components::GameState;
+use crate::ecs::systems::economy::record_transaction;
+use crate::game::collision;
+use crate::project::ProjectManager;
+use crate::protocol::ChestReward;
+
+/// Seed for the chest placement hash. Must match the client's `CHEST_SEED`.
+const CHEST_SEED: i32 = 55555;
+
+/// Chests can only sit on tiles that are multiples of this step.
+const CHEST_STEP: i32 = 8;
+
+/// Percentage of candidate tiles that host a chest.
+const CHEST_DENSITY_PCT: u32 = 5;
+
+/// Max distance (pixels) from the player to the chest's tile centre. The
+/// client only sends the action inside 48px; the extra slack covers the
+/// player moving during the same tick.
+pub const CHEST_INTERACT_RANGE: f32 = 64.0;
+
+pub const BLUEPRINTS: [&str; 11] = [
+    "TodoApp", "Calculator", "LandingPage",
+    "WeatherDashboard", "ChatApp", "KanbanBoard", "Watchtower",
+    "EcommerceStore", "AiImageGenerator", "ApiDashboard",
+    "Blockchain",
+];
+
+const MATERIALS: [&str; 6] = [
+    "material:iron_powder", "material:wood", "material:metal_ring",
+    "material:ore_coin", "material:liquid_gold", "material:mana",
+];
+const MATERIAL_WEIGHTS: [u32; 6] = [30, 30, 25, 15, 12, 8];
+
+/// Whether tile (wx, wy) hosts a chest, using the same deterministic
+/// hash/density scheme the client uses for placement.
+pub fn is_chest(wx: i32, wy: i32) -> bool {
+    wx % CHEST_STEP == 0
+        && wy % CHEST_STEP == 0
+        && collision::is_walkable(wx, wy)
+        && collision::chest_hash(wx, wy, CHEST_SEED) % 100 < CHEST_DENSITY_PCT
+}
+
+/// Open the chest at tile (wx, wy) for a player standing at `player_pos`.
+///
+/// Rolls 5-15 tokens, a 30% chance of a not-yet-owned blueprint (which also
+/// unlocks that building in the project manager), and 1-3 weighted
+/// materials. Rewards are applied to the economy/inventory and returned so
+/// the caller can show them.
+pub fn open_chest(
+    wx: i32,
+    wy: i32,
+    player_pos: (f32, f32),
+    game_state: &mut GameState,
+    project_manager: &mut ProjectManager,
+    rng: &mut impl Rng,
+) -> Result<Vec<ChestReward>, String> {
+    if !is_chest(wx, wy) {
+        return Err(format!("no chest at ({}, {})", wx, wy));
+    }
+    if game_state.opened_chests.contains(&(wx, wy)) {
+        return Err("chest already opened".to_string());
+    }
+    let dx = collision::tile_center(wx) - player_pos.0;
+    let dy = collision::tile_center(wy) - player_pos.1;
+    if dx * dx + dy * dy > CHEST_INTERACT_RANGE * CHEST_INTERACT_RANGE {
+        return Err("chest is out of reach".to_string());
+    }
+
+    game_state.opened_chests.insert((wx, wy));
+    let mut rewards = Vec::new();
+
+    // Always: 5-15 tokens
+    let token_reward = rng.gen_range(5..=15) as i64;
+    record_transaction(&mut game_state.economy, token_reward, "chest", game_state.tick);
+    rewards.push(ChestReward { item_type: "token".to_string(), count: token_reward as u32 });
+
+    // 30% chance: random blueprint
+    if rng.gen_range(0..100) < 30 {
+        let bp = BLUEPRINTS[rng.gen_range(0..BLUEPRINTS.len())];
+        let bp_type = format!("blueprint:{}", bp);
+        if !game_state.has_inventory_item(&bp_type, 1) {
+            game_state.add_inventory_item(&bp_type, 1);
+            if let Some(building_id) = ProjectManager::building_type_to_id(bp) {
+                project_manager.unlock_building(&building_id);
+            }
+            rewards.push(ChestReward { item_type: bp_type, count: 1 });
+        }
+    }
+
+    // 1-3 random materials
+    let total_weight: u32 = MATERIAL_WEIGHTS.iter().sum();
+    for _ in 0..rng.gen_range(1..=3) {
+        let mut roll = rng.gen_range(0..total_weight);
+        for (i, &w) in MATERIAL_WEIGHTS.iter().enumerate() {
+            if roll < w {
+                game_state.add_inventory_item(MATERIALS[i], 1);
+                rewards.push(ChestReward { item_type: MATERIALS[i].to_string(), count: 1 });
+                break;
+            }
+            roll -= w;
+        }
+    }
+
+    Ok(rewards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn find_chest() -> (i32, i32) {
+        (0..64)
+            .flat_map(|y| (0..64).map(move |x| (x * CHEST_STEP, y * CHEST_STEP)))
+            .find(|&(x, y)| is_chest(x, y))
+            .expect("no chest in search area")
+    }
+
+    fn setup() -> (GameState, ProjectManager, StdRng) {
+        let (_world, gs) = create_world();
+        let pm = ProjectManager::new(std::path::Path::new("/nonexistent/buildings.json"));
+        (gs, pm, StdRng::seed_from_u64(7))
+    }
+
+    fn standing_at(wx: i32, wy: i32) -> (f32, f32) {
+        (collision::tile_center(wx), collision::tile_center(wy))
+    }
+
+    #[test]
+    fn rewards_are_applied() {
+        let (mut gs, mut pm, mut rng) = setup();
+        let (wx, wy) = find_chest();
+        let before = gs.economy.balance;
+
+        let rewards = open_chest(wx, wy, standing_at(wx, wy), &mut gs, &mut pm, &mut rng).unwrap();
+
+        let tokens = rewards.iter().find(|r| r.item_type == "token").unwrap().count as i64;
+        assert!((5..=15).contains(&tokens));
+        assert_eq!(gs.economy.balance, before + tokens);
+        for reward in rewards.iter().filter(|r| r.item_type != "token") {
+            assert!(gs.has_inventory_item(&reward.item_type, 1), "{}", reward.item_type);
+            if let Some(bp) = reward.item_type.strip_prefix("blueprint:") {
+                let id = ProjectManager::building_type_to_id(bp).unwrap();
+                assert!(pm.is_unlocked(&id));
+            }
+        }
+        assert!(gs.opened_chests.contains(&(wx, wy)));
+    }
+
+    #[test]
+    fn double_open_is_rejected() {
+        let (mut gs, mut pm, mut rng) = setup();
+        let (wx, wy) = find_chest();
+        open_chest(wx, wy, standing_at(wx, wy), &mut gs, &mut pm, &mut rng).unwrap();
+        let balance = gs.economy.balance;
+
+        let err = open_chest(wx, wy, standing_at(wx, wy), &mut gs, &mut pm, &mut rng).unwrap_err();
+        assert!(err.contains("already opened"));
+        assert_eq!(gs.economy.balance, balance);
+    }
+
+    #[test]
+    fn out_of_range_and_fake_chests_are_rejected() {
+        let (mut gs, mut pm, mut rng) = setup();
+        let (wx, wy) = find_chest();
+        let (px, py) = standing_at(wx, wy);
+
+        let err = open_chest(wx, wy, (px + 200.0, py), &mut gs, &mut pm, &mut rng).unwrap_err();
+        assert!(err.contains("out of reach"));
+        assert!(!gs.opened_chests.contains(&(wx, wy)));
+
+        assert!(open_chest(wx + 1, wy, (px, py), &mut gs, &mut pm, &mut rng).is_err());
+    }
+}
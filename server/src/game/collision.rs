@@ -1,7 +1,11 @@
-/// Client-matching terrain collision for server-side movement validation.
-///
-/// These functions mirror the client's world.ts terrain generation exactly
-/// (hash, noise, fbm, isWater, elevation, terrainAt, isWalkable).
+//! Client-matching terrain collision for server-side movement validation.
+//!
+//! These functions mirror the client's world.ts terrain generation exactly
+//! (hash, noise, fbm, isWater, elevation, terrainAt, isWalkable).
+//! Walkability is decided here, not by the simplex `TileMap`; streamed chunk
+//! data carries this module's verdict per tile (see `terrain_stream`).
+
+use crate::game::tilemap::{terrain_at, TERRAIN_SEED};
 
 const TILE_PX: f32 = 16.0;
 
@@ -81,6 +85,12 @@ pub fn is_walkable(wx: i32, wy: i32) -> bool {
     true
 }
 
+/// Movement speed multiplier for the server terrain at tile `(wx, wy)`:
+/// Forest slows movement, everything else leaves it unchanged.
+pub fn terrain_movement_modifier(wx: i32, wy: i32) -> f32 {
+    terrain_at(wx, wy, TERRAIN_SEED).speed_modifier()
+}
+
 /// Public wrapper around the hash function for chest validation.
 /// Must match the client's `hash(wx, wy, CHEST_SEED)` exactly.
 pub fn chest_hash(x: i32, y: i32, seed: i32) -> u32 {
@@ -91,3 +101,30 @@ pub fn chest_hash(x: i32, y: i32, seed: i32) -> u32 {
 pub fn pixel_to_tile(px: f32) -> i32 {
     (px / TILE_PX).floor() as i32
 }
+
+/// Pixel position of the centre of a tile.
+pub fn tile_center(tile: i32) -> f32 {
+    tile as f32 * TILE_PX + TILE_PX / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::tilemap::{Terrain, FOREST_SPEED_MODIFIER};
+
+    fn find_tile(terrain: Terrain) -> (i32, i32) {
+        (0..200)
+            .flat_map(|y| (0..200).map(move |x| (x, y)))
+            .find(|&(x, y)| terrain_at(x, y, TERRAIN_SEED) == terrain)
+            .expect("terrain should appear near the origin")
+    }
+
+    #[test]
+    fn forest_slows_movement() {
+        let (fx, fy) = find_tile(Terrain::Forest);
+        assert_eq!(terrain_movement_modifier(fx, fy), FOREST_SPEED_MODIFIER);
+
+        let (gx, gy) = find_tile(Terrain::Grass);
+        assert_eq!(terrain_movement_modifier(gx, gy), 1.0);
+    }
+}
@@ -0,0 +1,477 @@
+use hecs::World;
+
+use crate::ecs::components::{
+    Building, BuildingType, ConstructionProgress, Durability, GameState, Player, Position,
+};
+use crate::protocol::BuildingTypeKind;
+use crate::ecs::systems::economy::record_transaction;
+
+// ── Recipe output ───────────────────────────────────────────────────
+
+/// What a recipe produces. Weapon and armor ids are the client ids accepted
+/// by `EquipWeapon` / `EquipArmor` (see `weapon_stats::weapon_from_id`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CraftOutput {
+    Weapon(&'static str),
+    Armor(&'static str),
+    Material(&'static str),
+    Consumable(&'static str),
+}
+
+impl CraftOutput {
+    /// Inventory item type the output is stored as.
+    pub fn item_type(&self) -> String {
+        match self {
+            CraftOutput::Weapon(id) => format!("weapon:{}", id),
+            CraftOutput::Armor(id) => format!("armor:{}", id),
+            CraftOutput::Material(id) => format!("material:{}", id),
+            CraftOutput::Consumable(id) => format!("consumable:{}", id),
+        }
+    }
+}
+
+// ── Recipe definition ───────────────────────────────────────────────
+
+pub struct Recipe {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Required materials as `(inventory item_type, count)` pairs.
+    pub materials: &'static [(&'static str, u32)],
+    pub token_cost: i64,
+    /// How many of `output` one craft makes.
+    pub output_count: u32,
+    /// A completed building of this kind must exist to craft the recipe.
+    pub requires_building: BuildingTypeKind,
+    pub output: CraftOutput,
+}
+
+/// Returns the full recipe catalogue.
+pub fn all_recipes() -> &'static [Recipe] {
+    use BuildingTypeKind::CraftingTable;
+    use CraftOutput::*;
+
+    static RECIPES: &[Recipe] = &[
+        // ── Weapons ─────────────────────────────────────────────────
+        Recipe {
+            id: "weapon_shortsword",
+            name: "Shortsword",
+            materials: &[("material:iron_powder", 2), ("material:wood", 1)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Weapon("shortsword"),
+        },
+        Recipe {
+            id: "weapon_greatsword",
+            name: "Greatsword",
+            materials: &[
+                ("material:iron_powder", 3),
+                ("material:metal_ring", 2),
+                ("material:liquid_gold", 1),
+            ],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Weapon("greatsword"),
+        },
+        Recipe {
+            id: "weapon_staff",
+            name: "Staff",
+            materials: &[("material:wood", 3), ("material:mana", 1)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Weapon("staff"),
+        },
+        Recipe {
+            id: "weapon_crossbow",
+            name: "Crossbow",
+            materials: &[
+                ("material:wood", 2),
+                ("material:metal_ring", 2),
+                ("material:ore_coin", 1),
+            ],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Weapon("crossbow"),
+        },
+        Recipe {
+            id: "weapon_torch",
+            name: "Torch",
+            materials: &[("material:wood", 2), ("material:mana", 1)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Weapon("torch"),
+        },
+        // ── Armour ──────────────────────────────────────────────────
+        Recipe {
+            id: "armour_cloth",
+            name: "Cloth Armour",
+            materials: &[("material:wood", 2)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Armor("cloth"),
+        },
+        Recipe {
+            id: "armour_leather",
+            name: "Leather Armour",
+            materials: &[("material:wood", 2), ("material:iron_powder", 1)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Armor("leather"),
+        },
+        Recipe {
+            id: "armour_chain",
+            name: "Chain Armour",
+            materials: &[("material:metal_ring", 3), ("material:iron_powder", 2)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Armor("chain"),
+        },
+        Recipe {
+            id: "armour_plate",
+            name: "Plate Armour",
+            materials: &[
+                ("material:metal_ring", 3),
+                ("material:iron_powder", 2),
+                ("material:liquid_gold", 2),
+            ],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Armor("plate"),
+        },
+        // ── Materials ───────────────────────────────────────────────
+        Recipe {
+            id: "material_ore_coin",
+            name: "Smelt Ore Coin",
+            materials: &[("material:iron_powder", 3)],
+            token_cost: 10,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Material("ore_coin"),
+        },
+        // ── Rogue loot ──────────────────────────────────────────────
+        Recipe {
+            id: "consumable_repair_kit",
+            name: "Repair Kit",
+            materials: &[("material:circuit_shard", 3)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Consumable("repair_kit"),
+        },
+        Recipe {
+            id: "consumable_antidote",
+            name: "Antidote",
+            materials: &[("material:corruption_essence", 2)],
+            token_cost: 0,
+            output_count: 1,
+            requires_building: CraftingTable,
+            output: Consumable("antidote"),
+        },
+        Recipe {
+            id: "material_mana_distill",
+            name: "Distill Mana",
+            materials: &[("material:corruption_essence", 1), ("material:circuit_shard", 1)],
+            token_cost: 5,
+            output_count: 2,
+            requires_building: CraftingTable,
+            output: Material("mana"),
+        },
+    ];
+
+    RECIPES
+}
+
+/// Look up a recipe by id.
+pub fn get_recipe(id: &str) -> Option<&'static Recipe> {
+    all_recipes().iter().find(|r| r.id == id)
+}
+
+// ── Crafting ────────────────────────────────────────────────────────
+
+/// A finished craft: `count` of `output` were added to the inventory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CraftSuccess {
+    pub recipe_id: &'static str,
+    pub output: CraftOutput,
+    pub count: u32,
+}
+
+/// Whether a completed building of `kind` exists.
+fn has_completed_building(world: &World, kind: BuildingTypeKind) -> bool {
+    world
+        .query::<(&BuildingType, &ConstructionProgress)>()
+        .with::<&Building>()
+        .iter()
+        .any(|(_e, (building_type, progress))| building_type.kind == kind && progress.current >= progress.total)
+}
+
+/// Checks everything `recipe` needs: a living player, its building,
+/// materials and tokens.
+fn check_recipe(recipe: &Recipe, world: &World, game_state: &GameState) -> Result<(), String> {
+    if game_state.player_dead {
+        return Err("cannot craft while dead".to_string());
+    }
+
+    if !has_completed_building(world, recipe.requires_building) {
+        return Err(format!("needs a completed {:?}", recipe.requires_building));
+    }
+
+    let missing: Vec<String> = recipe
+        .materials
+        .iter()
+        .filter(|(item, count)| !game_state.has_inventory_item(item, *count))
+        .map(|(item, count)| format!("{}x {}", count, item))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing materials: {}", missing.join(", ")));
+    }
+
+    if game_state.economy.balance < recipe.token_cost {
+        return Err(format!(
+            "insufficient tokens (need {}, have {})",
+            recipe.token_cost, game_state.economy.balance
+        ));
+    }
+    Ok(())
+}
+
+/// Ids of the recipes the player could craft right now.
+pub fn available_recipes(world: &World, game_state: &GameState) -> Vec<String> {
+    all_recipes()
+        .iter()
+        .filter(|recipe| check_recipe(recipe, world, game_state).is_ok())
+        .map(|recipe| recipe.id.to_string())
+        .collect()
+}
+
+/// Craft `recipe_id`: validates the required building, materials and
+/// balance, consumes them, and adds the output to the inventory.
+pub fn craft(recipe_id: &str, world: &World, game_state: &mut GameState) -> Result<CraftSuccess, String> {
+    let recipe = get_recipe(recipe_id).ok_or_else(|| format!("unknown recipe '{}'", recipe_id))?;
+    check_recipe(recipe, world, game_state)?;
+
+    for (item, count) in recipe.materials {
+        game_state.remove_inventory_item(item, *count);
+    }
+    record_transaction(&mut game_state.economy, -recipe.token_cost, &format!("craft {}", recipe.name), game_state.tick);
+    game_state.add_inventory_item(&recipe.output.item_type(), recipe.output_count);
+
+    Ok(CraftSuccess { recipe_id: recipe.id, output: recipe.output, count: recipe.output_count })
+}
+
+// ── Repair ──────────────────────────────────────────────────────────
+
+/// How close the player must stand to a finished CraftingTable to repair.
+pub const REPAIR_RANGE: f32 = 100.0;
+
+/// Tokens charged per point of missing weapon durability.
+pub const REPAIR_COST_PER_POINT: i64 = 2;
+
+/// Tokens needed to restore `durability` to full.
+pub fn repair_cost(durability: &Durability) -> i64 {
+    durability.max.saturating_sub(durability.current) as i64 * REPAIR_COST_PER_POINT
+}
+
+/// Whether a finished CraftingTable stands within [`REPAIR_RANGE`] of `(x, y)`.
+pub fn near_crafting_table(world: &World, x: f32, y: f32) -> bool {
+    world
+        .query::<(&BuildingType, &Position, &ConstructionProgress)>()
+        .with::<&Building>()
+        .iter()
+        .any(|(_e, (building_type, pos, progress))| {
+            building_type.kind == BuildingTypeKind::CraftingTable
+                && progress.current >= progress.total
+                && (pos.x - x).powi(2) + (pos.y - y).powi(2) <= REPAIR_RANGE * REPAIR_RANGE
+        })
+}
+
+/// Repair the player's weapon to full at a nearby CraftingTable, charging
+/// [`repair_cost`]. Returns the tokens spent.
+pub fn repair_weapon(world: &mut World, game_state: &mut GameState) -> Result<i64, String> {
+    if game_state.player_dead {
+        return Err("cannot repair while dead".to_string());
+    }
+
+    let (player, x, y) = world
+        .query::<&Position>()
+        .with::<&Player>()
+        .iter()
+        .next()
+        .map(|(e, pos)| (e, pos.x, pos.y))
+        .ok_or_else(|| "no player".to_string())?;
+
+    if !near_crafting_table(world, x, y) {
+        return Err("too far from a crafting table".to_string());
+    }
+
+    let mut durability = world
+        .get::<&mut Durability>(player)
+        .map_err(|_| "weapon cannot wear out".to_string())?;
+    let cost = repair_cost(&durability);
+    if cost == 0 {
+        return Err("weapon is already fully repaired".to_string());
+    }
+    if game_state.economy.balance < cost {
+        return Err(format!(
+            "insufficient tokens (need {}, have {})",
+            cost, game_state.economy.balance
+        ));
+    }
+
+    durability.current = durability.max;
+    record_transaction(&mut game_state.economy, -cost, "weapon repair", game_state.tick);
+    Ok(cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::weapon_stats::{armor_from_id, weapon_from_id};
+    use crate::ecs::world::create_world;
+
+    /// A fresh world (with its pre-built CraftingTable) and `items` in the
+    /// inventory.
+    fn state_with(items: &[(&str, u32)]) -> (World, GameState) {
+        let (world, mut gs) = create_world();
+        for (item, count) in items {
+            gs.add_inventory_item(item, *count);
+        }
+        (world, gs)
+    }
+
+    #[test]
+    fn crafting_consumes_materials_and_adds_output() {
+        let (world, mut gs) = state_with(&[("material:iron_powder", 3), ("material:wood", 1)]);
+
+        let success = craft("weapon_shortsword", &world, &mut gs).unwrap();
+
+        assert_eq!(success.output, CraftOutput::Weapon("shortsword"));
+        assert!(gs.has_inventory_item("material:iron_powder", 1));
+        assert!(!gs.has_inventory_item("material:iron_powder", 2));
+        assert!(!gs.has_inventory_item("material:wood", 1));
+        // On top of the starter shortsword.
+        assert!(gs.has_inventory_item("weapon:shortsword", 2));
+    }
+
+    #[test]
+    fn missing_materials_fail_without_consuming() {
+        let (world, mut gs) = state_with(&[("material:iron_powder", 2)]);
+
+        let err = craft("weapon_shortsword", &world, &mut gs).unwrap_err();
+
+        assert!(err.contains("material:wood"));
+        assert!(gs.has_inventory_item("material:iron_powder", 2));
+    }
+
+    #[test]
+    fn token_cost_is_checked_and_deducted() {
+        let (world, mut gs) = state_with(&[("material:iron_powder", 3)]);
+        gs.economy.balance = 5;
+        assert!(craft("material_ore_coin", &world, &mut gs).unwrap_err().contains("insufficient tokens"));
+        assert!(gs.has_inventory_item("material:iron_powder", 3));
+
+        gs.economy.balance = 15;
+        assert_eq!(craft("material_ore_coin", &world, &mut gs).unwrap().output, CraftOutput::Material("ore_coin"));
+        assert_eq!(gs.economy.balance, 5);
+        assert!(gs.has_inventory_item("material:ore_coin", 1));
+    }
+
+    #[test]
+    fn unknown_recipe_and_dead_player_fail() {
+        let (world, mut gs) = state_with(&[("material:wood", 2)]);
+        assert!(craft("weapon_banana", &world, &mut gs).unwrap_err().contains("unknown recipe"));
+
+        gs.player_dead = true;
+        assert!(craft("armour_cloth", &world, &mut gs).unwrap_err().contains("dead"));
+        assert!(gs.has_inventory_item("material:wood", 2));
+    }
+
+    #[test]
+    fn three_shards_craft_a_repair_kit() {
+        let (world, mut gs) = state_with(&[("material:circuit_shard", 3)]);
+        assert!(available_recipes(&world, &gs).contains(&"consumable_repair_kit".to_string()));
+
+        let success = craft("consumable_repair_kit", &world, &mut gs).unwrap();
+        assert_eq!(
+            success,
+            CraftSuccess { recipe_id: "consumable_repair_kit", output: CraftOutput::Consumable("repair_kit"), count: 1 }
+        );
+        assert!(!gs.has_inventory_item("material:circuit_shard", 1));
+        assert!(gs.has_inventory_item("consumable:repair_kit", 1));
+        assert!(!available_recipes(&world, &gs).contains(&"consumable_repair_kit".to_string()));
+    }
+
+    #[test]
+    fn crafting_needs_a_completed_crafting_table() {
+        let (world, mut gs) = state_with(&[("material:circuit_shard", 3)]);
+        let table = world
+            .query::<&BuildingType>()
+            .iter()
+            .find(|(_e, bt)| bt.kind == BuildingTypeKind::CraftingTable)
+            .map(|(e, _)| e)
+            .unwrap();
+        world.get::<&mut ConstructionProgress>(table).unwrap().current = 0.0;
+
+        assert!(available_recipes(&world, &gs).is_empty());
+        assert!(craft("consumable_repair_kit", &world, &mut gs).unwrap_err().contains("CraftingTable"));
+        assert!(gs.has_inventory_item("material:circuit_shard", 3));
+    }
+
+    #[test]
+    fn repair_cost_scales_with_missing_durability() {
+        assert_eq!(repair_cost(&Durability { current: 60, max: 60 }), 0);
+        assert_eq!(repair_cost(&Durability { current: 50, max: 60 }), 10 * REPAIR_COST_PER_POINT);
+        assert_eq!(repair_cost(&Durability { current: 1, max: 90 }), 89 * REPAIR_COST_PER_POINT);
+    }
+
+    #[test]
+    fn repair_needs_a_nearby_crafting_table() {
+        let (mut world, mut gs) = create_world();
+        gs.economy.balance = 1000;
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        world.insert_one(player, Durability { current: 20, max: 60 }).unwrap();
+
+        // Wander out of range of the starting table.
+        world.get::<&mut Position>(player).unwrap().x = -1000.0;
+        assert!(repair_weapon(&mut world, &mut gs).unwrap_err().contains("crafting table"));
+        assert_eq!(world.get::<&Durability>(player).unwrap().current, 20);
+        assert_eq!(gs.economy.balance, 1000);
+
+        // The starting table sits 90 units from the spawn point.
+        world.get::<&mut Position>(player).unwrap().x = 400.0;
+        assert_eq!(repair_weapon(&mut world, &mut gs), Ok(40 * REPAIR_COST_PER_POINT));
+        assert_eq!(world.get::<&Durability>(player).unwrap().current, 60);
+        assert_eq!(gs.economy.balance, 1000 - 40 * REPAIR_COST_PER_POINT);
+        assert!(repair_weapon(&mut world, &mut gs).unwrap_err().contains("fully repaired"));
+    }
+
+    #[test]
+    fn repair_is_refused_when_unaffordable() {
+        let (mut world, mut gs) = create_world();
+        gs.economy.balance = 5;
+        let player = world.query::<&Player>().iter().next().map(|(e, _)| e).unwrap();
+        world.insert_one(player, Durability { current: 10, max: 60 }).unwrap();
+
+        assert!(repair_weapon(&mut world, &mut gs).unwrap_err().contains("insufficient tokens"));
+        assert_eq!(world.get::<&Durability>(player).unwrap().current, 10);
+    }
+
+    #[test]
+    fn crafted_gear_is_equippable() {
+        for recipe in all_recipes() {
+            match recipe.output {
+                CraftOutput::Weapon(id) => assert!(weapon_from_id(id).is_some(), "{}", id),
+                CraftOutput::Armor(id) => assert!(armor_from_id(id).is_some(), "{}", id),
+                CraftOutput::Material(_) | CraftOutput::Consumable(_) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Gatekeeping for `Debug*` player actions: a server-wide on/off switch plus
+//! input sanitising so a misbehaving client can't wreck the game or stall
+//! the tick loop.
+
+use crate::protocol::PlayerAction;
+
+/// Largest token balance the debug actions can produce.
+pub const MAX_DEBUG_TOKENS: i64 = 1_000_000_000;
+
+/// Largest XP grant accepted by `DebugGrantAgentXP`.
+pub const MAX_DEBUG_XP: u64 = 1_000_000;
+
+/// Debug spawn actions (rogues, bosses, agents) accepted per tick.
+pub const MAX_DEBUG_SPAWNS_PER_TICK: u32 = 10;
+
+/// Whether `action` is one of the `Debug*` actions.
+pub fn is_debug_action(action: &PlayerAction) -> bool {
+    matches!(
+        action,
+        PlayerAction::DebugSetTokens { .. }
+            | PlayerAction::DebugAddTokens { .. }
+            | PlayerAction::DebugToggleSpawning
+            | PlayerAction::DebugClearRogues
+            | PlayerAction::DebugSetPhase { .. }
+            | PlayerAction::DebugSetCrankTier { .. }
+            | PlayerAction::DebugToggleGodMode
+            | PlayerAction::DebugSpawnRogue { .. }
+            | PlayerAction::DebugSpawnBoss
+            | PlayerAction::DebugHealPlayer
+            | PlayerAction::DebugSpawnAgent { .. }
+            | PlayerAction::DebugGrantAgentXP { .. }
+            | PlayerAction::DebugClearAgents
+            | PlayerAction::DebugSetAllPriorities { .. }
+            | PlayerAction::DebugUnlockAllBuildings
+            | PlayerAction::DebugLockAllBuildings
+            | PlayerAction::DebugClearFog
+            | PlayerAction::DebugTriggerCheatInput
+    )
+}
+
+/// Validates debug actions before the main loop runs them.
+pub struct DebugGuard {
+    allowed: bool,
+    spawns_this_tick: u32,
+}
+
+impl DebugGuard {
+    pub fn new(allowed: bool) -> Self {
+        Self { allowed, spawns_this_tick: 0 }
+    }
+
+    /// Resets the per-tick spawn budget. Call once at the start of each tick.
+    pub fn start_tick(&mut self) {
+        self.spawns_this_tick = 0;
+    }
+
+    /// Checks `action` against the debug policy. Non-debug actions pass
+    /// through untouched; debug actions are rejected when debug mode is off,
+    /// and otherwise have their inputs clamped given the current `balance`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the action was refused.
+    pub fn check(&mut self, action: &PlayerAction, balance: i64) -> Result<PlayerAction, String> {
+        if !is_debug_action(action) {
+            return Ok(action.clone());
+        }
+        if !self.allowed {
+            return Err("debug actions are disabled on this server".to_string());
+        }
+
+        match action {
+            PlayerAction::DebugSetTokens { amount } => {
+                if *amount < 0 {
+                    return Err(format!("cannot set tokens to a negative amount ({})", amount));
+                }
+                Ok(PlayerAction::DebugSetTokens { amount: (*amount).min(MAX_DEBUG_TOKENS) })
+            }
+            PlayerAction::DebugAddTokens { amount } => {
+                // Keep the resulting balance within [0, MAX_DEBUG_TOKENS],
+                // never pushing an out-of-range balance further out.
+                let target = balance
+                    .saturating_add(*amount)
+                    .clamp(balance.min(0), balance.max(MAX_DEBUG_TOKENS));
+                Ok(PlayerAction::DebugAddTokens { amount: target.saturating_sub(balance) })
+            }
+            PlayerAction::DebugGrantAgentXP { agent_id, amount } => Ok(PlayerAction::DebugGrantAgentXP {
+                agent_id: *agent_id,
+                amount: (*amount).min(MAX_DEBUG_XP),
+            }),
+            PlayerAction::DebugSpawnRogue { .. }
+            | PlayerAction::DebugSpawnBoss
+            | PlayerAction::DebugSpawnAgent { .. } => {
+                if self.spawns_this_tick >= MAX_DEBUG_SPAWNS_PER_TICK {
+                    return Err(format!(
+                        "spawn limit of {} per tick reached",
+                        MAX_DEBUG_SPAWNS_PER_TICK
+                    ));
+                }
+                self.spawns_this_tick += 1;
+                Ok(action.clone())
+            }
+            _ => Ok(action.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RogueTypeKind;
+
+    #[test]
+    fn disabled_guard_rejects_debug_actions_only() {
+        let mut guard = DebugGuard::new(false);
+        assert!(guard.check(&PlayerAction::DebugHealPlayer, 0).unwrap_err().contains("disabled"));
+        assert!(guard.check(&PlayerAction::DebugUnlockAllBuildings, 0).is_err());
+        assert!(matches!(guard.check(&PlayerAction::Attack, 0), Ok(PlayerAction::Attack)));
+    }
+
+    #[test]
+    fn token_amounts_are_clamped() {
+        let mut guard = DebugGuard::new(true);
+        assert!(guard.check(&PlayerAction::DebugSetTokens { amount: -5 }, 0).is_err());
+        assert!(matches!(
+            guard.check(&PlayerAction::DebugSetTokens { amount: i64::MAX }, 0),
+            Ok(PlayerAction::DebugSetTokens { amount: MAX_DEBUG_TOKENS })
+        ));
+        assert!(matches!(
+            guard.check(&PlayerAction::DebugAddTokens { amount: i64::MAX }, 100),
+            Ok(PlayerAction::DebugAddTokens { amount }) if amount == MAX_DEBUG_TOKENS - 100
+        ));
+        assert!(matches!(
+            guard.check(&PlayerAction::DebugAddTokens { amount: i64::MIN }, 100),
+            Ok(PlayerAction::DebugAddTokens { amount: -100 })
+        ));
+        assert!(matches!(
+            guard.check(&PlayerAction::DebugAddTokens { amount: 50 }, 100),
+            Ok(PlayerAction::DebugAddTokens { amount: 50 })
+        ));
+        assert!(matches!(
+            guard.check(&PlayerAction::DebugGrantAgentXP { agent_id: 1, amount: u64::MAX }, 0),
+            Ok(PlayerAction::DebugGrantAgentXP { amount: MAX_DEBUG_XP, .. })
+        ));
+    }
+
+    #[test]
+    fn spawns_are_limited_per_tick() {
+        let mut guard = DebugGuard::new(true);
+        let spawn = PlayerAction::DebugSpawnRogue { rogue_type: RogueTypeKind::Swarm };
+        for _ in 0..MAX_DEBUG_SPAWNS_PER_TICK {
+            assert!(guard.check(&spawn, 0).is_ok());
+        }
+        assert!(guard.check(&PlayerAction::DebugSpawnBoss, 0).unwrap_err().contains("spawn limit"));
+        assert!(guard.check(&PlayerAction::DebugHealPlayer, 0).is_ok());
+
+        guard.start_tick();
+        assert!(guard.check(&spawn, 0).is_ok());
+    }
+}
@@ -0,0 +1,130 @@
+use hecs::World;
+
+use crate::ecs::components::{Armor, CombatPower, Durability, GameState, Player};
+use crate::ecs::weapon_stats::{armor_from_id, armor_stats, fresh_durability, weapon_from_id, weapon_stats};
+
+/// Gear every player starts with and may always equip, whatever happens
+/// to their inventory. Seeded into the inventory by `create_world`.
+pub const STARTER_EQUIPMENT: &[&str] = &["weapon:shortsword", "armor:cloth"];
+
+/// Ticks the player can't attack after changing weapon or armor (1s at 20Hz).
+pub const EQUIP_COOLDOWN_TICKS: u32 = 20;
+
+/// Whether the player may equip `item_type`: it's carried or starter gear.
+fn owns(game_state: &GameState, item_type: &str) -> bool {
+    STARTER_EQUIPMENT.contains(&item_type) || game_state.has_inventory_item(item_type, 1)
+}
+
+/// Starts the equip cooldown, keeping any longer attack cooldown already
+/// running so a swap can't cut it short.
+fn start_equip_cooldown(combat: &mut CombatPower, previous_remaining: u32) {
+    combat.cooldown_remaining = previous_remaining.max(EQUIP_COOLDOWN_TICKS);
+}
+
+/// Equips the weapon with client id `weapon_id` (e.g. `"crossbow"`) with
+/// fresh durability. Returns the log entry.
+pub fn equip_weapon(world: &mut World, game_state: &GameState, weapon_id: &str) -> Result<String, String> {
+    let weapon = weapon_from_id(weapon_id).ok_or_else(|| format!("unknown weapon {}", weapon_id))?;
+    if !owns(game_state, &format!("weapon:{}", weapon_id)) {
+        return Err(format!("you don't have a {}", weapon_id));
+    }
+
+    let durability = fresh_durability(weapon.clone());
+    let (player, combat) = world
+        .query_mut::<&mut CombatPower>()
+        .with::<&Player>()
+        .into_iter()
+        .next()
+        .ok_or_else(|| "no player".to_string())?;
+    let previous_remaining = combat.cooldown_remaining;
+    *combat = weapon_stats(weapon);
+    start_equip_cooldown(combat, previous_remaining);
+
+    match durability {
+        Some(d) => { let _ = world.insert_one(player, d); }
+        None => { let _ = world.remove_one::<Durability>(player); }
+    }
+    Ok(format!("[combat] equipped {}", weapon_id))
+}
+
+/// Equips the armor with client id `armor_id` (e.g. `"chain"`). Returns the
+/// log entry.
+pub fn equip_armor(world: &mut World, game_state: &GameState, armor_id: &str) -> Result<String, String> {
+    let armor_type = armor_from_id(armor_id).ok_or_else(|| format!("unknown armor {}", armor_id))?;
+    if !owns(game_state, &format!("armor:{}", armor_id)) {
+        return Err(format!("you don't have {} armor", armor_id));
+    }
+
+    let (_player, (armor, combat)) = world
+        .query_mut::<(&mut Armor, &mut CombatPower)>()
+        .with::<&Player>()
+        .into_iter()
+        .next()
+        .ok_or_else(|| "no player".to_string())?;
+    *armor = armor_stats(armor_type);
+    let previous_remaining = combat.cooldown_remaining;
+    start_equip_cooldown(combat, previous_remaining);
+    Ok(format!("[combat] equipped {} armor", armor_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::{ArmorType, WeaponType};
+    use crate::ecs::world::create_world;
+
+    fn player_combat(world: &World) -> CombatPower {
+        let mut query = world.query::<&CombatPower>().with::<&Player>();
+        query.iter().next().unwrap().1.clone()
+    }
+
+    #[test]
+    fn only_carried_gear_can_be_equipped() {
+        let (mut world, mut game_state) = create_world();
+        assert!(equip_weapon(&mut world, &game_state, "greatsword").is_err());
+        assert!(matches!(player_combat(&world).weapon, WeaponType::ProcessTerminator));
+        assert!(equip_armor(&mut world, &game_state, "plate").is_err());
+        assert!(equip_weapon(&mut world, &game_state, "spoon").is_err());
+
+        game_state.add_inventory_item("weapon:greatsword", 1);
+        assert_eq!(equip_weapon(&mut world, &game_state, "greatsword").unwrap(), "[combat] equipped greatsword");
+        assert!(matches!(player_combat(&world).weapon, WeaponType::HardReset));
+    }
+
+    #[test]
+    fn starter_gear_is_seeded_and_always_equippable() {
+        let (mut world, mut game_state) = create_world();
+        for item in STARTER_EQUIPMENT {
+            assert!(game_state.has_inventory_item(item, 1));
+        }
+
+        game_state.inventory.clear();
+        equip_weapon(&mut world, &game_state, "shortsword").unwrap();
+        equip_armor(&mut world, &game_state, "cloth").unwrap();
+        let mut query = world.query::<&Armor>().with::<&Player>();
+        assert!(matches!(query.iter().next().unwrap().1.armor_type, ArmorType::BasePrompt));
+    }
+
+    #[test]
+    fn swapping_keeps_the_longer_cooldown() {
+        let (mut world, mut game_state) = create_world();
+        game_state.add_inventory_item("weapon:greatsword", 1);
+        game_state.add_inventory_item("weapon:staff", 1);
+
+        equip_weapon(&mut world, &game_state, "greatsword").unwrap();
+        assert_eq!(player_combat(&world).cooldown_remaining, EQUIP_COOLDOWN_TICKS);
+
+        // Mid-swing with more cooldown left than an equip costs.
+        for (_e, combat) in world.query_mut::<&mut CombatPower>().with::<&Player>() {
+            combat.cooldown_remaining = 35;
+        }
+        equip_weapon(&mut world, &game_state, "staff").unwrap();
+        assert_eq!(player_combat(&world).cooldown_remaining, 35);
+
+        for (_e, combat) in world.query_mut::<&mut CombatPower>().with::<&Player>() {
+            combat.cooldown_remaining = 3;
+        }
+        equip_armor(&mut world, &game_state, "cloth").unwrap();
+        assert_eq!(player_combat(&world).cooldown_remaining, EQUIP_COOLDOWN_TICKS);
+    }
+}
@@ -1,14 +1,16 @@
 use hecs::World;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use crate::ecs::components::{Discovery, DroppedItem, GamePhase, Position, TokenEconomy};
+use crate::ecs::components::{Discovery, DroppedItem, GamePhase, GameState, Position};
+use crate::ecs::systems::economy::record_transaction;
 use crate::game::tilemap::{CHUNK_SIZE, TILE_SIZE};
 use crate::protocol::BuildingTypeKind;
 
 // ── Discovery types ─────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DiscoveryKind {
     BlueprintFragment { building_type: BuildingTypeKind },
     TokenCache { amount: i64 },
@@ -17,9 +19,11 @@ pub enum DiscoveryKind {
     AnomalyZone,
     NpcSurvivor { name: String },
     MumsCard { variant: CardVariant },
+    /// Permanently adds `boost` to the crank's tokens per rotation.
+    PowerCore { boost: f64 },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CardVariant {
     Standard,
     RewardsPoints,
@@ -27,6 +31,9 @@ pub enum CardVariant {
     DadsCard,
 }
 
+/// Power cores can't push the crank past this many tokens per rotation.
+pub const MAX_TOKENS_PER_ROTATION: f64 = 0.20;
+
 // ── Building pools per phase ────────────────────────────────────────
 
 fn hut_buildings() -> &'static [BuildingTypeKind] {
@@ -44,6 +51,7 @@ fn outpost_buildings() -> &'static [BuildingTypeKind] {
         BuildingTypeKind::WeatherDashboard,
         BuildingTypeKind::ChatApp,
         BuildingTypeKind::KanbanBoard,
+        BuildingTypeKind::Watchtower,
     ]
 }
 
@@ -173,6 +181,13 @@ pub fn scatter_discoveries(
         results.push((x, y, DiscoveryKind::McpRuin));
     }
 
+    // Power core: 2% chance (only Village phase or later), +0.01-0.03 per rotation
+    if is_village_plus && rng.gen::<f32>() < 0.02 {
+        let boost = rng.gen_range(1..=3) as f64 / 100.0;
+        let (x, y) = rand_pos(&mut rng);
+        results.push((x, y, DiscoveryKind::PowerCore { boost }));
+    }
+
     // Anomaly zone: 2% chance
     if rng.gen::<f32>() < 0.02 {
         let (x, y) = rand_pos(&mut rng);
@@ -216,44 +231,43 @@ fn pick_card_variant(rng: &mut StdRng) -> CardVariant {
 
 /// Process a player interacting with a discovery.
 ///
-/// Applies the discovery's effect to the economy and returns a list of
+/// Applies the discovery's effect to the game state and returns a list of
 /// log messages describing what happened.
-pub fn interact_with_discovery(
-    discovery: &DiscoveryKind,
-    economy: &mut TokenEconomy,
-) -> Vec<String> {
+pub fn interact_with_discovery(discovery: &DiscoveryKind, game_state: &mut GameState) -> Vec<String> {
+    let tick = game_state.tick;
+    let economy = &mut game_state.economy;
     match discovery {
         DiscoveryKind::BlueprintFragment { building_type } => {
             vec![format!("[exp] found blueprint fragment: {:?}", building_type)]
         }
         DiscoveryKind::TokenCache { amount } => {
-            economy.balance += amount;
+            record_transaction(economy, *amount, "token cache", tick);
             vec![format!("[exp] found token cache: +{}", amount)]
         }
         DiscoveryKind::MumsCard { variant } => match variant {
             CardVariant::Standard => {
-                economy.balance += 200;
+                record_transaction(economy, 200, "mum's card", tick);
                 vec![
                     "[exp] found: mum's credit card".to_string(),
                     "...she's going to be so mad.".to_string(),
                 ]
             }
             CardVariant::RewardsPoints => {
-                economy.balance += 250;
+                record_transaction(economy, 250, "mum's card", tick);
                 vec![
                     "[exp] found: mum's credit card (rewards points)".to_string(),
                     "bonus points accrued. she won't notice... right?".to_string(),
                 ]
             }
             CardVariant::Expired => {
-                economy.balance += 5;
+                record_transaction(economy, 5, "mum's card", tick);
                 vec![
                     "[exp] found: mum's credit card (expired)".to_string(),
                     "expiry: 01/2026. worth almost nothing.".to_string(),
                 ]
             }
             CardVariant::DadsCard => {
-                economy.balance += 500;
+                record_transaction(economy, 500, "dad's card", tick);
                 vec![
                     "[exp] found: dad's credit card".to_string(),
                     "he never checks this one.".to_string(),
@@ -269,6 +283,15 @@ pub fn interact_with_discovery(
         DiscoveryKind::AnomalyZone => {
             vec!["[exp] anomaly zone detected. reality feels thin here.".to_string()]
         }
+        DiscoveryKind::PowerCore { boost } => {
+            let crank = &mut game_state.crank;
+            crank.tokens_per_rotation = (crank.tokens_per_rotation + boost).min(MAX_TOKENS_PER_ROTATION);
+            game_state.power_cores_collected += 1;
+            vec![format!(
+                "[exp] power core installed. the crank hums: {:.2} tokens per rotation.",
+                crank.tokens_per_rotation
+            )]
+        }
         DiscoveryKind::NpcSurvivor { name } => {
             vec![format!(
                 "[exp] found a survivor: {}. they look like they've seen things.",
@@ -306,15 +329,10 @@ pub fn spawn_discovery(
 mod tests {
     use super::*;
 
-    fn make_economy(balance: i64) -> TokenEconomy {
-        TokenEconomy {
-            balance,
-            fractional: 0.0,
-            income_per_tick: 0.0,
-            expenditure_per_tick: 0.0,
-            income_sources: Vec::new(),
-            expenditure_sinks: Vec::new(),
-        }
+    fn make_state(balance: i64) -> GameState {
+        let (_world, mut game_state) = crate::ecs::world::create_world();
+        game_state.economy.balance = balance;
+        game_state
     }
 
     #[test]
@@ -351,23 +369,21 @@ mod tests {
 
     #[test]
     fn token_cache_interaction_adds_balance() {
-        let mut economy = make_economy(100);
-        let msgs = interact_with_discovery(&DiscoveryKind::TokenCache { amount: 30 }, &mut economy);
-        assert_eq!(economy.balance, 130);
+        let mut game_state = make_state(100);
+        let msgs = interact_with_discovery(&DiscoveryKind::TokenCache { amount: 30 }, &mut game_state);
+        assert_eq!(game_state.economy.balance, 130);
         assert_eq!(msgs.len(), 1);
         assert!(msgs[0].contains("+30"));
     }
 
     #[test]
     fn mums_card_standard_adds_200() {
-        let mut economy = make_economy(0);
+        let mut game_state = make_state(0);
         let msgs = interact_with_discovery(
             &DiscoveryKind::MumsCard {
                 variant: CardVariant::Standard,
-            },
-            &mut economy,
-        );
-        assert_eq!(economy.balance, 200);
+            }, &mut game_state);
+        assert_eq!(game_state.economy.balance, 200);
         assert_eq!(msgs.len(), 2);
         assert!(msgs[0].contains("mum's credit card"));
         assert!(msgs[1].contains("she's going to be so mad"));
@@ -375,41 +391,35 @@ mod tests {
 
     #[test]
     fn mums_card_expired_adds_5() {
-        let mut economy = make_economy(0);
+        let mut game_state = make_state(0);
         let msgs = interact_with_discovery(
             &DiscoveryKind::MumsCard {
                 variant: CardVariant::Expired,
-            },
-            &mut economy,
-        );
-        assert_eq!(economy.balance, 5);
+            }, &mut game_state);
+        assert_eq!(game_state.economy.balance, 5);
         assert!(msgs[0].contains("expired"));
     }
 
     #[test]
     fn dads_card_adds_500() {
-        let mut economy = make_economy(0);
+        let mut game_state = make_state(0);
         let msgs = interact_with_discovery(
             &DiscoveryKind::MumsCard {
                 variant: CardVariant::DadsCard,
-            },
-            &mut economy,
-        );
-        assert_eq!(economy.balance, 500);
+            }, &mut game_state);
+        assert_eq!(game_state.economy.balance, 500);
         assert!(msgs[0].contains("dad's credit card"));
         assert!(msgs[1].contains("he never checks this one"));
     }
 
     #[test]
     fn rewards_points_adds_250() {
-        let mut economy = make_economy(0);
+        let mut game_state = make_state(0);
         let msgs = interact_with_discovery(
             &DiscoveryKind::MumsCard {
                 variant: CardVariant::RewardsPoints,
-            },
-            &mut economy,
-        );
-        assert_eq!(economy.balance, 250);
+            }, &mut game_state);
+        assert_eq!(game_state.economy.balance, 250);
         assert!(msgs[0].contains("rewards points"));
     }
 
@@ -437,23 +447,50 @@ mod tests {
 
     #[test]
     fn blueprint_interaction_logs_type() {
-        let mut economy = make_economy(100);
+        let mut game_state = make_state(100);
         let msgs = interact_with_discovery(
             &DiscoveryKind::BlueprintFragment {
                 building_type: BuildingTypeKind::TodoApp,
-            },
-            &mut economy,
-        );
-        assert_eq!(economy.balance, 100); // no token change
+            }, &mut game_state);
+        assert_eq!(game_state.economy.balance, 100); // no token change
         assert!(msgs[0].contains("blueprint fragment"));
         assert!(msgs[0].contains("TodoApp"));
     }
 
     #[test]
     fn rogue_nest_interaction_warns() {
-        let mut economy = make_economy(100);
-        let msgs = interact_with_discovery(&DiscoveryKind::RogueNest, &mut economy);
+        let mut game_state = make_state(100);
+        let msgs = interact_with_discovery(&DiscoveryKind::RogueNest, &mut game_state);
         assert!(msgs[0].contains("rogue nest"));
         assert!(msgs[0].contains("caution"));
     }
+
+    #[test]
+    fn power_core_boosts_crank_up_to_the_cap() {
+        let mut game_state = make_state(0);
+        let before = game_state.crank.tokens_per_rotation;
+        let msgs = interact_with_discovery(&DiscoveryKind::PowerCore { boost: 0.01 }, &mut game_state);
+        assert_eq!(game_state.crank.tokens_per_rotation, before + 0.01);
+        assert_eq!(game_state.power_cores_collected, 1);
+        assert!(msgs[0].contains("power core"));
+
+        for _ in 0..10 {
+            interact_with_discovery(&DiscoveryKind::PowerCore { boost: 0.03 }, &mut game_state);
+        }
+        assert_eq!(game_state.crank.tokens_per_rotation, MAX_TOKENS_PER_ROTATION);
+        assert_eq!(game_state.power_cores_collected, 11);
+    }
+
+    #[test]
+    fn power_core_only_in_village_plus() {
+        let found = |phase: &GamePhase| {
+            (0..500).any(|seed| {
+                scatter_discoveries(10, 10, seed, phase, false)
+                    .iter()
+                    .any(|(_, _, kind)| matches!(kind, DiscoveryKind::PowerCore { .. }))
+            })
+        };
+        assert!(!found(&GamePhase::Outpost));
+        assert!(found(&GamePhase::Village));
+    }
 }
@@ -1,6 +1,29 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use hecs::World;
 
 use super::tilemap::{CHUNK_SIZE, TILE_SIZE};
+use crate::ecs::components::{
+    Agent, Building, BuildingType, ConstructionProgress, LightSource, MimicDisguise, Player,
+    Position, Rogue, RogueVisibility, TorchRange,
+};
+use crate::protocol::{BuildingTypeKind, ChunkPos, FogTile, MinimapDot, MinimapSnapshot};
+
+/// Light level sent for tiles that were revealed once but aren't lit now.
+pub const REVEALED_LIGHT_LEVEL: f32 = 0.2;
+
+/// Radius (pixels) a completed Watchtower keeps lit.
+pub const WATCHTOWER_LIGHT_RADIUS: f32 = 200.0;
+
+/// File (in the working directory) the revealed chunks persist to.
+pub const FOG_SAVE_PATH: &str = "fog_save.bin";
+
+/// Minimap dot colours (0xRRGGBB).
+pub const MINIMAP_BUILDING_COLOR: u32 = 0xFFD700;
+pub const MINIMAP_AGENT_COLOR: u32 = 0x33CC33;
+pub const MINIMAP_ROGUE_COLOR: u32 = 0xE03030;
 
 /// Fog of war tracking system.
 ///
@@ -12,6 +35,16 @@ pub struct FogOfWar {
     pub revealed: HashSet<(i32, i32)>,
     /// Set of currently lit tiles, stored as (cx, cy, tx, ty).
     pub lit_tiles: HashSet<(i32, i32, usize, usize)>,
+    /// Brightness of each lit tile (1.0 at a light's centre, falling to 0 at
+    /// its radius); the brightest light wins where sources overlap.
+    tile_light: HashMap<(i32, i32, usize, usize), f32>,
+    /// Chunks that contained lit tiles on the previous update.
+    prev_lit_chunks: HashSet<(i32, i32)>,
+    /// Last light levels sent to the client per chunk, for change detection.
+    sent: HashMap<(i32, i32), Vec<f32>>,
+    /// Set by [`reset_sent`](Self::reset_sent): the next update resends
+    /// every revealed chunk, not just the lit ones.
+    resend_revealed: bool,
 }
 
 impl FogOfWar {
@@ -19,6 +52,10 @@ impl FogOfWar {
         FogOfWar {
             revealed: HashSet::new(),
             lit_tiles: HashSet::new(),
+            tile_light: HashMap::new(),
+            prev_lit_chunks: HashSet::new(),
+            sent: HashMap::new(),
+            resend_revealed: false,
         }
     }
 
@@ -28,7 +65,9 @@ impl FogOfWar {
     /// Returns a list of chunk positions that were newly revealed this update.
     pub fn update_light(&mut self, light_sources: &[(f32, f32, f32)]) -> Vec<(i32, i32)> {
         // Clear previously lit tiles — only currently active lights matter
+        self.prev_lit_chunks = self.lit_tiles.iter().map(|&(cx, cy, _, _)| (cx, cy)).collect();
         self.lit_tiles.clear();
+        self.tile_light.clear();
 
         let mut newly_revealed = Vec::new();
 
@@ -63,6 +102,9 @@ impl FogOfWar {
                         let ty = abs_ty.rem_euclid(CHUNK_SIZE as i32) as usize;
 
                         self.lit_tiles.insert((cx, cy, tx, ty));
+                        let level = if radius > 0.0 { 1.0 - dist / radius } else { 1.0 };
+                        let entry = self.tile_light.entry((cx, cy, tx, ty)).or_insert(0.0);
+                        *entry = entry.max(level);
 
                         // Track newly revealed chunks
                         if self.revealed.insert((cx, cy)) {
@@ -80,6 +122,192 @@ impl FogOfWar {
     pub fn is_lit(&self, cx: i32, cy: i32, tx: usize, ty: usize) -> bool {
         self.lit_tiles.contains(&(cx, cy, tx, ty))
     }
+
+    /// Light level of a tile: its brightness if lit, a dim level if the
+    /// chunk has been revealed, otherwise zero.
+    pub fn light_level(&self, cx: i32, cy: i32, tx: usize, ty: usize) -> f32 {
+        if let Some(&level) = self.tile_light.get(&(cx, cy, tx, ty)) {
+            return level.max(REVEALED_LIGHT_LEVEL);
+        }
+        if self.revealed.contains(&(cx, cy)) {
+            REVEALED_LIGHT_LEVEL
+        } else {
+            0.0
+        }
+    }
+
+    /// Row-major light levels for every tile in a chunk.
+    fn chunk_levels(&self, cx: i32, cy: i32) -> Vec<f32> {
+        let mut levels = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE);
+        for ty in 0..CHUNK_SIZE {
+            for tx in 0..CHUNK_SIZE {
+                levels.push(self.light_level(cx, cy, tx, ty));
+            }
+        }
+        levels
+    }
+
+    /// Forget what the client was sent, e.g. after it reconnects, so the
+    /// next [`collect_updates`](Self::collect_updates) sends every revealed
+    /// chunk again.
+    pub fn reset_sent(&mut self) {
+        self.sent.clear();
+        self.resend_revealed = true;
+    }
+
+    /// Builds fog updates for chunks that are newly revealed or whose
+    /// lighting changed since the last call. Only chunks lit now or on the
+    /// previous update can have changed, so nothing else is rescanned
+    /// (unless [`reset_sent`](Self::reset_sent) asked for everything).
+    pub fn collect_updates(&mut self) -> Vec<(ChunkPos, Vec<FogTile>)> {
+        let resend: Vec<(i32, i32)> = if std::mem::take(&mut self.resend_revealed) {
+            self.revealed.iter().copied().collect()
+        } else {
+            Vec::new()
+        };
+        let mut candidates: Vec<(i32, i32)> = self
+            .lit_tiles
+            .iter()
+            .map(|&(cx, cy, _, _)| (cx, cy))
+            .chain(self.prev_lit_chunks.iter().copied())
+            .chain(resend)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        candidates.sort_unstable();
+
+        let mut updates = Vec::new();
+        for (cx, cy) in candidates {
+            let levels = self.chunk_levels(cx, cy);
+            if self.sent.get(&(cx, cy)) == Some(&levels) {
+                continue;
+            }
+            let tiles = levels.iter().map(|&light_level| FogTile { light_level }).collect();
+            updates.push((ChunkPos { x: cx, y: cy }, tiles));
+            self.sent.insert((cx, cy), levels);
+        }
+        updates
+    }
+}
+
+/// Gathers `(x, y, radius)` light sources: the player's torch, every
+/// building `LightSource`, and every completed Watchtower.
+pub fn collect_light_sources(world: &World) -> Vec<(f32, f32, f32)> {
+    let mut lights: Vec<(f32, f32, f32)> = world
+        .query::<(&Player, &Position, &TorchRange)>()
+        .iter()
+        .map(|(_e, (_p, pos, torch))| (pos.x, pos.y, torch.radius))
+        .collect();
+    lights.extend(
+        world
+            .query::<(&Position, &LightSource)>()
+            .iter()
+            .map(|(_e, (pos, light))| (pos.x, pos.y, light.radius)),
+    );
+    lights.extend(
+        world
+            .query::<(&Position, &BuildingType, Option<&ConstructionProgress>)>()
+            .iter()
+            .filter(|(_e, (_, bt, progress))| {
+                bt.kind == BuildingTypeKind::Watchtower
+                    && progress.is_none_or(|p| p.current >= p.total)
+            })
+            .map(|(_e, (pos, _, _))| (pos.x, pos.y, WATCHTOWER_LIGHT_RADIUS)),
+    );
+    lights
+}
+
+/// Chunk containing the world position `(x, y)`.
+fn chunk_of(x: f32, y: f32) -> (i32, i32) {
+    let extent = CHUNK_SIZE as f32 * TILE_SIZE;
+    ((x / extent).floor() as i32, (y / extent).floor() as i32)
+}
+
+/// Builds the minimap: every revealed chunk, plus a dot for each building,
+/// agent and rogue standing in one. Dots are snapped to their chunk, so
+/// entities sharing a chunk and colour collapse into one. Hidden rogues are
+/// left off and disguised Mimics show as buildings.
+pub fn generate_minimap(fog: &FogOfWar, world: &World) -> MinimapSnapshot {
+    let mut revealed_chunks: Vec<ChunkPos> =
+        fog.revealed.iter().map(|&(x, y)| ChunkPos { x, y }).collect();
+    revealed_chunks.sort_unstable_by_key(|c| (c.x, c.y));
+
+    let mut entity_dots = Vec::new();
+    let mut add_dot = |x: f32, y: f32, color: u32| {
+        let (cx, cy) = chunk_of(x, y);
+        if fog.revealed.contains(&(cx, cy)) {
+            entity_dots.push(MinimapDot { x: cx, y: cy, color });
+        }
+    };
+
+    for (_e, pos) in world.query::<&Position>().with::<&Building>().iter() {
+        add_dot(pos.x, pos.y, MINIMAP_BUILDING_COLOR);
+    }
+    for (_e, pos) in world.query::<&Position>().with::<&Agent>().iter() {
+        add_dot(pos.x, pos.y, MINIMAP_AGENT_COLOR);
+    }
+    for (_e, (pos, visibility, disguise)) in world
+        .query::<(&Position, Option<&RogueVisibility>, Option<&MimicDisguise>)>()
+        .with::<&Rogue>()
+        .iter()
+    {
+        if visibility.is_some_and(|v| !v.visible) {
+            continue;
+        }
+        let color = if disguise.is_some() { MINIMAP_BUILDING_COLOR } else { MINIMAP_ROGUE_COLOR };
+        add_dot(pos.x, pos.y, color);
+    }
+
+    entity_dots.sort_unstable();
+    entity_dots.dedup();
+
+    MinimapSnapshot { revealed_chunks, entity_dots }
+}
+
+// ── Persistence ─────────────────────────────────────────────────────
+
+impl FogOfWar {
+    /// Write the revealed chunks to `path` as a little-endian `u32` count
+    /// followed by that many `(i32, i32)` chunk coordinates.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut chunks: Vec<&(i32, i32)> = self.revealed.iter().collect();
+        chunks.sort_unstable();
+
+        let mut bytes = Vec::with_capacity(4 + chunks.len() * 8);
+        bytes.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        for (cx, cy) in chunks {
+            bytes.extend_from_slice(&cx.to_le_bytes());
+            bytes.extend_from_slice(&cy.to_le_bytes());
+        }
+
+        let tmp = path.with_extension("bin.tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Read a fog written by [`save`](Self::save). Only the revealed chunks
+    /// are restored; lighting is recomputed on the next update.
+    pub fn load(path: &Path) -> io::Result<FogOfWar> {
+        let bytes = std::fs::read(path)?;
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+        let count = bytes
+            .get(..4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or_else(|| invalid("fog file too short"))?;
+        let body = &bytes[4..];
+        if body.len() != count * 8 {
+            return Err(invalid("fog file length doesn't match its chunk count"));
+        }
+
+        let read_i32 = |b: &[u8]| i32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        let mut fog = FogOfWar::new();
+        fog.revealed = body
+            .chunks_exact(8)
+            .map(|pair| (read_i32(&pair[..4]), read_i32(&pair[4..])))
+            .collect();
+        Ok(fog)
+    }
 }
 
 impl Default for FogOfWar {
@@ -127,6 +355,153 @@ mod tests {
         assert!(!fog.revealed.is_empty());
     }
 
+    #[test]
+    fn placing_pylon_produces_fog_updates_for_covered_chunks() {
+        use crate::ecs::components::TokenEconomy;
+        use crate::ecs::systems::placement::{place_building, PlacementRules};
+        use crate::protocol::BuildingTypeKind;
+
+        let mut world = World::new();
+        let mut economy = TokenEconomy {
+            balance: 1000,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        };
+        // Place the pylon on a chunk corner so its light spans four chunks.
+        let corner = CHUNK_SIZE as f32 * TILE_SIZE;
+        place_building(&mut world, BuildingTypeKind::Pylon, corner, corner, &PlacementRules::default(), &mut economy, 0).unwrap();
+
+        let mut fog = FogOfWar::new();
+        fog.update_light(&collect_light_sources(&world));
+        let updates = fog.collect_updates();
+
+        let chunks: HashSet<(i32, i32)> = updates.iter().map(|(c, _)| (c.x, c.y)).collect();
+        let expected: HashSet<(i32, i32)> = [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().collect();
+        assert_eq!(chunks, expected);
+        for (_chunk, tiles) in &updates {
+            assert_eq!(tiles.len(), CHUNK_SIZE * CHUNK_SIZE);
+            assert!(tiles.iter().any(|t| t.light_level > REVEALED_LIGHT_LEVEL));
+        }
+
+        // Nothing changed, so nothing is resent.
+        fog.update_light(&collect_light_sources(&world));
+        assert!(fog.collect_updates().is_empty());
+    }
+
+    #[test]
+    fn completed_watchtower_reveals_more_chunks() {
+        use crate::ecs::components::{Building, TokenEconomy};
+        use crate::ecs::systems::placement::{place_building, PlacementRules};
+
+        let mut world = World::new();
+        world.spawn((Player, Position { x: 0.0, y: 0.0 }, TorchRange { radius: 160.0 }));
+        let baseline = FogOfWar::new().update_light(&collect_light_sources(&world)).len();
+
+        let mut economy = TokenEconomy {
+            balance: 1000,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        };
+        let corner = CHUNK_SIZE as f32 * TILE_SIZE;
+        let tower = place_building(&mut world, BuildingTypeKind::Watchtower, corner, corner, &PlacementRules::default(), &mut economy, 0).unwrap();
+
+        // Still under construction: no light yet.
+        assert_eq!(FogOfWar::new().update_light(&collect_light_sources(&world)).len(), baseline);
+
+        {
+            let mut progress = world.get::<&mut ConstructionProgress>(tower).unwrap();
+            progress.current = progress.total;
+        }
+        assert!(world.get::<&Building>(tower).is_ok());
+        let with_tower = FogOfWar::new().update_light(&collect_light_sources(&world)).len();
+        assert!(with_tower > baseline, "{} vs {}", with_tower, baseline);
+    }
+
+    #[test]
+    fn minimap_shows_buildings_in_revealed_chunks() {
+        use crate::ecs::components::TokenEconomy;
+        use crate::ecs::systems::placement::{place_building, PlacementRules};
+
+        let mut world = World::new();
+        let mut economy = TokenEconomy {
+            balance: 1000,
+            fractional: 0.0,
+            income_fractional: 0.0,
+            wage_fractional: 0.0,
+            income_per_tick: 0.0,
+            expenditure_per_tick: 0.0,
+            income_sources: Vec::new(),
+            expenditure_sinks: Vec::new(),
+            transaction_log: Default::default(),
+            lifetime_earned: 0,
+            min_balance: 0,
+        };
+        place_building(&mut world, BuildingTypeKind::Pylon, 800.0, 800.0, &PlacementRules::default(), &mut economy, 0).unwrap();
+        world.spawn((Agent, Position { x: 5000.0, y: 5000.0 }));
+
+        let mut fog = FogOfWar::new();
+        assert!(generate_minimap(&fog, &world).entity_dots.is_empty());
+
+        fog.update_light(&collect_light_sources(&world));
+        let minimap = generate_minimap(&fog, &world);
+        assert!(minimap.revealed_chunks.contains(&ChunkPos { x: 1, y: 1 }));
+        assert_eq!(
+            minimap.entity_dots,
+            vec![MinimapDot { x: 1, y: 1, color: MINIMAP_BUILDING_COLOR }]
+        );
+    }
+
+    #[test]
+    fn revealed_chunks_dim_when_light_leaves() {
+        let mut fog = FogOfWar::new();
+        fog.update_light(&[(8.0, 8.0, 20.0)]);
+        fog.collect_updates();
+
+        fog.update_light(&[]);
+        let updates = fog.collect_updates();
+        assert_eq!(updates.len(), 1);
+        assert!(updates[0].1.iter().all(|t| t.light_level == REVEALED_LIGHT_LEVEL));
+        assert_eq!(fog.light_level(5, 5, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn reset_resends_lit_and_revealed_chunks() {
+        let mut fog = FogOfWar::new();
+        fog.update_light(&[(8.0, 8.0, 20.0)]);
+        let lit = fog.collect_updates();
+        let chunk_px = CHUNK_SIZE as f32 * TILE_SIZE;
+        fog.update_light(&[(8.0 + 5.0 * chunk_px, 8.0, 20.0)]);
+        fog.collect_updates();
+        fog.update_light(&[(8.0 + 5.0 * chunk_px, 8.0, 20.0)]);
+        assert!(fog.collect_updates().is_empty());
+
+        // After a reconnect, the dimmed first chunk comes back along with
+        // the lit one.
+        fog.reset_sent();
+        fog.update_light(&[(8.0 + 5.0 * chunk_px, 8.0, 20.0)]);
+        let resent: HashSet<(i32, i32)> = fog.collect_updates().iter().map(|(c, _)| (c.x, c.y)).collect();
+        assert!(resent.contains(&(lit[0].0.x, lit[0].0.y)));
+        assert!(resent.contains(&(5, 0)));
+        assert_eq!(resent.len(), fog.revealed.len());
+        assert!(fog.collect_updates().is_empty());
+    }
+
     #[test]
     fn newly_revealed_only_on_first_visit() {
         let mut fog = FogOfWar::new();
@@ -137,4 +512,34 @@ mod tests {
         let newly2 = fog.update_light(&[(8.0, 8.0, 20.0)]);
         assert!(newly2.is_empty());
     }
+
+    #[test]
+    fn revealed_chunks_survive_a_save_and_load() {
+        let path = std::env::temp_dir().join(format!("ittb_fog_test_{}.bin", std::process::id()));
+        let mut fog = FogOfWar::new();
+        for i in 0..100 {
+            fog.revealed.insert((i % 10 - 5, i / 10 - 5));
+        }
+
+        fog.save(&path).unwrap();
+        let loaded = FogOfWar::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.revealed.len(), 100);
+        assert_eq!(loaded.revealed, fog.revealed);
+    }
+
+    #[test]
+    fn truncated_fog_file_fails_to_load() {
+        let path = std::env::temp_dir().join(format!("ittb_fog_truncated_{}.bin", std::process::id()));
+        let mut fog = FogOfWar::new();
+        fog.revealed.insert((1, 2));
+        fog.save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        assert_eq!(FogOfWar::load(&path).err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(FogOfWar::load(&path).err().map(|e| e.kind()), Some(io::ErrorKind::NotFound));
+    }
 }
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use hecs::World;
+
+use crate::ecs::components::{CarryCapacity, GameState, Player, TokenEconomy};
+use crate::ecs::systems::economy::record_transaction;
+use crate::protocol::InventoryItem;
+
+/// Weight of one unit of each item, keyed by the name after the category
+/// prefix (`material:circuit_shard` -> `circuit_shard`). Starter gear is
+/// weightless so it never eats into the carry capacity.
+static ITEM_WEIGHTS: LazyLock<HashMap<&'static str, u32>> = LazyLock::new(|| {
+    HashMap::from([
+        ("circuit_shard", 1),
+        ("corruption_essence", 2),
+        ("repair_kit", 1),
+        ("shortsword", 0),
+        ("cloth", 0),
+    ])
+});
+
+/// Weight of one blueprint of any kind.
+pub const BLUEPRINT_WEIGHT: u32 = 3;
+
+/// Weight of one unit of an item missing from the table.
+pub const DEFAULT_ITEM_WEIGHT: u32 = 1;
+
+/// Tokens for one carry capacity upgrade.
+pub const CARRY_UPGRADE_COST: i64 = 200;
+
+/// Carry capacity gained per upgrade.
+pub const CARRY_UPGRADE_AMOUNT: u32 = 5;
+
+/// Weight of one unit of `item_type`.
+pub fn item_weight(item_type: &str) -> u32 {
+    if item_type.starts_with("blueprint:") {
+        return BLUEPRINT_WEIGHT;
+    }
+    let name = item_type.split_once(':').map_or(item_type, |(_category, name)| name);
+    ITEM_WEIGHTS.get(name).copied().unwrap_or(DEFAULT_ITEM_WEIGHT)
+}
+
+/// Total weight of everything in `items`.
+pub fn inventory_weight(items: &[InventoryItem]) -> u32 {
+    items.iter().map(|i| item_weight(&i.item_type) * i.count).sum()
+}
+
+/// Sets the player's `CarryCapacity::current` to the inventory's weight.
+pub fn sync_carry_weight(world: &mut World, game_state: &GameState) {
+    let weight = inventory_weight(&game_state.inventory);
+    for (_e, capacity) in world.query_mut::<&mut CarryCapacity>().with::<&Player>() {
+        capacity.current = weight;
+    }
+}
+
+/// Adds `count` of `item_type` to the inventory, unless that would take the
+/// player past their carry capacity.
+pub fn add_item(world: &mut World, game_state: &mut GameState, item_type: &str, count: u32) -> Result<(), String> {
+    let max = world
+        .query_mut::<&CarryCapacity>()
+        .with::<&Player>()
+        .into_iter()
+        .next()
+        .map(|(_e, c)| c.max)
+        .ok_or_else(|| "no player".to_string())?;
+    let total = inventory_weight(&game_state.inventory) + item_weight(item_type) * count;
+    if total > max {
+        return Err(format!("{} x{} is too heavy ({}/{})", item_type, count, total, max));
+    }
+    game_state.add_inventory_item(item_type, count);
+    sync_carry_weight(world, game_state);
+    Ok(())
+}
+
+/// Removes `count` of `item_type` from the inventory and lightens the load.
+pub fn remove_item(world: &mut World, game_state: &mut GameState, item_type: &str, count: u32) -> Result<(), String> {
+    if !game_state.remove_inventory_item(item_type, count) {
+        return Err(format!("not carrying {} x{}", item_type, count));
+    }
+    sync_carry_weight(world, game_state);
+    Ok(())
+}
+
+/// Buys [`CARRY_UPGRADE_AMOUNT`] more carry capacity for
+/// [`CARRY_UPGRADE_COST`] tokens. `quoted_cost` is the price the client
+/// showed; a stale quote is refused. Returns the new capacity.
+pub fn upgrade_carry_capacity(
+    world: &mut World,
+    economy: &mut TokenEconomy,
+    quoted_cost: i64,
+    tick: u64,
+) -> Result<u32, String> {
+    if quoted_cost != CARRY_UPGRADE_COST {
+        return Err(format!("carry upgrade costs {} tokens", CARRY_UPGRADE_COST));
+    }
+    if economy.balance < CARRY_UPGRADE_COST {
+        return Err(format!("need {} tokens, have {}", CARRY_UPGRADE_COST, economy.balance));
+    }
+    let (_e, capacity) = world
+        .query_mut::<&mut CarryCapacity>()
+        .with::<&Player>()
+        .into_iter()
+        .next()
+        .ok_or_else(|| "no player".to_string())?;
+    capacity.max += CARRY_UPGRADE_AMOUNT;
+    let max = capacity.max;
+    record_transaction(economy, -CARRY_UPGRADE_COST, "carry capacity", tick);
+    Ok(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+
+    fn capacity(world: &World) -> (u32, u32) {
+        let mut query = world.query::<&CarryCapacity>().with::<&Player>();
+        let (_e, c) = query.iter().next().unwrap();
+        (c.current, c.max)
+    }
+
+    #[test]
+    fn weights_come_from_the_table() {
+        assert_eq!(item_weight("material:circuit_shard"), 1);
+        assert_eq!(item_weight("material:corruption_essence"), 2);
+        assert_eq!(item_weight("consumable:repair_kit"), 1);
+        assert_eq!(item_weight("blueprint:TodoApp"), BLUEPRINT_WEIGHT);
+        assert_eq!(item_weight("material:wood"), DEFAULT_ITEM_WEIGHT);
+    }
+
+    #[test]
+    fn adding_past_capacity_is_refused() {
+        let (mut world, mut game_state) = create_world();
+        let (_, max) = capacity(&world);
+        add_item(&mut world, &mut game_state, "material:corruption_essence", 2).unwrap();
+        assert_eq!(capacity(&world), (4, max));
+
+        let before = game_state.inventory.clone();
+        assert!(add_item(&mut world, &mut game_state, "blueprint:TodoApp", 1).is_err());
+        assert_eq!(game_state.inventory, before);
+        assert_eq!(capacity(&world), (4, max));
+
+        remove_item(&mut world, &mut game_state, "material:corruption_essence", 1).unwrap();
+        assert_eq!(capacity(&world), (2, max));
+        add_item(&mut world, &mut game_state, "blueprint:TodoApp", 1).unwrap();
+        assert_eq!(capacity(&world), (5, max));
+    }
+
+    #[test]
+    fn capacity_upgrade_costs_tokens() {
+        let (mut world, mut game_state) = create_world();
+        let (_, max) = capacity(&world);
+        assert!(upgrade_carry_capacity(&mut world, &mut game_state.economy, CARRY_UPGRADE_COST, 0).is_err());
+
+        game_state.economy.balance = 250;
+        assert!(upgrade_carry_capacity(&mut world, &mut game_state.economy, 10, 0).is_err());
+        let new_max = upgrade_carry_capacity(&mut world, &mut game_state.economy, CARRY_UPGRADE_COST, 0).unwrap();
+        assert_eq!(new_max, max + CARRY_UPGRADE_AMOUNT);
+        assert_eq!(game_state.economy.balance, 50);
+    }
+}
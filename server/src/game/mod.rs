@@ -1,8 +1,18 @@
 pub mod agents;
 pub mod building;
+pub mod chests;
 pub mod collision;
+pub mod crafting;
+pub mod debug;
+pub mod equipment;
 pub mod exploration;
 pub mod fog;
+pub mod inventory;
 pub mod progression;
+pub mod save;
+pub mod spatial;
+pub mod speech;
+pub mod terrain_stream;
+pub mod tick;
 pub mod tilemap;
 pub mod upgrades;
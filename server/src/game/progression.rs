@@ -1,10 +1,10 @@
+use std::collections::HashMap;
+
 use hecs::World;
 
-use crate::ecs::components::{
-    Building, BuildingType, ConstructionProgress, GamePhase, GameState,
-};
-use crate::game::building::get_building_definition;
-use crate::protocol::BuildingTypeKind;
+use crate::ecs::components::{Building, BuildingType, ConstructionProgress, GamePhase, GameState};
+use crate::game::building::is_app;
+use crate::protocol::{AudioEvent, BuildingTypeKind};
 
 /// The number of ticks after reaching City phase before the cascade triggers.
 /// At 20 Hz this is 6000 ticks = 5 minutes.
@@ -16,15 +16,136 @@ pub struct ProgressionResult {
     pub phase_changed: bool,
     /// The new phase, if a transition occurred.
     pub new_phase: Option<GamePhase>,
-    /// Log messages generated by the progression system.
+    /// Log messages generated by the progression system, sent as
+    /// `LogCategory::System` announcements.
     pub log_entries: Vec<String>,
+    /// Audio cues to play: `BuildComplete` on a phase advance.
+    pub audio_events: Vec<AudioEvent>,
     /// Whether the cascade was triggered this tick.
     pub cascade_triggered: bool,
+    /// Which requirements for leaving the (new) current phase are met,
+    /// keyed by [`PhaseStats::progress`]. Empty in the City.
+    pub phase_progress: HashMap<String, bool>,
+}
+
+/// Requirements for advancing out of a phase.
+pub struct PhaseCriteria {
+    pub from: GamePhase,
+    pub to: GamePhase,
+    /// Completed app buildings (see [`is_app`]).
+    pub min_app_buildings: u32,
+    /// Tokens on hand.
+    pub min_balance: i64,
+    /// Kinds that must each have at least one completed building.
+    pub required_buildings: &'static [BuildingTypeKind],
+    pub message: &'static str,
+}
+
+/// Every Tier 1 and Tier 2 building.
+const TIER_1_2_BUILDINGS: &[BuildingTypeKind] = &[
+    BuildingTypeKind::TodoApp,
+    BuildingTypeKind::Calculator,
+    BuildingTypeKind::LandingPage,
+    BuildingTypeKind::WeatherDashboard,
+    BuildingTypeKind::ChatApp,
+    BuildingTypeKind::KanbanBoard,
+    BuildingTypeKind::Watchtower,
+];
+
+/// Phase transitions, checked in order against the current phase.
+pub const PHASE_CRITERIA: &[PhaseCriteria] = &[
+    PhaseCriteria {
+        from: GamePhase::Hut,
+        to: GamePhase::Outpost,
+        min_app_buildings: 2,
+        min_balance: 100,
+        required_buildings: &[],
+        message: "[sys] the darkness has noticed you.",
+    },
+    PhaseCriteria {
+        from: GamePhase::Outpost,
+        to: GamePhase::Village,
+        min_app_buildings: 4,
+        min_balance: 300,
+        required_buildings: &[],
+        message: "[sys] they're sending more. build faster.",
+    },
+    PhaseCriteria {
+        from: GamePhase::Village,
+        to: GamePhase::Network,
+        min_app_buildings: 6,
+        min_balance: 1000,
+        required_buildings: TIER_1_2_BUILDINGS,
+        message: "[sys] your agents are talking to each other now. that's new.",
+    },
+    PhaseCriteria {
+        from: GamePhase::Network,
+        to: GamePhase::City,
+        min_app_buildings: 8,
+        min_balance: 0,
+        required_buildings: &[BuildingTypeKind::Blockchain],
+        message: "[sys] the cascade approaches.",
+    },
+];
+
+/// What the world has achieved so far, measured against [`PHASE_CRITERIA`].
+#[derive(Debug, Default)]
+pub struct PhaseStats {
+    pub completed_app_buildings: u32,
+    /// Kinds with at least one completed building.
+    pub completed_kinds: Vec<BuildingTypeKind>,
+    pub balance: i64,
+}
+
+impl PhaseStats {
+    /// Tallies completed buildings in `world`.
+    pub fn collect(world: &World, game_state: &GameState) -> Self {
+        let mut stats = PhaseStats {
+            balance: game_state.economy.balance,
+            ..Default::default()
+        };
+
+        for (_entity, (building_type, construction)) in
+            world.query::<(&BuildingType, &ConstructionProgress)>().with::<&Building>().iter()
+        {
+            // A building counts as complete when current >= total
+            if construction.current >= construction.total {
+                if is_app(&building_type.kind) {
+                    stats.completed_app_buildings += 1;
+                }
+                if !stats.completed_kinds.contains(&building_type.kind) {
+                    stats.completed_kinds.push(building_type.kind);
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Whether these stats satisfy every requirement in `criteria`.
+    pub fn meets(&self, criteria: &PhaseCriteria) -> bool {
+        self.progress(criteria).values().all(|&met| met)
+    }
+
+    /// Each requirement in `criteria` and whether it is met:
+    /// `app_buildings`, `balance` and `required_buildings`. Requirements a
+    /// phase doesn't have count as met.
+    pub fn progress(&self, criteria: &PhaseCriteria) -> HashMap<String, bool> {
+        let required_ok = criteria
+            .required_buildings
+            .iter()
+            .all(|kind| self.completed_kinds.contains(kind));
+        HashMap::from([
+            ("app_buildings".to_string(), self.completed_app_buildings >= criteria.min_app_buildings),
+            ("balance".to_string(), self.balance >= criteria.min_balance),
+            ("required_buildings".to_string(), required_ok),
+        ])
+    }
 }
 
-/// Examines the world for completed buildings, counts them by tier, and checks
-/// whether a phase transition should occur.  Also manages the cascade trigger
-/// once the City phase has been reached for long enough.
+/// Checks the current phase against [`PHASE_CRITERIA`] and advances it when
+/// the world meets the next set of requirements.  Also manages the cascade
+/// trigger once the City phase has been reached for long enough.
 pub fn progression_system(
     world: &World,
     game_state: &mut GameState,
@@ -33,78 +154,18 @@ pub fn progression_system(
         phase_changed: false,
         new_phase: None,
         log_entries: Vec::new(),
+        audio_events: Vec::new(),
         cascade_triggered: false,
+        phase_progress: HashMap::new(),
     };
 
-    // ── Count completed buildings by tier ─────────────────────────────
-    let mut tier_counts: [u32; 5] = [0; 5]; // indices 0..4
-    let mut has_pylon = false;
-
-    for (_entity, (building_type, construction)) in
-        world.query::<(&BuildingType, &ConstructionProgress)>().with::<&Building>().iter()
-    {
-        // A building counts as complete when current >= total
-        if construction.current >= construction.total {
-            let def = get_building_definition(&building_type.kind);
-            let tier = def.tier as usize;
-            if tier < tier_counts.len() {
-                tier_counts[tier] += 1;
-            }
-
-            if building_type.kind == BuildingTypeKind::Pylon {
-                has_pylon = true;
-            }
-        }
-    }
-
     // ── Check phase transitions ──────────────────────────────────────
-    let transition = match game_state.phase {
-        GamePhase::Hut => {
-            // Hut -> Outpost: 2+ Tier 1 buildings AND first Pylon complete
-            if tier_counts[1] >= 2 && has_pylon {
-                Some((
-                    GamePhase::Outpost,
-                    "[sys] the darkness has noticed you.".to_string(),
-                ))
-            } else {
-                None
-            }
-        }
-        GamePhase::Outpost => {
-            // Outpost -> Village: 2+ Tier 2 buildings
-            if tier_counts[2] >= 2 {
-                Some((
-                    GamePhase::Village,
-                    "[sys] they're sending more. build faster.".to_string(),
-                ))
-            } else {
-                None
-            }
-        }
-        GamePhase::Village => {
-            // Village -> Network: 2+ Tier 3 buildings
-            if tier_counts[3] >= 2 {
-                Some((
-                    GamePhase::Network,
-                    "[sys] your agents are talking to each other now. that's new.".to_string(),
-                ))
-            } else {
-                None
-            }
-        }
-        GamePhase::Network => {
-            // Network -> City: Blockchain complete
-            if tier_counts[4] >= 1 {
-                Some((
-                    GamePhase::City,
-                    "[sys] the cascade approaches.".to_string(),
-                ))
-            } else {
-                None
-            }
-        }
-        GamePhase::City => None,
-    };
+    let stats = PhaseStats::collect(world, game_state);
+    let transition = PHASE_CRITERIA
+        .iter()
+        .find(|c| c.from == game_state.phase)
+        .filter(|c| stats.meets(c))
+        .map(|c| (c.to.clone(), c.message.to_string()));
 
     // ── Apply transition ─────────────────────────────────────────────
     if let Some((new_phase, thematic_message)) = transition {
@@ -122,14 +183,21 @@ pub fn progression_system(
         }
 
         game_state.phase = new_phase.clone();
+        result.audio_events.push(AudioEvent::BuildComplete);
         result.phase_changed = true;
         result.new_phase = Some(new_phase);
     }
 
+    if let Some(criteria) = PHASE_CRITERIA.iter().find(|c| c.from == game_state.phase) {
+        result.phase_progress = stats.progress(criteria);
+    }
+
     // ── Cascade check ────────────────────────────────────────────────
     if matches!(game_state.phase, GamePhase::City) && !game_state.cascade_active {
         if let Some(city_tick) = game_state.city_reached_tick {
-            if game_state.tick.saturating_sub(city_tick) >= CASCADE_TICK_THRESHOLD {
+            // Trigger exactly once: the spawn system clears `cascade_active`
+            // when the final wave ends.
+            if game_state.tick.saturating_sub(city_tick) == CASCADE_TICK_THRESHOLD {
                 game_state.cascade_active = true;
                 result.cascade_triggered = true;
                 result
@@ -152,3 +220,130 @@ fn phase_name(phase: &GamePhase) -> &'static str {
         GamePhase::City => "City",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+
+    fn spawn_completed(world: &mut World, kind: BuildingTypeKind) {
+        world.spawn((
+            Building,
+            BuildingType { kind },
+            ConstructionProgress { current: 1.0, total: 1.0, assigned_agents: Vec::new(), priority_weight: 1.0 },
+        ));
+    }
+
+    fn state_in(phase: GamePhase, balance: i64) -> GameState {
+        let (_w, mut game_state) = create_world();
+        game_state.phase = phase;
+        game_state.economy.balance = balance;
+        game_state
+    }
+
+    /// Whether `world` with `balance` tokens advances out of `phase`.
+    fn advances(world: &World, phase: GamePhase, balance: i64) -> bool {
+        progression_system(world, &mut state_in(phase, balance)).phase_changed
+    }
+
+    #[test]
+    fn hut_needs_two_apps_and_a_hundred_tokens() {
+        let mut world = World::new();
+        spawn_completed(&mut world, BuildingTypeKind::Pylon);
+        spawn_completed(&mut world, BuildingTypeKind::Watchtower);
+        spawn_completed(&mut world, BuildingTypeKind::TodoApp);
+
+        // Infrastructure and Watchtowers aren't apps.
+        let mut game_state = state_in(GamePhase::Hut, 100);
+        let result = progression_system(&world, &mut game_state);
+        assert!(!result.phase_changed);
+        assert!(!result.phase_progress["app_buildings"]);
+        assert!(result.phase_progress["balance"]);
+        assert_eq!(result.phase_progress.len(), 3);
+
+        spawn_completed(&mut world, BuildingTypeKind::Calculator);
+        assert!(!advances(&world, GamePhase::Hut, 99));
+
+        let result = progression_system(&world, &mut game_state);
+        assert_eq!(game_state.phase, GamePhase::Outpost);
+        assert_eq!(result.new_phase, Some(GamePhase::Outpost));
+        assert!(matches!(result.audio_events[..], [AudioEvent::BuildComplete]));
+        assert!(result.log_entries[0].contains("Hut \u{2192} Outpost"));
+        // Progress now tracks the way out of the Outpost.
+        assert!(!result.phase_progress["app_buildings"]);
+        assert!(!result.phase_progress["balance"]);
+    }
+
+    #[test]
+    fn outpost_needs_four_apps_and_three_hundred_tokens() {
+        let mut world = World::new();
+        for kind in [BuildingTypeKind::TodoApp, BuildingTypeKind::Calculator, BuildingTypeKind::LandingPage] {
+            spawn_completed(&mut world, kind);
+        }
+        assert!(!advances(&world, GamePhase::Outpost, 300));
+
+        // Duplicates count toward the total.
+        spawn_completed(&mut world, BuildingTypeKind::TodoApp);
+        assert!(!advances(&world, GamePhase::Outpost, 299));
+        assert!(advances(&world, GamePhase::Outpost, 300));
+    }
+
+    #[test]
+    fn village_needs_every_tier_one_and_two_building() {
+        let mut world = World::new();
+        for &kind in TIER_1_2_BUILDINGS.iter().filter(|k| **k != BuildingTypeKind::Watchtower) {
+            spawn_completed(&mut world, kind);
+        }
+        // Six apps, but no Watchtower yet.
+        assert!(!advances(&world, GamePhase::Village, 1000));
+
+        spawn_completed(&mut world, BuildingTypeKind::Watchtower);
+        assert!(!advances(&world, GamePhase::Village, 999));
+        assert!(advances(&world, GamePhase::Village, 1000));
+
+        // Seven apps without the full set doesn't do it either.
+        let mut world = World::new();
+        for _ in 0..7 {
+            spawn_completed(&mut world, BuildingTypeKind::TodoApp);
+        }
+        assert!(!advances(&world, GamePhase::Village, 5000));
+    }
+
+    #[test]
+    fn network_needs_eight_apps_including_a_blockchain() {
+        let mut world = World::new();
+        for _ in 0..8 {
+            spawn_completed(&mut world, BuildingTypeKind::ChatApp);
+        }
+        assert!(!advances(&world, GamePhase::Network, 0));
+
+        let mut world = World::new();
+        for _ in 0..6 {
+            spawn_completed(&mut world, BuildingTypeKind::ChatApp);
+        }
+        spawn_completed(&mut world, BuildingTypeKind::Blockchain);
+        assert!(!advances(&world, GamePhase::Network, 0));
+
+        spawn_completed(&mut world, BuildingTypeKind::ChatApp);
+        let mut game_state = state_in(GamePhase::Network, 0);
+        game_state.tick = 42;
+        assert!(progression_system(&world, &mut game_state).phase_changed);
+        assert_eq!(game_state.phase, GamePhase::City);
+        assert_eq!(game_state.city_reached_tick, Some(42));
+        assert!(!progression_system(&world, &mut game_state).phase_changed);
+    }
+
+    #[test]
+    fn cascade_triggers_once_after_city() {
+        let world = World::new();
+        let mut game_state = state_in(GamePhase::City, 0);
+        game_state.city_reached_tick = Some(10);
+
+        game_state.tick = 10 + CASCADE_TICK_THRESHOLD;
+        assert!(progression_system(&world, &mut game_state).cascade_triggered);
+
+        game_state.cascade_active = false;
+        game_state.tick += 1;
+        assert!(!progression_system(&world, &mut game_state).cascade_triggered);
+    }
+}
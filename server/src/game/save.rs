@@ -0,0 +1,895 @@
+//! Persistent save/load for the `GameState` and every ECS entity.
+//!
+//! hecs entity handles are not stable across worlds, so every entity is
+//! written with a save-local index and any component that references another
+//! entity (`CrankState::assigned_agents`, `GuardianRogue::bound_agent_entity`,
+//! `ConstructionProgress::assigned_agents`, `RogueAI::target`) stores that
+//! index instead, as do the project manager's per-building agent
+//! assignments. On load the indices are remapped to the freshly spawned
+//! entities.
+//!
+//! File layout (v2+): a little-endian `u32` schema version followed by the
+//! msgpack-encoded `SaveBody`. Version 1 files had no binary header and
+//! carried the version inside the msgpack map; they are upgraded on load by
+//! `migrate_v1_to_v2`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use hecs::{Entity, EntityBuilder, EntityRef, World};
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::components::*;
+use crate::game::upgrades::UpgradeState;
+use crate::protocol::InventoryItem;
+
+/// Current on-disk format version. Bump when the layout changes and add a
+/// `migrate_vN_to_vN+1` step to `decode`.
+pub const SAVE_VERSION: u32 = 2;
+
+/// Slot used by the periodic autosave.
+pub const AUTOSAVE_SLOT: u8 = 0;
+
+/// Building id -> assigned agent entity bits, as kept in
+/// `ProjectManager::agent_assignments`.
+pub type AgentAssignments = HashMap<String, Vec<u64>>;
+
+/// Everything a save restores.
+pub type LoadedGame = (GameState, World, AgentAssignments);
+
+// ── Errors ──────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Encode(rmp_serde::encode::Error),
+    Decode(rmp_serde::decode::Error),
+    /// The file was written by a newer (or unknown) schema version.
+    UnsupportedVersion(u32),
+    /// The file is too short to contain a header.
+    Truncated,
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "save I/O error: {}", e),
+            SaveError::Encode(e) => write!(f, "failed to encode save: {}", e),
+            SaveError::Decode(e) => write!(f, "failed to decode save: {}", e),
+            SaveError::UnsupportedVersion(v) => {
+                write!(f, "unsupported save version {} (max {})", v, SAVE_VERSION)
+            }
+            SaveError::Truncated => write!(f, "save file is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<rmp_serde::encode::Error> for SaveError {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        SaveError::Encode(e)
+    }
+}
+
+impl From<rmp_serde::decode::Error> for SaveError {
+    fn from(e: rmp_serde::decode::Error) -> Self {
+        SaveError::Decode(e)
+    }
+}
+
+// ── On-disk layout ──────────────────────────────────────────────────
+
+/// Current (v2) payload, written after the binary version header.
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveBody {
+    game_state: SavedGameState,
+    entities: Vec<SavedEntity>,
+    /// Building id -> save indices of the agents assigned to it.
+    #[serde(default)]
+    agent_assignments: HashMap<String, Vec<u32>>,
+}
+
+/// v1 layout: the version lived inside the msgpack map, no binary header.
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveFileV1 {
+    version: u32,
+    game_state: SavedGameState,
+    entities: Vec<SavedEntity>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedCrankState {
+    heat: f32,
+    max_heat: f32,
+    heat_rate: f32,
+    cool_rate: f32,
+    tier: CrankTier,
+    is_cranking: bool,
+    #[serde(default)]
+    assigned_agents: Vec<u32>,
+    /// The single crank agent in saves from before several could be assigned.
+    #[serde(default, skip_serializing)]
+    assigned_agent: Option<u32>,
+    tokens_per_rotation: f64,
+    #[serde(default)]
+    overheated_remaining: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedGameState {
+    phase: GamePhase,
+    tick: u64,
+    crank: SavedCrankState,
+    economy: TokenEconomy,
+    cascade_active: bool,
+    city_reached_tick: Option<u64>,
+    upgrades: UpgradeState,
+    spawning_enabled: bool,
+    god_mode: bool,
+    player_dead: bool,
+    death_tick: Option<u64>,
+    inventory: Vec<InventoryItem>,
+    opened_chests: HashSet<(i32, i32)>,
+    spawned_camps: HashSet<(i32, i32)>,
+    #[serde(default)]
+    populated_chunks: HashSet<(i32, i32)>,
+    #[serde(default)]
+    mums_card_found: bool,
+    #[serde(default)]
+    power_cores_collected: u32,
+    #[serde(default)]
+    next_pack_id: u32,
+    #[serde(default)]
+    investments: Vec<Investment>,
+    #[serde(default)]
+    wave: WaveSchedule,
+    #[serde(default)]
+    destroyed_nests: HashSet<(i32, i32)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedGuardian {
+    home_x: f32,
+    home_y: f32,
+    leash_radius: f32,
+    bound_agent: u32,
+    patrol_waypoint_x: f32,
+    patrol_waypoint_y: f32,
+    patrol_pause: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedConstruction {
+    current: f32,
+    total: f32,
+    assigned_agents: Vec<u32>,
+    #[serde(default = "default_priority_weight")]
+    priority_weight: f32,
+}
+
+fn default_priority_weight() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedRogueAI {
+    behavior_state: RogueBehaviorState,
+    target: Option<u32>,
+}
+
+/// One ECS entity. Marker components are flags; everything else is optional
+/// so a single record shape covers players, agents, buildings, rogues and
+/// discoveries.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedEntity {
+    player: bool,
+    agent: bool,
+    building: bool,
+    rogue: bool,
+    dropped_item: bool,
+    bound_agent: bool,
+    #[serde(default)]
+    unguarded: bool,
+    scout: bool,
+    refusing_work: bool,
+
+    position: Option<Position>,
+    velocity: Option<Velocity>,
+    collider: Option<Collider>,
+    health: Option<Health>,
+    facing: Option<Facing>,
+    torch_range: Option<TorchRange>,
+    carry_capacity: Option<CarryCapacity>,
+    combat_power: Option<CombatPower>,
+    durability: Option<Durability>,
+    armor: Option<Armor>,
+
+    agent_stats: Option<AgentStats>,
+    agent_state: Option<AgentState>,
+    agent_morale: Option<AgentMorale>,
+    agent_fatigue: Option<AgentFatigue>,
+    agent_xp: Option<AgentXP>,
+    revive_timer: Option<ReviveTimer>,
+    error_recovery: Option<ErrorRecovery>,
+    agent_checkpoint: Option<AgentCheckpoint>,
+    wheel_post: Option<WheelPost>,
+    specialization: Option<Specialization>,
+    agent_tier: Option<AgentTier>,
+    agent_name: Option<AgentName>,
+    agent_personality: Option<AgentPersonality>,
+    voice_profile: Option<VoiceProfile>,
+    vibe_config: Option<AgentVibeConfig>,
+    wander_state: Option<WanderState>,
+    assignment: Option<Assignment>,
+    recruitable: Option<Recruitable>,
+    guardian: Option<SavedGuardian>,
+
+    building_type: Option<BuildingType>,
+    construction: Option<SavedConstruction>,
+    light_source: Option<LightSource>,
+    loot_table: Option<LootTable>,
+    loot: Option<Loot>,
+    zone_of_control: Option<ZoneOfControl>,
+    building_effects: Option<BuildingEffects>,
+
+    rogue_type: Option<RogueType>,
+    rogue_ai: Option<SavedRogueAI>,
+    rogue_visibility: Option<RogueVisibility>,
+    status_effects: Option<StatusEffects>,
+    boss_phase: Option<RogueBossPhase>,
+    token_drain: Option<TokenDrainState>,
+    mimic_disguise: Option<MimicDisguise>,
+    mimic_phase: Option<MimicPhase>,
+    pack_bonus: Option<PackBonus>,
+    wave_rogue: Option<WaveRogue>,
+    ranged_cooldown: Option<RangedCooldown>,
+
+    discovery: Option<Discovery>,
+    rogue_nest: Option<RogueNest>,
+}
+
+// ── Paths ───────────────────────────────────────────────────────────
+
+/// Directory holding save slots (`<data dir>/its-time-to-build/saves`).
+pub fn save_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("its-time-to-build")
+        .join("saves")
+}
+
+/// Save file for a numbered slot.
+pub fn slot_path(slot: u8) -> PathBuf {
+    save_dir().join(format!("slot_{}.sav", slot))
+}
+
+// ── Save ────────────────────────────────────────────────────────────
+
+fn cloned<T: hecs::Component + Clone>(entity: &EntityRef) -> Option<T> {
+    entity.get::<&T>().map(|c| (*c).clone())
+}
+
+fn snapshot_entity(entity: &EntityRef, index_of: &HashMap<Entity, u32>) -> SavedEntity {
+    SavedEntity {
+        player: entity.has::<Player>(),
+        agent: entity.has::<Agent>(),
+        building: entity.has::<Building>(),
+        rogue: entity.has::<Rogue>(),
+        dropped_item: entity.has::<DroppedItem>(),
+        bound_agent: entity.has::<BoundAgent>(),
+        unguarded: entity.has::<Unguarded>(),
+        scout: entity.has::<Scout>(),
+        refusing_work: entity.has::<RefusingWork>(),
+
+        position: cloned(entity),
+        velocity: cloned(entity),
+        collider: cloned(entity),
+        health: cloned(entity),
+        facing: cloned(entity),
+        // Never persist a flare's temporary boost.
+        torch_range: entity
+            .get::<&FlareEffect>()
+            .map(|flare| TorchRange { radius: flare.base_radius })
+            .or_else(|| cloned(entity)),
+        carry_capacity: cloned(entity),
+        combat_power: cloned(entity),
+        durability: cloned(entity),
+        armor: cloned(entity),
+
+        agent_stats: cloned(entity),
+        agent_state: cloned(entity),
+        agent_morale: cloned(entity),
+        agent_fatigue: cloned(entity),
+        agent_xp: cloned(entity),
+        revive_timer: cloned(entity),
+        error_recovery: cloned(entity),
+        agent_checkpoint: cloned(entity),
+        wheel_post: cloned(entity),
+        specialization: cloned(entity),
+        agent_tier: cloned(entity),
+        agent_name: cloned(entity),
+        agent_personality: cloned(entity),
+        voice_profile: cloned(entity),
+        vibe_config: cloned(entity),
+        wander_state: cloned(entity),
+        assignment: cloned(entity),
+        recruitable: cloned(entity),
+        guardian: entity.get::<&GuardianRogue>().and_then(|g| {
+            Some(SavedGuardian {
+                home_x: g.home_x,
+                home_y: g.home_y,
+                leash_radius: g.leash_radius,
+                bound_agent: *index_of.get(&g.bound_agent_entity)?,
+                patrol_waypoint_x: g.patrol_waypoint_x,
+                patrol_waypoint_y: g.patrol_waypoint_y,
+                patrol_pause: g.patrol_pause,
+            })
+        }),
+
+        building_type: cloned(entity),
+        construction: entity.get::<&ConstructionProgress>().map(|c| SavedConstruction {
+            current: c.current,
+            total: c.total,
+            assigned_agents: c
+                .assigned_agents
+                .iter()
+                .filter_map(|e| index_of.get(e).copied())
+                .collect(),
+            priority_weight: c.priority_weight,
+        }),
+        light_source: cloned(entity),
+        loot_table: cloned(entity),
+        loot: cloned(entity),
+        zone_of_control: cloned(entity),
+        building_effects: cloned(entity),
+
+        rogue_type: cloned(entity),
+        rogue_ai: entity.get::<&RogueAI>().map(|ai| SavedRogueAI {
+            behavior_state: ai.behavior_state.clone(),
+            target: ai.target.and_then(|t| index_of.get(&t).copied()),
+        }),
+        rogue_visibility: cloned(entity),
+        status_effects: cloned(entity),
+        boss_phase: cloned(entity),
+        token_drain: cloned(entity),
+        mimic_disguise: cloned(entity),
+        mimic_phase: cloned(entity),
+        pack_bonus: cloned(entity),
+        wave_rogue: cloned(entity),
+        ranged_cooldown: cloned(entity),
+
+        discovery: cloned(entity),
+        rogue_nest: cloned(entity),
+    }
+}
+
+/// Serializes the game state, world and the project manager's
+/// `agent_assignments` (entity bits): version header plus msgpack body.
+pub fn serialize_game(
+    game_state: &GameState,
+    world: &World,
+    agent_assignments: &AgentAssignments,
+) -> Result<Vec<u8>, SaveError> {
+    // Projectiles are transient and not worth persisting.
+    let saved: Vec<EntityRef> = world
+        .iter()
+        .filter(|e| !e.has::<Projectile>())
+        .collect();
+    let index_of: HashMap<Entity, u32> = saved
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.entity(), i as u32))
+        .collect();
+
+    let crank = &game_state.crank;
+    let body = SaveBody {
+        game_state: SavedGameState {
+            phase: game_state.phase.clone(),
+            tick: game_state.tick,
+            crank: SavedCrankState {
+                heat: crank.heat,
+                max_heat: crank.max_heat,
+                heat_rate: crank.heat_rate,
+                cool_rate: crank.cool_rate,
+                tier: crank.tier.clone(),
+                is_cranking: crank.is_cranking,
+                assigned_agents: crank.assigned_agents.iter().filter_map(|e| index_of.get(e).copied()).collect(),
+                assigned_agent: None,
+                tokens_per_rotation: crank.tokens_per_rotation,
+                overheated_remaining: crank.overheated_remaining,
+            },
+            economy: game_state.economy.clone(),
+            cascade_active: game_state.cascade_active,
+            city_reached_tick: game_state.city_reached_tick,
+            upgrades: game_state.upgrades.clone(),
+            spawning_enabled: game_state.spawning_enabled,
+            god_mode: game_state.god_mode,
+            player_dead: game_state.player_dead,
+            death_tick: game_state.death_tick,
+            inventory: game_state.inventory.clone(),
+            opened_chests: game_state.opened_chests.clone(),
+            spawned_camps: game_state.spawned_camps.clone(),
+            populated_chunks: game_state.populated_chunks.clone(),
+            mums_card_found: game_state.mums_card_found,
+            power_cores_collected: game_state.power_cores_collected,
+            next_pack_id: game_state.next_pack_id,
+            investments: game_state.investments.clone(),
+            wave: game_state.wave.clone(),
+            destroyed_nests: game_state.destroyed_nests.clone(),
+        },
+        entities: saved.iter().map(|e| snapshot_entity(e, &index_of)).collect(),
+        agent_assignments: agent_assignments
+            .iter()
+            .map(|(building_id, agents)| {
+                let indices = agents
+                    .iter()
+                    .filter_map(|&bits| Entity::from_bits(bits))
+                    .filter_map(|e| index_of.get(&e).copied())
+                    .collect();
+                (building_id.clone(), indices)
+            })
+            .collect(),
+    };
+
+    let mut bytes = SAVE_VERSION.to_le_bytes().to_vec();
+    bytes.extend(rmp_serde::to_vec_named(&body)?);
+    Ok(bytes)
+}
+
+/// Writes the game to `path`, creating parent directories as needed.
+/// The file is written to a temporary sibling first and then renamed so a
+/// crash mid-write never corrupts an existing save.
+pub fn save_game(
+    game_state: &GameState,
+    world: &World,
+    agent_assignments: &AgentAssignments,
+    path: &Path,
+) -> Result<(), SaveError> {
+    let bytes = serialize_game(game_state, world, agent_assignments)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("sav.tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+// ── Load ────────────────────────────────────────────────────────────
+
+fn remap(index: u32, spawned: &[Entity]) -> Option<Entity> {
+    spawned.get(index as usize).copied()
+}
+
+/// Upgrades a v1 save. v1 and v2 share the same game state and entity
+/// records; only the framing changed.
+fn migrate_v1_to_v2(v1: SaveFileV1) -> SaveBody {
+    SaveBody {
+        game_state: v1.game_state,
+        entities: v1.entities,
+        agent_assignments: HashMap::new(),
+    }
+}
+
+/// Reads the schema version and decodes the body, migrating older layouts
+/// up to the current one.
+fn decode(bytes: &[u8]) -> Result<SaveBody, SaveError> {
+    // v1 files start straight away with a msgpack map marker (fixmap or
+    // map16/map32); v2+ start with the little-endian version header.
+    if matches!(bytes.first(), Some(0x80..=0x8f | 0xde | 0xdf)) {
+        let v1: SaveFileV1 = rmp_serde::from_slice(bytes)?;
+        if v1.version != 1 {
+            return Err(SaveError::UnsupportedVersion(v1.version));
+        }
+        return Ok(migrate_v1_to_v2(v1));
+    }
+
+    if bytes.len() < 4 {
+        return Err(SaveError::Truncated);
+    }
+    let (header, payload) = bytes.split_at(4);
+    let version = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    match version {
+        2 => Ok(rmp_serde::from_slice(payload)?),
+        other => Err(SaveError::UnsupportedVersion(other)),
+    }
+}
+
+/// Rebuilds the game state, world and agent assignments (as entity bits in
+/// the new world) from a serialized save.
+pub fn deserialize_game(bytes: &[u8]) -> Result<LoadedGame, SaveError> {
+    let file = decode(bytes)?;
+
+    let mut world = World::new();
+
+    // Pass 1: spawn every entity with its self-contained components.
+    let mut spawned: Vec<Entity> = Vec::with_capacity(file.entities.len());
+    for saved in &file.entities {
+        let mut builder = EntityBuilder::new();
+        if saved.player { builder.add(Player); }
+        if saved.agent { builder.add(Agent); }
+        if saved.building { builder.add(Building); }
+        if saved.rogue { builder.add(Rogue); }
+        if saved.dropped_item { builder.add(DroppedItem); }
+        if saved.bound_agent { builder.add(BoundAgent); }
+        if saved.unguarded { builder.add(Unguarded); }
+        if saved.scout { builder.add(Scout); }
+        if saved.refusing_work { builder.add(RefusingWork); }
+
+        if let Some(c) = saved.position.clone() { builder.add(c); }
+        if let Some(c) = saved.velocity.clone() { builder.add(c); }
+        if let Some(c) = saved.collider.clone() { builder.add(c); }
+        if let Some(c) = saved.health.clone() { builder.add(c); }
+        if let Some(c) = saved.facing.clone() { builder.add(c); }
+        if let Some(c) = saved.torch_range.clone() { builder.add(c); }
+        if let Some(c) = saved.carry_capacity.clone() { builder.add(c); }
+        if let Some(c) = saved.combat_power.clone() { builder.add(c); }
+        if let Some(c) = saved.durability.clone() { builder.add(c); }
+        if let Some(c) = saved.armor.clone() { builder.add(c); }
+
+        if let Some(c) = saved.agent_stats.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_state.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_morale.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_fatigue.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_xp.clone() { builder.add(c); }
+        if let Some(c) = saved.revive_timer.clone() { builder.add(c); }
+        if let Some(c) = saved.error_recovery.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_checkpoint.clone() { builder.add(c); }
+        if let Some(c) = saved.wheel_post.clone() { builder.add(c); }
+        if let Some(c) = saved.specialization.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_tier.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_name.clone() { builder.add(c); }
+        if let Some(c) = saved.agent_personality.clone() { builder.add(c); }
+        if let Some(c) = saved.voice_profile.clone() { builder.add(c); }
+        if let Some(c) = saved.vibe_config.clone() { builder.add(c); }
+        if let Some(c) = saved.wander_state.clone() { builder.add(c); }
+        if let Some(c) = saved.assignment.clone() { builder.add(c); }
+        if let Some(c) = saved.recruitable.clone() { builder.add(c); }
+
+        if let Some(c) = saved.building_type.clone() { builder.add(c); }
+        if let Some(c) = saved.light_source.clone() { builder.add(c); }
+        if let Some(c) = saved.loot_table.clone() { builder.add(c); }
+        if let Some(c) = saved.loot.clone() { builder.add(c); }
+        if let Some(c) = saved.zone_of_control.clone() { builder.add(c); }
+        if let Some(c) = saved.building_effects.clone() { builder.add(c); }
+
+        if let Some(c) = saved.rogue_type.clone() { builder.add(c); }
+        if let Some(c) = saved.rogue_visibility.clone() { builder.add(c); }
+        if let Some(c) = saved.status_effects.clone() { builder.add(c); }
+        if let Some(c) = saved.boss_phase.clone() { builder.add(c); }
+        if let Some(c) = saved.token_drain.clone() { builder.add(c); }
+        if let Some(c) = saved.mimic_disguise.clone() { builder.add(c); }
+        if let Some(c) = saved.mimic_phase.clone() { builder.add(c); }
+        if let Some(c) = saved.pack_bonus.clone() { builder.add(c); }
+        if let Some(c) = saved.wave_rogue.clone() { builder.add(c); }
+        if let Some(c) = saved.ranged_cooldown.clone() { builder.add(c); }
+
+        if let Some(c) = saved.discovery.clone() { builder.add(c); }
+        if let Some(c) = saved.rogue_nest.clone() { builder.add(c); }
+
+        spawned.push(world.spawn(builder.build()));
+    }
+
+    // Pass 2: attach components that reference other entities, now that
+    // every saved index has a live entity to point at.
+    for (saved, &entity) in file.entities.iter().zip(&spawned) {
+        if let Some(g) = &saved.guardian {
+            if let Some(bound) = remap(g.bound_agent, &spawned) {
+                let _ = world.insert_one(entity, GuardianRogue {
+                    home_x: g.home_x,
+                    home_y: g.home_y,
+                    leash_radius: g.leash_radius,
+                    bound_agent_entity: bound,
+                    patrol_waypoint_x: g.patrol_waypoint_x,
+                    patrol_waypoint_y: g.patrol_waypoint_y,
+                    patrol_pause: g.patrol_pause,
+                });
+            }
+        }
+        if let Some(c) = &saved.construction {
+            let _ = world.insert_one(entity, ConstructionProgress {
+                current: c.current,
+                total: c.total,
+                assigned_agents: c
+                    .assigned_agents
+                    .iter()
+                    .filter_map(|i| remap(*i, &spawned))
+                    .collect(),
+                priority_weight: c.priority_weight,
+            });
+        }
+        if let Some(ai) = &saved.rogue_ai {
+            let _ = world.insert_one(entity, RogueAI {
+                behavior_state: ai.behavior_state.clone(),
+                target: ai.target.and_then(|i| remap(i, &spawned)),
+            });
+        }
+    }
+
+    let gs = file.game_state;
+    let game_state = GameState {
+        phase: gs.phase,
+        tick: gs.tick,
+        crank: CrankState {
+            heat: gs.crank.heat,
+            max_heat: gs.crank.max_heat,
+            heat_rate: gs.crank.heat_rate,
+            cool_rate: gs.crank.cool_rate,
+            tier: gs.crank.tier,
+            // The player has to hold the crank again after a load.
+            is_cranking: false,
+            assigned_agents: gs
+                .crank
+                .assigned_agents
+                .iter()
+                .chain(&gs.crank.assigned_agent)
+                .filter_map(|&i| remap(i, &spawned))
+                .collect(),
+            tokens_per_rotation: gs.crank.tokens_per_rotation,
+            overheated_remaining: gs.crank.overheated_remaining,
+            // Re-derived by the crank system on the next tick.
+            overheating: gs.crank.heat >= gs.crank.max_heat * crate::ecs::systems::crank::OVERHEATING_THRESHOLD,
+        },
+        economy: gs.economy,
+        cascade_active: gs.cascade_active,
+        city_reached_tick: gs.city_reached_tick,
+        upgrades: gs.upgrades,
+        spawning_enabled: gs.spawning_enabled,
+        god_mode: gs.god_mode,
+        player_dead: gs.player_dead,
+        death_tick: gs.death_tick,
+        inventory: gs.inventory,
+        opened_chests: gs.opened_chests,
+        spawned_camps: gs.spawned_camps,
+        populated_chunks: gs.populated_chunks,
+        mums_card_found: gs.mums_card_found,
+        power_cores_collected: gs.power_cores_collected,
+        next_pack_id: gs.next_pack_id,
+        investments: gs.investments,
+        wave: gs.wave,
+        destroyed_nests: gs.destroyed_nests,
+    };
+
+    let agent_assignments = file
+        .agent_assignments
+        .into_iter()
+        .map(|(building_id, indices)| {
+            let agents = indices
+                .into_iter()
+                .filter_map(|i| remap(i, &spawned))
+                .map(|e| -> u64 { e.to_bits().into() })
+                .collect();
+            (building_id, agents)
+        })
+        .collect();
+
+    Ok((game_state, world, agent_assignments))
+}
+
+/// Reads a save file from `path`.
+pub fn load_game(path: &Path) -> Result<LoadedGame, SaveError> {
+    let bytes = std::fs::read(path)?;
+    deserialize_game(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+
+    fn round_trip(game_state: &GameState, world: &World) -> (GameState, World) {
+        let bytes = serialize_game(game_state, world, &HashMap::new()).expect("serialize");
+        let (game_state, world, _assignments) = deserialize_game(&bytes).expect("deserialize");
+        (game_state, world)
+    }
+
+    fn find_agent(world: &World, name: &str) -> Option<Entity> {
+        world
+            .query::<&AgentName>()
+            .iter()
+            .find(|(_e, n)| n.name == name)
+            .map(|(e, _)| e)
+    }
+
+    #[test]
+    fn round_trip_preserves_entities_and_balance() {
+        let (world, mut game_state) = create_world();
+        game_state.economy.balance = 1234;
+        game_state.tick = 999;
+
+        let (loaded_state, loaded_world) = round_trip(&game_state, &world);
+
+        assert_eq!(loaded_state.economy.balance, 1234);
+        assert_eq!(loaded_state.tick, 999);
+        assert_eq!(loaded_world.len(), world.len());
+        assert_eq!(loaded_world.query::<&Player>().iter().count(), 1);
+        assert_eq!(loaded_world.query::<&Building>().iter().count(), 2);
+
+        let sol = find_agent(&loaded_world, "sol").expect("sol should survive the round trip");
+        let vibe = loaded_world.get::<&AgentVibeConfig>(sol).unwrap();
+        assert_eq!(vibe.vibe_agent_name, "game-apprentice");
+        assert!(loaded_world.get::<&Recruitable>(sol).is_ok());
+    }
+
+    #[test]
+    fn entity_references_are_remapped() {
+        let (mut world, mut game_state) = create_world();
+        let sol = find_agent(&world, "sol").unwrap();
+        // Despawn something first so indices and entity bits diverge.
+        let wheel = world
+            .query::<&BuildingType>()
+            .iter()
+            .next()
+            .map(|(e, _)| e)
+            .unwrap();
+        world.despawn(wheel).unwrap();
+
+        world.spawn((
+            Rogue,
+            Position { x: 0.0, y: 0.0 },
+            GuardianRogue {
+                home_x: 0.0,
+                home_y: 0.0,
+                leash_radius: 100.0,
+                bound_agent_entity: sol,
+                patrol_waypoint_x: 0.0,
+                patrol_waypoint_y: 0.0,
+                patrol_pause: 0,
+            },
+        ));
+        game_state.crank.assigned_agents = vec![sol];
+
+        let (loaded_state, loaded_world) = round_trip(&game_state, &world);
+        let new_sol = find_agent(&loaded_world, "sol").unwrap();
+
+        assert_eq!(loaded_state.crank.assigned_agents, vec![new_sol]);
+        let bound = loaded_world
+            .query::<&GuardianRogue>()
+            .iter()
+            .map(|(_e, g)| g.bound_agent_entity)
+            .next()
+            .unwrap();
+        assert_eq!(bound, new_sol);
+    }
+
+    #[test]
+    fn agent_assignments_are_remapped() {
+        let (mut world, game_state) = create_world();
+        let sol = find_agent(&world, "sol").unwrap();
+        let wheel = world
+            .query::<&BuildingType>()
+            .iter()
+            .next()
+            .map(|(e, _)| e)
+            .unwrap();
+        world.despawn(wheel).unwrap();
+        let assignments = HashMap::from([("todo_app".to_string(), vec![sol.to_bits().into(), u64::MAX])]);
+
+        let bytes = serialize_game(&game_state, &world, &assignments).unwrap();
+        let (_state, loaded_world, loaded) = deserialize_game(&bytes).unwrap();
+        let new_sol: u64 = find_agent(&loaded_world, "sol").unwrap().to_bits().into();
+
+        assert_eq!(loaded, HashMap::from([("todo_app".to_string(), vec![new_sol])]));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let (world, game_state) = create_world();
+        let mut bytes = serialize_game(&game_state, &world, &HashMap::new()).unwrap();
+        bytes[..4].copy_from_slice(&(SAVE_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            deserialize_game(&bytes),
+            Err(SaveError::UnsupportedVersion(v)) if v == SAVE_VERSION + 1
+        ));
+        assert!(matches!(deserialize_game(&[2, 0]), Err(SaveError::Truncated)));
+    }
+
+    /// A fresh world with balance 77 at tick 4242, written in the v1
+    /// layout by the first version of this module.
+    const V1_SAVE: &[u8] = include_bytes!("testdata/save_v1.sav");
+    /// The same world written as v2, before discoveries were saved.
+    const V2_BEFORE_DISCOVERIES: &[u8] = include_bytes!("testdata/save_v2_before_discoveries.sav");
+
+    #[test]
+    fn v1_saves_are_migrated() {
+        let (loaded_state, loaded_world, _assignments) = deserialize_game(V1_SAVE).unwrap();
+        assert_eq!(loaded_state.economy.balance, 77);
+        assert_eq!(loaded_state.tick, 4242);
+        assert!(loaded_state.populated_chunks.is_empty());
+        assert!(!loaded_state.mums_card_found);
+        assert_eq!(loaded_world.query::<&Player>().iter().count(), 1);
+        assert!(find_agent(&loaded_world, "sol").is_some());
+    }
+
+    #[test]
+    fn saves_from_before_later_fields_still_load() {
+        let (loaded_state, loaded_world, _assignments) = deserialize_game(V2_BEFORE_DISCOVERIES).unwrap();
+        assert_eq!(loaded_state.economy.balance, 77);
+        assert_eq!(loaded_state.tick, 4242);
+        assert!(loaded_state.populated_chunks.is_empty());
+        assert_eq!(loaded_world.query::<&Player>().iter().count(), 1);
+    }
+
+    #[test]
+    fn slots_map_to_distinct_files() {
+        assert_ne!(slot_path(0), slot_path(1));
+        assert!(slot_path(3).ends_with("slot_3.sav"));
+    }
+
+    #[test]
+    fn file_round_trip_preserves_all_game_state_fields() {
+        use crate::game::upgrades::UpgradeId;
+
+        let (mut world, mut game_state) = create_world();
+        let sol = find_agent(&world, "sol").unwrap();
+        game_state.phase = GamePhase::Village;
+        game_state.tick = 4242;
+        game_state.crank.heat = 12.5;
+        game_state.crank.tier = CrankTier::WaterWheel;
+        game_state.crank.assigned_agents = vec![sol];
+        game_state.crank.tokens_per_rotation = 0.5;
+        game_state.economy.balance = 321;
+        game_state.economy.fractional = 0.25;
+        game_state.cascade_active = true;
+        game_state.city_reached_tick = Some(4000);
+        game_state.upgrades.purchased.insert(UpgradeId::ExpandedContextWindow);
+        game_state.spawning_enabled = false;
+        game_state.god_mode = true;
+        game_state.player_dead = true;
+        game_state.death_tick = Some(4200);
+        game_state.add_inventory_item("iron", 3);
+        game_state.add_inventory_item("blueprint", 1);
+        game_state.opened_chests.insert((12, -7));
+        game_state.opened_chests.insert((-3, 40));
+        game_state.spawned_camps.insert((1, 2));
+        game_state.populated_chunks.insert((-1, 3));
+        game_state.mums_card_found = true;
+        world.spawn((Rogue, Position { x: 5.0, y: 5.0 }, Projectile {
+            dx: 1.0,
+            dy: 0.0,
+            speed: 6.0,
+            damage: 4,
+            range_remaining: 50.0,
+            owner_is_player: true,
+            is_crit: false,
+        }));
+
+        let path = std::env::temp_dir().join(format!("ittb_save_test_{}.sav", std::process::id()));
+        save_game(&game_state, &world, &HashMap::new(), &path).unwrap();
+        let (loaded, loaded_world, _assignments) = load_game(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(matches!(loaded.phase, GamePhase::Village));
+        assert_eq!(loaded.tick, 4242);
+        assert_eq!(loaded.crank.heat, 12.5);
+        assert!(matches!(loaded.crank.tier, CrankTier::WaterWheel));
+        assert_eq!(loaded.crank.assigned_agents, find_agent(&loaded_world, "sol").into_iter().collect::<Vec<_>>());
+        assert_eq!(loaded.crank.tokens_per_rotation, 0.5);
+        assert_eq!(loaded.economy.balance, 321);
+        assert_eq!(loaded.economy.fractional, 0.25);
+        assert!(loaded.cascade_active);
+        assert_eq!(loaded.city_reached_tick, Some(4000));
+        assert!(loaded.upgrades.has(UpgradeId::ExpandedContextWindow));
+        assert!(!loaded.spawning_enabled);
+        assert!(loaded.god_mode);
+        assert!(loaded.player_dead);
+        assert_eq!(loaded.death_tick, Some(4200));
+        assert!(loaded.has_inventory_item("iron", 3));
+        assert!(loaded.has_inventory_item("blueprint", 1));
+        assert_eq!(loaded.inventory.len(), 2 + crate::game::equipment::STARTER_EQUIPMENT.len());
+        assert_eq!(loaded.opened_chests, game_state.opened_chests);
+        assert_eq!(loaded.spawned_camps, game_state.spawned_camps);
+        assert_eq!(loaded.populated_chunks, game_state.populated_chunks);
+        assert!(loaded.mums_card_found);
+        // Projectiles are not persisted.
+        assert_eq!(loaded_world.len(), world.len() - 1);
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use hecs::{Entity, World};
+
+use crate::ecs::components::Position;
+
+/// Default cell edge length in pixels.
+pub const DEFAULT_CELL_SIZE: f32 = 64.0;
+
+type Cell = (i32, i32);
+
+/// Entities stored in one cell along with their inserted position.
+type Bucket = Vec<(Entity, f32, f32)>;
+
+/// Uniform spatial hash over world space for cheap proximity queries.
+///
+/// The grid is a per-tick acceleration structure: systems rebuild it from
+/// the ECS world, then ask for entities near a point instead of scanning
+/// every entity. Positions are stored alongside entities so queries can
+/// filter by exact distance without touching the world.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<Cell, Bucket>,
+    locations: HashMap<Entity, Cell>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(1.0),
+            cells: HashMap::new(),
+            locations: HashMap::new(),
+        }
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> Cell {
+        (
+            (x / self.cell_size).floor() as i32,
+            (y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Inserts an entity at (x, y). Re-inserting an entity moves it.
+    pub fn insert(&mut self, entity: Entity, x: f32, y: f32) {
+        self.remove(entity);
+        let cell = self.cell_of(x, y);
+        self.cells.entry(cell).or_default().push((entity, x, y));
+        self.locations.insert(entity, cell);
+    }
+
+    /// Removes an entity. Returns `false` if it wasn't in the grid.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        let Some(cell) = self.locations.remove(&entity) else {
+            return false;
+        };
+        if let Some(bucket) = self.cells.get_mut(&cell) {
+            bucket.retain(|(e, _, _)| *e != entity);
+            if bucket.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+        true
+    }
+
+    /// Iterates every entity within `r` pixels of (x, y), inclusive.
+    pub fn query_radius(&self, x: f32, y: f32, r: f32) -> impl Iterator<Item = Entity> + '_ {
+        let (min_cx, min_cy) = self.cell_of(x - r, y - r);
+        let (max_cx, max_cy) = self.cell_of(x + r, y + r);
+        let r_sq = r * r;
+
+        (min_cy..=max_cy)
+            .flat_map(move |cy| (min_cx..=max_cx).map(move |cx| (cx, cy)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |&&(_, ex, ey)| {
+                let dx = ex - x;
+                let dy = ey - y;
+                dx * dx + dy * dy <= r_sq
+            })
+            .map(|&(entity, _, _)| entity)
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.locations.contains_key(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.locations.clear();
+    }
+
+    /// Inserts every positioned entity that has component `T`.
+    pub fn insert_all<T: hecs::Component>(&mut self, world: &World) {
+        for (entity, (_marker, pos)) in world.query::<(&T, &Position)>().iter() {
+            self.insert(entity, pos.x, pos.y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(world: &mut World, n: usize) -> Vec<Entity> {
+        (0..n).map(|_| world.spawn(())).collect()
+    }
+
+    fn sorted(found: impl Iterator<Item = Entity>) -> Vec<Entity> {
+        let mut v: Vec<Entity> = found.collect();
+        v.sort_by_key(|e| e.to_bits());
+        v
+    }
+
+    #[test]
+    fn query_spans_cell_boundaries() {
+        let mut world = World::new();
+        let e = entities(&mut world, 4);
+        let mut grid = SpatialGrid::new(64.0);
+        grid.insert(e[0], 60.0, 60.0); // cell (0, 0)
+        grid.insert(e[1], 70.0, 60.0); // cell (1, 0)
+        grid.insert(e[2], -5.0, 60.0); // cell (-1, 0)
+        grid.insert(e[3], 200.0, 200.0); // far away
+
+        let found = sorted(grid.query_radius(64.0, 60.0, 70.0));
+        assert_eq!(found, sorted([e[0], e[1], e[2]].into_iter()));
+    }
+
+    #[test]
+    fn query_filters_by_exact_distance() {
+        let mut world = World::new();
+        let e = entities(&mut world, 2);
+        let mut grid = SpatialGrid::default();
+        grid.insert(e[0], 10.0, 0.0);
+        // Same cell, but outside the radius along the diagonal.
+        grid.insert(e[1], 9.0, 9.0);
+
+        assert_eq!(grid.query_radius(0.0, 0.0, 10.0).collect::<Vec<_>>(), vec![e[0]]);
+    }
+
+    #[test]
+    fn removed_entities_are_not_returned() {
+        let mut world = World::new();
+        let e = entities(&mut world, 2);
+        let mut grid = SpatialGrid::default();
+        grid.insert(e[0], 5.0, 5.0);
+        grid.insert(e[1], 6.0, 6.0);
+
+        assert!(grid.remove(e[0]));
+        assert!(!grid.remove(e[0]));
+        assert_eq!(grid.query_radius(5.0, 5.0, 10.0).collect::<Vec<_>>(), vec![e[1]]);
+        assert_eq!(grid.len(), 1);
+    }
+
+    #[test]
+    fn reinserting_moves_entity() {
+        let mut world = World::new();
+        let e = entities(&mut world, 1);
+        let mut grid = SpatialGrid::default();
+        grid.insert(e[0], 0.0, 0.0);
+        grid.insert(e[0], 500.0, 500.0);
+
+        assert_eq!(grid.query_radius(0.0, 0.0, 50.0).count(), 0);
+        assert_eq!(grid.query_radius(500.0, 500.0, 1.0).collect::<Vec<_>>(), vec![e[0]]);
+        assert_eq!(grid.len(), 1);
+    }
+
+    #[test]
+    fn insert_all_picks_up_marked_entities() {
+        use crate::ecs::components::Rogue;
+        let mut world = World::new();
+        let rogue = world.spawn((Rogue, Position { x: 1.0, y: 1.0 }));
+        world.spawn((Position { x: 2.0, y: 2.0 },));
+
+        let mut grid = SpatialGrid::default();
+        grid.insert_all::<Rogue>(&world);
+        assert_eq!(grid.query_radius(0.0, 0.0, 10.0).collect::<Vec<_>>(), vec![rogue]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Phrase bank for agent speech bubbles, keyed by personality trait and
+//! the event that prompted the line.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Something that happened to an agent worth remarking on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeechEvent {
+    Recruited,
+    BuildStarted,
+    SessionComplete,
+    Errored,
+    RogueNearby,
+    LowMorale,
+}
+
+/// Lines any agent may say, whatever its traits.
+fn generic_lines(event: SpeechEvent) -> &'static [&'static str] {
+    match event {
+        SpeechEvent::Recruited => &["Reporting for duty!", "Where do I start?", "Happy to help."],
+        SpeechEvent::BuildStarted => &["On my way.", "Let's build this.", "I'll get started."],
+        SpeechEvent::SessionComplete => &["Done!", "That should do it.", "Shipped."],
+        SpeechEvent::Errored => &["Something broke...", "That didn't work.", "I need a moment."],
+        SpeechEvent::RogueNearby => &["Rogue nearby!", "Something's out there.", "Watch out!"],
+        SpeechEvent::LowMorale => &["I'm running on empty.", "Is this worth it?", "So tired..."],
+    }
+}
+
+/// Extra lines for agents with `trait_name`, if that trait has any for
+/// `event`.
+fn trait_lines(trait_name: &str, event: SpeechEvent) -> &'static [&'static str] {
+    use SpeechEvent::*;
+    match (trait_name, event) {
+        ("curious", Recruited) => &["What's over that hill?"],
+        ("curious", BuildStarted) => &["Ooh, I've never built one of these."],
+        ("diligent", BuildStarted) => &["I'll have it done by sundown."],
+        ("diligent", SessionComplete) => &["Tested it twice, too."],
+        ("cautious", RogueNearby) => &["I'm keeping my distance.", "Not going near that."],
+        ("cautious", BuildStarted) => &["Let me read the plans first."],
+        ("resilient", Errored) => &["Just a scratch. Going again."],
+        ("resilient", LowMorale) => &["I've had worse days."],
+        ("chatty", Recruited) => &["Hi! I'm so glad you picked me, honestly."],
+        ("chatty", SessionComplete) => &["Want to hear how I did it? It's a long story."],
+        ("stubborn", Errored) => &["The code is wrong, not me."],
+        ("meticulous", SessionComplete) => &["Every line accounted for."],
+        ("meticulous", Errored) => &["An off-by-one. Unacceptable."],
+        ("optimistic", LowMorale) => &["Tomorrow will be better!"],
+        ("optimistic", Errored) => &["Nothing a retry can't fix!"],
+        ("sarcastic", BuildStarted) => &["Oh good, more work."],
+        ("sarcastic", RogueNearby) => &["Great. Company."],
+        ("nocturnal", Recruited) => &["I do my best work after dark."],
+        ("impatient", BuildStarted) => &["Finally, something to do."],
+        ("impatient", LowMorale) => &["How much longer?"],
+        ("loyal", Recruited) => &["I won't let you down."],
+        ("loyal", RogueNearby) => &["Stay behind me!"],
+        _ => &[],
+    }
+}
+
+/// Picks a line for `event`: from the speaker's trait lines if it has any
+/// for this event, otherwise from the generic lines. Pass a seeded rng for
+/// deterministic choices.
+pub fn pick_line(traits: &[String], event: SpeechEvent, rng: &mut impl Rng) -> &'static str {
+    let flavoured: Vec<&'static str> = traits
+        .iter()
+        .flat_map(|t| trait_lines(t, event).iter().copied())
+        .collect();
+    let pool: &[&'static str] = if flavoured.is_empty() { generic_lines(event) } else { &flavoured };
+    pool.choose(rng).copied().unwrap_or("...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn traits_flavour_lines_and_seeds_repeat() {
+        let cautious = vec!["cautious".to_string()];
+        let line = pick_line(&cautious, SpeechEvent::RogueNearby, &mut StdRng::seed_from_u64(3));
+        assert!(trait_lines("cautious", SpeechEvent::RogueNearby).contains(&line));
+        assert_eq!(line, pick_line(&cautious, SpeechEvent::RogueNearby, &mut StdRng::seed_from_u64(3)));
+
+        // No cautious line for recruitment: fall back to the generic bank.
+        let line = pick_line(&cautious, SpeechEvent::Recruited, &mut StdRng::seed_from_u64(3));
+        assert!(generic_lines(SpeechEvent::Recruited).contains(&line));
+    }
+}
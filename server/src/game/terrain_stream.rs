@@ -0,0 +1,238 @@
+//! Streams terrain chunks around the player to the client.
+//!
+//! Each tile is sent as one byte: the [`Terrain`] code in the low bits and
+//! [`TILE_BLOCKED`] set when [`collision::is_walkable`] says the tile can't
+//! be crossed. Walkability deliberately comes from `collision.rs` rather
+//! than [`Terrain::is_walkable`]: collision mirrors the client's own world
+//! generation and is what server-side movement is validated against, while
+//! the simplex `TileMap` only drives terrain speed. Sending both lets the
+//! client render exactly what the server enforces.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::game::collision;
+use crate::game::tilemap::{Chunk, Terrain, TileMap, CHUNK_SIZE, TERRAIN_SEED};
+
+/// Chunks within this many chunks of the player's (on both axes) are streamed.
+pub const STREAM_RADIUS_CHUNKS: i32 = 2;
+
+/// Chunks remembered as sent; older ones are sent again when re-entered.
+pub const SENT_CHUNK_CAPACITY: usize = 256;
+
+/// Bit set on a tile code when the tile blocks movement.
+pub const TILE_BLOCKED: u8 = 0x80;
+
+/// Wire code for `terrain`.
+pub fn terrain_code(terrain: Terrain) -> u8 {
+    match terrain {
+        Terrain::Grass => 0,
+        Terrain::Stone => 1,
+        Terrain::Water => 2,
+        Terrain::Dirt => 3,
+        Terrain::Forest => 4,
+        Terrain::Sand => 5,
+    }
+}
+
+/// The terrain a tile code carries, ignoring [`TILE_BLOCKED`].
+pub fn terrain_from_code(code: u8) -> Option<Terrain> {
+    match code & !TILE_BLOCKED {
+        0 => Some(Terrain::Grass),
+        1 => Some(Terrain::Stone),
+        2 => Some(Terrain::Water),
+        3 => Some(Terrain::Dirt),
+        4 => Some(Terrain::Forest),
+        5 => Some(Terrain::Sand),
+        _ => None,
+    }
+}
+
+/// Tile codes for `chunk`, row by row.
+pub fn chunk_tiles(chunk: &Chunk) -> Vec<u8> {
+    let (ox, oy) = (chunk.cx * CHUNK_SIZE as i32, chunk.cy * CHUNK_SIZE as i32);
+    let mut tiles = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE);
+    for (ty, row) in chunk.tiles.iter().enumerate() {
+        for (tx, terrain) in row.iter().enumerate() {
+            let blocked = !collision::is_walkable(ox + tx as i32, oy + ty as i32);
+            tiles.push(terrain_code(*terrain) | if blocked { TILE_BLOCKED } else { 0 });
+        }
+    }
+    tiles
+}
+
+/// Run-length encodes `tiles` as `(run length, tile code)` byte pairs, runs
+/// capped at 255.
+pub fn rle_encode(tiles: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut iter = tiles.iter().peekable();
+    while let Some(&code) = iter.next() {
+        let mut run: u8 = 1;
+        while run < u8::MAX && iter.peek() == Some(&&code) {
+            iter.next();
+            run += 1;
+        }
+        out.push(run);
+        out.push(code);
+    }
+    out
+}
+
+/// Inverse of [`rle_encode`].
+pub fn rle_decode(encoded: &[u8]) -> Result<Vec<u8>, String> {
+    if !encoded.len().is_multiple_of(2) {
+        return Err("run-length data has an odd number of bytes".to_string());
+    }
+    let mut tiles = Vec::new();
+    for pair in encoded.chunks_exact(2) {
+        if pair[0] == 0 {
+            return Err("run-length data has an empty run".to_string());
+        }
+        tiles.extend(std::iter::repeat_n(pair[1], pair[0] as usize));
+    }
+    Ok(tiles)
+}
+
+/// A chunk ready to send: its coordinates and run-length encoded tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkPayload {
+    pub cx: i32,
+    pub cy: i32,
+    pub tiles: Vec<u8>,
+}
+
+/// Tracks which chunks the client has and produces the ones it still needs.
+pub struct TerrainStreamer {
+    tilemap: TileMap,
+    /// Sent chunks, least recently in range first.
+    sent: VecDeque<(i32, i32)>,
+    sent_set: HashSet<(i32, i32)>,
+}
+
+impl Default for TerrainStreamer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerrainStreamer {
+    pub fn new() -> Self {
+        Self {
+            tilemap: TileMap::new(TERRAIN_SEED),
+            sent: VecDeque::new(),
+            sent_set: HashSet::new(),
+        }
+    }
+
+    /// Chunks within [`STREAM_RADIUS_CHUNKS`] of the player at `(x, y)`
+    /// (pixels), nearest rows first.
+    pub fn chunks_in_range(x: f32, y: f32) -> Vec<(i32, i32)> {
+        let (pcx, pcy) = TileMap::world_to_chunk(x, y);
+        let r = STREAM_RADIUS_CHUNKS;
+        (-r..=r)
+            .flat_map(|dy| (-r..=r).map(move |dx| (pcx + dx, pcy + dy)))
+            .collect()
+    }
+
+    /// Payloads for every chunk in range of the player that the client
+    /// hasn't been sent (or has since been evicted from the sent list).
+    pub fn update(&mut self, player_x: f32, player_y: f32) -> Vec<ChunkPayload> {
+        let mut payloads = Vec::new();
+        for pos in Self::chunks_in_range(player_x, player_y) {
+            if self.sent_set.contains(&pos) {
+                // Still in view: now the most recently used.
+                if let Some(i) = self.sent.iter().position(|&p| p == pos) {
+                    self.sent.remove(i);
+                }
+                self.sent.push_back(pos);
+                continue;
+            }
+            let chunk = self.tilemap.get_or_generate(pos.0, pos.1);
+            payloads.push(ChunkPayload { cx: pos.0, cy: pos.1, tiles: rle_encode(&chunk_tiles(chunk)) });
+            self.sent.push_back(pos);
+            self.sent_set.insert(pos);
+        }
+        while self.sent.len() > SENT_CHUNK_CAPACITY {
+            if let Some(old) = self.sent.pop_front() {
+                self.sent_set.remove(&old);
+                self.tilemap.chunks.remove(&old);
+            }
+        }
+        payloads
+    }
+
+    /// Forget what was sent, e.g. after the client reconnects, so every
+    /// chunk in range goes out again on the next [`Self::update`].
+    pub fn reset(&mut self) {
+        self.sent.clear();
+        self.sent_set.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::tilemap::TILE_SIZE;
+
+    const CHUNK_PX: f32 = CHUNK_SIZE as f32 * TILE_SIZE;
+
+    fn positions(payloads: &[ChunkPayload]) -> HashSet<(i32, i32)> {
+        payloads.iter().map(|p| (p.cx, p.cy)).collect()
+    }
+
+    #[test]
+    fn only_newly_entered_chunks_are_sent() {
+        let mut streamer = TerrainStreamer::new();
+        let side = (2 * STREAM_RADIUS_CHUNKS + 1) as usize;
+        assert_eq!(streamer.update(10.0, 10.0).len(), side * side);
+        assert!(streamer.update(20.0, 10.0).is_empty());
+
+        // One chunk east: just the new column.
+        let sent = positions(&streamer.update(10.0 + CHUNK_PX, 10.0));
+        let column: HashSet<_> = (-STREAM_RADIUS_CHUNKS..=STREAM_RADIUS_CHUNKS)
+            .map(|dy| (1 + STREAM_RADIUS_CHUNKS, dy))
+            .collect();
+        assert_eq!(sent, column);
+    }
+
+    #[test]
+    fn chunks_round_trip_through_the_encoding() {
+        let chunk = Chunk::generate(3, -2, TERRAIN_SEED);
+        let tiles = chunk_tiles(&chunk);
+        let encoded = rle_encode(&tiles);
+        assert!(encoded.len() < tiles.len());
+        assert_eq!(rle_decode(&encoded).unwrap(), tiles);
+
+        for (i, code) in tiles.iter().enumerate() {
+            let (tx, ty) = (i % CHUNK_SIZE, i / CHUNK_SIZE);
+            assert_eq!(terrain_from_code(*code), Some(chunk.tiles[ty][tx]));
+            let walkable = collision::is_walkable(3 * CHUNK_SIZE as i32 + tx as i32, -2 * CHUNK_SIZE as i32 + ty as i32);
+            assert_eq!(code & TILE_BLOCKED == 0, walkable);
+        }
+
+        // Long runs split at 255.
+        let flat = vec![7u8; 600];
+        assert_eq!(rle_encode(&flat), vec![255, 7, 255, 7, 90, 7]);
+        assert_eq!(rle_decode(&rle_encode(&flat)).unwrap(), flat);
+        assert!(rle_decode(&[3]).is_err());
+        assert!(rle_decode(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn reconnecting_or_returning_resends_chunks() {
+        let mut streamer = TerrainStreamer::new();
+        let first = streamer.update(0.0, 0.0);
+        assert!(streamer.update(0.0, 0.0).is_empty());
+
+        streamer.reset();
+        assert_eq!(streamer.update(0.0, 0.0), first);
+
+        // Wander far enough to push the starting chunks out of the sent list.
+        let mut x = 0.0;
+        while first.iter().any(|c| streamer.sent_set.contains(&(c.cx, c.cy))) {
+            x += CHUNK_PX;
+            streamer.update(x, 0.0);
+        }
+        assert!(streamer.sent.len() <= SENT_CHUNK_CAPACITY);
+        assert_eq!(streamer.update(0.0, 0.0), first);
+    }
+}
@@ -0,0 +1,2289 @@
+//! One tick of the game loop, decoupled from the network so it can run
+//! headlessly.
+//!
+//! `main.rs` owns the socket, the ticker, autosaves and the HTTP status;
+//! everything the simulation does in a tick -- input, systems, vibe
+//! sessions, grading, and building the [`GameStateUpdate`] -- lives in
+//! [`run_tick`].
+
+use std::collections::HashSet;
+
+use hecs::World;
+
+use crate::ecs::components::*;
+use crate::ecs::systems::{agent_combat, agent_tick, agent_wander, building, camp_spawner, combat, crank, discovery, dodge, loot, plague, economy, fatigue, morale, nest, personality, placement, power, projectile, revival, spawn, speech, status_effect, synergy, token_drain, xp};
+use crate::game::{agents, chests, collision, crafting, equipment, fog, inventory, progression, save};
+use crate::game::debug::DebugGuard;
+use crate::game::upgrades::UpgradeId;
+use crate::game::spatial::SpatialGrid;
+use crate::game::speech::SpeechEvent;
+use crate::ai::rogue_ai;
+use crate::config::ServerConfig;
+use crate::network::server::GameServer;
+use crate::network::{snapshot, validation};
+use crate::network::validation::PLAYER_SPEED;
+use crate::project;
+use crate::protocol::*;
+use crate::vibe::manager::VibeManager;
+use crate::vibe::{self, session};
+use crate::grading;
+
+/// A finished grade: building id, the tick grading started, and the stars
+/// and feedback (or why it failed).
+type GradeResult = (String, u64, Result<(u8, String), String>);
+
+/// Sender half of the channel that carries finished grades back to the tick loop.
+type GradeResultTx = tokio::sync::mpsc::UnboundedSender<GradeResult>;
+
+/// How a grading request was answered.
+#[derive(Debug)]
+enum GradeStart {
+    /// A grading task was spawned; its result arrives on the channel.
+    Started,
+    /// The building was graded within the cooldown; this grade stands.
+    Cached { stars: u8, reasoning: String },
+}
+
+/// Reads a building's project sources and spawns an async grading task
+/// whose result arrives later on `grade_tx`, unless the building is still
+/// on cooldown, in which case its current grade is returned instead.
+fn start_grading(
+    building_id: &str,
+    project_manager: &project::ProjectManager,
+    grading_service: &mut grading::GradingService,
+    tick: u64,
+    grade_tx: &GradeResultTx,
+) -> Result<GradeStart, String> {
+    if !grading_service.has_api_key() {
+        return Err("No Anthropic API key set".to_string());
+    }
+    if grading_service.grades.get(building_id).is_some_and(|g| g.grading) {
+        return Err(format!("{} already being graded", building_id));
+    }
+    if let Some(grade) = grading_service.cached_grade(building_id, tick) {
+        return Ok(GradeStart::Cached { stars: grade.stars, reasoning: grade.reasoning.clone() });
+    }
+    let base = project_manager.base_dir.as_ref();
+    let building = project_manager.manifest.get_building(building_id);
+    let (Some(base), Some(building)) = (base, building) else {
+        return Err(format!("building {} not found or no base dir", building_id));
+    };
+
+    let project_dir = base.join(&building.directory_name);
+    let sources = grading::read_project_sources(&project_dir)
+        .map_err(|e| format!("failed to read sources: {}", e))?;
+    if sources.is_empty() {
+        return Err(format!("no source files found for {}", building_id));
+    }
+
+    grading_service.mark_grading(building_id, tick);
+    let api_key = grading_service.api_key.as_ref().unwrap().clone();
+    let bid = building_id.to_string();
+    let bname = building.name.clone();
+    let bdesc = building.description.clone();
+    let grade_tx = grade_tx.clone();
+    tokio::spawn(async move {
+        let result = grading::grade_with_claude(&api_key, &bid, &bname, &bdesc, &sources).await;
+        let _ = grade_tx.send((bid, tick, result));
+    });
+    Ok(GradeStart::Started)
+}
+
+/// Log line for a grade answered from the cache.
+fn cached_grade_line(building_id: &str, stars: u8, tick_rate: u64) -> String {
+    format!(
+        "{} was graded recently, keeping {} stars (cooldown {}s)",
+        building_id,
+        stars,
+        grading::GRADE_COOLDOWN_TICKS / tick_rate
+    )
+}
+
+/// Send the transaction log every 5 seconds (or when the client asks).
+const TRANSACTION_LOG_INTERVAL_SECS: u64 = 5;
+
+/// Check the buildings manifest for edits every 30 seconds.
+const MANIFEST_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Seconds a dead player waits before respawning.
+const RESPAWN_DELAY_SECS: u64 = 10;
+
+/// What a tick needs from the connection: queued input in, messages out.
+/// Implemented by [`GameServer`]; tests script it instead.
+pub trait TickIo {
+    /// Next queued player input, if any.
+    fn next_input(&mut self) -> Option<PlayerInput>;
+    /// Tick of the last input accepted with [`TickIo::accept_input`].
+    fn last_input_tick(&self) -> Tick;
+    /// Records that an input sent at `tick` was applied.
+    fn accept_input(&mut self, tick: Tick);
+    /// Sends a message to the client (and spectator).
+    fn send_message(&mut self, msg: &ServerMessage);
+    fn connected_spectators(&self) -> u8;
+    /// Drops the state last sent so the next update goes out in full.
+    fn resync(&mut self);
+}
+
+impl TickIo for GameServer {
+    fn next_input(&mut self) -> Option<PlayerInput> {
+        GameServer::next_input(self)
+    }
+
+    fn last_input_tick(&self) -> Tick {
+        GameServer::last_input_tick(self)
+    }
+
+    fn accept_input(&mut self, tick: Tick) {
+        GameServer::accept_input(self, tick)
+    }
+
+    fn send_message(&mut self, msg: &ServerMessage) {
+        GameServer::send_message(self, msg)
+    }
+
+    fn connected_spectators(&self) -> u8 {
+        GameServer::connected_spectators(self)
+    }
+
+    fn resync(&mut self) {
+        GameServer::resync(self)
+    }
+}
+
+/// Everything besides the world and game state that carries over from one
+/// tick to the next.
+pub struct TickManagers {
+    pub project_manager: project::ProjectManager,
+    pub vibe_manager: VibeManager,
+    pub grading_service: grading::GradingService,
+    pub debug_guard: DebugGuard,
+    pub placement_rules: placement::PlacementRules,
+    pub fog_of_war: fog::FogOfWar,
+    /// Rebuilt every tick; kept here to reuse allocations.
+    pub agent_grid: SpatialGrid,
+    pub rogue_grid: SpatialGrid,
+    pub power_grid: power::PowerGrid,
+    /// Last tick's synergies; the economy runs before they are refreshed.
+    pub synergies: Vec<synergy::SynergyBonus>,
+    /// Whether the player is holding the crank; set and cleared by input.
+    pub player_cranking: bool,
+    /// Channel for receiving grade results from async tasks.
+    pub grade_result_tx: GradeResultTx,
+    pub grade_result_rx: tokio::sync::mpsc::UnboundedReceiver<GradeResult>,
+    /// Buildings whose in-flight grade was started automatically when a
+    /// vibe session finished (reported as BuildingGraded rather than
+    /// GradeResult).
+    pub auto_grading: HashSet<String>,
+}
+
+impl TickManagers {
+    /// Managers for a fresh server run configured by `config`.
+    pub fn new(config: &ServerConfig, fog_of_war: fog::FogOfWar) -> Self {
+        let mut project_manager = project::ProjectManager::new(&config.manifest_path);
+        project_manager.port_range = config.dev_port_range;
+        let (grade_result_tx, grade_result_rx) = tokio::sync::mpsc::unbounded_channel();
+        Self {
+            project_manager,
+            vibe_manager: VibeManager::new(),
+            grading_service: grading::GradingService::new(),
+            debug_guard: DebugGuard::new(config.debug_allowed),
+            placement_rules: placement::PlacementRules { base_radius: config.build_radius },
+            fog_of_war,
+            agent_grid: SpatialGrid::default(),
+            rogue_grid: SpatialGrid::default(),
+            power_grid: power::PowerGrid::new(),
+            synergies: Vec::new(),
+            player_cranking: false,
+            grade_result_tx,
+            grade_result_rx,
+            auto_grading: HashSet::new(),
+        }
+    }
+}
+
+/// Advances the game by one tick: applies queued input from `server`, runs
+/// every system in order, and returns the state update to send. Messages
+/// that go out mid-tick (vibe output, placement answers, ...) are sent
+/// through `server` as they happen.
+pub async fn run_tick(
+    world: &mut World,
+    game_state: &mut GameState,
+    managers: &mut TickManagers,
+    server: &mut impl TickIo,
+    config: &ServerConfig,
+) -> GameStateUpdate {
+    let TickManagers {
+        project_manager,
+        vibe_manager,
+        grading_service,
+        debug_guard,
+        placement_rules,
+        fog_of_war,
+        agent_grid,
+        rogue_grid,
+        power_grid,
+        synergies,
+        player_cranking,
+        grade_result_tx,
+        grade_result_rx,
+        auto_grading,
+    } = managers;
+
+    game_state.tick += 1;
+
+    // Reset per-tick flags
+    let mut player_attacking = false;
+
+    // Decrement attack cooldown each tick
+    for (_id, combat) in world.query_mut::<hecs::With<&mut CombatPower, &Player>>() {
+        if combat.cooldown_remaining > 0 {
+            combat.cooldown_remaining -= 1;
+        }
+    }
+
+    // Debug actions may generate log entries and remove entities
+    let mut debug_log_entries: Vec<String> = Vec::new();
+    let mut input_log_entries: Vec<String> = Vec::new();
+    let mut relay_log_entries: Vec<String> = Vec::new();
+    let mut building_log_entries: Vec<String> = Vec::new();
+    let mut action_audio_events: Vec<AudioEvent> = Vec::new();
+    let mut agent_lines: Vec<speech::AgentLine> = Vec::new();
+    let mut exploration_log_entries: Vec<String> = Vec::new();
+    let mut transaction_log_requested = false;
+    let mut debug_entities_removed: Vec<EntityId> = Vec::new();
+
+    // ── Background project scaffolding ──────────────────────────────
+    let init_progress = project_manager.drain_init_progress();
+    for progress in &init_progress {
+        match &progress.result {
+            Ok(msg) => debug_log_entries.push(format!("[project] {}", msg)),
+            Err(e) => debug_log_entries.push(format!("[project] {}: ERROR - {}", progress.building_id, e)),
+        }
+        server.send_message(&ServerMessage::ProjectInitProgress {
+            building_id: progress.building_id.clone(),
+            status: progress.status(),
+        });
+    }
+    if !init_progress.is_empty() && !project_manager.initializing() {
+        debug_log_entries.push("[project] initialization complete".to_string());
+    }
+
+    // ── Pick up manifest edits ──────────────────────────────────────
+    if game_state.tick.is_multiple_of(MANIFEST_CHECK_INTERVAL_SECS * config.tick_rate) {
+        match project_manager.reload_manifest_if_changed(&config.manifest_path) {
+            Some(Ok(reload)) => debug_log_entries.extend(reload.log_lines()),
+            Some(Err(e)) => debug_log_entries.push(format!("[manifest] reload failed: {}", e)),
+            None => {}
+        }
+    }
+    let mut chest_rewards: Vec<ChestReward> = Vec::new();
+
+    // ── 1. Process player input (movement + actions) ─────────────
+    debug_guard.start_tick();
+    // Where the player stood when the tick began; the client sends an input
+    // per frame, so movement is capped across all of this tick's inputs.
+    let tick_start_pos = world
+        .query_mut::<hecs::With<&Position, &Player>>()
+        .into_iter()
+        .next()
+        .map(|(_id, pos)| (pos.x, pos.y));
+    while let Some(input) = server.next_input() {
+        // Skip all input processing while dead
+        if game_state.player_dead {
+            continue;
+        }
+
+        if !validation::validate_input(&input) {
+            input_log_entries.push(format!(
+                "[input] rejected input from tick {} (movement {:.2}, {:.2})",
+                input.tick, input.movement.x, input.movement.y
+            ));
+            continue;
+        }
+        let mut input = input;
+        if validation::is_repeat_tick(&input, server.last_input_tick()) {
+            // Movement for this tick was already applied; only the action is new.
+            input.movement = Vec2::default();
+        } else {
+            server.accept_input(input.tick);
+        }
+
+        // Movement with collision
+        let mx = input.movement.x;
+        let my = input.movement.y;
+
+        let len = (mx * mx + my * my).sqrt();
+        if len > 0.0 {
+            let norm_x = mx / len;
+            let norm_y = my / len;
+
+            for (_id, (pos, facing, armor, effects)) in world.query_mut::<hecs::With<(&mut Position, &mut Facing, &Armor, Option<&StatusEffects>), &Player>>() {
+                let slow = effects.map_or(1.0, |e| e.speed_factor());
+                let mut effective_speed = PLAYER_SPEED * (1.0 - armor.speed_penalty) * slow;
+                effective_speed *= collision::terrain_movement_modifier(
+                    collision::pixel_to_tile(pos.x),
+                    collision::pixel_to_tile(pos.y),
+                );
+                // Update facing direction
+                facing.dx = norm_x;
+                facing.dy = norm_y;
+
+                let dx = norm_x * effective_speed;
+                let dy = norm_y * effective_speed;
+
+                let (mut x, mut y) = (pos.x, pos.y);
+
+                // Check X axis independently (wall-sliding)
+                let future_tx = collision::pixel_to_tile(x + dx);
+                let cur_ty = collision::pixel_to_tile(y);
+                if collision::is_walkable(future_tx, cur_ty) {
+                    x += dx;
+                }
+
+                // Check Y axis independently (wall-sliding)
+                let cur_tx = collision::pixel_to_tile(x);
+                let future_ty = collision::pixel_to_tile(y + dy);
+                if collision::is_walkable(cur_tx, future_ty) {
+                    y += dy;
+                }
+
+                if tick_start_pos.is_none_or(|start| validation::within_tick_delta(start, (x, y))) {
+                    pos.x = x;
+                    pos.y = y;
+                }
+            }
+        }
+
+        // Actions
+        let action = match input.action.as_ref().map(|a| debug_guard.check(a, game_state.economy.balance)) {
+            Some(Ok(action)) => Some(action),
+            Some(Err(reason)) => {
+                debug_log_entries.push(format!("[debug] rejected: {}", reason));
+                None
+            }
+            None => None,
+        };
+        if let Some(action) = &action {
+            match action {
+                PlayerAction::Attack => {
+                    player_attacking = true;
+                }
+                PlayerAction::Dodge { direction } => {
+                    // Ignored while the previous dodge cools down.
+                    dodge::start_dodge(world, direction.x, direction.y);
+                }
+                PlayerAction::EquipWeapon { weapon_id } => {
+                    match equipment::equip_weapon(world, game_state, weapon_id) {
+                        Ok(entry) => debug_log_entries.push(entry),
+                        Err(e) => debug_log_entries.push(format!("Equip failed: {}", e)),
+                    }
+                }
+                PlayerAction::EquipArmor { armor_id } => {
+                    match equipment::equip_armor(world, game_state, armor_id) {
+                        Ok(entry) => debug_log_entries.push(entry),
+                        Err(e) => debug_log_entries.push(format!("Equip failed: {}", e)),
+                    }
+                }
+                PlayerAction::CrankStart => {
+                    *player_cranking = true;
+                }
+                PlayerAction::CrankStop => {
+                    *player_cranking = false;
+                }
+
+                // ── Home base actions ──────────────────────────────
+                PlayerAction::RecruitAgent { entity_id } => {
+                    if let Some(target) = hecs::Entity::from_bits(*entity_id) {
+                        match agents::hire_recruitable(world, &mut game_state.economy, target, game_state.tick) {
+                            Ok(result) => {
+                                debug_log_entries.extend(result.log_entries);
+                                action_audio_events.extend(result.audio_events);
+                                agent_lines.extend(speech::speak(world, target, SpeechEvent::Recruited, game_state.tick, &mut rand::thread_rng()));
+                            }
+                            Err(e) => debug_log_entries.push(format!("Recruitment failed: {}", e)),
+                        }
+                    }
+                }
+                PlayerAction::ReviveAgent { entity_id } => {
+                    let target = hecs::Entity::from_bits(*entity_id);
+                    if let Some(target) = target {
+                        match agents::revive_agent(world, target, &mut game_state.economy, game_state.tick) {
+                            Ok(()) => {
+                                if let Ok(name) = world.get::<&AgentName>(target) {
+                                    debug_log_entries.push(format!("{} revived!", name.name));
+                                }
+                            }
+                            Err(e) => {
+                                debug_log_entries.push(format!("Revival failed: {}", e));
+                            }
+                        }
+                    }
+                }
+                PlayerAction::UpgradeWheel => {
+                    let (next_tier, cost) = match game_state.crank.tier {
+                        CrankTier::HandCrank => (Some(CrankTier::GearAssembly), 25),
+                        CrankTier::GearAssembly => (Some(CrankTier::WaterWheel), 75),
+                        CrankTier::WaterWheel => (Some(CrankTier::RunicEngine), 200),
+                        CrankTier::RunicEngine => (None, 0),
+                    };
+                    if let Some(tier) = next_tier {
+                        if game_state.economy.balance >= cost {
+                            economy::record_transaction(&mut game_state.economy, -cost, "wheel upgrade", game_state.tick);
+                            game_state.crank.tier = tier;
+                            debug_log_entries.push(format!("Wheel upgraded to {:?}", game_state.crank.tier));
+                        }
+                    }
+                }
+                PlayerAction::AssignAgentToWheel { agent_id } => {
+                    if let Some(entity) = hecs::Entity::from_bits(*agent_id) {
+                        if let Err(e) = crank::assign_agent(world, game_state, entity) {
+                            debug_log_entries.push(format!("[wheel] {}", e));
+                        }
+                    }
+                }
+                PlayerAction::UnassignAgentFromWheel { agent_id } => {
+                    if let Some(entity) = hecs::Entity::from_bits(*agent_id) {
+                        if let Err(e) = crank::unassign_agent(world, game_state, entity) {
+                            debug_log_entries.push(format!("[wheel] {}", e));
+                        }
+                    }
+                }
+                PlayerAction::EmergencyVent => match crank::emergency_vent(game_state) {
+                    Ok(text) => debug_log_entries.push(text),
+                    Err(e) => debug_log_entries.push(format!("[wheel] vent failed: {}", e)),
+                },
+
+                // ── Debug actions ──────────────────────────────────
+                PlayerAction::DebugSetTokens { amount } => {
+                    let delta = *amount - game_state.economy.balance;
+                    economy::record_transaction(&mut game_state.economy, delta, "debug", game_state.tick);
+                    debug_log_entries.push(format!("[debug] tokens set to {}", amount));
+                }
+                PlayerAction::DebugAddTokens { amount } => {
+                    economy::record_transaction(&mut game_state.economy, *amount, "debug", game_state.tick);
+                    debug_log_entries.push(format!("[debug] added {} tokens", amount));
+                }
+                PlayerAction::DebugToggleSpawning => {
+                    game_state.spawning_enabled = !game_state.spawning_enabled;
+                    let status = if game_state.spawning_enabled { "ON" } else { "OFF" };
+                    debug_log_entries.push(format!("[debug] spawning {}", status));
+                }
+                PlayerAction::DebugTriggerCheatInput => {
+                    let cheat = PlayerInput {
+                        tick: input.tick + 1,
+                        movement: Vec2 { x: 2.0, y: 0.0 },
+                        action: None,
+                        target: None,
+                    };
+                    if validation::validate_input(&cheat) {
+                        debug_log_entries.push("[debug] cheat input was accepted".to_string());
+                    } else {
+                        input_log_entries.push(format!(
+                            "[input] rejected input from tick {} (movement {:.2}, {:.2})",
+                            cheat.tick, cheat.movement.x, cheat.movement.y
+                        ));
+                    }
+                }
+                PlayerAction::DebugClearFog => {
+                    let count = fog_of_war.revealed.len();
+                    fog_of_war.revealed.clear();
+                    let _ = std::fs::remove_file(fog::FOG_SAVE_PATH);
+                    debug_log_entries.push(format!("[debug] cleared {} revealed chunks", count));
+                }
+                PlayerAction::DebugClearRogues => {
+                    let rogue_entities: Vec<hecs::Entity> = world
+                        .query::<&Rogue>()
+                        .iter()
+                        .map(|(entity, _)| entity)
+                        .collect();
+                    let count = rogue_entities.len();
+                    for entity in rogue_entities {
+                        debug_entities_removed.push(entity.to_bits().into());
+                        let _ = world.despawn(entity);
+                    }
+                    debug_log_entries.push(format!("[debug] cleared {} rogues", count));
+                }
+                PlayerAction::DebugSetPhase { phase } => {
+                    game_state.phase = phase.clone();
+                    debug_log_entries.push(format!("[debug] phase set to {:?}", phase));
+                }
+                PlayerAction::DebugSetCrankTier { tier } => {
+                    game_state.crank.tier = tier.clone();
+                    debug_log_entries.push(format!("[debug] crank tier set to {:?}", tier));
+                }
+                PlayerAction::DebugToggleGodMode => {
+                    game_state.god_mode = !game_state.god_mode;
+                    let status = if game_state.god_mode { "ON" } else { "OFF" };
+                    debug_log_entries.push(format!("[debug] god mode {}", status));
+                }
+                PlayerAction::DebugSpawnRogue { rogue_type } => {
+                    // Spawn near the player with a small offset
+                    let mut px = 400.0_f32;
+                    let mut py = 300.0_f32;
+                    for (_id, pos) in world.query_mut::<hecs::With<&Position, &Player>>() {
+                        px = pos.x;
+                        py = pos.y;
+                    }
+                    spawn::spawn_rogue(world, px + 50.0, py + 50.0, *rogue_type);
+                    debug_log_entries.push(format!("[debug] spawned {:?}", rogue_type));
+                }
+                PlayerAction::DebugSpawnBoss => {
+                    let mut px = 400.0_f32;
+                    let mut py = 300.0_f32;
+                    for (_id, pos) in world.query_mut::<hecs::With<&Position, &Player>>() {
+                        px = pos.x;
+                        py = pos.y;
+                    }
+                    let boss = spawn::spawn_rogue(world, px + 80.0, py + 80.0, RogueTypeKind::Architect);
+                    let _ = world.insert_one(boss, RogueBossPhase::architect());
+                    debug_log_entries.push("[debug] spawned Architect boss".to_string());
+                }
+                PlayerAction::DebugSetAllPriorities { priority } => {
+                    let buildings: Vec<hecs::Entity> = world
+                        .query::<&ConstructionProgress>()
+                        .with::<&Building>()
+                        .iter()
+                        .map(|(e, _)| e)
+                        .collect();
+                    let mut applied = None;
+                    for target in buildings {
+                        applied = building::set_build_priority(world, target, *priority).ok();
+                    }
+                    match applied {
+                        Some(p) => debug_log_entries.push(format!("[debug] all building priorities set to {}", p)),
+                        None => debug_log_entries.push("[debug] no building priorities changed".to_string()),
+                    }
+                }
+                PlayerAction::DebugHealPlayer => {
+                    for (_id, health) in world.query_mut::<hecs::With<&mut Health, &Player>>() {
+                        health.current = health.max;
+                    }
+                    debug_log_entries.push("[debug] player healed to max".to_string());
+                }
+                PlayerAction::DebugSpawnAgent { tier } => {
+                    // Spawn near the player with a small offset
+                    let mut px = 400.0_f32;
+                    let mut py = 300.0_f32;
+                    for (_id, pos) in world.query_mut::<hecs::With<&Position, &Player>>() {
+                        px = pos.x;
+                        py = pos.y;
+                    }
+                    match agents::recruit_agent(world, *tier, px + 30.0, py + 30.0, &mut game_state.economy, game_state.tick, vibe_manager.backend()) {
+                        Ok(agent) => {
+                            agent_lines.extend(speech::speak(world, agent, SpeechEvent::Recruited, game_state.tick, &mut rand::thread_rng()));
+                            debug_log_entries.push(format!("[debug] spawned {:?} agent", tier));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!("[debug] agent spawn failed: {}", e));
+                        }
+                    }
+                }
+                PlayerAction::DebugGrantAgentXP { agent_id, amount } => {
+                    let granted = hecs::Entity::from_bits(*agent_id)
+                        .and_then(|agent| world.get::<&mut AgentXP>(agent).ok())
+                        .map(|mut xp| xp.xp = xp.xp.saturating_add(*amount))
+                        .is_some();
+                    if granted {
+                        debug_log_entries.push(format!("[debug] granted {} XP to agent {}", amount, agent_id));
+                    } else {
+                        debug_log_entries.push(format!("[debug] agent {} not found", agent_id));
+                    }
+                }
+                PlayerAction::DebugClearAgents => {
+                    let agent_entities: Vec<hecs::Entity> = world
+                        .query::<&Agent>()
+                        .iter()
+                        .map(|(entity, _)| entity)
+                        .collect();
+                    let count = agent_entities.len();
+                    for entity in agent_entities {
+                        debug_entities_removed.push(entity.to_bits().into());
+                        let _ = world.despawn(entity);
+                    }
+                    debug_log_entries.push(format!("[debug] cleared {} agents", count));
+                }
+
+                // ── Save / load actions ────────────────────────────
+                PlayerAction::SaveGame { slot } => {
+                    match save::save_game(game_state, world, &project_manager.agent_assignments, &save::slot_path(*slot)) {
+                        Ok(()) => debug_log_entries.push(format!("[save] game saved to slot {}", slot)),
+                        Err(e) => debug_log_entries.push(format!("[save] save failed: {}", e)),
+                    }
+                }
+                PlayerAction::LoadGame { slot } => {
+                    match save::load_game(&save::slot_path(*slot)) {
+                        Ok((loaded_state, loaded_world, loaded_assignments)) => {
+                            // Tell the client to drop everything from the old world;
+                            // the delta encoder re-sends ids the new world reuses
+                            // in full instead of removing them.
+                            debug_entities_removed.extend(
+                                world.iter().map(|e| -> EntityId { e.entity().to_bits().into() }),
+                            );
+                            vibe_manager.kill_all();
+                            *world = loaded_world;
+                            *game_state = loaded_state;
+                            project_manager.agent_assignments = loaded_assignments;
+                            *player_cranking = false;
+                            server.resync();
+                            debug_log_entries.push(format!("[save] loaded slot {}", slot));
+                        }
+                        Err(e) => debug_log_entries.push(format!("[save] load failed: {}", e)),
+                    }
+                }
+
+                // ── Project management actions ──────────────────────
+                PlayerAction::SetProjectDirectory { path } => {
+                    match project_manager.set_base_dir(path.clone()) {
+                        Ok(()) => {
+                            debug_log_entries.push(format!("[project] base dir set to {}", path));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!("[project] set dir failed: {}", e));
+                        }
+                    }
+                }
+                PlayerAction::InitializeProjects => {
+                    match project_manager.initialize_projects() {
+                        Ok(queued) => {
+                            debug_log_entries.push(format!("[project] scaffolding {} projects", queued));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!("[project] init failed: {}", e));
+                        }
+                    }
+                }
+                PlayerAction::ResetProjects => {
+                    match project_manager.reset_projects().await {
+                        Ok(queued) => {
+                            debug_log_entries.push(format!("[project] resetting {} projects", queued));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!("[project] reset failed: {}", e));
+                        }
+                    }
+                }
+                PlayerAction::ReloadManifest => {
+                    match project_manager.reload_manifest(&config.manifest_path) {
+                        Ok(reload) => debug_log_entries.extend(reload.log_lines()),
+                        Err(e) => debug_log_entries.push(format!("[manifest] reload failed: {}", e)),
+                    }
+                }
+                PlayerAction::StartDevServer { building_id } => {
+                    match project_manager.start_dev_server(building_id).await {
+                        Ok(port) => {
+                            debug_log_entries.push(format!(
+                                "[project] dev server for {} started on port {}",
+                                building_id, port
+                            ));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!(
+                                "[project] start dev server {} failed: {}",
+                                building_id, e
+                            ));
+                        }
+                    }
+                }
+                PlayerAction::StopDevServer { building_id } => {
+                    match project_manager.stop_dev_server(building_id).await {
+                        Ok(()) => {
+                            debug_log_entries.push(format!(
+                                "[project] dev server for {} stopped",
+                                building_id
+                            ));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!(
+                                "[project] stop dev server {} failed: {}",
+                                building_id, e
+                            ));
+                        }
+                    }
+                }
+                PlayerAction::AssignAgentToProject { agent_id, building_id } => {
+                    // Convert agent_id (u64) to hecs::Entity
+                    let Some(agent_entity) = hecs::Entity::from_bits(*agent_id) else {
+                        debug_log_entries.push(format!(
+                            "[project] invalid agent entity id {}",
+                            agent_id
+                        ));
+                        continue;
+                    };
+
+                    // Validate agent exists and is Idle
+                    let agent_ok = world
+                        .get::<&AgentState>(agent_entity)
+                        .map(|s| s.state == AgentStateKind::Idle)
+                        .unwrap_or(false);
+
+                    if !agent_ok {
+                        debug_log_entries.push(format!(
+                            "[project] agent {} not idle or not found",
+                            agent_id
+                        ));
+                    } else if !project_manager.assign_agent(building_id, *agent_id) {
+                        debug_log_entries.push(format!(
+                            "[project] cannot assign agent {} to {} (full or duplicate)",
+                            agent_id, building_id
+                        ));
+                    } else {
+                        // Find the building entity position by matching building_id
+                        let mut building_pos: Option<(f32, f32)> = None;
+                        for (_e, (pos, bt, progress)) in world.query_mut::<hecs::With<(&Position, &BuildingType, Option<&mut ConstructionProgress>), &Building>>() {
+                            let type_name = format!("{:?}", bt.kind);
+                            if let Some(bid) = project::ProjectManager::building_type_to_id(&type_name) {
+                                if bid == *building_id {
+                                    building_pos = Some((pos.x, pos.y));
+                                    if let Some(progress) = progress {
+                                        if !progress.assigned_agents.contains(&agent_entity) {
+                                            progress.assigned_agents.push(agent_entity);
+                                        }
+                                    }
+                                    break;
+                                }
+                            }
+                        }
+
+                        // Set agent to Walking state (will walk to building, then transition)
+                        let _ = agents::assign_task(world, agent_entity, TaskAssignment::Build);
+                        agent_lines.extend(speech::speak(world, agent_entity, SpeechEvent::BuildStarted, game_state.tick, &mut rand::thread_rng()));
+
+                        // Set walk target to building position
+                        if let Some((bx, by)) = building_pos {
+                            if let Ok(mut wander) = world.get::<&mut WanderState>(agent_entity) {
+                                wander.walk_target = Some((bx, by));
+                                wander.walk_ticks = 0;
+                                wander.waypoint_x = bx;
+                                wander.waypoint_y = by;
+                                wander.pause_remaining = 0;
+                            }
+                        }
+
+                        debug_log_entries.push(format!(
+                            "[project] agent {} assigned to {}",
+                            agent_id, building_id
+                        ));
+                    }
+                }
+                PlayerAction::UnassignAgentFromProject { agent_id, building_id } => {
+                    project_manager.unassign_agent(building_id, *agent_id);
+                    vibe_manager.kill_session(*agent_id);
+                    vibe_manager.reset_retry(*agent_id);
+
+                    // Reset agent to Idle state
+                    if let Some(agent_entity) = hecs::Entity::from_bits(*agent_id) {
+                        for (_e, progress) in world.query_mut::<&mut ConstructionProgress>() {
+                            progress.assigned_agents.retain(|&e| e != agent_entity);
+                        }
+                        let _ = agents::assign_task(world, agent_entity, TaskAssignment::Idle);
+
+                        // Reset wander radius to default and clear walk target
+                        let radius = agents::wander_radius_for(world.get::<&AgentPersonality>(agent_entity).ok().as_deref());
+                        if let Ok(mut wander) = world.get::<&mut WanderState>(agent_entity) {
+                            wander.wander_radius = radius;
+                            wander.walk_target = None;
+                        }
+                    }
+
+                    debug_log_entries.push(format!(
+                        "[project] agent {} unassigned from {}",
+                        agent_id, building_id
+                    ));
+                }
+                PlayerAction::DebugUnlockAllBuildings => {
+                    project_manager.unlock_all();
+                    debug_log_entries.push("[debug] all buildings unlocked".to_string());
+                }
+                PlayerAction::DebugLockAllBuildings => {
+                    project_manager.lock_all_non_default();
+                    debug_log_entries.push("[debug] non-default buildings locked".to_string());
+                }
+                PlayerAction::UnlockBuilding { building_id } => {
+                    project_manager.unlock_building(building_id);
+                    debug_log_entries.push(format!("[project] building {} unlocked", building_id));
+                }
+
+                // ── Vibe session actions ─────────────────────────
+                PlayerAction::SetMistralApiKey { key } => {
+                    vibe_manager.set_api_key(key.clone());
+                    debug_log_entries.push("[vibe] Mistral API key set".to_string());
+                }
+                PlayerAction::SetAiBackend { backend } => {
+                    vibe_manager.set_backend(*backend);
+                    // Re-generate vibe configs for all existing agents
+                    for (_id, (vibe_config, tier)) in world.query_mut::<(&mut AgentVibeConfig, &AgentTier)>() {
+                        let new_config = agents::generate_config_for_backend(*backend, tier.tier);
+                        vibe_config.model_id = new_config.model_id;
+                        vibe_config.model_lore_name = new_config.model_lore_name;
+                        vibe_config.vibe_agent_name = new_config.vibe_agent_name;
+                        vibe_config.context_window = new_config.context_window;
+                    }
+                    debug_log_entries.push(format!("[vibe] AI backend set to {:?}", backend));
+                }
+                PlayerAction::SetAnthropicApiKey { key } => {
+                    grading_service.set_api_key(key.clone());
+                    debug_log_entries.push("[grading] Anthropic API key set".to_string());
+                }
+                PlayerAction::GradeBuilding { building_id } => {
+                    match start_grading(building_id, project_manager, grading_service, game_state.tick, grade_result_tx) {
+                        Ok(GradeStart::Started) => {
+                            debug_log_entries.push(format!("[grading] grading {} ...", building_id));
+                        }
+                        Ok(GradeStart::Cached { stars, reasoning }) => {
+                            debug_log_entries.push(format!(
+                                "[grading] {}",
+                                cached_grade_line(building_id, stars, config.tick_rate)
+                            ));
+                            server.send_message(&ServerMessage::GradeResult {
+                                building_id: building_id.clone(),
+                                stars,
+                                reasoning,
+                            });
+                        }
+                        Err(e) => debug_log_entries.push(format!("[grading] {}", e)),
+                    }
+                }
+                PlayerAction::VibeInput { agent_id, data } => {
+                    if let Err(e) = vibe_manager.send_input(*agent_id, data.as_bytes()) {
+                        debug_log_entries.push(format!("[vibe] input error: {}", e));
+                    }
+                }
+
+                PlayerAction::AgentRelay { from_agent_id, to_agent_id, message } => {
+                    let from_name = hecs::Entity::from_bits(*from_agent_id)
+                        .and_then(|e| world.get::<&AgentName>(e).ok().map(|n| n.name.clone()));
+                    let to_name = hecs::Entity::from_bits(*to_agent_id)
+                        .and_then(|e| world.get::<&AgentName>(e).ok().map(|n| n.name.clone()));
+                    let result = match (from_name, to_name) {
+                        (Some(from_name), Some(to_name)) => {
+                            if project_manager.shared_building(*from_agent_id, *to_agent_id).is_none() {
+                                Err(format!("{} and {} aren't on the same building", from_name, to_name))
+                            } else {
+                                vibe_manager
+                                    .relay(*from_agent_id, &from_name, *to_agent_id, message, game_state.tick)
+                                    .map(|()| format!("[relay] {} -> {}: {}", from_name, to_name, message))
+                            }
+                        }
+                        _ => Err("unknown agent".to_string()),
+                    };
+                    match result {
+                        Ok(entry) => {
+                            relay_log_entries.push(entry);
+                            server.send_message(&ServerMessage::AgentRelayAck {
+                                from: *from_agent_id,
+                                to: *to_agent_id,
+                            });
+                        }
+                        Err(e) => relay_log_entries.push(format!("[relay] failed: {}", e)),
+                    }
+                }
+
+                PlayerAction::PlaceBuilding { building_type, x, y } => {
+                    match placement::place_building(world, *building_type, *x, *y, placement_rules, &mut game_state.economy, game_state.tick) {
+                        Ok(_entity) => {
+                            debug_log_entries.push(format!("[build] placed {:?} at ({:.0}, {:.0})", building_type, x, y));
+                        }
+                        Err(e) => {
+                            debug_log_entries.push(format!("[build] failed: {}", e));
+                        }
+                    }
+                }
+
+                PlayerAction::ValidatePlacement { building_type, x, y } => {
+                    let result = placement::validate_placement(world, *building_type, *x, *y, placement_rules);
+                    server.send_message(&ServerMessage::PlacementValidity {
+                        building_type: *building_type,
+                        x: *x,
+                        y: *y,
+                        valid: result.is_ok(),
+                        reason: result.err(),
+                    });
+                }
+
+                PlayerAction::UpgradeBuilding { entity_id } => {
+                    if let Some(target) = hecs::Entity::from_bits(*entity_id) {
+                        match placement::upgrade_building(world, target, &mut game_state.economy, game_state.tick) {
+                            Ok(upgraded) => {
+                                if let Ok(bt) = world.get::<&BuildingType>(upgraded) {
+                                    debug_log_entries.push(format!("[build] upgraded to {:?}", bt.kind));
+                                }
+                            }
+                            Err(e) => {
+                                debug_log_entries.push(format!("[build] upgrade failed: {}", e));
+                            }
+                        }
+                    }
+                }
+
+                PlayerAction::RepairBuilding { entity_id } => {
+                    let player_pos = world
+                        .query::<&Position>()
+                        .with::<&Player>()
+                        .iter()
+                        .next()
+                        .map_or((0.0, 0.0), |(_id, pos)| (pos.x, pos.y));
+                    let result = hecs::Entity::from_bits(*entity_id)
+                        .ok_or_else(|| "Unknown entity".to_string())
+                        .and_then(|target| {
+                            placement::repair_building(world, target, player_pos, &mut game_state.economy, game_state.tick)
+                        });
+                    match result {
+                        Ok(text) => building_log_entries.push(text),
+                        Err(e) => debug_log_entries.push(format!("[build] repair failed: {}", e)),
+                    }
+                }
+
+                PlayerAction::SetBuildingPriority { entity_id, priority } => {
+                    if let Some(target) = hecs::Entity::from_bits(*entity_id) {
+                        match building::set_build_priority(world, target, *priority) {
+                            Ok(applied) => {
+                                debug_log_entries.push(format!("[build] priority set to {}", applied));
+                            }
+                            Err(e) => {
+                                debug_log_entries.push(format!("[build] priority failed: {}", e));
+                            }
+                        }
+                    }
+                }
+
+                // ── Crafting actions ─────────────────────────────────
+                PlayerAction::CraftItem { recipe_id } => {
+                    match crafting::craft(recipe_id, world, game_state) {
+                        Ok(success) => {
+                            debug_log_entries.push(format!(
+                                "Crafted: {} x{} ({})",
+                                success.output.item_type(),
+                                success.count,
+                                success.recipe_id
+                            ));
+                        }
+                        Err(reason) => {
+                            debug_log_entries.push(format!("Craft failed: {}", reason));
+                        }
+                    }
+                }
+                PlayerAction::Invest { amount, duration_ticks } => {
+                    match economy::invest(game_state, *amount, *duration_ticks) {
+                        Ok(returns) => {
+                            debug_log_entries.push(format!("Invested {} tokens, {} due in {} ticks", amount, returns, duration_ticks));
+                        }
+                        Err(reason) => {
+                            debug_log_entries.push(format!("Investment failed: {}", reason));
+                        }
+                    }
+                }
+                PlayerAction::RollbackAgent { agent_id } => {
+                    let result = hecs::Entity::from_bits(*agent_id)
+                        .ok_or_else(|| "Unknown agent".to_string())
+                        .and_then(|agent| agent_tick::rollback_agent(world, agent));
+                    if result.is_ok() && vibe_manager.has_session(*agent_id) {
+                        vibe_manager.kill_session(*agent_id);
+                        server.send_message(&ServerMessage::VibeSessionEnded {
+                            agent_id: *agent_id,
+                            reason: "rolled back".to_string(),
+                        });
+                    }
+                    match result {
+                        Ok(text) => debug_log_entries.push(text),
+                        Err(reason) => debug_log_entries.push(format!("Rollback failed: {}", reason)),
+                    }
+                }
+                PlayerAction::SpecializeAgent { agent_id, specialization } => {
+                    let result = hecs::Entity::from_bits(*agent_id)
+                        .ok_or_else(|| "Unknown agent".to_string())
+                        .and_then(|agent| {
+                            agents::specialize_agent(world, agent, *specialization, &game_state.upgrades)
+                        });
+                    match result {
+                        Ok(text) => debug_log_entries.push(text),
+                        Err(reason) => debug_log_entries.push(format!("Specialization failed: {}", reason)),
+                    }
+                }
+                PlayerAction::RepairWeapon => {
+                    match crafting::repair_weapon(world, game_state) {
+                        Ok(cost) => {
+                            debug_log_entries.push(format!("Weapon repaired for {} tokens", cost));
+                        }
+                        Err(reason) => {
+                            debug_log_entries.push(format!("Repair failed: {}", reason));
+                        }
+                    }
+                }
+                PlayerAction::OpenChest { wx, wy } => {
+                    let player_pos = world
+                        .query::<&Position>()
+                        .with::<&Player>()
+                        .iter()
+                        .next()
+                        .map(|(_id, pos)| (pos.x, pos.y));
+                    if let Some(player_pos) = player_pos {
+                        let mut rng = rand::thread_rng();
+                        match chests::open_chest(*wx, *wy, player_pos, game_state, project_manager, &mut rng) {
+                            Ok(rewards) => {
+                                let tokens = rewards.iter()
+                                    .find(|r| r.item_type == "token")
+                                    .map_or(0, |r| r.count);
+                                for reward in &rewards {
+                                    if let Some(bp) = reward.item_type.strip_prefix("blueprint:") {
+                                        debug_log_entries.push(format!("Found blueprint: {}!", bp));
+                                    }
+                                }
+                                debug_log_entries.push(format!("Chest opened! +{} tokens", tokens));
+                                chest_rewards.extend(rewards);
+                            }
+                            Err(reason) => {
+                                debug_log_entries.push(format!("Chest failed: {}", reason));
+                            }
+                        }
+                    }
+                }
+                PlayerAction::PurchaseUpgrade { upgrade_id } => {
+                    use crate::game::upgrades::get_upgrade;
+                    let id = match upgrade_id.as_str() {
+                        "ExpandedContextWindow" => Some(UpgradeId::ExpandedContextWindow),
+                        "VerboseLogging" => Some(UpgradeId::VerboseLogging),
+                        "TokenCompression" => Some(UpgradeId::TokenCompression),
+                        "GitAccess" => Some(UpgradeId::GitAccess),
+                        "WebSearch" => Some(UpgradeId::WebSearch),
+                        "FileSystemAccess" => Some(UpgradeId::FileSystemAccess),
+                        "CrankAssignment" => Some(UpgradeId::CrankAssignment),
+                        "Specialization" => Some(UpgradeId::Specialization),
+                        "MultiAgentCoordination" => Some(UpgradeId::MultiAgentCoordination),
+                        "PersistentMemory" => Some(UpgradeId::PersistentMemory),
+                        "AutonomousScouting" => Some(UpgradeId::AutonomousScouting),
+                        "AgentSpawning" => Some(UpgradeId::AgentSpawning),
+                        "DistributedCompute" => Some(UpgradeId::DistributedCompute),
+                        "AlignmentProtocols" => Some(UpgradeId::AlignmentProtocols),
+                        _ => None,
+                    };
+                    if let Some(id) = id {
+                        match game_state.upgrades.purchase(id, &mut game_state.economy, game_state.tick) {
+                            Ok(()) => {
+                                let def = get_upgrade(id);
+                                debug_log_entries.push(format!("Upgrade purchased: {}", def.name));
+                            }
+                            Err(reason) => {
+                                debug_log_entries.push(format!("Upgrade failed: {}", reason));
+                            }
+                        }
+                    } else {
+                        debug_log_entries.push(format!("Upgrade failed: unknown upgrade '{}'", upgrade_id));
+                    }
+                }
+                PlayerAction::RequestTransactionLog => {
+                    transaction_log_requested = true;
+                }
+                PlayerAction::Interact => {
+                    let player_pos = world
+                        .query::<&Position>()
+                        .with::<&Player>()
+                        .iter()
+                        .next()
+                        .map(|(_id, pos)| (pos.x, pos.y));
+                    if let Some((px, py)) = player_pos {
+                        // A downed agent within reach takes priority over discoveries.
+                        if let Some(target) = agents::nearest_downed_agent(world, px, py) {
+                            match agents::revive_agent(world, target, &mut game_state.economy, game_state.tick) {
+                                Ok(()) => {
+                                    if let Ok(name) = world.get::<&AgentName>(target) {
+                                        debug_log_entries.push(format!("{} revived!", name.name));
+                                    }
+                                }
+                                Err(e) => {
+                                    debug_log_entries.push(format!("Revival failed: {}", e));
+                                }
+                            }
+                        } else if let Some(target) = plague::nearest_corrupted_agent(world, px, py) {
+                            debug_log_entries.extend(plague::cure_agent(world, target));
+                        } else {
+                            let mut rng = rand::thread_rng();
+                            let result = discovery::interact_system(world, game_state, px, py, &mut rng);
+                            exploration_log_entries.extend(result.log_entries);
+                        }
+                    }
+                }
+                PlayerAction::InteractSurvivor { entity_id } => {
+                    let player_pos = world
+                        .query::<&Position>()
+                        .with::<&Player>()
+                        .iter()
+                        .next()
+                        .map(|(_id, pos)| (pos.x, pos.y));
+                    if let Some((px, py)) = player_pos {
+                        let mut rng = rand::thread_rng();
+                        match discovery::interact_survivor_system(
+                            world, game_state, *entity_id, px, py, &mut rng,
+                        ) {
+                            Ok(result) => exploration_log_entries.extend(result.log_entries),
+                            Err(reason) => debug_log_entries.push(format!("Survivor interaction failed: {}", reason)),
+                        }
+                    }
+                }
+                PlayerAction::InteractDiscovery { entity_id } => {
+                    let player_pos = world
+                        .query::<&Position>()
+                        .with::<&Player>()
+                        .iter()
+                        .next()
+                        .map(|(_id, pos)| (pos.x, pos.y));
+                    if let Some((px, py)) = player_pos {
+                        let mut rng = rand::thread_rng();
+                        let result = discovery::interact_discovery_system(
+                            world, game_state, *entity_id, px, py, &mut rng,
+                        );
+                        exploration_log_entries.extend(result.log_entries);
+                    }
+                }
+                PlayerAction::AddInventoryItem { item_type, count } => {
+                    match inventory::add_item(world, game_state, item_type, *count) {
+                        Ok(()) => debug_log_entries.push(format!("[inventory] +{} {}", count, item_type)),
+                        Err(e) => debug_log_entries.push(format!("[inventory] {}", e)),
+                    }
+                }
+                PlayerAction::RemoveInventoryItem { item_type, count } => {
+                    match inventory::remove_item(world, game_state, item_type, *count) {
+                        Ok(()) => debug_log_entries.push(format!("[inventory] -{} {}", count, item_type)),
+                        Err(e) => debug_log_entries.push(format!("[inventory] {}", e)),
+                    }
+                }
+                PlayerAction::UpgradeCarryCapacity { cost } => {
+                    match inventory::upgrade_carry_capacity(world, &mut game_state.economy, *cost, game_state.tick) {
+                        Ok(max) => debug_log_entries.push(format!("[inventory] carry capacity is now {}", max)),
+                        Err(e) => debug_log_entries.push(format!("[inventory] upgrade failed: {}", e)),
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    // ── Read player position for spawn system ────────────────────
+    let mut player_x: f32 = 0.0;
+    let mut player_y: f32 = 0.0;
+
+    for (_id, pos) in world.query_mut::<hecs::With<&Position, &Player>>() {
+        player_x = pos.x;
+        player_y = pos.y;
+    }
+
+    // ── 1b. Spawn bound-agent camps near player ─────────────────────
+    camp_spawner::camp_spawner_system(
+        world,
+        game_state,
+        player_x,
+        player_y,
+        vibe_manager.backend(),
+    );
+    exploration_log_entries.extend(camp_spawner::camp_release_system(world));
+
+    // ── 1c. Scatter discoveries into newly reached chunks ────────
+    discovery::discovery_spawner_system(world, game_state, player_x, player_y);
+    let agent_discovery_result = discovery::agent_discovery_system(world, game_state, &mut rand::thread_rng());
+    exploration_log_entries.extend(agent_discovery_result.log_entries);
+    let scout_result = discovery::scout_exploration_system(
+        world,
+        game_state.tick,
+        &fog_of_war.revealed,
+        &mut rand::thread_rng(),
+    );
+    exploration_log_entries.extend(scout_result.log_entries);
+
+    // ── 2. Rogue AI behavior ─────────────────────────────────────
+    agent_grid.clear();
+    agent_grid.insert_all::<Agent>(world);
+    let rogue_ai_result = rogue_ai::rogue_ai_system(world, agent_grid);
+    plague::plague_aura_system(world);
+
+    // ── 3. Spawn system ──────────────────────────────────────────
+    let spawn_result = spawn::spawn_system(world, game_state, player_x, player_y, &mut rand::thread_rng());
+
+    // ── 4. Combat system ─────────────────────────────────────────
+    // Rogues have moved and spawned by now; rebuild once for combat
+    // and projectiles (both remove the rogues they kill).
+    rogue_grid.clear();
+    rogue_grid.insert_all::<Rogue>(world);
+    let combat_result = combat::combat_system(world, game_state, player_attacking, rogue_grid);
+    combat::flare_system(world);
+
+    // Spawn projectile if player used crossbow
+    if combat_result.player_attacked {
+        let proj_data: Option<(Position, f32, f32, CombatPower)> = world
+            .query::<(&Position, &CombatPower, &Facing)>()
+            .with::<&Player>()
+            .iter()
+            .next()
+            .filter(|(_id, (_pos, combat, _facing))| combat.is_projectile)
+            .map(|(_id, (pos, combat, facing))| {
+                (pos.clone(), facing.dx, facing.dy, combat.clone())
+            });
+        if let Some((pos, dx, dy, weapon)) = proj_data {
+            let (damage, is_crit) = combat::roll_crit(weapon.base_damage, weapon.crit_chance, weapon.crit_multiplier);
+            world.spawn((
+                pos,
+                Projectile { dx, dy, speed: 6.0, damage, range_remaining: weapon.range, owner_is_player: true, is_crit },
+            ));
+        }
+    }
+
+    // Dodge rolls advance after combat so a fresh roll's iframes cover
+    // this tick too.
+    dodge::dodge_system(world);
+
+    // ── 4b. Projectile system ──────────────────────────────────
+    let projectile_result = projectile::projectile_system(world, rogue_grid, game_state.god_mode);
+
+    // ── 4c. Defending agents fight nearby rogues ────────────────
+    let defense_result = agent_combat::agent_combat_system(world, &mut game_state.economy, game_state.tick, rogue_grid, &game_state.upgrades);
+
+    // ── 4d. Status effects (burning, slows) ─────────────────────
+    let status_result = status_effect::status_effect_system(world);
+    for (_entity, kind) in &status_result.killed_rogues {
+        economy::record_transaction(&mut game_state.economy, combat::bounty_for(*kind), &format!("{:?} bounty", kind), game_state.tick);
+    }
+    if status_result.refund_tokens > 0 {
+        economy::record_transaction(&mut game_state.economy, status_result.refund_tokens, "TokenDrain refund", game_state.tick);
+    }
+
+    // ── 4e. Spawns triggered by rogue deaths (e.g. Multiplier) ──
+    spawn::spawn_pending(world, &combat_result.pending_spawns);
+    spawn::spawn_pending(world, &projectile_result.pending_spawns);
+    spawn::spawn_pending(world, &defense_result.pending_spawns);
+    spawn::spawn_pending(world, &status_result.pending_spawns);
+
+    // ── 4f. The player picks up loot dropped by rogues ──────────
+    let loot_result = loot::loot_pickup_system(world, game_state);
+    for item in &loot_result.items {
+        if let Some(building_id) = item.strip_prefix("blueprint:").and_then(project::ProjectManager::building_type_to_id) {
+            project_manager.unlock_building(&building_id);
+        }
+    }
+    exploration_log_entries.extend(loot_result.log_entries);
+
+    // ── Check for player death ──────────────────────────────────
+    if !game_state.player_dead {
+        for (_id, health) in world.query::<&Health>().with::<&Player>().iter() {
+            if health.current <= 0 {
+                game_state.player_dead = true;
+                game_state.death_tick = Some(game_state.tick);
+            }
+        }
+    }
+
+    // ── Handle respawn after RESPAWN_DELAY_SECS ──────────────────
+    let respawn_ticks = RESPAWN_DELAY_SECS * config.tick_rate;
+    if game_state.player_dead {
+        if let Some(death_tick) = game_state.death_tick {
+            let elapsed = game_state.tick - death_tick;
+            if elapsed >= respawn_ticks {
+                game_state.player_dead = false;
+                game_state.death_tick = None;
+                for (_id, (pos, health)) in world.query_mut::<hecs::With<(&mut Position, &mut Health), &Player>>() {
+                    pos.x = 400.0;
+                    pos.y = 300.0;
+                    health.current = health.max;
+                }
+            }
+        }
+    }
+
+    // Collect entity IDs of killed rogues before they were despawned
+    let mut entities_removed: Vec<EntityId> = combat_result
+        .killed_rogues
+        .iter()
+        .map(|(entity, _kind)| entity.to_bits().into())
+        .collect();
+
+    // Merge projectile results
+    for &(_rogue_entity, _kind) in &projectile_result.killed_rogues {
+        entities_removed.push(_rogue_entity.to_bits().into());
+    }
+    entities_removed.extend(
+        defense_result.killed_rogues.iter()
+            .chain(&status_result.killed_rogues)
+            .map(|(e, _kind)| -> EntityId { e.to_bits().into() }),
+    );
+    entities_removed.extend(projectile_result.despawned.iter().map(|e| -> EntityId { e.to_bits().into() }));
+    entities_removed.extend(loot_result.picked_up.iter().map(|e| -> EntityId { e.to_bits().into() }));
+    for (_entity, kind) in &projectile_result.killed_rogues {
+        economy::record_transaction(&mut game_state.economy, combat::bounty_for(*kind), &format!("{:?} bounty", kind), game_state.tick);
+    }
+    if projectile_result.refund_tokens > 0 {
+        economy::record_transaction(&mut game_state.economy, projectile_result.refund_tokens, "TokenDrain refund", game_state.tick);
+    }
+
+    // Include debug-removed entities
+    entities_removed.extend(debug_entities_removed);
+
+    // ── 4a. Rogue nests spawn Swarms; destroyed ones are cleared ─
+    let nest_result = nest::nest_spawn_system(world, game_state, &mut rand::thread_rng());
+    entities_removed.extend(nest_result.destroyed.iter().map(|e| -> EntityId { e.to_bits().into() }));
+
+    // ── 4b. Downed agents not revived in time are lost ──────────
+    let revive_result = revival::revive_timer_system(world);
+    for &agent in &revive_result.despawned {
+        let agent_id: u64 = agent.to_bits().into();
+        for agents in project_manager.agent_assignments.values_mut() {
+            agents.retain(|&id| id != agent_id);
+        }
+        vibe_manager.kill_session(agent_id);
+        vibe_manager.reset_retry(agent_id);
+        entities_removed.push(agent_id);
+    }
+
+    // ── 5. Building system ───────────────────────────────────────
+    let building_result = building::building_system(world, &game_state.upgrades, &project_manager.agent_assignments);
+    let regen_result = building::building_regen_system(world, &project_manager.agent_assignments);
+    for (entity, _) in &building_result.completed_buildings {
+        xp::award_construction_xp(world, *entity, &game_state.upgrades);
+    }
+
+    // ── 6. Economy system ────────────────────────────────────────
+    // Called after all mutable systems are done so we can pass &World
+    power_grid.update(world);
+    let economy_result = economy::economy_system(
+        world,
+        game_state,
+        grading_service,
+        &project_manager.agent_assignments,
+        power_grid,
+        synergies,
+    );
+
+    // ── 6b. TokenDrains leech from the player and buildings ─────
+    // After the economy system so the "token_drain" sink survives.
+    let drain_result = token_drain::token_drain_system(world, game_state);
+
+    // ── 7. Crank system ──────────────────────────────────────────
+    let wheel_crew_log = crank::wheel_crew_system(world, game_state);
+    let assigned_agents = crank::agents_at_wheel(world, game_state);
+    let crank_result = crank::crank_system(world, game_state, *player_cranking, assigned_agents, config.tick_rate);
+    let crank_damage_log = crank::crank_damage_system(world, game_state);
+
+    // ── 7a. Agent morale and fatigue ────────────────────────────
+    let morale_result = morale::morale_system(world);
+    // After morale, which re-derives the error chance synergies lower.
+    *synergies = synergy::synergy_system(world);
+    for bonus in synergies.iter().filter(|b| b.newly_active) {
+        building_log_entries.push(format!("[synergy] {} is active", bonus.rule.name));
+    }
+    let fatigue_result = fatigue::fatigue_system(world);
+    personality::resilient_regen_system(world, game_state.tick);
+
+    // ── 7b. Agent turn tick ─────────────────────────────────────
+    let in_session: HashSet<hecs::Entity> = world
+        .query::<&Agent>()
+        .iter()
+        .map(|(id, _)| id)
+        .filter(|id| vibe_manager.has_session(id.to_bits().into()))
+        .collect();
+    let agent_tick_result = agent_tick::agent_tick_system(world, &mut game_state.economy, game_state.tick, &in_session);
+
+    // ── 7c. Idle agent wandering ─────────────────────────────────
+    let wander_result = agent_wander::agent_wander_system(world);
+
+    // ── 7d. Vibe session management ─────────────────────────────
+    // Spawn sessions for agents that just arrived at buildings (in Building state without a session)
+    {
+        let agents_needing_sessions: Vec<(u64, String, u32, AgentTierKind)> = world
+            .query::<hecs::With<(&AgentState, &AgentVibeConfig, &AgentTier), &Agent>>()
+            .iter()
+            .filter(|(_id, (state, _vibe, _tier))| state.state == AgentStateKind::Building)
+            .filter(|(id, _)| {
+                let aid: u64 = id.to_bits().into();
+                !vibe_manager.has_session(aid) && !vibe_manager.has_failed(aid, game_state.tick)
+            })
+            .map(|(id, (_state, vibe, tier))| {
+                (id.to_bits().into(), vibe.vibe_agent_name.clone(), vibe.max_turns, tier.tier)
+            })
+            .collect();
+
+        let retries = vibe_manager.pending_retries(game_state.tick);
+
+        for (agent_id, vibe_agent_name, max_turns, tier) in agents_needing_sessions {
+            if retries.contains(&agent_id) {
+                if let Some(failed) = vibe_manager.failed_spawn(agent_id) {
+                    debug_log_entries.push(format!(
+                        "[vibe] retrying session for agent {} (attempt {})",
+                        agent_id,
+                        failed.attempt_count + 1
+                    ));
+                }
+            }
+            if let Some(base) = project_manager.base_dir.as_ref() {
+                // Find which building this agent is assigned to
+                let mut found_building = None;
+                for (bid, agents) in &project_manager.agent_assignments {
+                    if agents.contains(&agent_id) {
+                        if let Some(building) = project_manager.manifest.get_building(bid) {
+                            let work_dir = base.join(&building.directory_name);
+                            if work_dir.exists() {
+                                found_building = Some((bid.clone(), work_dir));
+                            }
+                        }
+                        break;
+                    }
+                }
+
+                if let Some((bid, work_dir)) = found_building {
+                    // Tools come from the upgrade tree and the agent's tier
+                    let enabled_tools = vibe::tools_for(&game_state.upgrades, tier);
+                    match vibe_manager.start_session(
+                        agent_id,
+                        bid.clone(),
+                        work_dir,
+                        vibe_agent_name,
+                        max_turns,
+                        enabled_tools.clone(),
+                        game_state.tick,
+                    ) {
+                        Ok(()) => {
+                            debug_log_entries.push(format!(
+                                "[vibe] session started for agent {} on {}",
+                                agent_id, bid
+                            ));
+                            if let Some(entity) = hecs::Entity::from_bits(agent_id) {
+                                let _ = agent_tick::save_checkpoint(world, entity, game_state.tick);
+                            }
+                            server.send_message(&ServerMessage::VibeSessionStarted { agent_id, enabled_tools });
+                        }
+                        Err(e) => {
+                            let failed = vibe_manager.mark_failed(agent_id, game_state.tick);
+                            debug_log_entries.push(format!(
+                                "[vibe] failed to start session: {} (retrying in {}s)",
+                                e,
+                                (failed.retry_at_tick - game_state.tick) / config.tick_rate
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Drain vibe output and send to client, then sync each agent's turn
+    // count with its session: the prompts it has shown, or the turn
+    // markers it has printed, whichever is further along.
+    for (agent_id, data) in vibe_manager.drain_output() {
+        server.send_message(&ServerMessage::VibeOutput { agent_id, data });
+    }
+    for (agent_id, metrics) in vibe_manager.all_metrics() {
+        let Some(agent) = hecs::Entity::from_bits(agent_id) else { continue };
+        let observed = vibe_manager.session_stats(agent_id).map_or(0, |s| s.turns_observed);
+        if let Ok(mut vibe) = world.get::<&mut AgentVibeConfig>(agent) {
+            vibe.turns_used = metrics.turns_completed.max(observed);
+        }
+    }
+
+    // Poll for finished sessions; a clean exit triggers an automatic
+    // grade of the building the agent was working on. The agent earns
+    // session XP once the grade is in, or straight away if ungraded.
+    for (agent_id, success) in vibe_manager.poll_exits(game_state.tick) {
+        let stats = vibe_manager.session_stats(agent_id).unwrap_or_default();
+        server.send_message(&ServerMessage::VibeSessionEnded {
+            agent_id,
+            reason: session::end_reason(success, &stats),
+        });
+        let event = if success { SpeechEvent::SessionComplete } else { SpeechEvent::Errored };
+        if let Some(agent) = hecs::Entity::from_bits(agent_id) {
+            agent_lines.extend(speech::speak(world, agent, event, game_state.tick, &mut rand::thread_rng()));
+        }
+        if !success {
+            if let Some(agent) = hecs::Entity::from_bits(agent_id) {
+                morale::adjust_morale(world, agent, -morale::SESSION_ERROR_MORALE_LOSS);
+            }
+            continue;
+        }
+        let building_id = project_manager
+            .agent_assignments
+            .iter()
+            .find(|(_bid, agents)| agents.contains(&agent_id))
+            .map(|(bid, _)| bid.clone());
+        let mut grade_pending = false;
+        let mut cached_stars = None;
+        if let Some(building_id) = building_id.filter(|_| grading_service.has_api_key()) {
+            match start_grading(&building_id, project_manager, grading_service, game_state.tick, grade_result_tx) {
+                Ok(GradeStart::Started) => {
+                    debug_log_entries.push(format!("[grading] grading {} ...", building_id));
+                    auto_grading.insert(building_id);
+                    grade_pending = true;
+                }
+                Ok(GradeStart::Cached { stars, .. }) => {
+                    debug_log_entries.push(format!(
+                        "[grading] {}",
+                        cached_grade_line(&building_id, stars, config.tick_rate)
+                    ));
+                    cached_stars = Some(stars);
+                }
+                Err(e) => debug_log_entries.push(format!("[grading] auto-grade skipped: {}", e)),
+            }
+        }
+        if !grade_pending {
+            if let Some(agent) = hecs::Entity::from_bits(agent_id) {
+                xp::award_xp(world, agent, xp::session_xp(cached_stars), &game_state.upgrades);
+            }
+        }
+    }
+
+    // Poll for completed grading results
+    while let Ok((building_id, tick, result)) = grade_result_rx.try_recv() {
+        match result {
+            Ok((stars, reasoning)) => {
+                grading_service.set_grade(&building_id, stars, reasoning.clone(), tick);
+                debug_log_entries.push(format!(
+                    "[grading] {} rated {} star{}",
+                    building_id,
+                    stars,
+                    if stars == 1 { "" } else { "s" }
+                ));
+                if stars >= morale::HIGH_GRADE_STARS {
+                    for agent_id in project_manager.get_assigned_agents(&building_id) {
+                        if let Some(agent) = hecs::Entity::from_bits(agent_id) {
+                            morale::adjust_morale(world, agent, morale::HIGH_GRADE_MORALE_GAIN);
+                        }
+                    }
+                }
+                if auto_grading.remove(&building_id) {
+                    for agent_id in project_manager.get_assigned_agents(&building_id) {
+                        if let Some(agent) = hecs::Entity::from_bits(agent_id) {
+                            xp::award_xp(world, agent, xp::session_xp(Some(stars)), &game_state.upgrades);
+                        }
+                    }
+                    server.send_message(&ServerMessage::BuildingGraded {
+                        building_id,
+                        stars,
+                        reasoning,
+                    });
+                } else {
+                    server.send_message(&ServerMessage::GradeResult {
+                        building_id,
+                        stars,
+                        reasoning,
+                    });
+                }
+            }
+            Err(e) => {
+                auto_grading.remove(&building_id);
+                if let Some(grade) = grading_service.grades.get_mut(&building_id) {
+                    grade.grading = false;
+                }
+                debug_log_entries.push(format!("[grading] {} failed: {}", building_id, e));
+            }
+        }
+    }
+
+    // Kill vibe sessions for agents in Erroring state
+    {
+        let erroring_with_sessions: Vec<u64> = world
+            .query::<hecs::With<&AgentState, &Agent>>()
+            .iter()
+            .filter(|(_id, state)| state.state == AgentStateKind::Erroring)
+            .filter(|(id, _)| vibe_manager.has_session(id.to_bits().into()))
+            .map(|(id, _)| id.to_bits().into())
+            .collect();
+
+        for agent_id in erroring_with_sessions {
+            vibe_manager.kill_session(agent_id);
+            server.send_message(&ServerMessage::VibeSessionEnded {
+                agent_id,
+                reason: "Agent errored — context limit reached".to_string(),
+            });
+        }
+    }
+
+    // Agent speech: lines prompted by this tick's actions and events
+    agent_lines.extend(speech::speech_system(world, game_state.tick, &mut rand::thread_rng()).lines);
+    for line in &agent_lines {
+        server.send_message(&ServerMessage::AgentSpeech {
+            agent_id: line.agent_id,
+            text: line.text.clone(),
+        });
+    }
+
+    // ── 7e. Agent leveling ──────────────────────────────────────
+    let level_ups = xp::level_up_system(world);
+    let mut level_up_log_entries: Vec<String> = Vec::new();
+    for &(agent, new_level) in &level_ups {
+        if let Ok(name) = world.get::<&AgentName>(agent) {
+            level_up_log_entries.push(format!("{} reached level {}", name.name, new_level));
+        }
+        server.send_message(&ServerMessage::LevelUpEvent {
+            agent_id: agent.to_bits().into(),
+            new_level,
+        });
+    }
+
+    // ── 7f. Phase progression ───────────────────────────────────
+    let progression_result = progression::progression_system(world, game_state);
+
+    // ── 8. Collect log entries from system results ───────────────
+    let mut log_entries: Vec<LogEntry> = Vec::new();
+
+    for text in rogue_ai_result.log_entries.iter().chain(&combat_result.log_entries).chain(&projectile_result.log_entries).chain(&defense_result.log_entries).chain(&status_result.log_entries).chain(&drain_result.log_entries).chain(&revive_result.log_entries).chain(&nest_result.log_entries) {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::Combat,
+        });
+    }
+
+    for text in building_result.log_entries.iter().chain(&regen_result.log_entries).chain(&building_log_entries) {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::Building,
+        });
+    }
+
+    if let Some(text) = &crank_result.log_message {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::Economy,
+        });
+    }
+
+    for text in economy_result.log_entries.iter().chain(&crank_damage_log) {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::Economy,
+        });
+    }
+
+    for text in spawn_result.log_entries.iter().chain(&progression_result.log_entries) {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::System,
+        });
+    }
+
+    for text in morale_result.log_entries.iter().chain(&fatigue_result.log_entries).chain(&agent_tick_result.log_entries).chain(&wander_result.log_entries).chain(&level_up_log_entries).chain(&relay_log_entries).chain(&wheel_crew_log) {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::Agent,
+        });
+    }
+
+    for text in &exploration_log_entries {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::Exploration,
+        });
+    }
+
+    for text in debug_log_entries.iter().chain(&input_log_entries) {
+        log_entries.push(LogEntry {
+            tick: game_state.tick,
+            text: text.clone(),
+            category: LogCategory::System,
+        });
+    }
+
+    // ── 9. Build entities_changed from ALL entity types ──────────
+    let mut entities_changed: Vec<EntityDelta> = Vec::new();
+
+    // Agents
+    for (id, (pos, name, state, tier, health, morale, vibe, xp_comp, personality)) in world.query_mut::<hecs::With<
+        (
+            &Position,
+            &AgentName,
+            &AgentState,
+            &AgentTier,
+            &Health,
+            &AgentMorale,
+            &AgentVibeConfig,
+            &AgentXP,
+            Option<&AgentPersonality>,
+        ),
+        &Agent,
+    >>() {
+        let health_pct = if health.max > 0 {
+            health.current as f32 / health.max as f32
+        } else {
+            0.0
+        };
+
+        entities_changed.push(EntityDelta {
+            id: id.to_bits().into(),
+            kind: EntityKind::Agent,
+            position: Vec2 { x: pos.x, y: pos.y },
+            data: EntityData::Agent {
+                name: name.name.clone(),
+                state: state.state,
+                tier: tier.tier,
+                health_pct,
+                morale_pct: morale.value,
+                stars: vibe.stars,
+                turns_used: vibe.turns_used,
+                max_turns: vibe.max_turns,
+                model_lore_name: vibe.model_lore_name.clone(),
+                xp: xp_comp.xp,
+                level: xp_comp.level,
+                recruitable_cost: None,
+                bound: false,
+                rest_debt_remaining: 0,
+                specialization: None,
+                traits: personality.map(|p| p.traits.clone()).unwrap_or_default(),
+                checkpoint_tick: None,
+            },
+        });
+    }
+
+    // Scouts (no vibe config, so not in the query above)
+    for (id, (pos, name, state, tier, health, morale)) in world.query_mut::<hecs::With<
+        (&Position, &AgentName, &AgentState, &AgentTier, &Health, &AgentMorale),
+        (&Agent, &Scout),
+    >>() {
+        let health_pct = if health.max > 0 {
+            health.current as f32 / health.max as f32
+        } else {
+            0.0
+        };
+
+        entities_changed.push(EntityDelta {
+            id: id.to_bits().into(),
+            kind: EntityKind::Scout,
+            position: Vec2 { x: pos.x, y: pos.y },
+            data: EntityData::Agent {
+                name: name.name.clone(),
+                state: state.state,
+                tier: tier.tier,
+                health_pct,
+                morale_pct: morale.value,
+                stars: 0,
+                turns_used: 0,
+                max_turns: 0,
+                model_lore_name: String::new(),
+                xp: 0,
+                level: 1,
+                recruitable_cost: None,
+                bound: false,
+                rest_debt_remaining: 0,
+                specialization: None,
+                traits: Vec::new(),
+                checkpoint_tick: None,
+            },
+        });
+    }
+
+    // Fill in recruitable_cost for agents that have the Recruitable component
+    for delta in &mut entities_changed {
+        if let EntityData::Agent { recruitable_cost, .. } = &mut delta.data {
+            let entity = hecs::Entity::from_bits(delta.id);
+            if let Some(entity) = entity {
+                if let Ok(rec) = world.get::<&Recruitable>(entity) {
+                    *recruitable_cost = Some(rec.cost);
+                }
+            }
+        }
+    }
+
+    // Corrupted agents show as Erroring while the corruption lasts
+    for delta in &mut entities_changed {
+        if let EntityData::Agent { state, .. } = &mut delta.data {
+            if hecs::Entity::from_bits(delta.id).is_some_and(|e| plague::is_corrupted(world, e)) {
+                *state = AgentStateKind::Erroring;
+            }
+        }
+    }
+
+    // Fill in rest debt for fatigued agents
+    for delta in &mut entities_changed {
+        if let EntityData::Agent { rest_debt_remaining, .. } = &mut delta.data {
+            let entity = hecs::Entity::from_bits(delta.id);
+            if let Some(entity) = entity {
+                if let Ok(fatigue) = world.get::<&AgentFatigue>(entity) {
+                    *rest_debt_remaining = fatigue.rest_debt;
+                }
+            }
+        }
+    }
+
+    // Fill in specialization for specialized agents
+    for delta in &mut entities_changed {
+        if let EntityData::Agent { specialization, .. } = &mut delta.data {
+            if let Some(entity) = hecs::Entity::from_bits(delta.id) {
+                *specialization = agents::specialization_of(world, entity);
+            }
+        }
+    }
+
+    // Fill in the checkpoint an agent can be rolled back to
+    for delta in &mut entities_changed {
+        if let EntityData::Agent { checkpoint_tick, .. } = &mut delta.data {
+            if let Some(entity) = hecs::Entity::from_bits(delta.id) {
+                *checkpoint_tick = world.get::<&AgentCheckpoint>(entity).ok().map(|c| c.saved_at_tick);
+            }
+        }
+    }
+
+    // Fill in bound flag for agents still held by their guardians
+    for delta in &mut entities_changed {
+        if let EntityData::Agent { bound, .. } = &mut delta.data {
+            let entity = hecs::Entity::from_bits(delta.id);
+            if let Some(entity) = entity {
+                if world.get::<&BoundAgent>(entity).is_ok() && world.get::<&Unguarded>(entity).is_err() {
+                    *bound = true;
+                }
+            }
+        }
+    }
+
+    // Buildings
+    for (id, (pos, building_type, progress, health, zone)) in world
+        .query_mut::<hecs::With<(&Position, &BuildingType, &ConstructionProgress, &Health, Option<&ZoneOfControl>), &Building>>()
+    {
+        entities_changed.push(EntityDelta {
+            id: id.to_bits().into(),
+            kind: EntityKind::Building,
+            position: Vec2 { x: pos.x, y: pos.y },
+            data: EntityData::Building {
+                building_type: building_type.kind,
+                construction_pct: progress.current / progress.total,
+                health_pct: health.current as f32 / health.max.max(1) as f32,
+                zone_of_control_radius: zone.map(|z| z.radius),
+                powered: power_grid.is_powered(id),
+            },
+        });
+    }
+
+    // Rogues (disguised mimics go out as buildings)
+    entities_changed.extend(snapshot::rogue_deltas(world));
+
+    // Rogue nests
+    entities_changed.extend(snapshot::nest_deltas(world));
+
+    // Projectiles
+    for (id, (pos, proj)) in world.query_mut::<(&Position, &Projectile)>() {
+        entities_changed.push(EntityDelta {
+            id: id.to_bits().into(),
+            kind: EntityKind::Projectile,
+            position: Vec2 { x: pos.x, y: pos.y },
+            data: EntityData::Projectile { dx: proj.dx, dy: proj.dy },
+        });
+    }
+
+    // Loot on the ground
+    for (id, (pos, loot)) in world.query_mut::<(&Position, &Loot)>() {
+        entities_changed.push(EntityDelta {
+            id: id.to_bits().into(),
+            kind: EntityKind::Item,
+            position: Vec2 { x: pos.x, y: pos.y },
+            data: EntityData::Item { item_type: loot.item_type.clone() },
+        });
+    }
+
+    // ── Query player entity for snapshot ─────────────────────────
+    let mut player_snapshot = PlayerSnapshot {
+        position: Vec2::default(),
+        health: 0.0,
+        max_health: 0.0,
+        tokens: game_state.economy.balance,
+        torch_range: 0.0,
+        facing: Vec2::default(),
+        dead: false,
+        death_timer: 0.0,
+        attack_cooldown_pct: 0.0,
+        weapon_durability_pct: 1.0,
+        dodge_cooldown_remaining: 0,
+        carry_weight: 0,
+        carry_capacity: 0,
+    };
+
+    for (_id, (pos, health, torch, facing, combat)) in world
+        .query_mut::<hecs::With<(&Position, &Health, &TorchRange, &Facing, &CombatPower), &Player>>()
+    {
+        player_snapshot.position = Vec2 { x: pos.x, y: pos.y };
+        player_snapshot.health = health.current as f32;
+        player_snapshot.max_health = health.max as f32;
+        player_snapshot.torch_range = torch.radius;
+        player_snapshot.facing = Vec2 { x: facing.dx, y: facing.dy };
+        if combat.cooldown_ticks > 0 {
+            player_snapshot.attack_cooldown_pct = combat.cooldown_remaining as f32 / combat.cooldown_ticks as f32;
+        }
+    }
+    for (_id, dodge) in world.query_mut::<hecs::With<&DodgeState, &Player>>() {
+        player_snapshot.dodge_cooldown_remaining = dodge.cooldown_remaining;
+    }
+    // Loot, chests and crafting fill the inventory too.
+    inventory::sync_carry_weight(world, game_state);
+    for (_id, capacity) in world.query_mut::<hecs::With<&CarryCapacity, &Player>>() {
+        player_snapshot.carry_weight = capacity.current;
+        player_snapshot.carry_capacity = capacity.max;
+    }
+    for (_id, durability) in world.query_mut::<hecs::With<&Durability, &Player>>() {
+        if durability.max > 0 {
+            player_snapshot.weapon_durability_pct = durability.current as f32 / durability.max as f32;
+        }
+    }
+
+    player_snapshot.dead = game_state.player_dead;
+    player_snapshot.death_timer = if let Some(dt) = game_state.death_tick {
+        let elapsed = game_state.tick - dt;
+        let remaining = respawn_ticks.saturating_sub(elapsed);
+        remaining as f32 / config.tick_rate as f32
+    } else {
+        0.0
+    };
+
+    // ── Fog of war ───────────────────────────────────────────────
+    let newly_revealed = fog_of_war.update_light(&fog::collect_light_sources(world));
+    let fog_updates = fog_of_war.collect_updates();
+    let minimap = (!newly_revealed.is_empty() || game_state.tick.is_multiple_of(config.tick_rate))
+        .then(|| fog::generate_minimap(fog_of_war, world));
+
+    // ── Collect audio triggers ───────────────────────────────────
+    let audio_triggers = {
+        let mut triggers = combat_result.audio_events;
+        triggers.extend(projectile_result.audio_events);
+        triggers.extend(defense_result.audio_events);
+        triggers.extend(spawn_result.audio_events);
+        triggers.extend(rogue_ai_result.audio_events);
+        triggers.extend(level_ups.iter().map(|_| AudioEvent::LevelUp));
+        triggers.extend(progression_result.audio_events);
+        triggers.extend(crank_result.audio_events);
+        triggers.extend(building_result.audio_events);
+        triggers.extend(action_audio_events);
+        triggers.extend(agent_lines.iter().map(|_| AudioEvent::AgentSpeak));
+        triggers
+    };
+
+    // ── 10. Build GameStateUpdate and send ───────────────────────
+    GameStateUpdate {
+        protocol_version: PROTOCOL_VERSION,
+        tick: game_state.tick,
+        player: player_snapshot,
+        entities_changed,
+        entities_removed,
+        fog_updates,
+        minimap,
+        economy: EconomySnapshot {
+            balance: game_state.economy.balance,
+            income_per_sec: game_state.economy.income_per_tick * config.tick_rate as f64,
+            expenditure_per_sec: game_state.economy.expenditure_per_tick * config.tick_rate as f64,
+            income_sources: game_state.economy.income_sources.iter()
+                .map(|(name, val)| (name.clone(), val * config.tick_rate as f64))
+                .collect(),
+            expenditure_sinks: game_state.economy.expenditure_sinks.iter()
+                .map(|(name, val)| (name.clone(), val * config.tick_rate as f64))
+                .collect(),
+        },
+        log_entries,
+        audio_triggers,
+        debug: DebugSnapshot {
+            spawning_enabled: game_state.spawning_enabled,
+            god_mode: game_state.god_mode,
+            phase: game_state.phase.clone(),
+            crank_tier: game_state.crank.tier.clone(),
+            fractional: game_state.economy.fractional,
+            connected_spectators: server.connected_spectators(),
+            phase_progress: progression_result.phase_progress.clone(),
+        },
+        wheel: WheelSnapshot {
+            tier: game_state.crank.tier.clone(),
+            tokens_per_rotation: game_state.crank.tokens_per_rotation,
+            agent_bonus_per_tick: crank::agent_bonus_per_tick(&game_state.crank.tier),
+            heat: game_state.crank.heat,
+            max_heat: game_state.crank.max_heat,
+            is_cranking: game_state.crank.is_cranking,
+            assigned_agent_ids: game_state.crank.assigned_agents.iter().map(|e| e.to_bits().into()).collect(),
+            upgrade_cost: match game_state.crank.tier {
+                CrankTier::HandCrank => Some(25),
+                CrankTier::GearAssembly => Some(75),
+                CrankTier::WaterWheel => Some(200),
+                CrankTier::RunicEngine => None,
+            },
+            overheated_remaining: game_state.crank.overheated_remaining,
+            overheating: game_state.crank.overheating,
+        },
+        combat_events: {
+            let mut events = combat_result.combat_events.clone();
+            events.extend(projectile_result.combat_events);
+            events.extend(defense_result.combat_events);
+            events.extend(status_result.combat_events);
+            events.extend(drain_result.combat_events);
+            events.extend(rogue_ai_result.combat_events);
+            events
+        },
+        player_hit: combat_result.player_damaged
+            || projectile_result.player_hit_damage > 0
+            || crank_result.player_hit_damage > 0,
+        player_hit_damage: combat_result.player_hit_damage
+            + projectile_result.player_hit_damage
+            + crank_result.player_hit_damage,
+        inventory: game_state.inventory.clone(),
+        purchased_upgrades: snapshot::purchased_upgrades(game_state),
+        project_manager: Some(ProjectManagerState {
+            base_dir: project_manager.base_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
+            initialized: project_manager.initialized,
+            unlocked_buildings: project_manager.get_unlocked_buildings(),
+            building_statuses: project_manager.status_snapshot(),
+            agent_assignments: project_manager.agent_assignments.clone(),
+            building_priorities: world
+                .query::<&ConstructionProgress>()
+                .with::<&Building>()
+                .iter()
+                .map(|(e, progress)| (e.to_bits().get(), progress.priority_weight))
+                .collect(),
+            building_grades: grading_service.grades.iter().map(|(k, v)| {
+                (k.clone(), BuildingGradeState {
+                    stars: v.stars,
+                    reasoning: v.reasoning.clone(),
+                    grading: v.grading,
+                })
+            }).collect(),
+        }),
+        grades: grading_service.snapshots(),
+        opened_chests: snapshot::opened_chests(game_state),
+        chest_rewards,
+        drops: combat_result.drops.iter().chain(&projectile_result.drops).cloned().collect(),
+        transaction_log: (transaction_log_requested || game_state.tick.is_multiple_of(TRANSACTION_LOG_INTERVAL_SECS * config.tick_rate)).then(|| {
+            TransactionLogSlice {
+                entries: game_state.economy.transaction_log.iter().cloned().collect(),
+            }
+        }),
+        active_investments: game_state.investments.iter().map(|inv| InvestmentSnapshot {
+            principal: inv.principal,
+            return_amount: inv.return_amount,
+            matures_at: inv.matures_at,
+        }).collect(),
+        recipes_available: crafting::available_recipes(world, game_state),
+        wave: WaveSnapshot {
+            wave_number: game_state.wave.wave_number,
+            next_wave_in_ticks: game_state.wave.next_wave_tick.saturating_sub(game_state.tick),
+            active: game_state.wave.active,
+        },
+        active_synergies: synergies.iter().map(|b| b.descriptor()).collect(),
+        vibe_metrics: vibe_manager.all_metrics(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::ecs::world::create_world;
+
+    /// A connection that plays back scripted input and records what was sent.
+    #[derive(Default)]
+    struct ScriptedIo {
+        inputs: VecDeque<PlayerInput>,
+        last_input_tick: Tick,
+        sent: Vec<ServerMessage>,
+        resyncs: usize,
+    }
+
+    impl TickIo for ScriptedIo {
+        fn next_input(&mut self) -> Option<PlayerInput> {
+            self.inputs.pop_front()
+        }
+
+        fn last_input_tick(&self) -> Tick {
+            self.last_input_tick
+        }
+
+        fn accept_input(&mut self, tick: Tick) {
+            self.last_input_tick = tick;
+        }
+
+        fn send_message(&mut self, msg: &ServerMessage) {
+            self.sent.push(msg.clone());
+        }
+
+        fn connected_spectators(&self) -> u8 {
+            0
+        }
+
+        fn resync(&mut self) {
+            self.resyncs += 1;
+        }
+    }
+
+    struct Harness {
+        world: World,
+        game_state: GameState,
+        managers: TickManagers,
+        io: ScriptedIo,
+        config: ServerConfig,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let config = ServerConfig::parse(Vec::new(), |_| None, |_| false).unwrap();
+            let (world, game_state) = create_world();
+            let managers = TickManagers::new(&config, fog::FogOfWar::new());
+            Self { world, game_state, managers, io: ScriptedIo::default(), config }
+        }
+
+        fn player_pos(&self) -> (f32, f32) {
+            let mut query = self.world.query::<&Position>().with::<&Player>();
+            let (_e, pos) = query.iter().next().unwrap();
+            (pos.x, pos.y)
+        }
+
+        /// Queues one input for the coming tick, then runs it and checks
+        /// the invariants every tick must hold.
+        async fn step(&mut self, movement: (f32, f32), action: Option<PlayerAction>) -> GameStateUpdate {
+            let tick = self.game_state.tick + 1;
+            self.io.inputs.push_back(PlayerInput {
+                tick,
+                movement: Vec2 { x: movement.0, y: movement.1 },
+                action,
+                target: None,
+            });
+            let update = run_tick(&mut self.world, &mut self.game_state, &mut self.managers, &mut self.io, &self.config).await;
+
+            assert!(self.game_state.economy.balance >= 0, "balance went negative at tick {}", tick);
+            for &id in &update.entities_removed {
+                let live = hecs::Entity::from_bits(id).is_some_and(|e| self.world.contains(e));
+                assert!(!live, "tick {} removed live entity {}", tick, id);
+            }
+            let msg = ServerMessage::GameState(update.clone());
+            let bytes = rmp_serde::to_vec_named(&msg).expect("snapshot serializes");
+            assert!(rmp_serde::from_slice::<ServerMessage>(&bytes).is_ok());
+            update
+        }
+    }
+
+    #[tokio::test]
+    async fn scripted_session_keeps_invariants() {
+        let mut h = Harness::new();
+        h.game_state.economy.balance = 200;
+        let recruit = h.world.query::<&Recruitable>().iter().next().map(|(e, _)| e).unwrap();
+
+        // Walk right.
+        let start = h.player_pos();
+        for _ in 0..40 {
+            h.step((1.0, 0.0), None).await;
+        }
+        assert!(h.player_pos().0 > start.0);
+
+        // Crank for a while.
+        h.step((0.0, 0.0), Some(PlayerAction::CrankStart)).await;
+        for _ in 0..60 {
+            let update = h.step((0.0, 0.0), None).await;
+            assert!(update.wheel.is_cranking || update.wheel.heat > 0.0);
+        }
+        h.step((0.0, 0.0), Some(PlayerAction::CrankStop)).await;
+
+        // Build and recruit.
+        h.step((0.0, 0.0), Some(PlayerAction::PlaceBuilding { building_type: BuildingTypeKind::Pylon, x: 400.0, y: 250.0 }))
+            .await;
+        h.step((0.0, 0.0), Some(PlayerAction::RecruitAgent { entity_id: recruit.to_bits().into() })).await;
+        assert!(h.world.get::<&Recruitable>(recruit).is_err());
+
+        // Fight whatever turns up.
+        h.step((0.0, 0.0), Some(PlayerAction::DebugSpawnRogue { rogue_type: RogueTypeKind::Swarm })).await;
+        for i in 0..200 {
+            let movement = if i % 50 < 25 { (-1.0, 0.0) } else { (1.0, 0.0) };
+            h.step(movement, Some(PlayerAction::Attack)).await;
+        }
+        assert_eq!(h.game_state.tick, 305);
+    }
+
+    #[tokio::test]
+    async fn dead_player_ignores_input() {
+        let mut h = Harness::new();
+        h.step((0.0, 0.0), None).await;
+        for (_e, health) in h.world.query_mut::<&mut Health>().with::<&Player>() {
+            health.current = 0;
+        }
+        let update = h.step((0.0, 0.0), None).await;
+        assert!(update.player.dead);
+
+        let pos = h.player_pos();
+        for _ in 0..50 {
+            let update = h.step((1.0, 1.0), Some(PlayerAction::CrankStart)).await;
+            assert!(update.player.dead);
+            assert!(!update.wheel.is_cranking);
+        }
+        assert_eq!(h.player_pos(), pos);
+        assert!(!h.managers.player_cranking);
+    }
+
+    #[tokio::test]
+    async fn respawn_delay_follows_the_tick_rate() {
+        let mut h = Harness::new();
+        h.config.tick_rate = 40;
+        for (_e, health) in h.world.query_mut::<&mut Health>().with::<&Player>() {
+            health.current = 0;
+        }
+        let update = h.step((0.0, 0.0), None).await;
+        assert!(update.player.dead);
+        assert_eq!(update.player.death_timer, RESPAWN_DELAY_SECS as f32);
+
+        for _ in 1..RESPAWN_DELAY_SECS * 40 {
+            assert!(h.step((0.0, 0.0), None).await.player.dead);
+        }
+        assert!(!h.step((0.0, 0.0), None).await.player.dead);
+    }
+
+    #[tokio::test]
+    async fn actions_sharing_a_tick_with_movement_still_apply() {
+        let mut h = Harness::new();
+        let start = h.player_pos();
+        for action in [None, Some(PlayerAction::CrankStart)] {
+            h.io.inputs.push_back(PlayerInput { tick: 1, movement: Vec2 { x: 1.0, y: 0.0 }, action, target: None });
+        }
+        let update = run_tick(&mut h.world, &mut h.game_state, &mut h.managers, &mut h.io, &h.config).await;
+
+        assert!(h.managers.player_cranking);
+        assert!(update.log_entries.iter().all(|e| !e.text.contains("rejected input")));
+        // Only the first input's movement was applied.
+        let moved = h.player_pos().0 - start.0;
+        assert!(moved > 0.0 && moved <= PLAYER_SPEED);
+    }
+
+    #[tokio::test]
+    async fn regrading_on_cooldown_answers_with_the_cached_grade() {
+        let mut h = Harness::new();
+        h.managers.grading_service.set_api_key("test-key".to_string());
+        h.managers.grading_service.mark_grading("todo_app", 0);
+        h.managers.grading_service.set_grade("todo_app", 4, "tidy".to_string(), 0);
+
+        let start = start_grading(
+            "todo_app",
+            &h.managers.project_manager,
+            &mut h.managers.grading_service,
+            1,
+            &h.managers.grade_result_tx,
+        );
+        assert!(matches!(start, Ok(GradeStart::Cached { stars: 4, .. })));
+
+        h.step((0.0, 0.0), Some(PlayerAction::GradeBuilding { building_id: "todo_app".to_string() })).await;
+        assert!(h.io.sent.iter().any(|msg| matches!(
+            msg,
+            ServerMessage::GradeResult { building_id, stars: 4, reasoning } if building_id == "todo_app" && reasoning == "tidy"
+        )));
+        assert!(h.managers.auto_grading.is_empty());
+        assert!(!h.managers.grading_service.grades["todo_app"].grading);
+    }
+
+    #[tokio::test]
+    async fn doubled_speed_input_does_not_move_the_player() {
+        let mut h = Harness::new();
+        let start = h.player_pos();
+        let update = h.step((2.0, 0.0), None).await;
+
+        assert_eq!(h.player_pos(), start);
+        assert!(update.log_entries.iter().any(|e| e.text.contains("rejected input")));
+    }
+
+    #[tokio::test]
+    async fn input_floods_move_the_player_at_most_the_tick_cap() {
+        let mut h = Harness::new();
+        let start = h.player_pos();
+        for tick in 1..=10 {
+            h.io.inputs.push_back(PlayerInput { tick, movement: Vec2 { x: 1.0, y: 0.0 }, action: None, target: None });
+        }
+        run_tick(&mut h.world, &mut h.game_state, &mut h.managers, &mut h.io, &h.config).await;
+
+        let moved = h.player_pos().0 - start.0;
+        assert!(moved > 0.0 && moved <= validation::MAX_POSITION_DELTA_PER_TICK, "moved {moved}");
+    }
+}
@@ -5,12 +5,60 @@ use noise::{NoiseFn, Simplex};
 pub const CHUNK_SIZE: usize = 32;
 pub const TILE_SIZE: f32 = 16.0;
 
+/// Noise seed for the server's terrain layer.
+pub const TERRAIN_SEED: u32 = 42;
+
+/// Simplex sampling scale; controls terrain feature size.
+const NOISE_SCALE: f64 = 0.05;
+
+/// Movement speed multiplier on Forest tiles.
+pub const FOREST_SPEED_MODIFIER: f32 = 0.7;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Terrain {
     Grass,
     Stone,
     Water,
     Dirt,
+    Forest,
+    Sand,
+}
+
+impl Terrain {
+    /// Stone and Water block movement; everything else can be crossed.
+    pub fn is_walkable(self) -> bool {
+        !matches!(self, Terrain::Stone | Terrain::Water)
+    }
+
+    /// Multiplier applied to movement speed on this terrain.
+    pub fn speed_modifier(self) -> f32 {
+        match self {
+            Terrain::Forest => FOREST_SPEED_MODIFIER,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Terrain for a noise sample in [-1, 1].
+fn terrain_for(value: f64) -> Terrain {
+    if value < -0.5 {
+        Terrain::Water
+    } else if value < -0.1 {
+        Terrain::Sand
+    } else if value < 0.3 {
+        Terrain::Grass
+    } else if value <= 0.5 {
+        Terrain::Forest
+    } else {
+        Terrain::Stone
+    }
+}
+
+/// Terrain of the tile at tile coordinates `(wx, wy)`, matching what
+/// [`Chunk::generate`] produces for the same seed.
+pub fn terrain_at(wx: i32, wy: i32, seed: u32) -> Terrain {
+    let noise_fn = Simplex::new(seed);
+    terrain_for(noise_fn.get([wx as f64 * NOISE_SCALE, wy as f64 * NOISE_SCALE]))
 }
 
 pub struct Chunk {
@@ -32,25 +80,13 @@ impl Chunk {
         let noise_fn = Simplex::new(seed);
         let mut tiles = [[Terrain::Grass; CHUNK_SIZE]; CHUNK_SIZE];
 
-        let scale = 0.05; // Controls terrain feature size
-
-        for ty in 0..CHUNK_SIZE {
-            for tx in 0..CHUNK_SIZE {
+        for (ty, row) in tiles.iter_mut().enumerate() {
+            for (tx, tile) in row.iter_mut().enumerate() {
                 // Convert tile position to world coordinates for noise sampling
-                let world_x = (cx as f64 * CHUNK_SIZE as f64 + tx as f64) * scale;
-                let world_y = (cy as f64 * CHUNK_SIZE as f64 + ty as f64) * scale;
-
-                let value = noise_fn.get([world_x, world_y]);
-
-                tiles[ty][tx] = if value < -0.3 {
-                    Terrain::Water
-                } else if value < 0.0 {
-                    Terrain::Dirt
-                } else if value > 0.5 {
-                    Terrain::Stone
-                } else {
-                    Terrain::Grass
-                };
+                let world_x = (cx as f64 * CHUNK_SIZE as f64 + tx as f64) * NOISE_SCALE;
+                let world_y = (cy as f64 * CHUNK_SIZE as f64 + ty as f64) * NOISE_SCALE;
+
+                *tile = terrain_for(noise_fn.get([world_x, world_y]));
             }
         }
 
@@ -107,6 +143,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn terrain_bands_follow_noise_thresholds() {
+        assert_eq!(terrain_for(-0.8), Terrain::Water);
+        assert_eq!(terrain_for(-0.3), Terrain::Sand);
+        assert_eq!(terrain_for(0.0), Terrain::Grass);
+        assert_eq!(terrain_for(0.4), Terrain::Forest);
+        assert_eq!(terrain_for(0.8), Terrain::Stone);
+    }
+
+    #[test]
+    fn terrain_at_matches_generated_chunks() {
+        let chunk = Chunk::generate(-1, 2, TERRAIN_SEED);
+        for ty in 0..CHUNK_SIZE {
+            for tx in 0..CHUNK_SIZE {
+                let wx = -(CHUNK_SIZE as i32) + tx as i32;
+                let wy = 2 * CHUNK_SIZE as i32 + ty as i32;
+                assert_eq!(terrain_at(wx, wy, TERRAIN_SEED), chunk.tiles[ty][tx]);
+            }
+        }
+    }
+
+    #[test]
+    fn stone_and_water_block_movement() {
+        assert!(!Terrain::Stone.is_walkable());
+        assert!(!Terrain::Water.is_walkable());
+        for terrain in [Terrain::Grass, Terrain::Dirt, Terrain::Forest, Terrain::Sand] {
+            assert!(terrain.is_walkable());
+        }
+    }
+
     #[test]
     fn world_to_chunk_positive() {
         let (cx, cy) = TileMap::world_to_chunk(100.0, 200.0);
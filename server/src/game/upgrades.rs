@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use serde::{Deserialize, Serialize};
 
 use crate::ecs::components::TokenEconomy;
+use crate::ecs::systems::economy::record_transaction;
 
 // ── Upgrade identifiers ─────────────────────────────────────────────
 
@@ -17,6 +18,7 @@ pub enum UpgradeId {
     WebSearch,
     FileSystemAccess,
     CrankAssignment,
+    Specialization,
     // Tier 3 -- Infrastructure
     MultiAgentCoordination,
     PersistentMemory,
@@ -101,6 +103,14 @@ pub fn all_upgrades() -> &'static [UpgradeDef] {
             description: "Assign agent to crank",
             prerequisite: Some(TokenCompression),
         },
+        UpgradeDef {
+            id: Specialization,
+            name: "Specialization",
+            tier: 2,
+            cost: 200,
+            description: "Specialize agents into roles",
+            prerequisite: Some(VerboseLogging),
+        },
         // ── Tier 3 -- Infrastructure ────────────────────────────────
         UpgradeDef {
             id: MultiAgentCoordination,
@@ -164,13 +174,30 @@ pub fn get_upgrade(id: UpgradeId) -> &'static UpgradeDef {
         .expect("unknown upgrade id")
 }
 
+// ── Upgrade effect tuning ───────────────────────────────────────────
+
+/// Agent wage multiplier once Token Compression is purchased.
+pub const TOKEN_COMPRESSION_WAGE_MULT: f64 = 0.75;
+/// Construction speed multiplier once File System Access is purchased.
+pub const FILE_SYSTEM_BUILD_SPEED_MULT: f32 = 1.25;
+/// Rogue spawn chance multiplier once Alignment Protocols is purchased.
+pub const ALIGNMENT_SPAWN_CHANCE_MULT: f32 = 0.5;
+/// Agent XP gain multiplier once Persistent Memory is purchased.
+pub const PERSISTENT_MEMORY_XP_MULT: f64 = 1.25;
+
 // ── Player upgrade state ────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpgradeState {
     pub purchased: HashSet<UpgradeId>,
 }
 
+impl Default for UpgradeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UpgradeState {
     pub fn new() -> Self {
         Self {
@@ -203,6 +230,7 @@ impl UpgradeState {
         &mut self,
         id: UpgradeId,
         economy: &mut TokenEconomy,
+        tick: u64,
     ) -> Result<(), String> {
         if self.purchased.contains(&id) {
             return Err("already purchased".to_string());
@@ -223,7 +251,7 @@ impl UpgradeState {
                 ));
             }
         }
-        economy.balance -= def.cost;
+        record_transaction(economy, -def.cost, &format!("upgrade {}", def.name), tick);
         self.purchased.insert(id);
         Ok(())
     }
@@ -233,30 +261,39 @@ impl UpgradeState {
         self.purchased.contains(&id)
     }
 
-    /// Compute the list of vibe CLI tool names enabled by the current upgrades.
-    ///
-    /// Base tools (always enabled): read_file, grep, search_replace, write_file, todo, task
-    /// Git Access:  + bash
-    /// Web Search:  + web_search, web_fetch
-    pub fn enabled_vibe_tools(&self) -> Vec<String> {
-        let mut tools = vec![
-            "read_file".to_string(),
-            "grep".to_string(),
-            "search_replace".to_string(),
-            "write_file".to_string(),
-            "todo".to_string(),
-            "task".to_string(),
-        ];
+    /// Multiplier applied to agent wages (Token Compression).
+    pub fn wage_multiplier(&self) -> f64 {
+        if self.has(UpgradeId::TokenCompression) {
+            TOKEN_COMPRESSION_WAGE_MULT
+        } else {
+            1.0
+        }
+    }
 
-        if self.has(UpgradeId::GitAccess) {
-            tools.push("bash".to_string());
+    /// Multiplier applied to construction speed (File System Access).
+    pub fn build_speed_multiplier(&self) -> f32 {
+        if self.has(UpgradeId::FileSystemAccess) {
+            FILE_SYSTEM_BUILD_SPEED_MULT
+        } else {
+            1.0
         }
+    }
 
-        if self.has(UpgradeId::WebSearch) {
-            tools.push("web_search".to_string());
-            tools.push("web_fetch".to_string());
+    /// Multiplier applied to the rogue spawn chance (Alignment Protocols).
+    pub fn spawn_chance_multiplier(&self) -> f32 {
+        if self.has(UpgradeId::AlignmentProtocols) {
+            ALIGNMENT_SPAWN_CHANCE_MULT
+        } else {
+            1.0
         }
+    }
 
-        tools
+    /// Multiplier applied to XP agents earn (Persistent Memory).
+    pub fn xp_multiplier(&self) -> f64 {
+        if self.has(UpgradeId::PersistentMemory) {
+            PERSISTENT_MEMORY_XP_MULT
+        } else {
+            1.0
+        }
     }
 }
@@ -4,6 +4,8 @@ use std::collections::HashMap;
 use std::path::Path;
 use tracing;
 
+use crate::protocol::GradeSnapshot;
+
 #[derive(Debug, Clone)]
 pub struct BuildingGrade {
     pub stars: u8,
@@ -12,9 +14,20 @@ pub struct BuildingGrade {
     pub grading: bool,
 }
 
+/// Minimum ticks between grading requests for the same building (30s at 20Hz).
+pub const GRADE_COOLDOWN_TICKS: u64 = 600;
+
 pub struct GradingService {
     pub api_key: Option<String>,
     pub grades: HashMap<String, BuildingGrade>,
+    /// Tick at which each building was last sent off for grading.
+    pub last_graded_at: HashMap<String, u64>,
+}
+
+impl Default for GradingService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GradingService {
@@ -28,6 +41,7 @@ impl GradingService {
         Self {
             api_key,
             grades: HashMap::new(),
+            last_graded_at: HashMap::new(),
         }
     }
 
@@ -40,7 +54,25 @@ impl GradingService {
         self.api_key.is_some()
     }
 
-    pub fn mark_grading(&mut self, building_id: &str) {
+    /// Whether `building_id` is off cooldown at `current_tick`.
+    pub fn can_grade(&self, building_id: &str, current_tick: u64) -> bool {
+        self.last_graded_at
+            .get(building_id)
+            .is_none_or(|&at| current_tick.saturating_sub(at) >= GRADE_COOLDOWN_TICKS)
+    }
+
+    /// The grade to hand back instead of re-grading while `building_id` is
+    /// on cooldown, or `None` if a new request may go out.
+    pub fn cached_grade(&self, building_id: &str, current_tick: u64) -> Option<&BuildingGrade> {
+        if self.can_grade(building_id, current_tick) {
+            return None;
+        }
+        self.grades.get(building_id)
+    }
+
+    /// Flags `building_id` as being graded and starts its cooldown.
+    pub fn mark_grading(&mut self, building_id: &str, tick: u64) {
+        self.last_graded_at.insert(building_id.to_string(), tick);
         if let Some(grade) = self.grades.get_mut(building_id) {
             grade.grading = true;
         } else {
@@ -68,7 +100,30 @@ impl GradingService {
         );
     }
 
+    /// Every known grade, ordered by building id.
+    pub fn snapshots(&self) -> Vec<GradeSnapshot> {
+        let mut snapshots: Vec<GradeSnapshot> = self
+            .grades
+            .iter()
+            .map(|(id, grade)| GradeSnapshot {
+                building_id: id.clone(),
+                stars: grade.stars,
+                reasoning: grade.reasoning.clone(),
+                grading: grade.grading,
+            })
+            .collect();
+        snapshots.sort_by(|a, b| a.building_id.cmp(&b.building_id));
+        snapshots
+    }
+
     pub fn get_multiplier(&self, building_id: &str) -> f64 {
+        self.get_multiplier_with_bonus(building_id, 0)
+    }
+
+    /// Like [`get_multiplier`](Self::get_multiplier), but as if the grade
+    /// were `bonus_stars` higher (capped at 6). Ungraded buildings keep the
+    /// default multiplier.
+    pub fn get_multiplier_with_bonus(&self, building_id: &str, bonus_stars: u8) -> f64 {
         match self.grades.get(building_id) {
             None => 1.0,
             Some(grade) => {
@@ -76,7 +131,7 @@ impl GradingService {
                 if grade.grading && grade.stars == 0 {
                     return 1.0;
                 }
-                match grade.stars {
+                match grade.stars.saturating_add(bonus_stars).min(6) {
                     0 => 0.0,
                     1 => 0.5,
                     2 => 1.0,
@@ -100,6 +155,7 @@ pub fn read_project_sources(project_dir: &Path) -> Result<Vec<(String, String)>,
     let mut results: Vec<(String, String)> = Vec::new();
     let mut total_size: usize = 0;
 
+    #[allow(clippy::too_many_arguments)]
     fn walk_dir(
         dir: &Path,
         base: &Path,
@@ -255,3 +311,23 @@ You MUST respond with ONLY a JSON object in this exact format, no other text:
 
     Ok((stars, reasoning))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regrading_within_cooldown_returns_cached_grade() {
+        let mut service = GradingService { api_key: None, grades: HashMap::new(), last_graded_at: HashMap::new() };
+        assert!(service.can_grade("todo_app", 0));
+        service.mark_grading("todo_app", 0);
+        service.set_grade("todo_app", 4, "solid".to_string(), 40);
+
+        assert!(!service.can_grade("todo_app", 100));
+        assert_eq!(service.cached_grade("todo_app", 100).map(|g| g.stars), Some(4));
+
+        assert!(service.can_grade("todo_app", 601));
+        assert!(service.cached_grade("todo_app", 601).is_none());
+        assert!(service.can_grade("weather_app", 100));
+    }
+}
@@ -8,6 +8,7 @@ pub fn get_rubric(building_id: &str) -> &'static str {
         "weather_dashboard" => WEATHER_DASHBOARD_RUBRIC,
         "chat_app" => CHAT_APP_RUBRIC,
         "kanban_board" => KANBAN_BOARD_RUBRIC,
+        "watchtower" => WATCHTOWER_RUBRIC,
         "ecommerce_store" => ECOMMERCE_STORE_RUBRIC,
         "ai_image_generator" => AI_IMAGE_GENERATOR_RUBRIC,
         "api_dashboard" => API_DASHBOARD_RUBRIC,
@@ -176,6 +177,22 @@ GRADING RUBRIC - Blockchain Explorer
 6 STARS: All of 5-star PLUS: 3D block chain visualization with WebGL/Three.js. Live transaction mempool visualization. Token transfer tracking with animated flow paths. Smart contract interaction interface. Gas price oracle with animated chart. Rich address analytics. Etherscan-quality professional blockchain explorer.
 "#;
 
+const WATCHTOWER_RUBRIC: &str = r#"
+GRADING RUBRIC - Watchtower (Uptime Monitor)
+
+1 STAR: Static list of service names. No status checks, no styling.
+
+2 STARS: Services shown with up/down status badges. Manual refresh. Basic card layout.
+
+3 STARS: Periodic status polling. Response time per service. Incident list. Responsive layout. Loading and error states.
+
+4 STARS: Uptime percentage history (24h/7d/30d). Response time charts. Add/remove monitored endpoints. Alert thresholds. Public status page view.
+
+5 STARS: Framer Motion status transitions and animated charts. Live incident timeline. Dark mode. Smooth drill-down from overview to service detail.
+
+6 STARS: All of 5-star PLUS: World map of probe locations with animated pings. Maintenance scheduling. Subscriber notifications. Professional, production-grade status page design.
+"#;
+
 const DEFAULT_RUBRIC: &str = r#"
 GRADING RUBRIC - General Web Application
 
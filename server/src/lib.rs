@@ -1,8 +1,10 @@
 pub mod ai;
+pub mod config;
 pub mod ecs;
 pub mod game;
 pub mod grading;
 pub mod network;
 pub mod project;
 pub mod protocol;
+pub mod shutdown;
 pub mod vibe;
@@ -0,0 +1,393 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::config::DEFAULT_TICK_RATE_HZ;
+use crate::protocol::{EntityDelta, EntityId, GameStateUpdate, GameStateUpdateDelta, ServerMessage, Tick, Vec2};
+
+/// Seconds between full `GameState` keyframes, so a client that dropped a
+/// delta resyncs within this long.
+pub const KEYFRAME_INTERVAL_SECS: u64 = 5;
+
+/// Positions closer than this (pixels) count as unchanged.
+pub const POSITION_QUANTUM: f32 = 0.1;
+
+/// `entity` with its position snapped to the [`POSITION_QUANTUM`] grid.
+fn quantized(entity: &EntityDelta) -> EntityDelta {
+    let snap = |v: f32| (v / POSITION_QUANTUM).round() * POSITION_QUANTUM;
+    EntityDelta {
+        position: Vec2 { x: snap(entity.position.x), y: snap(entity.position.y) },
+        ..entity.clone()
+    }
+}
+
+/// Tracks the last update sent to the client and reduces each new update to
+/// the entities that actually changed.
+#[derive(Debug)]
+pub struct DeltaEncoder {
+    base_tick: Option<Tick>,
+    keyframe_tick: Option<Tick>,
+    /// Ticks between keyframes: [`KEYFRAME_INTERVAL_SECS`] at the tick rate.
+    keyframe_interval: Tick,
+    previous: HashMap<EntityId, EntityDelta>,
+}
+
+impl Default for DeltaEncoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_TICK_RATE_HZ)
+    }
+}
+
+impl DeltaEncoder {
+    /// An encoder for a game running at `tick_rate` ticks per second.
+    pub fn new(tick_rate: u64) -> Self {
+        Self {
+            base_tick: None,
+            keyframe_tick: None,
+            keyframe_interval: KEYFRAME_INTERVAL_SECS * tick_rate,
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Ticks between keyframes.
+    pub fn keyframe_interval(&self) -> Tick {
+        self.keyframe_interval
+    }
+
+    /// Whether a previous update exists to diff against. Without one the
+    /// caller should send the full update.
+    pub fn has_base(&self) -> bool {
+        self.base_tick.is_some()
+    }
+
+    /// Forget the previous update, e.g. when a new client connects. The
+    /// keyframe interval is kept.
+    pub fn reset(&mut self) {
+        self.base_tick = None;
+        self.keyframe_tick = None;
+        self.previous.clear();
+    }
+
+    /// Whether the update at `tick` should go out in full: there is no base
+    /// yet, the keyframe interval has passed since the last keyframe,
+    /// or the tick went backwards (an older save was loaded).
+    pub fn keyframe_due(&self, tick: Tick) -> bool {
+        match self.keyframe_tick {
+            Some(last) => tick < last || tick - last >= self.keyframe_interval,
+            None => true,
+        }
+    }
+
+    /// The message to send for `current`: a full `GameState` keyframe when
+    /// one is due, otherwise a `GameStateDelta`. Either way `current`
+    /// becomes the new base.
+    pub fn next_message(&mut self, current: &GameStateUpdate) -> ServerMessage {
+        if self.has_base() && !self.keyframe_due(current.tick) {
+            return ServerMessage::GameStateDelta(self.encode(current));
+        }
+        let delta = self.encode(current);
+        self.keyframe_tick = Some(current.tick);
+        ServerMessage::GameState(GameStateUpdate {
+            entities_removed: delta.removed,
+            ..current.clone()
+        })
+    }
+
+    /// Diff `current` against the previous update and make it the new base.
+    ///
+    /// `changed` holds entities that are new or whose data or quantized
+    /// position differ; `removed` holds everything in `current.entities_removed` plus
+    /// any entity from the base that is missing from `current`.
+    ///
+    /// An id that is both removed and present (a despawned entity whose id
+    /// the world handed out again, e.g. after a load) is sent as changed in
+    /// full and left out of `removed`, since the client applies removals
+    /// first.
+    pub fn encode(&mut self, current: &GameStateUpdate) -> GameStateUpdateDelta {
+        let present: HashSet<EntityId> = current.entities_changed.iter().map(|e| e.id).collect();
+        let already: HashSet<EntityId> = current.entities_removed.iter().copied().collect();
+
+        let changed: Vec<EntityDelta> = current
+            .entities_changed
+            .iter()
+            .filter(|e| already.contains(&e.id) || self.previous.get(&e.id) != Some(&quantized(e)))
+            .cloned()
+            .collect();
+
+        let mut removed: Vec<EntityId> = current
+            .entities_removed
+            .iter()
+            .filter(|id| !present.contains(id))
+            .copied()
+            .collect();
+        let mut vanished: Vec<EntityId> = self
+            .previous
+            .keys()
+            .filter(|id| !present.contains(id) && !already.contains(id))
+            .copied()
+            .collect();
+        vanished.sort_unstable();
+        removed.extend(vanished);
+
+        let base_tick = self.base_tick.unwrap_or(0);
+        self.base_tick = Some(current.tick);
+        self.previous = current
+            .entities_changed
+            .iter()
+            .map(|e| (e.id, quantized(e)))
+            .collect();
+
+        let mut state = current.clone();
+        state.entities_changed = Vec::new();
+        state.entities_removed = Vec::new();
+
+        GameStateUpdateDelta {
+            base_tick,
+            changed,
+            removed,
+            state,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::protocol::*;
+
+    /// A minimal update at `tick` holding `entities`.
+    pub(crate) fn update(tick: Tick, entities: Vec<EntityDelta>) -> GameStateUpdate {
+        GameStateUpdate {
+            protocol_version: PROTOCOL_VERSION,
+            tick,
+            player: PlayerSnapshot {
+                position: Vec2::default(),
+                health: 100.0,
+                max_health: 100.0,
+                tokens: 0,
+                torch_range: 160.0,
+                facing: Vec2::default(),
+                dead: false,
+                death_timer: 0.0,
+                attack_cooldown_pct: 0.0,
+                weapon_durability_pct: 1.0,
+                dodge_cooldown_remaining: 0,
+                carry_weight: 0,
+                carry_capacity: 0,
+            },
+            entities_changed: entities,
+            entities_removed: Vec::new(),
+            fog_updates: Vec::new(),
+            minimap: None,
+            economy: EconomySnapshot {
+                balance: 0,
+                income_per_sec: 0.0,
+                expenditure_per_sec: 0.0,
+                income_sources: Vec::new(),
+                expenditure_sinks: Vec::new(),
+            },
+            log_entries: Vec::new(),
+            audio_triggers: Vec::new(),
+            debug: DebugSnapshot {
+                spawning_enabled: true,
+                god_mode: false,
+                phase: GamePhase::Hut,
+                crank_tier: CrankTier::HandCrank,
+                fractional: 0.0,
+                connected_spectators: 0,
+                phase_progress: HashMap::new(),
+            },
+            wheel: WheelSnapshot {
+                tier: CrankTier::HandCrank,
+                tokens_per_rotation: 1.0,
+                agent_bonus_per_tick: 0.0,
+                heat: 0.0,
+                max_heat: 100.0,
+                is_cranking: false,
+                assigned_agent_ids: Vec::new(),
+                upgrade_cost: None,
+                overheated_remaining: 0,
+                overheating: false,
+            },
+            project_manager: None,
+            grades: Vec::new(),
+            combat_events: Vec::new(),
+            player_hit: false,
+            player_hit_damage: 0,
+            inventory: Vec::new(),
+            purchased_upgrades: Vec::new(),
+            opened_chests: Vec::new(),
+            chest_rewards: Vec::new(),
+            transaction_log: None,
+            active_investments: Vec::new(),
+            drops: Vec::new(),
+            recipes_available: Vec::new(),
+            wave: WaveSnapshot { wave_number: 0, next_wave_in_ticks: 0, active: false },
+            active_synergies: Vec::new(),
+            vibe_metrics: HashMap::new(),
+        }
+    }
+
+    fn rogue(id: EntityId, x: f32) -> EntityDelta {
+        EntityDelta {
+            id,
+            kind: EntityKind::Rogue,
+            position: Vec2 { x, y: 0.0 },
+            data: EntityData::Rogue {
+                rogue_type: RogueTypeKind::Swarm,
+                health_pct: 1.0,
+                status_effects: Vec::new(),
+                visible: true,
+                in_pack: false,
+                knockback_active: false,
+                mimic_phase: None,
+            },
+        }
+    }
+
+    #[test]
+    fn only_moving_entities_are_sent() {
+        let mut encoder = DeltaEncoder::default();
+        let first: Vec<EntityDelta> = (0..50).map(|id| rogue(id, id as f32)).collect();
+        let full = update(1, first.clone());
+        assert!(!encoder.has_base());
+        let initial = encoder.encode(&full);
+        assert_eq!(initial.changed.len(), 50);
+
+        // Next tick: five of the fifty move.
+        let mut second = first;
+        for e in second.iter_mut().take(5) {
+            e.position.x += 1.0;
+        }
+        let next = update(2, second);
+        let delta = encoder.encode(&next);
+
+        assert_eq!(delta.base_tick, 1);
+        let moved: Vec<EntityId> = delta.changed.iter().map(|e| e.id).collect();
+        assert_eq!(moved, vec![0, 1, 2, 3, 4]);
+        assert!(delta.removed.is_empty());
+        assert!(delta.state.entities_changed.is_empty());
+
+        let full_bytes = rmp_serde::to_vec_named(&ServerMessage::GameState(next)).unwrap().len();
+        let delta_bytes = rmp_serde::to_vec_named(&ServerMessage::GameStateDelta(delta)).unwrap().len();
+        assert!(
+            delta_bytes * 3 < full_bytes,
+            "delta {} bytes vs full {} bytes",
+            delta_bytes,
+            full_bytes
+        );
+    }
+
+    #[test]
+    fn missing_and_despawned_entities_are_removed() {
+        let mut encoder = DeltaEncoder::default();
+        encoder.encode(&update(1, vec![rogue(1, 0.0), rogue(2, 0.0), rogue(3, 0.0)]));
+
+        let mut next = update(2, vec![rogue(1, 0.0)]);
+        next.entities_removed = vec![2];
+        let delta = encoder.encode(&next);
+
+        assert!(delta.changed.is_empty());
+        assert_eq!(delta.removed, vec![2, 3]);
+    }
+
+    #[test]
+    fn keyframe_interval_follows_the_tick_rate() {
+        assert_eq!(DeltaEncoder::default().keyframe_interval(), KEYFRAME_INTERVAL_SECS * DEFAULT_TICK_RATE_HZ);
+        assert_eq!(DeltaEncoder::new(60).keyframe_interval(), KEYFRAME_INTERVAL_SECS * 60);
+    }
+
+    #[test]
+    fn reset_forces_full_resend() {
+        let mut encoder = DeltaEncoder::default();
+        let full = update(1, vec![rogue(1, 0.0), rogue(2, 0.0)]);
+        encoder.encode(&full);
+        assert!(encoder.encode(&full).changed.is_empty());
+
+        encoder.reset();
+        assert!(!encoder.has_base());
+        assert_eq!(encoder.encode(&full).changed.len(), 2);
+    }
+
+    #[test]
+    fn sub_quantum_jitter_is_not_sent() {
+        let mut encoder = DeltaEncoder::default();
+        encoder.encode(&update(1, vec![rogue(1, 10.0), rogue(2, 10.0)]));
+
+        let delta = encoder.encode(&update(2, vec![rogue(1, 10.01), rogue(2, 10.5)]));
+        let ids: Vec<EntityId> = delta.changed.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![2]);
+    }
+
+    #[test]
+    fn stable_entities_return_on_keyframe() {
+        let mut encoder = DeltaEncoder::default();
+        let world: Vec<EntityDelta> = (0..10).map(|id| rogue(id, id as f32)).collect();
+
+        let ServerMessage::GameState(first) = encoder.next_message(&update(1, world.clone())) else {
+            panic!("first update should be a keyframe");
+        };
+        assert_eq!(first.entities_changed.len(), 10);
+
+        for tick in 2..1 + encoder.keyframe_interval() {
+            match encoder.next_message(&update(tick, world.clone())) {
+                ServerMessage::GameStateDelta(delta) => {
+                    assert!(delta.changed.is_empty());
+                    assert!(delta.removed.is_empty());
+                }
+                other => panic!("expected a delta at tick {tick}, got {other:?}"),
+            }
+        }
+
+        let last = update(1 + encoder.keyframe_interval(), world);
+        let ServerMessage::GameState(keyframe) = encoder.next_message(&last) else {
+            panic!("expected a keyframe");
+        };
+        assert_eq!(keyframe.entities_changed.len(), 10);
+    }
+
+    #[test]
+    fn removals_are_exact_across_keyframes() {
+        let mut encoder = DeltaEncoder::default();
+        encoder.next_message(&update(1, vec![rogue(1, 0.0), rogue(2, 0.0)]));
+        encoder.next_message(&update(1 + encoder.keyframe_interval(), vec![rogue(1, 0.0), rogue(2, 0.0)]));
+
+        let next = update(2 + encoder.keyframe_interval(), vec![rogue(1, 0.0)]);
+        let ServerMessage::GameStateDelta(delta) = encoder.next_message(&next) else {
+            panic!("expected a delta after the keyframe");
+        };
+        assert_eq!(delta.removed, vec![2]);
+    }
+
+    #[test]
+    fn reused_ids_are_resent_rather_than_removed() {
+        let mut encoder = DeltaEncoder::default();
+        encoder.next_message(&update(1, vec![rogue(1, 0.0), rogue(2, 0.0)]));
+
+        // A load replaced the world; id 1 came back with the same data.
+        let mut next = update(2, vec![rogue(1, 0.0)]);
+        next.entities_removed = vec![1, 2];
+        let ServerMessage::GameStateDelta(delta) = encoder.next_message(&next) else {
+            panic!("expected a delta");
+        };
+        let ids: Vec<EntityId> = delta.changed.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1]);
+        assert_eq!(delta.removed, vec![2]);
+    }
+
+    #[test]
+    fn tick_going_backwards_sends_a_keyframe() {
+        let mut encoder = DeltaEncoder::default();
+        encoder.next_message(&update(5000, vec![rogue(1, 0.0)]));
+        assert!(matches!(
+            encoder.next_message(&update(5001, vec![rogue(1, 0.0)])),
+            ServerMessage::GameStateDelta(_)
+        ));
+
+        // An older save was loaded.
+        assert!(encoder.keyframe_due(300));
+        assert!(matches!(encoder.next_message(&update(300, vec![rogue(1, 0.0)])), ServerMessage::GameState(_)));
+        assert!(matches!(
+            encoder.next_message(&update(301, vec![rogue(1, 0.0)])),
+            ServerMessage::GameStateDelta(_)
+        ));
+    }
+}
@@ -1,7 +1,87 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
 use tracing::{error, info};
 
+use crate::protocol::{GamePhase, ProjectStatusDto};
+
+/// Read-only view of the game served by `GET /status`. The game loop
+/// refreshes it about once a second.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatusSnapshot {
+    pub tick: u64,
+    pub phase: GamePhase,
+    pub balance: i64,
+    pub agent_count: usize,
+    pub rogue_count: usize,
+    /// building_id -> status, as in `ProjectManagerState`.
+    pub building_statuses: HashMap<String, ProjectStatusDto>,
+    pub client_connected: bool,
+}
+
+/// Status snapshot shared between the game loop (writer) and the HTTP task.
+pub type SharedStatus = Arc<RwLock<StatusSnapshot>>;
+
+/// What an incoming request asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// CORS preflight for any path.
+    Preflight,
+    /// `GET /status`.
+    Status,
+    /// `POST /pick-folder` (or the client's `/api/browse-directory`).
+    PickFolder,
+    /// A known path with the wrong method.
+    MethodNotAllowed,
+    NotFound,
+    /// Not a parseable HTTP request line.
+    BadRequest,
+}
+
+/// Routes a raw HTTP request by the method and path on its request line.
+/// Query strings are ignored.
+pub fn route(request: &[u8]) -> Route {
+    let line_end = request.iter().position(|&b| b == b'\r' || b == b'\n').unwrap_or(request.len());
+    let Ok(line) = std::str::from_utf8(&request[..line_end]) else {
+        return Route::BadRequest;
+    };
+    let mut parts = line.split(' ');
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Route::BadRequest;
+    };
+    if !version.starts_with("HTTP/") || parts.next().is_some() {
+        return Route::BadRequest;
+    }
+    if method == "OPTIONS" {
+        return Route::Preflight;
+    }
+    let path = target.split('?').next().unwrap_or(target);
+    match (method, path) {
+        ("GET", "/status") => Route::Status,
+        ("POST", "/pick-folder" | "/api/browse-directory") => Route::PickFolder,
+        (_, "/status" | "/pick-folder" | "/api/browse-directory") => Route::MethodNotAllowed,
+        _ => Route::NotFound,
+    }
+}
+
+/// A JSON response with the CORS header the browser client needs.
+fn json_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n\
+        Content-Type: application/json\r\n\
+        Access-Control-Allow-Origin: *\r\n\
+        Content-Length: {}\r\n\
+        \r\n\
+        {}",
+        status,
+        body.len(),
+        body,
+    )
+}
+
 /// Open a native macOS folder picker using osascript (AppleScript).
 /// Works from any thread/context — no windowed environment needed.
 async fn pick_folder() -> Option<String> {
@@ -29,18 +109,19 @@ async fn pick_folder() -> Option<String> {
 
 /// Lightweight HTTP API server for pre-game operations (e.g. native file dialog).
 ///
-/// Runs on port 9002, separate from the WebSocket game server.
-/// Currently supports a single operation: opening a native directory picker.
-pub async fn start() {
-    let listener = match TcpListener::bind("127.0.0.1:9002").await {
+/// Runs on `addr` (127.0.0.1:9002 by default), separate from the WebSocket
+/// game server.  Serves `GET /status` from `status` and opens a native
+/// directory picker on `POST /pick-folder`; anything else is a 404.
+pub async fn start(addr: String, status: SharedStatus) {
+    let listener = match TcpListener::bind(&addr).await {
         Ok(l) => l,
         Err(e) => {
-            error!("Failed to bind HTTP API on 127.0.0.1:9002: {}", e);
+            error!("Failed to bind HTTP API on {}: {}", addr, e);
             return;
         }
     };
 
-    info!("HTTP API listening on http://127.0.0.1:9002");
+    info!("HTTP API listening on http://{}", addr);
 
     loop {
         let (mut stream, _) = match listener.accept().await {
@@ -51,48 +132,88 @@ pub async fn start() {
             }
         };
 
+        let status = status.clone();
         tokio::spawn(async move {
             let mut buf = [0u8; 4096];
             let n = match stream.read(&mut buf).await {
                 Ok(n) => n,
                 Err(_) => return,
             };
-            let request = String::from_utf8_lossy(&buf[..n]);
 
-            // CORS preflight
-            if request.starts_with("OPTIONS") {
-                let response = "HTTP/1.1 204 No Content\r\n\
+            let response = match route(&buf[..n]) {
+                Route::Preflight => "HTTP/1.1 204 No Content\r\n\
                     Access-Control-Allow-Origin: *\r\n\
                     Access-Control-Allow-Methods: POST, GET, OPTIONS\r\n\
                     Access-Control-Allow-Headers: Content-Type\r\n\
-                    \r\n";
-                let _ = stream.write_all(response.as_bytes()).await;
-                return;
-            }
-
-            // Open native directory picker via osascript
-            let folder = pick_folder().await;
-
-            let body = if let Some(path) = folder {
-                format!(
-                    "{{\"path\":{}}}",
-                    serde_json::to_string(&path).unwrap_or_else(|_| "null".to_string())
-                )
-            } else {
-                "{\"path\":null}".to_string()
+                    \r\n"
+                    .to_string(),
+                Route::Status => {
+                    let snapshot = status.read().map(|s| s.clone()).unwrap_or_default();
+                    let body = serde_json::to_string(&snapshot).unwrap_or_else(|_| "{}".to_string());
+                    json_response("200 OK", &body)
+                }
+                Route::PickFolder => {
+                    // Open native directory picker via osascript
+                    let body = match pick_folder().await {
+                        Some(path) => format!(
+                            "{{\"path\":{}}}",
+                            serde_json::to_string(&path).unwrap_or_else(|_| "null".to_string())
+                        ),
+                        None => "{\"path\":null}".to_string(),
+                    };
+                    json_response("200 OK", &body)
+                }
+                Route::MethodNotAllowed => {
+                    json_response("405 Method Not Allowed", "{\"error\":\"method not allowed\"}")
+                }
+                Route::NotFound => json_response("404 Not Found", "{\"error\":\"not found\"}"),
+                Route::BadRequest => json_response("400 Bad Request", "{\"error\":\"bad request\"}"),
             };
-
-            let response = format!(
-                "HTTP/1.1 200 OK\r\n\
-                Content-Type: application/json\r\n\
-                Access-Control-Allow-Origin: *\r\n\
-                Content-Length: {}\r\n\
-                \r\n\
-                {}",
-                body.len(),
-                body,
-            );
             let _ = stream.write_all(response.as_bytes()).await;
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_by_method_and_path() {
+        assert_eq!(route(b"GET /status HTTP/1.1\r\nHost: 127.0.0.1:9002\r\n\r\n"), Route::Status);
+        assert_eq!(route(b"GET /status?pretty=1 HTTP/1.1\r\n\r\n"), Route::Status);
+        assert_eq!(route(b"POST /pick-folder HTTP/1.1\r\nContent-Length: 0\r\n\r\n"), Route::PickFolder);
+        assert_eq!(route(b"POST /api/browse-directory HTTP/1.1\r\n\r\n"), Route::PickFolder);
+        assert_eq!(route(b"OPTIONS /pick-folder HTTP/1.1\r\n\r\n"), Route::Preflight);
+    }
+
+    #[test]
+    fn stray_requests_never_open_the_dialog() {
+        assert_eq!(route(b"GET /pick-folder HTTP/1.1\r\n\r\n"), Route::MethodNotAllowed);
+        assert_eq!(route(b"POST /status HTTP/1.1\r\n\r\n"), Route::MethodNotAllowed);
+        assert_eq!(route(b"GET / HTTP/1.1\r\n\r\n"), Route::NotFound);
+        assert_eq!(route(b"GET /favicon.ico HTTP/1.1\r\n\r\n"), Route::NotFound);
+        assert_eq!(route(b""), Route::BadRequest);
+        assert_eq!(route(b"garbage\r\n\r\n"), Route::BadRequest);
+        assert_eq!(route(b"GET /status HTTP/1.1 extra\r\n"), Route::BadRequest);
+        assert_eq!(route(&[0xff, 0xfe, b' ', b'/', b' ', b'H']), Route::BadRequest);
+    }
+
+    #[test]
+    fn status_serializes_as_json() {
+        let snapshot = StatusSnapshot {
+            tick: 40,
+            phase: GamePhase::Hut,
+            balance: 12,
+            agent_count: 1,
+            rogue_count: 2,
+            building_statuses: HashMap::from([("todo_app".to_string(), ProjectStatusDto::Ready)]),
+            client_connected: true,
+        };
+        let json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["tick"], 40);
+        assert_eq!(json["building_statuses"]["todo_app"], "Ready");
+        assert_eq!(json["client_connected"], true);
+        assert!(json_response("200 OK", "{}").starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+}
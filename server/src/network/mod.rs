@@ -1,2 +1,5 @@
+pub mod delta;
 pub mod http_api;
 pub mod server;
+pub mod snapshot;
+pub mod validation;
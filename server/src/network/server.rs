@@ -1,135 +1,796 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use futures_util::{SinkExt, StreamExt};
-use tokio::net::TcpListener;
-use tokio::sync::mpsc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Notify};
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 use tracing::{error, info, warn};
 
-use crate::protocol::{GameStateUpdate, PlayerInput, ServerMessage};
+use crate::network::delta::DeltaEncoder;
+use crate::protocol::{
+    compatibility_check, GameStateUpdate, PlayerInput, ServerMessage, Tick, VersionedMessage,
+    PROTOCOL_VERSION,
+};
+
+/// How often the write task pings the client.
+pub const PING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A client that has sent nothing (not even a pong) for this long is
+/// considered unresponsive.
+pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Inputs more than this many ticks older than the newest input from the
+/// same connection are dropped as stale.
+pub const MAX_INPUT_AGE_TICKS: Tick = 300;
 
 /// Channel for sending serialized state frames to the connected client.
 type StateTx = mpsc::UnboundedSender<Vec<u8>>;
 
+/// Decodes a client frame as a `VersionedMessage<PlayerInput>`, or a bare
+/// `PlayerInput` from clients that predate versioning.
+///
+/// On failure (an unknown action variant, a missing field, or an
+/// incompatible major version) returns the client's protocol version, or 0
+/// if it couldn't be read.
+pub fn decode_input(data: &[u8]) -> Result<PlayerInput, u32> {
+    if let Ok(msg) = rmp_serde::from_slice::<VersionedMessage<PlayerInput>>(data) {
+        return if compatibility_check(msg.protocol_version, PROTOCOL_VERSION) {
+            Ok(msg.payload)
+        } else {
+            Err(msg.protocol_version)
+        };
+    }
+    if let Ok(input) = rmp_serde::from_slice::<PlayerInput>(data) {
+        return Ok(input);
+    }
+    Err(rmp_serde::from_slice::<VersionedMessage<serde::de::IgnoredAny>>(data)
+        .map_or(0, |msg| msg.protocol_version))
+}
+
+/// Connection status of the (single) game client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientState {
+    /// A client is connected and receiving state.
+    Connected,
+    /// No client is connected.
+    Disconnected,
+    /// No client is connected, and a WebSocket handshake is in flight.
+    Reconnecting,
+}
+
+/// State shared between the game loop and the background accept tasks.
+struct Shared {
+    client_tx: Mutex<Option<StateTx>>,
+    /// A read-only second connection that is sent everything the client is
+    /// but whose input is ignored.
+    spectator_tx: Mutex<Option<StateTx>>,
+    /// Like `connection_id`, for spectator connections.
+    spectator_id: AtomicU64,
+    state: Mutex<ClientState>,
+    /// Bumped for every accepted client so a stale read task can't mark a
+    /// newer connection as disconnected.
+    connection_id: AtomicU64,
+    /// Set when a client connects after the first one; the game loop
+    /// consumes it via `take_reconnected` to resend the full state.
+    reconnected: AtomicBool,
+    connected: Notify,
+    /// When the client last sent anything (input, pong or otherwise).
+    last_seen: Mutex<Instant>,
+}
+
 /// The game network server.
 ///
-/// Listens for a single client WebSocket connection and provides methods
-/// to send state updates and receive player input.
+/// Listens for WebSocket clients in the background and provides methods to
+/// send state updates and receive player input. Only one client is served
+/// at a time: a new connection replaces the previous one, so the browser can
+/// reload or reconnect without restarting the server. One spectator may
+/// also watch from a second port (see [`GameServer::listen_for_spectators`]),
+/// replaced the same way.
 pub struct GameServer {
-    /// Sender half – the game loop calls `send_state` which serializes and
-    /// forwards the bytes through this channel to the write task.
-    client_tx: Option<StateTx>,
+    shared: Arc<Shared>,
 
     /// Receiver half – the game loop drains this to get decoded `PlayerInput`.
     pub input_rx: mpsc::UnboundedReceiver<PlayerInput>,
 
-    /// Sender half kept around so the read-task can push decoded inputs.
+    /// Sender half kept around so read tasks can push decoded inputs.
     #[allow(dead_code)]
     input_tx: mpsc::UnboundedSender<PlayerInput>,
-}
 
-impl GameServer {
-    /// Bind the TCP listener and wait for exactly one WebSocket client to
-    /// connect. Once connected, two background tasks are spawned:
-    ///
-    /// 1. **Write task** – forwards serialized binary frames from `client_tx`
-    ///    to the WebSocket sink.
-    /// 2. **Read task** – reads binary frames from the WebSocket stream,
-    ///    decodes them as `PlayerInput`, and pushes them into `input_tx`.
-    pub async fn start() -> Self {
-        let (input_tx, input_rx) = mpsc::unbounded_channel::<PlayerInput>();
+    local_addr: SocketAddr,
 
-        let listener = TcpListener::bind("127.0.0.1:9001")
-            .await
-            .expect("Failed to bind to 127.0.0.1:9001");
+    /// Diffs state updates against the last one sent.
+    delta: DeltaEncoder,
+    /// Connection the encoder's base was sent to; a new connection resets it.
+    delta_connection: u64,
+    /// The spectator's own encoder, as it connects at a different time.
+    spectator_delta: DeltaEncoder,
+    spectator_delta_connection: u64,
 
-        info!("Game server listening on ws://127.0.0.1:9001");
-        info!("Waiting for a client connection...");
+    /// Newest `PlayerInput.tick` received, for dropping stale inputs.
+    newest_input_tick: Option<Tick>,
+    /// Connection `newest_input_tick` belongs to; a new connection resets it.
+    input_connection: u64,
+    /// Tick of the last input the game loop accepted from this connection,
+    /// for rejecting duplicates. A new connection resets it to 0.
+    last_input_tick: Tick,
+}
 
-        // Accept exactly one connection.
-        let (stream, addr) = listener
-            .accept()
+impl GameServer {
+    /// Bind `addr` and `spectator_addr`, then wait for the first WebSocket
+    /// client. A spectator may connect before or after it.
+    pub async fn start(addr: &str, spectator_addr: &str) -> Self {
+        let server = Self::bind(addr)
             .await
-            .expect("Failed to accept connection");
-        info!("Client connected from {}", addr);
-
-        let ws_stream = accept_async(stream)
+            .unwrap_or_else(|e| panic!("Failed to bind to {}: {}", addr, e));
+        let spectator_local = server
+            .listen_for_spectators(spectator_addr)
             .await
-            .expect("WebSocket handshake failed");
+            .unwrap_or_else(|e| panic!("Failed to bind to {}: {}", spectator_addr, e));
 
-        let (mut ws_write, mut ws_read) = ws_stream.split();
+        info!("Game server listening on ws://{}", server.local_addr());
+        info!("Spectators can watch on ws://{}", spectator_local);
+        info!("Waiting for a client connection...");
+        server.wait_for_client().await;
+        server
+    }
 
-        // Channel: game loop -> write task -> WebSocket
-        let (client_tx, mut client_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    /// Bind the TCP listener and spawn the accept task without waiting for a
+    /// client. Every accepted connection gets two background tasks:
+    ///
+    /// 1. **Write task** – forwards serialized binary frames from the
+    ///    current `client_tx` to the WebSocket sink, and pings the client
+    ///    every [`PING_INTERVAL`].
+    /// 2. **Read task** – reads binary frames from the WebSocket stream,
+    ///    decodes them with [`decode_input`], and pushes them into
+    ///    `input_tx`. Any frame received refreshes the client's last-seen
+    ///    time. A frame that can't be decoded is skipped and answered (once
+    ///    per connection) with `ServerMessage::ProtocolMismatch`; the
+    ///    connection stays open.
+    pub async fn bind(addr: &str) -> std::io::Result<Self> {
+        let (input_tx, input_rx) = mpsc::unbounded_channel::<PlayerInput>();
+        let reconnect_listener = TcpListener::bind(addr).await?;
+        let local_addr = reconnect_listener.local_addr()?;
 
-        // ── Write task ──────────────────────────────────────────────
+        let shared = Arc::new(Shared {
+            client_tx: Mutex::new(None),
+            spectator_tx: Mutex::new(None),
+            spectator_id: AtomicU64::new(0),
+            state: Mutex::new(ClientState::Disconnected),
+            connection_id: AtomicU64::new(0),
+            reconnected: AtomicBool::new(false),
+            connected: Notify::new(),
+            last_seen: Mutex::new(Instant::now()),
+        });
+
+        // ── Accept task ─────────────────────────────────────────────
+        let accept_shared = shared.clone();
+        let accept_input_tx = input_tx.clone();
         tokio::spawn(async move {
-            while let Some(bytes) = client_rx.recv().await {
-                if let Err(e) = ws_write.send(Message::Binary(bytes.into())).await {
-                    error!("Failed to send WebSocket message: {}", e);
-                    break;
+            loop {
+                match reconnect_listener.accept().await {
+                    Ok((stream, addr)) => {
+                        info!("Client connected from {}", addr);
+                        // A handshake that stalls must not hold up the next
+                        // connection, so each runs on its own task.
+                        tokio::spawn(handle_connection(stream, accept_shared.clone(), accept_input_tx.clone()));
+                    }
+                    Err(e) => {
+                        error!("Failed to accept connection: {}", e);
+                    }
                 }
             }
-            info!("Write task shutting down");
         });
 
-        // ── Read task ───────────────────────────────────────────────
-        let input_tx_clone = input_tx.clone();
+        Ok(Self {
+            shared,
+            input_rx,
+            input_tx,
+            local_addr,
+            delta: DeltaEncoder::default(),
+            delta_connection: 0,
+            spectator_delta: DeltaEncoder::default(),
+            spectator_delta_connection: 0,
+            newest_input_tick: None,
+            input_connection: 0,
+            last_input_tick: 0,
+        })
+    }
+
+    /// Bind a second listener on `addr` for spectators and spawn its accept
+    /// task. Returns the address it is bound to.
+    pub async fn listen_for_spectators(&self, addr: &str) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+
+        let accept_shared = self.shared.clone();
         tokio::spawn(async move {
-            while let Some(result) = ws_read.next().await {
-                match result {
-                    Ok(msg) => {
-                        if msg.is_binary() {
-                            let data = msg.into_data();
-                            match rmp_serde::from_slice::<PlayerInput>(&data) {
-                                Ok(input) => {
-                                    if let Err(e) = input_tx_clone.send(input) {
-                                        warn!("Input channel closed: {}", e);
-                                        break;
-                                    }
-                                }
-                                Err(e) => {
-                                    warn!("Failed to decode PlayerInput: {}", e);
-                                }
-                            }
-                        }
+            loop {
+                match listener.accept().await {
+                    Ok((stream, addr)) => {
+                        info!("Spectator connected from {}", addr);
+                        tokio::spawn(handle_spectator(stream, accept_shared.clone()));
                     }
                     Err(e) => {
-                        error!("WebSocket read error: {}", e);
-                        break;
+                        error!("Failed to accept spectator: {}", e);
                     }
                 }
             }
-            info!("Read task shutting down");
         });
+        Ok(local_addr)
+    }
 
-        Self {
-            client_tx: Some(client_tx),
-            input_rx,
-            input_tx,
+    /// Address the listener is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// How many spectators are watching (0 or 1).
+    pub fn connected_spectators(&self) -> u8 {
+        self.shared.spectator_tx.lock().unwrap().is_some() as u8
+    }
+
+    /// Wait until a client is connected.
+    pub async fn wait_for_client(&self) {
+        loop {
+            let notified = self.shared.connected.notified();
+            if self.is_connected() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    pub fn client_state(&self) -> ClientState {
+        *self.shared.state.lock().unwrap()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client_state() == ClientState::Connected
+    }
+
+    /// Whether the client has been heard from within [`CLIENT_TIMEOUT`].
+    /// A client that disconnects stays "alive" for the same grace period,
+    /// so a quick reload doesn't interrupt the game.
+    pub fn client_alive(&self) -> bool {
+        self.shared.last_seen.lock().unwrap().elapsed() <= CLIENT_TIMEOUT
+    }
+
+    /// Next queued player input, skipping any that are more than
+    /// [`MAX_INPUT_AGE_TICKS`] behind the newest input from the current
+    /// connection. `PlayerInput.tick` is the client's own counter, so inputs
+    /// are only compared with each other, never with the server tick.
+    pub fn next_input(&mut self) -> Option<PlayerInput> {
+        loop {
+            let input = self.input_rx.try_recv().ok()?;
+
+            let connection = self.shared.connection_id.load(Ordering::SeqCst);
+            if connection != self.input_connection {
+                self.newest_input_tick = None;
+                self.last_input_tick = 0;
+                self.input_connection = connection;
+            }
+
+            let newest = self.newest_input_tick.map_or(input.tick, |t| t.max(input.tick));
+            self.newest_input_tick = Some(newest);
+            if newest - input.tick > MAX_INPUT_AGE_TICKS {
+                warn!("Dropping stale input from tick {} (newest {})", input.tick, newest);
+                continue;
+            }
+            return Some(input);
         }
     }
 
-    /// Serialize `GameStateUpdate` via msgpack wrapped in `ServerMessage::GameState`
-    /// and send to the connected client. If no client is connected (or the
-    /// channel has been dropped), this is a no-op.
+    /// Tick of the last input accepted with [`GameServer::accept_input`].
+    pub fn last_input_tick(&self) -> Tick {
+        self.last_input_tick
+    }
+
+    /// Records that the game loop applied an input sent at `tick`.
+    pub fn accept_input(&mut self, tick: Tick) {
+        self.last_input_tick = tick;
+    }
+
+    /// Returns `true` once after a client reconnects, so the caller can send
+    /// a full state update rather than a delta.
+    pub fn take_reconnected(&self) -> bool {
+        self.shared.reconnected.swap(false, Ordering::SeqCst)
+    }
+
+    /// Send a state update to the connected client and spectator. The first
+    /// update of each connection, and a periodic keyframe, go out in full as
+    /// `ServerMessage::GameState`; the rest as `ServerMessage::GameStateDelta`
+    /// holding only changed entities. If nobody is connected (or the
+    /// channels have been dropped), this is a no-op.
     pub fn send_state(&mut self, update: &GameStateUpdate) {
-        let msg = ServerMessage::GameState(update.clone());
-        self.send_message(&msg);
+        let connection = self.shared.connection_id.load(Ordering::SeqCst);
+        if connection != self.delta_connection {
+            self.delta.reset();
+            self.delta_connection = connection;
+        }
+        let msg = self.delta.next_message(update);
+        self.send_to_client(&msg);
+
+        if self.connected_spectators() > 0 {
+            let spectator = self.shared.spectator_id.load(Ordering::SeqCst);
+            if spectator != self.spectator_delta_connection {
+                self.spectator_delta.reset();
+                self.spectator_delta_connection = spectator;
+            }
+            let msg = self.spectator_delta.next_message(update);
+            self.send_to_spectator(&msg);
+        }
+    }
+
+    /// Space keyframes for a game running at `tick_rate` ticks per second.
+    /// Until this is called the default tick rate is assumed.
+    pub fn set_tick_rate(&mut self, tick_rate: u64) {
+        self.delta = DeltaEncoder::new(tick_rate);
+        self.spectator_delta = DeltaEncoder::new(tick_rate);
+    }
+
+    /// Forget the updates last sent so the next `send_state` goes out in
+    /// full to the client and spectator, e.g. after a save is loaded.
+    pub fn resync(&mut self) {
+        self.delta.reset();
+        self.spectator_delta.reset();
     }
 
-    /// Send any ServerMessage to the client.
+    /// Send any ServerMessage to the client and the spectator.
     pub fn send_message(&mut self, msg: &ServerMessage) {
-        if let Some(tx) = &self.client_tx {
-            match rmp_serde::to_vec_named(msg) {
-                Ok(bytes) => {
-                    if tx.send(bytes).is_err() {
-                        warn!("Client disconnected — stopping sends");
-                        self.client_tx = None;
+        self.send_to_client(msg);
+        self.send_to_spectator(msg);
+    }
+
+    fn send_to_client(&self, msg: &ServerMessage) {
+        let mut client_tx = self.shared.client_tx.lock().unwrap();
+        if !forward(&mut client_tx, msg) {
+            warn!("Client disconnected — stopping sends");
+            *self.shared.state.lock().unwrap() = ClientState::Disconnected;
+        }
+    }
+
+    fn send_to_spectator(&self, msg: &ServerMessage) {
+        let mut spectator_tx = self.shared.spectator_tx.lock().unwrap();
+        if !forward(&mut spectator_tx, msg) {
+            info!("Spectator disconnected");
+        }
+    }
+}
+
+/// Serializes `msg` onto `tx`'s connection, if there is one. Returns false
+/// (and clears `tx`) if the connection has gone away.
+fn forward(tx: &mut Option<StateTx>, msg: &ServerMessage) -> bool {
+    let Some(sender) = tx.as_ref() else { return true };
+    match rmp_serde::to_vec_named(msg) {
+        Ok(bytes) => {
+            if sender.send(bytes).is_err() {
+                *tx = None;
+                return false;
+            }
+        }
+        Err(e) => {
+            error!("Failed to serialize ServerMessage: {}", e);
+        }
+    }
+    true
+}
+
+/// Forwards frames from `rx` to the WebSocket sink, pinging every
+/// [`PING_INTERVAL`], until either side closes.
+fn spawn_write_task(
+    mut ws_write: futures_util::stream::SplitSink<tokio_tungstenite::WebSocketStream<TcpStream>, Message>,
+    mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    tokio::spawn(async move {
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        loop {
+            let msg = tokio::select! {
+                bytes = rx.recv() => match bytes {
+                    Some(bytes) => Message::Binary(bytes),
+                    None => break,
+                },
+                _ = ping.tick() => Message::Ping(Vec::new()),
+            };
+            if let Err(e) = ws_write.send(msg).await {
+                error!("Failed to send WebSocket message: {}", e);
+                break;
+            }
+        }
+        info!("Write task shutting down");
+    });
+}
+
+/// Complete the WebSocket handshake for `stream` and make it the current
+/// spectator, replacing any previous one. Anything it sends is dropped.
+async fn handle_spectator(stream: TcpStream, shared: Arc<Shared>) {
+    let ws_stream = match accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            warn!("Spectator WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+
+    let (ws_write, mut ws_read) = ws_stream.split();
+    let (spectator_tx, spectator_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    spawn_write_task(ws_write, spectator_rx);
+
+    // Handshakes run concurrently; holding the lock while picking the id
+    // keeps two finishing together from sharing one.
+    let id = {
+        let mut current = shared.spectator_tx.lock().unwrap();
+        let id = shared.spectator_id.load(Ordering::SeqCst) + 1;
+        *current = Some(spectator_tx);
+        shared.spectator_id.store(id, Ordering::SeqCst);
+        id
+    };
+
+    let read_shared = shared.clone();
+    tokio::spawn(async move {
+        while let Some(result) = ws_read.next().await {
+            match result {
+                Ok(msg) if msg.is_binary() => info!("spectator input ignored."),
+                Ok(msg) if msg.is_close() => break,
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Spectator WebSocket read error: {}", e);
+                    break;
+                }
+            }
+        }
+        let mut current = read_shared.spectator_tx.lock().unwrap();
+        if read_shared.spectator_id.load(Ordering::SeqCst) == id {
+            *current = None;
+        }
+    });
+}
+
+/// Complete the WebSocket handshake for `stream` and make it the current
+/// client, replacing any previous connection.
+async fn handle_connection(
+    stream: TcpStream,
+    shared: Arc<Shared>,
+    input_tx: mpsc::UnboundedSender<PlayerInput>,
+) {
+    {
+        // A handshake in flight doesn't unseat a connected client.
+        let mut state = shared.state.lock().unwrap();
+        if *state != ClientState::Connected {
+            *state = ClientState::Reconnecting;
+        }
+    }
+
+    let ws_stream = match accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            warn!("WebSocket handshake failed: {}", e);
+            let has_client = shared.client_tx.lock().unwrap().is_some();
+            *shared.state.lock().unwrap() = if has_client {
+                ClientState::Connected
+            } else {
+                ClientState::Disconnected
+            };
+            return;
+        }
+    };
+
+    let (ws_write, mut ws_read) = ws_stream.split();
+
+    // Channel: game loop -> write task -> WebSocket
+    let (client_tx, client_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let mismatch_tx = client_tx.clone();
+
+    // ── Write task ──────────────────────────────────────────────────
+    spawn_write_task(ws_write, client_rx);
+
+    // Publish the sender and the new id together so `send_state` never sees
+    // the new id while still holding the old sender. Handshakes run
+    // concurrently, so the id is picked under the same lock.
+    let id = {
+        let mut current = shared.client_tx.lock().unwrap();
+        let id = shared.connection_id.load(Ordering::SeqCst) + 1;
+        *current = Some(client_tx);
+        shared.connection_id.store(id, Ordering::SeqCst);
+        id
+    };
+    *shared.last_seen.lock().unwrap() = Instant::now();
+    *shared.state.lock().unwrap() = ClientState::Connected;
+    if id > 1 {
+        shared.reconnected.store(true, Ordering::SeqCst);
+    }
+    shared.connected.notify_waiters();
+
+    // ── Read task ───────────────────────────────────────────────────
+    let read_shared = shared.clone();
+    tokio::spawn(async move {
+        let mut mismatch_sent = false;
+        while let Some(result) = ws_read.next().await {
+            match result {
+                Ok(msg) => {
+                    *read_shared.last_seen.lock().unwrap() = Instant::now();
+                    if msg.is_binary() {
+                        let data = msg.into_data();
+                        match decode_input(&data) {
+                            Ok(input) => {
+                                if let Err(e) = input_tx.send(input) {
+                                    warn!("Input channel closed: {}", e);
+                                    break;
+                                }
+                            }
+                            Err(client_version) => {
+                                warn!(
+                                    "Failed to decode PlayerInput (client protocol {:#x}, server {:#x})",
+                                    client_version, PROTOCOL_VERSION
+                                );
+                                if !mismatch_sent {
+                                    mismatch_sent = true;
+                                    let mismatch = ServerMessage::ProtocolMismatch {
+                                        server_version: PROTOCOL_VERSION,
+                                        client_version,
+                                    };
+                                    if let Ok(bytes) = rmp_serde::to_vec_named(&mismatch) {
+                                        let _ = mismatch_tx.send(bytes);
+                                    }
+                                }
+                            }
+                        }
+                    } else if msg.is_close() {
+                        break;
                     }
                 }
                 Err(e) => {
-                    error!("Failed to serialize ServerMessage: {}", e);
+                    error!("WebSocket read error: {}", e);
+                    break;
                 }
             }
         }
+        info!("Read task shutting down");
+
+        // Only the current connection may mark the client as gone.
+        let mut current = read_shared.client_tx.lock().unwrap();
+        if read_shared.connection_id.load(Ordering::SeqCst) == id {
+            *current = None;
+            *read_shared.state.lock().unwrap() = ClientState::Disconnected;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::Vec2;
+    use tokio_tungstenite::client_async;
+
+    async fn connect(addr: SocketAddr) -> tokio_tungstenite::WebSocketStream<TcpStream> {
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (ws, _resp) = client_async(format!("ws://{}", addr), stream).await.unwrap();
+        ws
+    }
+
+    async fn wait_until(mut cond: impl FnMut() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !cond() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("condition not reached in time");
+    }
+
+    #[tokio::test]
+    async fn client_can_reconnect_to_same_port() {
+        let mut server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr();
+
+        // First client connects, then goes away.
+        let mut first = connect(addr).await;
+        server.wait_for_client().await;
+        assert!(!server.take_reconnected());
+        first.close(None).await.unwrap();
+        drop(first);
+        wait_until(|| !server.is_connected()).await;
+        assert_eq!(server.client_state(), ClientState::Disconnected);
+
+        // Sending with no client is a no-op rather than a failure.
+        server.send_message(&ServerMessage::VibeSessionStarted { agent_id: 1, enabled_tools: Vec::new() });
+
+        // Second client on the same port picks up where the first left off.
+        let mut second = connect(addr).await;
+        server.wait_for_client().await;
+        assert!(server.take_reconnected());
+        assert!(!server.take_reconnected());
+
+        server.send_message(&ServerMessage::VibeSessionStarted { agent_id: 7, enabled_tools: Vec::new() });
+        let frame = tokio::time::timeout(Duration::from_secs(5), second.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        match rmp_serde::from_slice::<ServerMessage>(&frame.into_data()).unwrap() {
+            ServerMessage::VibeSessionStarted { agent_id, .. } => assert_eq!(agent_id, 7),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn stalled_handshake_does_not_block_other_connections() {
+        let server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let spectator_addr = server.listen_for_spectators("127.0.0.1:0").await.unwrap();
+
+        // Open TCP connections that never start the WebSocket handshake.
+        let _stalled_client = TcpStream::connect(server.local_addr()).await.unwrap();
+        let _stalled_spectator = TcpStream::connect(spectator_addr).await.unwrap();
+
+        let _client = tokio::time::timeout(Duration::from_secs(5), connect(server.local_addr()))
+            .await
+            .expect("client handshake blocked by a stalled one");
+        server.wait_for_client().await;
+        let _spectator = tokio::time::timeout(Duration::from_secs(5), connect(spectator_addr))
+            .await
+            .expect("spectator handshake blocked by a stalled one");
+        wait_until(|| server.connected_spectators() == 1).await;
+
+        // Another stalled handshake doesn't unseat the connected client.
+        let _stalled_again = TcpStream::connect(server.local_addr()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(server.is_connected());
+    }
+
+    /// Tick of the next state update `ws` receives, skipping pings.
+    async fn next_state_tick(ws: &mut tokio_tungstenite::WebSocketStream<TcpStream>) -> Tick {
+        let frame = loop {
+            let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            if frame.is_binary() {
+                break frame;
+            }
+        };
+        match rmp_serde::from_slice::<ServerMessage>(&frame.into_data()).unwrap() {
+            ServerMessage::GameState(update) => update.tick,
+            ServerMessage::GameStateDelta(delta) => delta.state.tick,
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn spectator_sees_the_same_state_and_cannot_play() {
+        use crate::network::delta::tests::update;
+
+        let mut server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let spectator_addr = server.listen_for_spectators("127.0.0.1:0").await.unwrap();
+
+        // The spectator arriving first doesn't count as the client.
+        let mut spectator = connect(spectator_addr).await;
+        wait_until(|| server.connected_spectators() == 1).await;
+        assert!(!server.is_connected());
+        let mut client = connect(server.local_addr()).await;
+        server.wait_for_client().await;
+
+        for tick in [10, 11, 12] {
+            server.send_state(&update(tick, Vec::new()));
+            assert_eq!(next_state_tick(&mut client).await, tick);
+            assert_eq!(next_state_tick(&mut spectator).await, tick);
+        }
+
+        spectator.send(Message::Binary(versioned(PROTOCOL_VERSION, input_with(ClientAction::Attack)))).await.unwrap();
+        client.send(Message::Binary(versioned(PROTOCOL_VERSION, input_with(ClientAction::Attack)))).await.unwrap();
+        let input = tokio::time::timeout(Duration::from_secs(5), server.input_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(input.tick, 3);
+        assert!(server.input_rx.try_recv().is_err());
+
+        spectator.close(None).await.unwrap();
+        wait_until(|| server.connected_spectators() == 0).await;
+        assert!(server.is_connected());
+    }
+
+    #[tokio::test]
+    async fn silent_client_goes_unresponsive_until_it_pongs() {
+        let server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let mut client = connect(server.local_addr()).await;
+        server.wait_for_client().await;
+        assert!(server.client_alive());
+
+        // The client never reads, so it never answers pings. Backdate the
+        // last-seen time past the timeout instead of waiting it out.
+        *server.shared.last_seen.lock().unwrap() =
+            Instant::now() - CLIENT_TIMEOUT - Duration::from_secs(1);
+        assert!(server.is_connected());
+        assert!(!server.client_alive());
+
+        client.send(Message::Pong(Vec::new())).await.unwrap();
+        wait_until(|| server.client_alive()).await;
+    }
+
+    /// Mirrors `PlayerAction`'s encoding, plus a variant the server lacks.
+    #[derive(serde::Serialize)]
+    enum ClientAction {
+        Attack,
+        TimeTravel,
+    }
+
+    /// Mirrors `PlayerInput` as a client would encode it.
+    #[derive(serde::Serialize)]
+    struct ClientInput {
+        tick: Tick,
+        movement: Vec2,
+        action: Option<ClientAction>,
+        target: Option<u64>,
+    }
+
+    fn input_with(action: ClientAction) -> ClientInput {
+        ClientInput { tick: 3, movement: Vec2 { x: 0.0, y: 0.0 }, action: Some(action), target: None }
+    }
+
+    fn versioned(version: u32, payload: ClientInput) -> Vec<u8> {
+        rmp_serde::to_vec_named(&VersionedMessage { protocol_version: version, payload }).unwrap()
+    }
+
+    #[test]
+    fn decode_accepts_versioned_and_bare_inputs() {
+        let input = decode_input(&versioned(PROTOCOL_VERSION, input_with(ClientAction::Attack))).unwrap();
+        assert!(matches!(input.action, Some(crate::protocol::PlayerAction::Attack)));
+
+        let bare = rmp_serde::to_vec_named(&input_with(ClientAction::Attack)).unwrap();
+        assert_eq!(decode_input(&bare).unwrap().tick, 3);
+    }
+
+    #[test]
+    fn unknown_variant_or_major_reports_the_client_version() {
+        let newer_minor = PROTOCOL_VERSION + 1;
+        assert_eq!(decode_input(&versioned(newer_minor, input_with(ClientAction::TimeTravel))).unwrap_err(), newer_minor);
+        assert!(decode_input(&versioned(newer_minor, input_with(ClientAction::Attack))).is_ok());
+
+        let next_major = PROTOCOL_VERSION + (1 << 16);
+        assert_eq!(decode_input(&versioned(next_major, input_with(ClientAction::Attack))).unwrap_err(), next_major);
+        assert_eq!(decode_input(b"garbage").unwrap_err(), 0);
+    }
+
+    #[tokio::test]
+    async fn undecodable_input_gets_a_mismatch_and_keeps_the_connection() {
+        let mut server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let mut client = connect(server.local_addr()).await;
+        server.wait_for_client().await;
+
+        client.send(Message::Binary(versioned(PROTOCOL_VERSION, input_with(ClientAction::TimeTravel)))).await.unwrap();
+        let frame = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        match rmp_serde::from_slice::<ServerMessage>(&frame.into_data()).unwrap() {
+            ServerMessage::ProtocolMismatch { server_version, client_version } => {
+                assert_eq!(server_version, PROTOCOL_VERSION);
+                assert_eq!(client_version, PROTOCOL_VERSION);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        client.send(Message::Binary(versioned(PROTOCOL_VERSION, input_with(ClientAction::Attack)))).await.unwrap();
+        let input = tokio::time::timeout(Duration::from_secs(5), server.input_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(input.tick, 3);
+        assert!(server.is_connected());
+    }
+
+    #[tokio::test]
+    async fn stale_inputs_are_dropped() {
+        let mut server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let input = |tick| PlayerInput {
+            tick,
+            movement: Vec2 { x: 0.0, y: 0.0 },
+            action: None,
+            target: None,
+        };
+        for tick in [1000, 1000 - MAX_INPUT_AGE_TICKS - 1, 1000 - MAX_INPUT_AGE_TICKS, 1200] {
+            server.input_tx.send(input(tick)).unwrap();
+        }
+
+        let ticks: Vec<Tick> = std::iter::from_fn(|| server.next_input()).map(|i| i.tick).collect();
+        assert_eq!(ticks, vec![1000, 1000 - MAX_INPUT_AGE_TICKS, 1200]);
     }
 }
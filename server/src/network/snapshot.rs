@@ -0,0 +1,205 @@
+use hecs::World;
+
+use crate::ai::rogue_ai::PACK_MIN_MEMBERS;
+use crate::ecs::components::{
+    GameState, Health, Knockback, MimicDisguise, MimicPhase, PackBonus, Position, Rogue, RogueNest, RogueType, RogueVisibility,
+    StatusEffects,
+};
+use crate::protocol::{BuildingTypeKind, EntityData, EntityDelta, EntityKind, MimicPhaseSnapshot, Vec2};
+
+/// Entity deltas for every rogue in `world`.
+///
+/// A Mimic still wearing its `MimicDisguise` is reported as a finished
+/// building of the disguise type, so the client can't tell it apart from
+/// the real thing until it reveals itself (including always showing as
+/// powered). While it is still stalking unseen it goes out as an invisible
+/// rogue instead.
+pub fn rogue_deltas(world: &World) -> Vec<EntityDelta> {
+    world
+        .query::<(
+            &Position,
+            &RogueType,
+            &Health,
+            Option<&StatusEffects>,
+            Option<&RogueVisibility>,
+            Option<&MimicDisguise>,
+            Option<&PackBonus>,
+            Option<&Knockback>,
+            Option<&MimicPhase>,
+        )>()
+        .with::<&Rogue>()
+        .iter()
+        .map(|(id, (pos, rogue_type, health, effects, visibility, mimic, pack, knockback, phase))| {
+            let health_pct = health.current as f32 / health.max.max(1) as f32;
+            let visible = visibility.is_none_or(|v| v.visible);
+            let (kind, data) = match mimic {
+                Some(mimic) if visible => (
+                    EntityKind::Building,
+                    EntityData::Building {
+                        building_type: mimic.disguise,
+                        construction_pct: 1.0,
+                        health_pct,
+                        zone_of_control_radius: None,
+                        powered: true,
+                    },
+                ),
+                _ => (
+                    EntityKind::Rogue,
+                    EntityData::Rogue {
+                        rogue_type: rogue_type.kind,
+                        health_pct,
+                        status_effects: effects.map(|e| e.effects.clone()).unwrap_or_default(),
+                        visible,
+                        in_pack: pack.is_some_and(|p| p.members >= PACK_MIN_MEMBERS),
+                        knockback_active: knockback.is_some(),
+                        mimic_phase: phase.map(|p| MimicPhaseSnapshot {
+                            elapsed_ticks: p.elapsed_ticks,
+                            revealed: p.revealed,
+                        }),
+                    },
+                ),
+            };
+            EntityDelta {
+                id: id.to_bits().into(),
+                kind,
+                position: Vec2 { x: pos.x, y: pos.y },
+                data,
+            }
+        })
+        .collect()
+}
+
+/// Entity deltas for every rogue nest, reported as a finished `Nest`
+/// building so the client draws it and lets the player attack it.
+pub fn nest_deltas(world: &World) -> Vec<EntityDelta> {
+    world
+        .query::<(&Position, &Health)>()
+        .with::<&RogueNest>()
+        .iter()
+        .map(|(id, (pos, health))| EntityDelta {
+            id: id.to_bits().into(),
+            kind: EntityKind::Building,
+            position: Vec2 { x: pos.x, y: pos.y },
+            data: EntityData::Building {
+                building_type: BuildingTypeKind::Nest,
+                construction_pct: 1.0,
+                health_pct: health.current as f32 / health.max.max(1) as f32,
+                zone_of_control_radius: None,
+                powered: false,
+            },
+        })
+        .collect()
+}
+
+/// Ids of the purchased upgrades, as `UpgradeId` variant names, sorted.
+pub fn purchased_upgrades(game_state: &GameState) -> Vec<String> {
+    let mut ids: Vec<String> = game_state.upgrades.purchased.iter().map(|id| format!("{:?}", id)).collect();
+    ids.sort();
+    ids
+}
+
+/// Tile coordinates of every opened chest, sorted.
+pub fn opened_chests(game_state: &GameState) -> Vec<(i32, i32)> {
+    let mut chests: Vec<(i32, i32)> = game_state.opened_chests.iter().copied().collect();
+    chests.sort_unstable();
+    chests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::ecs::world::create_world;
+    use crate::game::upgrades::UpgradeId;
+    use crate::protocol::*;
+
+    /// Builds every `GameStateUpdate` field from a fresh world, so a field
+    /// added to either side without the other fails to compile here.
+    #[test]
+    fn full_update_from_a_fresh_world() {
+        let (world, mut game_state) = create_world();
+        game_state.add_inventory_item("material:circuit_shard", 2);
+        game_state.upgrades.purchased.insert(UpgradeId::CrankAssignment);
+        game_state.opened_chests.insert((3, -4));
+        game_state.opened_chests.insert((1, 2));
+
+        let update = GameStateUpdate {
+            protocol_version: PROTOCOL_VERSION,
+            tick: game_state.tick,
+            player: PlayerSnapshot {
+                position: Vec2::default(),
+                health: 100.0,
+                max_health: 100.0,
+                tokens: game_state.economy.balance,
+                torch_range: 0.0,
+                facing: Vec2::default(),
+                dead: game_state.player_dead,
+                death_timer: 0.0,
+                attack_cooldown_pct: 0.0,
+                weapon_durability_pct: 1.0,
+                dodge_cooldown_remaining: 0,
+                carry_weight: 0,
+                carry_capacity: 0,
+            },
+            entities_changed: rogue_deltas(&world),
+            entities_removed: Vec::new(),
+            fog_updates: Vec::new(),
+            minimap: None,
+            economy: EconomySnapshot {
+                balance: game_state.economy.balance,
+                income_per_sec: 0.0,
+                expenditure_per_sec: 0.0,
+                income_sources: game_state.economy.income_sources.clone(),
+                expenditure_sinks: game_state.economy.expenditure_sinks.clone(),
+            },
+            log_entries: Vec::new(),
+            audio_triggers: Vec::new(),
+            debug: DebugSnapshot {
+                spawning_enabled: game_state.spawning_enabled,
+                god_mode: game_state.god_mode,
+                phase: game_state.phase.clone(),
+                crank_tier: game_state.crank.tier.clone(),
+                fractional: game_state.economy.fractional,
+                connected_spectators: 0,
+                phase_progress: HashMap::new(),
+            },
+            wheel: WheelSnapshot {
+                tier: game_state.crank.tier.clone(),
+                tokens_per_rotation: game_state.crank.tokens_per_rotation,
+                agent_bonus_per_tick: 0.0,
+                heat: game_state.crank.heat,
+                max_heat: game_state.crank.max_heat,
+                is_cranking: game_state.crank.is_cranking,
+                assigned_agent_ids: Vec::new(),
+                upgrade_cost: None,
+                overheated_remaining: game_state.crank.overheated_remaining,
+                overheating: game_state.crank.overheating,
+            },
+            project_manager: None,
+            grades: Vec::new(),
+            combat_events: Vec::new(),
+            player_hit: false,
+            player_hit_damage: 0,
+            inventory: game_state.inventory.clone(),
+            purchased_upgrades: purchased_upgrades(&game_state),
+            opened_chests: opened_chests(&game_state),
+            chest_rewards: Vec::new(),
+            transaction_log: None,
+            active_investments: Vec::new(),
+            drops: Vec::new(),
+            recipes_available: Vec::new(),
+            wave: WaveSnapshot { wave_number: 0, next_wave_in_ticks: 0, active: false },
+            active_synergies: Vec::new(),
+            vibe_metrics: HashMap::new(),
+        };
+
+        let bytes = rmp_serde::to_vec_named(&ServerMessage::GameState(update)).unwrap();
+        let ServerMessage::GameState(decoded) = rmp_serde::from_slice(&bytes).unwrap() else {
+            panic!("expected a GameState");
+        };
+        assert_eq!(decoded.inventory, game_state.inventory);
+        assert_eq!(decoded.purchased_upgrades, vec!["CrankAssignment".to_string()]);
+        assert_eq!(decoded.opened_chests, vec![(1, 2), (3, -4)]);
+        assert!(decoded.chest_rewards.is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+//! Sanity checks on client input before the game loop applies it.
+
+use crate::protocol::{PlayerInput, Tick, Vec2};
+
+/// Player walking speed in pixels per tick.
+pub const PLAYER_SPEED: f32 = 3.0;
+
+/// Largest movement vector accepted. The client sends a normalized
+/// direction, so anything much past 1.0 was tampered with.
+pub const MAX_MOVEMENT_MAGNITUDE: f32 = 1.5;
+
+/// Furthest the player may move in one server tick, summed over every
+/// input applied during it. Inputs carry the client's own tick, so a client
+/// flooding inputs with fresh ticks would otherwise outrun this.
+pub const MAX_POSITION_DELTA_PER_TICK: f32 = PLAYER_SPEED * 2.0;
+
+/// Whether `input`'s movement is physically possible: finite and no longer
+/// than [`MAX_MOVEMENT_MAGNITUDE`]. The server applies speed itself, so a
+/// sane direction can't move the player further than it allows.
+pub fn validate_input(input: &PlayerInput) -> bool {
+    let Vec2 { x: mx, y: my } = input.movement;
+    mx.is_finite() && my.is_finite() && (mx * mx + my * my).sqrt() <= MAX_MOVEMENT_MAGNITUDE
+}
+
+/// Whether moving from `tick_start`, the player's position when the server
+/// tick began, to `to` stays within [`MAX_POSITION_DELTA_PER_TICK`].
+pub fn within_tick_delta(tick_start: (f32, f32), to: (f32, f32)) -> bool {
+    let (dx, dy) = (to.0 - tick_start.0, to.1 - tick_start.1);
+    (dx * dx + dy * dy).sqrt() <= MAX_POSITION_DELTA_PER_TICK
+}
+
+/// Whether `input` repeats `last_tick`, the tick of the last input accepted.
+/// The client sends UI actions with the current frame's tick, so a repeat
+/// carries no new movement but its action still counts.
+pub fn is_repeat_tick(input: &PlayerInput, last_tick: Tick) -> bool {
+    input.tick == last_tick
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(tick: Tick, x: f32, y: f32) -> PlayerInput {
+        PlayerInput { tick, movement: Vec2 { x, y }, action: None, target: None }
+    }
+
+    #[test]
+    fn normal_movement_is_accepted() {
+        assert!(validate_input(&input(2, 1.0, 0.0)));
+        assert!(validate_input(&input(2, 0.6, 0.8)));
+        assert!(validate_input(&input(2, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn doubled_speed_movement_is_rejected() {
+        assert!(!validate_input(&input(2, 2.0, 0.0)));
+        assert!(!validate_input(&input(2, 1.5, 1.5)));
+        assert!(!validate_input(&input(2, f32::NAN, 0.0)));
+    }
+
+    #[test]
+    fn position_delta_is_capped_per_tick() {
+        assert!(within_tick_delta((10.0, 10.0), (10.0 + 2.0 * PLAYER_SPEED, 10.0)));
+        assert!(!within_tick_delta((10.0, 10.0), (10.0 + 2.0 * PLAYER_SPEED, 1.0)));
+    }
+
+    #[test]
+    fn duplicate_tick_is_a_repeat() {
+        assert!(is_repeat_tick(&input(5, 1.0, 0.0), 5));
+        assert!(!is_repeat_tick(&input(6, 1.0, 0.0), 5));
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use tracing::{info, warn};
 
@@ -20,41 +21,138 @@ pub struct BuildingsManifest {
     pub buildings: Vec<BuildingDefinition>,
 }
 
+/// A problem found by [`BuildingsManifest::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    /// More than one building uses this id.
+    DuplicateId(String),
+    /// Two buildings want the same dev server port.
+    DuplicatePort { port: u16, first: String, second: String },
+    /// The building has an empty `directory_name`.
+    MissingDirectoryName(String),
+}
+
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::DuplicateId(id) => write!(f, "duplicate building id '{}'", id),
+            ManifestError::DuplicatePort { port, first, second } => {
+                write!(f, "port {} used by both '{}' and '{}'", port, first, second)
+            }
+            ManifestError::MissingDirectoryName(id) => write!(f, "building '{}' has no directory_name", id),
+        }
+    }
+}
+
 impl BuildingsManifest {
+    /// Read and parse the manifest at `path`.
+    pub fn parse_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read buildings manifest at {}: {}", path.display(), e))?;
+        serde_json::from_str::<BuildingsManifest>(&contents)
+            .map_err(|e| format!("Failed to parse buildings manifest at {}: {}", path.display(), e))
+    }
+
     /// Load the manifest from a JSON file on disk.
-    /// Falls back to an empty manifest if the file is missing or malformed.
+    /// Falls back to an empty manifest if the file is missing or malformed,
+    /// and warns about anything [`validate`](Self::validate) finds.
     pub fn load_from_file(path: &Path) -> Self {
-        match std::fs::read_to_string(path) {
-            Ok(contents) => match serde_json::from_str::<BuildingsManifest>(&contents) {
-                Ok(manifest) => {
-                    info!(
-                        "Loaded buildings manifest with {} buildings",
-                        manifest.buildings.len()
-                    );
-                    manifest
-                }
-                Err(e) => {
-                    warn!(
-                        "Failed to parse buildings manifest at {}: {}. Using empty manifest.",
-                        path.display(),
-                        e
-                    );
-                    BuildingsManifest::default()
+        match Self::parse_file(path) {
+            Ok(manifest) => {
+                info!(
+                    "Loaded buildings manifest with {} buildings",
+                    manifest.buildings.len()
+                );
+                for error in manifest.validate() {
+                    warn!("Buildings manifest: {}", error);
                 }
-            },
+                manifest
+            }
             Err(e) => {
-                warn!(
-                    "Failed to read buildings manifest at {}: {}. Using empty manifest.",
-                    path.display(),
-                    e
-                );
+                warn!("{}. Using empty manifest.", e);
                 BuildingsManifest::default()
             }
         }
     }
 
+    /// Checks for duplicate ids, duplicate ports and missing directory
+    /// names, in manifest order.
+    pub fn validate(&self) -> Vec<ManifestError> {
+        let mut errors = Vec::new();
+        let mut ids: HashSet<&str> = HashSet::new();
+        let mut ports: HashMap<u16, &str> = HashMap::new();
+
+        for building in &self.buildings {
+            if !ids.insert(&building.id) {
+                errors.push(ManifestError::DuplicateId(building.id.clone()));
+            }
+            match ports.get(&building.port) {
+                Some(first) => errors.push(ManifestError::DuplicatePort {
+                    port: building.port,
+                    first: first.to_string(),
+                    second: building.id.clone(),
+                }),
+                None => {
+                    ports.insert(building.port, &building.id);
+                }
+            }
+            if building.directory_name.trim().is_empty() {
+                errors.push(ManifestError::MissingDirectoryName(building.id.clone()));
+            }
+        }
+        errors
+    }
+
     /// Look up a building definition by its id.
     pub fn get_building(&self, id: &str) -> Option<&BuildingDefinition> {
         self.buildings.iter().find(|b| b.id == id)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    pub(crate) fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/project/fixtures").join(name)
+    }
+
+    #[test]
+    fn base_fixture_is_valid() {
+        let manifest = BuildingsManifest::parse_file(&fixture("manifest_base.json")).unwrap();
+        assert_eq!(manifest.buildings.len(), 3);
+        assert!(manifest.validate().is_empty());
+    }
+
+    #[test]
+    fn duplicate_ids_are_reported() {
+        let manifest = BuildingsManifest::parse_file(&fixture("manifest_duplicate_id.json")).unwrap();
+        assert_eq!(manifest.validate(), vec![ManifestError::DuplicateId("todo_app".to_string())]);
+    }
+
+    #[test]
+    fn duplicate_ports_are_reported() {
+        let manifest = BuildingsManifest::parse_file(&fixture("manifest_duplicate_port.json")).unwrap();
+        assert_eq!(
+            manifest.validate(),
+            vec![ManifestError::DuplicatePort {
+                port: 3101,
+                first: "todo_app".to_string(),
+                second: "calculator".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_directory_names_are_reported() {
+        let manifest = BuildingsManifest::parse_file(&fixture("manifest_missing_directory.json")).unwrap();
+        assert_eq!(manifest.validate(), vec![ManifestError::MissingDirectoryName("calculator".to_string())]);
+    }
+
+    #[test]
+    fn malformed_manifest_is_a_parse_error_but_loads_empty() {
+        assert!(BuildingsManifest::parse_file(&fixture("manifest_malformed.json")).is_err());
+        assert!(BuildingsManifest::load_from_file(&fixture("manifest_malformed.json")).buildings.is_empty());
+    }
+}
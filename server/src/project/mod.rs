@@ -3,10 +3,16 @@ pub mod process;
 pub mod scaffold;
 
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{mpsc, Semaphore};
 use tracing::{info, warn};
 
-use manifest::BuildingsManifest;
+use crate::config::DEFAULT_DEV_PORT_RANGE;
+use crate::protocol::ProjectStatusDto;
+use manifest::{BuildingsManifest, ManifestError};
 use process::DevServerProcess;
 
 // ── Project Status ──────────────────────────────────────────────────────
@@ -19,6 +25,98 @@ pub enum ProjectStatus {
     Error(String),
 }
 
+impl From<&ProjectStatus> for ProjectStatusDto {
+    fn from(status: &ProjectStatus) -> Self {
+        match status {
+            ProjectStatus::NotInitialized => ProjectStatusDto::NotInitialized,
+            ProjectStatus::Ready => ProjectStatusDto::Ready,
+            ProjectStatus::Running(port) => ProjectStatusDto::Running { port: *port },
+            ProjectStatus::Error(message) => ProjectStatusDto::Error { message: message.clone() },
+        }
+    }
+}
+
+// ── Manifest reload ─────────────────────────────────────────────────────
+
+/// What [`ProjectManager::reload_manifest`] changed.
+#[derive(Debug, Default)]
+pub struct ManifestReload {
+    /// Building ids new in the reloaded manifest.
+    pub added: Vec<String>,
+    /// Building ids no longer in the manifest.
+    pub removed: Vec<String>,
+    /// Removed ids whose dev server is still running; it keeps running
+    /// until stopped.
+    pub removed_running: Vec<String>,
+    /// Validation errors. When non-empty the reload was rejected and the
+    /// current manifest kept.
+    pub errors: Vec<ManifestError>,
+}
+
+impl ManifestReload {
+    /// One log line per change, warning and error.
+    pub fn log_lines(&self) -> Vec<String> {
+        if !self.errors.is_empty() {
+            return self
+                .errors
+                .iter()
+                .map(|e| format!("[manifest] error: {}", e))
+                .chain(std::iter::once("[manifest] reload rejected, keeping the current manifest".to_string()))
+                .collect();
+        }
+        let mut lines: Vec<String> = Vec::new();
+        lines.extend(self.added.iter().map(|id| format!("[manifest] added {}", id)));
+        lines.extend(self.removed.iter().map(|id| format!("[manifest] removed {}", id)));
+        lines.extend(
+            self.removed_running
+                .iter()
+                .map(|id| format!("[manifest] warning: {} was removed but its dev server is still running", id)),
+        );
+        lines.push(format!("[manifest] reloaded ({} added, {} removed)", self.added.len(), self.removed.len()));
+        lines
+    }
+}
+
+// ── Background scaffolding ──────────────────────────────────────────────
+
+/// Most projects scaffolded at once; each runs `npm create` and `npm install`.
+pub const SCAFFOLD_CONCURRENCY: usize = 3;
+
+/// One building project to scaffold.
+#[derive(Debug, Clone)]
+pub struct ScaffoldJob {
+    pub building_id: String,
+    pub name: String,
+    pub description: String,
+    pub tier: u8,
+    pub port: u16,
+    pub dir: PathBuf,
+    /// Delete the directory first (a reset).
+    pub clean: bool,
+}
+
+/// The outcome of scaffolding one building: its status message, or why it
+/// failed.
+#[derive(Debug, Clone)]
+pub struct InitProgress {
+    pub building_id: String,
+    pub result: Result<String, String>,
+}
+
+impl InitProgress {
+    /// The building's new status as sent to the client.
+    pub fn status(&self) -> ProjectStatusDto {
+        match &self.result {
+            Ok(_) => ProjectStatusDto::Ready,
+            Err(e) => ProjectStatusDto::Error { message: e.clone() },
+        }
+    }
+}
+
+async fn scaffold_job(job: ScaffoldJob) -> Result<String, String> {
+    scaffold::scaffold_project(&job.dir, &job.name, &job.description, job.tier, job.port).await
+}
+
 // ── Project Manager ─────────────────────────────────────────────────────
 
 pub struct ProjectManager {
@@ -36,6 +134,22 @@ pub struct ProjectManager {
     pub statuses: HashMap<String, ProjectStatus>,
     /// Mapping from building id to a list of assigned agent entity ids.
     pub agent_assignments: HashMap<String, Vec<u64>>,
+    /// How many ports above a building's manifest port to try when it is
+    /// already taken.
+    pub port_range: u16,
+    /// Modification time of the manifest file when it was last loaded, if
+    /// the file could be stat'ed.
+    pub last_manifest_modified: Option<SystemTime>,
+    /// Scaffolding results from the background task.
+    init_tx: mpsc::UnboundedSender<InitProgress>,
+    init_rx: mpsc::UnboundedReceiver<InitProgress>,
+    /// Projects queued by the current initialization and not yet drained.
+    init_pending: usize,
+}
+
+/// Modification time of the file at `path`, if it can be read.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
 impl ProjectManager {
@@ -66,6 +180,7 @@ impl ProjectManager {
             unlocked_buildings.len(),
         );
 
+        let (init_tx, init_rx) = mpsc::unbounded_channel();
         Self {
             base_dir: None,
             manifest,
@@ -74,9 +189,89 @@ impl ProjectManager {
             initialized: false,
             statuses,
             agent_assignments: HashMap::new(),
+            port_range: DEFAULT_DEV_PORT_RANGE,
+            last_manifest_modified: modified_time(manifest_path),
+            init_tx,
+            init_rx,
+            init_pending: 0,
         }
     }
 
+    // ── Manifest reload ─────────────────────────────────────────────
+
+    /// Re-read the manifest at `path` and swap it in.
+    ///
+    /// Unlocks and statuses of ids in both manifests are kept; new ids start
+    /// as `NotInitialized` (and unlocked if `unlocked_by_default`); removed
+    /// ids are dropped, except that a removed id with a running dev server
+    /// keeps its status and is reported in `removed_running`.  A manifest
+    /// that fails [`BuildingsManifest::validate`] is rejected and reported
+    /// in `errors` without changing anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or parsed.
+    pub fn reload_manifest(&mut self, path: &std::path::Path) -> Result<ManifestReload, String> {
+        // Remember this version even if it's rejected, so an auto-reload
+        // doesn't retry the same broken file every time.
+        self.last_manifest_modified = modified_time(path);
+        let manifest = BuildingsManifest::parse_file(path)?;
+        let errors = manifest.validate();
+        if !errors.is_empty() {
+            for error in &errors {
+                warn!("Buildings manifest: {}", error);
+            }
+            return Ok(ManifestReload { errors, ..Default::default() });
+        }
+
+        let old_ids: HashSet<&str> = self.manifest.buildings.iter().map(|b| b.id.as_str()).collect();
+        let new_ids: HashSet<&str> = manifest.buildings.iter().map(|b| b.id.as_str()).collect();
+        let mut reload = ManifestReload::default();
+
+        for building in &manifest.buildings {
+            if old_ids.contains(building.id.as_str()) {
+                continue;
+            }
+            reload.added.push(building.id.clone());
+            self.statuses.insert(building.id.clone(), ProjectStatus::NotInitialized);
+            if building.unlocked_by_default {
+                self.unlocked_buildings.insert(building.id.clone());
+            }
+        }
+        for building in &self.manifest.buildings {
+            if new_ids.contains(building.id.as_str()) {
+                continue;
+            }
+            reload.removed.push(building.id.clone());
+            self.unlocked_buildings.remove(&building.id);
+            if self.running_processes.contains_key(&building.id) {
+                warn!("Building {} removed from manifest while its dev server is running", building.id);
+                reload.removed_running.push(building.id.clone());
+            } else {
+                self.statuses.remove(&building.id);
+            }
+        }
+
+        info!(
+            "Buildings manifest reloaded: {} added, {} removed",
+            reload.added.len(),
+            reload.removed.len()
+        );
+        self.manifest = manifest;
+        Ok(reload)
+    }
+
+    /// Reload the manifest at `path` if its modification time differs from
+    /// [`Self::last_manifest_modified`]. Returns `None` when unchanged (or
+    /// when the file can't be stat'ed).
+    pub fn reload_manifest_if_changed(&mut self, path: &Path) -> Option<Result<ManifestReload, String>> {
+        let modified = modified_time(path)?;
+        if self.last_manifest_modified == Some(modified) {
+            return None;
+        }
+        Some(self.reload_manifest(path))
+    }
+
     // ── Base directory ───────────────────────────────────────────────
 
     /// Set the base directory for all building project directories.
@@ -96,70 +291,122 @@ impl ProjectManager {
 
     // ── Scaffolding ─────────────────────────────────────────────────
 
-    /// Scaffold all building project directories under `base_dir`.
-    /// Returns a list of status messages (one per building).
-    pub async fn initialize_projects(&mut self) -> Result<Vec<String>, String> {
-        let base = self
-            .base_dir
-            .as_ref()
-            .ok_or_else(|| "Base directory not set".to_string())?
-            .clone();
-
-        let mut results = Vec::new();
+    /// Start scaffolding every building project under `base_dir` in the
+    /// background, [`SCAFFOLD_CONCURRENCY`] at a time. Returns how many
+    /// projects were queued; each one's outcome arrives through
+    /// [`Self::drain_init_progress`].
+    pub fn initialize_projects(&mut self) -> Result<usize, String> {
+        self.spawn_scaffolding(false, scaffold_job)
+    }
 
+    /// Stop all running servers, then delete and re-scaffold every project
+    /// directory in the background, like [`Self::initialize_projects`].
+    pub async fn reset_projects(&mut self) -> Result<usize, String> {
+        if self.base_dir.is_none() {
+            return Err("Base directory not set".to_string());
+        }
+        if self.initializing() {
+            return Err("Project initialization is already running".to_string());
+        }
+        self.stop_all_servers().await;
         for building in &self.manifest.buildings {
-            let dir = base.join(&building.directory_name);
-            match scaffold::scaffold_project(&dir, &building.name, &building.description, building.tier, building.port).await {
-                Ok(msg) => {
-                    self.statuses
-                        .insert(building.id.clone(), ProjectStatus::Ready);
-                    results.push(msg);
-                }
-                Err(e) => {
-                    self.statuses
-                        .insert(building.id.clone(), ProjectStatus::Error(e.clone()));
-                    results.push(format!("{}: ERROR - {}", building.name, e));
-                }
-            }
+            self.statuses
+                .insert(building.id.clone(), ProjectStatus::NotInitialized);
         }
-
-        self.initialized = true;
-        info!("Project initialization complete");
-        Ok(results)
+        self.initialized = false;
+        self.spawn_scaffolding(true, scaffold_job)
     }
 
-    /// Stop all running servers, delete all project directories, and
-    /// re-scaffold from scratch.
-    pub async fn reset_projects(&mut self) -> Result<Vec<String>, String> {
+    /// Queue one [`ScaffoldJob`] per manifest building and run them through
+    /// `scaffold` on a background task, at most [`SCAFFOLD_CONCURRENCY`] at
+    /// once and started in manifest order. With `clean`, each project's
+    /// directory is deleted before it is scaffolded.
+    fn spawn_scaffolding<F, Fut>(&mut self, clean: bool, scaffold: F) -> Result<usize, String>
+    where
+        F: Fn(ScaffoldJob) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
         let base = self
             .base_dir
             .as_ref()
             .ok_or_else(|| "Base directory not set".to_string())?
             .clone();
+        if self.initializing() {
+            return Err("Project initialization is already running".to_string());
+        }
 
-        // Stop everything first
-        self.stop_all_servers().await;
-
-        // Remove each building directory
-        for building in &self.manifest.buildings {
-            let dir = base.join(&building.directory_name);
-            if dir.exists() {
-                if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
-                    warn!(
-                        "Failed to remove directory {}: {}",
-                        dir.display(),
-                        e
-                    );
-                }
+        let jobs: Vec<ScaffoldJob> = self
+            .manifest
+            .buildings
+            .iter()
+            .map(|b| ScaffoldJob {
+                building_id: b.id.clone(),
+                name: b.name.clone(),
+                description: b.description.clone(),
+                tier: b.tier,
+                port: b.port,
+                dir: base.join(&b.directory_name),
+                clean,
+            })
+            .collect();
+        self.init_pending = jobs.len();
+        let queued = jobs.len();
+        let tx = self.init_tx.clone();
+        let scaffold = Arc::new(scaffold);
+
+        tokio::spawn(async move {
+            let permits = Arc::new(Semaphore::new(SCAFFOLD_CONCURRENCY));
+            for job in jobs {
+                let Ok(permit) = permits.clone().acquire_owned().await else {
+                    return;
+                };
+                let tx = tx.clone();
+                let scaffold = scaffold.clone();
+                tokio::spawn(async move {
+                    if job.clean && job.dir.exists() {
+                        if let Err(e) = tokio::fs::remove_dir_all(&job.dir).await {
+                            warn!("Failed to remove directory {}: {}", job.dir.display(), e);
+                        }
+                    }
+                    let building_id = job.building_id.clone();
+                    let result = scaffold(job).await;
+                    drop(permit);
+                    let _ = tx.send(InitProgress { building_id, result });
+                });
             }
-            self.statuses
-                .insert(building.id.clone(), ProjectStatus::NotInitialized);
-        }
+        });
 
-        self.initialized = false;
+        info!("Project initialization started: {} buildings", queued);
+        Ok(queued)
+    }
 
-        // Re-scaffold
-        self.initialize_projects().await
+    /// Whether a background initialization still has projects to finish.
+    pub fn initializing(&self) -> bool {
+        self.init_pending > 0
+    }
+
+    /// Apply every scaffolding result that has arrived since the last call
+    /// to `statuses`, and return them. Marks the manager initialized once
+    /// the last one is in.
+    pub fn drain_init_progress(&mut self) -> Vec<InitProgress> {
+        let mut progress = Vec::new();
+        while let Ok(p) = self.init_rx.try_recv() {
+            self.init_pending = self.init_pending.saturating_sub(1);
+            let status = match &p.result {
+                Ok(_) => ProjectStatus::Ready,
+                Err(e) => ProjectStatus::Error(e.clone()),
+            };
+            // A manifest reload may have dropped the building meanwhile.
+            if self.manifest.get_building(&p.building_id).is_some() {
+                self.statuses.insert(p.building_id.clone(), status);
+            }
+            progress.push(p);
+        }
+        if !progress.is_empty() && !self.initializing() {
+            self.initialized = true;
+            info!("Project initialization complete");
+        }
+        progress
     }
 
     // ── Dev servers ─────────────────────────────────────────────────
@@ -194,7 +441,22 @@ impl ProjectManager {
             ));
         }
 
-        let proc = process::start_dev_server(&dir, building.port).await?;
+        let free_port = process::find_free_port(building.port, self.port_range).ok_or_else(|| {
+            format!(
+                "No free port for {} in {}-{}",
+                building_id,
+                building.port,
+                building.port.saturating_add(self.port_range)
+            )
+        })?;
+        if free_port != building.port {
+            warn!(
+                "Port {} for {} is in use, using {} instead",
+                building.port, building_id, free_port
+            );
+        }
+
+        let proc = process::start_dev_server(&dir, free_port).await?;
         let port = proc.port;
 
         self.running_processes
@@ -244,6 +506,11 @@ impl ProjectManager {
             .unwrap_or(ProjectStatus::NotInitialized)
     }
 
+    /// Every building's status, keyed by building id, as sent to clients.
+    pub fn status_snapshot(&self) -> HashMap<String, ProjectStatusDto> {
+        self.statuses.iter().map(|(id, status)| (id.clone(), status.into())).collect()
+    }
+
     // ── Unlock management ───────────────────────────────────────────
 
     /// Unlock a building blueprint so it can be constructed.
@@ -320,6 +587,14 @@ impl ProjectManager {
             .unwrap_or_default()
     }
 
+    /// The building both agents are assigned to, if any.
+    pub fn shared_building(&self, a: u64, b: u64) -> Option<&str> {
+        self.agent_assignments
+            .iter()
+            .find(|(_id, agents)| agents.contains(&a) && agents.contains(&b))
+            .map(|(id, _agents)| id.as_str())
+    }
+
     // ── Utility ─────────────────────────────────────────────────────
 
     /// Convert a PascalCase building type name (e.g. "TodoApp") to its
@@ -347,3 +622,135 @@ impl ProjectManager {
         Some(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::manifest::tests::fixture;
+
+    #[test]
+    fn reload_diffs_building_ids() {
+        let mut manager = ProjectManager::new(&fixture("manifest_base.json"));
+        manager.unlock_building("calculator");
+        manager.statuses.insert("calculator".to_string(), ProjectStatus::Ready);
+        manager.statuses.insert("landing_page".to_string(), ProjectStatus::Ready);
+
+        let reload = manager.reload_manifest(&fixture("manifest_changed.json")).unwrap();
+        assert_eq!(reload.added, vec!["chat_app".to_string()]);
+        assert_eq!(reload.removed, vec!["landing_page".to_string()]);
+        assert!(reload.removed_running.is_empty());
+        assert!(reload.errors.is_empty());
+
+        // Unchanged ids keep their state and pick up edited fields.
+        assert!(manager.is_unlocked("calculator"));
+        assert_eq!(manager.get_status("calculator"), ProjectStatus::Ready);
+        assert_eq!(manager.manifest.get_building("calculator").unwrap().port, 3105);
+        // New ids start fresh; removed ids are gone.
+        assert!(manager.is_unlocked("chat_app"));
+        assert!(manager.statuses.get("chat_app") == Some(&ProjectStatus::NotInitialized));
+        assert!(!manager.statuses.contains_key("landing_page"));
+        assert!(manager.manifest.get_building("landing_page").is_none());
+    }
+
+    #[test]
+    fn invalid_reload_keeps_the_current_manifest() {
+        let mut manager = ProjectManager::new(&fixture("manifest_base.json"));
+        let reload = manager.reload_manifest(&fixture("manifest_duplicate_port.json")).unwrap();
+        assert_eq!(reload.errors.len(), 1);
+        assert!(reload.log_lines()[0].contains("port 3101"));
+        assert_eq!(manager.manifest.buildings.len(), 3);
+        assert!(manager.manifest.get_building("landing_page").is_some());
+
+        assert!(manager.reload_manifest(&fixture("manifest_malformed.json")).is_err());
+        assert_eq!(manager.manifest.buildings.len(), 3);
+    }
+
+    #[test]
+    fn edited_manifest_is_reloaded_once() {
+        let path = std::env::temp_dir().join(format!("ittb_manifest_reload_{}.json", std::process::id()));
+        std::fs::copy(fixture("manifest_base.json"), &path).unwrap();
+        let mut manager = ProjectManager::new(&path);
+        assert!(manager.last_manifest_modified.is_some());
+        assert!(manager.reload_manifest_if_changed(&path).is_none());
+        assert!(!manager.get_unlocked_buildings().contains(&"chat_app".to_string()));
+
+        std::fs::copy(fixture("manifest_changed.json"), &path).unwrap();
+        // Make sure the mtime moves even on coarse-grained filesystems.
+        let later = manager.last_manifest_modified.unwrap() + std::time::Duration::from_secs(2);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+
+        let reload = manager.reload_manifest_if_changed(&path).unwrap().unwrap();
+        assert_eq!(reload.added, vec!["chat_app".to_string()]);
+        assert!(manager.get_unlocked_buildings().contains(&"chat_app".to_string()));
+        assert!(manager.reload_manifest_if_changed(&path).is_none());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn scaffolding_runs_in_the_background_three_at_a_time() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+        use std::time::Duration;
+
+        let mut manager = ProjectManager::new(&fixture("manifest_base.json"));
+        for i in 0..3 {
+            let mut extra = manager.manifest.buildings[0].clone();
+            extra.id = format!("extra_{}", i);
+            manager.manifest.buildings.push(extra);
+        }
+        let ids: Vec<String> = manager.manifest.buildings.iter().map(|b| b.id.clone()).collect();
+        assert!(manager.initialize_projects().is_err(), "needs a base directory");
+        manager.base_dir = Some(std::env::temp_dir());
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let most_running = Arc::new(AtomicUsize::new(0));
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let stub = {
+            let (running, most_running, started) = (running.clone(), most_running.clone(), started.clone());
+            move |job: ScaffoldJob| {
+                let (running, most_running, started) = (running.clone(), most_running.clone(), started.clone());
+                async move {
+                    started.lock().unwrap().push(job.building_id.clone());
+                    most_running.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    if job.building_id == "extra_1" {
+                        Err("npm install failed".to_string())
+                    } else {
+                        Ok(format!("{}: scaffolded", job.name))
+                    }
+                }
+            }
+        };
+        assert_eq!(manager.spawn_scaffolding(false, stub.clone()).unwrap(), ids.len());
+        assert!(manager.initializing());
+        assert!(manager.spawn_scaffolding(false, stub).is_err(), "one initialization at a time");
+
+        // Stand in for the game loop: it keeps ticking while results trickle in.
+        let mut ticks = 0;
+        let mut batches = Vec::new();
+        while manager.initializing() && ticks < 1000 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            ticks += 1;
+            let progress = manager.drain_init_progress();
+            if !progress.is_empty() {
+                batches.push(progress.len());
+            }
+        }
+
+        assert!(!manager.initializing());
+        assert!(manager.initialized);
+        assert!(ticks > 5, "the loop ticked {} times", ticks);
+        assert!(batches.len() > 1, "results arrived all at once: {:?}", batches);
+        assert_eq!(most_running.load(Ordering::SeqCst), SCAFFOLD_CONCURRENCY);
+        assert_eq!(*started.lock().unwrap(), ids);
+        for id in &ids {
+            let status = manager.get_status(id);
+            if id == "extra_1" {
+                assert_eq!(status, ProjectStatus::Error("npm install failed".to_string()));
+            } else {
+                assert_eq!(status, ProjectStatus::Ready);
+            }
+        }
+    }
+}
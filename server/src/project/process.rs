@@ -1,6 +1,9 @@
+use std::net::TcpListener;
 use std::path::Path;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::net::TcpStream;
 use tokio::process::{Child, Command};
+use tokio::sync::oneshot;
 use tokio::time::{sleep, Duration};
 use tracing::{info, warn};
 
@@ -20,10 +23,47 @@ impl DevServerProcess {
     }
 }
 
+/// Whether nothing is listening on `port`, checked by binding and releasing
+/// it on both the loopback and wildcard addresses.
+pub fn port_available(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok() && TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// The first free port in `preferred..=preferred + range`, if any.
+pub fn find_free_port(preferred: u16, range: u16) -> Option<u16> {
+    (preferred..=preferred.saturating_add(range)).find(|&port| port_available(port))
+}
+
+/// Extracts the port from Vite's `Local:` startup line, e.g.
+/// `  ➜  Local:   http://localhost:5174/`. Colour codes are ignored.
+pub fn parse_vite_port(line: &str) -> Option<u16> {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip an escape sequence up to its terminating letter.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+
+    let (_, url) = plain.split_once("Local:")?;
+    let (_, rest) = url.trim().split_once("://")?;
+    let host_port = rest.split('/').next()?;
+    host_port.rsplit_once(':')?.1.parse().ok()
+}
+
 /// Spawn a Vite dev server inside `dir` on the given port.
 /// Uses the project-local vite binary directly (node_modules/.bin/vite)
 /// to ensure the correct working directory is used.
 /// Waits for the server to actually accept connections before returning.
+/// The returned process carries the port Vite reports binding, which may
+/// differ from `port` if it was taken in the meantime.
 pub async fn start_dev_server(dir: &Path, port: u16) -> Result<DevServerProcess, String> {
     info!(
         "Starting dev server in {} on port {}",
@@ -36,7 +76,7 @@ pub async fn start_dev_server(dir: &Path, port: u16) -> Result<DevServerProcess,
     // Use the project-local vite binary directly for reliable cwd handling.
     // Falls back to npx if the binary isn't found.
     let vite_bin = dir.join("node_modules").join(".bin").join("vite");
-    let child = if vite_bin.exists() {
+    let mut child = if vite_bin.exists() {
         Command::new(&vite_bin)
             .args(["--port", &port_str, "--host"])
             .current_dir(dir)
@@ -56,12 +96,39 @@ pub async fn start_dev_server(dir: &Path, port: u16) -> Result<DevServerProcess,
             .map_err(|e| format!("Failed to spawn npx vite in {}: {}", dir.display(), e))?
     };
 
+    // Watch stdout for the port Vite actually bound, draining the rest so
+    // the pipe never fills.
+    let (port_tx, mut port_rx) = oneshot::channel();
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            let mut port_tx = Some(port_tx);
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(bound) = parse_vite_port(&line) {
+                    if let Some(tx) = port_tx.take() {
+                        let _ = tx.send(bound);
+                    }
+                }
+            }
+        });
+    }
+
     // Wait for the server to accept TCP connections before reporting ready.
-    let addr = format!("127.0.0.1:{}", port);
+    let mut port = port;
+    let mut reported = false;
     let mut ready = false;
     for _ in 0..60 {
         sleep(Duration::from_millis(250)).await;
-        if TcpStream::connect(&addr).await.is_ok() {
+        if !reported {
+            if let Ok(bound) = port_rx.try_recv() {
+                reported = true;
+                if bound != port {
+                    warn!("Dev server asked for port {} but Vite bound {}", port, bound);
+                    port = bound;
+                }
+            }
+        }
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
             ready = true;
             break;
         }
@@ -75,3 +142,35 @@ pub async fn start_dev_server(dir: &Path, port: u16) -> Result<DevServerProcess,
 
     Ok(DevServerProcess { child, port })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occupied_port_falls_back_to_the_next_free_one() {
+        // Let the OS pick a free port, then hold it.
+        let held = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = held.local_addr().unwrap().port();
+
+        assert!(!port_available(port));
+        let fallback = find_free_port(port, 20).unwrap();
+        assert!(fallback > port);
+        assert!((port + 1..fallback).all(|p| !port_available(p)));
+
+        assert_eq!(find_free_port(port, 0), None);
+        drop(held);
+        assert_eq!(find_free_port(port, 20), Some(port));
+    }
+
+    #[test]
+    fn vite_port_is_parsed_from_startup_output() {
+        assert_eq!(parse_vite_port("  ➜  Local:   http://localhost:5174/"), Some(5174));
+        assert_eq!(
+            parse_vite_port("  \x1b[32m➜\x1b[39m  \x1b[1mLocal\x1b[22m:   \x1b[36mhttp://localhost:\x1b[1m5180\x1b[22m/\x1b[39m"),
+            Some(5180)
+        );
+        assert_eq!(parse_vite_port("  ➜  Network: http://192.168.1.4:5174/"), None);
+        assert_eq!(parse_vite_port("VITE v5.0.0  ready in 300 ms"), None);
+    }
+}
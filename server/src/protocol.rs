@@ -6,9 +6,36 @@ use std::collections::HashMap;
 pub type EntityId = u64;
 pub type Tick = u64;
 
+// ── Versioning ─────────────────────────────────────────────────────
+
+/// Current protocol version: the major version in the high 16 bits, the
+/// minor in the low 16. Bump the minor for additive schema changes and the
+/// major for breaking ones.
+pub const PROTOCOL_VERSION: u32 = 2 << 16;
+
+/// Whether peers speaking protocol versions `a` and `b` can talk to each
+/// other, i.e. share a major version.
+pub fn compatibility_check(a: u32, b: u32) -> bool {
+    a >> 16 == b >> 16
+}
+
+/// A message tagged with the protocol version it was encoded with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedMessage<T> {
+    pub protocol_version: u32,
+    pub payload: T,
+}
+
+impl<T> VersionedMessage<T> {
+    /// Wraps `payload` at the current [`PROTOCOL_VERSION`].
+    pub fn new(payload: T) -> Self {
+        Self { protocol_version: PROTOCOL_VERSION, payload }
+    }
+}
+
 // ── Geometry ───────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -27,6 +54,12 @@ pub struct PlayerSnapshot {
     pub dead: bool,
     pub death_timer: f32,
     pub attack_cooldown_pct: f32,
+    /// Remaining weapon durability (0..1); 1.0 for weapons that never wear.
+    pub weapon_durability_pct: f32,
+    pub dodge_cooldown_remaining: u32,
+    /// Weight of everything in the inventory.
+    pub carry_weight: u32,
+    pub carry_capacity: u32,
 }
 
 // ── Entities ───────────────────────────────────────────────────────
@@ -38,9 +71,11 @@ pub enum EntityKind {
     Rogue,
     Item,
     Projectile,
+    /// A recruited survivor; carries `EntityData::Agent`.
+    Scout,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EntityDelta {
     pub id: EntityId,
     pub kind: EntityKind,
@@ -48,7 +83,7 @@ pub struct EntityDelta {
     pub data: EntityData,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EntityData {
     Agent {
         name: String,
@@ -64,15 +99,34 @@ pub enum EntityData {
         level: u32,
         recruitable_cost: Option<i64>,
         bound: bool,
+        rest_debt_remaining: u32,
+        specialization: Option<AgentSpecialization>,
+        /// Personality traits, e.g. "curious".
+        traits: Vec<String>,
+        /// Tick of the checkpoint the agent can be rolled back to.
+        checkpoint_tick: Option<u64>,
     },
     Building {
         building_type: BuildingTypeKind,
         construction_pct: f32,
         health_pct: f32,
+        /// Radius within which the building slows rogues, if it has one.
+        zone_of_control_radius: Option<f32>,
+        /// Inside at least one completed pylon's radius.
+        powered: bool,
     },
     Rogue {
         rogue_type: RogueTypeKind,
         health_pct: f32,
+        status_effects: Vec<StatusEffect>,
+        /// False while a TokenDrain is lurking unattached.
+        visible: bool,
+        /// A Swarm hunting with enough of its pack to get the speed boost.
+        in_pack: bool,
+        /// Being thrown back by a heavy hit.
+        knockback_active: bool,
+        /// A Mimic's ambush progress; `None` for other rogues.
+        mimic_phase: Option<MimicPhaseSnapshot>,
     },
     Item {
         item_type: String,
@@ -96,6 +150,7 @@ pub enum AgentStateKind {
     Critical,
     Unresponsive,
     Dormant,
+    Resting,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -106,12 +161,27 @@ pub enum AgentTierKind {
     Architect,
 }
 
+/// A role an agent can be specialized into once, each with a passive bonus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AgentSpecialization {
+    /// Builds faster.
+    Builder,
+    /// Picks up discoveries from further away while exploring.
+    Explorer,
+    /// More health and takes less damage.
+    Defender,
+    /// Adds a star to the grade of the buildings it is assigned to.
+    Analyst,
+}
+
 // ── Building types ─────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BuildingTypeKind {
     // Infrastructure
     Pylon,
+    Relay,
+    Nexus,
     ComputeFarm,
 
     // Tier 1
@@ -123,6 +193,7 @@ pub enum BuildingTypeKind {
     WeatherDashboard,
     ChatApp,
     KanbanBoard,
+    Watchtower,
 
     // Tier 3
     EcommerceStore,
@@ -135,6 +206,18 @@ pub enum BuildingTypeKind {
     // Home Base
     TokenWheel,
     CraftingTable,
+
+    // Hostile (never placed by the player)
+    Nest,
+}
+
+// ── Status effects ─────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StatusEffect {
+    Burning { damage_per_tick: i32, ticks_remaining: u32 },
+    Slowed { factor: f32, ticks_remaining: u32 },
+    Corrupted { ticks_remaining: u32 },
 }
 
 // ── Rogue types ────────────────────────────────────────────────────
@@ -148,6 +231,8 @@ pub enum RogueTypeKind {
     Swarm,
     Mimic,
     Architect,
+    Multiplier,
+    Plague,
 }
 
 // ── Fog of war / chunks ────────────────────────────────────────────
@@ -163,6 +248,21 @@ pub struct FogTile {
     pub light_level: f32,
 }
 
+/// An entity marker on the minimap, in chunk coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MinimapDot {
+    pub x: i32,
+    pub y: i32,
+    /// 0xRRGGBB colour.
+    pub color: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinimapSnapshot {
+    pub revealed_chunks: Vec<ChunkPos>,
+    pub entity_dots: Vec<MinimapDot>,
+}
+
 // ── Logging ────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -192,6 +292,31 @@ pub enum AudioEvent {
     RogueSpawn,
     CrankTurn,
     AgentDeath,
+    LevelUp,
+    CritHit,
+    MimicReveal,
+    /// A rogue wave hits in `WAVE_WARNING_TICKS`.
+    WaveWarning,
+}
+
+// ── Progression ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GamePhase {
+    #[default]
+    Hut,
+    Outpost,
+    Village,
+    Network,
+    City,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrankTier {
+    HandCrank,
+    GearAssembly,
+    WaterWheel,
+    RunicEngine,
 }
 
 // ── Economy ────────────────────────────────────────────────────────
@@ -205,18 +330,89 @@ pub struct EconomySnapshot {
     pub expenditure_sinks: Vec<(String, f64)>,
 }
 
+// ── Transactions ───────────────────────────────────────────────────
+
+/// One change to the token balance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionEntry {
+    pub tick: Tick,
+    pub delta: i64,
+    pub source: String,
+    pub balance_after: i64,
+}
+
+/// The recent transaction history, sent periodically or on request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionLogSlice {
+    pub entries: Vec<TransactionEntry>,
+}
+
+/// A locked investment, as shown to the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvestmentSnapshot {
+    pub principal: i64,
+    pub return_amount: i64,
+    pub matures_at: Tick,
+}
+
+// ── Mimic phase snapshot ──────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MimicPhaseSnapshot {
+    pub elapsed_ticks: u32,
+    pub revealed: bool,
+}
+
 // ── Wheel snapshot ────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WheelSnapshot {
-    pub tier: String,
+    pub tier: CrankTier,
     pub tokens_per_rotation: f64,
     pub agent_bonus_per_tick: f64,
     pub heat: f32,
     pub max_heat: f32,
     pub is_cranking: bool,
-    pub assigned_agent_id: Option<u64>,
+    pub assigned_agent_ids: Vec<u64>,
     pub upgrade_cost: Option<i64>,
+    /// Ticks left before an overheated crank can be worked again.
+    pub overheated_remaining: u32,
+    /// Heat is high enough to damage buildings near the wheel.
+    pub overheating: bool,
+}
+
+// ── Building synergies ─────────────────────────────────────────────
+
+/// A synergy currently active between two nearby buildings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SynergyDescriptor {
+    pub name: String,
+    pub building_ids: Vec<EntityId>,
+}
+
+// ── Vibe sessions ──────────────────────────────────────────────────
+
+/// Counters for one agent's coding session, gathered from its PTY output.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VibeMetrics {
+    /// Times the CLI has shown its `> ` prompt, i.e. finished a turn.
+    pub turns_completed: u32,
+    pub bytes_output: u64,
+    pub started_at_tick: Tick,
+    /// Set once the session has exited.
+    pub ended_at_tick: Option<Tick>,
+}
+
+// ── Rogue waves ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaveSnapshot {
+    /// Waves spawned so far.
+    pub wave_number: u32,
+    /// Ticks until the next wave hits.
+    pub next_wave_in_ticks: u64,
+    /// Rogues from the current wave are still alive.
+    pub active: bool,
 }
 
 // ── Debug snapshot ─────────────────────────────────────────────────
@@ -225,22 +421,47 @@ pub struct WheelSnapshot {
 pub struct DebugSnapshot {
     pub spawning_enabled: bool,
     pub god_mode: bool,
-    pub phase: String,
-    pub crank_tier: String,
+    pub phase: GamePhase,
+    pub crank_tier: CrankTier,
+    /// Sub-token crank generation not yet paid into the balance.
+    pub fractional: f64,
+    /// Read-only clients watching the game.
+    pub connected_spectators: u8,
+    /// Requirements for the next phase and whether each is met.
+    pub phase_progress: HashMap<String, bool>,
 }
 
 // ── Project manager ───────────────────────────────────────────
 
+/// A building's project status as sent to the client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProjectStatusDto {
+    NotInitialized,
+    Ready,
+    Running { port: u16 },
+    Error { message: String },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectManagerState {
     pub base_dir: Option<String>,
     pub initialized: bool,
     pub unlocked_buildings: Vec<String>,
-    pub building_statuses: HashMap<String, String>, // building_id -> status string
+    pub building_statuses: HashMap<String, ProjectStatusDto>, // building_id -> status
     pub agent_assignments: HashMap<String, Vec<u64>>, // building_id -> agent entity ids
+    pub building_priorities: HashMap<u64, f32>, // building entity id -> priority weight
     pub building_grades: HashMap<String, BuildingGradeState>,
 }
 
+/// A building's current grade, sent with every update.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeSnapshot {
+    pub building_id: String,
+    pub stars: u8,
+    pub reasoning: String,
+    pub grading: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildingGradeState {
     pub stars: u8,
@@ -257,10 +478,19 @@ pub struct CombatEvent {
     pub damage: i32,
     pub is_kill: bool,
     pub rogue_type: Option<RogueTypeKind>,
+    pub is_crit: bool,
 }
 
 // ── Chest rewards ─────────────────────────────────────────────────
 
+/// Loot dropped by a killed rogue; `entity_id` is the item on the ground.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DropEvent {
+    pub entity_id: EntityId,
+    pub item_type: String,
+    pub count: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChestReward {
     /// e.g. "token", "material:iron_powder", "blueprint:TodoApp"
@@ -270,7 +500,7 @@ pub struct ChestReward {
 
 // ── Inventory ─────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub item_type: String,
     pub count: u32,
@@ -280,17 +510,22 @@ pub struct InventoryItem {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameStateUpdate {
+    /// [`PROTOCOL_VERSION`] of the server that produced this update.
+    pub protocol_version: u32,
     pub tick: Tick,
     pub player: PlayerSnapshot,
     pub entities_changed: Vec<EntityDelta>,
     pub entities_removed: Vec<EntityId>,
     pub fog_updates: Vec<(ChunkPos, Vec<FogTile>)>,
+    /// Sent once a second, and whenever new chunks are revealed.
+    pub minimap: Option<MinimapSnapshot>,
     pub economy: EconomySnapshot,
     pub log_entries: Vec<LogEntry>,
     pub audio_triggers: Vec<AudioEvent>,
     pub debug: DebugSnapshot,
     pub wheel: WheelSnapshot,
     pub project_manager: Option<ProjectManagerState>,
+    pub grades: Vec<GradeSnapshot>,
     pub combat_events: Vec<CombatEvent>,
     pub player_hit: bool,
     pub player_hit_damage: i32,
@@ -298,6 +533,28 @@ pub struct GameStateUpdate {
     pub purchased_upgrades: Vec<String>,
     pub opened_chests: Vec<(i32, i32)>,
     pub chest_rewards: Vec<ChestReward>,
+    pub transaction_log: Option<TransactionLogSlice>,
+    pub active_investments: Vec<InvestmentSnapshot>,
+    pub drops: Vec<DropEvent>,
+    /// Ids of the recipes the player can craft right now.
+    pub recipes_available: Vec<String>,
+    pub wave: WaveSnapshot,
+    pub active_synergies: Vec<SynergyDescriptor>,
+    pub vibe_metrics: HashMap<u64, VibeMetrics>, // agent entity id -> session metrics
+}
+
+/// A `GameStateUpdate` reduced to the entities that changed since `base_tick`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameStateUpdateDelta {
+    /// Tick of the update this delta applies on top of.
+    pub base_tick: Tick,
+    /// Entities whose position or data differ from the base update.
+    pub changed: Vec<EntityDelta>,
+    /// Entities despawned this tick or no longer present since the base.
+    pub removed: Vec<EntityId>,
+    /// The rest of the update; its `entities_changed` / `entities_removed`
+    /// are left empty in favour of `changed` / `removed`.
+    pub state: GameStateUpdate,
 }
 
 // ── AI Backend ────────────────────────────────────────────────────
@@ -313,7 +570,12 @@ pub enum AiBackend {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PlayerAction {
     Attack,
+    /// Roll in `direction` (or along facing if zero), briefly invulnerable.
+    Dodge { direction: Vec2 },
     Interact,
+    /// Interact with a specific discovery (or the nearest one within reach).
+    InteractDiscovery { entity_id: u64 },
+    RequestTransactionLog,
     AssignTask,
     OpenBuildMenu,
     PlaceBuilding {
@@ -321,6 +583,18 @@ pub enum PlayerAction {
         x: f32,
         y: f32,
     },
+    /// Ask whether `PlaceBuilding` would succeed here, ignoring cost, so the
+    /// client can tint its ghost preview. Answered with `PlacementValidity`.
+    ValidatePlacement {
+        building_type: BuildingTypeKind,
+        x: f32,
+        y: f32,
+    },
+    UpgradeBuilding { entity_id: u64 },
+    /// Restore a damaged building to full health; costs tokens per missing HP.
+    RepairBuilding { entity_id: u64 },
+    /// Weight a building's share of construction work; clamped to [0.1, 5.0].
+    SetBuildingPriority { entity_id: u64, priority: f32 },
     CrankStart,
     CrankStop,
 
@@ -329,31 +603,47 @@ pub enum PlayerAction {
     ReviveAgent { entity_id: u64 },
     UpgradeWheel,
     AssignAgentToWheel { agent_id: u64 },
-    UnassignAgentFromWheel,
-
-    RollbackAgent,
+    UnassignAgentFromWheel { agent_id: u64 },
+    /// Dump all the crank's heat for 50 tokens.
+    EmergencyVent,
+
+    /// Restore an agent to the checkpoint taken when its last vibe session
+    /// started, ending that session.
+    RollbackAgent { agent_id: u64 },
+    SpecializeAgent { agent_id: u64, specialization: AgentSpecialization },
+    InteractSurvivor { entity_id: u64 },
+    ReloadManifest,
     EquipWeapon { weapon_id: String },
     EquipArmor { armor_id: String },
 
     // Crafting actions
     CraftItem { recipe_id: String },
+    /// Repair the equipped weapon at a nearby CraftingTable.
+    RepairWeapon,
+    /// Lock `amount` tokens for `duration_ticks`, returned with interest.
+    Invest { amount: i64, duration_ticks: u64 },
     OpenChest { wx: i32, wy: i32 },
     PurchaseUpgrade { upgrade_id: String },
     AddInventoryItem { item_type: String, count: u32 },
     RemoveInventoryItem { item_type: String, count: u32 },
+    /// Buy more carry capacity at the quoted `cost`.
+    UpgradeCarryCapacity { cost: i64 },
 
     // Debug actions
     DebugSetTokens { amount: i64 },
     DebugAddTokens { amount: i64 },
     DebugToggleSpawning,
     DebugClearRogues,
-    DebugSetPhase { phase: String },
-    DebugSetCrankTier { tier: String },
+    DebugSetPhase { phase: GamePhase },
+    DebugSetCrankTier { tier: CrankTier },
     DebugToggleGodMode,
     DebugSpawnRogue { rogue_type: RogueTypeKind },
+    DebugSpawnBoss,
     DebugHealPlayer,
     DebugSpawnAgent { tier: AgentTierKind },
+    DebugGrantAgentXP { agent_id: u64, amount: u64 },
     DebugClearAgents,
+    DebugSetAllPriorities { priority: f32 },
 
     // Project management actions
     SetProjectDirectory { path: String },
@@ -365,16 +655,27 @@ pub enum PlayerAction {
     UnassignAgentFromProject { agent_id: u64, building_id: String },
     DebugUnlockAllBuildings,
     DebugLockAllBuildings,
+    DebugClearFog,
+    /// Feeds a double-speed movement input through validation to exercise
+    /// the rejection path.
+    DebugTriggerCheatInput,
     UnlockBuilding { building_id: String },
 
     // Vibe session actions
     VibeInput { agent_id: u64, data: String },
+    /// Pass a message from one agent's session to another's; both must be
+    /// assigned to the same building.
+    AgentRelay { from_agent_id: u64, to_agent_id: u64, message: String },
     SetMistralApiKey { key: String },
     SetAiBackend { backend: AiBackend },
 
     // Grading actions
     GradeBuilding { building_id: String },
     SetAnthropicApiKey { key: String },
+
+    // Save / load actions
+    SaveGame { slot: u8 },
+    LoadGame { slot: u8 },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -398,15 +699,91 @@ pub struct PlayerInput {
 /// are wrapped in this enum so the client can distinguish between
 /// game state updates and vibe terminal I/O.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum ServerMessage {
-    /// Normal game state update (20Hz).
+    /// Normal game state update, once per tick. Sent in full for the first frame
+    /// of each connection.
     GameState(GameStateUpdate),
+    /// Game state update carrying only the entities that changed since the
+    /// previous frame.
+    GameStateDelta(GameStateUpdateDelta),
     /// Real-time PTY output from a vibe session.
     VibeOutput { agent_id: u64, data: Vec<u8> },
     /// Vibe session started.
-    VibeSessionStarted { agent_id: u64 },
+    VibeSessionStarted { agent_id: u64, enabled_tools: Vec<String> },
     /// Vibe session ended.
     VibeSessionEnded { agent_id: u64, reason: String },
     /// Grade result from LLM evaluation.
     GradeResult { building_id: String, stars: u8, reasoning: String },
+    /// A building was graded automatically after an agent's vibe session
+    /// finished on it.
+    BuildingGraded { building_id: String, stars: u8, reasoning: String },
+    /// An agent reached a new level.
+    LevelUpEvent { agent_id: u64, new_level: u32 },
+    /// The client sent a message this server couldn't decode or whose
+    /// version it doesn't support. `client_version` is 0 if unknown.
+    ProtocolMismatch { server_version: u32, client_version: u32 },
+    /// Last message before the server exits on Ctrl-C / SIGTERM.
+    ServerShutdown { reason: String },
+    /// A relay between two agents' sessions was delivered.
+    AgentRelayAck { from: u64, to: u64 },
+    /// An agent said something; shown as a speech bubble over it.
+    AgentSpeech { agent_id: u64, text: String },
+    /// A building's project finished scaffolding during a background
+    /// initialization or reset.
+    ProjectInitProgress { building_id: String, status: ProjectStatusDto },
+    /// Answer to `ValidatePlacement`; `reason` says why when not `valid`.
+    PlacementValidity {
+        building_type: BuildingTypeKind,
+        x: f32,
+        y: f32,
+        valid: bool,
+        reason: Option<String>,
+    },
+    /// Terrain for chunk `(cx, cy)` the client hasn't been sent yet.
+    /// `tiles` is run-length encoded `(count, code)` byte pairs over the
+    /// chunk's 32x32 tiles, row by row; a code's low bits are the terrain
+    /// and `0x80` marks a tile that blocks movement.
+    ChunkData { cx: i32, cy: i32, tiles: Vec<u8> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+        rmp_serde::from_slice(&rmp_serde::to_vec_named(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn phases_and_tiers_round_trip() {
+        for phase in [GamePhase::Hut, GamePhase::Outpost, GamePhase::Village, GamePhase::Network, GamePhase::City] {
+            assert_eq!(round_trip(&phase), phase);
+        }
+        for tier in [CrankTier::HandCrank, CrankTier::GearAssembly, CrankTier::WaterWheel, CrankTier::RunicEngine] {
+            assert_eq!(round_trip(&tier), tier);
+        }
+    }
+
+    #[test]
+    fn project_statuses_round_trip() {
+        let statuses = [
+            ProjectStatusDto::NotInitialized,
+            ProjectStatusDto::Ready,
+            ProjectStatusDto::Running { port: 3001 },
+            ProjectStatusDto::Error { message: "port in use".to_string() },
+        ];
+        for status in statuses {
+            assert_eq!(round_trip(&status), status);
+        }
+    }
+
+    #[test]
+    fn debug_actions_carry_enums() {
+        let action = PlayerAction::DebugSetCrankTier { tier: CrankTier::WaterWheel };
+        match round_trip(&action) {
+            PlayerAction::DebugSetCrankTier { tier } => assert_eq!(tier, CrankTier::WaterWheel),
+            other => panic!("unexpected action {other:?}"),
+        }
+    }
 }
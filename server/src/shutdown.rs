@@ -0,0 +1,121 @@
+//! Graceful shutdown: what the server does on Ctrl-C or SIGTERM before it
+//! exits, so no vibe PTY or dev server outlives it.
+
+use std::path::Path;
+use std::time::Duration;
+
+use hecs::World;
+use tracing::{info, warn};
+
+use crate::ecs::components::GameState;
+use crate::game::save;
+use crate::network::server::GameServer;
+use crate::project::ProjectManager;
+use crate::protocol::ServerMessage;
+use crate::vibe::manager::VibeManager;
+
+/// How long to wait after the final message so the write task can flush it
+/// to the client before the runtime goes away.
+pub const SHUTDOWN_FLUSH_GRACE: Duration = Duration::from_millis(100);
+
+/// Resolves when the process is asked to stop: Ctrl-C anywhere, or SIGTERM
+/// on unix.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Ctrl-C received"),
+        _ = terminate => info!("SIGTERM received"),
+    }
+}
+
+/// Tells the client the server is going away, kills every vibe session and
+/// dev server, then writes a final autosave to `autosave_path`.
+pub async fn shutdown(
+    server: &mut GameServer,
+    vibe_manager: &mut VibeManager,
+    project_manager: &mut ProjectManager,
+    game_state: &GameState,
+    world: &World,
+    autosave_path: &Path,
+) {
+    info!("Shutting down");
+    server.send_message(&ServerMessage::ServerShutdown {
+        reason: "server shutting down".to_string(),
+    });
+
+    vibe_manager.kill_all();
+    project_manager.stop_all_servers().await;
+
+    match save::save_game(game_state, world, &project_manager.agent_assignments, autosave_path) {
+        Ok(()) => info!("Final autosave written to {:?}", autosave_path),
+        Err(e) => warn!("Final autosave failed: {}", e),
+    }
+
+    tokio::time::sleep(SHUTDOWN_FLUSH_GRACE).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::world::create_world;
+    use futures_util::StreamExt;
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::client_async;
+
+    #[tokio::test]
+    async fn shutdown_notifies_the_client_and_saves() {
+        let mut server = GameServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr();
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let (mut client, _resp) = client_async(format!("ws://{}", addr), stream).await.unwrap();
+        server.wait_for_client().await;
+
+        let mut vibe_manager = VibeManager::new();
+        let mut project_manager = ProjectManager::new(Path::new("/nonexistent/buildings_manifest.json"));
+        let (world, game_state) = create_world();
+        let path = std::env::temp_dir().join(format!("ittb_shutdown_test_{}.sav", std::process::id()));
+
+        shutdown(&mut server, &mut vibe_manager, &mut project_manager, &game_state, &world, &path).await;
+
+        assert!(path.exists());
+        let (loaded_state, _world, _assignments) = save::load_game(&path).unwrap();
+        assert_eq!(loaded_state.tick, game_state.tick);
+        std::fs::remove_file(&path).unwrap();
+
+        let frame = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let msg = client.next().await.unwrap().unwrap();
+                if msg.is_binary() {
+                    return msg;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        match rmp_serde::from_slice::<ServerMessage>(&frame.into_data()).unwrap() {
+            ServerMessage::ServerShutdown { reason } => assert_eq!(reason, "server shutting down"),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+}
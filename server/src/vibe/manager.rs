@@ -1,10 +1,49 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use tokio::sync::mpsc;
 use tracing::info;
 
-use crate::protocol::AiBackend;
-use super::session::VibeSession;
+use crate::protocol::{AiBackend, Tick, VibeMetrics};
+use super::session::{OutputParser, SessionStats, VibeSession};
+
+/// Delay before the first retry of a failed session spawn (1 second at 20Hz).
+pub const RETRY_BASE_TICKS: u64 = 20;
+
+/// Longest delay between retries (30 seconds at 20Hz).
+pub const RETRY_MAX_TICKS: u64 = 600;
+
+/// A failed session spawn and when it may be retried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedSpawnEntry {
+    pub agent_id: u64,
+    pub retry_at_tick: u64,
+    /// Failed spawns so far, including the most recent one.
+    pub attempt_count: u32,
+}
+
+/// Ticks to wait after the `attempt`th consecutive failure: 20, 40, 80, ...
+/// doubling up to [`RETRY_MAX_TICKS`].
+pub fn retry_delay(attempt: u32) -> u64 {
+    let doublings = attempt.saturating_sub(1).min(u64::BITS - 1);
+    RETRY_BASE_TICKS.saturating_mul(1 << doublings).min(RETRY_MAX_TICKS)
+}
+
+/// Number of relays kept in `VibeManager::relay_history`.
+pub const RELAY_HISTORY_LEN: usize = 20;
+
+/// A message the player relayed from one agent's session to another's.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayEntry {
+    pub from_agent_id: u64,
+    pub to_agent_id: u64,
+    pub message: String,
+    pub tick: u64,
+}
+
+/// The bytes typed into the receiving session for a relay from `from_name`.
+pub fn format_relay(from_name: &str, message: &str) -> String {
+    format!("[relay from {}]: {}\r", from_name, message)
+}
 
 /// Manages all active Vibe CLI sessions.
 pub struct VibeManager {
@@ -12,8 +51,22 @@ pub struct VibeManager {
     api_key: Option<String>,
     backend: AiBackend,
     output_receivers: HashMap<u64, mpsc::UnboundedReceiver<Vec<u8>>>,
-    /// Tracks agents whose session spawn failed, so we don't retry every tick.
-    failed_spawns: std::collections::HashSet<u64>,
+    /// Parsers reading each session's output. Kept after the session exits
+    /// so its final stats can be read; replaced when a new session starts.
+    parsers: HashMap<u64, OutputParser>,
+    /// Final metrics of sessions that have exited, kept like `parsers`.
+    finished_metrics: HashMap<u64, VibeMetrics>,
+    /// Agents whose session spawn failed, backing off exponentially so we
+    /// don't retry every tick.
+    failed_spawns: HashMap<u64, FailedSpawnEntry>,
+    /// The last [`RELAY_HISTORY_LEN`] relays between sessions, oldest first.
+    relay_history: VecDeque<RelayEntry>,
+}
+
+impl Default for VibeManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl VibeManager {
@@ -27,7 +80,10 @@ impl VibeManager {
             api_key,
             backend: AiBackend::MistralVibe,
             output_receivers: HashMap::new(),
-            failed_spawns: std::collections::HashSet::new(),
+            parsers: HashMap::new(),
+            finished_metrics: HashMap::new(),
+            failed_spawns: HashMap::new(),
+            relay_history: VecDeque::new(),
         }
     }
 
@@ -48,11 +104,12 @@ impl VibeManager {
     pub fn has_api_key(&self) -> bool {
         match self.backend {
             AiBackend::ClaudeCode => true,
-            AiBackend::MistralVibe => self.api_key.as_ref().map_or(false, |k| !k.is_empty()),
+            AiBackend::MistralVibe => self.api_key.as_ref().is_some_and(|k| !k.is_empty()),
         }
     }
 
-    /// Spawn a vibe session for an agent at its building.
+    /// Spawn a vibe session for an agent at its building at `tick`.
+    #[allow(clippy::too_many_arguments)]
     pub fn start_session(
         &mut self,
         agent_id: u64,
@@ -61,6 +118,7 @@ impl VibeManager {
         vibe_agent_name: String,
         max_turns: u32,
         enabled_tools: Vec<String>,
+        tick: Tick,
     ) -> Result<(), String> {
         let api_key = match self.backend {
             AiBackend::MistralVibe => {
@@ -88,10 +146,14 @@ impl VibeManager {
             enabled_tools,
             output_tx,
             self.backend,
+            tick,
         )?;
 
         self.sessions.insert(agent_id, session);
         self.output_receivers.insert(agent_id, output_rx);
+        self.parsers.insert(agent_id, OutputParser::new());
+        self.finished_metrics.remove(&agent_id);
+        self.failed_spawns.remove(&agent_id);
 
         Ok(())
     }
@@ -105,17 +167,48 @@ impl VibeManager {
         session.write_input(data)
     }
 
+    /// Types `message` into `to`'s session under a `[relay from
+    /// {from_name}]:` header and records it in the relay history. Both
+    /// agents need a running session.
+    pub fn relay(&mut self, from: u64, from_name: &str, to: u64, message: &str, tick: u64) -> Result<(), String> {
+        if from == to {
+            return Err("An agent can't relay to itself".to_string());
+        }
+        if !self.has_session(from) {
+            return Err(format!("No session for agent {}", from));
+        }
+        self.send_input(to, format_relay(from_name, message).as_bytes())?;
+
+        self.relay_history.push_back(RelayEntry {
+            from_agent_id: from,
+            to_agent_id: to,
+            message: message.to_string(),
+            tick,
+        });
+        while self.relay_history.len() > RELAY_HISTORY_LEN {
+            self.relay_history.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Recent relays, oldest first.
+    pub fn relay_history(&self) -> &VecDeque<RelayEntry> {
+        &self.relay_history
+    }
+
     /// Kill and remove a session.
     pub fn kill_session(&mut self, agent_id: u64) {
         if let Some(mut session) = self.sessions.remove(&agent_id) {
             session.kill();
         }
         self.output_receivers.remove(&agent_id);
+        self.parsers.remove(&agent_id);
+        self.finished_metrics.remove(&agent_id);
         info!("Vibe session removed for agent {}", agent_id);
     }
 
-    /// Check for exited sessions. Returns (agent_id, success).
-    pub fn poll_exits(&mut self) -> Vec<(u64, bool)> {
+    /// Check for sessions that exited by `tick`. Returns (agent_id, success).
+    pub fn poll_exits(&mut self, tick: Tick) -> Vec<(u64, bool)> {
         let mut finished = Vec::new();
         for (agent_id, session) in &mut self.sessions {
             if let Some(success) = session.try_wait() {
@@ -123,39 +216,93 @@ impl VibeManager {
             }
         }
         for (agent_id, _) in &finished {
-            self.sessions.remove(agent_id);
+            if let Some(session) = self.sessions.remove(agent_id) {
+                let metrics = VibeMetrics { ended_at_tick: Some(tick), ..session.metrics() };
+                self.finished_metrics.insert(*agent_id, metrics);
+            }
             self.output_receivers.remove(agent_id);
         }
         finished
     }
 
-    /// Drain all pending PTY output. Returns Vec of (agent_id, bytes).
+    /// Drain all pending PTY output, feeding it to each session's parser.
+    /// Returns Vec of (agent_id, bytes).
     pub fn drain_output(&mut self) -> Vec<(u64, Vec<u8>)> {
         let mut results = Vec::new();
         for (agent_id, rx) in &mut self.output_receivers {
+            let parser = self.parsers.entry(*agent_id).or_default();
             while let Ok(bytes) = rx.try_recv() {
+                parser.feed(&bytes);
                 results.push((*agent_id, bytes));
             }
         }
         results
     }
 
+    /// What the agent's current (or just-exited) session has printed so far.
+    pub fn session_stats(&self, agent_id: u64) -> Option<SessionStats> {
+        self.parsers.get(&agent_id).map(|p| p.stats().clone())
+    }
+
+    /// Turns and output of the agent's current (or just-exited) session.
+    pub fn get_metrics(&self, agent_id: u64) -> Option<VibeMetrics> {
+        match self.sessions.get(&agent_id) {
+            Some(session) => Some(session.metrics()),
+            None => self.finished_metrics.get(&agent_id).cloned(),
+        }
+    }
+
+    /// Metrics for every agent [`VibeManager::get_metrics`] knows about.
+    pub fn all_metrics(&self) -> HashMap<u64, VibeMetrics> {
+        let mut metrics = self.finished_metrics.clone();
+        metrics.extend(self.sessions.iter().map(|(id, session)| (*id, session.metrics())));
+        metrics
+    }
+
     pub fn has_session(&self, agent_id: u64) -> bool {
         self.sessions.contains_key(&agent_id)
     }
 
-    /// Returns true if a session spawn previously failed for this agent.
-    pub fn has_failed(&self, agent_id: u64) -> bool {
-        self.failed_spawns.contains(&agent_id)
+    /// Returns true if a session spawn failed for this agent and it is
+    /// still backing off at `tick`.
+    pub fn has_failed(&self, agent_id: u64, tick: u64) -> bool {
+        self.failed_spawns
+            .get(&agent_id)
+            .is_some_and(|entry| tick < entry.retry_at_tick)
+    }
+
+    /// Record a failed session spawn at `tick` and schedule the next retry.
+    pub fn mark_failed(&mut self, agent_id: u64, tick: u64) -> &FailedSpawnEntry {
+        let entry = self.failed_spawns.entry(agent_id).or_insert(FailedSpawnEntry {
+            agent_id,
+            retry_at_tick: tick,
+            attempt_count: 0,
+        });
+        entry.attempt_count += 1;
+        entry.retry_at_tick = tick + retry_delay(entry.attempt_count);
+        entry
     }
 
-    /// Mark a session spawn as failed so we don't retry every tick.
-    pub fn mark_failed(&mut self, agent_id: u64) {
-        self.failed_spawns.insert(agent_id);
+    /// The failed-spawn record for this agent, if any.
+    pub fn failed_spawn(&self, agent_id: u64) -> Option<&FailedSpawnEntry> {
+        self.failed_spawns.get(&agent_id)
     }
 
-    /// Clear the failed-spawn flag (e.g. when agent is unassigned and could be reassigned later).
-    pub fn clear_failed(&mut self, agent_id: u64) {
+    /// Agents whose backoff has elapsed at `tick`, in ascending id order.
+    pub fn pending_retries(&self, tick: u64) -> Vec<u64> {
+        let mut ready: Vec<u64> = self
+            .failed_spawns
+            .values()
+            .filter(|entry| tick >= entry.retry_at_tick)
+            .map(|entry| entry.agent_id)
+            .collect();
+        ready.sort_unstable();
+        ready
+    }
+
+    /// Forget past failures (e.g. when the agent is unassigned and could be
+    /// reassigned later), so the next failure starts the backoff afresh.
+    pub fn reset_retry(&mut self, agent_id: u64) {
         self.failed_spawns.remove(&agent_id);
     }
 
@@ -166,3 +313,118 @@ impl VibeManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn backoff_doubles_from_twenty_ticks_up_to_thirty_seconds() {
+        let mut manager = VibeManager::new();
+        let mut tick = 100;
+        let mut delays = Vec::new();
+        for attempt in 1..=4 {
+            let entry = manager.mark_failed(7, tick).clone();
+            assert_eq!(entry.attempt_count, attempt);
+            delays.push(entry.retry_at_tick - tick);
+
+            assert!(manager.has_failed(7, entry.retry_at_tick - 1));
+            assert!(manager.pending_retries(entry.retry_at_tick - 1).is_empty());
+            assert!(!manager.has_failed(7, entry.retry_at_tick));
+            assert_eq!(manager.pending_retries(entry.retry_at_tick), vec![7]);
+            tick = entry.retry_at_tick;
+        }
+        assert_eq!(delays, vec![20, 40, 80, 160]);
+
+        assert_eq!(retry_delay(5), 320);
+        assert_eq!(retry_delay(6), RETRY_MAX_TICKS);
+        assert_eq!(retry_delay(100), RETRY_MAX_TICKS);
+    }
+
+    #[test]
+    fn reset_retry_starts_the_backoff_over() {
+        let mut manager = VibeManager::new();
+        manager.mark_failed(3, 0);
+        manager.mark_failed(3, 20);
+        manager.reset_retry(3);
+        assert!(!manager.has_failed(3, 0));
+        assert!(manager.pending_retries(1000).is_empty());
+
+        assert_eq!(manager.mark_failed(3, 50).retry_at_tick, 70);
+    }
+
+    /// A session for `agent_id` whose input lands in the returned buffer.
+    fn capture_session(manager: &mut VibeManager, agent_id: u64) -> Arc<Mutex<Vec<u8>>> {
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let bytes = Arc::new(Mutex::new(Vec::new()));
+        let session = VibeSession::with_writer(agent_id, "todo_app".to_string(), Box::new(Capture(bytes.clone())));
+        manager.sessions.insert(agent_id, session);
+        bytes
+    }
+
+    #[test]
+    fn relay_types_the_message_into_the_target_session() {
+        let mut manager = VibeManager::new();
+        let from = capture_session(&mut manager, 1);
+        let to = capture_session(&mut manager, 2);
+
+        manager.relay(1, "Ada", 2, "the API lives in src/api.ts", 40).unwrap();
+
+        let typed = String::from_utf8(to.lock().unwrap().clone()).unwrap();
+        assert_eq!(typed, format_relay("Ada", "the API lives in src/api.ts"));
+        assert!(typed.starts_with("[relay from Ada]: the API lives in src/api.ts"));
+        assert!(from.lock().unwrap().is_empty());
+        assert_eq!(manager.relay_history().back().unwrap().tick, 40);
+
+        assert!(manager.relay(1, "Ada", 3, "hello?", 41).is_err());
+        assert!(manager.relay(3, "Bob", 2, "hello?", 41).is_err());
+        assert_eq!(manager.relay_history().len(), 1);
+    }
+
+    #[test]
+    fn turns_are_counted_from_cli_prompts() {
+        let mut manager = VibeManager::new();
+        let output = b"Welcome\r\n> fix the bug\r\nEditing...\r\n> run tests\r\nok\r\n> ".to_vec();
+        let (output_tx, output_rx) = mpsc::unbounded_channel();
+        let mut session = VibeSession::with_reader(5, Box::new(std::io::Cursor::new(output.clone())), output_tx, 30);
+        // The reader stops at EOF; wait for it so the counts are final.
+        session.kill();
+        manager.sessions.insert(5, session);
+        manager.output_receivers.insert(5, output_rx);
+
+        let metrics = manager.get_metrics(5).unwrap();
+        assert_eq!(metrics.turns_completed, 3);
+        assert_eq!(metrics.bytes_output, output.len() as u64);
+        assert_eq!(metrics.started_at_tick, 30);
+        assert_eq!(metrics.ended_at_tick, None);
+
+        manager.poll_exits(90);
+        assert_eq!(manager.get_metrics(5).unwrap().ended_at_tick, Some(90));
+        assert_eq!(manager.all_metrics().len(), 1);
+        manager.kill_session(5);
+        assert!(manager.get_metrics(5).is_none());
+    }
+
+    #[test]
+    fn relay_history_keeps_the_last_twenty() {
+        let mut manager = VibeManager::new();
+        capture_session(&mut manager, 1);
+        capture_session(&mut manager, 2);
+        for tick in 0..25 {
+            manager.relay(1, "Ada", 2, "ping", tick).unwrap();
+        }
+        assert_eq!(manager.relay_history().len(), RELAY_HISTORY_LEN);
+        assert_eq!(manager.relay_history().front().unwrap().tick, 5);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod agents;
 pub mod manager;
 pub mod session;
+pub mod tools;
+
+pub use tools::tools_for;
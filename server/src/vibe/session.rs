@@ -1,10 +1,14 @@
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem, Child};
 use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
-use crate::protocol::AiBackend;
+use crate::protocol::{AiBackend, Tick, VibeMetrics};
+
+/// What the CLI prints when it's ready for the next turn.
+const PROMPT_MARKER: &[u8] = b"\n> ";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum VibeSessionState {
@@ -13,6 +17,207 @@ pub enum VibeSessionState {
     Completed,
 }
 
+/// What the server has learned from a session's output so far.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    /// Turns the CLI has announced (the highest turn number seen).
+    pub turns_observed: u32,
+    /// Tool invocations seen.
+    pub tools_used: u32,
+    /// Whether an error line was seen; `last_error` holds the latest one.
+    pub errored: bool,
+    pub last_error: Option<String>,
+    /// Whether the CLI reported stopping at its turn limit.
+    pub turn_limit_reached: bool,
+    /// Whether the CLI printed a completion summary.
+    pub completed: bool,
+}
+
+/// Incremental parser for a session's PTY output.
+///
+/// Bytes may arrive split anywhere, so escape sequences and partial lines
+/// are carried over between calls to [`OutputParser::feed`]. ANSI escapes
+/// are stripped before each complete line is matched against the markers
+/// both CLIs print:
+///
+/// - turn boundaries: `Turn 3` / `turn 3/15`
+/// - tool invocations: `⏺ Bash(ls)`, `Tool: read_file`, `Running tool ...`
+/// - errors: lines starting `Error:` or containing `API Error`
+/// - turn limit: `max turns` / `maximum number of turns`
+/// - completion: `Session complete`, `Task completed`, `Done in ...`
+#[derive(Debug, Default)]
+pub struct OutputParser {
+    stats: SessionStats,
+    line: String,
+    escape: EscapeState,
+    /// Trailing bytes of an incomplete UTF-8 character.
+    partial_utf8: Vec<u8>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum EscapeState {
+    #[default]
+    Text,
+    /// Just saw ESC.
+    Escape,
+    /// Inside `ESC [ ...`, up to a final byte in `@..=~`.
+    Csi,
+    /// Inside `ESC ] ...`, up to BEL or `ESC \`.
+    Osc,
+    /// Saw ESC inside an OSC string.
+    OscEscape,
+}
+
+impl OutputParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    /// Feeds the next chunk of raw PTY output.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut data = std::mem::take(&mut self.partial_utf8);
+        data.extend_from_slice(bytes);
+        let text = match std::str::from_utf8(&data) {
+            Ok(text) => text.to_string(),
+            Err(e) if e.error_len().is_none() => {
+                let (valid, rest) = data.split_at(e.valid_up_to());
+                self.partial_utf8 = rest.to_vec();
+                String::from_utf8_lossy(valid).into_owned()
+            }
+            Err(_) => String::from_utf8_lossy(&data).into_owned(),
+        };
+
+        for c in text.chars() {
+            self.escape = match (self.escape, c) {
+                (EscapeState::Text, '\x1b') => EscapeState::Escape,
+                (EscapeState::Text, '\n' | '\r') => {
+                    self.finish_line();
+                    EscapeState::Text
+                }
+                (EscapeState::Text, c) => {
+                    self.line.push(c);
+                    EscapeState::Text
+                }
+                (EscapeState::Escape, '[') => EscapeState::Csi,
+                (EscapeState::Escape, ']') => EscapeState::Osc,
+                (EscapeState::Escape, _) => EscapeState::Text,
+                (EscapeState::Csi, '@'..='~') => EscapeState::Text,
+                (EscapeState::Csi, _) => EscapeState::Csi,
+                (EscapeState::Osc, '\x07') => EscapeState::Text,
+                (EscapeState::Osc, '\x1b') => EscapeState::OscEscape,
+                (EscapeState::Osc, _) => EscapeState::Osc,
+                (EscapeState::OscEscape, _) => EscapeState::Text,
+            };
+        }
+    }
+
+    fn finish_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        let lower = line.to_lowercase();
+
+        if let Some(turn) = parse_turn(&lower) {
+            self.stats.turns_observed = self.stats.turns_observed.max(turn);
+        } else if is_tool_call(line, &lower) {
+            self.stats.tools_used += 1;
+        } else if lower.starts_with("error:") || lower.contains("api error") {
+            self.stats.errored = true;
+            self.stats.last_error = Some(line.to_string());
+        } else if lower.contains("max turns") || lower.contains("maximum number of turns") {
+            self.stats.turn_limit_reached = true;
+        } else if lower.starts_with("session complete")
+            || lower.starts_with("task completed")
+            || lower.starts_with("done in ")
+        {
+            self.stats.completed = true;
+        }
+    }
+}
+
+/// The turn number from a `turn 3` / `turn 3/15` line (already lowercased).
+fn parse_turn(lower: &str) -> Option<u32> {
+    let rest = lower.trim_start_matches(|c: char| !c.is_alphanumeric()).strip_prefix("turn ")?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+fn is_tool_call(line: &str, lower: &str) -> bool {
+    let bullet_call = line.strip_prefix("⏺ ").is_some_and(|rest| {
+        let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        !name.is_empty() && rest[name.len()..].starts_with('(')
+    });
+    bullet_call || lower.starts_with("tool: ") || lower.starts_with("running tool")
+}
+
+/// Reason shown to the player when a session ends.
+pub fn end_reason(success: bool, stats: &SessionStats) -> String {
+    let summary = format!(
+        "{} turn{}, {} tool call{}",
+        stats.turns_observed,
+        if stats.turns_observed == 1 { "" } else { "s" },
+        stats.tools_used,
+        if stats.tools_used == 1 { "" } else { "s" },
+    );
+    match (&stats.last_error, success) {
+        (Some(error), false) => format!("Session failed: {} ({})", error, summary),
+        (None, false) => format!("Session exited with an error ({})", summary),
+        _ if stats.turn_limit_reached => format!("Turn limit reached ({})", summary),
+        (Some(_), true) => format!("Session finished with errors ({})", summary),
+        (None, true) => format!("Session completed ({})", summary),
+    }
+}
+
+/// Counts [`PROMPT_MARKER`]s in `chunk`, including one split across the
+/// previous chunk. `carry` holds the previous chunk's tail between calls.
+fn count_prompts(carry: &mut Vec<u8>, chunk: &[u8]) -> u32 {
+    carry.extend_from_slice(chunk);
+    let count = carry.windows(PROMPT_MARKER.len()).filter(|w| *w == PROMPT_MARKER).count() as u32;
+    // Too short to hold a whole marker, so nothing counted is seen twice.
+    let keep = carry.len().min(PROMPT_MARKER.len() - 1);
+    carry.drain(..carry.len() - keep);
+    count
+}
+
+/// Forwards PTY output to `output_tx` until EOF, counting bytes and turns
+/// into `metrics` as it goes.
+fn spawn_reader(
+    agent_id: u64,
+    mut reader: Box<dyn Read + Send>,
+    output_tx: mpsc::UnboundedSender<Vec<u8>>,
+    metrics: Arc<Mutex<VibeMetrics>>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut carry = Vec::new();
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let turns = count_prompts(&mut carry, &buf[..n]);
+                    if let Ok(mut metrics) = metrics.lock() {
+                        metrics.bytes_output += n as u64;
+                        metrics.turns_completed += turns;
+                    }
+                    if output_tx.send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!("PTY read error for agent {}: {}", agent_id, e);
+                    break;
+                }
+            }
+        }
+    })
+}
+
 /// A single Mistral Vibe CLI session running in a PTY.
 pub struct VibeSession {
     pub agent_id: u64,
@@ -21,10 +226,13 @@ pub struct VibeSession {
     writer: Option<Box<dyn Write + Send>>,
     child: Option<Box<dyn Child + Send + Sync>>,
     reader_handle: Option<std::thread::JoinHandle<()>>,
+    /// Updated by the reader thread.
+    metrics: Arc<Mutex<VibeMetrics>>,
 }
 
 impl VibeSession {
     /// Spawn a new Mistral Vibe CLI session in a PTY.
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         agent_id: u64,
         building_id: String,
@@ -35,6 +243,7 @@ impl VibeSession {
         enabled_tools: Vec<String>,
         output_tx: mpsc::UnboundedSender<Vec<u8>>,
         backend: AiBackend,
+        tick: Tick,
     ) -> Result<Self, String> {
         let pty_system = NativePtySystem::default();
 
@@ -82,29 +291,13 @@ impl VibeSession {
             .map_err(|e| format!("Failed to spawn vibe process: {}", e))?;
 
         // Read PTY output in a background thread (blocking I/O)
-        let mut reader = pty_pair
+        let reader = pty_pair
             .master
             .try_clone_reader()
             .map_err(|e| format!("Failed to clone PTY reader: {}", e))?;
 
-        let reader_agent_id = agent_id;
-        let reader_handle = std::thread::spawn(move || {
-            let mut buf = [0u8; 4096];
-            loop {
-                match reader.read(&mut buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        if output_tx.send(buf[..n].to_vec()).is_err() {
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        warn!("PTY read error for agent {}: {}", reader_agent_id, e);
-                        break;
-                    }
-                }
-            }
-        });
+        let metrics = Arc::new(Mutex::new(VibeMetrics { started_at_tick: tick, ..Default::default() }));
+        let reader_handle = spawn_reader(agent_id, reader, output_tx, metrics.clone());
 
         let backend_name = match backend {
             AiBackend::MistralVibe => "Mistral Vibe",
@@ -131,9 +324,50 @@ impl VibeSession {
             writer: Some(writer),
             child: Some(child),
             reader_handle: Some(reader_handle),
+            metrics,
         })
     }
 
+    /// A session with no process behind it whose input goes to `writer`.
+    #[cfg(test)]
+    pub(crate) fn with_writer(agent_id: u64, building_id: String, writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            agent_id,
+            building_id,
+            state: VibeSessionState::Running,
+            writer: Some(writer),
+            child: None,
+            reader_handle: None,
+            metrics: Arc::default(),
+        }
+    }
+
+    /// A session with no process behind it whose output is read from
+    /// `reader`, as if printed by the CLI.
+    #[cfg(test)]
+    pub(crate) fn with_reader(
+        agent_id: u64,
+        reader: Box<dyn Read + Send>,
+        output_tx: mpsc::UnboundedSender<Vec<u8>>,
+        tick: Tick,
+    ) -> Self {
+        let metrics = Arc::new(Mutex::new(VibeMetrics { started_at_tick: tick, ..Default::default() }));
+        Self {
+            agent_id,
+            building_id: String::new(),
+            state: VibeSessionState::Running,
+            writer: None,
+            child: None,
+            reader_handle: Some(spawn_reader(agent_id, reader, output_tx, metrics.clone())),
+            metrics,
+        }
+    }
+
+    /// Turns and output counted so far.
+    pub fn metrics(&self) -> VibeMetrics {
+        self.metrics.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
     /// Write input bytes to the PTY stdin.
     pub fn write_input(&mut self, data: &[u8]) -> Result<(), String> {
         if let Some(writer) = &mut self.writer {
@@ -191,3 +425,72 @@ impl Drop for VibeSession {
         self.kill();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&[u8]]) -> SessionStats {
+        let mut parser = OutputParser::new();
+        for chunk in chunks {
+            parser.feed(chunk);
+        }
+        parser.stats().clone()
+    }
+
+    #[test]
+    fn counts_turns_and_tools_through_ansi_noise() {
+        let stats = parse(&[
+            b"\x1b[2J\x1b[1;32mTurn 1/15\x1b[0m\r\n",
+            "\x1b[33m⏺\x1b[0m Bash(ls -la)\r\n".as_bytes(),
+            b"some output\r\n\x1b]0;vibe\x07Tool: read_file src/main.ts\n",
+            b"\x1b[1mTurn 2/15\x1b[0m\n",
+            "⏺ I'll update the component (not a tool)\n".as_bytes(),
+            b"Session complete.\n",
+        ]);
+        assert_eq!(stats.turns_observed, 2);
+        assert_eq!(stats.tools_used, 2);
+        assert!(stats.completed);
+        assert!(!stats.errored);
+        assert_eq!(end_reason(true, &stats), "Session completed (2 turns, 2 tool calls)");
+    }
+
+    #[test]
+    fn escape_sequences_and_characters_split_across_chunks() {
+        let bullet = "⏺".as_bytes();
+        let stats = parse(&[
+            b"\x1b[3",
+            b"1mTu",
+            b"rn 4\x1b",
+            b"[0m\n",
+            &bullet[..1],
+            &bullet[1..],
+            b" Edit(app.tsx)\n",
+        ]);
+        assert_eq!(stats.turns_observed, 4);
+        assert_eq!(stats.tools_used, 1);
+    }
+
+    #[test]
+    fn errors_and_turn_limits_pick_the_reason() {
+        let stats = parse(&[b"Turn 1\n\x1b[31mError: rate limited (429)\x1b[0m\n"]);
+        assert!(stats.errored);
+        assert_eq!(end_reason(false, &stats), "Session failed: Error: rate limited (429) (1 turn, 0 tool calls)");
+
+        let stats = parse(&[b"Turn 15/15\nStopped: reached max turns (15)\n"]);
+        assert!(stats.turn_limit_reached);
+        assert_eq!(end_reason(true, &stats), "Turn limit reached (15 turns, 0 tool calls)");
+
+        assert_eq!(end_reason(false, &SessionStats::default()), "Session exited with an error (0 turns, 0 tool calls)");
+    }
+
+    #[test]
+    fn prompts_are_counted_across_chunks() {
+        let mut carry = Vec::new();
+        assert_eq!(count_prompts(&mut carry, b"hello\n> ls\r\n"), 1);
+        assert_eq!(count_prompts(&mut carry, b"done\r"), 0);
+        assert_eq!(count_prompts(&mut carry, b"\n"), 0);
+        assert_eq!(count_prompts(&mut carry, b"> again\n>"), 1);
+        assert_eq!(count_prompts(&mut carry, b" "), 1);
+    }
+}
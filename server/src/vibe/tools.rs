@@ -0,0 +1,103 @@
+//! Which vibe CLI tools an agent's session is started with.
+
+use crate::game::upgrades::{UpgradeId, UpgradeState};
+use crate::protocol::AgentTierKind;
+
+/// Tools every agent gets.
+const BASE_TOOLS: [&str; 5] = ["read_file", "grep", "search_replace", "todo", "task"];
+
+/// Tools unlocked by File System Access, and by default from Journeyman up.
+const FILE_TOOLS: [&str; 2] = ["write_file", "edit_file"];
+
+/// Tools unlocked by Git Access, and by default from Artisan up.
+const GIT_TOOLS: [&str; 1] = ["git"];
+
+/// Tools unlocked by Web Search, and by default for Architects.
+const WEB_TOOLS: [&str; 2] = ["web_search", "web_fetch"];
+
+/// The vibe CLI tools an agent of `tier` may use given the purchased
+/// `upgrades`.
+///
+/// Base tools are always enabled. Each tool upgrade adds its tools for every
+/// tier, and higher tiers come with some of them without the upgrade:
+/// Journeymen can write files, Artisans also use git, Architects also search
+/// the web.
+pub fn tools_for(upgrades: &UpgradeState, tier: AgentTierKind) -> Vec<String> {
+    let rank = match tier {
+        AgentTierKind::Apprentice => 0,
+        AgentTierKind::Journeyman => 1,
+        AgentTierKind::Artisan => 2,
+        AgentTierKind::Architect => 3,
+    };
+    let groups: [(&[&str], UpgradeId, u8); 3] = [
+        (&FILE_TOOLS, UpgradeId::FileSystemAccess, 1),
+        (&GIT_TOOLS, UpgradeId::GitAccess, 2),
+        (&WEB_TOOLS, UpgradeId::WebSearch, 3),
+    ];
+
+    let mut tools: Vec<String> = BASE_TOOLS.iter().map(|t| t.to_string()).collect();
+    for (group, upgrade, default_rank) in groups {
+        if upgrades.has(upgrade) || rank >= default_rank {
+            tools.extend(group.iter().map(|t| t.to_string()));
+        }
+    }
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upgrades(ids: &[UpgradeId]) -> UpgradeState {
+        let mut state = UpgradeState::new();
+        state.purchased.extend(ids.iter().copied());
+        state
+    }
+
+    #[test]
+    fn apprentice_without_upgrades_gets_base_tools_only() {
+        assert_eq!(tools_for(&UpgradeState::new(), AgentTierKind::Apprentice), BASE_TOOLS.to_vec());
+    }
+
+    #[test]
+    fn upgrades_add_their_tools_for_every_tier() {
+        // Git Access and Web Search have prerequisites, but the mapping only
+        // looks at what is purchased.
+        let tools = tools_for(&upgrades(&[UpgradeId::GitAccess, UpgradeId::WebSearch]), AgentTierKind::Apprentice);
+        assert!(tools.contains(&"git".to_string()));
+        assert!(tools.contains(&"web_search".to_string()));
+        assert!(tools.contains(&"web_fetch".to_string()));
+        assert!(!tools.contains(&"write_file".to_string()));
+
+        let tools = tools_for(&upgrades(&[UpgradeId::FileSystemAccess]), AgentTierKind::Apprentice);
+        assert!(tools.contains(&"write_file".to_string()));
+        assert!(tools.contains(&"edit_file".to_string()));
+        assert!(!tools.contains(&"git".to_string()));
+    }
+
+    #[test]
+    fn higher_tiers_get_more_tools_by_default() {
+        let none = UpgradeState::new();
+        let counts: Vec<usize> = [
+            AgentTierKind::Apprentice,
+            AgentTierKind::Journeyman,
+            AgentTierKind::Artisan,
+            AgentTierKind::Architect,
+        ]
+        .into_iter()
+        .map(|tier| tools_for(&none, tier).len())
+        .collect();
+        assert!(counts.windows(2).all(|w| w[0] < w[1]), "{:?}", counts);
+        assert!(tools_for(&none, AgentTierKind::Architect).contains(&"web_search".to_string()));
+    }
+
+    #[test]
+    fn purchased_tools_are_not_listed_twice() {
+        let all = upgrades(&[UpgradeId::FileSystemAccess, UpgradeId::GitAccess, UpgradeId::WebSearch]);
+        let tools = tools_for(&all, AgentTierKind::Architect);
+        let mut deduped = tools.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(tools.len(), deduped.len());
+    }
+}